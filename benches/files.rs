@@ -59,6 +59,9 @@ fn benchmark_files_no_partitions(c: &mut Criterion) {
                         )
                         .into(),
                         compression: sinks::file::Compression::None,
+                        rotation: Default::default(),
+                        retention: Default::default(),
+                        atomic_finalize: false,
                         acknowledgements: Default::default(),
                     },
                 );
@@ -48,6 +48,8 @@ fn benchmark_http(c: &mut Criterion) {
                             &["in"],
                             sinks::http::HttpSinkConfig {
                                 uri: out_addr.to_string().parse::<http::Uri>().unwrap().into(),
+                                uris: Default::default(),
+                                distribution: Default::default(),
                                 compression: *compression,
                                 method: Default::default(),
                                 auth: Default::default(),
@@ -103,6 +103,7 @@ fn main() {
         println!("cargo:rerun-if-changed=proto/dnstap.proto");
         println!("cargo:rerun-if-changed=proto/ddsketch.proto");
         println!("cargo:rerun-if-changed=proto/google/pubsub/v1/pubsub.proto");
+        println!("cargo:rerun-if-changed=proto/loki.proto");
         println!("cargo:rerun-if-changed=proto/vector.proto");
 
         let mut prost_build = prost_build::Config::new();
@@ -117,6 +118,7 @@ fn main() {
                     "proto/ddsketch.proto",
                     "proto/dd_trace.proto",
                     "proto/google/pubsub/v1/pubsub.proto",
+                    "proto/loki.proto",
                     "proto/vector.proto",
                 ],
                 &["proto/", "lib/vector-core/proto/"],
@@ -188,6 +188,16 @@ impl CheckpointsView {
                 }
             }
         }
+
+        if self.checkpoints.get(&fng).is_none() {
+            if let Ok(Some(fingerprint)) =
+                fingerprinter.get_compressed_checksum(path, fingerprint_buffer)
+            {
+                if let Some((_, pos)) = self.checkpoints.remove(&fingerprint) {
+                    self.update(fng, pos);
+                }
+            }
+        }
     }
 }
 
@@ -0,0 +1,126 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+};
+
+use flate2::bufread::MultiGzDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// The compression format of a file, as detected from its leading magic bytes.
+///
+/// Compressed files are expected to be complete: they are typically produced by log rotation
+/// and never appended to, so they are read to completion once and not tailed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Detects the compression of the data available in `reader`, without consuming it.
+    pub fn detect<R: BufRead>(reader: &mut R) -> io::Result<Self> {
+        // WARN: The paired `BufRead::consume` is not called intentionally. If we
+        // do we'll chop a decent part of the potential compressed stream off.
+        let header_bytes = reader.fill_buf()?;
+        Ok(if header_bytes.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if header_bytes.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        })
+    }
+
+    pub const fn is_compressed(self) -> bool {
+        !matches!(self, Compression::None)
+    }
+
+    /// Wraps `reader` so that it yields the decompressed content.
+    pub fn decode<R: BufRead + 'static>(self, reader: R) -> io::Result<Box<dyn BufRead>> {
+        Ok(match self {
+            Compression::None => Box::new(reader),
+            Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+            Compression::Zstd => Box::new(BufReader::new(
+                zstd::stream::read::Decoder::with_buffer(reader)?,
+            )),
+        })
+    }
+}
+
+/// Opens the file at `path`, transparently decompressing it if needed.
+pub fn open_decompressed(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let compression = Compression::detect(&mut reader)?;
+    compression.decode(reader)
+}
+
+/// Discards the next `count` bytes of `reader`. Returns an `UnexpectedEof` error if there
+/// are fewer bytes left.
+pub fn skip_bytes(reader: &mut impl Read, count: u64) -> io::Result<()> {
+    let skipped = io::copy(&mut reader.take(count), &mut io::sink())?;
+    if skipped < count {
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "EOF reached while skipping",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Cursor, Read, Write};
+
+    use flate2::{write::GzEncoder, Compression as GzLevel};
+
+    use super::{skip_bytes, Compression};
+
+    const CONTENT: &[u8] = b"first line\nsecond line\n";
+
+    fn roundtrip(compression: Compression, data: Vec<u8>) -> Vec<u8> {
+        let mut reader = Cursor::new(data);
+        assert_eq!(Compression::detect(&mut reader).unwrap(), compression);
+        let mut output = Vec::new();
+        compression
+            .decode(reader)
+            .unwrap()
+            .read_to_end(&mut output)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn detects_and_decodes_plain_text() {
+        assert_eq!(roundtrip(Compression::None, CONTENT.to_vec()), CONTENT);
+    }
+
+    #[test]
+    fn detects_and_decodes_gzip() {
+        let mut encoder = GzEncoder::new(Vec::new(), GzLevel::default());
+        encoder.write_all(CONTENT).unwrap();
+        let data = encoder.finish().unwrap();
+        assert_eq!(roundtrip(Compression::Gzip, data), CONTENT);
+    }
+
+    #[test]
+    fn detects_and_decodes_zstd() {
+        let data = zstd::stream::encode_all(CONTENT, 0).unwrap();
+        assert_eq!(roundtrip(Compression::Zstd, data), CONTENT);
+    }
+
+    #[test]
+    fn skips_bytes() {
+        let mut reader = Cursor::new(CONTENT);
+        skip_bytes(&mut reader, 11).unwrap();
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "second line\n");
+
+        assert!(skip_bytes(&mut Cursor::new(CONTENT), 100).is_err());
+    }
+}
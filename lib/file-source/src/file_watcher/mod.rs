@@ -7,11 +7,13 @@ use std::{
 
 use bytes::{Bytes, BytesMut};
 use chrono::{DateTime, Utc};
-use flate2::bufread::MultiGzDecoder;
 use tracing::debug;
 
 use crate::{
-    buffer::read_until_with_max_size, metadata_ext::PortableFileExt, FilePosition, ReadFrom,
+    buffer::read_until_with_max_size,
+    compression::{skip_bytes, Compression},
+    metadata_ext::PortableFileExt,
+    FilePosition, ReadFrom,
 };
 #[cfg(test)]
 mod tests;
@@ -65,28 +67,34 @@ impl FileWatcher {
             false
         };
 
-        let gzipped = is_gzipped(&mut reader)?;
+        let compression = Compression::detect(&mut reader)?;
 
         // Determine the actual position at which we should start reading
         let (reader, file_position): (Box<dyn BufRead>, FilePosition) =
-            match (gzipped, too_old, read_from) {
+            match (compression.is_compressed(), too_old, read_from) {
                 (true, true, _) => {
                     debug!(
-                        message = "Not reading gzipped file older than `ignore_older`.",
+                        message = "Not reading compressed file older than `ignore_older`.",
                         ?path,
                     );
                     (Box::new(null_reader()), 0)
                 }
+                // Compressed files are fingerprinted on their decompressed content, and positions
+                // in them are positions in the decompressed stream, so a checkpoint may come from
+                // this file or from its uncompressed predecessor. Either way, resume from there.
                 (true, _, ReadFrom::Checkpoint(file_position)) => {
                     debug!(
-                        message = "Not re-reading gzipped file with existing stored offset.",
+                        message = "Resuming compressed file from stored offset.",
                         ?path,
                         %file_position
                     );
-                    (Box::new(null_reader()), file_position)
+                    (
+                        open_compressed_at(compression, reader, file_position)?,
+                        file_position,
+                    )
                 }
-                // TODO: This may become the default, leading us to stop reading gzipped files that
-                // we were reading before. Should we merge this and the next branch to read
+                // TODO: This may become the default, leading us to stop reading compressed files
+                // that we were reading before. Should we merge this and the next branch to read
                 // compressed file from the beginning even when `read_from = "end"` (implicitly via
                 // default or explicitly via config)?
                 (true, _, ReadFrom::End) => {
@@ -96,9 +104,7 @@ impl FileWatcher {
                     );
                     (Box::new(null_reader()), 0)
                 }
-                (true, false, ReadFrom::Beginning) => {
-                    (Box::new(io::BufReader::new(MultiGzDecoder::new(reader))), 0)
-                }
+                (true, false, ReadFrom::Beginning) => (compression.decode(reader)?, 0),
                 (false, true, _) => {
                     let pos = reader.seek(io::SeekFrom::End(0)).unwrap();
                     (Box::new(reader), pos)
@@ -144,13 +150,12 @@ impl FileWatcher {
         let file_handle = File::open(&path)?;
         if (file_handle.portable_dev()?, file_handle.portable_ino()?) != (self.devno, self.inode) {
             let mut reader = io::BufReader::new(fs::File::open(&path)?);
-            let gzipped = is_gzipped(&mut reader)?;
-            let new_reader: Box<dyn BufRead> = if gzipped {
-                if self.file_position != 0 {
-                    Box::new(null_reader())
-                } else {
-                    Box::new(io::BufReader::new(MultiGzDecoder::new(reader)))
-                }
+            let compression = Compression::detect(&mut reader)?;
+            // A file compressed by log rotation is picked up here when its decompressed
+            // fingerprint matches the file we were reading, so continue from where we were
+            // in the decompressed stream to read the tail we hadn't read yet.
+            let new_reader: Box<dyn BufRead> = if compression.is_compressed() {
+                open_compressed_at(compression, reader, self.file_position)?
             } else {
                 reader.seek(io::SeekFrom::Start(self.file_position))?;
                 Box::new(reader)
@@ -252,11 +257,19 @@ impl FileWatcher {
     }
 }
 
-fn is_gzipped(r: &mut io::BufReader<fs::File>) -> io::Result<bool> {
-    let header_bytes = r.fill_buf()?;
-    // WARN: The paired `BufReader::consume` is not called intentionally. If we
-    // do we'll chop a decent part of the potential gzip stream off.
-    Ok(header_bytes.starts_with(&[0x1f, 0x8b]))
+/// Decompresses `reader`, skipping the first `position` bytes of decompressed content. If the
+/// decompressed content is shorter than that, there is nothing left to read from it.
+fn open_compressed_at(
+    compression: Compression,
+    reader: io::BufReader<fs::File>,
+    position: FilePosition,
+) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = compression.decode(reader)?;
+    match skip_bytes(&mut reader, position) {
+        Ok(()) => Ok(reader),
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(Box::new(null_reader())),
+        Err(error) => Err(error),
+    }
 }
 
 fn null_reader() -> impl BufRead {
@@ -0,0 +1,78 @@
+use std::{fs, io::Write};
+
+use bytes::Bytes;
+use flate2::{write::GzEncoder, Compression};
+
+use crate::{file_watcher::FileWatcher, ReadFrom};
+
+const CONTENT: &[u8] = b"one\ntwo\nthree\n";
+
+fn read_all(fw: &mut FileWatcher) -> Vec<Bytes> {
+    std::iter::from_fn(|| fw.read_line().unwrap()).collect()
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn resumes_file_compressed_by_rotation() {
+    let dir = tempfile::TempDir::new().expect("could not create tempdir");
+    let path = dir.path().join("a_file.log");
+    fs::write(&path, CONTENT).unwrap();
+
+    let mut fw = FileWatcher::new(
+        path.clone(),
+        ReadFrom::Beginning,
+        None,
+        100_000,
+        Bytes::from("\n"),
+    )
+    .expect("must be able to create");
+    assert_eq!(fw.read_line().unwrap(), Some(Bytes::from("one")));
+
+    // Rotation compresses the file and removes the original before the tail was read.
+    let rotated = dir.path().join("a_file.log.1.gz");
+    fs::write(&rotated, gzip(CONTENT)).unwrap();
+    fs::remove_file(&path).unwrap();
+    fw.update_path(rotated).unwrap();
+
+    assert_eq!(
+        read_all(&mut fw),
+        vec![Bytes::from("two"), Bytes::from("three")]
+    );
+    assert_eq!(fw.get_file_position(), CONTENT.len() as u64);
+}
+
+#[test]
+fn resumes_compressed_file_from_checkpoint() {
+    let dir = tempfile::TempDir::new().expect("could not create tempdir");
+    let path = dir.path().join("a_file.log.zst");
+    fs::write(&path, zstd::stream::encode_all(CONTENT, 0).unwrap()).unwrap();
+
+    let mut fw = FileWatcher::new(
+        path.clone(),
+        ReadFrom::Checkpoint(4),
+        None,
+        100_000,
+        Bytes::from("\n"),
+    )
+    .expect("must be able to create");
+    assert_eq!(
+        read_all(&mut fw),
+        vec![Bytes::from("two"), Bytes::from("three")]
+    );
+
+    // A checkpoint at the end of the decompressed content has nothing left to read.
+    let mut fw = FileWatcher::new(
+        path,
+        ReadFrom::Checkpoint(CONTENT.len() as u64),
+        None,
+        100_000,
+        Bytes::from("\n"),
+    )
+    .expect("must be able to create");
+    assert!(read_all(&mut fw).is_empty());
+}
@@ -8,7 +8,11 @@ use std::{
 use crc::Crc;
 use serde::{Deserialize, Serialize};
 
-use crate::{metadata_ext::PortableFileExt, FileSourceInternalEvents};
+use crate::{
+    compression::{open_decompressed, skip_bytes, Compression},
+    metadata_ext::PortableFileExt,
+    FileSourceInternalEvents,
+};
 
 const FINGERPRINT_CRC: Crc<u64> = Crc::<u64>::new(&crc::CRC_64_ECMA_182);
 const LEGACY_FINGERPRINT_CRC: Crc<u64> = Crc::<u64>::new(&crc::CRC_64_XZ);
@@ -93,10 +97,12 @@ impl Fingerprinter {
                 ignored_header_bytes,
                 lines,
             } => {
+                // Compressed files are fingerprinted on their decompressed content, so that they
+                // match the uncompressed file they were rotated from.
                 buffer.resize(self.max_line_length, 0u8);
-                let mut fp = fs::File::open(path)?;
-                fp.seek(SeekFrom::Start(ignored_header_bytes as u64))?;
-                fingerprinter_read_until(fp, b'\n', lines, buffer)?;
+                let mut reader = open_decompressed(path)?;
+                skip_bytes(&mut reader, ignored_header_bytes as u64)?;
+                fingerprinter_read_until(reader, b'\n', lines, buffer)?;
                 let fingerprint = FINGERPRINT_CRC.checksum(&buffer[..]);
                 Ok(FirstLinesChecksum(fingerprint))
             }
@@ -160,6 +166,37 @@ impl Fingerprinter {
         }
     }
 
+    /// Calculates the checksum of a compressed file over its compressed content, as done before
+    /// compressed files were fingerprinted on their decompressed content.
+    pub fn get_compressed_checksum(
+        &self,
+        path: &Path,
+        buffer: &mut Vec<u8>,
+    ) -> Result<Option<FileFingerprint>, io::Error> {
+        match self.strategy {
+            FingerprintStrategy::Checksum {
+                ignored_header_bytes,
+                bytes: _,
+                lines,
+            }
+            | FingerprintStrategy::FirstLinesChecksum {
+                ignored_header_bytes,
+                lines,
+            } => {
+                let mut reader = io::BufReader::new(fs::File::open(path)?);
+                if !Compression::detect(&mut reader)?.is_compressed() {
+                    return Ok(None);
+                }
+                buffer.resize(self.max_line_length, 0u8);
+                reader.seek(SeekFrom::Start(ignored_header_bytes as u64))?;
+                fingerprinter_read_until(reader, b'\n', lines, buffer)?;
+                let fingerprint = FINGERPRINT_CRC.checksum(&buffer[..]);
+                Ok(Some(FileFingerprint::FirstLinesChecksum(fingerprint)))
+            }
+            _ => Ok(None),
+        }
+    }
+
     /// Calculates checksums using strategy pre-0.14.0
     /// <https://github.com/vectordotdev/vector/issues/8182>
     pub fn get_legacy_checksum(
@@ -223,8 +260,15 @@ fn fingerprinter_read_until(
 
 #[cfg(test)]
 mod test {
-    use std::{collections::HashSet, fs, io::Error, path::Path, time::Duration};
-
+    use std::{
+        collections::HashSet,
+        fs,
+        io::{Error, Write},
+        path::Path,
+        time::Duration,
+    };
+
+    use flate2::{write::GzEncoder, Compression};
     use tempfile::tempdir;
 
     use super::{FileSourceInternalEvents, FingerprintStrategy, Fingerprinter};
@@ -402,6 +446,51 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_compressed_file_fingerprint() {
+        let fingerprinter = Fingerprinter {
+            strategy: FingerprintStrategy::FirstLinesChecksum {
+                ignored_header_bytes: 0,
+                lines: 1,
+            },
+            max_line_length: 1024,
+            ignore_not_found: false,
+        };
+
+        let target_dir = tempdir().unwrap();
+        let data = b"hello world\nthe next line\n";
+        let plain_path = target_dir.path().join("plain.log");
+        let gzip_path = target_dir.path().join("plain.log.1.gz");
+        let zstd_path = target_dir.path().join("plain.log.1.zst");
+        fs::write(&plain_path, data).unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        fs::write(&gzip_path, encoder.finish().unwrap()).unwrap();
+        fs::write(&zstd_path, zstd::stream::encode_all(&data[..], 0).unwrap()).unwrap();
+
+        let mut buf = Vec::new();
+        let plain = fingerprinter
+            .get_fingerprint_of_file(&plain_path, &mut buf)
+            .unwrap();
+        assert_eq!(
+            fingerprinter
+                .get_fingerprint_of_file(&gzip_path, &mut buf)
+                .unwrap(),
+            plain
+        );
+        assert_eq!(
+            fingerprinter
+                .get_fingerprint_of_file(&zstd_path, &mut buf)
+                .unwrap(),
+            plain
+        );
+
+        assert!(fingerprinter
+            .get_compressed_checksum(&plain_path, &mut buf)
+            .unwrap()
+            .is_none());
+    }
+
     #[test]
     fn test_inode_fingerprint() {
         let fingerprinter = Fingerprinter {
@@ -0,0 +1,83 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::state_store::{StateNotEnabled, StateStore};
+
+fn get_state(store: &StateStore, key: Value) -> Resolved {
+    let key = key.try_bytes_utf8_lossy()?;
+
+    Ok(store.get(&key).unwrap_or(Value::Null))
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GetState {
+    store: Option<StateStore>,
+}
+
+impl GetState {
+    pub fn new(store: Option<StateStore>) -> Self {
+        Self { store }
+    }
+}
+
+impl Function for GetState {
+    fn identifier(&self) -> &'static str {
+        "get_state"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "key",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "Get a value from the state",
+            source: r#"get_state("last_status")"#,
+            result: Ok("null"),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let store = self
+            .store
+            .clone()
+            .ok_or_else(|| Box::new(StateNotEnabled(ctx.span())) as Box<dyn DiagnosticMessage>)?;
+        let key = arguments.required("key");
+
+        Ok(Box::new(GetStateFn { store, key }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let store = self.store.as_ref().expect("compiled with a state store");
+        let key = args.required("key");
+
+        get_state(store, key)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GetStateFn {
+    store: StateStore,
+    key: Box<dyn Expression>,
+}
+
+impl Expression for GetStateFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let key = self.key.resolve(ctx)?;
+
+        get_state(&self.store, key)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::any().infallible()
+    }
+}
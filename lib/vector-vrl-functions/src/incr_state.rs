@@ -0,0 +1,125 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::state_store::{self, StateNotEnabled, StateStore};
+
+fn incr_state(
+    store: &StateStore,
+    key: Value,
+    by: Option<Value>,
+    ttl_secs: Option<Value>,
+) -> Resolved {
+    let key = key.try_bytes_utf8_lossy()?;
+    let by = by
+        .map(VrlValueConvert::try_integer)
+        .transpose()?
+        .unwrap_or(1);
+    let ttl = state_store::ttl(ttl_secs)?;
+
+    store
+        .incr(&key, by, ttl)
+        .map(Value::from)
+        .map_err(|err| err.to_string().into())
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct IncrState {
+    store: Option<StateStore>,
+}
+
+impl IncrState {
+    pub fn new(store: Option<StateStore>) -> Self {
+        Self { store }
+    }
+}
+
+impl Function for IncrState {
+    fn identifier(&self) -> &'static str {
+        "incr_state"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "key",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "by",
+                kind: kind::INTEGER,
+                required: false,
+            },
+            Parameter {
+                keyword: "ttl_secs",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "Count events per host",
+            source: r#"incr_state!("events:" + "my-host", ttl_secs: 60)"#,
+            result: Ok("1"),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let store = self
+            .store
+            .clone()
+            .ok_or_else(|| Box::new(StateNotEnabled(ctx.span())) as Box<dyn DiagnosticMessage>)?;
+        let key = arguments.required("key");
+        let by = arguments.optional("by");
+        let ttl_secs = arguments.optional("ttl_secs");
+
+        Ok(Box::new(IncrStateFn {
+            store,
+            key,
+            by,
+            ttl_secs,
+        }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let store = self.store.as_ref().expect("compiled with a state store");
+        let key = args.required("key");
+        let by = args.optional("by");
+        let ttl_secs = args.optional("ttl_secs");
+
+        incr_state(store, key, by, ttl_secs)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct IncrStateFn {
+    store: StateStore,
+    key: Box<dyn Expression>,
+    by: Option<Box<dyn Expression>>,
+    ttl_secs: Option<Box<dyn Expression>>,
+}
+
+impl Expression for IncrStateFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let key = self.key.resolve(ctx)?;
+        let by = self.by.as_ref().map(|by| by.resolve(ctx)).transpose()?;
+        let ttl_secs = self
+            .ttl_secs
+            .as_ref()
+            .map(|ttl_secs| ttl_secs.resolve(ctx))
+            .transpose()?;
+
+        incr_state(&self.store, key, by, ttl_secs)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::integer().fallible()
+    }
+}
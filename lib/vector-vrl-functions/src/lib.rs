@@ -1,20 +1,38 @@
 pub mod get_metadata_field;
+pub mod get_state;
+pub mod incr_state;
 pub mod remove_metadata_field;
 pub mod set_metadata_field;
 pub mod set_semantic_meaning;
+pub mod set_state;
+pub mod state_store;
 
 use ::value::Value;
 use vrl::prelude::*;
 
+pub use state_store::{StateSnapshot, StateStore, StateStoreLimits, StateStoreMetrics};
+
 pub(crate) fn keys() -> Vec<Value> {
     vec![value!("datadog_api_key"), value!("splunk_hec_token")]
 }
 
 pub fn vrl_functions() -> Vec<Box<dyn vrl::Function>> {
+    vrl_functions_with_state(None)
+}
+
+/// Returns the list of functions, with the state functions operating on the
+/// given store.
+///
+/// Without a store, programs using any of the state functions fail to
+/// compile.
+pub fn vrl_functions_with_state(store: Option<StateStore>) -> Vec<Box<dyn vrl::Function>> {
     vec![
         Box::new(get_metadata_field::GetMetadataField) as _,
+        Box::new(get_state::GetState::new(store.clone())) as _,
+        Box::new(incr_state::IncrState::new(store.clone())) as _,
         Box::new(remove_metadata_field::RemoveMetadataField) as _,
         Box::new(set_metadata_field::SetMetadataField) as _,
         Box::new(set_semantic_meaning::SetSemanticMeaning) as _,
+        Box::new(set_state::SetState::new(store)) as _,
     ]
 }
@@ -0,0 +1,115 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::state_store::{self, StateNotEnabled, StateStore};
+
+fn set_state(store: &StateStore, key: Value, value: Value, ttl_secs: Option<Value>) -> Resolved {
+    let key = key.try_bytes_utf8_lossy()?;
+    let ttl = state_store::ttl(ttl_secs)?;
+
+    store.set(&key, value, ttl).map_err(|err| err.to_string())?;
+
+    Ok(Value::Null)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SetState {
+    store: Option<StateStore>,
+}
+
+impl SetState {
+    pub fn new(store: Option<StateStore>) -> Self {
+        Self { store }
+    }
+}
+
+impl Function for SetState {
+    fn identifier(&self) -> &'static str {
+        "set_state"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "key",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "value",
+                kind: kind::ANY,
+                required: true,
+            },
+            Parameter {
+                keyword: "ttl_secs",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "Store a value in the state",
+            source: r#"set_state!("last_status", 200, ttl_secs: 300)"#,
+            result: Ok("null"),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let store = self
+            .store
+            .clone()
+            .ok_or_else(|| Box::new(StateNotEnabled(ctx.span())) as Box<dyn DiagnosticMessage>)?;
+        let key = arguments.required("key");
+        let value = arguments.required("value");
+        let ttl_secs = arguments.optional("ttl_secs");
+
+        Ok(Box::new(SetStateFn {
+            store,
+            key,
+            value,
+            ttl_secs,
+        }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let store = self.store.as_ref().expect("compiled with a state store");
+        let key = args.required("key");
+        let value = args.required("value");
+        let ttl_secs = args.optional("ttl_secs");
+
+        set_state(store, key, value, ttl_secs)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SetStateFn {
+    store: StateStore,
+    key: Box<dyn Expression>,
+    value: Box<dyn Expression>,
+    ttl_secs: Option<Box<dyn Expression>>,
+}
+
+impl Expression for SetStateFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let key = self.key.resolve(ctx)?;
+        let value = self.value.resolve(ctx)?;
+        let ttl_secs = self
+            .ttl_secs
+            .as_ref()
+            .map(|ttl_secs| ttl_secs.resolve(ctx))
+            .transpose()?;
+
+        set_state(&self.store, key, value, ttl_secs)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::null().fallible()
+    }
+}
@@ -0,0 +1,515 @@
+//! A bounded key/value store that allows VRL programs to keep state between
+//! events.
+//!
+//! Entries expire once their TTL elapses. When the store grows beyond its
+//! configured limits, the entries closest to expiring are evicted first.
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt, fs, io,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use ::value::Value;
+use serde::{Deserialize, Serialize};
+use vector_common::byte_size_of::ByteSizeOf;
+use vrl::{
+    diagnostic::{DiagnosticMessage, Label, Note, Span},
+    prelude::{ExpressionError, VrlValueConvert},
+};
+
+/// The limits applied to a [`StateStore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateStoreLimits {
+    /// The maximum number of entries kept in the store.
+    pub max_entries: usize,
+
+    /// The maximum (approximate) number of bytes used by the keys and values
+    /// in the store.
+    pub max_bytes: usize,
+
+    /// The TTL of entries that don't have an explicit TTL.
+    pub ttl: Duration,
+}
+
+/// A snapshot of the store metrics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateStoreMetrics {
+    /// The number of entries in the store.
+    pub entries: usize,
+
+    /// The (approximate) number of bytes used by the store.
+    pub bytes: usize,
+
+    /// The number of entries evicted because the store exceeded its limits
+    /// since the previous snapshot.
+    pub evicted: u64,
+
+    /// The number of entries removed because their TTL elapsed since the
+    /// previous snapshot.
+    pub expired: u64,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    TooLarge { size: usize, max_bytes: usize },
+    NotInteger(String),
+    Overflow,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::TooLarge { size, max_bytes } => write!(
+                f,
+                "state entry of {} bytes exceeds the state limit of {} bytes",
+                size, max_bytes
+            ),
+            Error::NotInteger(kind) => write!(f, "expected integer state, got {}", kind),
+            Error::Overflow => write!(f, "state counter overflowed"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A handle to a state store, cheap to clone.
+///
+/// All clones share the same underlying entries.
+#[derive(Debug, Clone)]
+pub struct StateStore {
+    limits: StateStoreLimits,
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+
+    /// The keys of all entries, ordered by the time at which they expire.
+    expiry: BTreeSet<(Instant, String)>,
+
+    bytes: usize,
+    evicted: u64,
+    expired: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: Value,
+    size: usize,
+    expires_at: Instant,
+}
+
+/// The on-disk representation of a store entry.
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckpointEntry {
+    key: String,
+    value: Value,
+
+    /// The expiry time in milliseconds since the Unix epoch.
+    expires_at: u64,
+}
+
+/// The live entries of a store at the time [`StateStore::snapshot`] was
+/// called.
+#[derive(Debug)]
+pub struct StateSnapshot {
+    entries: Vec<CheckpointEntry>,
+}
+
+impl StateSnapshot {
+    /// Writes the entries to the given path.
+    ///
+    /// The checkpoint is written to a temporary file first, which then
+    /// replaces the existing checkpoint, if any.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let data = serde_json::to_vec(&self.entries)?;
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, path)
+    }
+}
+
+impl StateStore {
+    pub fn new(limits: StateStoreLimits) -> Self {
+        Self {
+            limits,
+            inner: Arc::default(),
+        }
+    }
+
+    pub fn limits(&self) -> StateStoreLimits {
+        self.limits
+    }
+
+    /// Returns the value stored for the key, if any.
+    pub fn get(&self, key: &str) -> Option<Value> {
+        let mut inner = self.lock();
+        inner.expire(Instant::now());
+
+        inner.entries.get(key).map(|entry| entry.value.clone())
+    }
+
+    /// Stores the value for the key, replacing any existing value and
+    /// resetting its TTL.
+    pub fn set(&self, key: &str, value: Value, ttl: Option<Duration>) -> Result<(), Error> {
+        let size = entry_size(key, &value);
+        if size > self.limits.max_bytes {
+            return Err(Error::TooLarge {
+                size,
+                max_bytes: self.limits.max_bytes,
+            });
+        }
+
+        let now = Instant::now();
+        let mut inner = self.lock();
+        inner.expire(now);
+        inner.remove(key);
+        inner.insert(
+            key.to_owned(),
+            Entry {
+                value,
+                size,
+                expires_at: now + ttl.unwrap_or(self.limits.ttl),
+            },
+        );
+        inner.evict(&self.limits);
+
+        Ok(())
+    }
+
+    /// Increments the integer stored for the key, returning the new value.
+    ///
+    /// A missing key is treated as zero. The TTL only applies when the entry
+    /// is created, incrementing an existing entry keeps its expiry time.
+    pub fn incr(&self, key: &str, by: i64, ttl: Option<Duration>) -> Result<i64, Error> {
+        let now = Instant::now();
+        let mut inner = self.lock();
+        inner.expire(now);
+
+        if let Some(entry) = inner.entries.get_mut(key) {
+            let current = match entry.value {
+                Value::Integer(current) => current,
+                ref value => return Err(Error::NotInteger(value.kind().to_string())),
+            };
+            let value = current.checked_add(by).ok_or(Error::Overflow)?;
+            entry.value = Value::Integer(value);
+
+            return Ok(value);
+        }
+
+        let value = Value::Integer(by);
+        let size = entry_size(key, &value);
+        if size > self.limits.max_bytes {
+            return Err(Error::TooLarge {
+                size,
+                max_bytes: self.limits.max_bytes,
+            });
+        }
+
+        inner.insert(
+            key.to_owned(),
+            Entry {
+                value,
+                size,
+                expires_at: now + ttl.unwrap_or(self.limits.ttl),
+            },
+        );
+        inner.evict(&self.limits);
+
+        Ok(by)
+    }
+
+    /// Returns the current store metrics, resetting the eviction counters.
+    pub fn metrics(&self) -> StateStoreMetrics {
+        let mut inner = self.lock();
+        inner.expire(Instant::now());
+
+        StateStoreMetrics {
+            entries: inner.entries.len(),
+            bytes: inner.bytes,
+            evicted: std::mem::take(&mut inner.evicted),
+            expired: std::mem::take(&mut inner.expired),
+        }
+    }
+
+    /// Writes all live entries to the given path, see [`StateSnapshot::write`].
+    pub fn checkpoint(&self, path: &Path) -> io::Result<()> {
+        self.snapshot().write(path)
+    }
+
+    /// Copies all live entries, to be written as a checkpoint without holding
+    /// on to the store.
+    pub fn snapshot(&self) -> StateSnapshot {
+        let mut inner = self.lock();
+        let now = Instant::now();
+        inner.expire(now);
+
+        let unix_now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let entries = inner
+            .entries
+            .iter()
+            .map(|(key, entry)| {
+                let expires_at = unix_now + entry.expires_at.saturating_duration_since(now);
+
+                CheckpointEntry {
+                    key: key.clone(),
+                    value: entry.value.clone(),
+                    expires_at: expires_at.as_millis() as u64,
+                }
+            })
+            .collect();
+
+        StateSnapshot { entries }
+    }
+
+    /// Loads the entries of a checkpoint written by [`StateStore::checkpoint`],
+    /// returning the number of entries loaded.
+    ///
+    /// A missing checkpoint is not an error. Entries that expired in the
+    /// meantime are skipped.
+    pub fn restore(&self, path: &Path) -> io::Result<usize> {
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(error) => return Err(error),
+        };
+
+        let entries: Vec<CheckpointEntry> = serde_json::from_slice(&data)?;
+        let unix_now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let mut loaded = 0;
+        for CheckpointEntry {
+            key,
+            value,
+            expires_at,
+        } in entries
+        {
+            if expires_at <= unix_now {
+                continue;
+            }
+
+            let ttl = Duration::from_millis(expires_at - unix_now);
+            if self.set(&key, value, Some(ttl)).is_ok() {
+                loaded += 1;
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        // A panic while holding the lock can't leave the store in an
+        // inconsistent state that is worse than losing the state entirely.
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Inner {
+    fn insert(&mut self, key: String, entry: Entry) {
+        self.bytes += entry.size;
+        self.expiry.insert((entry.expires_at, key.clone()));
+        self.entries.insert(key, entry);
+    }
+
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        self.bytes -= entry.size;
+        self.expiry.remove(&(entry.expires_at, key.to_owned()));
+
+        Some(entry)
+    }
+
+    /// Removes all entries that expired at the given time.
+    fn expire(&mut self, now: Instant) {
+        while let Some((expires_at, key)) = self.expiry.iter().next().cloned() {
+            if expires_at > now {
+                break;
+            }
+
+            self.remove(&key);
+            self.expired += 1;
+        }
+    }
+
+    /// Removes the entries closest to expiring until the store is within its
+    /// limits.
+    fn evict(&mut self, limits: &StateStoreLimits) {
+        while self.entries.len() > limits.max_entries || self.bytes > limits.max_bytes {
+            let key = match self.expiry.iter().next() {
+                Some((_, key)) => key.clone(),
+                None => break,
+            };
+
+            self.remove(&key);
+            self.evicted += 1;
+        }
+    }
+}
+
+fn entry_size(key: &str, value: &Value) -> usize {
+    key.len() + value.size_of()
+}
+
+/// The compile-time error returned when a state function is used without a
+/// configured store.
+#[derive(Debug)]
+pub(crate) struct StateNotEnabled(pub(crate) Span);
+
+impl fmt::Display for StateNotEnabled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "state storage is not enabled")
+    }
+}
+
+impl std::error::Error for StateNotEnabled {}
+
+impl DiagnosticMessage for StateNotEnabled {
+    fn code(&self) -> usize {
+        112
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        vec![Label::primary("state storage is not enabled", self.0)]
+    }
+
+    fn notes(&self) -> Vec<Note> {
+        vec![Note::Hint(
+            "state functions are only available in the remap transform with `state` configured"
+                .to_owned(),
+        )]
+    }
+}
+
+/// Converts the optional `ttl_secs` argument of the state functions into a
+/// [`Duration`].
+pub(crate) fn ttl(ttl_secs: Option<Value>) -> Result<Option<Duration>, ExpressionError> {
+    ttl_secs
+        .map(|ttl_secs| match ttl_secs.try_integer()? {
+            ttl_secs if ttl_secs > 0 => Ok(Duration::from_secs(ttl_secs as u64)),
+            ttl_secs => {
+                Err(format!("ttl_secs must be a positive integer, got {}", ttl_secs).into())
+            }
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(max_entries: usize, max_bytes: usize) -> StateStore {
+        StateStore::new(StateStoreLimits {
+            max_entries,
+            max_bytes,
+            ttl: Duration::from_secs(60),
+        })
+    }
+
+    #[test]
+    fn set_and_get() {
+        let store = store(10, 1024);
+
+        assert_eq!(store.get("foo"), None);
+        store.set("foo", Value::from("bar"), None).unwrap();
+        assert_eq!(store.get("foo"), Some(Value::from("bar")));
+        store.set("foo", Value::from(true), None).unwrap();
+        assert_eq!(store.get("foo"), Some(Value::from(true)));
+        assert_eq!(store.metrics().entries, 1);
+    }
+
+    #[test]
+    fn incr() {
+        let store = store(10, 1024);
+
+        assert_eq!(store.incr("count", 1, None).unwrap(), 1);
+        assert_eq!(store.incr("count", 5, None).unwrap(), 6);
+        assert_eq!(store.get("count"), Some(Value::from(6)));
+
+        store.set("text", Value::from("bar"), None).unwrap();
+        assert!(matches!(
+            store.incr("text", 1, None),
+            Err(Error::NotInteger(_))
+        ));
+
+        store.set("max", Value::from(i64::MAX), None).unwrap();
+        assert!(matches!(store.incr("max", 1, None), Err(Error::Overflow)));
+    }
+
+    #[test]
+    fn expires_entries() {
+        let store = store(10, 1024);
+
+        store
+            .set("short", Value::from(1), Some(Duration::from_millis(1)))
+            .unwrap();
+        store.set("long", Value::from(2), None).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert_eq!(store.get("short"), None);
+        assert_eq!(store.get("long"), Some(Value::from(2)));
+
+        let metrics = store.metrics();
+        assert_eq!(metrics.entries, 1);
+        assert_eq!(metrics.expired, 1);
+        assert_eq!(store.metrics().expired, 0);
+    }
+
+    #[test]
+    fn evicts_entries_over_limits() {
+        let store = store(2, 1024);
+
+        store
+            .set("first", Value::from(1), Some(Duration::from_secs(10)))
+            .unwrap();
+        store
+            .set("second", Value::from(2), Some(Duration::from_secs(20)))
+            .unwrap();
+        store
+            .set("third", Value::from(3), Some(Duration::from_secs(30)))
+            .unwrap();
+
+        assert_eq!(store.get("first"), None);
+        assert_eq!(store.get("third"), Some(Value::from(3)));
+        assert_eq!(store.metrics().evicted, 1);
+
+        let store = self::store(10, 256);
+        store.set("a", Value::from("x".repeat(100)), None).unwrap();
+        store.set("b", Value::from("x".repeat(100)), None).unwrap();
+
+        assert_eq!(store.get("a"), None);
+        assert!(store.metrics().bytes <= 256);
+        assert!(matches!(
+            store.set("c", Value::from("x".repeat(300)), None),
+            Err(Error::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn checkpoint_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+
+        let store = store(10, 1024);
+        assert_eq!(store.restore(&path).unwrap(), 0);
+
+        store.set("foo", Value::from("bar"), None).unwrap();
+        store.incr("count", 3, None).unwrap();
+        store.checkpoint(&path).unwrap();
+
+        let restored = self::store(10, 1024);
+        assert_eq!(restored.restore(&path).unwrap(), 2);
+        assert_eq!(restored.get("foo"), Some(Value::from("bar")));
+        assert_eq!(restored.get("count"), Some(Value::from(3)));
+    }
+}
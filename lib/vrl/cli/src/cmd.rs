@@ -17,11 +17,14 @@ use vrl::{
 
 #[cfg(feature = "repl")]
 use super::repl;
-use super::Error;
+use super::{fmt, test, Error};
 
 #[derive(Parser, Debug)]
 #[clap(name = "VRL", about = "Vector Remap Language CLI")]
 pub struct Opts {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// The VRL program to execute. The program ".foo = true", for example, sets the event object's
     /// `foo` field to `true`.
     #[clap(name = "PROGRAM")]
@@ -35,6 +38,11 @@ pub struct Opts {
     #[clap(short, long = "program", conflicts_with("PROGRAM"), parse(from_os_str))]
     program_file: Option<PathBuf>,
 
+    /// A directory in which modules imported by the program are searched for. Can be provided
+    /// multiple times, the directories are searched in order.
+    #[clap(long = "import-path", parse(from_os_str))]
+    import_paths: Vec<PathBuf>,
+
     /// Print the (modified) event object instead of the result of the final expression. Setting
     /// this flag is equivalent to using `.` as the final expression.
     #[clap(short = 'o', long)]
@@ -53,6 +61,17 @@ pub struct Opts {
     print_warnings: bool,
 }
 
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Format VRL programs, and the programs of the `remap` transforms in Vector configuration
+    /// files.
+    Fmt(fmt::Opts),
+
+    /// Run VRL test files, checking the output of programs for given input events, and report the
+    /// lines of the programs covered by the tests.
+    Test(test::Opts),
+}
+
 impl Opts {
     fn timezone(&self) -> Result<TimeZone, Error> {
         if let Some(ref tz) = self.timezone {
@@ -94,6 +113,12 @@ impl Opts {
 }
 
 pub fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    match &opts.command {
+        Some(Command::Fmt(opts)) => return fmt::cmd(opts),
+        Some(Command::Test(opts)) => return test::cmd(opts),
+        None => {}
+    }
+
     match run(opts) {
         Ok(_) => exitcode::OK,
         Err(err) => {
@@ -122,9 +147,12 @@ fn run(opts: &Opts) -> Result<(), Error> {
     } else {
         let objects = opts.read_into_objects()?;
         let source = opts.read_program()?;
-        let (program, warnings) = vrl::compile(&source, &stdlib::all()).map_err(|diagnostics| {
-            Error::Parse(Formatter::new(&source, diagnostics).colored().to_string())
-        })?;
+        let mut state = ExternalEnv::default();
+        let (program, warnings) =
+            vrl::compile_with_search_path(&source, &stdlib::all(), &mut state, &opts.import_paths)
+                .map_err(|diagnostics| {
+                    Error::Parse(Formatter::new(&source, diagnostics).colored().to_string())
+                })?;
 
         #[allow(clippy::print_stderr)]
         if opts.print_warnings {
@@ -0,0 +1,369 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use vrl::diagnostic::Formatter;
+
+use super::Error;
+
+#[derive(Parser, Debug)]
+pub struct Opts {
+    /// The files to format. VRL files are formatted as a whole, Vector configuration files
+    /// (`.toml`, `.yaml`, `.yml` or `.json`) by formatting the `source` of their `remap`
+    /// transforms. Without files, the program read from stdin is written formatted to stdout.
+    #[clap(name = "PATH", parse(from_os_str))]
+    paths: Vec<PathBuf>,
+
+    /// Don't write the formatted files, exit with an error if any of them isn't formatted.
+    #[clap(long)]
+    check: bool,
+}
+
+pub fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    let result = if opts.paths.is_empty() {
+        format_stdin(opts.check)
+    } else {
+        format_files(&opts.paths, opts.check)
+    };
+
+    match result {
+        Ok(true) => exitcode::OK,
+        Ok(false) => exitcode::DATAERR,
+        Err(err) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("{}", err);
+            }
+            exitcode::SOFTWARE
+        }
+    }
+}
+
+/// Format the program read from stdin, returning whether it was formatted
+/// already in `check` mode.
+fn format_stdin(check: bool) -> Result<bool, Error> {
+    let mut source = String::new();
+    io::Read::read_to_string(&mut io::stdin(), &mut source)?;
+
+    let formatted = format_program(&source)?;
+
+    if !check {
+        io::stdout().write_all(formatted.as_bytes())?;
+    }
+
+    Ok(!check || formatted == source)
+}
+
+/// Format the given files in place, or only check them in `check` mode,
+/// returning whether all of them were formatted already in that mode.
+fn format_files(paths: &[PathBuf], check: bool) -> Result<bool, Error> {
+    let mut formatted = true;
+
+    for path in paths {
+        let text = fs::read_to_string(path)?;
+        let (output, unformatted) = format_file(path, &text)?;
+
+        #[allow(clippy::print_stdout)]
+        for name in &unformatted {
+            match name {
+                Some(name) => println!(
+                    "{}: the source of the `{}` transform is not formatted",
+                    path.display(),
+                    name
+                ),
+                None => println!("{}: not formatted", path.display()),
+            }
+        }
+
+        if !unformatted.is_empty() {
+            formatted = false;
+
+            if !check {
+                fs::write(path, output)?;
+            }
+        }
+    }
+
+    Ok(formatted || !check)
+}
+
+/// Format the content of a file, returning the formatted content along with
+/// the programs that weren't formatted: the whole file for VRL files, or the
+/// names of the transforms for configuration files.
+fn format_file(path: &Path, text: &str) -> Result<(String, Vec<Option<String>>), Error> {
+    let format = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => ConfigFormat::Toml,
+        Some("yaml" | "yml") => ConfigFormat::Yaml,
+        Some("json") => ConfigFormat::Json,
+        _ => {
+            let formatted = format_program(text)
+                .map_err(|err| Error::Parse(format!("{}:\n{}", path.display(), err)))?;
+            let unformatted = if formatted == text {
+                vec![]
+            } else {
+                vec![None]
+            };
+
+            return Ok((formatted, unformatted));
+        }
+    };
+
+    let mut output = text.to_owned();
+    let mut unformatted = vec![];
+
+    for (name, source) in format.remap_sources(text)? {
+        let mut formatted = format_program(&source).map_err(|err| {
+            Error::Parse(format!(
+                "{}: the source of the `{}` transform doesn't parse:\n{}",
+                path.display(),
+                name,
+                err
+            ))
+        })?;
+
+        // Keep the source ending the way it did, as single-line strings
+        // can't end with a newline.
+        if !source.ends_with('\n') && formatted.ends_with('\n') {
+            formatted.pop();
+        }
+
+        if formatted != source {
+            output = format.replace_source(&output, &name, &source, &formatted)?;
+            unformatted.push(Some(name));
+        }
+    }
+
+    Ok((output, unformatted))
+}
+
+fn format_program(source: &str) -> Result<String, Error> {
+    vrl::format(source).map_err(|diagnostics| {
+        Error::Parse(Formatter::new(source, diagnostics).colored().to_string())
+    })
+}
+
+#[derive(Clone, Copy)]
+enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    fn parse(self, text: &str) -> Result<serde_json::Value, Error> {
+        let value = match self {
+            Self::Toml => toml::from_str(text).map_err(|err| err.to_string()),
+            Self::Yaml => serde_yaml::from_str(text).map_err(|err| err.to_string()),
+            Self::Json => serde_json::from_str(text).map_err(|err| err.to_string()),
+        };
+
+        value.map_err(Error::Config)
+    }
+
+    /// Returns the names and sources of the `remap` transforms of a
+    /// configuration file.
+    fn remap_sources(self, text: &str) -> Result<Vec<(String, String)>, Error> {
+        let config = self.parse(text)?;
+        let transforms = match config.get("transforms").and_then(|v| v.as_object()) {
+            Some(transforms) => transforms,
+            None => return Ok(vec![]),
+        };
+
+        Ok(transforms
+            .iter()
+            .filter(|(_, transform)| {
+                transform.get("type").and_then(|v| v.as_str()) == Some("remap")
+            })
+            .filter_map(|(name, transform)| {
+                let source = transform.get("source")?.as_str()?;
+
+                Some((name.clone(), source.to_owned()))
+            })
+            .collect())
+    }
+
+    /// Replace the `source` of the `name` transform in the text of the
+    /// configuration file, keeping the rest of the file as it is.
+    ///
+    /// The source is looked up as it's written in the file, raw or indented
+    /// as in TOML multi-line strings and YAML block scalars, or escaped as in
+    /// TOML and JSON strings. The replaced file is parsed again, to make sure
+    /// that the transform ends up with the formatted source.
+    fn replace_source(
+        self,
+        text: &str,
+        name: &str,
+        source: &str,
+        formatted: &str,
+    ) -> Result<String, Error> {
+        let mut encodings = vec![
+            (source.to_owned(), formatted.to_owned()),
+            (escape(source, true), escape(formatted, true)),
+            (escape(source, false), escape(formatted, false)),
+        ];
+
+        for indent in indents(text, source) {
+            encodings.push((indented(source, &indent), indented(formatted, &indent)));
+        }
+
+        for (from, to) in encodings {
+            if from.is_empty() || text.matches(&from).count() != 1 {
+                continue;
+            }
+
+            let replaced = text.replacen(&from, &to, 1);
+            let sources = self.remap_sources(&replaced).unwrap_or_default();
+
+            if sources
+                .iter()
+                .any(|(other, source)| other == name && source == formatted)
+            {
+                return Ok(replaced);
+            }
+        }
+
+        Err(Error::Config(format!(
+            "unable to find the source of the `{}` transform in the configuration, move it to a \
+             VRL file to format it",
+            name
+        )))
+    }
+}
+
+/// Escape a string as in JSON strings and TOML basic strings, keeping the
+/// newlines unescaped if `newlines` is unset, as in TOML multi-line basic
+/// strings.
+fn escape(string: &str, newlines: bool) -> String {
+    let mut escaped = String::with_capacity(string.len());
+
+    for c in string.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' if newlines => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Returns the indentations of the lines of `text` holding the first line of
+/// `source`, for sources written as YAML block scalars.
+fn indents(text: &str, source: &str) -> Vec<String> {
+    let first = match source.lines().find(|line| !line.trim().is_empty()) {
+        Some(line) => line,
+        None => return vec![],
+    };
+
+    let mut indents = text
+        .lines()
+        .filter_map(|line| {
+            let content = line.trim_start_matches(' ');
+
+            (content == first && content.len() < line.len())
+                .then(|| line[..line.len() - content.len()].to_owned())
+        })
+        .collect::<Vec<_>>();
+
+    indents.dedup();
+    indents
+}
+
+/// Indent the non-empty lines of `string`.
+fn indented(string: &str, indent: &str) -> String {
+    string
+        .split('\n')
+        .map(|line| match line {
+            "" => String::new(),
+            line => format!("{}{}", indent, line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn formats_toml_sources() {
+        let text = indoc! {r#"
+            [transforms.parse]
+            type = "remap"
+            inputs = ["in"]
+            source = '''
+            .message=parse_json!( .message )
+            '''
+
+            [transforms.tag]
+            type = "remap"
+            inputs = ["parse"]
+            source = ".tags=[\"a\",\"b\"]"
+
+            [transforms.formatted]
+            type = "remap"
+            inputs = ["tag"]
+            source = ".foo = 1"
+        "#};
+
+        let (output, unformatted) = format_file(Path::new("vector.toml"), text).unwrap();
+
+        assert_eq!(
+            unformatted,
+            vec![Some("parse".to_owned()), Some("tag".to_owned())]
+        );
+        assert!(output.contains("source = '''\n.message = parse_json!(.message)\n'''"));
+        assert!(output.contains(r#"source = ".tags = [\"a\", \"b\"]""#));
+        assert!(output.contains(r#"source = ".foo = 1""#));
+    }
+
+    #[test]
+    fn formats_yaml_sources() {
+        let text = indoc! {r#"
+            transforms:
+              parse:
+                type: remap
+                inputs: [in]
+                source: |
+                  if .a==1 {
+                  .b=2
+                  }
+        "#};
+
+        let (output, unformatted) = format_file(Path::new("vector.yaml"), text).unwrap();
+
+        assert_eq!(unformatted, vec![Some("parse".to_owned())]);
+        assert_eq!(
+            output,
+            indoc! {r#"
+                transforms:
+                  parse:
+                    type: remap
+                    inputs: [in]
+                    source: |
+                      if .a == 1 {
+                          .b = 2
+                      }
+            "#}
+        );
+    }
+
+    #[test]
+    fn formats_vrl_files() {
+        let (output, unformatted) = format_file(Path::new("program.vrl"), ".a=1\n").unwrap();
+
+        assert_eq!(output, ".a = 1\n");
+        assert_eq!(unformatted, vec![None]);
+
+        let (_, unformatted) = format_file(Path::new("program.vrl"), ".a = 1\n").unwrap();
+        assert!(unformatted.is_empty());
+    }
+}
@@ -6,8 +6,10 @@
 #![deny(unused_comparisons)]
 
 pub mod cmd;
+pub mod fmt;
 #[cfg(feature = "repl")]
 mod repl;
+pub mod test;
 
 pub use cmd::{cmd, Opts};
 
@@ -26,6 +28,9 @@ pub enum Error {
     #[error("input error: {}", .0)]
     Json(#[from] serde_json::Error),
 
+    #[error("configuration error: {}", .0)]
+    Config(String),
+
     #[error("repl feature disabled, program input required")]
     ReplFeature,
 }
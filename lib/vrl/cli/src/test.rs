@@ -0,0 +1,545 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use ::value::Value;
+use clap::Parser;
+use vector_common::TimeZone;
+use vrl::{
+    coverage::Coverage,
+    diagnostic::Formatter,
+    state::{self, ExternalEnv},
+    Program, Runtime, Terminate, Vm, VrlRuntime,
+};
+
+use super::Error;
+
+/// Files ending with this suffix are the ones run when searching directories for tests.
+const TEST_SUFFIX: &str = "_test.vrl";
+
+/// The headers describing a test, at the start of a test file.
+const HEADERS: [&str; 5] = ["input", "output", "result", "error", "program"];
+
+#[derive(Parser, Debug)]
+pub struct Opts {
+    /// The test files to run, or directories in which the files ending with `_test.vrl` are run,
+    /// including those of subdirectories.
+    ///
+    /// A test file starts with comment headers: `input` is the event the program runs on,
+    /// `output` the event expected after the run, `result` the expected value of the program, and
+    /// `error` part of the message of the error the program is expected to fail with. The values
+    /// are written as VRL expressions, JSON documents for example, and can span multiple comment
+    /// lines. The program is either written below the headers, or read from the file set in the
+    /// `program` header, relative to the test file.
+    #[clap(name = "PATH", parse(from_os_str), required = true)]
+    paths: Vec<PathBuf>,
+
+    /// Report the lines of each program reached by its tests. A line is covered once all the
+    /// expressions starting on it were reached.
+    #[clap(long)]
+    coverage: bool,
+
+    /// A directory in which modules imported by the programs are searched for. Can be provided
+    /// multiple times, the directories are searched in order.
+    #[clap(long = "import-path", parse(from_os_str))]
+    import_paths: Vec<PathBuf>,
+
+    /// The timezone used to parse dates.
+    #[clap(short = 'z', long)]
+    timezone: Option<String>,
+
+    /// The runtime the tests are run with.
+    #[clap(short, long = "runtime", default_value_t)]
+    runtime: VrlRuntime,
+}
+
+impl Opts {
+    fn timezone(&self) -> Result<TimeZone, Error> {
+        if let Some(ref tz) = self.timezone {
+            TimeZone::parse(tz)
+                .ok_or_else(|| Error::Parse(format!("unable to parse timezone: {}", tz)))
+        } else {
+            Ok(TimeZone::default())
+        }
+    }
+}
+
+pub fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    match run(opts) {
+        Ok(true) => exitcode::OK,
+        Ok(false) => exitcode::DATAERR,
+        Err(err) => {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("{}", err);
+            }
+            exitcode::SOFTWARE
+        }
+    }
+}
+
+/// Run the tests, returning whether all of them passed.
+#[allow(clippy::print_stdout)]
+fn run(opts: &Opts) -> Result<bool, Error> {
+    let timezone = opts.timezone()?;
+    let paths = test_paths(&opts.paths)?;
+
+    if paths.is_empty() {
+        return Err(Error::Config("no test files found".to_owned()));
+    }
+
+    let mut programs = BTreeMap::new();
+    let mut failed = 0;
+
+    for path in &paths {
+        match run_test(path, opts, &timezone, &mut programs) {
+            Ok(()) => println!("{} ... ok", path.display()),
+            Err(err) => {
+                failed += 1;
+                println!("{} ... FAILED", path.display());
+                for line in err.lines() {
+                    println!("    {}", line);
+                }
+            }
+        }
+    }
+
+    if opts.coverage {
+        println!("\ncoverage:");
+
+        for (path, program) in &programs {
+            if let Ok(program) = program {
+                print!("{}", coverage_report(path, program));
+            }
+        }
+    }
+
+    println!("\n{} passed, {} failed", paths.len() - failed, failed);
+
+    Ok(failed == 0)
+}
+
+/// The test files to run: the given files, and the test files found in the
+/// given directories.
+fn test_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>, Error> {
+    let mut tests = vec![];
+
+    for path in paths {
+        if path.is_dir() {
+            find_tests(path, &mut tests)?;
+        } else {
+            tests.push(path.clone());
+        }
+    }
+
+    Ok(tests)
+}
+
+fn find_tests(dir: &Path, tests: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            find_tests(&path, tests)?;
+        } else if path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map_or(false, |name| name.ends_with(TEST_SUFFIX))
+        {
+            tests.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the test at the given path, compiling its program unless another test
+/// already did, so that the coverage of a program is that of all its tests.
+fn run_test(
+    path: &Path,
+    opts: &Opts,
+    timezone: &TimeZone,
+    programs: &mut BTreeMap<PathBuf, Result<TestProgram, String>>,
+) -> Result<(), String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("unable to read the test: {}", err))?;
+    let test = Test::parse(&content)?;
+
+    // An inline program is the test file itself, the headers being comments,
+    // so that the lines reported by the coverage are those of the file.
+    let (program_path, source) = match &test.program {
+        Some(program) => (
+            path.parent().unwrap_or_else(|| Path::new("")).join(program),
+            None,
+        ),
+        None => (path.to_owned(), Some(content)),
+    };
+
+    let program = programs
+        .entry(program_path)
+        .or_insert_with_key(|program_path| {
+            let source = match source {
+                Some(source) => source,
+                None => fs::read_to_string(program_path)
+                    .map_err(|err| format!("unable to read {}: {}", program_path.display(), err))?,
+            };
+
+            TestProgram::compile(source, &opts.import_paths, opts.runtime)
+        })
+        .as_ref()
+        .map_err(Clone::clone)?;
+
+    test.check(program, timezone)
+}
+
+/// A test read from a test file.
+#[derive(Debug, Default, PartialEq)]
+struct Test {
+    input: Option<String>,
+    output: Option<String>,
+    result: Option<String>,
+    error: Option<String>,
+    program: Option<PathBuf>,
+}
+
+impl Test {
+    fn parse(content: &str) -> Result<Self, String> {
+        let mut headers = BTreeMap::new();
+        let mut current = None;
+        let mut inline = false;
+        let mut in_headers = true;
+
+        for line in content.lines() {
+            let comment = match line.strip_prefix('#') {
+                Some(comment) if in_headers => comment,
+                _ => {
+                    in_headers = false;
+
+                    let line = line.trim_start();
+                    inline |= !line.is_empty() && !line.starts_with('#');
+                    continue;
+                }
+            };
+            let comment = comment.strip_prefix(' ').unwrap_or(comment);
+
+            let header = HEADERS.iter().find_map(|&name| {
+                comment
+                    .strip_prefix(name)
+                    .and_then(|value| value.strip_prefix(':'))
+                    .map(|value| (name, value))
+            });
+
+            match header {
+                Some((name, value)) => {
+                    if headers.insert(name, value.to_owned()).is_some() {
+                        return Err(format!("the `{}` header is set more than once", name));
+                    }
+                    current = Some(name);
+                }
+                // Comment lines following a header continue it.
+                None => {
+                    if let Some(value) = current.and_then(|name| headers.get_mut(name)) {
+                        value.push('\n');
+                        value.push_str(comment);
+                    }
+                }
+            }
+        }
+
+        let mut header = |name: &str| {
+            headers
+                .remove(name)
+                .map(|value: String| value.trim().to_owned())
+        };
+
+        let test = Self {
+            input: header("input"),
+            output: header("output"),
+            result: header("result"),
+            error: header("error"),
+            program: header("program").map(PathBuf::from),
+        };
+
+        if test.program.is_some() && inline {
+            return Err(
+                "the test has both a `program` header and a program below its headers".to_owned(),
+            );
+        }
+
+        if test.program.is_none() && !inline {
+            return Err(
+                "the test has no program, set the `program` header or write it below the headers"
+                    .to_owned(),
+            );
+        }
+
+        if test.error.is_some() && (test.output.is_some() || test.result.is_some()) {
+            return Err(
+                "the `error` header can't be set along with the `output` or `result` headers"
+                    .to_owned(),
+            );
+        }
+
+        Ok(test)
+    }
+
+    /// Run the program on the input of the test, and check that it behaves as
+    /// expected.
+    fn check(&self, program: &TestProgram, timezone: &TimeZone) -> Result<(), String> {
+        let mut target = match &self.input {
+            Some(input) => header_value(input).map_err(|err| format!("invalid input: {}", err))?,
+            None => Value::Object(BTreeMap::new()),
+        };
+
+        let result = program.run(&mut target, timezone);
+
+        let value = match (result, &self.error) {
+            (Ok(value), None) => value,
+            (Ok(value), Some(want)) => {
+                return Err(format!(
+                    "expected an error containing: {}\ngot result: {}",
+                    want, value
+                ))
+            }
+            (Err(err), Some(want)) if err.to_string().contains(want.as_str()) => return Ok(()),
+            (Err(err), Some(want)) => {
+                return Err(format!(
+                    "expected an error containing: {}\ngot error: {}",
+                    want, err
+                ))
+            }
+            (Err(err), None) => return Err(format!("unexpected error: {}", err)),
+        };
+
+        let mut failures = vec![];
+
+        for (name, want, got) in [
+            ("result", &self.result, &value),
+            ("output", &self.output, &target),
+        ] {
+            if let Some(want) = want {
+                let want =
+                    header_value(want).map_err(|err| format!("invalid {}: {}", name, err))?;
+
+                if &want != got {
+                    failures.push(format!(
+                        "expected {}: {}\ngot {}: {}",
+                        name, want, name, got
+                    ));
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures.join("\n"))
+        }
+    }
+}
+
+/// Resolve a value of a test header, written as a VRL expression.
+fn header_value(source: &str) -> Result<Value, String> {
+    let (program, _) = vrl::compile(source, &[])
+        .map_err(|diagnostics| Formatter::new(source, diagnostics).to_string())?;
+
+    let mut target = Value::Object(BTreeMap::new());
+    Runtime::new(state::Runtime::default())
+        .resolve(&mut target, &program, &TimeZone::default())
+        .map_err(|err| err.to_string())
+}
+
+/// A program under test, along with the coverage of its tests.
+struct TestProgram {
+    source: String,
+    program: Program,
+    vm: Option<Vm>,
+    coverage: Coverage,
+}
+
+impl TestProgram {
+    fn compile(
+        source: String,
+        import_paths: &[PathBuf],
+        vrl_runtime: VrlRuntime,
+    ) -> Result<Self, String> {
+        let coverage = Coverage::default();
+        let mut state = ExternalEnv::default();
+        let (program, _) = vrl::compile_with_coverage(
+            &source,
+            &stdlib::all(),
+            &mut state,
+            import_paths,
+            &coverage,
+        )
+        .map_err(|diagnostics| Formatter::new(&source, diagnostics).colored().to_string())?;
+
+        let vm = match vrl_runtime {
+            VrlRuntime::Vm => Some(
+                Runtime::new(state::Runtime::default())
+                    .compile(stdlib::all(), &program, &mut state)
+                    .map_err(|err| format!("unable to compile to the vm: {}", err))?,
+            ),
+            VrlRuntime::Ast => None,
+        };
+
+        Ok(Self {
+            source,
+            program,
+            vm,
+            coverage,
+        })
+    }
+
+    fn run(&self, target: &mut Value, timezone: &TimeZone) -> Result<Value, Terminate> {
+        let mut runtime = Runtime::new(state::Runtime::default());
+
+        match &self.vm {
+            Some(vm) => runtime.run_vm(vm, target, timezone),
+            None => runtime.resolve(target, &self.program, timezone),
+        }
+    }
+}
+
+fn coverage_report(path: &Path, program: &TestProgram) -> String {
+    let lines = program.coverage.lines(&program.source);
+    let uncovered = lines
+        .iter()
+        .filter(|line| !line.is_covered())
+        .map(|line| line.line.to_string())
+        .collect::<Vec<_>>();
+    let covered = lines.len() - uncovered.len();
+
+    let percentage = if lines.is_empty() {
+        100.0
+    } else {
+        covered as f64 / lines.len() as f64 * 100.0
+    };
+
+    let mut report = format!(
+        "  {}: {}/{} lines ({:.1}%)\n",
+        path.display(),
+        covered,
+        lines.len(),
+        percentage
+    );
+
+    if !uncovered.is_empty() {
+        report.push_str(&format!("    uncovered lines: {}\n", uncovered.join(", ")));
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    const PROGRAM: &str = indoc! {r#"
+        # input: { "message": "foo" }
+        # output: {
+        #   "message": "foo",
+        #   "length": 3
+        # }
+
+        .length = length!(.message)
+        if .length > 5 {
+            .long = true
+        }
+    "#};
+
+    fn check(content: &str, vrl_runtime: VrlRuntime) -> Result<(), String> {
+        let test = Test::parse(content)?;
+        let program = TestProgram::compile(content.to_owned(), &[], vrl_runtime)?;
+
+        test.check(&program, &TimeZone::default())
+    }
+
+    #[test]
+    fn parses_headers() {
+        let test = Test::parse(indoc! {r#"
+            # A description of the test.
+            # input: { "message": "foo" }
+            # result: [
+            #   1,
+            #   2
+            # ]
+            # program: ../remap.vrl
+        "#})
+        .unwrap();
+
+        assert_eq!(
+            test,
+            Test {
+                input: Some(r#"{ "message": "foo" }"#.to_owned()),
+                result: Some("[\n  1,\n  2\n]".to_owned()),
+                program: Some(PathBuf::from("../remap.vrl")),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_tests() {
+        assert!(Test::parse("# input: {}\n").is_err());
+        assert!(Test::parse("# program: remap.vrl\n\n.foo = 1\n").is_err());
+        assert!(Test::parse("# error: foo\n# result: 1\n\n.foo = 1\n").is_err());
+        assert!(Test::parse("# input: {}\n# input: {}\n\n.foo = 1\n").is_err());
+    }
+
+    #[test]
+    fn checks_tests() {
+        for vrl_runtime in [VrlRuntime::Ast, VrlRuntime::Vm] {
+            assert_eq!(check(PROGRAM, vrl_runtime), Ok(()));
+
+            let err = check(
+                &PROGRAM.replace(r#""length": 3"#, r#""length": 4"#),
+                vrl_runtime,
+            )
+            .unwrap_err();
+            assert!(err.starts_with("expected output:"), "{}", err);
+
+            let err = check("# result: 2\n\n1 + 2\n", vrl_runtime).unwrap_err();
+            assert_eq!(err, "expected result: 2\ngot result: 3");
+
+            assert_eq!(
+                check(
+                    "# error: unable to parse json\n\nparse_json!(\"{\")\n",
+                    vrl_runtime
+                ),
+                Ok(())
+            );
+
+            let err = check("# error: foo\n\n.foo = 1\n", vrl_runtime).unwrap_err();
+            assert!(
+                err.starts_with("expected an error containing: foo"),
+                "{}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn reports_coverage() {
+        for vrl_runtime in [VrlRuntime::Ast, VrlRuntime::Vm] {
+            let program = TestProgram::compile(PROGRAM.to_owned(), &[], vrl_runtime).unwrap();
+            Test::parse(PROGRAM)
+                .unwrap()
+                .check(&program, &TimeZone::default())
+                .unwrap();
+
+            assert_eq!(
+                coverage_report(Path::new("length_test.vrl"), &program),
+                "  length_test.vrl: 2/3 lines (66.7%)\n    uncovered lines: 9\n"
+            );
+        }
+    }
+}
@@ -1,13 +1,19 @@
+#[cfg(feature = "expr-function_call")]
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
 use diagnostic::{DiagnosticList, DiagnosticMessage, Severity, Span};
 use lookup::LookupBuf;
 use parser::ast::{self, Node};
 
 use crate::{
+    coverage::Coverage,
     expression::*,
     program::ProgramInfo,
     state::{ExternalEnv, LocalEnv},
     Function, Program,
 };
+#[cfg(feature = "expr-function_call")]
+use crate::{module::Modules, parser::Ident};
 
 pub(crate) type Diagnostics = Vec<Box<dyn DiagnosticMessage>>;
 
@@ -19,6 +25,16 @@ pub(crate) struct Compiler<'a> {
     local: LocalEnv,
     external_queries: Vec<LookupBuf>,
     external_assignments: Vec<LookupBuf>,
+
+    /// The functions defined by the program, or by the modules it imports.
+    #[cfg(feature = "expr-function_call")]
+    user_functions: HashMap<Ident, Arc<UserFunction>>,
+
+    #[cfg(feature = "expr-function_call")]
+    modules: Modules,
+
+    /// When set, each statement of the program records to it when reached.
+    coverage: Option<Coverage>,
 }
 
 impl<'a> Compiler<'a> {
@@ -31,9 +47,32 @@ impl<'a> Compiler<'a> {
             local: LocalEnv::default(),
             external_queries: vec![],
             external_assignments: vec![],
+            #[cfg(feature = "expr-function_call")]
+            user_functions: HashMap::new(),
+            #[cfg(feature = "expr-function_call")]
+            modules: Modules::default(),
+            coverage: None,
         }
     }
 
+    /// Record the statements of the program reached at runtime to the given
+    /// coverage.
+    ///
+    /// Statements of imported modules aren't recorded, as their spans don't
+    /// point into the source of the program.
+    pub(super) fn with_coverage(mut self, coverage: &Coverage) -> Self {
+        self.coverage = Some(coverage.clone());
+        self
+    }
+
+    /// Set the directories in which modules imported by the program are
+    /// searched for.
+    #[cfg(feature = "expr-function_call")]
+    pub(super) fn with_search_path(mut self, search_path: &[PathBuf]) -> Self {
+        self.modules = Modules::new(search_path);
+        self
+    }
+
     /// An intenal function used by `compile_for_repl`.
     ///
     /// This should only be used for its intended purpose.
@@ -96,7 +135,15 @@ impl<'a> Compiler<'a> {
                             self.diagnostics.push(Box::new(err));
                         }
 
-                        Some(expr)
+                        Some(self.probe(span, expr))
+                    }
+                    FunctionDefinition(node) => {
+                        self.compile_function_definition(node, external);
+                        None
+                    }
+                    Import(node) => {
+                        self.compile_import(node);
+                        None
                     }
                     Error(err) => {
                         self.handle_parser_error(err);
@@ -128,7 +175,7 @@ impl<'a> Compiler<'a> {
             Op(node) => self.compile_op(node, external).into(),
             Assignment(node) => self.compile_assignment(node, external).into(),
             Query(node) => self.compile_query(node, external).into(),
-            FunctionCall(node) => self.compile_function_call(node, external).into(),
+            FunctionCall(node) => self.compile_call(node, external),
             Variable(node) => self.compile_variable(node, external).into(),
             Unary(node) => self.compile_unary(node, external).into(),
             Abort(node) => self.compile_abort(node, external).into(),
@@ -226,7 +273,15 @@ impl<'a> Compiler<'a> {
         // We can now start compiling the expressions within the block, which
         // will use the existing local state of the compiler, as blocks have
         // access to any state of their parent expressions.
-        let exprs = self.compile_exprs(node.into_inner().into_iter(), external);
+        let exprs = node
+            .into_inner()
+            .into_iter()
+            .map(|node| {
+                let span = node.span();
+                let expr = self.compile_expr(node, external);
+                self.probe(span, expr)
+            })
+            .collect();
 
         // Now that we've compiled the expressions, we pass them into the block,
         // and also a copy of the local state, which includes any state added by
@@ -494,12 +549,34 @@ impl<'a> Compiler<'a> {
                 Target::Container(container)
             }
             FunctionCall(call) => {
+                // Calls to user-defined functions are queried like any other
+                // expression wrapped in a group, e.g. `(foo()).bar`.
+                #[cfg(feature = "expr-function_call")]
+                if let Some(function) = self.user_functions.get(call.ident.inner()).cloned() {
+                    let call = Node::new(span, call);
+                    let expr = self.compile_user_function_call(call, function, external);
+                    return Target::Container(crate::expression::Container::new(
+                        Group::new(expr).into(),
+                    ));
+                }
+
                 let call = self.compile_function_call(Node::new(span, call), external);
                 Target::FunctionCall(call)
             }
         }
     }
 
+    /// Compiles a call to either a user-defined function, or a function of the
+    /// standard library.
+    fn compile_call(&mut self, node: Node<ast::FunctionCall>, external: &mut ExternalEnv) -> Expr {
+        #[cfg(feature = "expr-function_call")]
+        if let Some(function) = self.user_functions.get(node.ident.inner()).cloned() {
+            return self.compile_user_function_call(node, function, external);
+        }
+
+        self.compile_function_call(node, external).into()
+    }
+
     #[cfg(feature = "expr-function_call")]
     fn compile_function_call(
         &mut self,
@@ -559,12 +636,25 @@ impl<'a> Compiler<'a> {
         )
         // Then, we compile the closure block, and compile the final
         // function-call expression, including the attached closure.
-        .and_then(|builder| {
+        .and_then(|mut builder| {
             let block = closure_block.map(|block| {
                 let span = block.span();
-                let block = self.compile_block(block, external);
+                let diagnostics = self.diagnostics.len();
+                let mut local = self.local.clone();
+                let mut compiled = self.compile_block(block.clone(), external);
+
+                // The type of the accumulator variable of a closure depends
+                // on the type returned by the closure itself, so the block is
+                // compiled again until the type of the variable settles.
+                while builder
+                    .widen_accumulator(&mut local, compiled.type_def((&self.local, external)))
+                {
+                    self.diagnostics.truncate(diagnostics);
+                    self.local = local.clone();
+                    compiled = self.compile_block(block.clone(), external);
+                }
 
-                Node::new(span, block)
+                Node::new(span, compiled)
             });
 
             builder.compile(&mut self.local, external, block, local_snapshot)
@@ -586,6 +676,297 @@ impl<'a> Compiler<'a> {
         FunctionArgument::new(ident, expr)
     }
 
+    #[cfg(feature = "expr-function_call")]
+    fn compile_user_function_call(
+        &mut self,
+        node: Node<ast::FunctionCall>,
+        function: Arc<UserFunction>,
+        external: &mut ExternalEnv,
+    ) -> Expr {
+        let call_span = node.span();
+        let ast::FunctionCall {
+            ident,
+            abort_on_error,
+            arguments,
+            closure,
+        } = node.into_inner();
+
+        let arguments = arguments
+            .into_iter()
+            .map(|node| Node::new(node.span(), self.compile_function_argument(node, external)))
+            .collect();
+
+        if abort_on_error {
+            self.fallible = true;
+        }
+
+        let closure_span = closure.map(|closure| closure.span());
+
+        let call = match UserFunctionCall::new(
+            call_span,
+            ident.span(),
+            abort_on_error,
+            arguments,
+            closure_span,
+            function,
+            (&self.local, external),
+        ) {
+            Ok(call) => call,
+            Err(err) => {
+                self.diagnostics.push(Box::new(err));
+                return Noop.into();
+            }
+        };
+
+        // The function can change the type of the external target, which is
+        // then known for the rest of the program.
+        if let Some(target) = call.function().target() {
+            external.update_target(target.clone());
+        }
+
+        call.into()
+    }
+
+    #[cfg(feature = "expr-function_call")]
+    fn compile_function_definition(
+        &mut self,
+        node: Node<ast::FunctionDefinition>,
+        external: &mut ExternalEnv,
+    ) {
+        use crate::{
+            type_def::Details,
+            value::{Collection, Kind},
+            TypeDef,
+        };
+        use user_function::{annotation_kind, Error, Parameter};
+
+        let ast::FunctionDefinition {
+            ident,
+            parameters,
+            return_kind,
+            block,
+        } = node.into_inner();
+
+        let (ident_span, ident) = ident.take();
+
+        let builtin = self.fns.iter().any(|f| f.identifier() == ident.as_ref());
+        if builtin || self.user_functions.contains_key(&ident) {
+            self.diagnostics.push(Box::new(Error::AlreadyDefined {
+                ident_span,
+                ident,
+                builtin,
+            }));
+            return;
+        }
+
+        let mut local = LocalEnv::default();
+        let mut function_parameters: Vec<Parameter> = Vec::with_capacity(parameters.len());
+
+        for parameter in parameters {
+            let parameter_span = parameter.span();
+            let ast::FunctionParameter { ident, kind } = parameter.into_inner();
+            let ident = ident.into_inner();
+
+            if function_parameters.iter().any(|p| p.ident == ident) {
+                self.diagnostics.push(Box::new(Error::DuplicateParameter {
+                    parameter_span,
+                    ident,
+                }));
+                return;
+            }
+
+            let kind = match kind.map(annotation_kind).transpose() {
+                Ok(kind) => kind.unwrap_or_else(Kind::any),
+                Err(err) => {
+                    self.diagnostics.push(Box::new(err));
+                    return;
+                }
+            };
+
+            let details = Details {
+                type_def: kind.clone().into(),
+                value: None,
+            };
+
+            local.insert_variable(ident.clone(), details);
+            function_parameters.push(Parameter { ident, kind });
+        }
+
+        let return_kind = match return_kind
+            .map(|node| (node.span(), node))
+            .map(|(span, node)| annotation_kind(node).map(|kind| (span, kind)))
+            .transpose()
+        {
+            Ok(return_kind) => return_kind,
+            Err(err) => {
+                self.diagnostics.push(Box::new(err));
+                return;
+            }
+        };
+
+        // The body of the function only has access to its parameters, and
+        // can't make any assumption about the external target, as it can be
+        // called from anywhere in the program.
+        let local = std::mem::replace(&mut self.local, local);
+        let target = external.swap_target(Some(Details {
+            type_def: TypeDef::object(Collection::any()),
+            value: None,
+        }));
+        let assignments = self.external_assignments.len();
+
+        let block_span = block.span();
+        let body = self.compile_block(block, external);
+        let type_def = body.type_def((&self.local, external));
+
+        let function_target = external.swap_target(target);
+        let function_target = if self.external_assignments.len() > assignments {
+            function_target
+        } else {
+            None
+        };
+        self.local = local;
+
+        if let Some((return_span, expected_kind)) = return_kind {
+            if !expected_kind.is_superset(type_def.kind()) {
+                self.diagnostics.push(Box::new(Error::ReturnTypeMismatch {
+                    block_span,
+                    return_span,
+                    found_kind: type_def.kind().clone(),
+                    expected_kind,
+                }));
+                return;
+            }
+        }
+
+        // Functions are only registered once their body is compiled, so that
+        // they can't call themselves.
+        let function = UserFunction::new(
+            ident.clone(),
+            function_parameters,
+            body,
+            type_def,
+            function_target,
+        );
+
+        self.user_functions.insert(ident, Arc::new(function));
+    }
+
+    #[cfg(not(feature = "expr-function_call"))]
+    fn compile_function_definition(
+        &mut self,
+        node: Node<ast::FunctionDefinition>,
+        _: &mut ExternalEnv,
+    ) {
+        self.handle_missing_feature_error(node.span(), "expr-function_call");
+    }
+
+    #[cfg(feature = "expr-function_call")]
+    fn compile_import(&mut self, node: Node<ast::Import>) {
+        use crate::module::Error;
+
+        let (path_span, path) = node.into_inner().path.take();
+
+        let module_path = match self.modules.find(&path) {
+            Some(module_path) => module_path,
+            None => {
+                self.diagnostics.push(Box::new(Error::NotFound {
+                    path_span,
+                    path,
+                    search_path: self.modules.search_path().to_vec(),
+                }));
+                return;
+            }
+        };
+
+        if let Err(cycle) = self.modules.enter(&module_path) {
+            self.diagnostics
+                .push(Box::new(Error::Cycle { path_span, cycle }));
+            return;
+        }
+
+        // A module imported more than once only defines its functions once.
+        if self.modules.insert(module_path.clone()) {
+            match std::fs::read_to_string(&module_path) {
+                Ok(source) => {
+                    let diagnostics = self.compile_module(&source);
+
+                    if !diagnostics.is_empty() {
+                        let severity = if diagnostics.is_err() {
+                            Severity::Error
+                        } else {
+                            Severity::Warning
+                        };
+
+                        let diagnostics = diagnostic::Formatter::new(&source, diagnostics)
+                            .with_file_name(module_path.display().to_string())
+                            .to_string();
+
+                        self.diagnostics.push(Box::new(Error::Module {
+                            path_span,
+                            path: module_path.clone(),
+                            diagnostics,
+                            severity,
+                        }));
+                    }
+                }
+                Err(error) => self.diagnostics.push(Box::new(Error::Read {
+                    path_span,
+                    path: module_path.clone(),
+                    error,
+                })),
+            }
+        }
+
+        self.modules.exit();
+    }
+
+    /// Compiles the function definitions and imports of a module, returning
+    /// the diagnostics tied to the source of the module.
+    #[cfg(feature = "expr-function_call")]
+    fn compile_module(&mut self, source: &str) -> DiagnosticList {
+        use crate::module::Error;
+
+        let ast = match parser::parse(source) {
+            Ok(ast) => ast,
+            Err(err) => return DiagnosticList::from(vec![Box::new(err) as Box<_>]),
+        };
+
+        let mut compiler = Compiler::new(self.fns);
+        compiler.user_functions = std::mem::take(&mut self.user_functions);
+        compiler.modules = std::mem::take(&mut self.modules);
+
+        let nodes = ast
+            .into_iter()
+            .filter(|node| match node.inner() {
+                ast::RootExpr::Expr(_) => {
+                    let span = node.span();
+                    compiler
+                        .diagnostics
+                        .push(Box::new(Error::UnexpectedExpression { span }));
+                    false
+                }
+                _ => true,
+            })
+            .collect::<Vec<_>>();
+
+        compiler.compile_root_exprs(nodes, &mut ExternalEnv::default());
+
+        self.user_functions = compiler.user_functions;
+        self.modules = compiler.modules;
+        self.fallible |= compiler.fallible;
+        self.abortable |= compiler.abortable;
+        self.external_queries.extend(compiler.external_queries);
+        self.external_assignments
+            .extend(compiler.external_assignments);
+
+        compiler.diagnostics.into()
+    }
+
+    #[cfg(not(feature = "expr-function_call"))]
+    fn compile_import(&mut self, node: Node<ast::Import>) {
+        self.handle_missing_feature_error(node.span(), "expr-function_call");
+    }
+
     #[cfg(not(feature = "expr-function_call"))]
     fn compile_function_call(
         &mut self,
@@ -665,6 +1046,14 @@ impl<'a> Compiler<'a> {
         self.handle_missing_feature_error(node.span(), "expr-abort")
     }
 
+    /// Wrap a statement in a coverage probe, if coverage is recorded.
+    fn probe(&self, span: Span, expr: Expr) -> Expr {
+        match &self.coverage {
+            Some(coverage) => Probe::new(coverage, span, expr).into(),
+            None => expr,
+        }
+    }
+
     fn handle_parser_error(&mut self, error: parser::Error) {
         self.diagnostics.push(Box::new(error))
     }
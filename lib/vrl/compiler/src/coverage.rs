@@ -0,0 +1,146 @@
+//! Expression coverage of a program.
+//!
+//! A program compiled with [`crate::compile_with_coverage`] has each of its
+//! statements wrapped in a [`Probe`](crate::expression::Probe), which records
+//! to a shared [`Coverage`] whenever the statement is reached, with either
+//! runtime.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use diagnostic::Span;
+
+/// Records how many times each probed expression of a program was reached.
+///
+/// This is a handle, all clones record to the same probes.
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    probes: Arc<Mutex<Vec<(Span, u64)>>>,
+}
+
+impl Coverage {
+    /// Adds a probe for the expression at the given span, returning its
+    /// position in the list of probes.
+    pub(crate) fn register(&self, span: Span) -> usize {
+        let mut probes = self.probes.lock().expect("coverage lock poisoned");
+        probes.push((span, 0));
+        probes.len() - 1
+    }
+
+    pub(crate) fn hit(&self, probe: usize) {
+        if let Some((_, hits)) = self
+            .probes
+            .lock()
+            .expect("coverage lock poisoned")
+            .get_mut(probe)
+        {
+            *hits += 1;
+        }
+    }
+
+    /// The span of each probed expression, along with the amount of times it
+    /// was reached.
+    pub fn probes(&self) -> Vec<(Span, u64)> {
+        self.probes.lock().expect("coverage lock poisoned").clone()
+    }
+
+    /// The coverage of each line of the given source on which at least one
+    /// probed expression starts, ordered by line.
+    ///
+    /// The source must be the one the program was compiled from.
+    pub fn lines(&self, source: &str) -> Vec<LineCoverage> {
+        let mut lines = BTreeMap::new();
+
+        for (span, hits) in self.probes() {
+            let start = span.start().min(source.len());
+            let line = source.as_bytes()[..start]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count()
+                + 1;
+
+            let coverage = lines.entry(line).or_insert(LineCoverage {
+                line,
+                hits: 0,
+                missed: 0,
+            });
+
+            coverage.hits = coverage.hits.max(hits);
+            if hits == 0 {
+                coverage.missed += 1;
+            }
+        }
+
+        lines.into_values().collect()
+    }
+}
+
+impl PartialEq for Coverage {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.probes, &other.probes)
+    }
+}
+
+/// The coverage of a single line of source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCoverage {
+    /// The line number, starting at 1.
+    pub line: usize,
+
+    /// The amount of times the most reached expression of the line was
+    /// reached.
+    pub hits: u64,
+
+    /// The amount of expressions starting on the line that were never reached.
+    pub missed: usize,
+}
+
+impl LineCoverage {
+    /// Whether all the expressions starting on the line were reached.
+    pub fn is_covered(&self) -> bool {
+        self.missed == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        let source = "foo\nbar baz\n\nqux";
+        let coverage = Coverage::default();
+
+        let foo = coverage.register(Span::new(0, 3));
+        let bar = coverage.register(Span::new(4, 7));
+        coverage.register(Span::new(8, 11));
+        coverage.register(Span::new(13, 16));
+
+        coverage.hit(foo);
+        coverage.hit(foo);
+        coverage.hit(bar);
+
+        assert_eq!(
+            coverage.lines(source),
+            vec![
+                LineCoverage {
+                    line: 1,
+                    hits: 2,
+                    missed: 0
+                },
+                LineCoverage {
+                    line: 2,
+                    hits: 1,
+                    missed: 1
+                },
+                LineCoverage {
+                    line: 4,
+                    hits: 0,
+                    missed: 1
+                },
+            ]
+        );
+    }
+}
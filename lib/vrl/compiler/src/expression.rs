@@ -24,6 +24,7 @@ mod not;
 mod object;
 #[cfg(feature = "expr-op")]
 mod op;
+mod probe;
 #[cfg(feature = "expr-unary")]
 mod unary;
 mod variable;
@@ -39,6 +40,8 @@ pub(crate) mod literal;
 pub(crate) mod predicate;
 #[cfg(feature = "expr-query")]
 pub(crate) mod query;
+#[cfg(feature = "expr-function_call")]
+pub(crate) mod user_function;
 
 pub use core::{ExpressionError, Resolved};
 
@@ -65,10 +68,13 @@ pub use object::Object;
 pub use op::Op;
 #[cfg(feature = "expr-if_statement")]
 pub use predicate::Predicate;
+pub use probe::Probe;
 #[cfg(feature = "expr-query")]
 pub use query::{Query, Target};
 #[cfg(feature = "expr-unary")]
 pub use unary::Unary;
+#[cfg(feature = "expr-function_call")]
+pub use user_function::{UserFunction, UserFunctionCall};
 pub use variable::Variable;
 
 pub trait Expression: Send + Sync + fmt::Debug + DynClone {
@@ -137,12 +143,15 @@ pub enum Expr {
     Query(Query),
     #[cfg(feature = "expr-function_call")]
     FunctionCall(FunctionCall),
+    #[cfg(feature = "expr-function_call")]
+    UserFunctionCall(UserFunctionCall),
     Variable(Variable),
     Noop(Noop),
     #[cfg(feature = "expr-unary")]
     Unary(Unary),
     #[cfg(feature = "expr-abort")]
     Abort(Abort),
+    Probe(Probe),
 }
 
 impl Expr {
@@ -169,12 +178,15 @@ impl Expr {
             Query(..) => "query",
             #[cfg(feature = "expr-function_call")]
             FunctionCall(..) => "function call",
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(..) => "function call",
             Variable(..) => "variable call",
             Noop(..) => "noop",
             #[cfg(feature = "expr-unary")]
             Unary(..) => "unary operation",
             #[cfg(feature = "expr-abort")]
             Abort(..) => "abort operation",
+            Probe(v) => v.inner().as_str(),
         }
     }
 
@@ -253,12 +265,15 @@ impl Expression for Expr {
             Query(v) => v.resolve(ctx),
             #[cfg(feature = "expr-function_call")]
             FunctionCall(v) => v.resolve(ctx),
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(v) => v.resolve(ctx),
             Variable(v) => v.resolve(ctx),
             Noop(v) => v.resolve(ctx),
             #[cfg(feature = "expr-unary")]
             Unary(v) => v.resolve(ctx),
             #[cfg(feature = "expr-abort")]
             Abort(v) => v.resolve(ctx),
+            Probe(v) => v.resolve(ctx),
         }
     }
 
@@ -279,12 +294,15 @@ impl Expression for Expr {
             Query(v) => Expression::as_value(v),
             #[cfg(feature = "expr-function_call")]
             FunctionCall(v) => Expression::as_value(v),
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(v) => Expression::as_value(v),
             Variable(v) => Expression::as_value(v),
             Noop(v) => Expression::as_value(v),
             #[cfg(feature = "expr-unary")]
             Unary(v) => Expression::as_value(v),
             #[cfg(feature = "expr-abort")]
             Abort(v) => Expression::as_value(v),
+            Probe(v) => Expression::as_value(v),
         }
     }
 
@@ -305,12 +323,15 @@ impl Expression for Expr {
             Query(v) => v.type_def(state),
             #[cfg(feature = "expr-function_call")]
             FunctionCall(v) => v.type_def(state),
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(v) => v.type_def(state),
             Variable(v) => v.type_def(state),
             Noop(v) => v.type_def(state),
             #[cfg(feature = "expr-unary")]
             Unary(v) => v.type_def(state),
             #[cfg(feature = "expr-abort")]
             Abort(v) => v.type_def(state),
+            Probe(v) => v.type_def(state),
         }
     }
 
@@ -336,12 +357,15 @@ impl Expression for Expr {
             Query(v) => v.compile_to_vm(vm, state),
             #[cfg(feature = "expr-function_call")]
             FunctionCall(v) => v.compile_to_vm(vm, state),
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(v) => v.compile_to_vm(vm, state),
             Variable(v) => v.compile_to_vm(vm, state),
             Noop(v) => v.compile_to_vm(vm, state),
             #[cfg(feature = "expr-unary")]
             Unary(v) => v.compile_to_vm(vm, state),
             #[cfg(feature = "expr-abort")]
             Abort(v) => v.compile_to_vm(vm, state),
+            Probe(v) => v.compile_to_vm(vm, state),
         }
     }
 }
@@ -364,12 +388,15 @@ impl fmt::Display for Expr {
             Query(v) => v.fmt(f),
             #[cfg(feature = "expr-function_call")]
             FunctionCall(v) => v.fmt(f),
+            #[cfg(feature = "expr-function_call")]
+            UserFunctionCall(v) => v.fmt(f),
             Variable(v) => v.fmt(f),
             Noop(v) => v.fmt(f),
             #[cfg(feature = "expr-unary")]
             Unary(v) => v.fmt(f),
             #[cfg(feature = "expr-abort")]
             Abort(v) => v.fmt(f),
+            Probe(v) => v.fmt(f),
         }
     }
 }
@@ -424,6 +451,13 @@ impl From<FunctionCall> for Expr {
     }
 }
 
+#[cfg(feature = "expr-function_call")]
+impl From<UserFunctionCall> for Expr {
+    fn from(user_function_call: UserFunctionCall) -> Self {
+        Expr::UserFunctionCall(user_function_call)
+    }
+}
+
 impl From<Variable> for Expr {
     fn from(variable: Variable) -> Self {
         Expr::Variable(variable)
@@ -450,6 +484,12 @@ impl From<Abort> for Expr {
     }
 }
 
+impl From<Probe> for Expr {
+    fn from(probe: Probe) -> Self {
+        Expr::Probe(probe)
+    }
+}
+
 #[cfg(feature = "expr-literal")]
 impl From<Value> for Expr {
     fn from(value: Value) -> Self {
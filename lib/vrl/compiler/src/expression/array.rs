@@ -74,7 +74,7 @@ impl Expression for Array {
 
         // Evaluate each of the elements of the array, the result of each
         // will be added to the stack.
-        for value in self.inner.iter().rev() {
+        for value in &self.inner {
             value.compile_to_vm(vm, (local, external))?;
         }
 
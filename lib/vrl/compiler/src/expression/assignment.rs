@@ -454,8 +454,14 @@ where
                 expr,
                 default,
             } => {
-                // Compile the expression which will leave the result at the top of the stack.
+                // Compile the expression which will leave the result at the top of the stack,
+                // guarded by a handler that continues at `SetPathInfallible` should the
+                // expression raise an error.
+                let handler = vm.emit_jump(OpCode::PushHandler);
                 expr.compile_to_vm(vm, state)?;
+                vm.write_opcode(OpCode::PopHandler);
+                vm.patch_jump(handler);
+
                 vm.write_opcode(OpCode::SetPathInfallible);
 
                 // Write the target for the `Ok` path.
@@ -76,7 +76,6 @@ impl Expression for Block {
         state: (&mut LocalEnv, &mut ExternalEnv),
     ) -> Result<(), String> {
         let (local, external) = state;
-        let mut jumps = Vec::new();
 
         // An empty block should resolve to Null.
         if self.inner.is_empty() {
@@ -92,18 +91,13 @@ impl Expression for Block {
             expr.compile_to_vm(vm, (local, external))?;
 
             if expressions.peek().is_some() {
-                // At the end of each statement (apart from the last one) we need to clean up
-                // This involves popping the value remaining on the stack, and jumping to the end
-                // of the block if we are in error.
-                jumps.push(vm.emit_jump(OpCode::EndStatement));
+                // At the end of each statement (apart from the last one) we need to pop the
+                // value remaining on the stack. Should the statement raise an error, the VM
+                // unwinds past the rest of the block by itself.
+                vm.write_opcode(OpCode::Pop);
             }
         }
 
-        // Update all the jumps to jump to the end of the block.
-        for jump in jumps {
-            vm.patch_jump(jump);
-        }
-
         Ok(())
     }
 }
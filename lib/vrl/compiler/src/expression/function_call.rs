@@ -18,6 +18,10 @@ use crate::{
     Context, Expression, Function, Resolved, Span, TypeDef,
 };
 
+/// The number of times the type definition of the accumulator variable of a
+/// closure is widened, before it's assumed to be of any type.
+const MAX_ACCUMULATOR_WIDENINGS: usize = 3;
+
 pub(crate) struct Builder<'a> {
     abort_on_error: bool,
     maybe_fallible_arguments: bool,
@@ -28,6 +32,10 @@ pub(crate) struct Builder<'a> {
     closure: Option<(Vec<Ident>, closure::Input)>,
     list: ArgumentList,
     function: &'a dyn Function,
+
+    /// The accumulator variable of the closure, if it has one, along with
+    /// its type definition and the number of times it was widened.
+    accumulator: Option<(Ident, TypeDef, usize)>,
 }
 
 impl<'a> Builder<'a> {
@@ -167,6 +175,7 @@ impl<'a> Builder<'a> {
             })?;
 
         // Check function closure validity.
+        let mut accumulator = None;
         let closure = match (function.closure(), closure_variables) {
             // Error if closure is provided for function that doesn't support
             // any.
@@ -330,6 +339,26 @@ impl<'a> Builder<'a> {
 
                                     (kind.into(), None)
                                 }
+
+                                // The variable kind is expected to be equal to
+                                // the kind of the argument it starts with.
+                                //
+                                // The kind returned by the closure is merged
+                                // into it once the closure block is compiled,
+                                // see `Builder::widen_accumulator`.
+                                VariableKind::Accumulator(keyword) => {
+                                    let type_def = list
+                                        .arguments
+                                        .get(keyword)
+                                        .map_or_else(TypeDef::any, |expr| {
+                                            expr.type_def((local, external))
+                                        });
+
+                                    accumulator =
+                                        Some((call_ident.inner().clone(), type_def.clone(), 0));
+
+                                    (type_def, None)
+                                }
                             };
 
                             let details = Details { type_def, value };
@@ -361,9 +390,46 @@ impl<'a> Builder<'a> {
             closure,
             list,
             function: function.as_ref(),
+            accumulator,
         })
     }
 
+    /// Widen the type definition of the accumulator variable of the closure,
+    /// if it has one, to include the type definition `returned` by the
+    /// closure block, and update it in `local`.
+    ///
+    /// Returns `true` if the type definition changed, in which case the block
+    /// has to be compiled again, as it was compiled assuming a narrower type
+    /// for the variable.
+    pub(crate) fn widen_accumulator(&mut self, local: &mut LocalEnv, returned: TypeDef) -> bool {
+        let (ident, type_def, widened) = match self.accumulator.as_mut() {
+            Some(accumulator) => accumulator,
+            None => return false,
+        };
+
+        if type_def.kind().is_superset(returned.kind()) {
+            return false;
+        }
+
+        // Give up on narrowing the type after a few rounds, blocks can keep
+        // nesting the accumulator in new collections.
+        *widened += 1;
+        *type_def = if *widened < MAX_ACCUMULATOR_WIDENINGS {
+            type_def.clone().merge_deep(returned).infallible()
+        } else {
+            TypeDef::any()
+        };
+
+        let details = Details {
+            type_def: type_def.clone(),
+            value: None,
+        };
+
+        local.insert_variable(ident.clone(), details);
+
+        true
+    }
+
     pub(crate) fn compile(
         mut self,
         local: &mut LocalEnv,
@@ -572,10 +638,10 @@ impl FunctionCall {
 impl Expression for FunctionCall {
     fn resolve(&self, ctx: &mut Context) -> Resolved {
         self.expr.resolve(ctx).map_err(|err| match err {
+            // An abort can only be raised from within a closure, it is passed
+            // on unchanged.
             #[cfg(feature = "expr-abort")]
-            ExpressionError::Abort { .. } => {
-                panic!("abort errors must only be defined by `abort` statement")
-            }
+            err @ ExpressionError::Abort { .. } => err,
             ExpressionError::Error {
                 message,
                 mut labels,
@@ -689,6 +755,16 @@ impl Expression for FunctionCall {
         vm: &mut crate::vm::Vm,
         (local, external): (&mut LocalEnv, &mut ExternalEnv),
     ) -> Result<(), String> {
+        // A function resolving to a value known at compile time (such as
+        // `type_def`) is written as a constant.
+        if let Some(value) = self.expr.as_value() {
+            let constant = vm.add_constant(value);
+            vm.write_opcode(OpCode::Constant);
+            vm.write_primitive(constant);
+
+            return Ok(());
+        }
+
         // Resolve the arguments so they are in the order defined in the function.
         let args = match vm.function(self.function_id) {
             Some(fun) => self.resolve_arguments(fun)?,
@@ -288,15 +288,23 @@ impl Expression for Op {
     ) -> Result<(), String> {
         let (local, external) = state;
 
-        self.lhs.compile_to_vm(vm, (local, external))?;
+        if self.opcode == ast::Opcode::Err {
+            // Err is rewritten as a handler around the lhs, which jumps to the
+            // rhs should the lhs raise an error.
+            let handler = vm.emit_jump(OpCode::PushHandler);
+            self.lhs.compile_to_vm(vm, (local, external))?;
+            vm.write_opcode(OpCode::PopHandler);
+            let end_jump = vm.emit_jump(OpCode::Jump);
 
-        let err_jump = if self.opcode != ast::Opcode::Err {
-            // For all Ops other than the Err op, we want to jump to the end of
-            // the statement if the lhs results in an error.
-            Some(vm.emit_jump(OpCode::JumpIfErr))
-        } else {
-            None
-        };
+            vm.patch_jump(handler);
+            vm.write_opcode(OpCode::ClearError);
+            self.rhs.compile_to_vm(vm, (local, external))?;
+            vm.patch_jump(end_jump);
+
+            return Ok(());
+        }
+
+        self.lhs.compile_to_vm(vm, (local, external))?;
 
         // Note, not all opcodes want the RHS evaluated straight away, so we
         // only compile the rhs in each branch as necessary.
@@ -337,13 +345,7 @@ impl Expression for Op {
                 vm.write_opcode(OpCode::And);
                 vm.patch_jump(if_jump);
             }
-            ast::Opcode::Err => {
-                // Err is rewritten as an if statement to allow short circuiting
-                let if_jump = vm.emit_jump(OpCode::JumpIfNotErr);
-                vm.write_opcode(OpCode::ClearError);
-                self.rhs.compile_to_vm(vm, (local, external))?;
-                vm.patch_jump(if_jump);
-            }
+            ast::Opcode::Err => unreachable!(),
             ast::Opcode::Ne => {
                 self.rhs.compile_to_vm(vm, (local, external))?;
                 vm.write_opcode(OpCode::NotEqual);
@@ -374,9 +376,6 @@ impl Expression for Op {
             }
         };
 
-        if let Some(err_jump) = err_jump {
-            vm.patch_jump(err_jump);
-        }
         Ok(())
     }
 }
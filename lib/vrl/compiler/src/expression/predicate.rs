@@ -9,6 +9,7 @@ use crate::{
     parser::Node,
     state::{ExternalEnv, LocalEnv},
     value::Kind,
+    vm::OpCode,
     Context, Expression, Span, TypeDef,
 };
 
@@ -98,9 +99,24 @@ impl Expression for Predicate {
     ) -> std::result::Result<(), String> {
         let (local, external) = state;
 
-        for inner in &self.inner {
-            inner.compile_to_vm(vm, (local, external))?;
+        // An empty predicate resolves to false.
+        if self.inner.is_empty() {
+            let constant = vm.add_constant(Value::Boolean(false));
+            vm.write_opcode(OpCode::Constant);
+            vm.write_primitive(constant);
         }
+
+        let mut expressions = self.inner.iter().peekable();
+
+        while let Some(expr) = expressions.next() {
+            expr.compile_to_vm(vm, (local, external))?;
+
+            // Only the value of the last expression is kept.
+            if expressions.peek().is_some() {
+                vm.write_opcode(OpCode::Pop);
+            }
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,64 @@
+use std::fmt;
+
+use crate::{
+    coverage::Coverage,
+    expression::{Expr, Resolved},
+    state::{ExternalEnv, LocalEnv},
+    vm::{OpCode, Vm},
+    Context, Expression, Span, TypeDef,
+};
+
+/// Records to a [`Coverage`] that the inner expression was reached, before
+/// resolving it.
+///
+/// Probes are only added by the compiler when coverage is requested, see
+/// [`crate::compile_with_coverage`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    coverage: Coverage,
+    id: usize,
+    inner: Box<Expr>,
+}
+
+impl Probe {
+    pub(crate) fn new(coverage: &Coverage, span: Span, inner: Expr) -> Self {
+        Self {
+            coverage: coverage.clone(),
+            id: coverage.register(span),
+            inner: Box::new(inner),
+        }
+    }
+
+    pub fn inner(&self) -> &Expr {
+        &self.inner
+    }
+}
+
+impl Expression for Probe {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        self.coverage.hit(self.id);
+        self.inner.resolve(ctx)
+    }
+
+    fn type_def(&self, state: (&LocalEnv, &ExternalEnv)) -> TypeDef {
+        self.inner.type_def(state)
+    }
+
+    fn compile_to_vm(
+        &self,
+        vm: &mut Vm,
+        state: (&mut LocalEnv, &mut ExternalEnv),
+    ) -> Result<(), String> {
+        let probe = vm.add_probe(self.coverage.clone(), self.id);
+        vm.write_opcode(OpCode::Probe);
+        vm.write_primitive(probe);
+
+        self.inner.compile_to_vm(vm, state)
+    }
+}
+
+impl fmt::Display for Probe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
@@ -0,0 +1,638 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use ::value::Value;
+use diagnostic::{DiagnosticMessage, Label, Note, Urls};
+
+use crate::{
+    expression::{Block, ExpressionError, FunctionArgument},
+    parser::{ast, Ident, Node},
+    state::{ExternalEnv, LocalEnv},
+    type_def::Details,
+    value::{self, Kind},
+    vm::{OpCode, Vm},
+    Context, Expression, Resolved, Span, TypeDef,
+};
+
+/// A function defined in a VRL program (or in a module it imports), using the
+/// `fn name(parameter: type) -> type { ... }` syntax.
+///
+/// The body of the function is compiled once, and resolved for every call,
+/// with only the parameters of the function in scope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserFunction {
+    ident: Ident,
+    parameters: Vec<Parameter>,
+    body: Block,
+    type_def: TypeDef,
+
+    /// The type of the external target once the function returns, if the
+    /// function assigns to it.
+    target: Option<Details>,
+}
+
+impl UserFunction {
+    pub(crate) fn new(
+        ident: Ident,
+        parameters: Vec<Parameter>,
+        body: Block,
+        type_def: TypeDef,
+        target: Option<Details>,
+    ) -> Self {
+        Self {
+            ident,
+            parameters,
+            body,
+            type_def,
+            target,
+        }
+    }
+
+    pub fn ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    pub(crate) fn target(&self) -> Option<&Details> {
+        self.target.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Parameter {
+    pub(crate) ident: Ident,
+    pub(crate) kind: Kind,
+}
+
+/// Resolves a type annotation (e.g. `string | null`) to the [`Kind`] it
+/// represents.
+pub(crate) fn annotation_kind(annotation: Node<ast::TypeAnnotation>) -> Result<Kind, Error> {
+    let mut kind = Kind::empty();
+
+    for name in annotation.into_inner().0 {
+        let (type_span, name) = name.take();
+
+        match name.as_ref() {
+            "any" => kind = Kind::any(),
+            "string" => {
+                kind.add_bytes();
+            }
+            "int" | "integer" => {
+                kind.add_integer();
+            }
+            "float" => {
+                kind.add_float();
+            }
+            "bool" | "boolean" => {
+                kind.add_boolean();
+            }
+            "timestamp" => {
+                kind.add_timestamp();
+            }
+            "regex" => {
+                kind.add_regex();
+            }
+            "null" => {
+                kind.add_null();
+            }
+            "array" => {
+                kind.add_array(value::Collection::any());
+            }
+            "object" => {
+                kind.add_object(value::Collection::any());
+            }
+            _ => return Err(Error::UnknownType { type_span, name }),
+        }
+    }
+
+    Ok(kind)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserFunctionCall {
+    abort_on_error: bool,
+    maybe_fallible_arguments: bool,
+    span: Span,
+
+    /// The arguments of the call, in the order of the function parameters.
+    arguments: Vec<FunctionArgument>,
+    function: Arc<UserFunction>,
+}
+
+impl UserFunctionCall {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        call_span: Span,
+        ident_span: Span,
+        abort_on_error: bool,
+        arguments: Vec<Node<FunctionArgument>>,
+        closure_span: Option<Span>,
+        function: Arc<UserFunction>,
+        (local, external): (&LocalEnv, &ExternalEnv),
+    ) -> Result<Self, Error> {
+        if let Some(closure_span) = closure_span {
+            return Err(Error::UnexpectedClosure {
+                call_span,
+                closure_span,
+            });
+        }
+
+        let parameters = &function.parameters;
+
+        if arguments.len() > parameters.len() {
+            let start = arguments.first().unwrap().span().start();
+            let end = arguments.last().unwrap().span().end();
+
+            return Err(Error::WrongNumberOfArgs {
+                arguments_span: Span::new(start, end),
+                max: parameters.len(),
+            });
+        }
+
+        // Positional arguments fill the parameters that weren't given by
+        // keyword, in the order they are defined in.
+        let mut ordered = vec![None; parameters.len()];
+        let mut positional = vec![];
+
+        for node in arguments {
+            let (argument_span, argument) = node.take();
+
+            match argument.keyword() {
+                None => positional.push((argument_span, argument)),
+                Some(keyword) => {
+                    let position = parameters
+                        .iter()
+                        .position(|parameter| parameter.ident.as_ref() == keyword)
+                        .ok_or_else(|| Error::UnknownKeyword {
+                            keyword_span: argument.keyword_span().expect("exists"),
+                            ident_span,
+                            keywords: parameters.iter().map(|p| p.ident.clone()).collect(),
+                        })?;
+
+                    ordered[position] = Some((argument_span, argument));
+                }
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        for slot in ordered.iter_mut().filter(|slot| slot.is_none()) {
+            match positional.next() {
+                Some(argument) => *slot = Some(argument),
+                None => break,
+            }
+        }
+
+        let mut maybe_fallible_arguments = false;
+        let mut arguments = Vec::with_capacity(parameters.len());
+
+        for (position, (parameter, argument)) in parameters.iter().zip(ordered).enumerate() {
+            let (argument_span, argument) = argument.ok_or_else(|| Error::MissingArgument {
+                call_span,
+                keyword: parameter.ident.clone(),
+                position,
+            })?;
+
+            let type_def = argument.type_def((local, external));
+
+            if !parameter.kind.intersects(type_def.kind()) {
+                return Err(Error::InvalidArgumentKind {
+                    keyword: parameter.ident.clone(),
+                    expected: parameter.kind.clone(),
+                    got: type_def.kind().clone(),
+                    expr_span: argument.span(),
+                    argument_span,
+                });
+            } else if !parameter.kind.is_superset(type_def.kind()) {
+                maybe_fallible_arguments = true;
+            }
+
+            if type_def.is_fallible() {
+                return Err(Error::FallibleArgument {
+                    expr_span: argument.span(),
+                });
+            }
+
+            arguments.push(argument);
+        }
+
+        if abort_on_error && !maybe_fallible_arguments && !function.type_def.is_fallible() {
+            return Err(Error::AbortInfallible {
+                ident_span,
+                abort_span: Span::new(ident_span.end(), ident_span.end() + 1),
+            });
+        }
+
+        Ok(Self {
+            abort_on_error,
+            maybe_fallible_arguments,
+            span: call_span,
+            arguments,
+            function,
+        })
+    }
+
+    pub fn ident(&self) -> &Ident {
+        &self.function.ident
+    }
+
+    pub(crate) fn function(&self) -> &UserFunction {
+        &self.function
+    }
+
+    pub(crate) fn arguments_len(&self) -> usize {
+        self.arguments.len()
+    }
+
+    /// Calls the function with the resolved values of its arguments, using
+    /// `body` to resolve the body of the function.
+    ///
+    /// This is shared by both runtimes, which only differ in how they resolve
+    /// the body.
+    pub(crate) fn call(
+        &self,
+        ctx: &mut Context,
+        arguments: Vec<Value>,
+        body: impl FnOnce(&mut Context) -> Resolved,
+    ) -> Resolved {
+        let mut variables = HashMap::with_capacity(arguments.len());
+
+        for (parameter, value) in self.function.parameters.iter().zip(arguments) {
+            if self.maybe_fallible_arguments && !parameter.kind.is_superset(&value.kind()) {
+                let err = value::Error::Expected {
+                    got: value.kind(),
+                    expected: parameter.kind.clone(),
+                };
+
+                return Err(self.error(err.into()));
+            }
+
+            variables.insert(parameter.ident.clone(), value);
+        }
+
+        // The body only has access to the parameters of the function, the
+        // variables of the caller are put back once it returns.
+        let variables = ctx.state_mut().swap_variables(variables);
+        let resolved = body(ctx);
+        ctx.state_mut().swap_variables(variables);
+
+        resolved.map_err(|err| self.error(err))
+    }
+
+    fn error(&self, err: ExpressionError) -> ExpressionError {
+        match err {
+            #[cfg(feature = "expr-abort")]
+            err @ ExpressionError::Abort { .. } => err,
+            ExpressionError::Error {
+                message,
+                mut labels,
+                notes,
+            } => {
+                labels.push(Label::primary(message.clone(), self.span));
+
+                ExpressionError::Error {
+                    message: format!(
+                        r#"function call error for "{}" at ({}:{}): {}"#,
+                        self.function.ident,
+                        self.span.start(),
+                        self.span.end(),
+                        message
+                    ),
+                    labels,
+                    notes,
+                }
+            }
+        }
+    }
+}
+
+impl Expression for UserFunctionCall {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|argument| argument.resolve(ctx))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| self.error(err))?;
+
+        self.call(ctx, arguments, |ctx| self.function.body.resolve(ctx))
+    }
+
+    fn type_def(&self, _: (&LocalEnv, &ExternalEnv)) -> TypeDef {
+        let mut type_def = self.function.type_def.clone();
+
+        if self.maybe_fallible_arguments {
+            type_def = type_def.with_fallibility(true);
+        }
+
+        if self.abort_on_error {
+            type_def = type_def.with_fallibility(false);
+        }
+
+        type_def
+    }
+
+    fn compile_to_vm(
+        &self,
+        vm: &mut Vm,
+        (local, external): (&mut LocalEnv, &mut ExternalEnv),
+    ) -> Result<(), String> {
+        // The arguments are left on the stack, in the order of the function
+        // parameters.
+        for argument in &self.arguments {
+            argument.expr().compile_to_vm(vm, (local, external))?;
+        }
+
+        // The body is compiled to its own VM, as it runs with only the
+        // parameters of the function in scope.
+        let mut body = Vm::new(vm.functions());
+        self.function
+            .body
+            .compile_to_vm(&mut body, (local, external))?;
+        body.write_opcode(OpCode::Return);
+
+        let call = vm.write_user_function_call(self.clone(), body);
+        vm.write_opcode(OpCode::CallUserFunction);
+        vm.write_primitive(call);
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for UserFunctionCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.function.ident.fmt(f)?;
+
+        if self.abort_on_error {
+            f.write_str("!")?;
+        }
+
+        f.write_str("(")?;
+
+        let mut iter = self.arguments.iter().peekable();
+        while let Some(arg) = iter.next() {
+            arg.fmt(f)?;
+
+            if iter.peek().is_some() {
+                f.write_str(", ")?;
+            }
+        }
+
+        f.write_str(")")
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    #[error("function already defined")]
+    AlreadyDefined {
+        ident_span: Span,
+        ident: Ident,
+        builtin: bool,
+    },
+
+    #[error("duplicate function parameter")]
+    DuplicateParameter { parameter_span: Span, ident: Ident },
+
+    #[error("unknown type")]
+    UnknownType { type_span: Span, name: Ident },
+
+    #[error("type mismatch in function return type")]
+    ReturnTypeMismatch {
+        block_span: Span,
+        return_span: Span,
+        found_kind: Kind,
+        expected_kind: Kind,
+    },
+
+    #[error("wrong number of function arguments")]
+    WrongNumberOfArgs { arguments_span: Span, max: usize },
+
+    #[error("unknown function argument keyword")]
+    UnknownKeyword {
+        keyword_span: Span,
+        ident_span: Span,
+        keywords: Vec<Ident>,
+    },
+
+    #[error("missing function argument")]
+    MissingArgument {
+        call_span: Span,
+        keyword: Ident,
+        position: usize,
+    },
+
+    #[error("unexpected closure")]
+    UnexpectedClosure { call_span: Span, closure_span: Span },
+
+    #[error("invalid argument type")]
+    InvalidArgumentKind {
+        keyword: Ident,
+        expected: Kind,
+        got: Kind,
+        expr_span: Span,
+        argument_span: Span,
+    },
+
+    #[error("can't abort infallible function")]
+    AbortInfallible { ident_span: Span, abort_span: Span },
+
+    #[error("fallible argument")]
+    FallibleArgument { expr_span: Span },
+}
+
+impl DiagnosticMessage for Error {
+    fn code(&self) -> usize {
+        use Error::*;
+
+        match self {
+            AlreadyDefined { .. } => 130,
+            DuplicateParameter { .. } => 131,
+            UnknownType { .. } => 132,
+            ReturnTypeMismatch { .. } => 133,
+            WrongNumberOfArgs { .. } => 106,
+            MissingArgument { .. } => 107,
+            UnknownKeyword { .. } => 108,
+            UnexpectedClosure { .. } => 109,
+            InvalidArgumentKind { .. } => 110,
+            AbortInfallible { .. } => 620,
+            FallibleArgument { .. } => 630,
+        }
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        use Error::*;
+
+        let kind_str = |kind: &Kind| {
+            if kind.is_any() {
+                kind.to_string()
+            } else if kind.is_exact() {
+                format!(r#"the exact type {}"#, kind)
+            } else {
+                format!("one of {}", kind)
+            }
+        };
+
+        match self {
+            AlreadyDefined {
+                ident_span,
+                ident,
+                builtin,
+            } => {
+                let context = if *builtin {
+                    format!(r#""{}" is a function of the standard library"#, ident)
+                } else {
+                    format!(r#""{}" is already defined earlier in the program"#, ident)
+                };
+
+                vec![
+                    Label::primary("function already defined", ident_span),
+                    Label::context(context, ident_span),
+                ]
+            }
+
+            DuplicateParameter {
+                parameter_span,
+                ident,
+            } => vec![
+                Label::primary("duplicate parameter", parameter_span),
+                Label::context(
+                    format!(r#"the parameter "{}" is already defined"#, ident),
+                    parameter_span,
+                ),
+            ],
+
+            UnknownType { type_span, name } => vec![
+                Label::primary(format!(r#"unknown type "{}""#, name), type_span),
+                Label::context(
+                    "expected one of: any, string, integer, float, boolean, timestamp, regex, null, array, object",
+                    type_span,
+                ),
+            ],
+
+            ReturnTypeMismatch {
+                block_span,
+                return_span,
+                found_kind,
+                expected_kind,
+            } => vec![
+                Label::primary(
+                    format!(
+                        "this function body resolves to {}",
+                        kind_str(found_kind)
+                    ),
+                    block_span,
+                ),
+                Label::context(
+                    format!("but the function returns {}", kind_str(expected_kind)),
+                    return_span,
+                ),
+            ],
+
+            WrongNumberOfArgs {
+                arguments_span,
+                max,
+            } => {
+                let arg = if *max == 1 { "argument" } else { "arguments" };
+
+                vec![
+                    Label::primary("too many function arguments", arguments_span),
+                    Label::context(
+                        format!("this function takes a maximum of {} {}", max, arg),
+                        arguments_span,
+                    ),
+                ]
+            }
+
+            UnknownKeyword {
+                keyword_span,
+                ident_span,
+                keywords,
+            } => vec![
+                Label::primary("unknown keyword", keyword_span),
+                Label::context(
+                    format!(
+                        "this function accepts the following keywords: {}",
+                        keywords
+                            .iter()
+                            .map(|k| format!(r#""{}""#, k))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    ident_span,
+                ),
+            ],
+
+            MissingArgument {
+                call_span,
+                keyword,
+                position,
+            } => vec![Label::primary(
+                format!(
+                    r#"required argument missing: "{}" (position {})"#,
+                    keyword, position
+                ),
+                call_span,
+            )],
+
+            UnexpectedClosure {
+                call_span,
+                closure_span,
+            } => vec![
+                Label::primary("unexpected closure", closure_span),
+                Label::context("this function does not accept a closure", call_span),
+            ],
+
+            InvalidArgumentKind {
+                keyword,
+                expected,
+                got,
+                expr_span,
+                argument_span,
+            } => vec![
+                Label::primary(
+                    format!("this expression resolves to {}", kind_str(got)),
+                    expr_span,
+                ),
+                Label::context(
+                    format!(
+                        r#"but the parameter "{}" expects {}"#,
+                        keyword,
+                        kind_str(expected)
+                    ),
+                    argument_span,
+                ),
+            ],
+
+            AbortInfallible {
+                ident_span,
+                abort_span,
+            } => vec![
+                Label::primary("this function can't fail", ident_span),
+                Label::context("remove this abort-instruction", abort_span),
+            ],
+
+            FallibleArgument { expr_span } => vec![
+                Label::primary("this expression can fail", expr_span),
+                Label::context(
+                    "handle the error before passing it in as an argument",
+                    expr_span,
+                ),
+            ],
+        }
+    }
+
+    fn notes(&self) -> Vec<Note> {
+        use Error::*;
+
+        match self {
+            WrongNumberOfArgs { .. } => vec![Note::SeeDocs(
+                "function arguments".to_owned(),
+                Urls::expression_docs_url("#arguments"),
+            )],
+            InvalidArgumentKind { .. } | AbortInfallible { .. } | FallibleArgument { .. } => {
+                vec![Note::SeeErrorDocs]
+            }
+            _ => vec![],
+        }
+    }
+}
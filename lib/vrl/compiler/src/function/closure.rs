@@ -93,6 +93,17 @@ pub enum VariableKind {
     /// a `Value::bytes()`, if it's known to be exactly an array, it is
     /// a `Value::integer()`, otherwise it is one of the two.
     TargetInnerKey,
+
+    /// The variable holds the value returned by the previous run of the
+    /// closure, starting with the argument of the function parameter with the
+    /// given keyword.
+    ///
+    /// For example, in `reduce`, the first variable is the value accumulated
+    /// so far, starting with the `initial` argument.
+    ///
+    /// The variable [`Kind`] is the kind of that argument, merged with the
+    /// kind returned by the closure block.
+    Accumulator(&'static str),
 }
 
 /// The output type required by the closure block.
@@ -166,23 +177,7 @@ where
         key: &str,
         value: &Value,
     ) -> Result<(), ExpressionError> {
-        // TODO: we need to allow `LocalEnv` to take a muable reference to
-        // values, instead of owning them.
-        let cloned_key = key.to_owned();
-        let cloned_value = value.clone();
-
-        let key_ident = self.ident(0);
-        let value_ident = self.ident(1);
-
-        let old_key = insert(ctx.state_mut(), key_ident, cloned_key.into());
-        let old_value = insert(ctx.state_mut(), value_ident, cloned_value);
-
-        (self.runner)(ctx)?;
-
-        cleanup(ctx.state_mut(), key_ident, old_key);
-        cleanup(ctx.state_mut(), value_ident, old_value);
-
-        Ok(())
+        self.resolve_key_value(ctx, key, value).map(|_| ())
     }
 
     /// Run the closure to completion, given the provided index/value pair, and
@@ -197,22 +192,63 @@ where
         index: usize,
         value: &Value,
     ) -> Result<(), ExpressionError> {
+        self.resolve_index_value(ctx, index, value).map(|_| ())
+    }
+
+    /// Run the closure to completion, given the provided key/value pair, and
+    /// the runtime context, returning the value the closure resolves to.
+    ///
+    /// The provided values are *NOT* mutated during the run.
+    pub fn resolve_key_value(
+        &self,
+        ctx: &mut Context,
+        key: &str,
+        value: &Value,
+    ) -> Result<Value, ExpressionError> {
         // TODO: we need to allow `LocalEnv` to take a muable reference to
         // values, instead of owning them.
-        let cloned_value = value.clone();
-
-        let index_ident = self.ident(0);
-        let value_ident = self.ident(1);
-
-        let old_index = insert(ctx.state_mut(), index_ident, index.into());
-        let old_value = insert(ctx.state_mut(), value_ident, cloned_value);
+        self.resolve(ctx, vec![key.to_owned().into(), value.clone()])
+    }
 
-        (self.runner)(ctx)?;
+    /// Run the closure to completion, given the provided index/value pair, and
+    /// the runtime context, returning the value the closure resolves to.
+    ///
+    /// The provided values are *NOT* mutated during the run.
+    pub fn resolve_index_value(
+        &self,
+        ctx: &mut Context,
+        index: usize,
+        value: &Value,
+    ) -> Result<Value, ExpressionError> {
+        // TODO: we need to allow `LocalEnv` to take a muable reference to
+        // values, instead of owning them.
+        self.resolve(ctx, vec![index.into(), value.clone()])
+    }
 
-        cleanup(ctx.state_mut(), index_ident, old_index);
-        cleanup(ctx.state_mut(), value_ident, old_value);
+    /// Run the closure to completion, given the value accumulated so far, the
+    /// provided key/value pair, and the runtime context, returning the new
+    /// accumulated value the closure resolves to.
+    pub fn reduce_key_value(
+        &self,
+        ctx: &mut Context,
+        accumulator: Value,
+        key: &str,
+        value: &Value,
+    ) -> Result<Value, ExpressionError> {
+        self.resolve(ctx, vec![accumulator, key.to_owned().into(), value.clone()])
+    }
 
-        Ok(())
+    /// Run the closure to completion, given the value accumulated so far, the
+    /// provided index/value pair, and the runtime context, returning the new
+    /// accumulated value the closure resolves to.
+    pub fn reduce_index_value(
+        &self,
+        ctx: &mut Context,
+        accumulator: Value,
+        index: usize,
+        value: &Value,
+    ) -> Result<Value, ExpressionError> {
+        self.resolve(ctx, vec![accumulator, index.into(), value.clone()])
     }
 
     /// Run the closure to completion, given the provided key, and the runtime
@@ -257,6 +293,28 @@ where
         Ok(())
     }
 
+    /// Run the closure with its variables set to `values`, in order, and
+    /// restore the variables they shadow afterwards.
+    fn resolve(&self, ctx: &mut Context, values: Vec<Value>) -> Result<Value, ExpressionError> {
+        let idents = (0..values.len())
+            .map(|index| self.ident(index))
+            .collect::<Vec<_>>();
+
+        let old_values = idents
+            .iter()
+            .zip(values)
+            .map(|(ident, value)| insert(ctx.state_mut(), *ident, value))
+            .collect::<Vec<_>>();
+
+        let result = (self.runner)(ctx);
+
+        for (ident, old_value) in idents.into_iter().zip(old_values).rev() {
+            cleanup(ctx.state_mut(), ident, old_value);
+        }
+
+        result
+    }
+
     fn ident(&self, index: usize) -> Option<&Ident> {
         self.variables
             .get(index)
@@ -7,9 +7,12 @@
 
 mod compiler;
 mod context;
+#[cfg(feature = "expr-function_call")]
+mod module;
 mod program;
 mod test_util;
 
+pub mod coverage;
 pub mod expression;
 pub mod function;
 pub mod state;
@@ -18,7 +21,7 @@ pub mod value;
 pub mod vm;
 
 pub use core::{value, ExpressionError, Resolved, Target};
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, path::PathBuf, str::FromStr};
 
 use ::serde::{Deserialize, Serialize};
 pub use context::Context;
@@ -104,6 +107,45 @@ pub fn compile_with_state(
     compiler::Compiler::new(fns).compile(ast, state)
 }
 
+/// Similar to [`compile_with_state`], except that modules imported by the
+/// program are searched for in the given directories, in order.
+pub fn compile_with_search_path(
+    ast: parser::Program,
+    fns: &[Box<dyn Function>],
+    state: &mut ExternalEnv,
+    search_path: &[PathBuf],
+) -> Result {
+    let compiler = compiler::Compiler::new(fns);
+
+    #[cfg(feature = "expr-function_call")]
+    let compiler = compiler.with_search_path(search_path);
+    #[cfg(not(feature = "expr-function_call"))]
+    let _ = search_path;
+
+    compiler.compile(ast, state)
+}
+
+/// Similar to [`compile_with_search_path`], except that the statements of the
+/// program reached at runtime are recorded to the given [`Coverage`].
+///
+/// [`Coverage`]: coverage::Coverage
+pub fn compile_with_coverage(
+    ast: parser::Program,
+    fns: &[Box<dyn Function>],
+    state: &mut ExternalEnv,
+    search_path: &[PathBuf],
+    coverage: &coverage::Coverage,
+) -> Result {
+    let compiler = compiler::Compiler::new(fns).with_coverage(coverage);
+
+    #[cfg(feature = "expr-function_call")]
+    let compiler = compiler.with_search_path(search_path);
+    #[cfg(not(feature = "expr-function_call"))]
+    let _ = search_path;
+
+    compiler.compile(ast, state)
+}
+
 /// re-export of commonly used parser types.
 pub(crate) mod parser {
     pub(crate) use ::parser::{
@@ -0,0 +1,235 @@
+//! Modules are VRL files containing function definitions, which a program can
+//! bring into scope using `import "path/to/module.vrl"`.
+//!
+//! Module paths are resolved against a search path configured by the caller of
+//! the compiler.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use diagnostic::{DiagnosticMessage, Label, Note, Severity};
+
+use crate::Span;
+
+/// The modules known to the compiler, shared by the program and all the
+/// modules it imports.
+#[derive(Debug, Default)]
+pub(crate) struct Modules {
+    search_path: Vec<PathBuf>,
+
+    /// The modules imported so far, which are not compiled again when imported
+    /// a second time.
+    imported: HashSet<PathBuf>,
+
+    /// The chain of modules currently being compiled, to detect import cycles.
+    loading: Vec<PathBuf>,
+}
+
+impl Modules {
+    pub(crate) fn new(search_path: &[PathBuf]) -> Self {
+        Self {
+            search_path: search_path.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn search_path(&self) -> &[PathBuf] {
+        &self.search_path
+    }
+
+    /// Returns the canonical path of the first module matching `path` in the
+    /// search path.
+    pub(crate) fn find(&self, path: &str) -> Option<PathBuf> {
+        let path = Path::new(path);
+
+        if path.is_absolute() {
+            return path.canonicalize().ok();
+        }
+
+        self.search_path
+            .iter()
+            .map(|directory| directory.join(path))
+            .find(|candidate| candidate.is_file())
+            .and_then(|candidate| candidate.canonicalize().ok())
+    }
+
+    /// Marks the module as being compiled, returning the import cycle if the
+    /// module is already being compiled.
+    pub(crate) fn enter(&mut self, path: &Path) -> Result<(), Vec<PathBuf>> {
+        if let Some(position) = self.loading.iter().position(|loading| loading == path) {
+            let mut cycle = self.loading[position..].to_vec();
+            cycle.push(path.to_owned());
+
+            return Err(cycle);
+        }
+
+        self.loading.push(path.to_owned());
+        Ok(())
+    }
+
+    pub(crate) fn exit(&mut self) {
+        self.loading.pop();
+    }
+
+    /// Returns `false` if the module has already been imported.
+    pub(crate) fn insert(&mut self, path: PathBuf) -> bool {
+        self.imported.insert(path)
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    #[error("module not found")]
+    NotFound {
+        path_span: Span,
+        path: String,
+        search_path: Vec<PathBuf>,
+    },
+
+    #[error("unable to read module")]
+    Read {
+        path_span: Span,
+        path: PathBuf,
+        error: std::io::Error,
+    },
+
+    #[error("import cycle")]
+    Cycle {
+        path_span: Span,
+        cycle: Vec<PathBuf>,
+    },
+
+    #[error("error in imported module")]
+    Module {
+        path_span: Span,
+        path: PathBuf,
+        diagnostics: String,
+        severity: Severity,
+    },
+
+    #[error("unexpected expression in module")]
+    UnexpectedExpression { span: Span },
+}
+
+impl DiagnosticMessage for Error {
+    fn code(&self) -> usize {
+        use Error::*;
+
+        match self {
+            NotFound { .. } => 140,
+            Read { .. } => 141,
+            Cycle { .. } => 142,
+            Module { .. } => 143,
+            UnexpectedExpression { .. } => 144,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Error::Module { severity, .. } if severity.is_warning() => {
+                "warning in imported module".to_owned()
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        use Error::*;
+
+        match self {
+            NotFound {
+                path_span,
+                path,
+                search_path,
+            } => {
+                let context = if search_path.is_empty() {
+                    "no import paths are configured".to_owned()
+                } else {
+                    format!(
+                        "searched in: {}",
+                        search_path
+                            .iter()
+                            .map(|path| path.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                };
+
+                vec![
+                    Label::primary(format!(r#"module "{}" not found"#, path), path_span),
+                    Label::context(context, path_span),
+                ]
+            }
+
+            Read {
+                path_span,
+                path,
+                error,
+            } => vec![Label::primary(
+                format!("unable to read {}: {}", path.display(), error),
+                path_span,
+            )],
+
+            Cycle { path_span, cycle } => vec![
+                Label::primary("this module imports itself", path_span),
+                Label::context(
+                    format!(
+                        "import chain: {}",
+                        cycle
+                            .iter()
+                            .map(|path| path.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(" -> ")
+                    ),
+                    path_span,
+                ),
+            ],
+
+            Module {
+                path_span,
+                path,
+                severity,
+                ..
+            } => {
+                let problem = if severity.is_warning() {
+                    "warnings"
+                } else {
+                    "errors"
+                };
+
+                vec![Label::primary(
+                    format!("{} has {}", path.display(), problem),
+                    path_span,
+                )]
+            }
+
+            UnexpectedExpression { span } => vec![
+                Label::primary("unexpected expression", span),
+                Label::context(
+                    "modules can only contain function definitions and imports",
+                    span,
+                ),
+            ],
+        }
+    }
+
+    fn notes(&self) -> Vec<Note> {
+        use Error::*;
+
+        match self {
+            Module { diagnostics, .. } => vec![Note::Basic(diagnostics.trim().to_owned())],
+            _ => vec![],
+        }
+    }
+
+    fn severity(&self) -> Severity {
+        match self {
+            Error::Module { severity, .. } => *severity,
+            _ => Severity::Error,
+        }
+    }
+}
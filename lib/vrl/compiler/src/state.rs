@@ -3,7 +3,7 @@ use std::collections::{hash_map::Entry, HashMap};
 use anymap::AnyMap;
 use value::{Kind, Value};
 
-use crate::{parser::ast::Ident, type_def::Details};
+use crate::{parser::ast::Ident, type_def::Details, TypeDef};
 
 /// Local environment, limited to a given scope.
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -20,6 +20,12 @@ impl LocalEnv {
         self.bindings.get(ident)
     }
 
+    /// Get the type definition of a variable, if it is defined in this
+    /// environment.
+    pub fn variable_type_def(&self, ident: &Ident) -> Option<&TypeDef> {
+        self.variable(ident).map(|details| &details.type_def)
+    }
+
     #[cfg(any(feature = "expr-assignment", feature = "expr-function_call"))]
     pub(crate) fn insert_variable(&mut self, ident: Ident, details: Details) {
         self.bindings.insert(ident, details);
@@ -82,11 +88,21 @@ impl ExternalEnv {
         self.target().map(|details| details.type_def.kind())
     }
 
-    #[cfg(any(feature = "expr-assignment", feature = "expr-query"))]
+    #[cfg(any(
+        feature = "expr-assignment",
+        feature = "expr-query",
+        feature = "expr-function_call"
+    ))]
     pub(crate) fn update_target(&mut self, details: Details) {
         self.target = Some(details);
     }
 
+    /// Swap the existing target with a new one, returning the old one.
+    #[cfg(feature = "expr-function_call")]
+    pub(crate) fn swap_target(&mut self, target: Option<Details>) -> Option<Details> {
+        std::mem::replace(&mut self.target, target)
+    }
+
     /// Sets the external context data for VRL functions to use.
     pub fn set_external_context<T: 'static>(&mut self, data: T) {
         self.custom.insert::<T>(data);
@@ -132,6 +148,15 @@ impl Runtime {
         self.variables.remove(ident);
     }
 
+    /// Swap all existing variables with new ones, returning the old ones.
+    #[cfg(feature = "expr-function_call")]
+    pub(crate) fn swap_variables(
+        &mut self,
+        variables: HashMap<Ident, Value>,
+    ) -> HashMap<Ident, Value> {
+        std::mem::replace(&mut self.variables, variables)
+    }
+
     pub(crate) fn swap_variable(&mut self, ident: Ident, value: Value) -> Option<Value> {
         match self.variables.entry(ident) {
             Entry::Occupied(mut v) => Some(std::mem::replace(v.get_mut(), value)),
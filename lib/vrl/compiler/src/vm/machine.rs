@@ -4,10 +4,14 @@ use value::Value;
 
 use super::VmFunctionClosure;
 use super::{state::VmState, Variable};
+#[cfg(feature = "expr-function_call")]
+use crate::expression::UserFunctionCall;
 #[cfg(feature = "expr-op")]
 use crate::value::VrlValueArithmetic;
 use crate::value::VrlValueConvert;
-use crate::{vm::argument_list::VmArgument, Context, ExpressionError, Function};
+use crate::{
+    coverage::Coverage, vm::argument_list::VmArgument, Context, ExpressionError, Function,
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum OpCode {
@@ -100,13 +104,15 @@ pub enum OpCode {
     /// The value at the top of the stack is swapped for False.
     JumpAndSwapIfFalsey,
 
-    /// If the error field of the VM is not set advances the instruction pointer by the amount
+    /// Pushes an error handler onto the handler stack. Should an error be raised before the
+    /// handler is popped, the stacks are unwound to the state they were in when the handler was
+    /// pushed, the error field is set and the instruction pointer is advanced to the position
     /// set by the ensuing primitive instruction.
-    JumpIfNotErr,
+    PushHandler,
 
-    /// If the error field of the VM is set advances the instruction pointer by the amount
-    /// set by the ensuing primitive instruction.
-    JumpIfErr,
+    /// Pops the error handler at the top of the handler stack, once the expression it guards has
+    /// run without raising an error.
+    PopHandler,
 
     /// Unconditionally advances the instruction pointer by the amount set by the ensuing
     /// primitive instruction.
@@ -157,9 +163,14 @@ pub enum OpCode {
     /// Moves a closure onto the closure stack.
     MoveClosure,
 
-    /// After each statement (with the exception of the last one) within a block we need to pop the
-    /// stack, and if we are in an error state jump to the end of the block.
-    EndStatement,
+    /// Calls the user-defined function indicated by the ensuing primitive. The arguments of the
+    /// function are popped from the stack.
+    #[cfg(feature = "expr-function_call")]
+    CallUserFunction,
+
+    /// Records that the expression which follows was reached, to the probe indicated by the
+    /// ensuing primitive.
+    Probe,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -174,7 +185,9 @@ pub enum Instruction {
     /// Index into targets for path get and set operations.
     /// Number of fields whilst building up arrays and objects.
     /// Index into the list of functions during a call.
+    /// Index into the list of user-defined function calls.
     /// Index into statics for static parameters.
+    /// Index into the list of coverage probes.
     Primitive(usize),
 }
 
@@ -186,6 +199,15 @@ pub struct Vm {
     targets: Vec<Variable>,
     closures: Vec<VmFunctionClosure>,
     static_params: Vec<Box<dyn std::any::Any + Send + Sync>>,
+
+    /// The user-defined function calls made by the program, each with the
+    /// body of the called function compiled to its own `Vm`.
+    #[cfg(feature = "expr-function_call")]
+    user_function_calls: Vec<(UserFunctionCall, Vm)>,
+
+    /// The coverage probes of the program, only present when the program was compiled with
+    /// coverage.
+    probes: Vec<(Coverage, usize)>,
 }
 
 impl Vm {
@@ -249,6 +271,20 @@ impl Vm {
         Arc::clone(&self.fns)
     }
 
+    /// Adds a user-defined function call, along with the compiled body of the
+    /// function, and returns its position in the list.
+    #[cfg(feature = "expr-function_call")]
+    pub fn write_user_function_call(&mut self, call: UserFunctionCall, body: Vm) -> usize {
+        self.user_function_calls.push((call, body));
+        self.user_function_calls.len() - 1
+    }
+
+    /// Adds a coverage probe and returns its position in the list.
+    pub fn add_probe(&mut self, coverage: Coverage, probe: usize) -> usize {
+        self.probes.push((coverage, probe));
+        self.probes.len() - 1
+    }
+
     /// Gets a target from the list of targets used, if it hasn't already been added then add it.
     pub fn get_target(&mut self, target: &Variable) -> usize {
         match self.targets.iter().position(|t| t == target) {
@@ -318,10 +354,10 @@ impl Vm {
                         Value::Null => None,
                         value => Some(value.try_bytes_utf8_lossy()?.to_string()),
                     };
-                    return Err(ExpressionError::Abort {
+                    state.raise(ExpressionError::Abort {
                         span: diagnostic::Span::new(start, end),
                         message,
-                    });
+                    })?;
                 }
                 OpCode::Return => {
                     // Ends the process and returns the top item from the stack - or `Null` if the stack is empty.
@@ -335,9 +371,9 @@ impl Vm {
                     state.stack.push(value);
                 }
                 #[cfg(feature = "expr-unary")]
-                OpCode::Not => match state.pop_stack()? {
-                    Value::Boolean(value) => state.stack.push(Value::Boolean(!value)),
-                    _ => return Err("Negating non boolean".into()),
+                OpCode::Not => match state.pop_stack()?.try_boolean() {
+                    Ok(value) => state.stack.push(Value::Boolean(!value)),
+                    Err(err) => state.raise(err.into())?,
                 },
                 #[cfg(feature = "expr-op")]
                 OpCode::Add => binary_op(&mut state, Value::try_add)?,
@@ -363,23 +399,15 @@ impl Vm {
                 OpCode::LessEqual => binary_op(&mut state, Value::try_le)?,
                 #[cfg(feature = "expr-op")]
                 OpCode::NotEqual => {
-                    if state.error.is_none() {
-                        let rhs = state.pop_stack()?;
-                        let lhs = state.pop_stack()?;
-                        state.stack.push((!lhs.eq_lossy(&rhs)).into());
-                    } else {
-                        state.pop_stack()?;
-                    }
+                    let rhs = state.pop_stack()?;
+                    let lhs = state.pop_stack()?;
+                    state.stack.push((!lhs.eq_lossy(&rhs)).into());
                 }
                 #[cfg(feature = "expr-op")]
                 OpCode::Equal => {
-                    if state.error.is_none() {
-                        let rhs = state.pop_stack()?;
-                        let lhs = state.pop_stack()?;
-                        state.stack.push(lhs.eq_lossy(&rhs).into());
-                    } else {
-                        state.pop_stack()?;
-                    }
+                    let rhs = state.pop_stack()?;
+                    let lhs = state.pop_stack()?;
+                    state.stack.push(lhs.eq_lossy(&rhs).into());
                 }
                 OpCode::Pop => {
                     // Removes the top item from the stack.
@@ -392,15 +420,19 @@ impl Vm {
                 OpCode::JumpIfFalse => {
                     // If the value at the top of the stack is false, jump by the given amount.
                     let jump = state.next_primitive()?;
-                    if !is_true(state.peek_stack()?)? {
-                        state.instruction_pointer += jump;
+                    match is_true(state.peek_stack()?) {
+                        Ok(false) => state.instruction_pointer += jump,
+                        Ok(true) => (),
+                        Err(err) => state.raise(err)?,
                     }
                 }
                 OpCode::JumpIfTrue => {
                     // If the value at the top of the stack is true, jump by the given amount.
                     let jump = state.next_primitive()?;
-                    if is_true(state.peek_stack()?)? {
-                        state.instruction_pointer += jump;
+                    match is_true(state.peek_stack()?) {
+                        Ok(true) => state.instruction_pointer += jump,
+                        Ok(false) => (),
+                        Err(err) => state.raise(err)?,
                     }
                 }
                 OpCode::JumpIfTruthy => {
@@ -421,29 +453,15 @@ impl Vm {
                         state.instruction_pointer += jump;
                     }
                 }
-                OpCode::JumpIfNotErr => {
-                    // If the current state is not in error, jump by the given amount.
+                OpCode::PushHandler => {
+                    // Any error raised from here on jumps by the given amount, until the handler
+                    // is popped again.
                     let jump = state.next_primitive()?;
-                    if state.error.is_none() {
-                        state.instruction_pointer += jump;
-                    }
-                }
-                OpCode::JumpIfErr => {
-                    // If the current state is in error, jump by the given amount.
-                    let jump = state.next_primitive()?;
-                    if state.error.is_some() {
-                        state.instruction_pointer += jump;
-                    }
+                    state.push_handler(jump);
                 }
-                OpCode::EndStatement => {
-                    let jump = state.next_primitive()?;
-                    if state.error.is_some() {
-                        state.instruction_pointer += jump;
-                    } else {
-                        state.pop_stack()?;
-                    }
+                OpCode::PopHandler => {
+                    state.pop_handler()?;
                 }
-
                 OpCode::Jump => {
                     // Moves the instruction pointer by the amount specified.
                     let jump = state.next_primitive()?;
@@ -503,7 +521,9 @@ impl Vm {
                         Variable::External(path) => {
                             let value = ctx
                                 .target()
-                                .target_get(path)?
+                                .target_get(path)
+                                .ok()
+                                .flatten()
                                 .cloned()
                                 .unwrap_or(Value::Null);
                             state.stack.push(value);
@@ -523,11 +543,9 @@ impl Vm {
                         }
                         Variable::None => state.stack.push(Value::Null),
                         Variable::Stack(path) => {
-                            if state.error.is_none() {
-                                let value = state.pop_stack()?;
-                                let value = value.get_by_path(path).cloned().unwrap_or(Value::Null);
-                                state.stack.push(value);
-                            }
+                            let value = state.pop_stack()?;
+                            let value = value.get_by_path(path).cloned().unwrap_or(Value::Null);
+                            state.stack.push(value);
                         }
                     }
                 }
@@ -536,13 +554,9 @@ impl Vm {
                     // The next primitive on the stack is the number of fields in the array
                     // followed by the values to be added to the array.
                     let count = state.next_primitive()?;
-                    let mut arr = Vec::new();
-
-                    for _ in 0..count {
-                        arr.push(state.pop_stack()?);
-                    }
+                    let array = state.pop_stack_many(count)?;
 
-                    state.stack.push(Value::Array(arr));
+                    state.stack.push(Value::Array(array));
                 }
                 OpCode::CreateObject => {
                     // Creates an object from the values on the stack.
@@ -592,16 +606,19 @@ impl Vm {
                     match result {
                         Ok(result) => state.stack.push(result),
                         Err(err) => match err {
+                            // An abort can only be raised from within a closure, it is passed on
+                            // unchanged.
                             #[cfg(feature = "expr-abort")]
-                            ExpressionError::Abort { .. } => {
-                                panic!("abort errors must only be defined by `abort` statement")
-                            }
+                            err @ ExpressionError::Abort { .. } => state.raise(err)?,
                             ExpressionError::Error {
                                 message,
-                                labels,
+                                mut labels,
                                 notes,
                             } => {
-                                state.error = Some(ExpressionError::Error {
+                                let span = diagnostic::Span::new(span_start, span_end);
+                                labels.push(diagnostic::Label::primary(message.clone(), span));
+
+                                state.raise(ExpressionError::Error {
                                     message: format!(
                                         r#"function call error for "{}" at ({}:{}): {}"#,
                                         function.identifier(),
@@ -611,7 +628,7 @@ impl Vm {
                                     ),
                                     labels,
                                     notes,
-                                });
+                                })?;
                             }
                         },
                     }
@@ -639,6 +656,24 @@ impl Vm {
                     let idx = state.next_primitive()?;
                     state.closure_stack.push(&self.closures[idx]);
                 }
+                #[cfg(feature = "expr-function_call")]
+                OpCode::CallUserFunction => {
+                    // Calls a user-defined function, its arguments are the values at the top of
+                    // the stack, in the order of the function parameters.
+                    let idx = state.next_primitive()?;
+                    let (call, body) = &self.user_function_calls[idx];
+                    let arguments = state.pop_stack_many(call.arguments_len())?;
+
+                    match call.call(ctx, arguments, |ctx| body.interpret(ctx)) {
+                        Ok(value) => state.stack.push(value),
+                        Err(err) => state.raise(err)?,
+                    }
+                }
+                OpCode::Probe => {
+                    let idx = state.next_primitive()?;
+                    let (coverage, probe) = &self.probes[idx];
+                    coverage.hit(*probe);
+                }
             }
         }
     }
@@ -651,21 +686,11 @@ where
     E: Into<ExpressionError>,
     F: Fn(Value, Value) -> Result<Value, E>,
 {
-    // If we are in an error state we don't want to perform the operation
-    // so we pass the error along.
-    if state.error.is_none() {
-        let rhs = state.pop_stack()?;
-        let lhs = state.pop_stack()?;
-        match fun(lhs, rhs) {
-            Ok(value) => state.stack.push(value),
-            Err(err) => state.error = Some(err.into()),
-        }
-    } else {
-        // If we are in error, we need to pop the stack to remove the lhs
-        // value from the stack.
-        // (If the lhs had errored, a Jump will have already been evoked to jump
-        // past the binary op.)
-        state.pop_stack()?;
+    let rhs = state.pop_stack()?;
+    let lhs = state.pop_stack()?;
+    match fun(lhs, rhs) {
+        Ok(value) => state.stack.push(value),
+        Err(err) => state.raise(err.into())?,
     }
 
     Ok(())
@@ -3,6 +3,17 @@ use value::Value;
 use super::{argument_list::VmArgument, machine::Instruction, OpCode, Vm, VmFunctionClosure};
 use crate::ExpressionError;
 
+/// An error handler pushed by the `PushHandler` `OpCode`.
+struct Handler {
+    /// The position of the instruction to jump to when an error is raised.
+    instruction_pointer: usize,
+    /// The heights of the stacks when the handler was pushed, the stacks are
+    /// truncated to these when an error is raised.
+    stack: usize,
+    parameter_stack: usize,
+    closure_stack: usize,
+}
+
 /// `VmState` contains the mutable state used to run the Vm.
 pub(crate) struct VmState<'a> {
     vm: &'a Vm,
@@ -16,6 +27,8 @@ pub(crate) struct VmState<'a> {
     pub(super) closure_stack: Vec<&'a VmFunctionClosure>,
     /// Errors generated by the last expression are stored here.
     pub(super) error: Option<ExpressionError>,
+    /// A stack of handlers for the errors raised whilst running the process.
+    handlers: Vec<Handler>,
 }
 
 impl<'a> VmState<'a> {
@@ -27,6 +40,7 @@ impl<'a> VmState<'a> {
             parameter_stack: Vec::new(),
             closure_stack: Vec::new(),
             error: None,
+            handlers: Vec::new(),
         }
     }
 
@@ -74,6 +88,55 @@ impl<'a> VmState<'a> {
             .ok_or_else(|| "closure stack underflow".into())
     }
 
+    /// Pops the given number of values from the top of the stack, returning them in the order
+    /// they were pushed.
+    /// Errors if the stack doesn't hold enough values.
+    pub(super) fn pop_stack_many(&mut self, count: usize) -> Result<Vec<Value>, ExpressionError> {
+        match self.stack.len().checked_sub(count) {
+            Some(len) => Ok(self.stack.split_off(len)),
+            None => Err("stack underflow".into()),
+        }
+    }
+
+    /// Pushes a handler for the errors raised from here on, which jumps by the given amount.
+    pub(super) fn push_handler(&mut self, jump: usize) {
+        self.handlers.push(Handler {
+            instruction_pointer: self.instruction_pointer + jump,
+            stack: self.stack.len(),
+            parameter_stack: self.parameter_stack.len(),
+            closure_stack: self.closure_stack.len(),
+        });
+    }
+
+    /// Pops the handler at the top of the handler stack.
+    /// Errors if the handler stack is empty.
+    pub(super) fn pop_handler(&mut self) -> Result<(), ExpressionError> {
+        self.handlers
+            .pop()
+            .map(|_| ())
+            .ok_or_else(|| "handler stack underflow".into())
+    }
+
+    /// Raises the given error.
+    ///
+    /// If a handler has been pushed, the stacks are unwound to where they were when the handler
+    /// was pushed, and the process continues at the handler with the error stored. Otherwise the
+    /// error is returned, ending the process.
+    pub(super) fn raise(&mut self, error: ExpressionError) -> Result<(), ExpressionError> {
+        let handler = match self.handlers.pop() {
+            Some(handler) => handler,
+            None => return Err(error),
+        };
+
+        self.instruction_pointer = handler.instruction_pointer;
+        self.stack.truncate(handler.stack);
+        self.parameter_stack.truncate(handler.parameter_stack);
+        self.closure_stack.truncate(handler.closure_stack);
+        self.error = Some(error);
+
+        Ok(())
+    }
+
     pub(super) fn peek_stack(&self) -> Result<&Value, ExpressionError> {
         if self.stack.is_empty() {
             return Err("peeking empty stack".into());
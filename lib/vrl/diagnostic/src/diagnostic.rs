@@ -60,6 +60,10 @@ impl Diagnostic {
         self.severity
     }
 
+    pub fn code(&self) -> usize {
+        self.code
+    }
+
     pub fn message(&self) -> &str {
         &self.message
     }
@@ -5,6 +5,7 @@ use crate::DiagnosticList;
 /// A formatter to display diagnostics tied to a given source.
 pub struct Formatter<'a> {
     source: &'a str,
+    file_name: String,
     diagnostics: DiagnosticList,
     color: bool,
 }
@@ -13,11 +14,19 @@ impl<'a> Formatter<'a> {
     pub fn new(source: &'a str, diagnostics: impl Into<DiagnosticList>) -> Self {
         Self {
             source,
+            file_name: String::new(),
             diagnostics: diagnostics.into(),
             color: false,
         }
     }
 
+    /// Name the file the source was read from, for the diagnostics to point at
+    /// (e.g. `┌─ module.vrl:3:5`).
+    pub fn with_file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = file_name.into();
+        self
+    }
+
     pub fn colored(mut self) -> Self {
         self.color = true;
         self
@@ -43,7 +52,7 @@ impl<'a> fmt::Display for Formatter<'a> {
             return Ok(());
         }
 
-        let file = SimpleFile::new("", self.source);
+        let file = SimpleFile::new(self.file_name.as_str(), self.source);
         let config = term::Config::default();
         let mut buffer = if self.color {
             Buffer::ansi()
@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Documentation, InsertTextFormat, MarkupContent, MarkupKind,
+};
+
+use crate::{
+    document::Document,
+    hover::{documentation, signature},
+    Environment,
+};
+
+/// Completes the variable, function or function parameter being typed at
+/// `offset`.
+pub(crate) fn completion(
+    environment: &Environment,
+    document: &Document,
+    offset: usize,
+) -> Vec<CompletionItem> {
+    let text = &document.text()[..offset];
+    let word = text
+        .rfind(|c: char| !is_ident(c))
+        .map_or(0, |index| index + 1);
+
+    // Paths can't be completed, as the fields of the event aren't known.
+    if text[..word].ends_with('.') {
+        return vec![];
+    }
+
+    let mut items = vec![];
+
+    if let Some(function) = enclosing_call(text).and_then(|ident| environment.function(ident)) {
+        items.extend(function.parameters().iter().map(|parameter| {
+            let kind = parameter.kind().to_string();
+
+            CompletionItem {
+                label: parameter.keyword.to_owned(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                detail: Some(match parameter.required {
+                    true => kind,
+                    false => format!("{} (optional)", kind),
+                }),
+                insert_text: Some(format!("{}: ", parameter.keyword)),
+                sort_text: Some(format!("0{}", parameter.keyword)),
+                ..Default::default()
+            }
+        }));
+    }
+
+    let symbols = document.symbols();
+    let mut variables = HashSet::new();
+    items.extend(
+        symbols
+            .visible(offset)
+            .filter(|definition| variables.insert(definition.ident.as_str()))
+            .map(|definition| CompletionItem {
+                label: definition.ident.clone(),
+                kind: Some(CompletionItemKind::VARIABLE),
+                detail: definition.detail.clone(),
+                sort_text: Some(format!("1{}", definition.ident)),
+                ..Default::default()
+            }),
+    );
+
+    items.extend(symbols.functions().map(|definition| CompletionItem {
+        label: definition.ident.clone(),
+        kind: Some(CompletionItemKind::FUNCTION),
+        detail: definition.detail.clone(),
+        insert_text: Some(format!("{}($0)", definition.ident)),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        sort_text: Some(format!("2{}", definition.ident)),
+        ..Default::default()
+    }));
+
+    items.extend(environment.functions().iter().map(|function| {
+        let ident = function.identifier();
+
+        CompletionItem {
+            label: ident.to_owned(),
+            kind: Some(CompletionItemKind::FUNCTION),
+            detail: Some(signature(function.as_ref())),
+            documentation: Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: documentation(function.as_ref()),
+            })),
+            insert_text: Some(format!("{}($0)", ident)),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            sort_text: Some(format!("2{}", ident)),
+            ..Default::default()
+        }
+    }));
+
+    items
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Returns the function whose arguments `text` ends in, if any.
+///
+/// The program being typed rarely parses, so this only looks for the opening
+/// parenthesis of the call, skipping over nested groups.
+fn enclosing_call(text: &str) -> Option<&str> {
+    let mut depth = 0_usize;
+
+    for (index, c) in text.char_indices().rev() {
+        match c {
+            ')' | ']' | '}' => depth += 1,
+            '(' | '[' | '{' if depth > 0 => depth -= 1,
+            '(' => {
+                let callee = text[..index].trim_end_matches('!');
+                let start = callee
+                    .rfind(|c: char| !is_ident(c))
+                    .map_or(0, |index| index + 1);
+                let ident = &callee[start..];
+
+                return (!ident.is_empty()).then(|| ident);
+            }
+            '[' | '{' => return None,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(source: &str) -> Vec<String> {
+        let document = Document::new(source.to_owned(), None);
+
+        completion(&Environment::new(vec![]), &document, source.len())
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+    }
+
+    #[test]
+    fn completes_functions_and_variables() {
+        let labels = labels("status = 200\n.message = s");
+
+        assert!(labels.contains(&"status".to_owned()));
+        assert!(labels.contains(&"parse_json".to_owned()));
+        assert!(!labels.contains(&"value".to_owned()));
+    }
+
+    #[test]
+    fn completes_parameters() {
+        let labels = labels(".message = parse_json!(.message, ");
+
+        assert_eq!(labels[..2], ["value".to_owned(), "max_depth".to_owned()]);
+        assert!(labels.contains(&"upcase".to_owned()));
+    }
+
+    #[test]
+    fn skips_paths() {
+        assert!(labels(".message = .mess").is_empty());
+    }
+
+    #[test]
+    fn finds_enclosing_call() {
+        assert_eq!(enclosing_call("parse_json!("), Some("parse_json"));
+        assert_eq!(enclosing_call("upcase(downcase(.a), "), Some("upcase"));
+        assert_eq!(enclosing_call("upcase([1, 2"), None);
+        assert_eq!(enclosing_call(".a = (1 + "), None);
+        assert_eq!(enclosing_call("upcase(.a)\n.b = "), None);
+    }
+}
@@ -0,0 +1,76 @@
+use parser::Span;
+
+use crate::{
+    document::Document,
+    symbols::{DefinitionKind, Reference, Symbol},
+};
+
+/// Returns where the variable or user-defined function at `offset` is
+/// defined.
+pub(crate) fn definition(document: &Document, offset: usize) -> Option<Span> {
+    let symbols = document.symbols();
+
+    let definition = match symbols.at(offset)? {
+        Symbol::Reference(Reference::Variable { ident, .. }) => symbols.variable(ident, offset)?,
+        Symbol::Reference(Reference::Function { ident, .. }) => symbols.function(ident)?,
+        Symbol::Reference(Reference::Target { .. }) => return None,
+        Symbol::Definition(definition) => match definition.kind {
+            DefinitionKind::Variable => symbols
+                .variable(&definition.ident, offset)
+                .unwrap_or(definition),
+            DefinitionKind::Parameter | DefinitionKind::Function => definition,
+        },
+    };
+
+    Some(definition.span)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn definition<'a>(source: &'a str, at: &str) -> Option<&'a str> {
+        let document = Document::new(source.to_owned(), None);
+        let offset = source.rfind(at).unwrap();
+
+        super::definition(&document, offset).map(|span| &source[span.start()..])
+    }
+
+    #[test]
+    fn goes_to_variable_assignment() {
+        let source = indoc! {r#"
+            level = downcase!(.level)
+            if level == "warn" {
+                level = "warning"
+            }
+            .level = level
+        "#};
+
+        assert!(definition(source, "level\n")
+            .unwrap()
+            .starts_with("level = downcase!"));
+        assert!(definition(source, "level = \"warning\"")
+            .unwrap()
+            .starts_with("level = downcase!"));
+        assert_eq!(definition(source, ".level ="), None);
+    }
+
+    #[test]
+    fn goes_to_function_definition() {
+        let source = indoc! {r#"
+            fn double(value: int) -> int {
+                value * 2
+            }
+            .count = double(21)
+        "#};
+
+        assert!(definition(source, "double(21)")
+            .unwrap()
+            .starts_with("double(value: int)"));
+        assert!(definition(source, "value * 2")
+            .unwrap()
+            .starts_with("value: int"));
+    }
+}
@@ -0,0 +1,116 @@
+use lsp_types::{
+    DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Range, Url,
+};
+use vrl::diagnostic::{Diagnostic, Severity};
+
+use crate::{document::Document, Environment};
+
+/// Compiles the document, returning the errors and warnings of the compiler.
+pub(crate) fn diagnostics(
+    environment: &Environment,
+    uri: &Url,
+    document: &Document,
+) -> Vec<lsp_types::Diagnostic> {
+    environment
+        .compile(document.text(), document.directory())
+        .diagnostics
+        .iter()
+        .map(|diagnostic| to_lsp(uri, document, diagnostic))
+        .collect()
+}
+
+fn to_lsp(uri: &Url, document: &Document, diagnostic: &Diagnostic) -> lsp_types::Diagnostic {
+    let labels = diagnostic.labels();
+    let primary = labels
+        .iter()
+        .find(|label| label.primary)
+        .or_else(|| labels.first());
+
+    // Editors only show the message of a diagnostic, so the primary label and
+    // the notes are added to it.
+    let mut message = diagnostic.message().to_owned();
+    if let Some(label) = primary.filter(|label| !label.message.is_empty()) {
+        message.push_str("\n\n");
+        message.push_str(&label.message);
+    }
+    for note in diagnostic.notes() {
+        message.push('\n');
+        message.push_str(&note.to_string());
+    }
+
+    let related_information = labels
+        .iter()
+        .filter(|label| !label.primary)
+        .map(|label| DiagnosticRelatedInformation {
+            location: Location::new(uri.clone(), document.range(label.span)),
+            message: label.message.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    lsp_types::Diagnostic {
+        range: primary.map_or_else(Range::default, |label| document.range(label.span)),
+        severity: Some(severity(diagnostic.severity())),
+        code: Some(NumberOrString::String(format!("E{:03}", diagnostic.code()))),
+        source: Some("vrl".to_owned()),
+        message,
+        related_information: (!related_information.is_empty()).then(|| related_information),
+        ..Default::default()
+    }
+}
+
+fn severity(severity: Severity) -> DiagnosticSeverity {
+    match severity {
+        Severity::Bug | Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Note => DiagnosticSeverity::INFORMATION,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use super::*;
+
+    fn diagnostics(source: &str) -> Vec<lsp_types::Diagnostic> {
+        let uri = Url::parse("file:///etc/vector/program.vrl").unwrap();
+        let document = Document::new(source.to_owned(), None);
+
+        super::diagnostics(&Environment::new(vec![]), &uri, &document)
+    }
+
+    #[test]
+    fn reports_compilation_errors() {
+        let diagnostics = diagnostics(".foo = 1\n.bar = parse_json(.message)");
+
+        assert_eq!(diagnostics.len(), 1);
+
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("E103".to_owned()))
+        );
+        assert_eq!(diagnostic.range.start, Position::new(1, 7));
+        assert!(diagnostic
+            .message
+            .starts_with("unhandled fallible assignment\n\nthis expression is fallible"));
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let diagnostics = diagnostics(".foo = 1\n.bar = ");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert!(matches!(
+            &diagnostics[0].code,
+            Some(NumberOrString::String(code)) if code.starts_with("E2")
+        ));
+    }
+
+    #[test]
+    fn accepts_valid_programs() {
+        assert!(diagnostics(".foo = 1\n.bar = parse_json!(.message)").is_empty());
+    }
+}
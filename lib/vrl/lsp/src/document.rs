@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+
+use lsp_types::{Position, Range};
+use parser::Span;
+
+use crate::symbols::Symbols;
+
+/// A VRL program opened in the editor.
+#[derive(Debug)]
+pub(crate) struct Document {
+    text: String,
+    lines: LineIndex,
+
+    /// The directory containing the program, if it is stored on disk.
+    directory: Option<PathBuf>,
+
+    /// The symbols of the program, kept from the last version of the program
+    /// that could be parsed.
+    symbols: Symbols,
+}
+
+impl Document {
+    pub(crate) fn new(text: String, directory: Option<PathBuf>) -> Self {
+        let lines = LineIndex::new(&text);
+        let symbols = parser::parse(&text)
+            .map(|program| Symbols::new(&program))
+            .unwrap_or_default();
+
+        Self {
+            text,
+            lines,
+            directory,
+            symbols,
+        }
+    }
+
+    /// Replaces the text of the document.
+    pub(crate) fn update(&mut self, text: String) {
+        if let Ok(program) = parser::parse(&text) {
+            self.symbols = Symbols::new(&program);
+        }
+
+        self.lines = LineIndex::new(&text);
+        self.text = text;
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub(crate) fn directory(&self) -> Option<&Path> {
+        self.directory.as_deref()
+    }
+
+    pub(crate) fn symbols(&self) -> &Symbols {
+        &self.symbols
+    }
+
+    /// Converts an editor position to a byte offset in the program.
+    pub(crate) fn offset(&self, position: Position) -> usize {
+        self.lines.offset(&self.text, position)
+    }
+
+    /// Converts a byte offset in the program to an editor position.
+    pub(crate) fn position(&self, offset: usize) -> Position {
+        self.lines.position(&self.text, offset)
+    }
+
+    pub(crate) fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start()), self.position(span.end()))
+    }
+}
+
+/// The byte offsets at which the lines of a document start.
+///
+/// Editors count characters in UTF-16 code units, as required by the
+/// language server protocol, while the VRL parser uses byte offsets.
+#[derive(Debug)]
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self { starts }
+    }
+
+    fn position(&self, text: &str, offset: usize) -> Position {
+        let offset = offset.min(text.len());
+        let line = self.starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.starts[line];
+        let character = text
+            .get(start..offset)
+            .map_or(0, |prefix| prefix.encode_utf16().count());
+
+        Position::new(line as u32, character as u32)
+    }
+
+    fn offset(&self, text: &str, position: Position) -> usize {
+        let line = position.line as usize;
+        let start = match self.starts.get(line) {
+            Some(start) => *start,
+            None => return text.len(),
+        };
+        let end = self.starts.get(line + 1).copied().unwrap_or(text.len());
+        let line = text[start..end].trim_end_matches('\n');
+
+        let mut units = 0;
+        for (index, c) in line.char_indices() {
+            if units >= position.character as usize {
+                return start + index;
+            }
+            units += c.len_utf16();
+        }
+
+        start + line.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_positions() {
+        let document = Document::new(
+            ".message = \"héllo 👋\"\n.level = \"info\"".to_owned(),
+            None,
+        );
+
+        let cases = [
+            (0, Position::new(0, 0)),
+            (11, Position::new(0, 11)),
+            (19, Position::new(0, 18)),
+            (23, Position::new(0, 20)),
+            (24, Position::new(0, 21)),
+            (25, Position::new(1, 0)),
+            (31, Position::new(1, 6)),
+        ];
+
+        for (offset, position) in cases {
+            assert_eq!(document.position(offset), position);
+            assert_eq!(document.offset(position), offset);
+        }
+
+        assert_eq!(document.offset(Position::new(0, 100)), 24);
+        assert_eq!(document.offset(Position::new(5, 0)), document.text().len());
+    }
+}
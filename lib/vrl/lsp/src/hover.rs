@@ -0,0 +1,238 @@
+use lookup::LookupBuf;
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind};
+use parser::ast::Ident;
+use vrl::{diagnostic::Urls, prelude::TypeDef, Function};
+
+use crate::{
+    document::Document,
+    symbols::{Definition, DefinitionKind, Reference, Symbol},
+    Environment,
+};
+
+pub(crate) fn hover(
+    environment: &Environment,
+    document: &Document,
+    offset: usize,
+) -> Option<Hover> {
+    let symbols = document.symbols();
+
+    let (span, contents) = match symbols.at(offset)? {
+        Symbol::Reference(Reference::Function { ident, span }) => {
+            let contents = match symbols.function(ident) {
+                Some(definition) => code(definition.detail.as_deref().unwrap_or(ident)),
+                None => documentation(environment.function(ident)?),
+            };
+
+            (*span, contents)
+        }
+        Symbol::Reference(Reference::Variable { ident, span }) => {
+            let definition = symbols.variable(ident, offset)?;
+
+            (*span, variable(environment, document, definition, offset))
+        }
+        Symbol::Reference(Reference::Target { path, span }) => {
+            let source = &document.text()[span.range()];
+
+            (*span, target(environment, document, source, path, offset)?)
+        }
+        Symbol::Definition(definition) => {
+            let contents = match definition.kind {
+                DefinitionKind::Function => {
+                    code(definition.detail.as_deref().unwrap_or(&definition.ident))
+                }
+                _ => variable(environment, document, definition, offset),
+            };
+
+            (definition.span, contents)
+        }
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: contents,
+        }),
+        range: Some(document.range(span)),
+    })
+}
+
+/// The signature of a function, with its optional parameters in brackets,
+/// e.g. `parse_json(value: string, [max_depth: integer])`.
+pub(crate) fn signature(function: &dyn Function) -> String {
+    let parameters = function
+        .parameters()
+        .iter()
+        .map(|parameter| {
+            let kind = parameter.kind().to_string();
+            let kind = kind.replace(" or ", " | ").replace(", ", " | ");
+
+            match parameter.required {
+                true => format!("{}: {}", parameter.keyword, kind),
+                false => format!("[{}: {}]", parameter.keyword, kind),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!("{}({})", function.identifier(), parameters)
+}
+
+/// The signature, summary and first example of a function, along with a link
+/// to its documentation.
+pub(crate) fn documentation(function: &dyn Function) -> String {
+    let mut documentation = code(&signature(function));
+
+    if function.summary() != "TODO" {
+        documentation.push_str("\n\n");
+        documentation.push_str(function.summary());
+    }
+
+    if let Some(example) = function.examples().first() {
+        documentation.push_str("\n\n");
+        documentation.push_str(&code(example.source));
+    }
+
+    documentation.push_str(&format!(
+        "\n\n[Documentation]({})",
+        Urls::func_docs(function.identifier())
+    ));
+
+    documentation
+}
+
+/// The type of a variable at `offset`.
+///
+/// The type of variables assigned to at the root of the program is known by
+/// compiling the program up to the expression `offset` is in. The type of
+/// parameters is the one they are annotated with.
+fn variable(
+    environment: &Environment,
+    document: &Document,
+    definition: &Definition,
+    offset: usize,
+) -> String {
+    let ident = &definition.ident;
+
+    if definition.kind == DefinitionKind::Parameter {
+        return match &definition.detail {
+            Some(kind) => code(&format!("{}: {}", ident, kind)),
+            None => code(ident),
+        };
+    }
+
+    let type_def = document.symbols().root_end(offset).and_then(|end| {
+        environment
+            .compile(&document.text()[..end], document.directory())
+            .program?
+            .local_env()
+            .variable_type_def(&Ident::new(ident.as_str()))
+            .cloned()
+    });
+
+    match type_def {
+        Some(type_def) => code(&format!("{}: {}", ident, type_def.kind())),
+        None => code(ident),
+    }
+}
+
+/// The type of a path of the event at `offset`.
+fn target(
+    environment: &Environment,
+    document: &Document,
+    source: &str,
+    path: &LookupBuf,
+    offset: usize,
+) -> Option<String> {
+    let end = document.symbols().root_end(offset)?;
+    let compilation = environment.compile(&document.text()[..end], document.directory());
+    compilation.program.as_ref()?;
+
+    let kind = compilation.external.target_kind()?.clone();
+    let type_def = TypeDef::from(kind).at_path(&path.to_lookup());
+
+    Some(code(&format!("{}: {}", source, type_def.kind())))
+}
+
+fn code(source: &str) -> String {
+    format!("```vrl\n{}\n```", source)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    /// Hovers over the last character of the last occurrence of `at`.
+    fn hover(source: &str, at: &str) -> Option<String> {
+        let document = Document::new(source.to_owned(), None);
+        let offset = source.rfind(at).unwrap() + at.len() - 1;
+
+        super::hover(&Environment::new(vec![]), &document, offset).map(|hover| {
+            match hover.contents {
+                HoverContents::Markup(content) => content.value,
+                contents => panic!("unexpected contents: {:?}", contents),
+            }
+        })
+    }
+
+    #[test]
+    fn hover_stdlib_function() {
+        let contents = hover(".message = upcase(.message)", "upcase").unwrap();
+
+        assert!(contents.starts_with("```vrl\nupcase(value: string)\n```"));
+        assert!(contents.ends_with("[Documentation](https://functions.vrl.dev/upcase)"));
+    }
+
+    #[test]
+    fn hover_optional_parameters() {
+        let contents = hover(".message = parse_json!(.message)", "parse_json").unwrap();
+
+        assert!(contents.starts_with("```vrl\nparse_json(value: string, [max_depth: integer])"));
+    }
+
+    #[test]
+    fn hover_user_function() {
+        let source = indoc! {r#"
+            fn double(value: int) -> int {
+                value * 2
+            }
+            .count = double(21)
+        "#};
+
+        assert_eq!(
+            hover(source, "double").unwrap(),
+            "```vrl\nfn double(value: int) -> int\n```"
+        );
+        assert_eq!(hover(source, "value").unwrap(), "```vrl\nvalue: int\n```");
+    }
+
+    #[test]
+    fn hover_variable_type() {
+        let source = indoc! {r#"
+            count = 1
+            .count = count
+            count = "one"
+            .message = count
+        "#};
+
+        assert_eq!(
+            hover(source, ".count = count").unwrap(),
+            "```vrl\ncount: integer\n```"
+        );
+        assert_eq!(
+            hover(source, "count").unwrap(),
+            "```vrl\ncount: string\n```"
+        );
+    }
+
+    #[test]
+    fn hover_event_field_type() {
+        let source = ".message = \"hello\"\n.length = strlen(.message)";
+
+        assert_eq!(
+            hover(source, ".message").unwrap(),
+            "```vrl\n.message: string\n```"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+#![deny(clippy::all)]
+#![deny(unreachable_pub)]
+#![deny(unused_allocation)]
+#![deny(unused_extern_crates)]
+#![deny(unused_assignments)]
+#![deny(unused_comparisons)]
+
+mod completion;
+mod definition;
+mod diagnostics;
+mod document;
+mod hover;
+mod server;
+mod symbols;
+
+use std::path::{Path, PathBuf};
+
+pub use server::run;
+use vrl::{diagnostic::DiagnosticList, state::ExternalEnv, Function, Program};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("io error: {}", .0)]
+    Io(#[from] std::io::Error),
+
+    #[error("protocol error: {}", .0)]
+    Protocol(#[from] lsp_server::ProtocolError),
+
+    #[error("invalid message: {}", .0)]
+    Json(#[from] serde_json::Error),
+
+    #[error("the connection to the client is closed")]
+    Disconnected,
+}
+
+/// The functions and modules available to the programs edited through the
+/// language server.
+pub(crate) struct Environment {
+    functions: Vec<Box<dyn Function>>,
+    import_paths: Vec<PathBuf>,
+}
+
+/// The outcome of compiling a program.
+pub(crate) struct Compilation {
+    /// The compiled program, if it compiles.
+    pub(crate) program: Option<Program>,
+    pub(crate) diagnostics: DiagnosticList,
+    pub(crate) external: ExternalEnv,
+}
+
+impl Environment {
+    pub(crate) fn new(import_paths: Vec<PathBuf>) -> Self {
+        let mut functions = stdlib::all();
+        functions.append(&mut vector_vrl_functions::vrl_functions());
+
+        Self {
+            functions,
+            import_paths,
+        }
+    }
+
+    pub(crate) fn functions(&self) -> &[Box<dyn Function>] {
+        &self.functions
+    }
+
+    pub(crate) fn function(&self, ident: &str) -> Option<&dyn Function> {
+        self.functions
+            .iter()
+            .find(|function| function.identifier() == ident)
+            .map(AsRef::as_ref)
+    }
+
+    /// Compiles `source`, searching for imported modules in the configured
+    /// import paths and then in `directory`, the directory of the program.
+    pub(crate) fn compile(&self, source: &str, directory: Option<&Path>) -> Compilation {
+        let mut search_path = self.import_paths.clone();
+        search_path.extend(directory.map(Path::to_owned));
+
+        let mut external = ExternalEnv::default();
+        let (program, diagnostics) = match vrl::compile_with_search_path(
+            source,
+            &self.functions,
+            &mut external,
+            &search_path,
+        ) {
+            Ok((program, warnings)) => (Some(program), warnings),
+            Err(errors) => (None, errors),
+        };
+
+        Compilation {
+            program,
+            diagnostics,
+            external,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+extern crate vrl_lsp;
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+#[clap(
+    name = "vrl-lsp",
+    about = "Vector Remap Language language server, communicating over stdin and stdout"
+)]
+struct Opts {
+    /// A directory in which modules imported by programs are searched for. Can be provided
+    /// multiple times, the directories are searched in order, before the directory of the program.
+    #[clap(long = "import-path", parse(from_os_str))]
+    import_paths: Vec<PathBuf>,
+
+    /// Communicate over stdin and stdout, which is the only supported transport. Accepted as
+    /// editors commonly pass it to language servers.
+    #[clap(long)]
+    #[allow(dead_code)]
+    stdio: bool,
+}
+
+fn main() {
+    let opts = Opts::parse();
+
+    if let Err(error) = vrl_lsp::run(opts.import_paths) {
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!("{}", error);
+        }
+        std::process::exit(exitcode::SOFTWARE);
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{self, Notification as _},
+    request::{self, Request as _},
+    CompletionOptions, CompletionParams, CompletionResponse, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverParams, HoverProviderCapability, Location, OneOf,
+    PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    Url,
+};
+
+use crate::{completion, definition, diagnostics, document::Document, hover, Environment, Error};
+
+/// Runs the language server over stdin and stdout, until the client shuts it
+/// down.
+pub fn run(import_paths: Vec<PathBuf>) -> Result<(), Error> {
+    let (connection, io_threads) = Connection::stdio();
+
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+
+    Server {
+        connection,
+        environment: Environment::new(import_paths),
+        documents: HashMap::new(),
+    }
+    .serve()?;
+
+    io_threads.join()?;
+
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["(".to_owned()]),
+            ..Default::default()
+        }),
+        definition_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+struct Server {
+    connection: Connection,
+    environment: Environment,
+    documents: HashMap<Url, Document>,
+}
+
+impl Server {
+    fn serve(&mut self) -> Result<(), Error> {
+        let receiver = self.connection.receiver.clone();
+
+        for message in &receiver {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+
+                    let response = self.handle_request(request);
+                    self.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            request::HoverRequest::METHOD => {
+                self.respond::<request::HoverRequest>(request, Self::hover)
+            }
+            request::Completion::METHOD => {
+                self.respond::<request::Completion>(request, Self::completion)
+            }
+            request::GotoDefinition::METHOD => {
+                self.respond::<request::GotoDefinition>(request, Self::definition)
+            }
+            method => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request: {}", method),
+            ),
+        }
+    }
+
+    fn respond<R: request::Request>(
+        &self,
+        request: Request,
+        handler: impl FnOnce(&Self, R::Params) -> R::Result,
+    ) -> Response {
+        match serde_json::from_value(request.params) {
+            Ok(params) => Response::new_ok(request.id, handler(self, params)),
+            Err(error) => Response::new_err(
+                request.id,
+                ErrorCode::InvalidParams as i32,
+                error.to_string(),
+            ),
+        }
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let document = self.documents.get(&position.text_document.uri)?;
+
+        hover::hover(
+            &self.environment,
+            document,
+            document.offset(position.position),
+        )
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let position = params.text_document_position;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let items = completion::completion(
+            &self.environment,
+            document,
+            document.offset(position.position),
+        );
+
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let uri = position.text_document.uri;
+        let document = self.documents.get(&uri)?;
+        let span = definition::definition(document, document.offset(position.position))?;
+
+        Some(GotoDefinitionResponse::Scalar(Location::new(
+            uri,
+            document.range(span),
+        )))
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), Error> {
+        match notification.method.as_str() {
+            notification::DidOpenTextDocument::METHOD => {
+                let params = parse::<notification::DidOpenTextDocument>(notification)?;
+                let uri = params.text_document.uri;
+                let document = Document::new(params.text_document.text, directory(&uri));
+
+                self.publish_diagnostics(&uri, &document, Some(params.text_document.version))?;
+                self.documents.insert(uri, document);
+            }
+            notification::DidChangeTextDocument::METHOD => {
+                let params = parse::<notification::DidChangeTextDocument>(notification)?;
+                let uri = params.text_document.uri;
+
+                // Documents are synchronized in full, the last change holds
+                // the whole text of the document.
+                if let (Some(document), Some(change)) = (
+                    self.documents.get_mut(&uri),
+                    params.content_changes.into_iter().last(),
+                ) {
+                    document.update(change.text);
+                }
+
+                if let Some(document) = self.documents.get(&uri) {
+                    self.publish_diagnostics(&uri, document, Some(params.text_document.version))?;
+                }
+            }
+            notification::DidCloseTextDocument::METHOD => {
+                let params = parse::<notification::DidCloseTextDocument>(notification)?;
+                let uri = params.text_document.uri;
+
+                self.documents.remove(&uri);
+                self.send(diagnostics_notification(uri, vec![], None))?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn publish_diagnostics(
+        &self,
+        uri: &Url,
+        document: &Document,
+        version: Option<i32>,
+    ) -> Result<(), Error> {
+        let diagnostics = diagnostics::diagnostics(&self.environment, uri, document);
+
+        self.send(diagnostics_notification(uri.clone(), diagnostics, version))
+    }
+
+    fn send(&self, message: Message) -> Result<(), Error> {
+        self.connection
+            .sender
+            .send(message)
+            .map_err(|_| Error::Disconnected)
+    }
+}
+
+fn parse<N: notification::Notification>(notification: Notification) -> Result<N::Params, Error> {
+    Ok(serde_json::from_value(notification.params)?)
+}
+
+fn diagnostics_notification(
+    uri: Url,
+    diagnostics: Vec<lsp_types::Diagnostic>,
+    version: Option<i32>,
+) -> Message {
+    Message::Notification(Notification::new(
+        notification::PublishDiagnostics::METHOD.to_owned(),
+        PublishDiagnosticsParams::new(uri, diagnostics, version),
+    ))
+}
+
+/// Returns the directory of documents stored on disk, in which the modules
+/// they import are also searched for.
+fn directory(uri: &Url) -> Option<PathBuf> {
+    uri.to_file_path()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_owned))
+}
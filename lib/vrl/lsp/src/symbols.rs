@@ -0,0 +1,480 @@
+//! The variables and functions defined and referenced in a program, collected
+//! from its syntax tree.
+
+use lookup::LookupBuf;
+use parser::{
+    ast::{
+        self, Assignment, AssignmentTarget, Container, Expr, FunctionCall, IfStatement, Node,
+        Predicate, QueryTarget, RootExpr, Unary,
+    },
+    Span,
+};
+
+/// The scope of definitions made at the root of the program.
+fn root_scope() -> Span {
+    Span::new(0, usize::MAX)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DefinitionKind {
+    Variable,
+    Parameter,
+    Function,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Definition {
+    pub(crate) ident: String,
+    pub(crate) kind: DefinitionKind,
+    pub(crate) span: Span,
+
+    /// The part of the program in which the definition is visible.
+    pub(crate) scope: Span,
+
+    /// The signature of a function, or the type annotation of a parameter.
+    pub(crate) detail: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Reference {
+    Variable { ident: String, span: Span },
+    Function { ident: String, span: Span },
+    Target { path: LookupBuf, span: Span },
+}
+
+impl Reference {
+    pub(crate) fn span(&self) -> Span {
+        match self {
+            Reference::Variable { span, .. }
+            | Reference::Function { span, .. }
+            | Reference::Target { span, .. } => *span,
+        }
+    }
+}
+
+/// The symbol found at a given position in the program.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Symbol<'a> {
+    Definition(&'a Definition),
+    Reference(&'a Reference),
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Symbols {
+    definitions: Vec<Definition>,
+    references: Vec<Reference>,
+
+    /// The spans of the user-defined functions, whose bodies don't share
+    /// variables with the rest of the program.
+    functions: Vec<Span>,
+
+    /// The spans of the root expressions of the program.
+    roots: Vec<Span>,
+}
+
+impl Symbols {
+    pub(crate) fn new(program: &ast::Program) -> Self {
+        let mut symbols = Self::default();
+        let mut walker = Walker {
+            symbols: &mut symbols,
+            scope: root_scope(),
+        };
+
+        for node in &program.0 {
+            walker.root_expr(node);
+        }
+
+        symbols
+    }
+
+    /// Returns the innermost symbol at `offset`.
+    pub(crate) fn at(&self, offset: usize) -> Option<Symbol<'_>> {
+        let definitions = self
+            .definitions
+            .iter()
+            .filter(|definition| contains(definition.span, offset))
+            .map(|definition| (definition.span, Symbol::Definition(definition)));
+
+        let references = self
+            .references
+            .iter()
+            .filter(|reference| contains(reference.span(), offset))
+            .map(|reference| (reference.span(), Symbol::Reference(reference)));
+
+        definitions
+            .chain(references)
+            .min_by_key(|(span, _)| span.end() - span.start())
+            .map(|(_, symbol)| symbol)
+    }
+
+    /// Returns where the variable `ident`, used at `offset`, is first
+    /// assigned to.
+    pub(crate) fn variable(&self, ident: &str, offset: usize) -> Option<&Definition> {
+        self.visible(offset)
+            .filter(|definition| definition.ident == ident)
+            .min_by_key(|definition| definition.span.start())
+    }
+
+    pub(crate) fn function(&self, ident: &str) -> Option<&Definition> {
+        self.functions()
+            .find(|definition| definition.ident == ident)
+    }
+
+    pub(crate) fn functions(&self) -> impl Iterator<Item = &Definition> {
+        self.definitions
+            .iter()
+            .filter(|definition| definition.kind == DefinitionKind::Function)
+    }
+
+    /// Returns the variables and parameters visible at `offset`, including the
+    /// ones defined by the expression `offset` is in.
+    pub(crate) fn visible(&self, offset: usize) -> impl Iterator<Item = &Definition> {
+        let function = self
+            .functions
+            .iter()
+            .copied()
+            .find(|function| contains(*function, offset));
+
+        self.definitions.iter().filter(move |definition| {
+            definition.kind != DefinitionKind::Function
+                && definition.span.start() <= offset
+                && contains(definition.scope, offset)
+                && function.map_or(true, |function| {
+                    function.start() <= definition.scope.start()
+                        && definition.scope.end() <= function.end()
+                })
+        })
+    }
+
+    /// Returns the end of the root expression `offset` is in.
+    pub(crate) fn root_end(&self, offset: usize) -> Option<usize> {
+        self.roots
+            .iter()
+            .find(|root| contains(**root, offset))
+            .map(|root| root.end())
+    }
+}
+
+fn contains(span: Span, offset: usize) -> bool {
+    span.start() <= offset && offset <= span.end()
+}
+
+struct Walker<'a> {
+    symbols: &'a mut Symbols,
+    scope: Span,
+}
+
+impl Walker<'_> {
+    fn root_expr(&mut self, node: &Node<RootExpr>) {
+        match node.inner() {
+            RootExpr::Expr(expr) => {
+                self.symbols.roots.push(node.span());
+                self.expr(expr);
+            }
+            RootExpr::FunctionDefinition(definition) => {
+                self.symbols.roots.push(node.span());
+                self.function_definition(definition);
+            }
+            RootExpr::Import(_) | RootExpr::Error(_) => {}
+        }
+    }
+
+    fn function_definition(&mut self, node: &Node<ast::FunctionDefinition>) {
+        let ast::FunctionDefinition {
+            ident,
+            parameters,
+            return_kind,
+            block,
+        } = node.inner();
+
+        let mut signature = format!(
+            "fn {}({})",
+            ident,
+            parameters
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if let Some(kind) = return_kind {
+            signature.push_str(&format!(" -> {}", kind));
+        }
+
+        self.define(ident, DefinitionKind::Function, Some(signature));
+        self.symbols.functions.push(node.span());
+
+        let outer = std::mem::replace(&mut self.scope, block.span());
+        for parameter in parameters {
+            let detail = parameter.kind.as_ref().map(ToString::to_string);
+            self.define(&parameter.ident, DefinitionKind::Parameter, detail);
+        }
+        self.scope = outer;
+
+        self.block(block);
+    }
+
+    fn block(&mut self, node: &Node<ast::Block>) {
+        let outer = std::mem::replace(&mut self.scope, node.span());
+
+        for expr in &node.0 {
+            self.expr(expr);
+        }
+
+        self.scope = outer;
+    }
+
+    fn expr(&mut self, node: &Node<Expr>) {
+        match node.inner() {
+            Expr::Literal(_) => {}
+            Expr::Container(container) => self.container(container),
+            Expr::IfStatement(node) => {
+                let IfStatement {
+                    predicate,
+                    consequent,
+                    alternative,
+                } = node.inner();
+
+                match predicate.inner() {
+                    Predicate::One(expr) => self.expr(expr),
+                    Predicate::Many(exprs) => exprs.iter().for_each(|expr| self.expr(expr)),
+                }
+
+                self.block(consequent);
+                if let Some(alternative) = alternative {
+                    self.block(alternative);
+                }
+            }
+            Expr::Op(node) => {
+                let ast::Op(lhs, _, rhs) = node.inner();
+
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            Expr::Assignment(node) => match node.inner() {
+                Assignment::Single { target, expr, .. } => {
+                    self.expr(expr);
+                    self.assignment_target(target);
+                }
+                Assignment::Infallible { ok, err, expr, .. } => {
+                    self.expr(expr);
+                    self.assignment_target(ok);
+                    self.assignment_target(err);
+                }
+            },
+            Expr::Query(node) => self.query(node),
+            Expr::FunctionCall(node) => self.function_call(node),
+            Expr::Variable(ident) => self.symbols.references.push(Reference::Variable {
+                ident: ident.to_string(),
+                span: ident.span(),
+            }),
+            Expr::Unary(node) => match node.inner() {
+                Unary::Not(not) => self.expr(not.expr()),
+            },
+            Expr::Abort(node) => {
+                if let Some(message) = &node.message {
+                    self.expr(message);
+                }
+            }
+        }
+    }
+
+    fn container(&mut self, node: &Node<Container>) {
+        match node.inner() {
+            Container::Group(group) => self.expr(&group.0),
+            Container::Block(block) => self.block(block),
+            Container::Array(array) => array.iter().for_each(|expr| self.expr(expr)),
+            Container::Object(object) => object.iter().for_each(|(_, expr)| self.expr(expr)),
+        }
+    }
+
+    fn assignment_target(&mut self, node: &Node<AssignmentTarget>) {
+        match node.inner() {
+            AssignmentTarget::Noop => {}
+            AssignmentTarget::Internal(ident, _) => self.define(
+                &Node::new(node.span(), ident.clone()),
+                DefinitionKind::Variable,
+                None,
+            ),
+            AssignmentTarget::External(path) => self.symbols.references.push(Reference::Target {
+                path: path.clone().unwrap_or_else(LookupBuf::root),
+                span: node.span(),
+            }),
+            AssignmentTarget::Query(query) => match query.target.inner() {
+                QueryTarget::Internal(ident) => self.define(
+                    &Node::new(query.target.span(), ident.clone()),
+                    DefinitionKind::Variable,
+                    None,
+                ),
+                _ => self.query(&Node::new(node.span(), query.clone())),
+            },
+        }
+    }
+
+    fn query(&mut self, node: &Node<ast::Query>) {
+        let ast::Query { target, path } = node.inner();
+
+        match target.inner() {
+            QueryTarget::Internal(ident) => self.symbols.references.push(Reference::Variable {
+                ident: ident.to_string(),
+                span: target.span(),
+            }),
+            QueryTarget::External => self.symbols.references.push(Reference::Target {
+                path: path.inner().clone(),
+                span: node.span(),
+            }),
+            QueryTarget::FunctionCall(call) => {
+                self.function_call(&Node::new(target.span(), call.clone()));
+            }
+            QueryTarget::Container(container) => {
+                self.container(&Node::new(target.span(), container.clone()));
+            }
+        }
+    }
+
+    fn function_call(&mut self, node: &Node<FunctionCall>) {
+        let FunctionCall {
+            ident,
+            arguments,
+            closure,
+            ..
+        } = node.inner();
+
+        self.symbols.references.push(Reference::Function {
+            ident: ident.to_string(),
+            span: ident.span(),
+        });
+
+        for argument in arguments {
+            self.expr(&argument.expr);
+        }
+
+        if let Some(closure) = closure {
+            let outer = std::mem::replace(&mut self.scope, closure.block.span());
+            for variable in closure.variables.iter().filter(|ident| !ident.is_empty()) {
+                self.define(variable, DefinitionKind::Parameter, None);
+            }
+            self.scope = outer;
+
+            self.block(&closure.block);
+        }
+    }
+
+    fn define(&mut self, ident: &Node<ast::Ident>, kind: DefinitionKind, detail: Option<String>) {
+        let scope = match kind {
+            DefinitionKind::Function => root_scope(),
+            _ => self.scope,
+        };
+
+        // Only the identifier itself is part of the definition, not the path
+        // queried on it.
+        let span = Span::new(ident.start(), ident.start() + ident.len());
+
+        self.symbols.definitions.push(Definition {
+            ident: ident.to_string(),
+            kind,
+            span,
+            scope,
+            detail,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn symbols(source: &str) -> Symbols {
+        Symbols::new(&parser::parse(source).unwrap())
+    }
+
+    #[test]
+    fn resolves_variables_to_their_first_assignment() {
+        let source = indoc! {r#"
+            foo = 1
+            foo = foo + 1
+            bar = foo
+        "#};
+        let symbols = symbols(source);
+
+        let usage = source.rfind("foo").unwrap();
+        let definition = symbols.variable("foo", usage).unwrap();
+
+        assert_eq!(definition.span, Span::new(0, 3));
+        assert_eq!(definition.kind, DefinitionKind::Variable);
+    }
+
+    #[test]
+    fn scopes_variables_to_blocks() {
+        let source = indoc! {r#"
+            if true {
+                foo = 1
+            }
+            foo
+        "#};
+        let symbols = symbols(source);
+
+        assert!(symbols.variable("foo", source.len() - 2).is_none());
+        assert!(symbols.variable("foo", source.find("1").unwrap()).is_some());
+    }
+
+    #[test]
+    fn scopes_parameters_to_function_bodies() {
+        let source = indoc! {r#"
+            value = "outer"
+            fn greet(name: string) -> string {
+                "hello " + name
+            }
+            value = greet(value)
+        "#};
+        let symbols = symbols(source);
+
+        let body = source.find("\"hello").unwrap();
+        let parameter = symbols.variable("name", body).unwrap();
+        assert_eq!(parameter.kind, DefinitionKind::Parameter);
+        assert_eq!(parameter.detail.as_deref(), Some("string"));
+        assert!(symbols.variable("value", body).is_none());
+
+        let function = symbols.function("greet").unwrap();
+        assert_eq!(
+            function.detail.as_deref(),
+            Some("fn greet(name: string) -> string")
+        );
+        assert!(symbols.variable("name", source.len() - 1).is_none());
+    }
+
+    #[test]
+    fn finds_innermost_symbol() {
+        let source = r#".message = upcase(.message)"#;
+        let symbols = symbols(source);
+
+        assert_eq!(
+            symbols.at(source.find("upcase").unwrap() + 1),
+            Some(Symbol::Reference(&Reference::Function {
+                ident: "upcase".to_owned(),
+                span: Span::new(11, 17),
+            }))
+        );
+        assert!(matches!(
+            symbols.at(1),
+            Some(Symbol::Reference(Reference::Target { .. }))
+        ));
+    }
+
+    #[test]
+    fn defines_closure_variables() {
+        let source = indoc! {r#"
+            for_each({ "a": 1 }) -> |key, _value| {
+                log(key)
+            }
+        "#};
+        let symbols = symbols(source);
+
+        let usage = source.rfind("key").unwrap();
+        let definition = symbols.variable("key", usage).unwrap();
+
+        assert_eq!(definition.span.start(), source.find("key").unwrap());
+        assert!(symbols.variable("key", source.len()).is_none());
+    }
+}
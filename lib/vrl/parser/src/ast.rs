@@ -179,6 +179,13 @@ impl IntoIterator for Program {
 pub enum RootExpr {
     Expr(Node<Expr>),
 
+    /// A function defined by the program, which can only appear at the root of
+    /// the program, or of an imported module.
+    FunctionDefinition(Node<FunctionDefinition>),
+
+    /// An import of the function definitions of another VRL file.
+    Import(Node<Import>),
+
     /// A special expression that is returned if a given expression could not be
     /// parsed. This allows the parser to continue on to the next expression.
     Error(Error),
@@ -190,6 +197,8 @@ impl fmt::Debug for RootExpr {
 
         let value = match self {
             Expr(v) => format!("{:?}", v),
+            FunctionDefinition(v) => format!("{:?}", v),
+            Import(v) => format!("{:?}", v),
             Error(v) => format!("{:?}", v),
         };
 
@@ -203,6 +212,8 @@ impl fmt::Display for RootExpr {
 
         match self {
             Expr(v) => v.fmt(f),
+            FunctionDefinition(v) => v.fmt(f),
+            Import(v) => v.fmt(f),
             Error(v) => v.fmt(f),
         }
     }
@@ -483,6 +494,12 @@ impl fmt::Debug for Group {
 #[derive(Clone, PartialEq)]
 pub struct Array(pub(crate) Vec<Node<Expr>>);
 
+impl Array {
+    pub fn iter(&self) -> std::slice::Iter<'_, Node<Expr>> {
+        self.0.iter()
+    }
+}
+
 impl fmt::Display for Array {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let exprs = self
@@ -525,6 +542,12 @@ impl IntoIterator for Array {
 #[derive(Clone, PartialEq)]
 pub struct Object(pub(crate) BTreeMap<Node<String>, Node<Expr>>);
 
+impl Object {
+    pub fn iter(&self) -> std::collections::btree_map::Iter<'_, Node<String>, Node<Expr>> {
+        self.0.iter()
+    }
+}
+
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let exprs = self
@@ -1139,6 +1162,11 @@ impl Not {
     pub fn take(self) -> (Node<()>, Box<Node<Expr>>) {
         (self.0, self.1)
     }
+
+    /// Get a reference to the negated expression.
+    pub fn expr(&self) -> &Node<Expr> {
+        &self.1
+    }
 }
 
 impl fmt::Display for Not {
@@ -1180,6 +1208,149 @@ impl fmt::Debug for Abort {
     }
 }
 
+// -----------------------------------------------------------------------------
+// function definition
+// -----------------------------------------------------------------------------
+
+/// A function defined in VRL.
+///
+/// Calls to the function run its block with the parameters bound to the
+/// arguments of the call, and none of the variables of the caller.
+#[derive(Clone, PartialEq)]
+pub struct FunctionDefinition {
+    pub ident: Node<Ident>,
+    pub parameters: Vec<Node<FunctionParameter>>,
+    pub return_kind: Option<Node<TypeAnnotation>>,
+    pub block: Node<Block>,
+}
+
+impl fmt::Display for FunctionDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fn {}(", self.ident)?;
+
+        let mut iter = self.parameters.iter().peekable();
+        while let Some(parameter) = iter.next() {
+            parameter.fmt(f)?;
+
+            if iter.peek().is_some() {
+                f.write_str(", ")?;
+            }
+        }
+
+        f.write_str(")")?;
+
+        if let Some(kind) = &self.return_kind {
+            write!(f, " -> {}", kind)?;
+        }
+
+        write!(f, " {}", self.block)
+    }
+}
+
+impl fmt::Debug for FunctionDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FunctionDefinition({:?}(", self.ident)?;
+
+        let mut iter = self.parameters.iter().peekable();
+        while let Some(parameter) = iter.next() {
+            parameter.fmt(f)?;
+
+            if iter.peek().is_some() {
+                f.write_str(", ")?;
+            }
+        }
+
+        f.write_str(")")?;
+
+        if let Some(kind) = &self.return_kind {
+            write!(f, " -> {:?}", kind)?;
+        }
+
+        write!(f, " {:?})", self.block)
+    }
+}
+
+/// A parameter of a function definition, with an optional type annotation.
+///
+/// Parameters without a type annotation accept any value.
+#[derive(Clone, PartialEq)]
+pub struct FunctionParameter {
+    pub ident: Node<Ident>,
+    pub kind: Option<Node<TypeAnnotation>>,
+}
+
+impl fmt::Display for FunctionParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.ident.fmt(f)?;
+
+        if let Some(kind) = &self.kind {
+            write!(f, ": {}", kind)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FunctionParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            Some(kind) => write!(f, "Parameter({:?}: {:?})", self.ident, kind),
+            None => write!(f, "Parameter({:?})", self.ident),
+        }
+    }
+}
+
+/// The type of a parameter or return value, as one or more type names
+/// separated by `|`, such as `string | null`.
+///
+/// The type names are validated by the compiler.
+#[derive(Clone, PartialEq)]
+pub struct TypeAnnotation(pub Vec<Node<Ident>>);
+
+impl fmt::Display for TypeAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut iter = self.0.iter().peekable();
+        while let Some(name) = iter.next() {
+            name.fmt(f)?;
+
+            if iter.peek().is_some() {
+                f.write_str(" | ")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for TypeAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Type({})", self)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// import
+// -----------------------------------------------------------------------------
+
+/// An import of the functions defined in another VRL file, found relative to
+/// the directories of the search path the program is compiled with.
+#[derive(Clone, PartialEq)]
+pub struct Import {
+    pub path: Node<String>,
+}
+
+impl fmt::Display for Import {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "import {:?}", self.path.inner())
+    }
+}
+
+impl fmt::Debug for Import {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Import({:?})", self.path.inner())
+    }
+}
+
 // -----------------------------------------------------------------------------
 // testing utilities
 // -----------------------------------------------------------------------------
@@ -0,0 +1,706 @@
+//! A pretty-printer for VRL programs.
+//!
+//! The program is printed from its AST, with the comments of the source put
+//! back in place. Indentation and spacing are normalized, blocks are always
+//! broken over several lines, and arrays, objects and function calls are
+//! broken over several lines, one element per line, when they don't fit
+//! within [`MAX_WIDTH`] columns or contain comments.
+//!
+//! Literals, paths and assignment targets are printed as they appear in the
+//! source, to keep the escapes and quoting chosen by the author.
+
+use diagnostic::Span;
+
+use crate::{
+    ast::{
+        Assignment, AssignmentTarget, Block, Container, Expr, FunctionArgument, FunctionCall,
+        FunctionClosure, FunctionDefinition, IfStatement, Node, Object, Op, Predicate, Query,
+        QueryTarget, RootExpr, Unary,
+    },
+    lex::Lexer,
+    Error,
+};
+
+/// The width of a level of indentation.
+const INDENT: &str = "    ";
+
+/// The column past which arrays, objects and function calls are broken over
+/// several lines.
+pub const MAX_WIDTH: usize = 100;
+
+/// Format a VRL program.
+///
+/// Returns the first syntax error of the program, if it doesn't parse.
+pub fn format(source: &str) -> Result<String, Error> {
+    let mut program = crate::parse(source)?;
+
+    if let Some(index) = program
+        .0
+        .iter()
+        .position(|expr| matches!(expr.inner(), RootExpr::Error(_)))
+    {
+        if let RootExpr::Error(error) = program.0.swap_remove(index).into_inner() {
+            return Err(error);
+        }
+    }
+
+    let mut formatter = Formatter::new(source);
+    formatter.lines(
+        &program.0,
+        source.len(),
+        "",
+        Node::span,
+        Formatter::root_expr,
+    );
+
+    let mut output = formatter.output;
+    if !output.is_empty() {
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+/// Returns the spans of the comments in `source`, in order.
+fn comments(source: &str) -> Vec<Span> {
+    let mut lexer = Lexer::new(source);
+    while let Some(Ok(_)) = lexer.next() {}
+
+    lexer.into_comments()
+}
+
+#[derive(Clone, Copy)]
+struct Comment {
+    span: Span,
+    emitted: bool,
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    comments: Vec<Comment>,
+    output: String,
+    indent: usize,
+
+    /// Set while trying to print a container on a single line, in which case
+    /// the containers nested in it are printed on a single line too.
+    flat: bool,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(source: &'a str) -> Self {
+        let comments = comments(source)
+            .into_iter()
+            .map(|span| Comment {
+                span,
+                emitted: false,
+            })
+            .collect();
+
+        Self {
+            source,
+            comments,
+            output: String::new(),
+            indent: 0,
+            flat: false,
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        self.output.push_str(text);
+    }
+
+    fn slice(&mut self, span: Span) {
+        let source = self.source;
+        self.write(&source[span.start()..span.end()]);
+    }
+
+    /// Start a new line at the current indentation, preceded by an empty line
+    /// if `blank` is set.
+    fn newline(&mut self, blank: bool) {
+        if self.output.is_empty() {
+            return;
+        }
+
+        self.output.push('\n');
+        if blank {
+            self.output.push('\n');
+        }
+
+        for _ in 0..self.indent {
+            self.output.push_str(INDENT);
+        }
+    }
+
+    /// The width of the last line of the output.
+    fn column(&self) -> usize {
+        let start = self.output.rfind('\n').map_or(0, |index| index + 1);
+
+        self.output[start..].chars().count()
+    }
+
+    /// Whether the source has an empty line between `start` and `end`.
+    fn blank_line(&self, start: usize, end: usize) -> bool {
+        self.source[start..end].matches('\n').count() > 1
+    }
+
+    fn has_comments(&self, span: Span) -> bool {
+        self.comments.iter().any(|comment| {
+            !comment.emitted
+                && comment.span.start() >= span.start()
+                && comment.span.start() < span.end()
+        })
+    }
+
+    /// Mark the comments starting before `end` as emitted, and return them.
+    fn take_comments(&mut self, end: usize) -> Vec<Span> {
+        self.comments
+            .iter_mut()
+            .filter(|comment| !comment.emitted && comment.span.start() < end)
+            .map(|comment| {
+                comment.emitted = true;
+                comment.span
+            })
+            .collect()
+    }
+
+    fn comment(&mut self, span: Span) {
+        let source = self.source;
+        self.write(source[span.start()..span.end()].trim_end());
+    }
+
+    /// Write `f` one level of indentation deeper than the current one, and
+    /// start a new line at the current indentation after it.
+    fn indented(&mut self, f: impl FnOnce(&mut Self)) {
+        self.indent += 1;
+        f(self);
+        self.indent -= 1;
+        self.newline(false);
+    }
+
+    /// Write `items` one per line, each followed by `separator`. `end` is the
+    /// end of the region holding the items, such as the closing brace of a
+    /// block.
+    ///
+    /// The comments before and in between the items are written on their own
+    /// lines, those on the same line as the end of an item after it. Single
+    /// empty lines between items are preserved.
+    fn lines<T>(
+        &mut self,
+        items: &[T],
+        end: usize,
+        separator: &str,
+        span: impl Fn(&T) -> Span,
+        mut write: impl FnMut(&mut Self, &T),
+    ) {
+        let mut previous: Option<usize> = None;
+
+        for (index, item) in items.iter().enumerate() {
+            let item_span = span(item);
+
+            for comment in self.take_comments(item_span.start()) {
+                let blank = previous.map_or(false, |p| self.blank_line(p, comment.start()));
+                self.newline(blank);
+                self.comment(comment);
+                previous = Some(comment.end());
+            }
+
+            let blank = previous.map_or(false, |p| self.blank_line(p, item_span.start()));
+            self.newline(blank);
+            write(self, item);
+            self.write(separator);
+
+            // Comments left inside the item, and the one following it on the
+            // same line, are written after it.
+            let next = items.get(index + 1).map_or(end, |next| span(next).start());
+            let line_end = self.source[item_span.end()..next]
+                .find('\n')
+                .map_or(next, |offset| item_span.end() + offset);
+
+            let mut comments = self.take_comments(line_end).into_iter();
+            if let Some(comment) = comments.next() {
+                self.write(" ");
+                self.comment(comment);
+                previous = Some(comment.end());
+            } else {
+                previous = Some(item_span.end());
+            }
+
+            for comment in comments {
+                self.newline(false);
+                self.comment(comment);
+                previous = Some(comment.end());
+            }
+        }
+
+        for comment in self.take_comments(end) {
+            let blank = previous.map_or(false, |p| self.blank_line(p, comment.start()));
+            self.newline(blank);
+            self.comment(comment);
+            previous = Some(comment.end());
+        }
+    }
+
+    /// Write a container delimited by `open` and `close`, on a single line if
+    /// it fits and holds no comments, or with one item per line otherwise.
+    fn list<T>(
+        &mut self,
+        open: &str,
+        close: &str,
+        container: Span,
+        items: &[T],
+        span: impl Fn(&T) -> Span,
+        write: impl Fn(&mut Self, &T),
+    ) {
+        let has_comments = self.has_comments(container);
+
+        if items.is_empty() && !has_comments {
+            self.write(open);
+            self.write(close);
+            return;
+        }
+
+        if !has_comments {
+            if self.flat {
+                self.flat_list(open, close, items, &write);
+                return;
+            }
+
+            let checkpoint = self.output.len();
+            let comments = self.comments.clone();
+
+            self.flat = true;
+            self.flat_list(open, close, items, &write);
+            self.flat = false;
+
+            if !self.output[checkpoint..].contains('\n') && self.column() <= MAX_WIDTH {
+                return;
+            }
+
+            self.output.truncate(checkpoint);
+            self.comments = comments;
+        }
+
+        self.write(open);
+        self.indented(|f| f.lines(items, container.end(), ",", span, write));
+        self.write(close);
+    }
+
+    fn flat_list<T>(
+        &mut self,
+        open: &str,
+        close: &str,
+        items: &[T],
+        write: impl Fn(&mut Self, &T),
+    ) {
+        self.write(open);
+
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.write(", ");
+            }
+
+            write(self, item);
+        }
+
+        self.write(close);
+    }
+
+    fn root_expr(&mut self, expr: &Node<RootExpr>) {
+        match expr.inner() {
+            RootExpr::Expr(expr) => self.expr(expr),
+            RootExpr::FunctionDefinition(definition) => self.function_definition(definition),
+            RootExpr::Import(import) => {
+                self.write("import ");
+                self.slice(import.path.span());
+            }
+            RootExpr::Error(_) => self.slice(expr.span()),
+        }
+    }
+
+    fn expr(&mut self, expr: &Node<Expr>) {
+        match expr.inner() {
+            Expr::Literal(literal) => self.slice(literal.span()),
+            Expr::Container(container) => self.container(container.inner(), container.span()),
+            Expr::IfStatement(if_statement) => self.if_statement(if_statement.inner()),
+            Expr::Op(op) => self.op(op.inner()),
+            Expr::Assignment(assignment) => self.assignment(assignment.inner()),
+            Expr::Query(query) => self.query(query.inner()),
+            Expr::FunctionCall(call) => self.function_call(call.inner(), call.span()),
+            Expr::Variable(ident) => self.write(ident),
+            Expr::Unary(unary) => match unary.inner() {
+                Unary::Not(not) => {
+                    self.write("!");
+                    self.expr(not.expr());
+                }
+            },
+            Expr::Abort(abort) => {
+                self.write("abort");
+
+                if let Some(message) = &abort.message {
+                    self.write(" ");
+                    self.expr(message);
+                }
+            }
+        }
+    }
+
+    fn container(&mut self, container: &Container, span: Span) {
+        match container {
+            Container::Group(group) => {
+                self.write("(");
+                self.expr(&group.0);
+                self.write(")");
+            }
+            Container::Block(block) => self.block(block),
+            Container::Array(array) => {
+                let elements = array.iter().collect::<Vec<_>>();
+
+                self.list(
+                    "[",
+                    "]",
+                    span,
+                    &elements,
+                    |expr| expr.span(),
+                    |f, expr| f.expr(expr),
+                );
+            }
+            Container::Object(object) => self.object(object, span),
+        }
+    }
+
+    fn object(&mut self, object: &Object, span: Span) {
+        let fields = object.iter().collect::<Vec<_>>();
+
+        self.list(
+            "{",
+            "}",
+            span,
+            &fields,
+            |(key, value)| Span::new(key.start(), value.end()),
+            |f, (key, value)| {
+                f.slice(key.span());
+                f.write(": ");
+                f.expr(value);
+            },
+        );
+    }
+
+    fn block(&mut self, block: &Node<Block>) {
+        self.write("{");
+        self.indented(|f| f.lines(&block.0, block.end(), "", Node::span, Self::expr));
+        self.write("}");
+    }
+
+    fn if_statement(&mut self, if_statement: &IfStatement) {
+        self.write("if ");
+
+        match if_statement.predicate.inner() {
+            Predicate::One(expr) => self.expr(expr),
+            Predicate::Many(exprs) => {
+                self.write("(");
+
+                for (index, expr) in exprs.iter().enumerate() {
+                    if index > 0 {
+                        self.write("; ");
+                    }
+
+                    self.expr(expr);
+                }
+
+                self.write(")");
+            }
+        }
+
+        self.write(" ");
+        self.block(&if_statement.consequent);
+
+        if let Some(alternative) = &if_statement.alternative {
+            self.write(" else ");
+
+            // An `else if` is parsed as an alternative block holding the
+            // nested if-statement, spanning exactly that statement.
+            match alternative.0.as_slice() {
+                [expr] if expr.span() == alternative.span() => match expr.inner() {
+                    Expr::IfStatement(nested) => self.if_statement(nested.inner()),
+                    _ => self.block(alternative),
+                },
+                _ => self.block(alternative),
+            }
+        }
+    }
+
+    fn op(&mut self, op: &Op) {
+        let Op(lhs, opcode, rhs) = op;
+
+        self.expr(lhs);
+        self.write(" ");
+        self.write(opcode.as_str());
+        self.write(" ");
+        self.expr(rhs);
+    }
+
+    fn assignment(&mut self, assignment: &Assignment) {
+        match assignment {
+            Assignment::Single { target, op, expr } => {
+                self.assignment_target(target);
+                self.write(&format!(" {} ", op));
+                self.expr(expr);
+            }
+            Assignment::Infallible { ok, err, op, expr } => {
+                self.assignment_target(ok);
+                self.write(", ");
+                self.assignment_target(err);
+                self.write(&format!(" {} ", op));
+                self.expr(expr);
+            }
+        }
+    }
+
+    fn assignment_target(&mut self, target: &Node<AssignmentTarget>) {
+        match target.inner() {
+            AssignmentTarget::Query(query) => self.query(query),
+            _ => self.slice(target.span()),
+        }
+    }
+
+    fn query(&mut self, query: &Query) {
+        match query.target.inner() {
+            QueryTarget::Internal(_) | QueryTarget::External => {
+                self.slice(Span::new(query.target.start(), query.path.end()));
+            }
+            QueryTarget::FunctionCall(call) => {
+                self.function_call(call, query.target.span());
+                self.slice(query.path.span());
+            }
+            QueryTarget::Container(container) => {
+                self.container(container, query.target.span());
+                self.slice(query.path.span());
+            }
+        }
+    }
+
+    fn function_call(&mut self, call: &FunctionCall, span: Span) {
+        self.write(&call.ident);
+        if call.abort_on_error {
+            self.write("!");
+        }
+
+        // The arguments end where the closure starts, if there is one.
+        let arguments = Span::new(
+            span.start(),
+            call.closure.as_ref().map_or(span.end(), Node::start),
+        );
+
+        self.list(
+            "(",
+            ")",
+            arguments,
+            &call.arguments,
+            Node::span,
+            |f, argument| f.function_argument(argument.inner()),
+        );
+
+        if let Some(closure) = &call.closure {
+            self.write(" ");
+            self.function_closure(closure.inner());
+        }
+    }
+
+    fn function_argument(&mut self, argument: &FunctionArgument) {
+        if let Some(ident) = &argument.ident {
+            self.write(ident);
+            self.write(": ");
+        }
+
+        self.expr(&argument.expr);
+    }
+
+    fn function_closure(&mut self, closure: &FunctionClosure) {
+        let variables = closure
+            .variables
+            .iter()
+            .map(|ident| match ident.inner().as_ref() {
+                "" => "_",
+                ident => ident,
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.write(&format!("-> |{}| ", variables));
+        self.block(&closure.block);
+    }
+
+    fn function_definition(&mut self, definition: &FunctionDefinition) {
+        self.write(&format!("fn {}(", definition.ident.inner()));
+
+        for (index, parameter) in definition.parameters.iter().enumerate() {
+            if index > 0 {
+                self.write(", ");
+            }
+
+            self.write(&parameter.to_string());
+        }
+
+        self.write(")");
+
+        if let Some(kind) = &definition.return_kind {
+            self.write(&format!(" -> {}", kind.inner()));
+        }
+
+        self.write(" ");
+        self.block(&definition.block);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn assert_formats(source: &str, expected: &str) {
+        let formatted = format(source).unwrap();
+
+        assert_eq!(formatted, expected);
+        assert_eq!(format(&formatted).unwrap(), expected, "not idempotent");
+    }
+
+    #[test]
+    fn normalizes_spacing_and_indentation() {
+        assert_formats(
+            indoc! {r#"
+                .foo=upcase( .bar )
+                if .a==1 {
+                  .b =  [1,2 ,3]
+                }else if .a == 2 {
+                        .c={"x" :1}
+                } else {
+                  abort
+                }
+                ok,err=parse_json(.message);.d = !ok
+            "#},
+            indoc! {r#"
+                .foo = upcase(.bar)
+                if .a == 1 {
+                    .b = [1, 2, 3]
+                } else if .a == 2 {
+                    .c = {"x": 1}
+                } else {
+                    abort
+                }
+                ok, err = parse_json(.message)
+                .d = !ok
+            "#},
+        );
+    }
+
+    #[test]
+    fn preserves_comments() {
+        assert_formats(
+            indoc! {r#"
+                # Parse the message.
+                .message = parse_json!(.message) # always JSON
+
+
+                # Tag the event.
+                .tags = [
+                  "a", # first
+                  # the last one
+                  "b"
+                ]
+                if .a {
+                    .b = 1
+                    # done
+                }
+                # trailing
+            "#},
+            indoc! {r#"
+                # Parse the message.
+                .message = parse_json!(.message) # always JSON
+
+                # Tag the event.
+                .tags = [
+                    "a", # first
+                    # the last one
+                    "b",
+                ]
+                if .a {
+                    .b = 1
+                    # done
+                }
+                # trailing
+            "#},
+        );
+    }
+
+    #[test]
+    fn wraps_long_function_calls() {
+        assert_formats(
+            indoc! {r#"
+                .message = parse_regex!(.message, r'^(?P<host>[\w\.]+) - (?P<user>[\w]+) (?P<bytes_in>[\d]+)', numeric_groups: true)
+                .short = join!(["a", "b"], separator: ", ")
+            "#},
+            indoc! {r#"
+                .message = parse_regex!(
+                    .message,
+                    r'^(?P<host>[\w\.]+) - (?P<user>[\w]+) (?P<bytes_in>[\d]+)',
+                    numeric_groups: true,
+                )
+                .short = join!(["a", "b"], separator: ", ")
+            "#},
+        );
+    }
+
+    #[test]
+    fn formats_closures_and_functions() {
+        assert_formats(
+            indoc! {r#"
+                import   "common.vrl"
+                fn double(value:int)->int{value*2}
+                .values = map_values(.values)->|value|{double(value)}
+                for_each(.)->|_,v|{ .count = v }
+            "#},
+            indoc! {r#"
+                import "common.vrl"
+                fn double(value: int) -> int {
+                    value * 2
+                }
+                .values = map_values(.values) -> |value| {
+                    double(value)
+                }
+                for_each(.) -> |_, v| {
+                    .count = v
+                }
+            "#},
+        );
+    }
+
+    #[test]
+    fn keeps_literals_and_paths() {
+        assert_formats(
+            indoc! {r#"
+                .a."b.c"[0] = s'raw\n' + "it's \"quoted\""
+                .t = t'2021-01-01T00:00:00Z'
+                .f = 1.50
+                .q = parse_json!(.x).y
+            "#},
+            indoc! {r#"
+                .a."b.c"[0] = s'raw\n' + "it's \"quoted\""
+                .t = t'2021-01-01T00:00:00Z'
+                .f = 1.50
+                .q = parse_json!(.x).y
+            "#},
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_programs() {
+        assert!(format(".foo = ").is_err());
+    }
+
+    #[test]
+    fn formats_empty_program() {
+        assert_eq!(format("").unwrap(), "");
+        assert_eq!(format("# only a comment").unwrap(), "# only a comment\n");
+    }
+}
@@ -110,6 +110,8 @@ impl DiagnosticMessage for Error {
                         r#""null""#,
                         r#""true""#,
                         r#""if""#,
+                        r#""fn""#,
+                        r#""import""#,
                     ];
                     let is_any_ident = any_ident.iter().all(|i| expected.contains(&i.to_string()));
                     if is_any_ident {
@@ -216,6 +218,10 @@ pub struct Lexer<'input> {
     ///   ~~~~~~~~~~  0..10
     ///    ~~~~       1..5
     rquery_indices: Vec<usize>,
+
+    /// The spans of the comments skipped so far, without their trailing
+    /// newline.
+    comments: Vec<Span>,
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
@@ -245,6 +251,8 @@ pub enum Token<S> {
     False,
     True,
     Abort,
+    Fn,
+    Import,
 
     // tokens
     Colon,
@@ -331,6 +339,8 @@ impl<S> Token<S> {
             Null => Null,
             True => True,
             Abort => Abort,
+            Fn => Fn,
+            Import => Import,
 
             // tokens
             Colon => Colon,
@@ -386,6 +396,8 @@ where
             Null => "Null",
             True => "True",
             Abort => "Abort",
+            Fn => "Fn",
+            Import => "Import",
 
             // tokens
             Colon => "Colon",
@@ -428,6 +440,8 @@ impl<'input> Token<&'input str> {
             "false" => False,
             "null" => Null,
             "abort" => Abort,
+            "fn" => Fn,
+            "import" => Import,
 
             // reserved identifiers
             "array" | "bool" | "boolean" | "break" | "continue" | "do" | "emit" | "float"
@@ -602,7 +616,8 @@ impl<'input> Iterator for Lexer<'input> {
                     }
 
                     '#' => {
-                        self.take_until(start, |ch| ch == '\n');
+                        let (end, _) = self.take_until(start, |ch| ch == '\n');
+                        self.comments.push(Span::new(start, end));
                         continue;
                     }
 
@@ -1129,9 +1144,15 @@ impl<'input> Lexer<'input> {
             open_brackets: 0,
             open_parens: 0,
             rquery_indices: vec![],
+            comments: vec![],
         }
     }
 
+    /// Consume the lexer, returning the spans of the comments it skipped.
+    pub(crate) fn into_comments(self) -> Vec<Span> {
+        self.comments
+    }
+
     fn bump(&mut self) -> Option<(usize, char)> {
         self.chars.next()
     }
@@ -1503,6 +1524,40 @@ mod test {
         );
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn function_definitions() {
+        use StringLiteralToken as S;
+        use StringLiteral as L;
+
+        test(
+            data(r#"fn add(x: string, y) -> int {}"#),
+            vec![
+                (r#"~~                            "#, Fn),
+                (r#"   ~~~                        "#, FunctionCall("add")),
+                (r#"      ~                       "#, LParen),
+                (r#"       ~                      "#, Identifier("x")),
+                (r#"        ~                     "#, Colon),
+                (r#"          ~~~~~~              "#, ReservedIdentifier("string")),
+                (r#"                ~             "#, Comma),
+                (r#"                  ~           "#, Identifier("y")),
+                (r#"                   ~          "#, RParen),
+                (r#"                     ~~       "#, Arrow),
+                (r#"                        ~~~   "#, ReservedIdentifier("int")),
+                (r#"                            ~ "#, LBrace),
+                (r#"                             ~"#, RBrace),
+            ],
+        );
+
+        test(
+            data(r#"import "math.vrl""#),
+            vec![
+                (r#"~~~~~~           "#, Import),
+                (r#"       ~~~~~~~~~~"#, L(S("math.vrl"))),
+            ],
+        );
+    }
+
     #[test]
     fn single_query() {
         test(
@@ -10,6 +10,7 @@ mod arbitrary;
 #[cfg(feature = "fuzz")]
 mod arbitrary_depth;
 pub mod ast;
+pub mod format;
 mod lex;
 mod template_string;
 
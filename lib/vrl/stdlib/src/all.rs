@@ -0,0 +1,136 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn all<T>(value: Value, ctx: &mut Context, runner: closure::Runner<T>) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                if !runner.resolve_key_value(ctx, &key, &value)?.try_boolean()? {
+                    return Ok(false.into());
+                }
+            }
+        }
+        Value::Array(array) => {
+            for (index, value) in array.into_iter().enumerate() {
+                if !runner
+                    .resolve_index_value(ctx, index, &value)?
+                    .try_boolean()?
+                {
+                    return Ok(false.into());
+                }
+            }
+        }
+        value => {
+            return Err(value::Error::Expected {
+                got: value.kind(),
+                expected: Kind::object(Collection::any()) | Kind::array(Collection::any()),
+            }
+            .into())
+        }
+    }
+
+    Ok(true.into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct All;
+
+impl Function for All {
+    fn identifier(&self) -> &'static str {
+        "all"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::OBJECT | kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "all elements match",
+                source: r#"all([1, 2, 3]) -> |_index, value| { value > 0 }"#,
+                result: Ok("true"),
+            },
+            Example {
+                title: "not all fields match",
+                source: r#"all({ "a": 1, "b": 2 }) -> |_key, value| { value == 1 }"#,
+                result: Ok("false"),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(AllFn { value, closure }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::object(Collection::any()).or_array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(Kind::boolean()),
+                example: Example {
+                    title: "not all elements match",
+                    source: r#"all([1, 2]) -> |_index, value| { value == 2 }"#,
+                    result: Ok("false"),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+
+    fn call_by_vm(&self, ctx: &mut Context, args: &mut VmArgumentList) -> Result<Value> {
+        let value = args.required("value");
+        let VmFunctionClosure { variables, vm } = args.closure();
+        let runner = closure::Runner::new(variables, |ctx| vm.interpret(ctx));
+
+        all(value, ctx, runner)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AllFn {
+    value: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for AllFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        all(value, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let fallible = self.closure.block.type_def(ctx).is_fallible();
+
+        TypeDef::boolean().with_fallibility(fallible)
+    }
+}
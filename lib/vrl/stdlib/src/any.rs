@@ -0,0 +1,136 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn any<T>(value: Value, ctx: &mut Context, runner: closure::Runner<T>) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                if runner.resolve_key_value(ctx, &key, &value)?.try_boolean()? {
+                    return Ok(true.into());
+                }
+            }
+        }
+        Value::Array(array) => {
+            for (index, value) in array.into_iter().enumerate() {
+                if runner
+                    .resolve_index_value(ctx, index, &value)?
+                    .try_boolean()?
+                {
+                    return Ok(true.into());
+                }
+            }
+        }
+        value => {
+            return Err(value::Error::Expected {
+                got: value.kind(),
+                expected: Kind::object(Collection::any()) | Kind::array(Collection::any()),
+            }
+            .into())
+        }
+    }
+
+    Ok(false.into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Any;
+
+impl Function for Any {
+    fn identifier(&self) -> &'static str {
+        "any"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::OBJECT | kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "any element matches",
+                source: r#"any([1, 2, 3]) -> |_index, value| { value > 2 }"#,
+                result: Ok("true"),
+            },
+            Example {
+                title: "no field matches",
+                source: r#"any({ "a": 1, "b": 2 }) -> |key, _value| { key == "c" }"#,
+                result: Ok("false"),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(AnyFn { value, closure }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::object(Collection::any()).or_array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(Kind::boolean()),
+                example: Example {
+                    title: "any element matches",
+                    source: r#"any([1, 2]) -> |_index, value| { value == 2 }"#,
+                    result: Ok("true"),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+
+    fn call_by_vm(&self, ctx: &mut Context, args: &mut VmArgumentList) -> Result<Value> {
+        let value = args.required("value");
+        let VmFunctionClosure { variables, vm } = args.closure();
+        let runner = closure::Runner::new(variables, |ctx| vm.interpret(ctx));
+
+        any(value, ctx, runner)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AnyFn {
+    value: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for AnyFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        any(value, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let fallible = self.closure.block.type_def(ctx).is_fallible();
+
+        TypeDef::boolean().with_fallibility(fallible)
+    }
+}
@@ -0,0 +1,127 @@
+use ::value::Value;
+use flate2::read::MultiGzDecoder;
+use vrl::prelude::*;
+
+use crate::util::read_decompressed;
+
+fn decode_gzip(value: Value) -> Resolved {
+    let value = value.try_bytes()?;
+
+    // Concatenated gzip members are decoded as a single stream.
+    let buffer = read_decompressed(MultiGzDecoder::new(value.as_ref()), "gzip")?;
+
+    Ok(Value::Bytes(buffer.into()))
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DecodeGzip;
+
+impl Function for DecodeGzip {
+    fn identifier(&self) -> &'static str {
+        "decode_gzip"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "demo string",
+            source: r#"decode_gzip!(decode_base64!("H4sIAAAAAAACAyvOz011SSxJBADuEj7zCAAAAA=="))"#,
+            result: Ok("someData"),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(DecodeGzipFn { value }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+
+        decode_gzip(value)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct DecodeGzipFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for DecodeGzipFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        decode_gzip(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        // Always fallible due to the possibility of decoding errors that VRL can't detect in
+        // advance.
+        TypeDef::bytes().fallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use crate::util::MAX_DECOMPRESSED_SIZE;
+
+    const MEMBER_SIZE: usize = 1024 * 1024;
+
+    fn decode_base64(text: &str) -> Bytes {
+        base64::decode(text).expect("valid base64").into()
+    }
+
+    /// Concatenates the given number of gzip members, each decoding into `MEMBER_SIZE` zeroes.
+    fn repeated_member(count: usize) -> Bytes {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[0; MEMBER_SIZE]).unwrap();
+        encoder.finish().unwrap().repeat(count).into()
+    }
+
+    test_function![
+        decode_gzip => DecodeGzip;
+
+        right_gzip {
+            args: func_args![value: decode_base64("H4sIAAAAAAACAyvOz011SSxJBADuEj7zCAAAAA==")],
+            want: Ok(value!("someData")),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        concatenated_gzip {
+            args: func_args![value: decode_base64("H4sIAAAAAAACAyvOz011SSxJBADuEj7zCAAAAB+LCAAAAAAAAgMrzs9NdUksSQQA7hI+8wgAAAA=")],
+            want: Ok(value!("someDatasomeData")),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        too_large_gzip {
+            args: func_args![value: repeated_member(MAX_DECOMPRESSED_SIZE as usize / MEMBER_SIZE + 1)],
+            want: Err(format!("decoded value is larger than {} bytes", MAX_DECOMPRESSED_SIZE)),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        wrong_gzip {
+            args: func_args![value: value!("some string value")],
+            want: Err("unable to decode value with gzip decoder"),
+            tdef: TypeDef::bytes().fallible(),
+        }
+    ];
+}
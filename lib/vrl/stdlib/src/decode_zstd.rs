@@ -0,0 +1,125 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::util::read_decompressed;
+
+fn decode_zstd(value: Value) -> Resolved {
+    let value = value.try_bytes()?;
+
+    // Concatenated frames are decoded as a single stream.
+    let decoder = zstd::stream::read::Decoder::new(value.as_ref())
+        .map_err(|_| "unable to decode value with zstd decoder")?;
+    let buffer = read_decompressed(decoder, "zstd")?;
+
+    Ok(Value::Bytes(buffer.into()))
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DecodeZstd;
+
+impl Function for DecodeZstd {
+    fn identifier(&self) -> &'static str {
+        "decode_zstd"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "demo string",
+            source: r#"decode_zstd!(decode_base64!("KLUv/QRYQQAAc29tZURhdGF0iFpg"))"#,
+            result: Ok("someData"),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(DecodeZstdFn { value }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+
+        decode_zstd(value)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct DecodeZstdFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for DecodeZstdFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        decode_zstd(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        // Always fallible due to the possibility of decoding errors that VRL can't detect in
+        // advance.
+        TypeDef::bytes().fallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::MAX_DECOMPRESSED_SIZE;
+
+    const FRAME_SIZE: usize = 1024 * 1024;
+
+    fn decode_base64(text: &str) -> Bytes {
+        base64::decode(text).expect("valid base64").into()
+    }
+
+    /// Concatenates the given number of zstd frames, each decoding into `FRAME_SIZE` zeroes.
+    fn repeated_frame(count: usize) -> Bytes {
+        zstd::stream::encode_all(&[0; FRAME_SIZE][..], 0)
+            .unwrap()
+            .repeat(count)
+            .into()
+    }
+
+    test_function![
+        decode_zstd => DecodeZstd;
+
+        right_zstd {
+            args: func_args![value: decode_base64("KLUv/QRYQQAAc29tZURhdGF0iFpg")],
+            want: Ok(value!("someData")),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        concatenated_zstd {
+            args: func_args![value: decode_base64("KLUv/QRYQQAAc29tZURhdGF0iFpgKLUv/QRYQQAAc29tZURhdGF0iFpg")],
+            want: Ok(value!("someDatasomeData")),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        too_large_zstd {
+            args: func_args![value: repeated_frame(MAX_DECOMPRESSED_SIZE as usize / FRAME_SIZE + 1)],
+            want: Err(format!("decoded value is larger than {} bytes", MAX_DECOMPRESSED_SIZE)),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        wrong_zstd {
+            args: func_args![value: value!("some string value")],
+            want: Err("unable to decode value with zstd decoder"),
+            tdef: TypeDef::bytes().fallible(),
+        }
+    ];
+}
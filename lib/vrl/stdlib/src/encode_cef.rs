@@ -0,0 +1,178 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::util::{encode_field, escape_header, is_cef_key_char, CEF_HEADER_FIELDS};
+
+fn encode_cef(value: Value) -> Resolved {
+    let mut object = value.try_object()?;
+    let mut header = Vec::with_capacity(CEF_HEADER_FIELDS.len());
+
+    for key in CEF_HEADER_FIELDS {
+        let field = match object.remove(key).map(|value| encode_field(key, value)) {
+            Some(Ok(Some(field))) => field,
+            Some(Err(err)) => return Err(err.into()),
+            _ if key == "cefVersion" => "0".to_owned(),
+            _ => return Err(format!("missing header field `{}`", key).into()),
+        };
+
+        header.push(escape_header(&field).map_err(|err| format!("`{}` {}", key, err))?);
+    }
+
+    let mut extension = Vec::with_capacity(object.len());
+
+    for (key, value) in object {
+        if key.is_empty() || !key.chars().all(is_cef_key_char) {
+            return Err(format!("invalid extension key: {:?}", key).into());
+        }
+
+        if let Some(value) = encode_field(&key, value)? {
+            extension.push(format!("{}={}", key, escape_value(&value)));
+        }
+    }
+
+    Ok(format!("CEF:{}|{}", header.join("|"), extension.join(" ")).into())
+}
+
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\\"),
+            '=' => escaped.push_str(r"\="),
+            '\n' => escaped.push_str(r"\n"),
+            '\r' => escaped.push_str(r"\r"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EncodeCef;
+
+impl Function for EncodeCef {
+    fn identifier(&self) -> &'static str {
+        "encode_cef"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::OBJECT,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "encode object",
+            source: r#"encode_cef!({ "deviceVendor": "Security", "deviceProduct": "threatmanager", "deviceVersion": "1.0", "deviceEventClassId": "100", "name": "worm stopped", "severity": 10, "src": "10.0.0.1", "msg": "a=b" })"#,
+            result: Ok(
+                r#"s'CEF:0|Security|threatmanager|1.0|100|worm stopped|10|msg=a\=b src=10.0.0.1'"#,
+            ),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(EncodeCefFn { value }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+
+        encode_cef(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EncodeCefFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for EncodeCefFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        encode_cef(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::bytes().fallible(/* missing header fields or unsupported values */)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use vector_common::btreemap;
+
+    use super::*;
+
+    test_function![
+        encode_cef => EncodeCef;
+
+        valid {
+            args: func_args![value: btreemap! {
+                "cefVersion" => 1,
+                "deviceVendor" => "Security",
+                "deviceProduct" => "threat|manager",
+                "deviceVersion" => "1.0",
+                "deviceEventClassId" => "100",
+                "name" => r"worm\stopped",
+                "severity" => "High",
+                "spt" => 1232,
+                "cfp1" => 1.5,
+                "rt" => Utc.timestamp_millis(1_644_865_494_000),
+                "msg" => "a=b\nc\\d",
+                "dst" => Value::Null,
+            }],
+            want: Ok(r"CEF:1|Security|threat\|manager|1.0|100|worm\\stopped|High|cfp1=1.5 msg=a\=b\nc\\d rt=1644865494000 spt=1232"),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        missing_header_field {
+            args: func_args![value: btreemap! {
+                "deviceVendor" => "Security",
+            }],
+            want: Err("missing header field `deviceProduct`"),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        invalid_key {
+            args: func_args![value: btreemap! {
+                "deviceVendor" => "V",
+                "deviceProduct" => "P",
+                "deviceVersion" => "1",
+                "deviceEventClassId" => "id",
+                "name" => "name",
+                "severity" => 1,
+                "bad key" => "value",
+            }],
+            want: Err(r#"invalid extension key: "bad key""#),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        unsupported_value {
+            args: func_args![value: btreemap! {
+                "deviceVendor" => "V",
+                "deviceProduct" => "P",
+                "deviceVersion" => "1",
+                "deviceEventClassId" => "id",
+                "name" => "name",
+                "severity" => 1,
+                "tags" => vec!["a"],
+            }],
+            want: Err("unsupported value for `tags`: array"),
+            tdef: TypeDef::bytes().fallible(),
+        }
+    ];
+}
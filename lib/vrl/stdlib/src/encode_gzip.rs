@@ -0,0 +1,196 @@
+use std::io::Read;
+
+use ::value::Value;
+use flate2::{read::GzEncoder, Compression};
+use vrl::prelude::*;
+
+const MAX_COMPRESSION_LEVEL: u32 = 9;
+
+fn encode_gzip(value: Value, compression_level: Option<Value>) -> Resolved {
+    let level = match compression_level {
+        None => Compression::default(),
+        Some(value) => Compression::new(get_level(value)?),
+    };
+    let value = value.try_bytes()?;
+    let mut buffer = Vec::new();
+
+    // Reading from a byte slice should not be able to fail.
+    GzEncoder::new(value.as_ref(), level)
+        .read_to_end(&mut buffer)
+        .expect("gzip compression failed");
+
+    Ok(Value::Bytes(buffer.into()))
+}
+
+fn get_level(value: Value) -> std::result::Result<u32, &'static str> {
+    let level = value.try_integer().expect("level must be an integer");
+
+    u32::try_from(level)
+        .ok()
+        .filter(|level| *level <= MAX_COMPRESSION_LEVEL)
+        .ok_or("compression level must be between 0 and 9")
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EncodeGzip;
+
+impl Function for EncodeGzip {
+    fn identifier(&self) -> &'static str {
+        "encode_gzip"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "compression_level",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "round trip",
+            source: r#"decode_gzip!(encode_gzip("please encode me", compression_level: 9))"#,
+            result: Ok("please encode me"),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let compression_level = arguments.optional("compression_level");
+
+        if let Some(literal) = compression_level
+            .as_ref()
+            .and_then(|level| level.as_value())
+        {
+            let _ = get_level(literal.clone()).map_err(|err| {
+                vrl::function::Error::InvalidArgument {
+                    keyword: "compression_level",
+                    value: literal,
+                    error: err,
+                }
+            })?;
+        }
+
+        Ok(Box::new(EncodeGzipFn {
+            value,
+            compression_level,
+        }))
+    }
+
+    fn compile_argument(
+        &self,
+        _args: &[(&'static str, Option<FunctionArgument>)],
+        _ctx: &mut FunctionCompileContext,
+        name: &str,
+        expr: Option<&expression::Expr>,
+    ) -> CompiledArgument {
+        match (name, expr.and_then(|expr| expr.as_value())) {
+            ("compression_level", Some(literal)) => {
+                let _ = get_level(literal.clone()).map_err(|err| {
+                    vrl::function::Error::InvalidArgument {
+                        keyword: "compression_level",
+                        value: literal,
+                        error: err,
+                    }
+                })?;
+
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+        let compression_level = args.optional("compression_level");
+
+        encode_gzip(value, compression_level)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EncodeGzipFn {
+    value: Box<dyn Expression>,
+    compression_level: Option<Box<dyn Expression>>,
+}
+
+impl Expression for EncodeGzipFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let compression_level = self
+            .compression_level
+            .as_ref()
+            .map(|level| level.resolve(ctx))
+            .transpose()?;
+
+        encode_gzip(value, compression_level)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        // Literal compression levels are validated at compile time.
+        match &self.compression_level {
+            Some(level) if level.as_value().is_none() => TypeDef::bytes().fallible(),
+            _ => TypeDef::bytes().infallible(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::read::MultiGzDecoder;
+
+    use super::*;
+
+    fn decode(value: Value) -> String {
+        let value = value.try_bytes().unwrap();
+        let mut output = String::new();
+        MultiGzDecoder::new(value.as_ref())
+            .read_to_string(&mut output)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn round_trip() {
+        for level in [None, Some(0), Some(1), Some(9)] {
+            let encoded = encode_gzip("please encode me".into(), level.map(Value::from)).unwrap();
+
+            assert_eq!(decode(encoded), "please encode me");
+        }
+    }
+
+    #[test]
+    fn invalid_level() {
+        for level in [-1, 10] {
+            let error = encode_gzip("please encode me".into(), Some(level.into())).unwrap_err();
+
+            assert_eq!(
+                error.to_string(),
+                "compression level must be between 0 and 9"
+            );
+        }
+    }
+
+    test_function![
+        encode_gzip => EncodeGzip;
+
+        invalid_literal_level {
+            args: func_args![value: "please encode me", compression_level: 10],
+            want: Err("invalid argument"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+    ];
+}
@@ -0,0 +1,217 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+use crate::util::{encode_field, escape_header, LEEF_HEADER_FIELDS};
+
+fn encode_leef(value: Value, delimiter: Value) -> Resolved {
+    let mut object = value.try_object()?;
+    let delimiter = delimiter.try_bytes_utf8_lossy()?;
+
+    let mut chars = delimiter.chars();
+    let delimiter = match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '=' && c != '\\' => c,
+        _ => return Err(format!("invalid attribute delimiter: {:?}", delimiter).into()),
+    };
+
+    let mut header = Vec::with_capacity(LEEF_HEADER_FIELDS.len() + 1);
+
+    for key in LEEF_HEADER_FIELDS {
+        let field = match object.remove(key).map(|value| encode_field(key, value)) {
+            Some(Ok(Some(field))) => field,
+            Some(Err(err)) => return Err(err.into()),
+            _ if key == "leefVersion" => "2.0".to_owned(),
+            _ => return Err(format!("missing header field `{}`", key).into()),
+        };
+
+        header.push(escape_header(&field).map_err(|err| format!("`{}` {}", key, err))?);
+    }
+
+    // Version 1.0 only supports tabs, 2.0 declares its delimiter in the
+    // header, in hexadecimal unless it's a visible character.
+    if header[0].starts_with('1') {
+        if delimiter != '\t' {
+            return Err("LEEF 1.0 only supports tab delimiters".into());
+        }
+    } else if delimiter.is_ascii_graphic() && delimiter != '|' {
+        header.push(delimiter.to_string());
+    } else {
+        header.push(format!("x{:02X}", u32::from(delimiter)));
+    }
+
+    let mut attributes = Vec::with_capacity(object.len());
+
+    for (key, value) in object {
+        if key.trim().is_empty() || key.contains(|c| c == '=' || c == '\\' || c == delimiter) {
+            return Err(format!("invalid attribute key: {:?}", key).into());
+        }
+
+        if let Some(value) = encode_field(&key, value)? {
+            if value.contains(|c| c == '\n' || c == '\r') {
+                return Err(format!("`{}` attribute can't contain line breaks", key).into());
+            }
+
+            let value = value
+                .replace('\\', r"\\")
+                .replace(delimiter, &format!("\\{}", delimiter));
+
+            attributes.push(format!("{}={}", key, value));
+        }
+    }
+
+    let separator = delimiter.to_string();
+
+    Ok(format!(
+        "LEEF:{}|{}",
+        header.join("|"),
+        attributes.join(separator.as_str())
+    )
+    .into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EncodeLeef;
+
+impl Function for EncodeLeef {
+    fn identifier(&self) -> &'static str {
+        "encode_leef"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::OBJECT,
+                required: true,
+            },
+            Parameter {
+                keyword: "delimiter",
+                kind: kind::BYTES,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "encode object",
+                source: r#"encode_leef!({ "vendor": "Lancope", "product": "StealthWatch", "productVersion": "1.0", "eventId": "41", "src": "10.0.1.8", "sev": 5 }, delimiter: "^")"#,
+                result: Ok(r#"s'LEEF:2.0|Lancope|StealthWatch|1.0|41|^|sev=5^src=10.0.1.8'"#),
+            },
+            Example {
+                title: "encode LEEF 1.0",
+                source: r#"encode_leef!({ "leefVersion": "1.0", "vendor": "Microsoft", "product": "MSExchange", "productVersion": "4.0 SP1", "eventId": "15345", "src": "192.0.2.0", "dst": "172.50.123.1" })"#,
+                result: Ok(
+                    r#""LEEF:1.0|Microsoft|MSExchange|4.0 SP1|15345|dst=172.50.123.1\tsrc=192.0.2.0""#,
+                ),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let delimiter = arguments
+            .optional("delimiter")
+            .unwrap_or_else(|| expr!("\t"));
+
+        Ok(Box::new(EncodeLeefFn { value, delimiter }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+        let delimiter = args
+            .optional("delimiter")
+            .unwrap_or_else(|| Value::from("\t"));
+
+        encode_leef(value, delimiter)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EncodeLeefFn {
+    value: Box<dyn Expression>,
+    delimiter: Box<dyn Expression>,
+}
+
+impl Expression for EncodeLeefFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let delimiter = self.delimiter.resolve(ctx)?;
+
+        encode_leef(value, delimiter)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::bytes().fallible(/* missing header fields or unsupported values */)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use vector_common::btreemap;
+
+    use super::*;
+
+    test_function![
+        encode_leef => EncodeLeef;
+
+        leef_2 {
+            args: func_args![value: btreemap! {
+                "vendor" => "Lancope",
+                "product" => "Stealth|Watch",
+                "productVersion" => "1.0",
+                "eventId" => "41",
+                "src" => "10.0.1.8",
+                "sev" => 5,
+                "devTime" => Utc.timestamp_millis(1_644_865_494_000),
+                "msg" => r"a^b\c=d",
+                "usrName" => Value::Null,
+            }, delimiter: "^"],
+            want: Ok(r"LEEF:2.0|Lancope|Stealth\|Watch|1.0|41|^|devTime=1644865494000^msg=a\^b\\c=d^sev=5^src=10.0.1.8"),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        leef_2_tab {
+            args: func_args![value: btreemap! {
+                "vendor" => "V",
+                "product" => "P",
+                "productVersion" => "1",
+                "eventId" => "id",
+                "a" => 1,
+                "b" => 2,
+            }],
+            want: Ok("LEEF:2.0|V|P|1|id|x09|a=1\tb=2"),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        leef_1_delimiter {
+            args: func_args![value: btreemap! {
+                "leefVersion" => "1.0",
+                "vendor" => "V",
+                "product" => "P",
+                "productVersion" => "1",
+                "eventId" => "id",
+            }, delimiter: "^"],
+            want: Err("LEEF 1.0 only supports tab delimiters"),
+            tdef: TypeDef::bytes().fallible(),
+        }
+
+        line_breaks {
+            args: func_args![value: btreemap! {
+                "vendor" => "V",
+                "product" => "P",
+                "productVersion" => "1",
+                "eventId" => "id",
+                "msg" => "a\nb",
+            }],
+            want: Err("`msg` attribute can't contain line breaks"),
+            tdef: TypeDef::bytes().fallible(),
+        }
+    ];
+}
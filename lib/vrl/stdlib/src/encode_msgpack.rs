@@ -0,0 +1,140 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn encode_msgpack(value: Value) -> Resolved {
+    let mut buffer = Vec::new();
+
+    // Writing to a `Vec` should not be able to fail.
+    match rmpv::encode::write_value(&mut buffer, &to_msgpack(value)) {
+        Ok(()) => Ok(Value::Bytes(buffer.into())),
+        Err(error) => unreachable!("unable to encode to msgpack: {}", error),
+    }
+}
+
+fn to_msgpack(value: Value) -> rmpv::Value {
+    match value {
+        // Valid UTF-8 is encoded as a string, anything else as binary.
+        Value::Bytes(bytes) => match String::from_utf8(bytes.to_vec()) {
+            Ok(string) => string.into(),
+            Err(err) => err.into_bytes().into(),
+        },
+        Value::Regex(regex) => regex.as_str().into(),
+        Value::Integer(integer) => integer.into(),
+        Value::Float(float) => float.into_inner().into(),
+        Value::Boolean(boolean) => boolean.into(),
+        Value::Timestamp(timestamp) => {
+            timestamp_extension(timestamp.timestamp(), timestamp.timestamp_subsec_nanos())
+        }
+        Value::Object(object) => rmpv::Value::Map(
+            object
+                .into_iter()
+                .map(|(key, value)| (key.into(), to_msgpack(value)))
+                .collect(),
+        ),
+        Value::Array(array) => rmpv::Value::Array(array.into_iter().map(to_msgpack).collect()),
+        Value::Null => rmpv::Value::Nil,
+    }
+}
+
+/// Encodes a timestamp using the msgpack timestamp extension, in its 32-bit
+/// format when possible.
+fn timestamp_extension(seconds: i64, nanoseconds: u32) -> rmpv::Value {
+    let bytes = match u32::try_from(seconds) {
+        Ok(seconds) if nanoseconds == 0 => seconds.to_be_bytes().to_vec(),
+        _ => {
+            let mut bytes = nanoseconds.to_be_bytes().to_vec();
+            bytes.extend_from_slice(&seconds.to_be_bytes());
+            bytes
+        }
+    };
+
+    rmpv::Value::Ext(-1, bytes)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EncodeMsgpack;
+
+impl Function for EncodeMsgpack {
+    fn identifier(&self) -> &'static str {
+        "encode_msgpack"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ANY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "encode object",
+            source: r#"encode_base64(encode_msgpack({"field": "value"}))"#,
+            result: Ok("gaVmaWVsZKV2YWx1ZQ=="),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(EncodeMsgpackFn { value }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+
+        encode_msgpack(value)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EncodeMsgpackFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for EncodeMsgpackFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        encode_msgpack(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::bytes().infallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    test_function![
+        encode_msgpack => EncodeMsgpack;
+
+        object {
+            args: func_args![value: value!({ field: "value", list: [1, (-2), 1.5, true, null], binary: (Bytes::from_static(b"\xff")) })],
+            want: Ok(Bytes::from_static(b"\x83\xa6binary\xc4\x01\xff\xa5field\xa5value\xa4list\x95\x01\xfe\xcb\x3f\xf8\x00\x00\x00\x00\x00\x00\xc3\xc0")),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        timestamp {
+            args: func_args![value: Utc.timestamp(1_644_865_494, 0)],
+            want: Ok(Bytes::from_static(b"\xd6\xff\x62\x0a\xa7\xd6")),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        timestamp_nanoseconds {
+            args: func_args![value: Utc.timestamp(1_644_865_494, 1000)],
+            want: Ok(Bytes::from_static(b"\xc7\x0c\xff\x00\x00\x03\xe8\x00\x00\x00\x00\x62\x0a\xa7\xd6")),
+            tdef: TypeDef::bytes().infallible(),
+        }
+    ];
+}
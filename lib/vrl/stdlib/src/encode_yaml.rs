@@ -0,0 +1,97 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn encode_yaml(value: Value) -> Resolved {
+    // With `vrl::Value` it should not be possible to get `Err`.
+    match serde_yaml::to_string(&value) {
+        // The document start marker and the trailing newline are left out,
+        // as the value is usually embedded in another document.
+        Ok(yaml) => {
+            let yaml = yaml.strip_prefix("---\n").unwrap_or(&yaml);
+            let yaml = yaml.strip_suffix('\n').unwrap_or(yaml);
+
+            Ok(yaml.to_owned().into())
+        }
+        Err(error) => unreachable!("unable encode to yaml: {}", error),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EncodeYaml;
+
+impl Function for EncodeYaml {
+    fn identifier(&self) -> &'static str {
+        "encode_yaml"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ANY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "encode object",
+            source: r#"encode_yaml({"service": "api", "replicas": 3})"#,
+            result: Ok(r#""replicas: 3\nservice: api""#),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(EncodeYamlFn { value }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+
+        encode_yaml(value)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EncodeYamlFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for EncodeYamlFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        encode_yaml(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::bytes().infallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        encode_yaml => EncodeYaml;
+
+        object {
+            args: func_args![value: value!({ service: "api", replicas: 3, tls: true })],
+            want: Ok("replicas: 3\nservice: api\ntls: true"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        string {
+            args: func_args![value: "hello"],
+            want: Ok("hello"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+    ];
+}
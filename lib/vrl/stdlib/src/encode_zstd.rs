@@ -0,0 +1,187 @@
+use std::ops::RangeInclusive;
+
+use ::value::Value;
+use vrl::prelude::*;
+
+const COMPRESSION_LEVELS: RangeInclusive<i64> = 1..=21;
+
+fn encode_zstd(value: Value, compression_level: Option<Value>) -> Resolved {
+    let level = match compression_level {
+        None => zstd::DEFAULT_COMPRESSION_LEVEL,
+        Some(value) => get_level(value)?,
+    };
+    let value = value.try_bytes()?;
+
+    // Reading from a byte slice should not be able to fail.
+    let buffer = zstd::stream::encode_all(value.as_ref(), level).expect("zstd compression failed");
+
+    Ok(Value::Bytes(buffer.into()))
+}
+
+fn get_level(value: Value) -> std::result::Result<i32, &'static str> {
+    let level = value.try_integer().expect("level must be an integer");
+
+    if COMPRESSION_LEVELS.contains(&level) {
+        Ok(level as i32)
+    } else {
+        Err("compression level must be between 1 and 21")
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EncodeZstd;
+
+impl Function for EncodeZstd {
+    fn identifier(&self) -> &'static str {
+        "encode_zstd"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "compression_level",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "round trip",
+            source: r#"decode_zstd!(encode_zstd("please encode me", compression_level: 19))"#,
+            result: Ok("please encode me"),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let compression_level = arguments.optional("compression_level");
+
+        if let Some(literal) = compression_level
+            .as_ref()
+            .and_then(|level| level.as_value())
+        {
+            let _ = get_level(literal.clone()).map_err(|err| {
+                vrl::function::Error::InvalidArgument {
+                    keyword: "compression_level",
+                    value: literal,
+                    error: err,
+                }
+            })?;
+        }
+
+        Ok(Box::new(EncodeZstdFn {
+            value,
+            compression_level,
+        }))
+    }
+
+    fn compile_argument(
+        &self,
+        _args: &[(&'static str, Option<FunctionArgument>)],
+        _ctx: &mut FunctionCompileContext,
+        name: &str,
+        expr: Option<&expression::Expr>,
+    ) -> CompiledArgument {
+        match (name, expr.and_then(|expr| expr.as_value())) {
+            ("compression_level", Some(literal)) => {
+                let _ = get_level(literal.clone()).map_err(|err| {
+                    vrl::function::Error::InvalidArgument {
+                        keyword: "compression_level",
+                        value: literal,
+                        error: err,
+                    }
+                })?;
+
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+        let compression_level = args.optional("compression_level");
+
+        encode_zstd(value, compression_level)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EncodeZstdFn {
+    value: Box<dyn Expression>,
+    compression_level: Option<Box<dyn Expression>>,
+}
+
+impl Expression for EncodeZstdFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let compression_level = self
+            .compression_level
+            .as_ref()
+            .map(|level| level.resolve(ctx))
+            .transpose()?;
+
+        encode_zstd(value, compression_level)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        // Literal compression levels are validated at compile time.
+        match &self.compression_level {
+            Some(level) if level.as_value().is_none() => TypeDef::bytes().fallible(),
+            _ => TypeDef::bytes().infallible(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(value: Value) -> String {
+        let value = value.try_bytes().unwrap();
+        String::from_utf8(zstd::stream::decode_all(value.as_ref()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        for level in [None, Some(1), Some(21)] {
+            let encoded = encode_zstd("please encode me".into(), level.map(Value::from)).unwrap();
+
+            assert_eq!(decode(encoded), "please encode me");
+        }
+    }
+
+    #[test]
+    fn invalid_level() {
+        for level in [0, 22] {
+            let error = encode_zstd("please encode me".into(), Some(level.into())).unwrap_err();
+
+            assert_eq!(
+                error.to_string(),
+                "compression level must be between 1 and 21"
+            );
+        }
+    }
+
+    test_function![
+        encode_zstd => EncodeZstd;
+
+        invalid_literal_level {
+            args: func_args![value: "please encode me", compression_level: 22],
+            want: Err("invalid argument"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+    ];
+}
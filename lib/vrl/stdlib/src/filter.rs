@@ -0,0 +1,151 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn filter<T>(value: Value, ctx: &mut Context, runner: closure::Runner<T>) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    match value {
+        Value::Object(object) => {
+            let mut filtered = BTreeMap::new();
+
+            for (key, value) in object {
+                if runner.resolve_key_value(ctx, &key, &value)?.try_boolean()? {
+                    filtered.insert(key, value);
+                }
+            }
+
+            Ok(filtered.into())
+        }
+        Value::Array(array) => {
+            let mut filtered = vec![];
+
+            for (index, value) in array.into_iter().enumerate() {
+                if runner
+                    .resolve_index_value(ctx, index, &value)?
+                    .try_boolean()?
+                {
+                    filtered.push(value);
+                }
+            }
+
+            Ok(filtered.into())
+        }
+        value => Err(value::Error::Expected {
+            got: value.kind(),
+            expected: Kind::object(Collection::any()) | Kind::array(Collection::any()),
+        }
+        .into()),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Filter;
+
+impl Function for Filter {
+    fn identifier(&self) -> &'static str {
+        "filter"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::OBJECT | kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "filter array",
+                source: r#"filter([1, 2, 3, 4]) -> |_index, value| { value > 2 }"#,
+                result: Ok("[3, 4]"),
+            },
+            Example {
+                title: "filter object",
+                source: r#"filter({ "a": 1, "b": 2 }) -> |key, _value| { key != "a" }"#,
+                result: Ok(r#"{ "b": 2 }"#),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(FilterFn { value, closure }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::object(Collection::any()).or_array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(Kind::boolean()),
+                example: Example {
+                    title: "filter array",
+                    source: r#"filter([1, 2]) -> |_index, value| { value != 1 }"#,
+                    result: Ok("[2]"),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+
+    fn call_by_vm(&self, ctx: &mut Context, args: &mut VmArgumentList) -> Result<Value> {
+        let value = args.required("value");
+        let VmFunctionClosure { variables, vm } = args.closure();
+        let runner = closure::Runner::new(variables, |ctx| vm.interpret(ctx));
+
+        filter(value, ctx, runner)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FilterFn {
+    value: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for FilterFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        filter(value, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let value = self.value.type_def(ctx);
+        let fallible = self.closure.block.type_def(ctx).is_fallible();
+
+        // Any element might be filtered out, so none of them are known to
+        // exist anymore.
+        let mut kind = Kind::empty();
+        if let Some(object) = value.as_object() {
+            kind.add_object(Collection::from_unknown(object.reduced_kind()));
+        }
+        if let Some(array) = value.as_array() {
+            kind.add_array(Collection::from_unknown(array.reduced_kind()));
+        }
+
+        TypeDef::from(kind).with_fallibility(fallible)
+    }
+}
@@ -0,0 +1,125 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn group_by<T>(value: Value, ctx: &mut Context, runner: closure::Runner<T>) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    let mut groups: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+
+    for (index, value) in value.try_array()?.into_iter().enumerate() {
+        let key = runner.resolve_index_value(ctx, index, &value)?;
+        let key = key.try_bytes_utf8_lossy()?.into_owned();
+
+        groups.entry(key).or_default().push(value);
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|(key, values)| (key, values.into()))
+        .collect::<BTreeMap<_, Value>>()
+        .into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GroupBy;
+
+impl Function for GroupBy {
+    fn identifier(&self) -> &'static str {
+        "group_by"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "group by field",
+            source: r#"group_by([{ "level": "info", "n": 1 }, { "level": "warn", "n": 2 }, { "level": "info", "n": 3 }]) -> |_index, value| { value.level }"#,
+            result: Ok(
+                r#"{ "info": [{ "level": "info", "n": 1 }, { "level": "info", "n": 3 }], "warn": [{ "level": "warn", "n": 2 }] }"#,
+            ),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(GroupByFn { value, closure }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(Kind::bytes()),
+                example: Example {
+                    title: "group by parity",
+                    source: r#"group_by([1, 2, 3]) -> |_index, value| { if value % 2 == 0 { "even" } else { "odd" } }"#,
+                    result: Ok(r#"{ "even": [2], "odd": [1, 3] }"#),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+
+    fn call_by_vm(&self, ctx: &mut Context, args: &mut VmArgumentList) -> Result<Value> {
+        let value = args.required("value");
+        let VmFunctionClosure { variables, vm } = args.closure();
+        let runner = closure::Runner::new(variables, |ctx| vm.interpret(ctx));
+
+        group_by(value, ctx, runner)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GroupByFn {
+    value: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for GroupByFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        group_by(value, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let fallible = self.closure.block.type_def(ctx).is_fallible();
+        let element = self
+            .value
+            .type_def(ctx)
+            .as_array()
+            .map_or_else(Kind::any, Collection::reduced_kind);
+
+        TypeDef::object(Collection::from_unknown(Kind::array(
+            Collection::from_unknown(element),
+        )))
+        .with_fallibility(fallible)
+    }
+}
@@ -0,0 +1,222 @@
+use ::value::Value;
+use crypto_hmac::Mac;
+use sha_2::{Sha224, Sha256, Sha384, Sha512};
+use vrl::prelude::*;
+
+macro_rules! sign {
+    ($digest:ty, $key:expr, $value:expr) => {{
+        let mut mac = <crypto_hmac::Hmac<$digest> as Mac>::new_from_slice($key)
+            .expect("HMAC accepts keys of any size");
+        mac.update($value);
+        mac.finalize().into_bytes().to_vec()
+    }};
+}
+
+fn hmac(value: Value, key: Value, algorithm: &Bytes, encoding: &Bytes) -> Resolved {
+    let value = value.try_bytes()?;
+    let key = key.try_bytes()?;
+    let mac = match algorithm.as_ref() {
+        b"SHA1" => sign!(sha1::Sha1, &key, &value),
+        b"SHA-224" => sign!(Sha224, &key, &value),
+        b"SHA-256" => sign!(Sha256, &key, &value),
+        b"SHA-384" => sign!(Sha384, &key, &value),
+        b"SHA-512" => sign!(Sha512, &key, &value),
+        _ => unreachable!("enum invariant"),
+    };
+    let mac = match encoding.as_ref() {
+        b"hex" => hex::encode(mac),
+        b"base64" => base64::encode(mac),
+        _ => unreachable!("enum invariant"),
+    };
+
+    Ok(mac.into())
+}
+
+fn algorithms() -> Vec<Value> {
+    vec![
+        value!("SHA1"),
+        value!("SHA-224"),
+        value!("SHA-256"),
+        value!("SHA-384"),
+        value!("SHA-512"),
+    ]
+}
+
+fn encodings() -> Vec<Value> {
+    vec![value!("hex"), value!("base64")]
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Hmac;
+
+impl Function for Hmac {
+    fn identifier(&self) -> &'static str {
+        "hmac"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "key",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "algorithm",
+                kind: kind::BYTES,
+                required: false,
+            },
+            Parameter {
+                keyword: "encoding",
+                kind: kind::BYTES,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "default algorithm",
+                source: r#"hmac("Hello there", "super-secret-key")"#,
+                result: Ok("78b184f1832f8aff3934f5e0212454671b2d04d494e3b25075c5e45167029662"),
+            },
+            Example {
+                title: "custom algorithm and encoding",
+                source: r#"hmac("Hello there", "super-secret-key", algorithm: "SHA1", encoding: "base64")"#,
+                result: Ok("MiyBIHO8Set9+6crALiwkS0yFPE="),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let key = arguments.required("key");
+        let algorithm = arguments
+            .optional_enum("algorithm", &algorithms())?
+            .unwrap_or_else(|| value!("SHA-256"))
+            .try_bytes()
+            .expect("algorithm not bytes");
+        let encoding = arguments
+            .optional_enum("encoding", &encodings())?
+            .unwrap_or_else(|| value!("hex"))
+            .try_bytes()
+            .expect("encoding not bytes");
+
+        Ok(Box::new(HmacFn {
+            value,
+            key,
+            algorithm,
+            encoding,
+        }))
+    }
+
+    fn compile_argument(
+        &self,
+        _args: &[(&'static str, Option<FunctionArgument>)],
+        _ctx: &mut FunctionCompileContext,
+        name: &str,
+        expr: Option<&expression::Expr>,
+    ) -> CompiledArgument {
+        match (name, expr) {
+            ("algorithm", Some(expr)) => {
+                let algorithm = expr
+                    .as_enum("algorithm", algorithms())?
+                    .try_bytes()
+                    .expect("algorithm not bytes");
+
+                Ok(Some(Box::new(algorithm) as _))
+            }
+            ("algorithm", None) => Ok(Some(Box::new(Bytes::from("SHA-256")) as _)),
+            ("encoding", Some(expr)) => {
+                let encoding = expr
+                    .as_enum("encoding", encodings())?
+                    .try_bytes()
+                    .expect("encoding not bytes");
+
+                Ok(Some(Box::new(encoding) as _))
+            }
+            ("encoding", None) => Ok(Some(Box::new(Bytes::from("hex")) as _)),
+            _ => Ok(None),
+        }
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+        let key = args.required("key");
+        let algorithm = args
+            .required_any("algorithm")
+            .downcast_ref::<Bytes>()
+            .unwrap();
+        let encoding = args
+            .required_any("encoding")
+            .downcast_ref::<Bytes>()
+            .unwrap();
+
+        hmac(value, key, algorithm, encoding)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HmacFn {
+    value: Box<dyn Expression>,
+    key: Box<dyn Expression>,
+    algorithm: Bytes,
+    encoding: Bytes,
+}
+
+impl Expression for HmacFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let key = self.key.resolve(ctx)?;
+
+        hmac(value, key, &self.algorithm, &self.encoding)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::bytes().infallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        hmac => Hmac;
+
+        sha_256 {
+            args: func_args![value: "The quick brown fox jumps over the lazy dog", key: "key"],
+            want: Ok("f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        sha_1 {
+            args: func_args![value: "The quick brown fox jumps over the lazy dog", key: "key", algorithm: "SHA1"],
+            want: Ok("de7c9b85b8b78aa6bc8a7a36f70a90701c9db4d9"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        sha_512 {
+            args: func_args![value: "The quick brown fox jumps over the lazy dog", key: "key", algorithm: "SHA-512"],
+            want: Ok("b42af09057bac1e2d41708e48a902e09b5ff7f12ab428a4fe86653c73dd248fb82f948a549f7b791a5b41915ee4d1ec3935357e4e2317250d0372afa2ebeeb3a"),
+            tdef: TypeDef::bytes().infallible(),
+        }
+
+        base64_encoding {
+            args: func_args![value: "The quick brown fox jumps over the lazy dog", key: "key", encoding: "base64"],
+            want: Ok("97yD9DBThCSxMpjmqm+xQ+9NWaFJRhdZl0edvC0aPNg="),
+            tdef: TypeDef::bytes().infallible(),
+        }
+    ];
+}
@@ -7,6 +7,10 @@
 
 mod util;
 
+#[cfg(feature = "all")]
+mod all;
+#[cfg(feature = "any")]
+mod any;
 #[cfg(feature = "append")]
 mod append;
 #[cfg(feature = "array")]
@@ -25,8 +29,12 @@ mod compact;
 mod contains;
 #[cfg(feature = "decode_base64")]
 mod decode_base64;
+#[cfg(feature = "decode_gzip")]
+mod decode_gzip;
 #[cfg(feature = "decode_percent")]
 mod decode_percent;
+#[cfg(feature = "decode_zstd")]
+mod decode_zstd;
 #[cfg(feature = "decrypt")]
 mod decrypt;
 #[cfg(feature = "del")]
@@ -35,20 +43,34 @@ mod del;
 mod downcase;
 #[cfg(feature = "encode_base64")]
 mod encode_base64;
+#[cfg(feature = "encode_cef")]
+mod encode_cef;
+#[cfg(feature = "encode_gzip")]
+mod encode_gzip;
 #[cfg(feature = "encode_json")]
 mod encode_json;
 #[cfg(feature = "encode_key_value")]
 mod encode_key_value;
+#[cfg(feature = "encode_leef")]
+mod encode_leef;
 #[cfg(feature = "encode_logfmt")]
 mod encode_logfmt;
+#[cfg(feature = "encode_msgpack")]
+mod encode_msgpack;
 #[cfg(feature = "encode_percent")]
 mod encode_percent;
+#[cfg(feature = "encode_yaml")]
+mod encode_yaml;
+#[cfg(feature = "encode_zstd")]
+mod encode_zstd;
 #[cfg(feature = "encrypt")]
 mod encrypt;
 #[cfg(feature = "ends_with")]
 mod ends_with;
 #[cfg(feature = "exists")]
 mod exists;
+#[cfg(feature = "filter")]
+mod filter;
 #[cfg(feature = "find")]
 mod find;
 #[cfg(feature = "flatten")]
@@ -71,6 +93,10 @@ mod get;
 mod get_env_var;
 #[cfg(feature = "get_hostname")]
 mod get_hostname;
+#[cfg(feature = "group_by")]
+mod group_by;
+#[cfg(feature = "hmac")]
+mod hmac;
 #[cfg(feature = "includes")]
 mod includes;
 #[cfg(feature = "integer")]
@@ -125,6 +151,8 @@ mod log;
     feature = "parse_nginx_log"
 ))]
 mod log_util;
+#[cfg(feature = "map")]
+mod map;
 #[cfg(feature = "map_keys")]
 mod map_keys;
 #[cfg(feature = "map_values")]
@@ -155,6 +183,10 @@ mod parse_aws_alb_log;
 mod parse_aws_cloudwatch_log_subscription_message;
 #[cfg(feature = "parse_aws_vpc_flow_log")]
 mod parse_aws_vpc_flow_log;
+#[cfg(feature = "parse_cbor")]
+mod parse_cbor;
+#[cfg(feature = "parse_cef")]
+mod parse_cef;
 #[cfg(feature = "parse_common_log")]
 mod parse_common_log;
 #[cfg(feature = "parse_csv")]
@@ -171,14 +203,20 @@ mod parse_groks;
 mod parse_int;
 #[cfg(feature = "parse_json")]
 mod parse_json;
+#[cfg(feature = "parse_jwt")]
+mod parse_jwt;
 #[cfg(feature = "parse_key_value")]
 mod parse_key_value;
 #[cfg(feature = "parse_klog")]
 mod parse_klog;
+#[cfg(feature = "parse_leef")]
+mod parse_leef;
 #[cfg(feature = "parse_linux_authorization")]
 mod parse_linux_authorization;
 #[cfg(feature = "parse_logfmt")]
 mod parse_logfmt;
+#[cfg(feature = "parse_msgpack")]
+mod parse_msgpack;
 #[cfg(feature = "parse_nginx_log")]
 mod parse_nginx_log;
 #[cfg(feature = "parse_query_string")]
@@ -195,18 +233,24 @@ mod parse_syslog;
 mod parse_timestamp;
 #[cfg(feature = "parse_tokens")]
 mod parse_tokens;
+#[cfg(feature = "parse_toml")]
+mod parse_toml;
 #[cfg(feature = "parse_url")]
 mod parse_url;
 #[cfg(feature = "parse_user_agent")]
 mod parse_user_agent;
 #[cfg(feature = "parse_xml")]
 mod parse_xml;
+#[cfg(feature = "parse_yaml")]
+mod parse_yaml;
 #[cfg(feature = "push")]
 mod push;
 #[cfg(feature = "random_bytes")]
 mod random_bytes;
 #[cfg(feature = "redact")]
 mod redact;
+#[cfg(feature = "reduce")]
+mod reduce;
 #[cfg(feature = "remove")]
 mod remove;
 #[cfg(feature = "replace")]
@@ -225,6 +269,8 @@ mod sha2;
 mod sha3;
 #[cfg(feature = "slice")]
 mod slice;
+#[cfg(feature = "sort_by")]
+mod sort_by;
 #[cfg(feature = "split")]
 mod split;
 #[cfg(feature = "starts_with")]
@@ -277,9 +323,15 @@ mod unnest;
 mod upcase;
 #[cfg(feature = "uuid_v4")]
 mod uuid_v4;
+#[cfg(feature = "verify_jwt")]
+mod verify_jwt;
 
 // -----------------------------------------------------------------------------
 
+#[cfg(feature = "all")]
+pub use all::All;
+#[cfg(feature = "any")]
+pub use any::Any;
 #[cfg(feature = "append")]
 pub use append::Append;
 #[cfg(feature = "assert")]
@@ -296,8 +348,12 @@ pub use compact::Compact;
 pub use contains::Contains;
 #[cfg(feature = "decode_base64")]
 pub use decode_base64::DecodeBase64;
+#[cfg(feature = "decode_gzip")]
+pub use decode_gzip::DecodeGzip;
 #[cfg(feature = "decode_percent")]
 pub use decode_percent::DecodePercent;
+#[cfg(feature = "decode_zstd")]
+pub use decode_zstd::DecodeZstd;
 #[cfg(feature = "decrypt")]
 pub use decrypt::Decrypt;
 #[cfg(feature = "del")]
@@ -306,20 +362,34 @@ pub use del::Del;
 pub use downcase::Downcase;
 #[cfg(feature = "encode_base64")]
 pub use encode_base64::EncodeBase64;
+#[cfg(feature = "encode_cef")]
+pub use encode_cef::EncodeCef;
+#[cfg(feature = "encode_gzip")]
+pub use encode_gzip::EncodeGzip;
 #[cfg(feature = "encode_json")]
 pub use encode_json::EncodeJson;
 #[cfg(feature = "encode_key_value")]
 pub use encode_key_value::EncodeKeyValue;
+#[cfg(feature = "encode_leef")]
+pub use encode_leef::EncodeLeef;
 #[cfg(feature = "encode_logfmt")]
 pub use encode_logfmt::EncodeLogfmt;
+#[cfg(feature = "encode_msgpack")]
+pub use encode_msgpack::EncodeMsgpack;
 #[cfg(feature = "encode_percent")]
 pub use encode_percent::EncodePercent;
+#[cfg(feature = "encode_yaml")]
+pub use encode_yaml::EncodeYaml;
+#[cfg(feature = "encode_zstd")]
+pub use encode_zstd::EncodeZstd;
 #[cfg(feature = "encrypt")]
 pub use encrypt::Encrypt;
 #[cfg(feature = "ends_with")]
 pub use ends_with::EndsWith;
 #[cfg(feature = "exists")]
 pub use exists::Exists;
+#[cfg(feature = "filter")]
+pub use filter::Filter;
 #[cfg(feature = "find")]
 pub use find::Find;
 #[cfg(feature = "flatten")]
@@ -342,6 +412,10 @@ pub use get::Get;
 pub use get_env_var::GetEnvVar;
 #[cfg(feature = "get_hostname")]
 pub use get_hostname::GetHostname;
+#[cfg(feature = "group_by")]
+pub use group_by::GroupBy;
+#[cfg(feature = "hmac")]
+pub use hmac::Hmac;
 #[cfg(feature = "includes")]
 pub use includes::Includes;
 #[cfg(feature = "integer")]
@@ -390,6 +464,8 @@ pub use join::Join;
 pub use length::Length;
 #[cfg(feature = "log")]
 pub use log::Log;
+#[cfg(feature = "map")]
+pub use map::Map;
 #[cfg(feature = "map_keys")]
 pub use map_keys::MapKeys;
 #[cfg(feature = "map_values")]
@@ -416,6 +492,10 @@ pub use parse_aws_alb_log::ParseAwsAlbLog;
 pub use parse_aws_cloudwatch_log_subscription_message::ParseAwsCloudWatchLogSubscriptionMessage;
 #[cfg(feature = "parse_aws_vpc_flow_log")]
 pub use parse_aws_vpc_flow_log::ParseAwsVpcFlowLog;
+#[cfg(feature = "parse_cbor")]
+pub use parse_cbor::ParseCbor;
+#[cfg(feature = "parse_cef")]
+pub use parse_cef::ParseCef;
 #[cfg(feature = "parse_common_log")]
 pub use parse_common_log::ParseCommonLog;
 #[cfg(feature = "parse_csv")]
@@ -432,14 +512,20 @@ pub use parse_groks::ParseGroks;
 pub use parse_int::ParseInt;
 #[cfg(feature = "parse_json")]
 pub use parse_json::ParseJson;
+#[cfg(feature = "parse_jwt")]
+pub use parse_jwt::ParseJwt;
 #[cfg(feature = "parse_key_value")]
 pub use parse_key_value::ParseKeyValue;
 #[cfg(feature = "parse_klog")]
 pub use parse_klog::ParseKlog;
+#[cfg(feature = "parse_leef")]
+pub use parse_leef::ParseLeef;
 #[cfg(feature = "parse_linux_authorization")]
 pub use parse_linux_authorization::ParseLinuxAuthorization;
 #[cfg(feature = "parse_logfmt")]
 pub use parse_logfmt::ParseLogFmt;
+#[cfg(feature = "parse_msgpack")]
+pub use parse_msgpack::ParseMsgpack;
 #[cfg(feature = "parse_nginx_log")]
 pub use parse_nginx_log::ParseNginxLog;
 #[cfg(feature = "parse_query_string")]
@@ -456,12 +542,16 @@ pub use parse_syslog::ParseSyslog;
 pub use parse_timestamp::ParseTimestamp;
 #[cfg(feature = "parse_tokens")]
 pub use parse_tokens::ParseTokens;
+#[cfg(feature = "parse_toml")]
+pub use parse_toml::ParseToml;
 #[cfg(feature = "parse_url")]
 pub use parse_url::ParseUrl;
 #[cfg(feature = "parse_user_agent")]
 pub use parse_user_agent::ParseUserAgent;
 #[cfg(feature = "parse_xml")]
 pub use parse_xml::ParseXml;
+#[cfg(feature = "parse_yaml")]
+pub use parse_yaml::ParseYaml;
 #[cfg(feature = "push")]
 pub use push::Push;
 #[cfg(feature = "match")]
@@ -470,6 +560,8 @@ pub use r#match::Match;
 pub use random_bytes::RandomBytes;
 #[cfg(feature = "redact")]
 pub use redact::Redact;
+#[cfg(feature = "reduce")]
+pub use reduce::Reduce;
 #[cfg(feature = "remove")]
 pub use remove::Remove;
 #[cfg(feature = "replace")]
@@ -486,6 +578,8 @@ pub use sha2::Sha2;
 pub use sha3::Sha3;
 #[cfg(feature = "slice")]
 pub use slice::Slice;
+#[cfg(feature = "sort_by")]
+pub use sort_by::SortBy;
 #[cfg(feature = "split")]
 pub use split::Split;
 #[cfg(feature = "starts_with")]
@@ -538,6 +632,8 @@ pub use unnest::Unnest;
 pub use upcase::Upcase;
 #[cfg(feature = "uuid_v4")]
 pub use uuid_v4::UuidV4;
+#[cfg(feature = "verify_jwt")]
+pub use verify_jwt::VerifyJwt;
 
 #[cfg(feature = "array")]
 pub use crate::array::Array;
@@ -548,6 +644,10 @@ pub use crate::sha1::Sha1;
 
 pub fn all() -> Vec<Box<dyn vrl::Function>> {
     vec![
+        #[cfg(feature = "all")]
+        Box::new(All),
+        #[cfg(feature = "any")]
+        Box::new(Any),
         #[cfg(feature = "append")]
         Box::new(Append),
         #[cfg(feature = "array")]
@@ -566,8 +666,12 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(Contains),
         #[cfg(feature = "decode_base64")]
         Box::new(DecodeBase64),
+        #[cfg(feature = "decode_gzip")]
+        Box::new(DecodeGzip),
         #[cfg(feature = "decode_percent")]
         Box::new(DecodePercent),
+        #[cfg(feature = "decode_zstd")]
+        Box::new(DecodeZstd),
         #[cfg(feature = "decrypt")]
         Box::new(Decrypt),
         #[cfg(feature = "del")]
@@ -576,20 +680,34 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(Downcase),
         #[cfg(feature = "encode_base64")]
         Box::new(EncodeBase64),
+        #[cfg(feature = "encode_cef")]
+        Box::new(EncodeCef),
+        #[cfg(feature = "encode_gzip")]
+        Box::new(EncodeGzip),
         #[cfg(feature = "encode_json")]
         Box::new(EncodeJson),
         #[cfg(feature = "encode_key_value")]
         Box::new(EncodeKeyValue),
+        #[cfg(feature = "encode_leef")]
+        Box::new(EncodeLeef),
         #[cfg(feature = "encode_logfmt")]
         Box::new(EncodeLogfmt),
+        #[cfg(feature = "encode_msgpack")]
+        Box::new(EncodeMsgpack),
         #[cfg(feature = "encode_percent")]
         Box::new(EncodePercent),
+        #[cfg(feature = "encode_yaml")]
+        Box::new(EncodeYaml),
+        #[cfg(feature = "encode_zstd")]
+        Box::new(EncodeZstd),
         #[cfg(feature = "encrypt")]
         Box::new(Encrypt),
         #[cfg(feature = "ends_with")]
         Box::new(EndsWith),
         #[cfg(feature = "exists")]
         Box::new(Exists),
+        #[cfg(feature = "filter")]
+        Box::new(Filter),
         #[cfg(feature = "find")]
         Box::new(Find),
         #[cfg(feature = "flatten")]
@@ -612,6 +730,10 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(GetEnvVar),
         #[cfg(feature = "get_hostname")]
         Box::new(GetHostname),
+        #[cfg(feature = "group_by")]
+        Box::new(GroupBy),
+        #[cfg(feature = "hmac")]
+        Box::new(Hmac),
         #[cfg(feature = "includes")]
         Box::new(Includes),
         #[cfg(feature = "integer")]
@@ -660,6 +782,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(Length),
         #[cfg(feature = "log")]
         Box::new(Log),
+        #[cfg(feature = "map")]
+        Box::new(Map),
         #[cfg(feature = "map_keys")]
         Box::new(MapKeys),
         #[cfg(feature = "map_values")]
@@ -692,6 +816,10 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ParseAwsCloudWatchLogSubscriptionMessage),
         #[cfg(feature = "parse_aws_vpc_flow_log")]
         Box::new(ParseAwsVpcFlowLog),
+        #[cfg(feature = "parse_cbor")]
+        Box::new(ParseCbor),
+        #[cfg(feature = "parse_cef")]
+        Box::new(ParseCef),
         #[cfg(feature = "parse_common_log")]
         Box::new(ParseCommonLog),
         #[cfg(feature = "parse_csv")]
@@ -708,14 +836,20 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ParseInt),
         #[cfg(feature = "parse_json")]
         Box::new(ParseJson),
+        #[cfg(feature = "parse_jwt")]
+        Box::new(ParseJwt),
         #[cfg(feature = "parse_key_value")]
         Box::new(ParseKeyValue),
         #[cfg(feature = "parse_klog")]
         Box::new(ParseKlog),
+        #[cfg(feature = "parse_leef")]
+        Box::new(ParseLeef),
         #[cfg(feature = "parse_linux_authorization")]
         Box::new(ParseLinuxAuthorization),
         #[cfg(feature = "parse_logfmt")]
         Box::new(ParseLogFmt),
+        #[cfg(feature = "parse_msgpack")]
+        Box::new(ParseMsgpack),
         #[cfg(feature = "parse_nginx_log")]
         Box::new(ParseNginxLog),
         #[cfg(feature = "parse_query_string")]
@@ -732,18 +866,24 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ParseTimestamp),
         #[cfg(feature = "parse_tokens")]
         Box::new(ParseTokens),
+        #[cfg(feature = "parse_toml")]
+        Box::new(ParseToml),
         #[cfg(feature = "parse_url")]
         Box::new(ParseUrl),
         #[cfg(feature = "parse_user_agent")]
         Box::new(ParseUserAgent),
         #[cfg(feature = "parse_xml")]
         Box::new(ParseXml),
+        #[cfg(feature = "parse_yaml")]
+        Box::new(ParseYaml),
         #[cfg(feature = "push")]
         Box::new(Push),
         #[cfg(feature = "random_bytes")]
         Box::new(RandomBytes),
         #[cfg(feature = "redact")]
         Box::new(Redact),
+        #[cfg(feature = "reduce")]
+        Box::new(Reduce),
         #[cfg(feature = "remove")]
         Box::new(Remove),
         #[cfg(feature = "replace")]
@@ -762,6 +902,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(Sha3),
         #[cfg(feature = "slice")]
         Box::new(Slice),
+        #[cfg(feature = "sort_by")]
+        Box::new(SortBy),
         #[cfg(feature = "split")]
         Box::new(Split),
         #[cfg(feature = "starts_with")]
@@ -814,5 +956,7 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(Upcase),
         #[cfg(feature = "uuid_v4")]
         Box::new(UuidV4),
+        #[cfg(feature = "verify_jwt")]
+        Box::new(VerifyJwt),
     ]
 }
@@ -0,0 +1,132 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn map<T>(value: Value, ctx: &mut Context, runner: closure::Runner<T>) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    let mut mapped = vec![];
+
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                mapped.push(runner.resolve_key_value(ctx, &key, &value)?);
+            }
+        }
+        Value::Array(array) => {
+            for (index, value) in array.into_iter().enumerate() {
+                mapped.push(runner.resolve_index_value(ctx, index, &value)?);
+            }
+        }
+        value => {
+            return Err(value::Error::Expected {
+                got: value.kind(),
+                expected: Kind::object(Collection::any()) | Kind::array(Collection::any()),
+            }
+            .into())
+        }
+    }
+
+    Ok(mapped.into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Map;
+
+impl Function for Map {
+    fn identifier(&self) -> &'static str {
+        "map"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::OBJECT | kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "map array",
+                source: r#"map([1, 2, 3]) -> |_index, value| { value * 2 }"#,
+                result: Ok("[2, 4, 6]"),
+            },
+            Example {
+                title: "map object",
+                source: r#"map({ "a": 1, "b": 2 }) -> |key, value| { key + "=" + to_string(value) }"#,
+                result: Ok(r#"["a=1", "b=2"]"#),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(MapFn { value, closure }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::object(Collection::any()).or_array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(Kind::any()),
+                example: Example {
+                    title: "map array",
+                    source: r#"map(["a", "b"]) -> |_index, value| { upcase(value) }"#,
+                    result: Ok(r#"["A", "B"]"#),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+
+    fn call_by_vm(&self, ctx: &mut Context, args: &mut VmArgumentList) -> Result<Value> {
+        let value = args.required("value");
+        let VmFunctionClosure { variables, vm } = args.closure();
+        let runner = closure::Runner::new(variables, |ctx| vm.interpret(ctx));
+
+        map(value, ctx, runner)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MapFn {
+    value: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for MapFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        map(value, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let closure = self.closure.block.type_def(ctx);
+
+        TypeDef::array(Collection::from_unknown(closure.kind().clone()))
+            .with_fallibility(closure.is_fallible())
+    }
+}
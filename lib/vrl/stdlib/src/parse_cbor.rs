@@ -0,0 +1,164 @@
+use ::value::Value;
+use serde_cbor::Value as CborValue;
+use vrl::prelude::*;
+
+fn parse_cbor(value: Value) -> Resolved {
+    let bytes = value.try_bytes()?;
+    let cbor = serde_cbor::from_slice::<CborValue>(&bytes)
+        .map_err(|err| format!("unable to parse cbor: {}", err))?;
+
+    Ok(from_cbor(cbor).map_err(|err| format!("unable to parse cbor: {}", err))?)
+}
+
+fn from_cbor(cbor: CborValue) -> std::result::Result<Value, String> {
+    Ok(match cbor {
+        CborValue::Null => Value::Null,
+        CborValue::Bool(boolean) => boolean.into(),
+        CborValue::Integer(integer) => i64::try_from(integer)
+            .map_err(|_| format!("integer out of range: {}", integer))?
+            .into(),
+        // NaN is converted to null, as VRL floats can't be NaN.
+        CborValue::Float(float) => NotNan::new(float).map(Value::Float).unwrap_or(Value::Null),
+        CborValue::Bytes(bytes) => Value::Bytes(bytes.into()),
+        CborValue::Text(text) => text.into(),
+        CborValue::Array(array) => array
+            .into_iter()
+            .map(from_cbor)
+            .collect::<std::result::Result<Vec<_>, _>>()?
+            .into(),
+        CborValue::Map(map) => map
+            .into_iter()
+            .map(|(key, value)| Ok((map_key(key)?, from_cbor(value)?)))
+            .collect::<std::result::Result<BTreeMap<_, _>, String>>()?
+            .into(),
+        // Semantic tags carry no information VRL can represent.
+        CborValue::Tag(_, value) => from_cbor(*value)?,
+        _ => return Err("unsupported value".to_owned()),
+    })
+}
+
+/// Object keys are strings, so integer keys are converted to their string
+/// representation.
+fn map_key(key: CborValue) -> std::result::Result<String, String> {
+    match key {
+        CborValue::Text(text) => Ok(text),
+        CborValue::Integer(integer) => Ok(integer.to_string()),
+        _ => Err("map keys must be strings or integers".to_owned()),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParseCbor;
+
+impl Function for ParseCbor {
+    fn identifier(&self) -> &'static str {
+        "parse_cbor"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "object",
+            source: r#"parse_cbor!(decode_base64!("oWVmaWVsZGV2YWx1ZQ=="))"#,
+            result: Ok(r#"{ "field": "value" }"#),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(ParseCborFn { value }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+
+        parse_cbor(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseCborFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for ParseCborFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        parse_cbor(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        type_def()
+    }
+}
+
+fn inner_kind() -> Kind {
+    Kind::null()
+        | Kind::bytes()
+        | Kind::integer()
+        | Kind::float()
+        | Kind::boolean()
+        | Kind::array(Collection::any())
+        | Kind::object(Collection::any())
+}
+
+fn type_def() -> TypeDef {
+    TypeDef::bytes()
+        .fallible()
+        .add_boolean()
+        .add_integer()
+        .add_float()
+        .add_null()
+        .add_array(Collection::from_unknown(inner_kind()))
+        .add_object(Collection::from_unknown(inner_kind()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        parse_cbor => ParseCbor;
+
+        object {
+            // {"field": "value", "list": [1, -2, 1.5, true, null], 10: h'0102'}
+            args: func_args![value: Bytes::from_static(b"\xa3\x0a\x42\x01\x02\x64list\x85\x01\x21\xf9\x3e\x00\xf5\xf6\x65field\x65value")],
+            want: Ok(value!({ "10": (Bytes::from_static(b"\x01\x02")), field: "value", list: [1, (-2), 1.5, true, null] })),
+            tdef: type_def(),
+        }
+
+        tagged {
+            // 1(1644865494)
+            args: func_args![value: Bytes::from_static(b"\xc1\x1a\x62\x0a\xa7\xd6")],
+            want: Ok(1_644_865_494),
+            tdef: type_def(),
+        }
+
+        out_of_range {
+            // 18446744073709551615
+            args: func_args![value: Bytes::from_static(b"\x1b\xff\xff\xff\xff\xff\xff\xff\xff")],
+            want: Err("unable to parse cbor: integer out of range: 18446744073709551615"),
+            tdef: type_def(),
+        }
+
+        truncated {
+            args: func_args![value: Bytes::from_static(b"\xa1\x65field")],
+            want: Err("unable to parse cbor: EOF while parsing a value at offset 7"),
+            tdef: type_def(),
+        }
+    ];
+}
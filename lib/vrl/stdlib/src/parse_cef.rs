@@ -0,0 +1,452 @@
+use std::collections::BTreeMap;
+
+use ::value::Value;
+use vector_common::TimeZone;
+use vrl::prelude::*;
+
+use crate::util::{is_cef_key_char, parse_log_timestamp, split_header, CEF_HEADER_FIELDS};
+
+/// The extension keys holding integers.
+const INTEGER_KEYS: &[&str] = &[
+    "cn1",
+    "cn2",
+    "cn3",
+    "cnt",
+    "destinationTranslatedPort",
+    "deviceDirection",
+    "dpid",
+    "dpt",
+    "dvcpid",
+    "flexNumber1",
+    "flexNumber2",
+    "fsize",
+    "in",
+    "oldFileSize",
+    "out",
+    "sourceTranslatedPort",
+    "spid",
+    "spt",
+    "type",
+];
+
+/// The extension keys holding floats.
+const FLOAT_KEYS: &[&str] = &[
+    "cfp1", "cfp2", "cfp3", "cfp4", "dlat", "dlong", "slat", "slong",
+];
+
+/// The extension keys holding timestamps.
+const TIMESTAMP_KEYS: &[&str] = &[
+    "art",
+    "deviceCustomDate1",
+    "deviceCustomDate2",
+    "end",
+    "fileCreateTime",
+    "fileModificationTime",
+    "flexDate1",
+    "oldFileCreateTime",
+    "oldFileModificationTime",
+    "rt",
+    "start",
+];
+
+fn parse_cef(value: Value, translate_custom_fields: bool, tz: &TimeZone) -> Resolved {
+    let bytes = value.try_bytes()?;
+    let input = String::from_utf8_lossy(&bytes);
+
+    parse_log(&input, translate_custom_fields, tz)
+        .map(Into::into)
+        .map_err(|err| format!("unable to parse CEF message: {}", err).into())
+}
+
+fn parse_log(
+    input: &str,
+    translate_custom_fields: bool,
+    tz: &TimeZone,
+) -> std::result::Result<BTreeMap<String, Value>, String> {
+    // The message is usually prefixed by a syslog header.
+    let start = input.find("CEF:").ok_or("missing `CEF:` prefix")?;
+    let (header, extension) = split_header(&input[start + 4..], CEF_HEADER_FIELDS.len())?;
+
+    let mut log = BTreeMap::new();
+
+    for (key, value) in parse_extension(extension)? {
+        log.insert(key.to_owned(), coerce(key, value, tz));
+    }
+
+    if translate_custom_fields {
+        translate(&mut log);
+    }
+
+    for (key, value) in CEF_HEADER_FIELDS.iter().zip(header) {
+        let value: Value = match *key {
+            "cefVersion" | "severity" => value
+                .trim()
+                .parse::<i64>()
+                .map_or_else(|_| value.into(), Into::into),
+            _ => value.into(),
+        };
+
+        log.insert((*key).to_owned(), value);
+    }
+
+    Ok(log)
+}
+
+/// Parses the space separated `key=value` pairs of the extension.
+///
+/// Values can contain spaces, they end where the next key starts.
+fn parse_extension(input: &str) -> std::result::Result<Vec<(&str, String)>, String> {
+    // The spans of the keys, from their start to their `=` separator.
+    let mut keys = vec![];
+    let mut escaped = false;
+
+    for (index, c) in input.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '=' => {
+                let start = input[..index]
+                    .char_indices()
+                    .rev()
+                    .find(|(_, c)| !is_cef_key_char(*c))
+                    .map_or(Some(0), |(position, c)| {
+                        c.is_whitespace().then(|| position + c.len_utf8())
+                    });
+
+                // An `=` that doesn't follow a key is part of the value.
+                if let Some(start) = start.filter(|start| *start < index) {
+                    keys.push((start, index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let leading = keys.first().map_or(input, |(start, _)| &input[..*start]);
+    if !leading.trim().is_empty() {
+        return Err(format!(
+            "unexpected extension content: {:?}",
+            leading.trim()
+        ));
+    }
+
+    Ok(keys
+        .iter()
+        .enumerate()
+        .map(|(n, (start, separator))| {
+            let end = keys.get(n + 1).map_or(input.len(), |(next, _)| *next);
+            let value = input[separator + 1..end].trim_end();
+
+            (&input[*start..*separator], unescape(value))
+        })
+        .collect())
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(c @ ('\\' | '=' | '|')) => unescaped.push(c),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+/// Converts the value of the well-known extension keys to their type, keeping
+/// the values that don't parse as they are.
+fn coerce(key: &str, value: String, tz: &TimeZone) -> Value {
+    if INTEGER_KEYS.contains(&key) {
+        if let Ok(integer) = value.parse::<i64>() {
+            return integer.into();
+        }
+    } else if FLOAT_KEYS.contains(&key) {
+        if let Ok(float) = value.parse::<f64>() {
+            if let Ok(float) = NotNan::new(float) {
+                return float.into();
+            }
+        }
+    } else if TIMESTAMP_KEYS.contains(&key) {
+        if let Some(timestamp) = parse_log_timestamp(&value, tz) {
+            return timestamp.into();
+        }
+    }
+
+    value.into()
+}
+
+/// Replaces the custom fields, such as `cs1`, along with their label, such as
+/// `cs1Label`, by a field named after the label.
+fn translate(log: &mut BTreeMap<String, Value>) {
+    let labels = log
+        .keys()
+        .filter_map(|key| {
+            let field = key.strip_suffix("Label")?;
+            log.contains_key(field)
+                .then(|| (key.clone(), field.to_owned()))
+        })
+        .collect::<Vec<_>>();
+
+    for (label_key, field) in labels {
+        if let Some(Value::Bytes(label)) = log.get(&label_key) {
+            let label = String::from_utf8_lossy(label).into_owned();
+            if let Some(value) = log.remove(&field) {
+                log.remove(&label_key);
+                log.insert(label, value);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParseCef;
+
+impl Function for ParseCef {
+    fn identifier(&self) -> &'static str {
+        "parse_cef"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "translate_custom_fields",
+                kind: kind::BOOLEAN,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "parse message",
+                source: r#"parse_cef!(s'CEF:0|Security|threatmanager|1.0|100|worm successfully stopped|10|src=10.0.0.1 dst=2.1.2.2 spt=1232 msg=Detected a threat. No action needed.')"#,
+                result: Ok(indoc! {r#"{
+                    "cefVersion": 0,
+                    "deviceVendor": "Security",
+                    "deviceProduct": "threatmanager",
+                    "deviceVersion": "1.0",
+                    "deviceEventClassId": "100",
+                    "name": "worm successfully stopped",
+                    "severity": 10,
+                    "src": "10.0.0.1",
+                    "dst": "2.1.2.2",
+                    "spt": 1232,
+                    "msg": "Detected a threat. No action needed."
+                }"#}),
+            },
+            Example {
+                title: "translate custom fields",
+                source: r#"parse_cef!(s'CEF:0|Vendor|Product|1|id|name|3|cs1=admin cs1Label=user', translate_custom_fields: true)"#,
+                result: Ok(indoc! {r#"{
+                    "cefVersion": 0,
+                    "deviceVendor": "Vendor",
+                    "deviceProduct": "Product",
+                    "deviceVersion": "1",
+                    "deviceEventClassId": "id",
+                    "name": "name",
+                    "severity": 3,
+                    "user": "admin"
+                }"#}),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let translate_custom_fields = arguments
+            .optional("translate_custom_fields")
+            .unwrap_or_else(|| expr!(false));
+
+        Ok(Box::new(ParseCefFn {
+            value,
+            translate_custom_fields,
+        }))
+    }
+
+    fn call_by_vm(&self, ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+        let translate_custom_fields = args
+            .optional("translate_custom_fields")
+            .map(|value| value.try_boolean())
+            .transpose()?
+            .unwrap_or(false);
+
+        parse_cef(value, translate_custom_fields, ctx.timezone())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseCefFn {
+    value: Box<dyn Expression>,
+    translate_custom_fields: Box<dyn Expression>,
+}
+
+impl Expression for ParseCefFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let translate_custom_fields = self.translate_custom_fields.resolve(ctx)?.try_boolean()?;
+
+        parse_cef(value, translate_custom_fields, ctx.timezone())
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::object(inner_kind()).fallible(/* message parsing error */)
+    }
+}
+
+fn inner_kind() -> Collection<Field> {
+    let mut collection = Collection::from(
+        map! {
+            "cefVersion": Kind::integer() | Kind::bytes(),
+            "deviceVendor": Kind::bytes(),
+            "deviceProduct": Kind::bytes(),
+            "deviceVersion": Kind::bytes(),
+            "deviceEventClassId": Kind::bytes(),
+            "name": Kind::bytes(),
+            "severity": Kind::integer() | Kind::bytes(),
+        }
+        .into_iter()
+        .map(|(key, kind): (&str, _)| (key.into(), kind))
+        .collect::<BTreeMap<_, _>>(),
+    );
+
+    collection.set_unknown(Kind::bytes().or_integer().or_float().or_timestamp());
+    collection
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone as _, Utc};
+    use vector_common::btreemap;
+
+    use super::*;
+
+    #[test]
+    fn parses_extension() {
+        assert_eq!(
+            parse_extension(r"src=10.0.0.1 msg=a b\=c\\ d\nx  act=blocked a = b").unwrap(),
+            vec![
+                ("src", "10.0.0.1".to_owned()),
+                ("msg", "a b=c\\ d\nx".to_owned()),
+                ("act", "blocked a = b".to_owned()),
+            ]
+        );
+        assert_eq!(parse_extension("").unwrap(), vec![]);
+        assert!(parse_extension("junk src=1").is_err());
+        assert!(parse_extension("junk").is_err());
+    }
+
+    #[test]
+    fn translates_custom_fields() {
+        let mut log = btreemap! {
+            "cs1" => "admin",
+            "cs1Label" => "user",
+            "cn1Label" => "count",
+            "cs2" => "unlabeled",
+        };
+
+        translate(&mut log);
+
+        assert_eq!(
+            log,
+            btreemap! {
+                "user" => "admin",
+                "cn1Label" => "count",
+                "cs2" => "unlabeled",
+            }
+        );
+    }
+
+    test_function![
+        parse_cef => ParseCef;
+
+        valid {
+            args: func_args![value: r"<134>Feb 14 19:04:54 host CEF:0|Security|threat\|manager|1.0|100|worm stopped|High|src=10.0.0.1 spt=1232 cfp1=1.5 rt=1644865494000 msg=Detected a threat\=worm. No action needed."],
+            want: Ok(btreemap! {
+                "cefVersion" => 0,
+                "deviceVendor" => "Security",
+                "deviceProduct" => "threat|manager",
+                "deviceVersion" => "1.0",
+                "deviceEventClassId" => "100",
+                "name" => "worm stopped",
+                "severity" => "High",
+                "src" => "10.0.0.1",
+                "spt" => 1232,
+                "cfp1" => 1.5,
+                "rt" => Utc.timestamp_millis(1_644_865_494_000),
+                "msg" => "Detected a threat=worm. No action needed.",
+            }),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        timestamp_formats {
+            args: func_args![value: "CEF:1|V|P|1|id|name|5|start=Feb 14 2022 19:04:54.123 UTC end=not a date spt=unknown"],
+            want: Ok(btreemap! {
+                "cefVersion" => 1,
+                "deviceVendor" => "V",
+                "deviceProduct" => "P",
+                "deviceVersion" => "1",
+                "deviceEventClassId" => "id",
+                "name" => "name",
+                "severity" => 5,
+                "start" => Utc.ymd(2022, 2, 14).and_hms_milli(19, 4, 54, 123),
+                "end" => "not a date",
+                "spt" => "unknown",
+            }),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        translated {
+            args: func_args![value: "CEF:0|V|P|1|id|name|5|cs1=admin cs1Label=user cn1=3 cn1Label=attempts", translate_custom_fields: true],
+            want: Ok(btreemap! {
+                "cefVersion" => 0,
+                "deviceVendor" => "V",
+                "deviceProduct" => "P",
+                "deviceVersion" => "1",
+                "deviceEventClassId" => "id",
+                "name" => "name",
+                "severity" => 5,
+                "user" => "admin",
+                "attempts" => 3,
+            }),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        missing_header_fields {
+            args: func_args![value: "CEF:0|V|P|1|id"],
+            want: Err("unable to parse CEF message: expected 7 header fields, found 4"),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        not_cef {
+            args: func_args![value: "LEEF:1.0|V|P|1|id|"],
+            want: Err("unable to parse CEF message: missing `CEF:` prefix"),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+    ];
+}
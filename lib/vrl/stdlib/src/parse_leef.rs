@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+
+use ::value::Value;
+use chrono::{DateTime, Utc};
+use vector_common::TimeZone;
+use vrl::prelude::*;
+
+use crate::util::{parse_log_timestamp, split_header, LEEF_HEADER_FIELDS};
+
+/// The attributes holding integers.
+const INTEGER_KEYS: &[&str] = &[
+    "dstBytes",
+    "dstPackets",
+    "dstPort",
+    "dstPostNATPort",
+    "dstPreNATPort",
+    "sev",
+    "srcBytes",
+    "srcPackets",
+    "srcPort",
+    "srcPostNATPort",
+    "srcPreNATPort",
+    "totalBytes",
+    "totalPackets",
+];
+
+fn parse_leef(value: Value, tz: &TimeZone) -> Resolved {
+    let bytes = value.try_bytes()?;
+    let input = String::from_utf8_lossy(&bytes);
+
+    parse_log(&input, tz)
+        .map(Into::into)
+        .map_err(|err| format!("unable to parse LEEF message: {}", err).into())
+}
+
+fn parse_log(input: &str, tz: &TimeZone) -> std::result::Result<BTreeMap<String, Value>, String> {
+    // The message is usually prefixed by a syslog header.
+    let start = input.find("LEEF:").ok_or("missing `LEEF:` prefix")?;
+    let (header, attributes) = split_header(&input[start + 5..], LEEF_HEADER_FIELDS.len())?;
+
+    // Version 2.0 adds the attribute delimiter to the header, 1.0 only uses
+    // tabs.
+    let (delimiter, attributes) = if header[0].trim().starts_with('1') {
+        ('\t', attributes)
+    } else {
+        let (delimiter, attributes) = split_header(attributes, 1)?;
+        (parse_delimiter(&delimiter[0])?, attributes)
+    };
+
+    let attributes = parse_attributes(attributes, delimiter)?;
+    let dev_time_format = attributes.get("devTimeFormat").cloned();
+
+    let mut log = BTreeMap::new();
+
+    for (key, value) in attributes {
+        let value: Value = if INTEGER_KEYS.contains(&key.as_str()) {
+            value
+                .parse::<i64>()
+                .map_or_else(|_| value.into(), Into::into)
+        } else if key == "devTime" {
+            parse_dev_time(&value, dev_time_format.as_deref(), tz)
+                .map_or_else(|| value.into(), Into::into)
+        } else {
+            value.into()
+        };
+
+        log.insert(key, value);
+    }
+
+    for (key, value) in LEEF_HEADER_FIELDS.iter().zip(header) {
+        log.insert((*key).to_owned(), value.into());
+    }
+
+    Ok(log)
+}
+
+/// Parses the delimiter of the attributes, either a single character, or
+/// the hexadecimal code of one prefixed with `x` or `0x`, defaulting to tabs.
+fn parse_delimiter(delimiter: &str) -> std::result::Result<char, String> {
+    let mut chars = delimiter.chars();
+
+    match (chars.next(), chars.next()) {
+        (None, _) => Ok('\t'),
+        (Some(c), None) => Ok(c),
+        _ => delimiter
+            .strip_prefix("0x")
+            .or_else(|| delimiter.strip_prefix('x'))
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("invalid attribute delimiter: {:?}", delimiter)),
+    }
+}
+
+/// Parses the `key=value` attributes separated by `delimiter`.
+///
+/// Delimiters, equal signs and backslashes within values are escaped with a
+/// backslash.
+fn parse_attributes(
+    input: &str,
+    delimiter: char,
+) -> std::result::Result<BTreeMap<String, String>, String> {
+    let mut attributes = BTreeMap::new();
+    let mut attribute = String::new();
+    let mut separator = None;
+    let mut chars = input.trim_end_matches(&['\r', '\n'][..]).chars();
+
+    loop {
+        match chars.next() {
+            Some('\\') => match chars.next() {
+                Some(c) if c == delimiter || c == '=' || c == '\\' => attribute.push(c),
+                Some(c) => {
+                    attribute.push('\\');
+                    attribute.push(c);
+                }
+                None => attribute.push('\\'),
+            },
+            Some('=') if separator.is_none() => {
+                separator = Some(attribute.len());
+                attribute.push('=');
+            }
+            Some(c) if c != delimiter => attribute.push(c),
+            next => {
+                match separator.take() {
+                    Some(separator) => {
+                        let key = attribute[..separator].trim();
+                        if key.is_empty() {
+                            return Err(format!("missing attribute key: {:?}", attribute));
+                        }
+
+                        attributes.insert(key.to_owned(), attribute[separator + 1..].to_owned());
+                    }
+                    None if attribute.trim().is_empty() => {}
+                    None => return Err(format!("missing attribute value: {:?}", attribute)),
+                }
+
+                attribute.clear();
+
+                if next.is_none() {
+                    return Ok(attributes);
+                }
+            }
+        }
+    }
+}
+
+/// Parses the `devTime` attribute, in the `devTimeFormat` if any, or in one
+/// of the default formats otherwise.
+fn parse_dev_time(value: &str, format: Option<&str>, tz: &TimeZone) -> Option<DateTime<Utc>> {
+    let format = match format {
+        Some(format) => java_format(format)?,
+        None => return parse_log_timestamp(value, tz),
+    };
+
+    if format.contains("%z") {
+        DateTime::parse_from_str(value, &format)
+            .ok()
+            .map(|datetime| datetime.with_timezone(&Utc))
+    } else {
+        tz.datetime_from_str(value, &format).ok()
+    }
+}
+
+/// Converts the Java `SimpleDateFormat` patterns used by `devTimeFormat` to
+/// `strftime` formats, for the subset `chrono` is able to parse.
+fn java_format(format: &str) -> Option<String> {
+    let mut converted = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\'' {
+            // Quoted literal, with `''` being a single quote.
+            let mut literal = String::new();
+            loop {
+                match chars.next()? {
+                    '\'' if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        literal.push('\'');
+                    }
+                    '\'' => break,
+                    c => literal.push(c),
+                }
+            }
+            converted.push_str(&if literal.is_empty() {
+                "'".to_owned()
+            } else {
+                literal.replace('%', "%%")
+            });
+            continue;
+        }
+
+        if !c.is_ascii_alphabetic() {
+            match c {
+                '%' => converted.push_str("%%"),
+                c => converted.push(c),
+            }
+            continue;
+        }
+
+        let mut count = 1;
+        while chars.peek() == Some(&c) {
+            chars.next();
+            count += 1;
+        }
+
+        let specifier = match (c, count) {
+            ('y', 2) => "%y",
+            ('y', _) => "%Y",
+            ('M', 1 | 2) => "%m",
+            ('M', 3) => "%b",
+            ('M', _) => "%B",
+            ('d', _) => "%d",
+            ('E', 1..=3) => "%a",
+            ('E', _) => "%A",
+            ('H', _) => "%H",
+            ('h', _) => "%I",
+            ('m', _) => "%M",
+            ('s', _) => "%S",
+            ('a', _) => "%p",
+            ('Z', _) => "%z",
+            // Milliseconds are only supported after a dot.
+            ('S', 3) if converted.ends_with('.') => {
+                converted.pop();
+                "%.3f"
+            }
+            _ => return None,
+        };
+
+        converted.push_str(specifier);
+    }
+
+    Some(converted)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParseLeef;
+
+impl Function for ParseLeef {
+    fn identifier(&self) -> &'static str {
+        "parse_leef"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "parse LEEF 2.0 message",
+                source: r#"parse_leef!(s'LEEF:2.0|Lancope|StealthWatch|1.0|41|^|src=10.0.1.8^dst=10.0.0.5^sev=5^srcPort=81^usrName=joe.black')"#,
+                result: Ok(indoc! {r#"{
+                    "leefVersion": "2.0",
+                    "vendor": "Lancope",
+                    "product": "StealthWatch",
+                    "productVersion": "1.0",
+                    "eventId": "41",
+                    "src": "10.0.1.8",
+                    "dst": "10.0.0.5",
+                    "sev": 5,
+                    "srcPort": 81,
+                    "usrName": "joe.black"
+                }"#}),
+            },
+            Example {
+                title: "parse LEEF 1.0 message",
+                source: r#"parse_leef!("LEEF:1.0|Microsoft|MSExchange|4.0 SP1|15345|src=192.0.2.0\tdst=172.50.123.1\tdevTime=1644865494000")"#,
+                result: Ok(indoc! {r#"{
+                    "leefVersion": "1.0",
+                    "vendor": "Microsoft",
+                    "product": "MSExchange",
+                    "productVersion": "4.0 SP1",
+                    "eventId": "15345",
+                    "src": "192.0.2.0",
+                    "dst": "172.50.123.1",
+                    "devTime": "2022-02-14T19:04:54Z"
+                }"#}),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(ParseLeefFn { value }))
+    }
+
+    fn call_by_vm(&self, ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+
+        parse_leef(value, ctx.timezone())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseLeefFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for ParseLeefFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        parse_leef(value, ctx.timezone())
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        TypeDef::object(inner_kind()).fallible(/* message parsing error */)
+    }
+}
+
+fn inner_kind() -> Collection<Field> {
+    let mut collection = Collection::from(
+        LEEF_HEADER_FIELDS
+            .iter()
+            .map(|key| ((*key).into(), Kind::bytes()))
+            .collect::<BTreeMap<_, _>>(),
+    );
+
+    collection.set_unknown(Kind::bytes().or_integer().or_timestamp());
+    collection
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone as _, Utc};
+    use vector_common::btreemap;
+
+    use super::*;
+
+    #[test]
+    fn parses_delimiters() {
+        assert_eq!(parse_delimiter("^"), Ok('^'));
+        assert_eq!(parse_delimiter(""), Ok('\t'));
+        assert_eq!(parse_delimiter("x09"), Ok('\t'));
+        assert_eq!(parse_delimiter("0x7C"), Ok('|'));
+        assert!(parse_delimiter("^^").is_err());
+    }
+
+    #[test]
+    fn parses_attributes() {
+        assert_eq!(
+            parse_attributes(r"a=1^b=x=y\^z^^c=\\d\n^", '^').unwrap(),
+            btreemap! {
+                "a" => "1",
+                "b" => "x=y^z",
+                "c" => r"\d\n",
+            }
+        );
+        assert!(parse_attributes("a=1^b", '^').is_err());
+        assert!(parse_attributes("=1", '^').is_err());
+    }
+
+    #[test]
+    fn converts_java_formats() {
+        assert_eq!(
+            java_format("MMM dd yyyy HH:mm:ss.SSS").as_deref(),
+            Some("%b %d %Y %H:%M:%S%.3f")
+        );
+        assert_eq!(
+            java_format("yyyy-MM-dd'T'HH:mm:ssZ").as_deref(),
+            Some("%Y-%m-%dT%H:%M:%S%z")
+        );
+        assert_eq!(java_format("SSS"), None);
+        assert_eq!(java_format("G"), None);
+    }
+
+    test_function![
+        parse_leef => ParseLeef;
+
+        leef_2 {
+            args: func_args![value: "<13>Feb 14 19:04:54 host LEEF:2.0|Lancope|StealthWatch|1.0|41|x5E|src=10.0.1.8^sev=5^srcPort=81^devTime=2022-02-14T19:04:54+0100^devTimeFormat=yyyy-MM-dd'T'HH:mm:ssZ"],
+            want: Ok(btreemap! {
+                "leefVersion" => "2.0",
+                "vendor" => "Lancope",
+                "product" => "StealthWatch",
+                "productVersion" => "1.0",
+                "eventId" => "41",
+                "src" => "10.0.1.8",
+                "sev" => 5,
+                "srcPort" => 81,
+                "devTime" => Utc.ymd(2022, 2, 14).and_hms(18, 4, 54),
+                "devTimeFormat" => "yyyy-MM-dd'T'HH:mm:ssZ",
+            }),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        leef_1 {
+            args: func_args![value: "LEEF:1.0|Microsoft|MSExchange|4.0 SP1|15345|src=192.0.2.0\tdstPort=unknown\tdevTime=Feb 14 2022 19:04:54\n"],
+            want: Ok(btreemap! {
+                "leefVersion" => "1.0",
+                "vendor" => "Microsoft",
+                "product" => "MSExchange",
+                "productVersion" => "4.0 SP1",
+                "eventId" => "15345",
+                "src" => "192.0.2.0",
+                "dstPort" => "unknown",
+                "devTime" => Utc.ymd(2022, 2, 14).and_hms(19, 4, 54),
+            }),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+
+        invalid_attribute {
+            args: func_args![value: "LEEF:2.0|V|P|1|id|^|a=1^b"],
+            want: Err(r#"unable to parse LEEF message: missing attribute value: "b""#),
+            tdef: TypeDef::object(inner_kind()).fallible(),
+        }
+    ];
+}
@@ -0,0 +1,213 @@
+use ::value::Value;
+use chrono::{TimeZone, Utc};
+use vrl::prelude::*;
+
+fn parse_msgpack(value: Value) -> Resolved {
+    let bytes = value.try_bytes()?;
+    let mut input = bytes.as_ref();
+    let msgpack = rmpv::decode::read_value(&mut input)
+        .map_err(|err| format!("unable to parse msgpack: {}", err))?;
+
+    if !input.is_empty() {
+        return Err(format!(
+            "unable to parse msgpack: {} trailing bytes after value",
+            input.len()
+        )
+        .into());
+    }
+
+    Ok(from_msgpack(msgpack).map_err(|err| format!("unable to parse msgpack: {}", err))?)
+}
+
+fn from_msgpack(msgpack: rmpv::Value) -> std::result::Result<Value, String> {
+    Ok(match msgpack {
+        rmpv::Value::Nil => Value::Null,
+        rmpv::Value::Boolean(boolean) => boolean.into(),
+        rmpv::Value::Integer(integer) => integer
+            .as_i64()
+            .ok_or_else(|| format!("integer out of range: {}", integer))?
+            .into(),
+        // NaN is converted to null, as VRL floats can't be NaN.
+        rmpv::Value::F32(float) => NotNan::new(float as f64)
+            .map(Value::Float)
+            .unwrap_or(Value::Null),
+        rmpv::Value::F64(float) => NotNan::new(float).map(Value::Float).unwrap_or(Value::Null),
+        rmpv::Value::String(string) => Value::Bytes(string.into_bytes().into()),
+        rmpv::Value::Binary(bytes) => Value::Bytes(bytes.into()),
+        rmpv::Value::Array(array) => array
+            .into_iter()
+            .map(from_msgpack)
+            .collect::<std::result::Result<Vec<_>, _>>()?
+            .into(),
+        rmpv::Value::Map(map) => map
+            .into_iter()
+            .map(|(key, value)| Ok((map_key(key)?, from_msgpack(value)?)))
+            .collect::<std::result::Result<BTreeMap<_, _>, String>>()?
+            .into(),
+        rmpv::Value::Ext(code, bytes) => match timestamp(code, &bytes) {
+            Some(timestamp) => timestamp,
+            None => BTreeMap::from([
+                ("msgpack_extension_code".to_owned(), i64::from(code).into()),
+                ("bytes".to_owned(), Value::Bytes(bytes.into())),
+            ])
+            .into(),
+        },
+    })
+}
+
+/// Decodes the msgpack timestamp extension, and Fluent's `EventTime`
+/// extension.
+fn timestamp(code: i8, bytes: &[u8]) -> Option<Value> {
+    let (seconds, nanoseconds) = match (code, bytes.len()) {
+        (-1, 4) => (i64::from(u32::from_be_bytes(bytes.try_into().ok()?)), 0),
+        (-1, 8) => {
+            let value = u64::from_be_bytes(bytes.try_into().ok()?);
+            ((value & 0x3_ffff_ffff) as i64, (value >> 34) as u32)
+        }
+        (-1, 12) => (
+            i64::from_be_bytes(bytes[4..].try_into().ok()?),
+            u32::from_be_bytes(bytes[..4].try_into().ok()?),
+        ),
+        (0, 8) => (
+            i64::from(u32::from_be_bytes(bytes[..4].try_into().ok()?)),
+            u32::from_be_bytes(bytes[4..].try_into().ok()?),
+        ),
+        _ => return None,
+    };
+
+    Utc.timestamp_opt(seconds, nanoseconds)
+        .single()
+        .map(Into::into)
+}
+
+/// Object keys are strings, so integer keys are converted to their string
+/// representation.
+fn map_key(key: rmpv::Value) -> std::result::Result<String, String> {
+    match key {
+        rmpv::Value::String(string) => Ok(String::from_utf8_lossy(string.as_bytes()).into_owned()),
+        rmpv::Value::Integer(integer) => Ok(integer.to_string()),
+        _ => Err("map keys must be strings or integers".to_owned()),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParseMsgpack;
+
+impl Function for ParseMsgpack {
+    fn identifier(&self) -> &'static str {
+        "parse_msgpack"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "object",
+            source: r#"parse_msgpack!(decode_base64!("gaVmaWVsZKV2YWx1ZQ=="))"#,
+            result: Ok(r#"{ "field": "value" }"#),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(ParseMsgpackFn { value }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+
+        parse_msgpack(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseMsgpackFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for ParseMsgpackFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        parse_msgpack(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        type_def()
+    }
+}
+
+fn inner_kind() -> Kind {
+    Kind::null()
+        | Kind::bytes()
+        | Kind::integer()
+        | Kind::float()
+        | Kind::boolean()
+        | Kind::timestamp()
+        | Kind::array(Collection::any())
+        | Kind::object(Collection::any())
+}
+
+fn type_def() -> TypeDef {
+    TypeDef::bytes()
+        .fallible()
+        .add_boolean()
+        .add_integer()
+        .add_float()
+        .add_null()
+        .add_timestamp()
+        .add_array(Collection::from_unknown(inner_kind()))
+        .add_object(Collection::from_unknown(inner_kind()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        parse_msgpack => ParseMsgpack;
+
+        object {
+            // {"field": "value", "list": [1, -2, 1.5, true, nil], 10: bin(0x0102)}
+            args: func_args![value: Bytes::from_static(b"\x83\xa5field\xa5value\xa4list\x95\x01\xfe\xcb\x3f\xf8\x00\x00\x00\x00\x00\x00\xc3\xc0\x0a\xc4\x02\x01\x02")],
+            want: Ok(value!({ "10": (Bytes::from_static(b"\x01\x02")), field: "value", list: [1, (-2), 1.5, true, null] })),
+            tdef: type_def(),
+        }
+
+        timestamp_extension {
+            args: func_args![value: Bytes::from_static(b"\xd6\xff\x62\x0a\xa7\xd6")],
+            want: Ok(Utc.timestamp(1_644_865_494, 0)),
+            tdef: type_def(),
+        }
+
+        event_time {
+            args: func_args![value: Bytes::from_static(b"\xd7\x00\x62\x0a\xa7\xd6\x00\x00\x03\xe8")],
+            want: Ok(Utc.timestamp(1_644_865_494, 1000)),
+            tdef: type_def(),
+        }
+
+        extension {
+            args: func_args![value: Bytes::from_static(b"\xd4\x05\x2a")],
+            want: Ok(value!({ msgpack_extension_code: 5, bytes: (Bytes::from_static(b"\x2a")) })),
+            tdef: type_def(),
+        }
+
+        trailing_bytes {
+            args: func_args![value: Bytes::from_static(b"\x01\x02")],
+            want: Err("unable to parse msgpack: 1 trailing bytes after value"),
+            tdef: type_def(),
+        }
+    ];
+}
@@ -0,0 +1,112 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn parse_toml(value: Value) -> Resolved {
+    let bytes = value.try_bytes()?;
+    let toml = toml::from_slice::<toml::Value>(&bytes)
+        .map_err(|err| format!("unable to parse toml: {}", err))?;
+
+    Value::try_from(toml).map_err(|err| format!("unable to parse toml: {}", err).into())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParseToml;
+
+impl Function for ParseToml {
+    fn identifier(&self) -> &'static str {
+        "parse_toml"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "object",
+            source: r#"parse_toml!("[server]\nhost = \"localhost\"\nports = [80, 443]")"#,
+            result: Ok(r#"{ "server": { "host": "localhost", "ports": [80, 443] } }"#),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(ParseTomlFn { value }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+
+        parse_toml(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseTomlFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for ParseTomlFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        parse_toml(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        type_def()
+    }
+}
+
+fn inner_kind() -> Kind {
+    Kind::bytes()
+        | Kind::integer()
+        | Kind::float()
+        | Kind::boolean()
+        | Kind::timestamp()
+        | Kind::array(Collection::any())
+        | Kind::object(Collection::any())
+}
+
+fn type_def() -> TypeDef {
+    TypeDef::object(Collection::from_unknown(inner_kind())).fallible()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    test_function![
+        parse_toml => ParseToml;
+
+        table {
+            args: func_args![value: "title = \"example\"\n\n[server]\nhost = \"localhost\"\nports = [80, 443]\nenabled = true\nratio = 0.5\n"],
+            want: Ok(value!({ title: "example", server: { host: "localhost", ports: [80, 443], enabled: true, ratio: 0.5 } })),
+            tdef: type_def(),
+        }
+
+        datetime {
+            args: func_args![value: "updated = 2022-02-14T19:04:54Z"],
+            want: Ok(value!({ updated: (Utc.ymd(2022, 2, 14).and_hms(19, 4, 54)) })),
+            tdef: type_def(),
+        }
+
+        invalid {
+            args: func_args![value: "title = "],
+            want: Err("unable to parse toml: unexpected eof encountered at line 1 column 9"),
+            tdef: type_def(),
+        }
+    ];
+}
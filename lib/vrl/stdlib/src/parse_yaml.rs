@@ -0,0 +1,163 @@
+use ::value::Value;
+use serde_yaml::Value as YamlValue;
+use vrl::prelude::*;
+
+fn parse_yaml(value: Value) -> Resolved {
+    let bytes = value.try_bytes()?;
+    let yaml = serde_yaml::from_slice::<YamlValue>(&bytes)
+        .map_err(|err| format!("unable to parse yaml: {}", err))?;
+
+    Ok(from_yaml(yaml)?)
+}
+
+fn from_yaml(yaml: YamlValue) -> std::result::Result<Value, String> {
+    Ok(match yaml {
+        YamlValue::Null => Value::Null,
+        YamlValue::Bool(boolean) => boolean.into(),
+        YamlValue::Number(number) => match number.as_i64() {
+            Some(integer) => integer.into(),
+            // `.nan` is converted to null, as VRL floats can't be NaN.
+            None => number
+                .as_f64()
+                .and_then(|float| NotNan::new(float).ok())
+                .map(Value::Float)
+                .unwrap_or(Value::Null),
+        },
+        YamlValue::String(string) => string.into(),
+        YamlValue::Sequence(sequence) => sequence
+            .into_iter()
+            .map(from_yaml)
+            .collect::<std::result::Result<Vec<_>, _>>()?
+            .into(),
+        YamlValue::Mapping(mapping) => mapping
+            .into_iter()
+            .map(|(key, value)| Ok((mapping_key(key)?, from_yaml(value)?)))
+            .collect::<std::result::Result<BTreeMap<_, _>, String>>()?
+            .into(),
+    })
+}
+
+/// Object keys are strings, so scalar keys are converted to their string
+/// representation.
+fn mapping_key(key: YamlValue) -> std::result::Result<String, String> {
+    match key {
+        YamlValue::String(string) => Ok(string),
+        YamlValue::Number(number) => Ok(number.to_string()),
+        YamlValue::Bool(boolean) => Ok(boolean.to_string()),
+        YamlValue::Null => Ok("null".to_owned()),
+        _ => Err("unable to parse yaml: mapping keys must be scalars".to_owned()),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParseYaml;
+
+impl Function for ParseYaml {
+    fn identifier(&self) -> &'static str {
+        "parse_yaml"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "object",
+            source: r#"parse_yaml!("service: api\nports:\n  - 80\n  - 443\n")"#,
+            result: Ok(r#"{ "service": "api", "ports": [80, 443] }"#),
+        }]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(ParseYamlFn { value }))
+    }
+
+    fn call_by_vm(&self, _ctx: &mut Context, args: &mut VmArgumentList) -> Resolved {
+        let value = args.required("value");
+
+        parse_yaml(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseYamlFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for ParseYamlFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        parse_yaml(value)
+    }
+
+    fn type_def(&self, _: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        type_def()
+    }
+}
+
+fn inner_kind() -> Kind {
+    Kind::null()
+        | Kind::bytes()
+        | Kind::integer()
+        | Kind::float()
+        | Kind::boolean()
+        | Kind::array(Collection::any())
+        | Kind::object(Collection::any())
+}
+
+fn type_def() -> TypeDef {
+    TypeDef::bytes()
+        .fallible()
+        .add_boolean()
+        .add_integer()
+        .add_float()
+        .add_null()
+        .add_array(Collection::from_unknown(inner_kind()))
+        .add_object(Collection::from_unknown(inner_kind()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        parse_yaml => ParseYaml;
+
+        object {
+            args: func_args![value: "service: api\nports:\n  - 80\n  - 443\ntls: true\nratio: 0.5\nowner: ~\n"],
+            want: Ok(value!({ service: "api", ports: [80, 443], tls: true, ratio: 0.5, owner: null })),
+            tdef: type_def(),
+        }
+
+        scalar_keys {
+            args: func_args![value: "1: one\ntrue: yes\n"],
+            want: Ok(value!({ "1": "one", "true": "yes" })),
+            tdef: type_def(),
+        }
+
+        scalar {
+            args: func_args![value: "hello"],
+            want: Ok("hello"),
+            tdef: type_def(),
+        }
+
+        complex_key {
+            args: func_args![value: "? [a, b]\n: value\n"],
+            want: Err("unable to parse yaml: mapping keys must be scalars"),
+            tdef: type_def(),
+        }
+    ];
+}
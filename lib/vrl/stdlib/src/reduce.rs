@@ -0,0 +1,159 @@
+use ::value::Value;
+use vrl::prelude::*;
+
+fn reduce<T>(
+    value: Value,
+    initial: Value,
+    ctx: &mut Context,
+    runner: closure::Runner<T>,
+) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    let mut accumulator = initial;
+
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                accumulator = runner.reduce_key_value(ctx, accumulator, &key, &value)?;
+            }
+        }
+        Value::Array(array) => {
+            for (index, value) in array.into_iter().enumerate() {
+                accumulator = runner.reduce_index_value(ctx, accumulator, index, &value)?;
+            }
+        }
+        value => {
+            return Err(value::Error::Expected {
+                got: value.kind(),
+                expected: Kind::object(Collection::any()) | Kind::array(Collection::any()),
+            }
+            .into())
+        }
+    }
+
+    Ok(accumulator)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Reduce;
+
+impl Function for Reduce {
+    fn identifier(&self) -> &'static str {
+        "reduce"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::OBJECT | kind::ARRAY,
+                required: true,
+            },
+            Parameter {
+                keyword: "initial",
+                kind: kind::ANY,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "sum array",
+                source: r#"reduce([1, 2, 3], 0) -> |sum, _index, value| { sum + value }"#,
+                result: Ok("6"),
+            },
+            Example {
+                title: "fold object into string",
+                source: r#"reduce({ "a": 1, "b": 2 }, "") -> |line, key, value| { line + key + "=" + to_string(value) + ";" }"#,
+                result: Ok(r#""a=1;b=2;""#),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let initial = arguments.required("initial");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(ReduceFn {
+            value,
+            initial,
+            closure,
+        }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::object(Collection::any()).or_array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::Accumulator("initial"),
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(Kind::any()),
+                example: Example {
+                    title: "sum array",
+                    source: r#"reduce([1, 2], 0) -> |sum, _index, value| { sum + value }"#,
+                    result: Ok("3"),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+
+    fn call_by_vm(&self, ctx: &mut Context, args: &mut VmArgumentList) -> Result<Value> {
+        let value = args.required("value");
+        let initial = args.required("initial");
+        let VmFunctionClosure { variables, vm } = args.closure();
+        let runner = closure::Runner::new(variables, |ctx| vm.interpret(ctx));
+
+        reduce(value, initial, ctx, runner)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ReduceFn {
+    value: Box<dyn Expression>,
+    initial: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for ReduceFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let initial = self.initial.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        reduce(value, initial, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let closure = self.closure.block.type_def(ctx);
+        let fallible = closure.is_fallible();
+
+        // The initial value is returned as-is for empty collections.
+        self.initial
+            .type_def(ctx)
+            .merge_deep(closure)
+            .with_fallibility(fallible)
+    }
+}
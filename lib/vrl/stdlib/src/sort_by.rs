@@ -0,0 +1,183 @@
+use std::cmp::Ordering;
+
+use ::value::Value;
+use vrl::prelude::*;
+
+fn sort_by<T>(value: Value, ctx: &mut Context, runner: closure::Runner<T>) -> Resolved
+where
+    T: Fn(&mut Context) -> Resolved,
+{
+    let array = value.try_array()?;
+    let mut keyed = Vec::with_capacity(array.len());
+
+    for (index, value) in array.into_iter().enumerate() {
+        let key = runner.resolve_index_value(ctx, index, &value)?;
+        keyed.push((key, value));
+    }
+
+    // The sort is stable, elements with equal keys keep their order.
+    keyed.sort_by(|(a, _), (b, _)| compare(a, b));
+
+    Ok(keyed
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect::<Vec<_>>()
+        .into())
+}
+
+/// Compare two sort keys. Integers and floats are compared numerically,
+/// keys of different types are ordered by type.
+fn compare(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+        (Value::Float(a), Value::Float(b)) => a.cmp(b),
+        (Value::Integer(a), Value::Float(b)) => {
+            NotNan::new(*a as f64).map_or(Ordering::Equal, |a| a.cmp(b))
+        }
+        (Value::Float(a), Value::Integer(b)) => {
+            NotNan::new(*b as f64).map_or(Ordering::Equal, |b| a.cmp(&b))
+        }
+        (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+        (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+        (Value::Timestamp(a), Value::Timestamp(b)) => a.cmp(b),
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn rank(value: &Value) -> u8 {
+    match value {
+        Value::Null => 0,
+        Value::Boolean(_) => 1,
+        Value::Integer(_) | Value::Float(_) => 2,
+        Value::Timestamp(_) => 3,
+        Value::Bytes(_) => 4,
+        _ => 5,
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SortBy;
+
+impl Function for SortBy {
+    fn identifier(&self) -> &'static str {
+        "sort_by"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "sort by field",
+                source: r#"sort_by([{ "n": 2 }, { "n": 1 }]) -> |_index, value| { value.n }"#,
+                result: Ok(r#"[{ "n": 1 }, { "n": 2 }]"#),
+            },
+            Example {
+                title: "sort by length",
+                source: r#"sort_by(["ccc", "a", "bb"]) -> |_index, value| { length(value) }"#,
+                result: Ok(r#"["a", "bb", "ccc"]"#),
+            },
+        ]
+    }
+
+    fn compile(
+        &self,
+        _state: (&mut state::LocalEnv, &mut state::ExternalEnv),
+        _ctx: &mut FunctionCompileContext,
+        mut arguments: ArgumentList,
+    ) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure()?;
+
+        Ok(Box::new(SortByFn { value, closure }))
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        use closure::{Definition, Input, Output, Variable, VariableKind};
+
+        Some(Definition {
+            inputs: vec![Input {
+                parameter_keyword: "value",
+                kind: Kind::array(Collection::any()),
+                variables: vec![
+                    Variable {
+                        kind: VariableKind::TargetInnerKey,
+                    },
+                    Variable {
+                        kind: VariableKind::TargetInnerValue,
+                    },
+                ],
+                output: Output::Kind(
+                    Kind::bytes()
+                        .or_integer()
+                        .or_float()
+                        .or_boolean()
+                        .or_timestamp()
+                        .or_null(),
+                ),
+                example: Example {
+                    title: "sort descending",
+                    source: r#"sort_by([1, 3, 2]) -> |_index, value| { 0 - value }"#,
+                    result: Ok("[3, 2, 1]"),
+                },
+            }],
+            is_iterator: true,
+        })
+    }
+
+    fn call_by_vm(&self, ctx: &mut Context, args: &mut VmArgumentList) -> Result<Value> {
+        let value = args.required("value");
+        let VmFunctionClosure { variables, vm } = args.closure();
+        let runner = closure::Runner::new(variables, |ctx| vm.interpret(ctx));
+
+        sort_by(value, ctx, runner)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SortByFn {
+    value: Box<dyn Expression>,
+    closure: FunctionClosure,
+}
+
+impl Expression for SortByFn {
+    fn resolve(&self, ctx: &mut Context) -> Result<Value> {
+        let value = self.value.resolve(ctx)?;
+        let FunctionClosure { variables, block } = &self.closure;
+        let runner = closure::Runner::new(variables, |ctx| block.resolve(ctx));
+
+        sort_by(value, ctx, runner)
+    }
+
+    fn type_def(&self, ctx: (&state::LocalEnv, &state::ExternalEnv)) -> TypeDef {
+        let fallible = self.closure.block.type_def(ctx).is_fallible();
+        let element = self
+            .value
+            .type_def(ctx)
+            .as_array()
+            .map_or_else(Kind::any, Collection::reduced_kind);
+
+        // The elements keep their kinds, but not their indices.
+        TypeDef::array(Collection::from_unknown(element)).with_fallibility(fallible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_keys() {
+        assert_eq!(compare(&1.into(), &2.into()), Ordering::Less);
+        assert_eq!(compare(&2.into(), &1.5.into()), Ordering::Greater);
+        assert_eq!(compare(&"b".into(), &"a".into()), Ordering::Greater);
+        assert_eq!(compare(&Value::Null, &"a".into()), Ordering::Less);
+        assert_eq!(compare(&true.into(), &1.into()), Ordering::Less);
+    }
+}
@@ -1,3 +1,4 @@
+use ::value::Value;
 use vrl::prelude::*;
 
 use vrl::prelude::TypeDef as VrlTypeDef;
@@ -53,7 +54,9 @@ impl Function for TypeDef {
     }
 
     fn call_by_vm(&self, _ctx: &mut Context, _args: &mut VmArgumentList) -> Resolved {
-        Err("function not supported in VM runtime.".into())
+        // The type definition is only known at compile time, the VM writes
+        // the value returned by `TypeDefFn::as_value` as a constant instead.
+        Err("function can only be called with a type definition known at compile time".into())
     }
 }
 
@@ -64,7 +67,11 @@ struct TypeDefFn {
 
 impl Expression for TypeDefFn {
     fn resolve(&self, _ctx: &mut Context) -> Resolved {
-        type_def(&self.type_def.clone())
+        type_def(&self.type_def)
+    }
+
+    fn as_value(&self) -> Option<Value> {
+        type_def(&self.type_def).ok()
     }
 
     fn type_def(&self, _state: (&state::LocalEnv, &state::ExternalEnv)) -> VrlTypeDef {
@@ -122,3 +122,165 @@ impl std::str::FromStr for Base64Charset {
         }
     }
 }
+
+/// Splits the first `count` fields of the header of a CEF or LEEF message,
+/// separated by pipes, returning the unescaped fields and the rest of the
+/// message.
+///
+/// Pipes and backslashes within fields are escaped with a backslash.
+#[cfg(any(feature = "parse_cef", feature = "parse_leef"))]
+pub(crate) fn split_header(input: &str, count: usize) -> Result<(Vec<String>, &str), String> {
+    let mut fields = Vec::with_capacity(count);
+    let mut field = String::new();
+    let mut escaped = false;
+
+    for (index, c) in input.char_indices() {
+        match c {
+            '|' | '\\' if escaped => {
+                field.push(c);
+                escaped = false;
+            }
+            c if escaped => {
+                field.push('\\');
+                field.push(c);
+                escaped = false;
+            }
+            '\\' => escaped = true,
+            '|' => {
+                fields.push(std::mem::take(&mut field));
+
+                if fields.len() == count {
+                    return Ok((fields, &input[index + 1..]));
+                }
+            }
+            c => field.push(c),
+        }
+    }
+
+    Err(format!(
+        "expected {} header fields, found {}",
+        count,
+        fields.len()
+    ))
+}
+
+/// The header fields of a CEF message, after the `CEF:` prefix.
+#[cfg(any(feature = "parse_cef", feature = "encode_cef"))]
+pub(crate) const CEF_HEADER_FIELDS: [&str; 7] = [
+    "cefVersion",
+    "deviceVendor",
+    "deviceProduct",
+    "deviceVersion",
+    "deviceEventClassId",
+    "name",
+    "severity",
+];
+
+/// The header fields of a LEEF message, after the `LEEF:` prefix, except for
+/// the attribute delimiter of version 2.0.
+#[cfg(any(feature = "parse_leef", feature = "encode_leef"))]
+pub(crate) const LEEF_HEADER_FIELDS: [&str; 5] = [
+    "leefVersion",
+    "vendor",
+    "product",
+    "productVersion",
+    "eventId",
+];
+
+/// Whether a character can be part of the key of a CEF extension.
+#[cfg(any(feature = "parse_cef", feature = "encode_cef"))]
+pub(crate) fn is_cef_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-' | '[' | ']')
+}
+
+/// Escapes a field of the header of a CEF or LEEF message.
+#[cfg(any(feature = "encode_cef", feature = "encode_leef"))]
+pub(crate) fn escape_header(field: &str) -> Result<String, String> {
+    if field.contains(|c| c == '\n' || c == '\r') {
+        return Err(format!(
+            "header field can't contain line breaks: {:?}",
+            field
+        ));
+    }
+
+    Ok(field.replace('\\', r"\\").replace('|', r"\|"))
+}
+
+/// Converts the value of a field of a CEF or LEEF message to its unescaped
+/// representation, timestamps being represented as milliseconds since the
+/// Unix epoch, skipping null values.
+#[cfg(any(feature = "encode_cef", feature = "encode_leef"))]
+pub(crate) fn encode_field(key: &str, value: ::value::Value) -> Result<Option<String>, String> {
+    use ::value::Value;
+
+    Ok(Some(match value {
+        Value::Bytes(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Value::Integer(integer) => integer.to_string(),
+        Value::Float(float) => float.to_string(),
+        Value::Boolean(boolean) => boolean.to_string(),
+        Value::Timestamp(timestamp) => timestamp.timestamp_millis().to_string(),
+        Value::Null => return Ok(None),
+        value => {
+            return Err(format!(
+                "unsupported value for `{}`: {}",
+                key,
+                value.kind_str()
+            ))
+        }
+    }))
+}
+
+/// Parses a timestamp of a CEF or LEEF message, given in milliseconds since
+/// the Unix epoch, or in one of the `MMM dd yyyy HH:mm:ss[.SSS]` formats both
+/// specifications accept, in the given timezone unless it's explicitly UTC.
+#[cfg(any(feature = "parse_cef", feature = "parse_leef"))]
+pub(crate) fn parse_log_timestamp(
+    value: &str,
+    tz: &vector_common::TimeZone,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::TimeZone;
+
+    const FORMATS: &[&str] = &["%b %d %Y %H:%M:%S%.3f", "%b %d %Y %H:%M:%S"];
+
+    if let Ok(millis) = value.parse::<i64>() {
+        return chrono::Utc.timestamp_millis_opt(millis).single();
+    }
+
+    let utc = value
+        .strip_suffix(" UTC")
+        .or_else(|| value.strip_suffix(" GMT"));
+
+    FORMATS.iter().find_map(|format| match utc {
+        Some(value) => chrono::Utc.datetime_from_str(value, format).ok(),
+        None => tz.datetime_from_str(value, format).ok(),
+    })
+}
+
+/// The largest value a compressed value is decoded into, so that small values can't expand
+/// into more memory than any event could sensibly hold.
+#[cfg(any(feature = "decode_gzip", feature = "decode_zstd"))]
+pub(crate) const MAX_DECOMPRESSED_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Reads a value from the given decoder, failing once it grows past `MAX_DECOMPRESSED_SIZE`.
+#[cfg(any(feature = "decode_gzip", feature = "decode_zstd"))]
+pub(crate) fn read_decompressed(
+    decoder: impl std::io::Read,
+    algorithm: &str,
+) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let mut buffer = Vec::new();
+    decoder
+        .take(MAX_DECOMPRESSED_SIZE + 1)
+        .read_to_end(&mut buffer)
+        .map_err(|_| format!("unable to decode value with {} decoder", algorithm))?;
+
+    if buffer.len() as u64 > MAX_DECOMPRESSED_SIZE {
+        return Err(format!(
+            "decoded value is larger than {} bytes",
+            MAX_DECOMPRESSED_SIZE
+        ));
+    }
+
+    Ok(buffer)
+}
@@ -24,6 +24,9 @@ const SKIP_FUNCTION_EXAMPLES: &[&str] = &[
     "get_metadata_field",
     "set_metadata_field",
     "remove_metadata_field",
+    "get_state",
+    "set_state",
+    "incr_state",
 ];
 
 #[derive(Debug, Deserialize)]
@@ -3,6 +3,7 @@
 
 mod test_enrichment;
 
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Instant;
 
@@ -22,11 +23,8 @@ use vrl_tests::{docs, Test};
 #[global_allocator]
 static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
-/// A list of tests currently only working on the "AST" runtime.
-///
-/// This list should ideally be zero, but might not be if specific features
-/// haven't been released on the "VM" runtime yet.
-static AST_ONLY_TESTS: &[&str] = &["functions/type_def/return type definition"];
+/// The directory in which modules imported by tests are searched for.
+const MODULES_PATH: &str = "modules";
 
 #[derive(Parser, Debug)]
 #[clap(name = "VRL Tests", about = "Vector Remap Language Tests")]
@@ -59,6 +57,11 @@ pub struct Cmd {
     #[clap(short, long = "runtime", default_value_t)]
     runtime: VrlRuntime,
 
+    /// Run each test on both the AST and VM runtimes, failing the test if
+    /// their results or resulting targets differ.
+    #[clap(short, long)]
+    differential: bool,
+
     /// Ignore the Cue tests (to speed up run)
     #[clap(long)]
     ignore_cue: bool,
@@ -74,11 +77,7 @@ impl Cmd {
     }
 }
 
-fn should_run(name: &str, pat: &Option<String>, runtime: VrlRuntime) -> bool {
-    if matches!(runtime, VrlRuntime::Vm) && AST_ONLY_TESTS.contains(&name) {
-        return false;
-    }
-
+fn should_run(name: &str, pat: &Option<String>) -> bool {
     if name == "tests/example.vrl" {
         return false;
     }
@@ -148,13 +147,7 @@ fn main() {
             tests.into_iter()
         })
         .chain(docs::tests(cmd.ignore_cue).into_iter())
-        .filter(|test| {
-            should_run(
-                &format!("{}/{}", test.category, test.name),
-                &cmd.pattern,
-                cmd.runtime,
-            )
-        })
+        .filter(|test| should_run(&format!("{}/{}", test.category, test.name), &cmd.pattern))
         .collect::<Vec<_>>();
 
     for mut test in tests {
@@ -181,6 +174,23 @@ fn main() {
             println!("{}", Colour::Yellow.bold().paint("SKIPPED"));
         }
 
+        if cmd.differential && !test.skip {
+            if let Some(diff) = differential(&test, cmd.timezone()) {
+                println!("{} (differential)", Colour::Red.bold().paint("FAILED"));
+                failed_count += 1;
+
+                if !cmd.no_diff {
+                    println!("{}", diff);
+                }
+
+                if cmd.fail_early {
+                    std::process::exit(1)
+                }
+
+                continue;
+            }
+        }
+
         let state = state::Runtime::default();
         let runtime = Runtime::new(state);
         let mut functions = stdlib::all();
@@ -191,7 +201,12 @@ fn main() {
         state.set_external_context(test_enrichment.clone());
 
         let compile_start = Instant::now();
-        let program = vrl::compile_with_state(&test.source, &functions, &mut state);
+        let program = vrl::compile_with_search_path(
+            &test.source,
+            &functions,
+            &mut state,
+            &[PathBuf::from(MODULES_PATH)],
+        );
         let compile_end = compile_start.elapsed();
 
         let want = test.result.clone();
@@ -209,7 +224,7 @@ fn main() {
                     runtime,
                     functions,
                     program,
-                    &mut test,
+                    &mut test.object,
                     timezone,
                     cmd.runtime,
                     state,
@@ -394,7 +409,7 @@ fn run_vrl(
     mut runtime: Runtime,
     functions: Vec<Box<dyn vrl::Function>>,
     program: vrl::Program,
-    test: &mut Test,
+    target: &mut Value,
     timezone: TimeZone,
     vrl_runtime: VrlRuntime,
     mut state: vrl::state::ExternalEnv,
@@ -402,17 +417,102 @@ fn run_vrl(
 ) -> Result<Value, Terminate> {
     match vrl_runtime {
         VrlRuntime::Vm => {
-            let vm = runtime.compile(functions, &program, &mut state).unwrap();
+            let vm = runtime
+                .compile(functions, &program, &mut state)
+                .map_err(|err| {
+                    Terminate::Error(format!("unable to compile to the vm: {}", err).into())
+                })?;
             test_enrichment.finish_load();
-            runtime.run_vm(&vm, &mut test.object, &timezone)
+            runtime.run_vm(&vm, target, &timezone)
         }
         VrlRuntime::Ast => {
             test_enrichment.finish_load();
-            runtime.resolve(&mut test.object, &program, &timezone)
+            runtime.resolve(target, &program, &timezone)
         }
     }
 }
 
+/// Functions returning a different value on each call. The timestamps and
+/// UUIDs in the outcomes of programs calling them are masked before comparing
+/// them, see [`mask_nondeterministic`]. Values of `random_bytes` can't be told
+/// apart from others, so tests should only check properties of them, like
+/// their length.
+const NONDETERMINISTIC_FUNCTIONS: &[&str] = &["now", "uuid_v4"];
+
+/// The outcome of running a test on one of the runtimes: the result of the
+/// program along with the resulting target, or the compilation diagnostics.
+type Outcome = Result<(Result<Value, Terminate>, Value), String>;
+
+/// Runs the test on both runtimes, returning the difference between their
+/// outcomes, if any.
+fn differential(test: &Test, timezone: TimeZone) -> Option<String> {
+    let ast = outcome(test, timezone, VrlRuntime::Ast);
+    let vm = outcome(test, timezone, VrlRuntime::Vm);
+
+    let mut ast = format!("{:#?}", ast);
+    let mut vm = format!("{:#?}", vm);
+    if is_nondeterministic(&test.source) {
+        ast = mask_nondeterministic(&ast);
+        vm = mask_nondeterministic(&vm);
+    }
+
+    (ast != vm).then(|| prettydiff::diff_lines(&ast, &vm).to_string())
+}
+
+fn outcome(test: &Test, timezone: TimeZone, vrl_runtime: VrlRuntime) -> Outcome {
+    let mut functions = stdlib::all();
+    functions.append(&mut enrichment::vrl_functions());
+    let test_enrichment = test_enrichment::test_enrichment_table();
+
+    let mut state = vrl::state::ExternalEnv::default();
+    state.set_external_context(test_enrichment.clone());
+
+    let (program, _) = vrl::compile_with_search_path(
+        &test.source,
+        &functions,
+        &mut state,
+        &[PathBuf::from(MODULES_PATH)],
+    )
+    .map_err(|diagnostics| Formatter::new(&test.source, diagnostics).to_string())?;
+
+    let runtime = Runtime::new(state::Runtime::default());
+    let mut target = test.object.clone();
+    let result = run_vrl(
+        runtime,
+        functions,
+        program,
+        &mut target,
+        timezone,
+        vrl_runtime,
+        state,
+        test_enrichment,
+    );
+
+    Ok((result, target))
+}
+
+fn is_nondeterministic(source: &str) -> bool {
+    NONDETERMINISTIC_FUNCTIONS.iter().any(|function| {
+        source
+            .match_indices(&format!("{}(", function))
+            .any(|(start, _)| !source[..start].ends_with(|c: char| c.is_alphanumeric() || c == '_'))
+    })
+}
+
+/// Replaces the timestamps and UUIDs in the formatted outcome of a program,
+/// which differ between runs of programs calling nondeterministic functions.
+fn mask_nondeterministic(outcome: &str) -> String {
+    let timestamp = regex::Regex::new(
+        r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2}| UTC)",
+    )
+    .unwrap();
+    let uuid =
+        regex::Regex::new(r"[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}").unwrap();
+
+    let outcome = timestamp.replace_all(outcome, "<timestamp>");
+    uuid.replace_all(&outcome, "<uuid>").into_owned()
+}
+
 fn compare_partial_diagnostic(got: &str, want: &str) -> bool {
     got.lines()
         .filter(|line| line.trim().starts_with("error[E"))
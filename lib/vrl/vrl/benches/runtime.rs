@@ -90,6 +90,67 @@ static SOURCES: &[Source] = &[
             .origin, .err = .hostname + "/" + .matches.name + "/" + .matches.num
         "#},
     },
+    Source {
+        name: "closures",
+        target: r#"{
+            "tags": { "env": "prod", "debug": "", "region": "eu", "team": "", "tier": "web" },
+            "codes": [200, 404, 500, 301, 200, 503, 302, 200]
+        }"#,
+        program: indoc! {r#"
+            .tags = filter(object!(.tags)) -> |_key, value| { value != "" }
+            .tags = map_values(.tags) -> |value| { upcase!(value) }
+            .errors = filter(array!(.codes)) -> |_index, value| { int!(value) >= 400 }
+        "#},
+    },
+    Source {
+        name: "user_functions",
+        target: r#"{ "status": "404", "message": "  Not Found  " }"#,
+        program: indoc! {r#"
+            fn status_code(value: string) -> integer {
+                parse_int(value) ?? 0
+            }
+
+            fn normalize(value: string) -> string {
+                downcase(strip_whitespace(value))
+            }
+
+            .status = status_code(string!(.status))
+            .message = normalize(string!(.message))
+        "#},
+    },
+    Source {
+        name: "error_handling",
+        target: r#"{ "status": "abc", "duration": "12.5", "retries": null }"#,
+        program: indoc! {r#"
+            .status = parse_int(.status) ?? 0
+            .duration = to_float(.duration) ?? 0.0
+            .retries, err = to_int(.retries)
+            if err != "" {
+                .retries = 0
+            }
+        "#},
+    },
+    Source {
+        name: "transform",
+        target: r#"{
+            "message": "<13>1 2020-03-13T20:45:38.119Z dynamicwireless.name non 2426 ID931 [exampleSDID@32473 iut=\"3\" eventSource= \"Application\" eventID=\"1011\"] Try to override the THX port, maybe it will reboot the neural interface!",
+            "source_type": "syslog"
+        }"#,
+        program: indoc! {r#"
+            . |= parse_syslog!(.message)
+            .severity = upcase!(.severity)
+            .timestamp = format_timestamp!(.timestamp, format: "%+")
+            .tags = compact({ "host": .hostname, "app": .appname, "env": null })
+
+            if .severity == "ERR" || .severity == "CRIT" {
+                .alert = true
+            } else {
+                del(.procid)
+            }
+
+            del(.source_type)
+        "#},
+    },
 ];
 
 fn benchmark_vrl_runtimes(c: &mut Criterion) {
@@ -10,8 +10,8 @@ pub mod prelude;
 mod runtime;
 
 pub use compiler::{
-    function, state, value, vm::Vm, Context, Expression, Function, Program, ProgramInfo, Target,
-    VrlRuntime,
+    coverage, function, state, value, vm::Vm, Context, Expression, Function, Program, ProgramInfo,
+    Target, VrlRuntime,
 };
 pub use diagnostic;
 pub use runtime::{Runtime, RuntimeResult, Terminate};
@@ -34,6 +34,41 @@ pub fn compile_with_state(
     compiler::compile_with_state(ast, fns, state)
 }
 
+/// Similar to [`compile_with_state`], except that modules imported by the
+/// program are searched for in the given directories, in order.
+pub fn compile_with_search_path(
+    source: &str,
+    fns: &[Box<dyn Function>],
+    state: &mut state::ExternalEnv,
+    search_path: &[std::path::PathBuf],
+) -> compiler::Result {
+    let ast = parser::parse(source)
+        .map_err(|err| diagnostic::DiagnosticList::from(vec![Box::new(err) as Box<_>]))?;
+
+    compiler::compile_with_search_path(ast, fns, state, search_path)
+}
+
+/// Similar to [`compile_with_search_path`], except that the statements of the
+/// program reached at runtime are recorded to the given [`coverage::Coverage`].
+pub fn compile_with_coverage(
+    source: &str,
+    fns: &[Box<dyn Function>],
+    state: &mut state::ExternalEnv,
+    search_path: &[std::path::PathBuf],
+    coverage: &coverage::Coverage,
+) -> compiler::Result {
+    let ast = parser::parse(source)
+        .map_err(|err| diagnostic::DiagnosticList::from(vec![Box::new(err) as Box<_>]))?;
+
+    compiler::compile_with_coverage(ast, fns, state, search_path, coverage)
+}
+
+/// Format a given source, keeping its comments, see [`parser::format`].
+pub fn format(source: &str) -> Result<String, diagnostic::DiagnosticList> {
+    parser::format::format(source)
+        .map_err(|err| diagnostic::DiagnosticList::from(vec![Box::new(err) as Box<_>]))
+}
+
 pub fn compile_for_repl(
     source: &str,
     fns: &[Box<dyn Function>],
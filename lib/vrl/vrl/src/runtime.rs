@@ -72,10 +72,22 @@ impl Runtime {
         program: &Program,
         timezone: &TimeZone,
     ) -> RuntimeResult {
-        // Validate that the path is an object.
-        //
-        // VRL technically supports any `Value` object as the root, but the
-        // assumption is people are expected to use it to query objects.
+        self.validate_target(target)?;
+
+        let mut ctx = Context::new(target, &mut self.state, timezone);
+
+        program.resolve(&mut ctx).map_err(|err| match err {
+            #[cfg(feature = "expr-abort")]
+            ExpressionError::Abort { .. } => Terminate::Abort(err),
+            err @ ExpressionError::Error { .. } => Terminate::Error(err),
+        })
+    }
+
+    /// Validates that the root of the [`Target`] is an object.
+    ///
+    /// VRL technically supports any `Value` object as the root, but the
+    /// assumption is people are expected to use it to query objects.
+    fn validate_target(&self, target: &dyn Target) -> Result<(), Terminate> {
         match target.target_get(&self.root_lookup) {
             Ok(Some(&Value::Object(_))) => {}
             Ok(Some(value)) => {
@@ -100,13 +112,7 @@ impl Runtime {
             }
         };
 
-        let mut ctx = Context::new(target, &mut self.state, timezone);
-
-        program.resolve(&mut ctx).map_err(|err| match err {
-            #[cfg(feature = "expr-abort")]
-            ExpressionError::Abort { .. } => Terminate::Abort(err),
-            err @ ExpressionError::Error { .. } => Terminate::Error(err),
-        })
+        Ok(())
     }
 
     pub fn compile(
@@ -132,6 +138,8 @@ impl Runtime {
         target: &mut dyn Target,
         timezone: &TimeZone,
     ) -> Result<Value, Terminate> {
+        self.validate_target(target)?;
+
         let mut context = Context::new(target, &mut self.state, timezone);
         vm.interpret(&mut context).map_err(|err| match err {
             #[cfg(feature = "expr-abort")]
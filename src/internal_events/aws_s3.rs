@@ -0,0 +1,31 @@
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+use super::prelude::{error_stage, error_type};
+
+#[derive(Debug)]
+pub struct AwsS3MultipartUploadError<'a> {
+    pub error: &'a crate::Error,
+    pub operation: &'static str,
+    pub key: &'a str,
+}
+
+impl InternalEvent for AwsS3MultipartUploadError<'_> {
+    fn emit(self) {
+        error!(
+            message = "Multipart upload failed.",
+            error = %self.error,
+            operation = self.operation,
+            key = %self.key,
+            error_code = "multipart_upload",
+            error_type = error_type::REQUEST_FAILED,
+            stage = error_stage::SENDING,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_code" => "multipart_upload",
+            "error_type" => error_type::REQUEST_FAILED,
+            "stage" => error_stage::SENDING,
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use std::fmt::Display;
+
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+use super::prelude::{error_stage, error_type};
+
+#[derive(Debug)]
+pub struct ClickhouseRowCoercionError<'a, E> {
+    pub column: &'a str,
+    pub error: &'a E,
+}
+
+impl<E: Display> InternalEvent for ClickhouseRowCoercionError<'_, E> {
+    fn emit(self) {
+        error!(
+            message = "Event doesn't fit the columns of the table; dropping event.",
+            column = %self.column,
+            error = %self.error,
+            error_type = error_type::CONVERSION_FAILED,
+            stage = error_stage::SENDING,
+            internal_log_rate_secs = 10,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_type" => error_type::CONVERSION_FAILED,
+            "stage" => error_stage::SENDING,
+        );
+        counter!(
+            "component_discarded_events_total", 1,
+            "error_type" => error_type::CONVERSION_FAILED,
+            "stage" => error_stage::SENDING,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct ClickhouseSchemaFetchError<'a, E> {
+    pub error: &'a E,
+}
+
+impl<E: Display> InternalEvent for ClickhouseSchemaFetchError<'_, E> {
+    fn emit(self) {
+        error!(
+            message = "Failed to fetch the schema of the table.",
+            error = %self.error,
+            error_type = error_type::REQUEST_FAILED,
+            stage = error_stage::SENDING,
+            internal_log_rate_secs = 10,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_type" => error_type::REQUEST_FAILED,
+            "stage" => error_stage::SENDING,
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+#[derive(Debug)]
+pub struct EndpointEjected<'a> {
+    pub endpoint: &'a str,
+    pub backoff: Duration,
+}
+
+impl InternalEvent for EndpointEjected<'_> {
+    fn emit(self) {
+        warn!(
+            message = "Endpoint is unhealthy, no longer sending requests to it.",
+            endpoint = %self.endpoint,
+            backoff_secs = %self.backoff.as_secs_f64(),
+        );
+        counter!(
+            "endpoint_ejections_total", 1,
+            "endpoint" => self.endpoint.to_owned(),
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct EndpointRestored<'a> {
+    pub endpoint: &'a str,
+}
+
+impl InternalEvent for EndpointRestored<'_> {
+    fn emit(self) {
+        info!(
+            message = "Endpoint is healthy again, resuming requests to it.",
+            endpoint = %self.endpoint,
+        );
+        counter!(
+            "endpoint_restorations_total", 1,
+            "endpoint" => self.endpoint.to_owned(),
+        );
+    }
+}
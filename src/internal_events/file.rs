@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, path::Path};
 
 use bytes::Bytes;
 use metrics::{counter, gauge};
@@ -67,6 +67,38 @@ impl<'a> InternalEvent for FileIoError<'a> {
     }
 }
 
+#[derive(Debug)]
+pub struct FileRotated<'a> {
+    pub path: &'a Path,
+    pub rotated_path: &'a Path,
+}
+
+impl InternalEvent for FileRotated<'_> {
+    fn emit(self) {
+        debug!(
+            message = "Rotated file.",
+            path = ?self.path,
+            rotated_path = ?self.rotated_path,
+        );
+        counter!("files_rotated_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct FileRetentionDeleted<'a> {
+    pub path: &'a Path,
+}
+
+impl InternalEvent for FileRetentionDeleted<'_> {
+    fn emit(self) {
+        debug!(
+            message = "Deleted rotated file beyond retention limits.",
+            path = ?self.path,
+        );
+        counter!("rotated_files_deleted_total", 1);
+    }
+}
+
 #[cfg(any(feature = "sources-file", feature = "sources-kubernetes_logs"))]
 mod source {
     use std::{io::Error, path::Path, time::Duration};
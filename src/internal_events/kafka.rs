@@ -147,6 +147,20 @@ impl InternalEvent for KafkaStatisticsReceived<'_> {
             "kafka_consumed_messages_bytes_total",
             self.statistics.rxmsg_bytes as u64
         );
+
+        for (topic_id, topic) in &self.statistics.topics {
+            for (partition_id, partition) in &topic.partitions {
+                // Producers, and consumers for partitions they don't consume, report a lag of -1.
+                if *partition_id >= 0 && partition.consumer_lag >= 0 {
+                    gauge!(
+                        "kafka_consumer_lag",
+                        partition.consumer_lag as f64,
+                        "topic_id" => topic_id.clone(),
+                        "partition_id" => partition_id.to_string(),
+                    );
+                }
+            }
+        }
     }
 }
 
@@ -173,3 +187,28 @@ impl InternalEvent for KafkaHeaderExtractionError<'_> {
         counter!("kafka_header_extraction_failures_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct KafkaTransactionError<'a> {
+    pub error: &'a rdkafka::error::KafkaError,
+    pub operation: &'static str,
+}
+
+impl InternalEvent for KafkaTransactionError<'_> {
+    fn emit(self) {
+        error!(
+            message = "Kafka transaction failed.",
+            error = %self.error,
+            operation = self.operation,
+            error_code = "kafka_transaction",
+            error_type = error_type::REQUEST_FAILED,
+            stage = error_stage::SENDING,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_code" => "kafka_transaction",
+            "error_type" => error_type::REQUEST_FAILED,
+            "stage" => error_stage::SENDING,
+        );
+    }
+}
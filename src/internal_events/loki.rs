@@ -49,3 +49,19 @@ impl InternalEvent for LokiOutOfOrderEventRewritten {
         counter!("rewritten_timestamp_events_total", self.count as u64);
     }
 }
+
+#[derive(Debug)]
+pub struct LokiLabelSetLimited<'a> {
+    pub tenant_id: Option<&'a str>,
+}
+
+impl InternalEvent for LokiLabelSetLimited<'_> {
+    fn emit(self) {
+        debug!(
+            message = "Tenant is over its label set limit, moving dynamic labels into the log line.",
+            tenant_id = ?self.tenant_id,
+            internal_log_rate_secs = 10,
+        );
+        counter!("label_set_limited_events_total", 1);
+    }
+}
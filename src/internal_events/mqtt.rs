@@ -0,0 +1,95 @@
+use metrics::counter;
+use rumqttc::{ClientError, ConnectionError};
+use vector_core::internal_event::InternalEvent;
+
+use super::prelude::{error_stage, error_type};
+
+#[derive(Debug)]
+pub struct MqttConnectionError {
+    pub error: ConnectionError,
+    pub stage: &'static str,
+}
+
+impl InternalEvent for MqttConnectionError {
+    fn emit(self) {
+        error!(
+            message = "MQTT connection error; reconnecting.",
+            error = %self.error,
+            error_type = error_type::CONNECTION_FAILED,
+            stage = self.stage,
+            internal_log_rate_secs = 10,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_type" => error_type::CONNECTION_FAILED,
+            "stage" => self.stage,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct MqttSubscribeError {
+    pub error: ClientError,
+}
+
+impl InternalEvent for MqttSubscribeError {
+    fn emit(self) {
+        error!(
+            message = "Failed to subscribe to topics.",
+            error = %self.error,
+            error_type = error_type::READER_FAILED,
+            stage = error_stage::RECEIVING,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_type" => error_type::READER_FAILED,
+            "stage" => error_stage::RECEIVING,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct MqttAcknowledgementError {
+    pub error: ClientError,
+}
+
+impl InternalEvent for MqttAcknowledgementError {
+    fn emit(self) {
+        error!(
+            message = "Failed to acknowledge message.",
+            error = %self.error,
+            error_type = error_type::ACKNOWLEDGMENT_FAILED,
+            stage = error_stage::RECEIVING,
+            internal_log_rate_secs = 10,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_type" => error_type::ACKNOWLEDGMENT_FAILED,
+            "stage" => error_stage::RECEIVING,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct MqttEventSendError {
+    pub error: ClientError,
+}
+
+impl InternalEvent for MqttEventSendError {
+    fn emit(self) {
+        error!(
+            message = "Failed to send message.",
+            error = %self.error,
+            error_type = error_type::WRITER_FAILED,
+            stage = error_stage::SENDING,
+            internal_log_rate_secs = 10,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_type" => error_type::WRITER_FAILED,
+            "stage" => error_stage::SENDING,
+        );
+        // deprecated
+        counter!("send_errors_total", 1);
+    }
+}
@@ -0,0 +1,54 @@
+use metrics::counter;
+use vector_core::internal_event::InternalEvent;
+
+use super::prelude::{error_stage, error_type};
+
+#[derive(Debug)]
+pub struct ParquetValueConversionError<'a> {
+    pub column: &'a str,
+    pub column_type: &'a str,
+    pub count: usize,
+}
+
+impl InternalEvent for ParquetValueConversionError<'_> {
+    fn emit(self) {
+        error!(
+            message = "Values don't fit the type of their column; writing them as nulls.",
+            column = %self.column,
+            column_type = %self.column_type,
+            count = %self.count,
+            error_type = error_type::CONVERSION_FAILED,
+            stage = error_stage::PROCESSING,
+            internal_log_rate_secs = 10,
+        );
+        counter!(
+            "component_errors_total", self.count as u64,
+            "error_type" => error_type::CONVERSION_FAILED,
+            "stage" => error_stage::PROCESSING,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct ParquetFieldsDropped<'a> {
+    pub fields: &'a [&'a str],
+    pub count: usize,
+}
+
+impl InternalEvent for ParquetFieldsDropped<'_> {
+    fn emit(self) {
+        error!(
+            message = "Fields aren't part of the schema inferred from the first batch; dropping them.",
+            fields = ?self.fields,
+            count = %self.count,
+            error_type = error_type::CONDITION_FAILED,
+            stage = error_stage::PROCESSING,
+            internal_log_rate_secs = 10,
+        );
+        counter!(
+            "component_errors_total", self.count as u64,
+            "error_type" => error_type::CONDITION_FAILED,
+            "stage" => error_stage::PROCESSING,
+        );
+    }
+}
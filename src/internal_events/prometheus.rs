@@ -3,6 +3,8 @@ use std::borrow::Cow;
 use std::time::Instant;
 
 use hyper::StatusCode;
+#[cfg(feature = "sources-prometheus")]
+use metrics::gauge;
 use metrics::{counter, histogram};
 #[cfg(feature = "sources-prometheus")]
 use prometheus_parser::ParserError;
@@ -185,3 +187,52 @@ impl InternalEvent for PrometheusServerRequestComplete {
         counter!("requests_received_total", 1);
     }
 }
+
+#[cfg(feature = "sources-prometheus")]
+#[derive(Debug)]
+pub struct PrometheusTargetsDiscovered<'a> {
+    pub provider: &'a str,
+    pub count: usize,
+}
+
+#[cfg(feature = "sources-prometheus")]
+impl<'a> InternalEvent for PrometheusTargetsDiscovered<'a> {
+    fn emit(self) {
+        debug!(
+            message = "Scrape targets updated.",
+            provider = %self.provider,
+            count = %self.count,
+        );
+        gauge!(
+            "discovered_targets", self.count as f64,
+            "provider" => self.provider.to_string(),
+        );
+    }
+}
+
+#[cfg(feature = "sources-prometheus")]
+#[derive(Debug)]
+pub struct PrometheusDiscoveryError {
+    pub provider: &'static str,
+    pub error: crate::Error,
+}
+
+#[cfg(feature = "sources-prometheus")]
+impl InternalEvent for PrometheusDiscoveryError {
+    fn emit(self) {
+        error!(
+            message = "Target discovery failed.",
+            provider = %self.provider,
+            error = %self.error,
+            error_type = error_type::REQUEST_FAILED,
+            stage = error_stage::RECEIVING,
+            internal_log_rate_secs = 10,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "provider" => self.provider,
+            "error_type" => error_type::REQUEST_FAILED,
+            "stage" => error_stage::RECEIVING,
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use metrics::counter;
+use std::{io, path::Path};
+
+use metrics::{counter, gauge};
 use vector_core::internal_event::InternalEvent;
 
 use super::prelude::{error_stage, error_type};
@@ -69,3 +71,60 @@ impl InternalEvent for RemapMappingAbort {
         }
     }
 }
+
+#[derive(Debug)]
+pub struct RemapStateStoreMetrics {
+    pub entries: usize,
+    pub bytes: usize,
+    pub evicted: u64,
+    pub expired: u64,
+}
+
+impl InternalEvent for RemapStateStoreMetrics {
+    fn emit(self) {
+        gauge!("remap_state_entries", self.entries as f64);
+        gauge!("remap_state_bytes", self.bytes as f64);
+        counter!("remap_state_evictions_total", self.evicted, "reason" => "limit");
+        counter!("remap_state_evictions_total", self.expired, "reason" => "expired");
+    }
+}
+
+#[derive(Debug)]
+pub struct RemapStateCheckpointLoaded<'a> {
+    pub entries: usize,
+    pub path: &'a Path,
+}
+
+impl<'a> InternalEvent for RemapStateCheckpointLoaded<'a> {
+    fn emit(self) {
+        debug!(
+            message = "Loaded state checkpoint.",
+            entries = %self.entries,
+            path = ?self.path,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct RemapStateCheckpointError<'a> {
+    pub error: io::Error,
+    pub path: &'a Path,
+}
+
+impl<'a> InternalEvent for RemapStateCheckpointError<'a> {
+    fn emit(self) {
+        error!(
+            message = "Failed to access state checkpoint.",
+            error = %self.error,
+            path = ?self.path,
+            error_type = error_type::IO_FAILED,
+            stage = error_stage::PROCESSING,
+            internal_log_rate_secs = 10,
+        );
+        counter!(
+            "component_errors_total", 1,
+            "error_type" => error_type::IO_FAILED,
+            "stage" => error_stage::PROCESSING,
+        );
+    }
+}
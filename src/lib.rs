@@ -41,6 +41,8 @@ pub mod internal_events;
 #[cfg(feature = "api")]
 #[allow(unreachable_pub)]
 pub mod api;
+#[cfg(any(feature = "sources-amqp", feature = "sinks-amqp"))]
+pub(crate) mod amqp;
 pub mod app;
 pub mod async_read;
 #[cfg(feature = "aws-config")]
@@ -60,6 +62,8 @@ pub(crate) mod kafka;
 pub mod kubernetes;
 pub mod line_agg;
 pub mod list;
+#[cfg(any(feature = "sources-mqtt", feature = "sinks-mqtt"))]
+pub(crate) mod mqtt;
 #[cfg(any(feature = "sources-nats", feature = "sinks-nats"))]
 pub(crate) mod nats;
 #[allow(unreachable_pub)]
@@ -0,0 +1,241 @@
+use std::{
+    convert::TryFrom,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use rumqttc::{AsyncClient, EventLoop, Key, MqttOptions, QoS, TlsConfiguration, Transport};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+
+use crate::tls::TlsEnableableConfig;
+
+/// Bounds the number of requests (publishes, acknowledgements, subscriptions) queued between the
+/// client and its event loop.
+const REQUEST_CHANNEL_CAPACITY: usize = 100;
+
+#[derive(Debug, Snafu)]
+pub enum MqttConfigError {
+    #[snafu(display("MQTT TLS Config Error: a CA file is required when TLS is enabled"))]
+    TlsMissingCa,
+    #[snafu(display("MQTT TLS Config Error: missing key"))]
+    TlsMissingKey,
+    #[snafu(display("MQTT TLS Config Error: missing cert"))]
+    TlsMissingCert,
+    #[snafu(display("MQTT TLS Config Error: could not read {:?}: {}", path, source))]
+    TlsRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("MQTT Config Error: user and password must be set together"))]
+    IncompleteCredentials,
+}
+
+/// Connection settings shared by the `mqtt` source and sink.
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+pub(crate) struct MqttConnectionConfig {
+    #[derivative(Default(value = "default_host()"))]
+    #[serde(default = "default_host")]
+    pub(crate) host: String,
+    #[derivative(Default(value = "default_port()"))]
+    #[serde(default = "default_port")]
+    pub(crate) port: u16,
+    #[derivative(Default(value = "default_client_id()"))]
+    #[serde(default = "default_client_id")]
+    pub(crate) client_id: String,
+    pub(crate) user: Option<String>,
+    pub(crate) password: Option<String>,
+    #[derivative(Default(value = "default_keep_alive_secs()"))]
+    #[serde(default = "default_keep_alive_secs")]
+    pub(crate) keep_alive_secs: u64,
+    pub(crate) tls: Option<TlsEnableableConfig>,
+}
+
+fn default_host() -> String {
+    String::from("localhost")
+}
+
+const fn default_port() -> u16 {
+    1883
+}
+
+fn default_client_id() -> String {
+    String::from("vector")
+}
+
+const fn default_keep_alive_secs() -> u64 {
+    60
+}
+
+impl MqttConnectionConfig {
+    /// Builds the client options. `client_id` overrides the configured client identifier.
+    pub(crate) fn options(
+        &self,
+        client_id: &str,
+        clean_session: bool,
+    ) -> Result<MqttOptions, MqttConfigError> {
+        let mut options = MqttOptions::new(client_id, &self.host, self.port);
+        options
+            .set_keep_alive(Duration::from_secs(self.keep_alive_secs))
+            .set_clean_session(clean_session);
+
+        match (&self.user, &self.password) {
+            (Some(user), Some(password)) => {
+                options.set_credentials(user, password);
+            }
+            (None, None) => {}
+            _ => return Err(MqttConfigError::IncompleteCredentials),
+        }
+
+        if let Some(tls) = &self.tls {
+            if tls.enabled.unwrap_or(false) {
+                options.set_transport(Transport::Tls(tls_configuration(tls)?));
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Creates a client and the event loop driving its connection. No connection is made until
+    /// the event loop is first polled.
+    pub(crate) fn connect(
+        &self,
+        clean_session: bool,
+        manual_acks: bool,
+    ) -> Result<(AsyncClient, EventLoop), MqttConfigError> {
+        let mut options = self.options(&self.client_id, clean_session)?;
+        options.set_manual_acks(manual_acks);
+        Ok(AsyncClient::new(options, REQUEST_CHANNEL_CAPACITY))
+    }
+}
+
+fn tls_configuration(config: &TlsEnableableConfig) -> Result<TlsConfiguration, MqttConfigError> {
+    let options = &config.options;
+    let ca = read_file(
+        options
+            .ca_file
+            .as_ref()
+            .ok_or(MqttConfigError::TlsMissingCa)?,
+    )?;
+
+    let client_auth = match (&options.crt_file, &options.key_file) {
+        (None, None) => None,
+        (Some(_), None) => return Err(MqttConfigError::TlsMissingKey),
+        (None, Some(_)) => return Err(MqttConfigError::TlsMissingCert),
+        (Some(crt_file), Some(key_file)) => {
+            let crt = read_file(crt_file)?;
+            let key = read_file(key_file)?;
+            // The key type can only be told apart by the PEM label.
+            let key = if String::from_utf8_lossy(&key).contains("EC PRIVATE KEY") {
+                Key::ECC(key)
+            } else {
+                Key::RSA(key)
+            };
+            Some((crt, key))
+        }
+    };
+
+    Ok(TlsConfiguration::Simple {
+        ca,
+        alpn: None,
+        client_auth,
+    })
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, MqttConfigError> {
+    fs::read(path).context(TlsReadSnafu { path })
+}
+
+/// The MQTT quality of service levels, configured by their numeric value.
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(try_from = "u8", into = "u8")]
+pub(crate) enum MqttQoS {
+    AtMostOnce,
+    #[derivative(Default)]
+    AtLeastOnce,
+    ExactlyOnce,
+}
+
+impl TryFrom<u8> for MqttQoS {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MqttQoS::AtMostOnce),
+            1 => Ok(MqttQoS::AtLeastOnce),
+            2 => Ok(MqttQoS::ExactlyOnce),
+            _ => Err(format!("invalid QoS {}, expected 0, 1 or 2", value)),
+        }
+    }
+}
+
+impl From<MqttQoS> for u8 {
+    fn from(qos: MqttQoS) -> Self {
+        match qos {
+            MqttQoS::AtMostOnce => 0,
+            MqttQoS::AtLeastOnce => 1,
+            MqttQoS::ExactlyOnce => 2,
+        }
+    }
+}
+
+impl From<MqttQoS> for QoS {
+    fn from(qos: MqttQoS) -> Self {
+        match qos {
+            MqttQoS::AtMostOnce => QoS::AtMostOnce,
+            MqttQoS::AtLeastOnce => QoS::AtLeastOnce,
+            MqttQoS::ExactlyOnce => QoS::ExactlyOnce,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tls::TlsConfig;
+
+    #[test]
+    fn parses_qos() {
+        #[derive(Deserialize)]
+        struct Config {
+            qos: MqttQoS,
+        }
+
+        let config: Config = toml::from_str("qos = 2").unwrap();
+        assert_eq!(config.qos, MqttQoS::ExactlyOnce);
+        assert!(toml::from_str::<Config>("qos = 3").is_err());
+    }
+
+    #[test]
+    fn rejects_incomplete_credentials() {
+        let config = MqttConnectionConfig {
+            user: Some("vector".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.options("vector", true),
+            Err(MqttConfigError::IncompleteCredentials)
+        ));
+    }
+
+    #[test]
+    fn tls_requires_ca_file() {
+        let mut config = MqttConnectionConfig {
+            tls: Some(TlsEnableableConfig::enabled()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.options("vector", true),
+            Err(MqttConfigError::TlsMissingCa)
+        ));
+
+        config.tls = Some(TlsEnableableConfig {
+            enabled: Some(true),
+            options: TlsConfig::test_config(),
+        });
+        assert!(config.options("vector", true).is_ok());
+    }
+}
@@ -126,6 +126,7 @@ impl CloudwatchLogsSinkConfig {
 #[typetag::serde(name = "aws_cloudwatch_logs")]
 impl SinkConfig for CloudwatchLogsSinkConfig {
     async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        self.compression.ensure_gzip_or_none()?;
         let batcher_settings = self.batch.into_batcher_settings()?;
         let request_settings = self.request.unwrap_with(&TowerRequestConfig::default());
         let client = self.create_client(cx.proxy()).await?;
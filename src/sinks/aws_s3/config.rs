@@ -7,7 +7,10 @@ use serde::{Deserialize, Serialize};
 use tower::ServiceBuilder;
 use vector_core::sink::VectorSink;
 
-use super::sink::S3RequestOptions;
+use super::{
+    multipart::{MultipartRetryLogic, MultipartS3Sink, MultipartService, MultipartUploadConfig},
+    sink::S3RequestOptions,
+};
 use crate::aws::{AwsAuthentication, RegionOrEndpoint};
 use crate::sinks::util::encoding::EncodingConfigWithFramingAdapter;
 use crate::{
@@ -22,6 +25,7 @@ use crate::{
         },
         util::{
             encoding::{EncodingConfig, StandardEncodings, StandardEncodingsWithFramingMigrator},
+            parquet::{ObjectEncoder, ParquetEncodingConfig, PARQUET_CONTENT_TYPE},
             partitioner::KeyPartitioner,
             BatchConfig, BulkSizeBasedDefaultBatchSettings, Compression, ServiceBuilderExt,
             TowerRequestConfig,
@@ -51,8 +55,12 @@ pub struct S3SinkConfig {
         EncodingConfig<StandardEncodings>,
         StandardEncodingsWithFramingMigrator,
     >,
+    /// Writes each batch as a Parquet file instead of encoding its events with `encoding.codec`.
+    pub parquet: Option<ParquetEncodingConfig>,
     #[serde(default = "Compression::gzip_default")]
     pub compression: Compression,
+    /// Streams each batch with a multipart upload instead of uploading it in one go.
+    pub multipart: Option<MultipartUploadConfig>,
     #[serde(default)]
     pub batch: BatchConfig<BulkSizeBasedDefaultBatchSettings>,
     #[serde(default)]
@@ -79,7 +87,9 @@ impl GenerateConfig for S3SinkConfig {
             options: S3Options::default(),
             region: RegionOrEndpoint::default(),
             encoding: EncodingConfig::from(StandardEncodings::Text).into(),
+            parquet: None,
             compression: Compression::gzip_default(),
+            multipart: None,
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
             tls: Some(TlsConfig::default()),
@@ -119,14 +129,7 @@ impl S3SinkConfig {
         service: S3Service,
         cx: SinkContext,
     ) -> crate::Result<VectorSink> {
-        // Build our S3 client/service, which is what we'll ultimately feed
-        // requests into in order to ship files to S3.  We build this here in
-        // order to configure the client/service with retries, concurrency
-        // limits, rate limits, and whatever else the client should have.
         let request_limits = self.request.unwrap_with(&Default::default());
-        let service = ServiceBuilder::new()
-            .settings(request_limits, S3RetryLogic)
-            .service(service);
 
         // Configure our partitioning/batching.
         let batch_settings = self.batch.into_batcher_settings()?;
@@ -134,9 +137,8 @@ impl S3SinkConfig {
             .key_prefix
             .as_ref()
             .cloned()
-            .unwrap_or_else(|| DEFAULT_KEY_PREFIX.into())
-            .try_into()?;
-        let partitioner = KeyPartitioner::new(key_prefix);
+            .unwrap_or_else(|| DEFAULT_KEY_PREFIX.into());
+        let partitioner = KeyPartitioner::new(key_prefix.as_str().try_into()?);
 
         // And now collect all of the S3-specific options and configuration knobs.
         let filename_time_format = self
@@ -162,18 +164,61 @@ impl S3SinkConfig {
             }
         };
         let encoder = Encoder::<Framer>::new(framer, serializer);
+        let encoder =
+            ObjectEncoder::new(transformer, encoder, self.parquet.as_ref(), &batch_settings)?;
+
+        let mut api_options = self.options.clone();
+        if encoder.is_parquet() && api_options.content_type.is_none() {
+            api_options.content_type = Some(PARQUET_CONTENT_TYPE.to_owned());
+        }
 
         let request_options = S3RequestOptions {
             bucket: self.bucket.clone(),
-            api_options: self.options.clone(),
+            api_options,
             filename_extension: self.filename_extension.clone(),
             filename_time_format,
             filename_append_uuid,
-            encoder: (transformer, encoder),
+            encoder,
             compression: self.compression,
         };
 
-        let sink = S3Sink::new(cx, service, request_options, partitioner, batch_settings);
+        if let Some(multipart) = &self.multipart {
+            multipart.validate(self.compression)?;
+            let data_dir = cx.globals.resolve_and_make_data_subdir(
+                multipart.data_dir.as_ref(),
+                &MultipartUploadConfig::data_subdir(&self.bucket, &key_prefix),
+            )?;
+            let multipart_service = ServiceBuilder::new()
+                .settings(request_limits, MultipartRetryLogic::default())
+                .service(MultipartService::new(&service, &request_options));
+            let sink = MultipartS3Sink::new(
+                cx.acker(),
+                multipart_service,
+                service.client(),
+                request_options,
+                partitioner,
+                batch_settings,
+                multipart.part_size_bytes,
+                data_dir,
+            )?;
+            return Ok(VectorSink::from_event_streamsink(sink));
+        }
+
+        // Build our S3 client/service, which is what we'll ultimately feed
+        // requests into in order to ship files to S3.  We build this here in
+        // order to configure the client/service with retries, concurrency
+        // limits, rate limits, and whatever else the client should have.
+        let request_service = ServiceBuilder::new()
+            .settings(request_limits, S3RetryLogic::new())
+            .service(service);
+
+        let sink = S3Sink::new(
+            cx,
+            request_service,
+            request_options,
+            partitioner,
+            batch_settings,
+        );
 
         Ok(VectorSink::from_event_streamsink(sink))
     }
@@ -0,0 +1,99 @@
+//! Keeping track of the multipart uploads in progress, so that those left behind by a previous
+//! run can be aborted.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+const JOURNAL_FILE: &str = "multipart_uploads.json";
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct PendingUpload {
+    pub bucket: String,
+    pub key: String,
+    pub upload_id: String,
+}
+
+/// The multipart uploads this sink has created but not yet completed or aborted.
+///
+/// Uploads only complete once all of their events are delivered, so the events of any upload left
+/// in the journal by a crash are still in the disk buffer and are sent again after the restart,
+/// while the upload itself is only taking up storage and has to be aborted.
+#[derive(Debug)]
+pub struct UploadJournal {
+    path: PathBuf,
+    uploads: Vec<PendingUpload>,
+}
+
+impl UploadJournal {
+    /// Opens the journal in `directory`, along with the uploads left in it by a previous run.
+    pub async fn open(directory: &Path) -> io::Result<Self> {
+        let path = directory.join(JOURNAL_FILE);
+        let uploads = match fs::read(&path).await {
+            Ok(contents) => serde_json::from_slice(&contents)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error),
+        };
+
+        Ok(Self { path, uploads })
+    }
+
+    pub fn pending(&self) -> &[PendingUpload] {
+        &self.uploads
+    }
+
+    pub async fn add(&mut self, upload: PendingUpload) -> io::Result<()> {
+        self.uploads.push(upload);
+        self.persist().await
+    }
+
+    pub async fn remove(&mut self, upload_id: &str) -> io::Result<()> {
+        self.uploads.retain(|upload| upload.upload_id != upload_id);
+        self.persist().await
+    }
+
+    /// Writes the journal next to its final path first, so that it is never left half written.
+    async fn persist(&self) -> io::Result<()> {
+        let contents = serde_json::to_vec(&self.uploads)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let temporary = self.path.with_extension("json.tmp");
+        fs::write(&temporary, contents).await?;
+        fs::rename(&temporary, &self.path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn upload(id: &str) -> PendingUpload {
+        PendingUpload {
+            bucket: "bucket".into(),
+            key: format!("key-{}.log", id),
+            upload_id: id.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_pending_uploads_across_restarts() {
+        let directory = temp_dir();
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let mut journal = UploadJournal::open(&directory).await.unwrap();
+        assert!(journal.pending().is_empty());
+        journal.add(upload("1")).await.unwrap();
+        journal.add(upload("2")).await.unwrap();
+        journal.add(upload("3")).await.unwrap();
+        journal.remove("2").await.unwrap();
+        drop(journal);
+
+        let journal = UploadJournal::open(&directory).await.unwrap();
+        assert_eq!(journal.pending(), &[upload("1"), upload("3")]);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::config::SinkDescription;
 
 mod config;
+mod journal;
+mod multipart;
 mod sink;
 
 #[cfg(test)]
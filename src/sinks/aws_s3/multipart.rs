@@ -0,0 +1,1060 @@
+//! Streaming batches to S3 with multipart uploads, uploading parts as events come in rather than
+//! holding a whole batch in memory.
+
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap, VecDeque},
+    fmt,
+    io::{self, Write},
+    path::PathBuf,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use aws_sdk_s3::{
+    error::{
+        AbortMultipartUploadError, CompleteMultipartUploadError, CreateMultipartUploadError,
+        UploadPartError,
+    },
+    model::{CompletedMultipartUpload, CompletedPart},
+    types::{ByteStream, SdkError},
+    Client as S3Client, Region,
+};
+use bytes::{Bytes, BytesMut};
+use codecs::encoding::Framer;
+use futures::{
+    future::{poll_fn, BoxFuture},
+    stream::{BoxStream, FuturesUnordered},
+    FutureExt, StreamExt,
+};
+use md5::Digest;
+use serde::{Deserialize, Serialize};
+use tokio_util::codec::Encoder as _;
+use tower::Service;
+use tracing::Instrument;
+use vector_core::{
+    buffers::Acker,
+    event::{EventFinalizers, EventStatus, Finalizable},
+    internal_event::EventsSent,
+    partition::Partitioner,
+    sink::StreamSink,
+    stream::BatcherSettings,
+    ByteSizeOf,
+};
+
+use super::{
+    journal::{PendingUpload, UploadJournal},
+    sink::S3RequestOptions,
+};
+use crate::{
+    codecs::Encoder,
+    event::Event,
+    internal_events::{AwsBytesSent, AwsS3MultipartUploadError},
+    sinks::{
+        s3_common::{
+            config::{S3Options, S3RetryLogic},
+            service::{format_tags, S3Service},
+        },
+        util::{
+            encoding::Transformer, parquet::ObjectEncoder, partitioner::KeyPartitioner,
+            Compression, Compressor,
+        },
+    },
+};
+
+/// S3 rejects parts smaller than this, except for the last part of an upload.
+const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+const MAX_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+/// S3 rejects uploads of more parts than this, so uploads reaching it are completed early.
+const MAX_PARTS: usize = 10_000;
+
+const TIMEOUT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+const fn default_part_size_bytes() -> usize {
+    16 * 1024 * 1024
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MultipartUploadConfig {
+    /// The size of the parts uploaded while a batch is written, after compression.
+    #[serde(default = "default_part_size_bytes")]
+    pub part_size_bytes: usize,
+    /// The directory keeping track of the uploads in progress, instead of the global `data_dir`.
+    pub data_dir: Option<PathBuf>,
+}
+
+impl MultipartUploadConfig {
+    pub fn validate(&self, compression: Compression) -> crate::Result<()> {
+        if self.part_size_bytes < MIN_PART_SIZE || self.part_size_bytes as u64 > MAX_PART_SIZE {
+            return Err(format!(
+                "`multipart.part_size_bytes` must be between {} and {}.",
+                MIN_PART_SIZE, MAX_PART_SIZE
+            )
+            .into());
+        }
+        if matches!(compression, Compression::Snappy | Compression::Lz4(_)) {
+            return Err(
+                "Multipart uploads can't be compressed with Snappy or LZ4, `compression` must be \"none\", \"gzip\" or \"zstd\"."
+                    .into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Names the data subdirectory of the sink, which is told apart from others by its bucket and
+    /// key prefix since sinks don't know their own name.
+    pub fn data_subdir(bucket: &str, key_prefix: &str) -> String {
+        let digest = md5::Md5::digest(format!("{}\n{}", bucket, key_prefix).as_bytes());
+        format!("aws_s3_multipart_{:x}", digest)
+    }
+}
+
+/// A request made for a multipart upload, through the `request` settings of the sink.
+#[derive(Clone, Debug)]
+pub enum MultipartRequest {
+    Create {
+        key: String,
+    },
+    UploadPart {
+        key: String,
+        upload_id: String,
+        part_number: i32,
+        body: Bytes,
+    },
+    Complete {
+        key: String,
+        upload_id: String,
+        parts: Vec<CompletedPart>,
+    },
+    Abort {
+        key: String,
+        upload_id: String,
+    },
+}
+
+impl MultipartRequest {
+    const fn operation(&self) -> &'static str {
+        match self {
+            Self::Create { .. } => "create_multipart_upload",
+            Self::UploadPart { .. } => "upload_part",
+            Self::Complete { .. } => "complete_multipart_upload",
+            Self::Abort { .. } => "abort_multipart_upload",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MultipartResponse {
+    /// The ID of the new upload, which S3 is expected to always return.
+    Created(Option<String>),
+    PartUploaded {
+        part_number: i32,
+        e_tag: Option<String>,
+    },
+    Completed,
+    Aborted,
+}
+
+#[derive(Debug)]
+pub enum MultipartError {
+    Create(CreateMultipartUploadError),
+    UploadPart(UploadPartError),
+    Complete(CompleteMultipartUploadError),
+    Abort(AbortMultipartUploadError),
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultipartError::Create(error) => write!(f, "{}", error),
+            MultipartError::UploadPart(error) => write!(f, "{}", error),
+            MultipartError::Complete(error) => write!(f, "{}", error),
+            MultipartError::Abort(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for MultipartError {}
+
+pub type MultipartRetryLogic = S3RetryLogic<MultipartError, MultipartResponse>;
+
+/// Moves the error of one of the operations into a `MultipartError`, keeping the raw response
+/// that retries are decided on.
+fn multipart_error<E>(
+    error: SdkError<E>,
+    operation_error: fn(E) -> MultipartError,
+) -> SdkError<MultipartError> {
+    match error {
+        SdkError::ConstructionFailure(error) => SdkError::ConstructionFailure(error),
+        SdkError::TimeoutError(error) => SdkError::TimeoutError(error),
+        SdkError::DispatchFailure(error) => SdkError::DispatchFailure(error),
+        SdkError::ResponseError { err, raw } => SdkError::ResponseError { err, raw },
+        SdkError::ServiceError { err, raw } => SdkError::ServiceError {
+            err: operation_error(err),
+            raw,
+        },
+    }
+}
+
+/// Makes the requests of the multipart uploads of the sink's bucket.
+#[derive(Clone)]
+pub struct MultipartService {
+    client: S3Client,
+    region: Option<Region>,
+    bucket: String,
+    options: S3Options,
+    compression: Compression,
+}
+
+impl MultipartService {
+    pub fn new(service: &S3Service, request_options: &S3RequestOptions) -> Self {
+        Self {
+            client: service.client(),
+            region: service.region(),
+            bucket: request_options.bucket.clone(),
+            options: request_options.api_options.clone(),
+            compression: request_options.compression,
+        }
+    }
+}
+
+impl Service<MultipartRequest> for MultipartService {
+    type Response = MultipartResponse;
+    type Error = SdkError<MultipartError>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: MultipartRequest) -> Self::Future {
+        let client = self.client.clone();
+        let bucket = self.bucket.clone();
+
+        match request {
+            MultipartRequest::Create { key } => {
+                let options = self.options.clone();
+                let content_encoding = options
+                    .content_encoding
+                    .or_else(|| self.compression.content_encoding().map(Into::into));
+                let content_type = options
+                    .content_type
+                    .or_else(|| Some("text/x-log".to_owned()));
+
+                Box::pin(async move {
+                    let output = client
+                        .create_multipart_upload()
+                        .bucket(bucket)
+                        .key(key)
+                        .set_content_encoding(content_encoding)
+                        .set_content_type(content_type)
+                        .set_acl(options.acl.map(Into::into))
+                        .set_grant_full_control(options.grant_full_control)
+                        .set_grant_read(options.grant_read)
+                        .set_grant_read_acp(options.grant_read_acp)
+                        .set_grant_write_acp(options.grant_write_acp)
+                        .set_server_side_encryption(options.server_side_encryption.map(Into::into))
+                        .set_ssekms_key_id(options.ssekms_key_id)
+                        .set_storage_class(options.storage_class.map(Into::into))
+                        .set_tagging(options.tags.map(format_tags))
+                        .send()
+                        .in_current_span()
+                        .await
+                        .map_err(|error| multipart_error(error, MultipartError::Create))?;
+
+                    Ok(MultipartResponse::Created(
+                        output.upload_id().map(Into::into),
+                    ))
+                })
+            }
+            MultipartRequest::UploadPart {
+                key,
+                upload_id,
+                part_number,
+                body,
+            } => {
+                let region = self.region.clone();
+                let byte_size = body.len();
+                let content_md5 = base64::encode(md5::Md5::digest(&body));
+
+                Box::pin(async move {
+                    let output = client
+                        .upload_part()
+                        .bucket(bucket)
+                        .key(key)
+                        .upload_id(upload_id)
+                        .part_number(part_number)
+                        .content_md5(content_md5)
+                        .body(ByteStream::from(body))
+                        .send()
+                        .in_current_span()
+                        .await
+                        .map_err(|error| multipart_error(error, MultipartError::UploadPart))?;
+
+                    emit!(AwsBytesSent { byte_size, region });
+                    Ok(MultipartResponse::PartUploaded {
+                        part_number,
+                        e_tag: output.e_tag().map(Into::into),
+                    })
+                })
+            }
+            MultipartRequest::Complete {
+                key,
+                upload_id,
+                parts,
+            } => Box::pin(async move {
+                client
+                    .complete_multipart_upload()
+                    .bucket(bucket)
+                    .key(key)
+                    .upload_id(upload_id)
+                    .multipart_upload(
+                        CompletedMultipartUpload::builder()
+                            .set_parts(Some(parts))
+                            .build(),
+                    )
+                    .send()
+                    .in_current_span()
+                    .await
+                    .map_err(|error| multipart_error(error, MultipartError::Complete))?;
+
+                Ok(MultipartResponse::Completed)
+            }),
+            MultipartRequest::Abort { key, upload_id } => Box::pin(async move {
+                client
+                    .abort_multipart_upload()
+                    .bucket(bucket)
+                    .key(key)
+                    .upload_id(upload_id)
+                    .send()
+                    .in_current_span()
+                    .await
+                    .map_err(|error| multipart_error(error, MultipartError::Abort))?;
+
+                Ok(MultipartResponse::Aborted)
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct UploadError {
+    operation: &'static str,
+    error: crate::Error,
+}
+
+impl UploadError {
+    fn new(operation: &'static str, error: impl Into<crate::Error>) -> Self {
+        Self {
+            operation,
+            error: error.into(),
+        }
+    }
+}
+
+/// Acknowledges events in the order they were read from the buffer, like the acknowledgements of
+/// `Driver`, while the uploads holding them finish in any order.
+#[derive(Debug, Default)]
+struct AcknowledgementTracker {
+    /// Runs of consecutive events written to the same upload, oldest first.
+    runs: VecDeque<(u64, usize)>,
+    /// The number of runs left of each upload, and whether the upload is finished.
+    uploads: HashMap<u64, (usize, bool)>,
+}
+
+impl AcknowledgementTracker {
+    fn push(&mut self, upload: u64) {
+        match self.runs.back_mut() {
+            Some((last, count)) if *last == upload => *count += 1,
+            _ => {
+                self.runs.push_back((upload, 1));
+                self.uploads.entry(upload).or_default().0 += 1;
+            }
+        }
+    }
+
+    fn mark_complete(&mut self, upload: u64) {
+        if let Some((_, finished)) = self.uploads.get_mut(&upload) {
+            *finished = true;
+        }
+    }
+
+    /// Consumes the number of events that can be acknowledged, which are those before the oldest
+    /// event of an unfinished upload.
+    fn consume_ack_depth(&mut self) -> usize {
+        let mut ack_depth = 0;
+        while let Some(&(upload, count)) = self.runs.front() {
+            let (runs, finished) = self
+                .uploads
+                .get_mut(&upload)
+                .expect("runs are counted for their upload");
+            if !*finished {
+                break;
+            }
+            ack_depth += count;
+            *runs -= 1;
+            if *runs == 0 {
+                self.uploads.remove(&upload);
+            }
+            self.runs.pop_front();
+        }
+        ack_depth
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum UploadStage {
+    Writing,
+    /// All of the events are written, the last parts are being uploaded.
+    Closed,
+    Completing,
+    Aborting,
+}
+
+/// The object written for one partition of the events, which is uploaded part by part.
+struct OpenUpload {
+    key: String,
+    upload_id: Option<String>,
+    stage: UploadStage,
+    /// The number of parts taken so far, which numbers the next one.
+    part_count: i32,
+    /// The parts taken while the upload is being created, waiting for its ID.
+    waiting: Vec<(i32, Bytes)>,
+    /// The ETags of the uploaded parts, by part number.
+    parts: BTreeMap<i32, Option<String>>,
+    /// The requests made for the upload that haven't been answered yet.
+    in_flight: usize,
+    error: Option<UploadError>,
+    encoder: Encoder<Framer>,
+    compressor: Compressor,
+    /// The last event added, which is written once it's known whether it ends the batch.
+    pending: Option<Event>,
+    finalizers: EventFinalizers,
+    count: usize,
+    byte_size: usize,
+    encoded_size: usize,
+    opened_at: Instant,
+}
+
+impl OpenUpload {
+    fn new(key: String, encoder: Encoder<Framer>, compression: Compression) -> Self {
+        Self {
+            key,
+            upload_id: None,
+            stage: UploadStage::Writing,
+            part_count: 0,
+            waiting: Vec::new(),
+            parts: BTreeMap::new(),
+            in_flight: 0,
+            error: None,
+            encoder,
+            compressor: Compressor::from(compression),
+            pending: None,
+            finalizers: EventFinalizers::default(),
+            count: 0,
+            byte_size: 0,
+            encoded_size: 0,
+            opened_at: Instant::now(),
+        }
+    }
+
+    /// Adds an event, writing out the one before it, the same way events are framed when a batch
+    /// is encoded as a whole.
+    fn push(&mut self, transformer: &Transformer, mut event: Event) -> io::Result<()> {
+        if self.count == 0 {
+            let prefix = self.encoder.batch_prefix().to_vec();
+            self.write(&prefix)?;
+        }
+        self.finalizers.merge(event.take_finalizers());
+        self.count += 1;
+        self.byte_size += event.size_of();
+
+        transformer.transform(&mut event);
+        if let Some(previous) = self.pending.replace(event) {
+            let mut bytes = BytesMut::new();
+            self.encoder
+                .encode(previous, &mut bytes)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            self.write(&bytes)?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.encoded_size += bytes.len();
+        self.compressor.write_all(bytes)
+    }
+
+    /// The compressed bytes waiting to be uploaded.
+    fn buffered(&self) -> usize {
+        self.compressor.get_ref().len()
+    }
+
+    fn take_part(&mut self) -> Bytes {
+        self.compressor
+            .take_output()
+            .expect("multipart uploads are only compressed with streaming compression")
+            .freeze()
+    }
+
+    /// Writes the last event and the end of the batch, returning the last part.
+    fn close(&mut self) -> io::Result<Bytes> {
+        if let Some(last) = self.pending.take() {
+            let mut bytes = BytesMut::new();
+            self.encoder
+                .serialize(last, &mut bytes)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            self.write(&bytes)?;
+        }
+        let suffix = self.encoder.batch_suffix().to_vec();
+        self.write(&suffix)?;
+
+        let compressor =
+            std::mem::replace(&mut self.compressor, Compressor::from(Compression::None));
+        Ok(compressor.finish()?.freeze())
+    }
+}
+
+/// Writes events into the uploads of their partitions, queueing the requests each upload needs
+/// until the service is ready for them.
+struct Uploader {
+    acker: Acker,
+    request_options: S3RequestOptions,
+    transformer: Transformer,
+    encoder: Encoder<Framer>,
+    partitioner: KeyPartitioner,
+    batcher_settings: BatcherSettings,
+    part_size: usize,
+    /// The uploads still taking events, by partition.
+    open: HashMap<String, u64>,
+    uploads: HashMap<u64, OpenUpload>,
+    next_id: u64,
+    queued: VecDeque<(u64, MultipartRequest)>,
+    acks: AcknowledgementTracker,
+}
+
+impl Uploader {
+    fn is_idle(&self) -> bool {
+        self.uploads.is_empty() && self.queued.is_empty()
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        let partition_key = match self.partitioner.partition(&event) {
+            Some(partition_key) => partition_key,
+            None => {
+                // The event is dropped, but it's still acknowledged in order.
+                let id = self.next_id;
+                self.next_id += 1;
+                self.acks.push(id);
+                self.acks.mark_complete(id);
+                self.ack();
+                return;
+            }
+        };
+
+        let id = match self.open.entry(partition_key) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                let id = self.next_id;
+                self.next_id += 1;
+                let key = self.request_options.object_key(entry.key());
+                self.uploads.insert(
+                    id,
+                    OpenUpload::new(key, self.encoder.clone(), self.request_options.compression),
+                );
+                *entry.insert(id)
+            }
+        };
+        self.acks.push(id);
+
+        let upload = self
+            .uploads
+            .get_mut(&id)
+            .expect("open uploads are in progress");
+        if let Err(error) = upload.push(&self.transformer, event) {
+            self.fail(id, UploadError::new("encode", error));
+            self.advance(id);
+            return;
+        }
+        if upload.buffered() >= self.part_size {
+            let part = upload.take_part();
+            self.queue_part(id, part);
+        }
+
+        let upload = &self.uploads[&id];
+        if upload.count >= self.batcher_settings.item_limit
+            || upload.encoded_size >= self.batcher_settings.size_limit
+            || upload.part_count as usize + 1 >= MAX_PARTS
+        {
+            self.close(id);
+        }
+    }
+
+    fn close_expired(&mut self, timeout: Duration) {
+        let expired = self
+            .open
+            .values()
+            .filter(|id| self.uploads[*id].opened_at.elapsed() >= timeout)
+            .copied()
+            .collect::<Vec<_>>();
+        for id in expired {
+            self.close(id);
+        }
+    }
+
+    fn close_all(&mut self) {
+        let open = self.open.values().copied().collect::<Vec<_>>();
+        for id in open {
+            self.close(id);
+        }
+    }
+
+    /// Writes the end of the batch and uploads the last part, after which the upload is completed.
+    fn close(&mut self, id: u64) {
+        self.open.retain(|_, open| *open != id);
+        let upload = self
+            .uploads
+            .get_mut(&id)
+            .expect("open uploads are in progress");
+        upload.stage = UploadStage::Closed;
+        match upload.close() {
+            Ok(last_part) => self.queue_part(id, last_part),
+            Err(error) => self.fail(id, UploadError::new("encode", error)),
+        }
+        self.advance(id);
+    }
+
+    fn queue_part(&mut self, id: u64, body: Bytes) {
+        let upload = self.uploads.get_mut(&id).expect("upload is in progress");
+        upload.part_count += 1;
+        let part_number = upload.part_count;
+        match upload.upload_id.clone() {
+            Some(upload_id) => {
+                upload.in_flight += 1;
+                self.queued.push_back((
+                    id,
+                    MultipartRequest::UploadPart {
+                        key: upload.key.clone(),
+                        upload_id,
+                        part_number,
+                        body,
+                    },
+                ));
+            }
+            None => {
+                // Uploads are created along with their first part.
+                if upload.waiting.is_empty() {
+                    upload.in_flight += 1;
+                    self.queued.push_back((
+                        id,
+                        MultipartRequest::Create {
+                            key: upload.key.clone(),
+                        },
+                    ));
+                }
+                upload.waiting.push((part_number, body));
+            }
+        }
+    }
+
+    /// Stops writing to the upload, which is aborted once its requests in flight are answered.
+    fn fail(&mut self, id: u64, error: UploadError) {
+        self.open.retain(|_, open| *open != id);
+        let upload = self.uploads.get_mut(&id).expect("upload is in progress");
+        upload.waiting.clear();
+        upload.error.get_or_insert(error);
+    }
+
+    /// Moves the upload on once none of its requests are in flight, completing it after its last
+    /// part or aborting it after an error.
+    fn advance(&mut self, id: u64) {
+        let upload = self.uploads.get_mut(&id).expect("upload is in progress");
+        if upload.in_flight > 0 {
+            return;
+        }
+
+        if let Some(error) = upload.error.take() {
+            emit!(AwsS3MultipartUploadError {
+                error: &error.error,
+                operation: error.operation,
+                key: &upload.key,
+            });
+            match upload.upload_id.clone() {
+                Some(upload_id) => {
+                    upload.stage = UploadStage::Aborting;
+                    upload.in_flight += 1;
+                    self.queued.push_back((
+                        id,
+                        MultipartRequest::Abort {
+                            key: upload.key.clone(),
+                            upload_id,
+                        },
+                    ));
+                }
+                None => self.finish(id, EventStatus::Errored),
+            }
+        } else if upload.stage == UploadStage::Closed {
+            let upload_id = upload
+                .upload_id
+                .clone()
+                .expect("the last part was uploaded");
+            let parts = upload
+                .parts
+                .iter()
+                .map(|(part_number, e_tag)| {
+                    CompletedPart::builder()
+                        .set_e_tag(e_tag.clone())
+                        .part_number(*part_number)
+                        .build()
+                })
+                .collect();
+            upload.stage = UploadStage::Completing;
+            upload.in_flight += 1;
+            self.queued.push_back((
+                id,
+                MultipartRequest::Complete {
+                    key: upload.key.clone(),
+                    upload_id,
+                    parts,
+                },
+            ));
+        }
+    }
+
+    async fn handle_response(
+        &mut self,
+        journal: &mut UploadJournal,
+        id: u64,
+        operation: &'static str,
+        result: crate::Result<MultipartResponse>,
+    ) {
+        let upload = self
+            .uploads
+            .get_mut(&id)
+            .expect("requests are only made for uploads in progress");
+        upload.in_flight -= 1;
+
+        match result {
+            Ok(MultipartResponse::Created(Some(upload_id))) => {
+                upload.upload_id = Some(upload_id.clone());
+                let journaled = journal
+                    .add(PendingUpload {
+                        bucket: self.request_options.bucket.clone(),
+                        key: upload.key.clone(),
+                        upload_id: upload_id.clone(),
+                    })
+                    .await;
+                match journaled {
+                    Ok(()) => {
+                        for (part_number, body) in std::mem::take(&mut upload.waiting) {
+                            upload.in_flight += 1;
+                            self.queued.push_back((
+                                id,
+                                MultipartRequest::UploadPart {
+                                    key: upload.key.clone(),
+                                    upload_id: upload_id.clone(),
+                                    part_number,
+                                    body,
+                                },
+                            ));
+                        }
+                    }
+                    Err(error) => self.fail(id, UploadError::new("journal", error)),
+                }
+            }
+            Ok(MultipartResponse::Created(None)) => {
+                self.fail(id, UploadError::new(operation, "No upload ID returned."));
+            }
+            Ok(MultipartResponse::PartUploaded { part_number, e_tag }) => {
+                upload.parts.insert(part_number, e_tag);
+            }
+            Ok(response @ (MultipartResponse::Completed | MultipartResponse::Aborted)) => {
+                let upload_id = upload.upload_id.as_deref().expect("upload was created");
+                if let Err(error) = journal.remove(upload_id).await {
+                    warn!(message = "Failed to update the journal of multipart uploads.", %error);
+                }
+                let status = match response {
+                    MultipartResponse::Completed => EventStatus::Delivered,
+                    _ => EventStatus::Errored,
+                };
+                self.finish(id, status);
+                return;
+            }
+            Err(error) if upload.stage == UploadStage::Aborting => {
+                // The upload stays in the journal, to be aborted on the next start.
+                warn!(
+                    message = "Failed to abort multipart upload.",
+                    key = %upload.key,
+                    %error,
+                );
+                self.finish(id, EventStatus::Errored);
+                return;
+            }
+            Err(error) => self.fail(id, UploadError::new(operation, error)),
+        }
+        self.advance(id);
+    }
+
+    fn finish(&mut self, id: u64, status: EventStatus) {
+        let upload = self.uploads.remove(&id).expect("upload is in progress");
+        if status == EventStatus::Delivered {
+            emit!(EventsSent {
+                count: upload.count,
+                byte_size: upload.byte_size,
+                output: None,
+            });
+        }
+        upload.finalizers.update_status(status);
+        self.acks.mark_complete(id);
+        self.ack();
+    }
+
+    fn ack(&mut self) {
+        let ack_depth = self.acks.consume_ack_depth();
+        if ack_depth > 0 {
+            self.acker.ack(ack_depth);
+        }
+    }
+}
+
+pub struct MultipartS3Sink<Svc> {
+    service: Svc,
+    client: S3Client,
+    data_dir: PathBuf,
+    uploader: Uploader,
+}
+
+impl<Svc> MultipartS3Sink<Svc> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        acker: Acker,
+        service: Svc,
+        client: S3Client,
+        request_options: S3RequestOptions,
+        partitioner: KeyPartitioner,
+        batcher_settings: BatcherSettings,
+        part_size: usize,
+        data_dir: PathBuf,
+    ) -> crate::Result<Self> {
+        let (transformer, encoder) = match &request_options.encoder {
+            ObjectEncoder::Framed((transformer, encoder)) => (transformer.clone(), encoder.clone()),
+            ObjectEncoder::Parquet(..) => {
+                return Err("Parquet files can't be written with multipart uploads.".into())
+            }
+        };
+
+        Ok(Self {
+            service,
+            client,
+            data_dir,
+            uploader: Uploader {
+                acker,
+                request_options,
+                transformer,
+                encoder,
+                partitioner,
+                batcher_settings,
+                part_size,
+                open: HashMap::new(),
+                uploads: HashMap::new(),
+                next_id: 0,
+                queued: VecDeque::new(),
+                acks: AcknowledgementTracker::default(),
+            },
+        })
+    }
+
+    /// Aborts the uploads a previous run didn't get to complete, whose events are sent again from
+    /// the disk buffer.
+    async fn abort_leftover_uploads(&self, journal: &mut UploadJournal) {
+        for upload in journal.pending().to_vec() {
+            let result = self
+                .client
+                .abort_multipart_upload()
+                .bucket(&upload.bucket)
+                .key(&upload.key)
+                .upload_id(&upload.upload_id)
+                .send()
+                .await;
+            match result {
+                Ok(_) => info!(
+                    message = "Aborted multipart upload left over from a previous run.",
+                    key = %upload.key,
+                ),
+                Err(SdkError::ServiceError { err, .. }) if err.is_no_such_upload() => {}
+                Err(error) => {
+                    warn!(
+                        message = "Failed to abort multipart upload left over from a previous run, retrying on the next start.",
+                        key = %upload.key,
+                        %error,
+                    );
+                    continue;
+                }
+            }
+            if let Err(error) = journal.remove(&upload.upload_id).await {
+                warn!(message = "Failed to update the journal of multipart uploads.", %error);
+            }
+        }
+    }
+}
+
+impl<Svc> MultipartS3Sink<Svc>
+where
+    Svc: Service<MultipartRequest, Response = MultipartResponse> + Send + 'static,
+    Svc::Future: Send + 'static,
+    Svc::Error: fmt::Debug + Into<crate::Error> + Send,
+{
+    async fn run_inner(self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let mut journal = UploadJournal::open(&self.data_dir).await.map_err(|error| {
+            error!(
+                message = "Failed to open the journal of multipart uploads.",
+                %error,
+                path = ?self.data_dir,
+            );
+        })?;
+        self.abort_leftover_uploads(&mut journal).await;
+
+        let Self {
+            mut service,
+            mut uploader,
+            ..
+        } = *self;
+        let timeout = uploader.batcher_settings.timeout;
+        let mut timer = tokio::time::interval(
+            TIMEOUT_CHECK_INTERVAL
+                .min(timeout)
+                .max(Duration::from_millis(10)),
+        );
+        let mut in_flight = FuturesUnordered::new();
+        let mut input = input.fuse();
+        let mut input_done = false;
+
+        // Requests are only queued while handling events and responses, so the next event is only
+        // read once the service took all of them, and parts are uploaded while events are written.
+        while !(input_done && uploader.is_idle()) {
+            tokio::select! {
+                biased;
+
+                Some((id, operation, result)) = in_flight.next(), if !in_flight.is_empty() => {
+                    uploader.handle_response(&mut journal, id, operation, result).await;
+                }
+
+                ready = poll_fn(|cx| service.poll_ready(cx)), if !uploader.queued.is_empty() => {
+                    if let Err(error) = ready {
+                        error!(message = "Service return error from `poll_ready()`.", ?error);
+                        return Err(());
+                    }
+                    let (id, request) = uploader.queued.pop_front().expect("requests are queued");
+                    let operation = request.operation();
+                    in_flight.push(service.call(request).map(move |result| {
+                        let result: crate::Result<MultipartResponse> = result.map_err(Into::into);
+                        (id, operation, result)
+                    }));
+                }
+
+                event = input.next(), if !input_done && uploader.queued.is_empty() => match event {
+                    Some(event) => uploader.handle_event(event),
+                    None => {
+                        input_done = true;
+                        uploader.close_all();
+                    }
+                },
+
+                _ = timer.tick() => uploader.close_expired(timeout),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<Svc> StreamSink<Event> for MultipartS3Sink<Svc>
+where
+    Svc: Service<MultipartRequest, Response = MultipartResponse> + Send + 'static,
+    Svc::Future: Send + 'static,
+    Svc::Error: fmt::Debug + Into<crate::Error> + Send,
+{
+    async fn run(self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        self.run_inner(input).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codecs::{
+        CharacterDelimitedEncoder, JsonSerializer, NewlineDelimitedEncoder, RawMessageSerializer,
+    };
+
+    use super::*;
+    use crate::sinks::util::encoding::Encoder as _;
+
+    fn stream(encoder: Encoder<Framer>, events: Vec<Event>) -> Vec<u8> {
+        let mut upload = OpenUpload::new("key".into(), encoder, Compression::None);
+        let mut output = Vec::new();
+        for event in events {
+            upload.push(&Transformer::default(), event).unwrap();
+            output.extend_from_slice(&upload.take_part());
+        }
+        output.extend_from_slice(&upload.close().unwrap());
+        output
+    }
+
+    #[test]
+    fn streams_the_same_bytes_as_whole_batches() {
+        let events = (0..5)
+            .map(|i| Event::from(format!("line {}", i)))
+            .collect::<Vec<_>>();
+
+        for encoder in [
+            Encoder::<Framer>::new(
+                NewlineDelimitedEncoder::new().into(),
+                RawMessageSerializer::new().into(),
+            ),
+            Encoder::<Framer>::new(
+                CharacterDelimitedEncoder::new(b',').into(),
+                JsonSerializer::new().into(),
+            ),
+        ] {
+            let mut batch = Vec::new();
+            (Transformer::default(), encoder.clone())
+                .encode_input(events.clone(), &mut batch)
+                .unwrap();
+
+            assert_eq!(stream(encoder, events.clone()), batch);
+        }
+    }
+
+    #[test]
+    fn acknowledges_events_in_order() {
+        let mut acks = AcknowledgementTracker::default();
+        for upload in [0, 0, 1, 0, 2, 1] {
+            acks.push(upload);
+        }
+
+        acks.mark_complete(1);
+        assert_eq!(acks.consume_ack_depth(), 0);
+        acks.mark_complete(0);
+        assert_eq!(acks.consume_ack_depth(), 4);
+        acks.mark_complete(2);
+        assert_eq!(acks.consume_ack_depth(), 2);
+        assert!(acks.runs.is_empty());
+        assert!(acks.uploads.is_empty());
+    }
+
+    #[test]
+    fn validates_config() {
+        let config = MultipartUploadConfig {
+            part_size_bytes: default_part_size_bytes(),
+            data_dir: None,
+        };
+        assert!(config.validate(Compression::gzip_default()).is_ok());
+        assert!(config.validate(Compression::Snappy).is_err());
+
+        let config = MultipartUploadConfig {
+            part_size_bytes: 1024,
+            data_dir: None,
+        };
+        assert!(config.validate(Compression::None).is_err());
+    }
+}
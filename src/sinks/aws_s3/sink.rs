@@ -2,19 +2,17 @@ use std::io;
 
 use bytes::Bytes;
 use chrono::Utc;
-use codecs::encoding::Framer;
 use uuid::Uuid;
 use vector_core::{event::Finalizable, ByteSizeOf};
 
 use crate::{
-    codecs::Encoder,
     event::Event,
     sinks::{
         s3_common::{
             config::S3Options,
             service::{S3Metadata, S3Request},
         },
-        util::{encoding::Transformer, Compression, RequestBuilder},
+        util::{parquet::ObjectEncoder, Compression, RequestBuilder},
     },
 };
 
@@ -25,20 +23,40 @@ pub struct S3RequestOptions {
     pub filename_append_uuid: bool,
     pub filename_extension: Option<String>,
     pub api_options: S3Options,
-    pub encoder: (Transformer, Encoder<Framer>),
+    pub encoder: ObjectEncoder,
     pub compression: Compression,
 }
 
+impl S3RequestOptions {
+    /// Names a new object for the events partitioned under `partition_key`.
+    pub fn object_key(&self, partition_key: &str) -> String {
+        let filename = {
+            let formatted_ts = Utc::now().format(self.filename_time_format.as_str());
+
+            self.filename_append_uuid
+                .then(|| format!("{}-{}", formatted_ts, Uuid::new_v4().hyphenated()))
+                .unwrap_or_else(|| formatted_ts.to_string())
+        };
+
+        let extension = self
+            .filename_extension
+            .as_ref()
+            .cloned()
+            .unwrap_or_else(|| self.encoder.extension(self.compression).into());
+        format!("{}{}.{}", partition_key, filename, extension)
+    }
+}
+
 impl RequestBuilder<(String, Vec<Event>)> for S3RequestOptions {
     type Metadata = S3Metadata;
     type Events = Vec<Event>;
-    type Encoder = (Transformer, Encoder<Framer>);
+    type Encoder = ObjectEncoder;
     type Payload = Bytes;
     type Request = S3Request;
     type Error = io::Error; // TODO: this is ugly.
 
     fn compression(&self) -> Compression {
-        self.compression
+        self.encoder.compression(self.compression)
     }
 
     fn encoder(&self) -> &Self::Encoder {
@@ -59,20 +77,7 @@ impl RequestBuilder<(String, Vec<Event>)> for S3RequestOptions {
     }
 
     fn build_request(&self, mut metadata: Self::Metadata, payload: Self::Payload) -> Self::Request {
-        let filename = {
-            let formatted_ts = Utc::now().format(self.filename_time_format.as_str());
-
-            self.filename_append_uuid
-                .then(|| format!("{}-{}", formatted_ts, Uuid::new_v4().hyphenated()))
-                .unwrap_or_else(|| formatted_ts.to_string())
-        };
-
-        let extension = self
-            .filename_extension
-            .as_ref()
-            .cloned()
-            .unwrap_or_else(|| self.compression.extension().into());
-        metadata.partition_key = format!("{}{}.{}", metadata.partition_key, filename, extension);
+        metadata.partition_key = self.object_key(&metadata.partition_key);
 
         // TODO: move this into `.request_builder(...)` closure?
         trace!(
@@ -87,7 +92,7 @@ impl RequestBuilder<(String, Vec<Event>)> for S3RequestOptions {
             body: payload,
             bucket: self.bucket.clone(),
             metadata,
-            content_encoding: self.compression.content_encoding(),
+            content_encoding: self.compression().content_encoding(),
             options: self.api_options.clone(),
         }
     }
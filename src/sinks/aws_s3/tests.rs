@@ -17,6 +17,7 @@ mod integration_tests {
     use bytes::Buf;
     use flate2::read::MultiGzDecoder;
     use futures::{stream, Stream};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
     use pretty_assertions::assert_eq;
     use tokio_stream::StreamExt;
     use vector_core::{
@@ -30,14 +31,19 @@ mod integration_tests {
     use crate::{
         config::SinkContext,
         sinks::{
-            aws_s3::S3SinkConfig,
+            aws_s3::{
+                journal::{PendingUpload, UploadJournal},
+                multipart::MultipartUploadConfig,
+                S3SinkConfig,
+            },
             s3_common::config::S3Options,
             util::{
                 encoding::{EncodingConfig, StandardEncodings},
+                parquet::ParquetEncodingConfig,
                 BatchConfig, Compression, TowerRequestConfig,
             },
         },
-        test_util::{random_lines_with_stream, random_string},
+        test_util::{random_lines_with_stream, random_string, temp_dir},
     };
 
     fn s3_address() -> String {
@@ -210,6 +216,63 @@ mod integration_tests {
         assert_eq!(lines, response_lines);
     }
 
+    #[tokio::test]
+    async fn s3_parquet() {
+        let cx = SinkContext::new_test();
+
+        let bucket = uuid::Uuid::new_v4().to_string();
+
+        create_bucket(&bucket, false).await;
+
+        let config = S3SinkConfig {
+            parquet: Some(ParquetEncodingConfig {
+                row_group_size: Some(40),
+                ..Default::default()
+            }),
+            compression: Compression::gzip_default(),
+            ..config(&bucket, 100)
+        };
+        let prefix = config.key_prefix.clone();
+        let service = config.create_service(&cx.globals.proxy).await.unwrap();
+        let sink = config.build_processor(service, cx).unwrap();
+
+        let (lines, events, receiver) = make_events_batch(100, 100);
+        sink.run(events).await.unwrap();
+        assert_eq!(receiver.await, BatchStatus::Delivered);
+
+        let keys = get_keys(&bucket, prefix.unwrap()).await;
+        assert_eq!(keys.len(), 1);
+        assert!(keys[0].ends_with(".parquet"));
+
+        let obj = get_object(&bucket, keys[0].clone()).await;
+        assert_eq!(obj.content_encoding, Some("identity".to_string()));
+        assert_eq!(
+            obj.content_type,
+            Some("application/vnd.apache.parquet".to_string())
+        );
+
+        let mut file = tempfile::tempfile().unwrap();
+        std::io::copy(&mut get_object_output_body(obj).await, &mut file).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 3);
+
+        let messages = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| {
+                row.get_column_iter()
+                    .find(|(name, _)| name.as_str() == "message")
+                    .map(|(_, field)| field.to_string())
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let expected = lines
+            .iter()
+            .map(|line| format!("{:?}", line))
+            .collect::<Vec<_>>();
+        assert_eq!(messages, expected);
+    }
+
     // NOTE: this test doesn't actually validate anything because localstack
     // doesn't enforce the required Content-MD5 header on the request for
     // buckets with object lock enabled
@@ -268,6 +331,102 @@ mod integration_tests {
         assert_eq!(lines, response_lines);
     }
 
+    #[tokio::test]
+    async fn s3_multipart_upload() {
+        let cx = SinkContext::new_test();
+
+        let bucket = uuid::Uuid::new_v4().to_string();
+
+        create_bucket(&bucket, false).await;
+
+        // Around 12MB of events in a single batch, streamed in three 5MB parts.
+        let data_dir = temp_dir();
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let mut config = config(&bucket, 100_000);
+        config.batch.max_bytes = Some(100_000_000);
+        config.multipart = Some(MultipartUploadConfig {
+            part_size_bytes: 5 * 1024 * 1024,
+            data_dir: Some(data_dir),
+        });
+        let prefix = config.key_prefix.clone();
+        let service = config.create_service(&cx.globals.proxy).await.unwrap();
+        let sink = config.build_processor(service, cx).unwrap();
+
+        let (lines, events, receiver) = make_events_batch(1000, 12_000);
+        sink.run(events).await.unwrap();
+        assert_eq!(receiver.await, BatchStatus::Delivered);
+
+        let keys = get_keys(&bucket, prefix.unwrap()).await;
+        assert_eq!(keys.len(), 1);
+
+        let obj = get_object(&bucket, keys[0].clone()).await;
+        let response_lines = get_lines(obj).await;
+        assert_eq!(lines, response_lines);
+
+        assert!(list_multipart_uploads(&bucket).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn s3_multipart_aborts_uploads_left_by_a_previous_run() {
+        let cx = SinkContext::new_test();
+
+        let bucket = uuid::Uuid::new_v4().to_string();
+
+        create_bucket(&bucket, false).await;
+
+        let data_dir = temp_dir();
+        let mut config = config(&bucket, 10);
+        config.multipart = Some(MultipartUploadConfig {
+            part_size_bytes: 5 * 1024 * 1024,
+            data_dir: Some(data_dir.clone()),
+        });
+
+        // An upload created by a run that stopped before completing it.
+        let key = format!("{}/leftover.log", random_string(10));
+        let upload_id = client()
+            .await
+            .create_multipart_upload()
+            .bucket(&bucket)
+            .key(&key)
+            .send()
+            .await
+            .unwrap()
+            .upload_id
+            .unwrap();
+        let journal_dir = data_dir.join(MultipartUploadConfig::data_subdir(
+            &bucket,
+            config.key_prefix.as_deref().unwrap(),
+        ));
+        std::fs::create_dir_all(&journal_dir).unwrap();
+        let mut journal = UploadJournal::open(&journal_dir).await.unwrap();
+        journal
+            .add(PendingUpload {
+                bucket: bucket.clone(),
+                key,
+                upload_id: upload_id.clone(),
+            })
+            .await
+            .unwrap();
+        drop(journal);
+
+        let prefix = config.key_prefix.clone();
+        let service = config.create_service(&cx.globals.proxy).await.unwrap();
+        let sink = config.build_processor(service, cx).unwrap();
+
+        let (lines, events, receiver) = make_events_batch(100, 10);
+        sink.run(events).await.unwrap();
+        assert_eq!(receiver.await, BatchStatus::Delivered);
+
+        let keys = get_keys(&bucket, prefix.unwrap()).await;
+        assert_eq!(keys.len(), 1);
+        let obj = get_object(&bucket, keys[0].clone()).await;
+        assert_eq!(lines, get_lines(obj).await);
+
+        assert!(list_multipart_uploads(&bucket).await.is_empty());
+        let journal = UploadJournal::open(&journal_dir).await.unwrap();
+        assert!(journal.pending().is_empty());
+    }
+
     #[tokio::test]
     async fn acknowledges_failures() {
         let cx = SinkContext::new_test();
@@ -349,7 +508,9 @@ mod integration_tests {
             options: S3Options::default(),
             region: RegionOrEndpoint::with_both("minio", s3_address()),
             encoding: EncodingConfig::from(StandardEncodings::Text).into(),
+            parquet: None,
             compression: Compression::None,
+            multipart: None,
             batch,
             request: TowerRequestConfig::default(),
             tls: Default::default(),
@@ -406,6 +567,18 @@ mod integration_tests {
             .contents
     }
 
+    async fn list_multipart_uploads(bucket: &str) -> Vec<aws_sdk_s3::model::MultipartUpload> {
+        client()
+            .await
+            .list_multipart_uploads()
+            .bucket(bucket.to_string())
+            .send()
+            .await
+            .unwrap()
+            .uploads
+            .unwrap_or_default()
+    }
+
     async fn get_keys(bucket: &str, prefix: String) -> Vec<String> {
         list_objects(bucket, prefix)
             .await
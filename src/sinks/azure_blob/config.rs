@@ -21,6 +21,7 @@ use crate::{
                 EncodingConfig, EncodingConfigWithFramingAdapter, StandardEncodings,
                 StandardEncodingsWithFramingMigrator,
             },
+            parquet::{ObjectEncoder, ParquetEncodingConfig},
             partitioner::KeyPartitioner,
             BatchConfig, BulkSizeBasedDefaultBatchSettings, Compression, ServiceBuilderExt,
             TowerRequestConfig,
@@ -42,6 +43,8 @@ pub struct AzureBlobSinkConfig {
         EncodingConfig<StandardEncodings>,
         StandardEncodingsWithFramingMigrator,
     >,
+    /// Writes each batch as a Parquet file instead of encoding its events with `encoding.codec`.
+    pub parquet: Option<ParquetEncodingConfig>,
     #[serde(default = "Compression::gzip_default")]
     pub compression: Compression,
     #[serde(default)]
@@ -65,6 +68,7 @@ impl GenerateConfig for AzureBlobSinkConfig {
             blob_time_format: Some(String::from("%s")),
             blob_append_uuid: Some(true),
             encoding: EncodingConfig::from(StandardEncodings::Ndjson).into(),
+            parquet: None,
             compression: Compression::gzip_default(),
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
@@ -147,12 +151,18 @@ impl AzureBlobSinkConfig {
             }
         };
         let encoder = Encoder::<Framer>::new(framer, serializer);
+        let encoder = ObjectEncoder::new(
+            transformer,
+            encoder,
+            self.parquet.as_ref(),
+            &batcher_settings,
+        )?;
 
         let request_options = AzureBlobRequestOptions {
             container_name: self.container_name.clone(),
             blob_time_format,
             blob_append_uuid,
-            encoder: (transformer, encoder),
+            encoder,
             compression: self.compression,
         };
 
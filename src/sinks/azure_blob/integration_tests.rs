@@ -212,6 +212,7 @@ impl AzureBlobSinkConfig {
                 blob_time_format: None,
                 blob_append_uuid: None,
                 encoding: EncodingConfig::from(StandardEncodings::Text).into(),
+                parquet: None,
                 compression: Compression::None,
                 batch: Default::default(),
                 request: TowerRequestConfig::default(),
@@ -1,15 +1,16 @@
 use bytes::Bytes;
 use chrono::Utc;
-use codecs::encoding::Framer;
 use uuid::Uuid;
 use vector_core::ByteSizeOf;
 
 use crate::{
-    codecs::Encoder,
     event::{Event, Finalizable},
     sinks::{
         azure_common::config::{AzureBlobMetadata, AzureBlobRequest},
-        util::{encoding::Transformer, Compression, RequestBuilder},
+        util::{
+            parquet::{ObjectEncoder, PARQUET_CONTENT_TYPE},
+            Compression, RequestBuilder,
+        },
     },
 };
 
@@ -18,20 +19,20 @@ pub struct AzureBlobRequestOptions {
     pub container_name: String,
     pub blob_time_format: String,
     pub blob_append_uuid: bool,
-    pub encoder: (Transformer, Encoder<Framer>),
+    pub encoder: ObjectEncoder,
     pub compression: Compression,
 }
 
 impl RequestBuilder<(String, Vec<Event>)> for AzureBlobRequestOptions {
     type Metadata = AzureBlobMetadata;
     type Events = Vec<Event>;
-    type Encoder = (Transformer, Encoder<Framer>);
+    type Encoder = ObjectEncoder;
     type Payload = Bytes;
     type Request = AzureBlobRequest;
     type Error = std::io::Error;
 
     fn compression(&self) -> Compression {
-        self.compression
+        self.encoder.compression(self.compression)
     }
 
     fn encoder(&self) -> &Self::Encoder {
@@ -60,7 +61,8 @@ impl RequestBuilder<(String, Vec<Event>)> for AzureBlobRequestOptions {
                 .unwrap_or_else(|| formatted_ts.to_string())
         };
 
-        let extension = self.compression.extension();
+        let compression = self.compression();
+        let extension = self.encoder.extension(compression);
         metadata.partition_key = format!("{}{}.{}", metadata.partition_key, blob_name, extension);
 
         debug!(
@@ -73,8 +75,12 @@ impl RequestBuilder<(String, Vec<Event>)> for AzureBlobRequestOptions {
 
         AzureBlobRequest {
             blob_data: payload,
-            content_encoding: self.compression.content_encoding(),
-            content_type: self.compression.content_type(),
+            content_encoding: compression.content_encoding(),
+            content_type: if self.encoder.is_parquet() {
+                PARQUET_CONTENT_TYPE
+            } else {
+                compression.content_type()
+            },
             metadata,
         }
     }
@@ -85,6 +91,9 @@ impl Compression {
         match self {
             Self::None => "text/plain",
             Self::Gzip(_) => "application/gzip",
+            Self::Zstd(_) => "application/zstd",
+            Self::Snappy => "application/x-snappy",
+            Self::Lz4(_) => "application/x-lz4",
         }
     }
 }
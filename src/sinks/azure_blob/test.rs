@@ -9,6 +9,7 @@ use crate::codecs::Encoder;
 use crate::event::Event;
 use crate::sinks::util::{
     encoding::{EncodingConfig, StandardEncodings},
+    parquet::ObjectEncoder,
     request_builder::RequestBuilder,
     Compression,
 };
@@ -21,6 +22,7 @@ fn default_config(e: StandardEncodings) -> AzureBlobSinkConfig {
         blob_time_format: Default::default(),
         blob_append_uuid: Default::default(),
         encoding: EncodingConfig::from(e).into(),
+        parquet: Default::default(),
         compression: Compression::gzip_default(),
         batch: Default::default(),
         request: Default::default(),
@@ -56,13 +58,13 @@ fn azure_blob_build_request_without_compression() {
         container_name,
         blob_time_format,
         blob_append_uuid,
-        encoder: (
+        encoder: ObjectEncoder::Framed((
             Default::default(),
             Encoder::<Framer>::new(
                 NewlineDelimitedEncoder::new().into(),
                 RawMessageSerializer::new().into(),
             ),
-        ),
+        )),
         compression,
     };
 
@@ -97,13 +99,13 @@ fn azure_blob_build_request_with_compression() {
         container_name,
         blob_time_format,
         blob_append_uuid,
-        encoder: (
+        encoder: ObjectEncoder::Framed((
             Default::default(),
             Encoder::<Framer>::new(
                 NewlineDelimitedEncoder::new().into(),
                 RawMessageSerializer::new().into(),
             ),
-        ),
+        )),
         compression,
     };
 
@@ -115,6 +117,47 @@ fn azure_blob_build_request_with_compression() {
     assert_eq!(request.content_type, "application/gzip");
 }
 
+#[test]
+fn azure_blob_build_request_with_zstd_compression() {
+    let log = Event::from("test message");
+    let compression = Compression::zstd_default();
+    let container_name = String::from("logs");
+    let sink_config = AzureBlobSinkConfig {
+        blob_prefix: Some("blob".into()),
+        container_name: container_name.clone(),
+        ..default_config(StandardEncodings::Text)
+    };
+    let blob_time_format = String::from("");
+    let blob_append_uuid = false;
+
+    let key = sink_config
+        .key_partitioner()
+        .unwrap()
+        .partition(&log)
+        .expect("key wasn't provided");
+
+    let request_options = AzureBlobRequestOptions {
+        container_name,
+        blob_time_format,
+        blob_append_uuid,
+        encoder: ObjectEncoder::Framed((
+            Default::default(),
+            Encoder::<Framer>::new(
+                NewlineDelimitedEncoder::new().into(),
+                RawMessageSerializer::new().into(),
+            ),
+        )),
+        compression,
+    };
+
+    let (metadata, _events) = request_options.split_input((key, vec![log]));
+    let request = request_options.build_request(metadata, Bytes::new());
+
+    assert_eq!(request.metadata.partition_key, "blob.log.zst".to_string());
+    assert_eq!(request.content_encoding, Some("zstd"));
+    assert_eq!(request.content_type, "application/zstd");
+}
+
 #[test]
 fn azure_blob_build_request_with_time_format() {
     let log = Event::from("test message");
@@ -138,13 +181,13 @@ fn azure_blob_build_request_with_time_format() {
         container_name,
         blob_time_format,
         blob_append_uuid,
-        encoder: (
+        encoder: ObjectEncoder::Framed((
             Default::default(),
             Encoder::<Framer>::new(
                 NewlineDelimitedEncoder::new().into(),
                 RawMessageSerializer::new().into(),
             ),
-        ),
+        )),
         compression,
     };
 
@@ -182,13 +225,13 @@ fn azure_blob_build_request_with_uuid() {
         container_name,
         blob_time_format,
         blob_append_uuid,
-        encoder: (
+        encoder: ObjectEncoder::Framed((
             Default::default(),
             Encoder::<Framer>::new(
                 NewlineDelimitedEncoder::new().into(),
                 RawMessageSerializer::new().into(),
             ),
-        ),
+        )),
         compression,
     };
 
@@ -199,3 +242,44 @@ fn azure_blob_build_request_with_uuid() {
     assert_eq!(request.content_encoding, None);
     assert_eq!(request.content_type, "text/plain");
 }
+
+#[test]
+fn azure_blob_build_parquet_request() {
+    let log = Event::from("test message");
+    let sink_config = AzureBlobSinkConfig {
+        blob_prefix: Some("blob".into()),
+        parquet: Some(Default::default()),
+        ..default_config(StandardEncodings::Text)
+    };
+    let batch_settings = sink_config.batch.into_batcher_settings().unwrap();
+
+    let key = sink_config
+        .key_partitioner()
+        .unwrap()
+        .partition(&log)
+        .expect("key wasn't provided");
+
+    let request_options = AzureBlobRequestOptions {
+        container_name: String::from("logs"),
+        blob_time_format: String::from(""),
+        blob_append_uuid: false,
+        encoder: ObjectEncoder::new(
+            Default::default(),
+            Encoder::<Framer>::new(
+                NewlineDelimitedEncoder::new().into(),
+                RawMessageSerializer::new().into(),
+            ),
+            sink_config.parquet.as_ref(),
+            &batch_settings,
+        )
+        .unwrap(),
+        compression: sink_config.compression,
+    };
+
+    let (metadata, _events) = request_options.split_input((key, vec![log]));
+    let request = request_options.build_request(metadata, Bytes::new());
+
+    assert_eq!(request.metadata.partition_key, "blob.parquet".to_string());
+    assert_eq!(request.content_encoding, None);
+    assert_eq!(request.content_type, "application/vnd.apache.parquet");
+}
@@ -0,0 +1,585 @@
+//! Coercion of log events to the columns of a table, encoded as `JSONEachRow` or `RowBinary` rows.
+
+use bytes::{BufMut, BytesMut};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use snafu::Snafu;
+use vector_core::event::{LogEvent, Value};
+
+use super::schema::{ColumnType, TableSchema};
+
+#[derive(Debug, Snafu)]
+pub enum CoercionError {
+    #[snafu(display("expected {}, found {}", expected, found))]
+    Mismatch {
+        expected: &'static str,
+        found: String,
+    },
+    #[snafu(display("{} is out of range for {}", value, column_type))]
+    OutOfRange {
+        value: String,
+        column_type: &'static str,
+    },
+    #[snafu(display("{:?} is not a variant of the enum", value))]
+    UnknownVariant { value: String },
+    #[snafu(display("type {} is not supported", column_type))]
+    Unsupported { column_type: String },
+}
+
+/// A row that couldn't be coerced to the table, because of the value of the given column.
+#[derive(Debug)]
+pub struct RowError<'a> {
+    pub column: &'a str,
+    pub error: CoercionError,
+}
+
+fn mismatch(expected: &'static str, value: &Value) -> CoercionError {
+    CoercionError::Mismatch {
+        expected,
+        found: value.kind_str().to_owned(),
+    }
+}
+
+fn to_integer(value: &Value) -> Result<i128, CoercionError> {
+    match value {
+        Value::Integer(integer) => Ok(*integer as i128),
+        Value::Float(float) if float.fract() == 0.0 => Ok(float.into_inner() as i128),
+        Value::Boolean(boolean) => Ok(*boolean as i128),
+        Value::Timestamp(timestamp) => Ok(timestamp.timestamp() as i128),
+        Value::Bytes(bytes) => String::from_utf8_lossy(bytes)
+            .trim()
+            .parse()
+            .map_err(|_| mismatch("integer", value)),
+        _ => Err(mismatch("integer", value)),
+    }
+}
+
+fn to_float(value: &Value) -> Result<f64, CoercionError> {
+    match value {
+        Value::Integer(integer) => Ok(*integer as f64),
+        Value::Float(float) => Ok(float.into_inner()),
+        Value::Boolean(boolean) => Ok(*boolean as u8 as f64),
+        Value::Bytes(bytes) => String::from_utf8_lossy(bytes)
+            .trim()
+            .parse()
+            .map_err(|_| mismatch("float", value)),
+        _ => Err(mismatch("float", value)),
+    }
+}
+
+fn to_bool(value: &Value) -> Result<bool, CoercionError> {
+    match value {
+        Value::Boolean(boolean) => Ok(*boolean),
+        Value::Integer(0) => Ok(false),
+        Value::Integer(1) => Ok(true),
+        Value::Bytes(bytes) => match bytes.as_ref() {
+            b"true" | b"1" => Ok(true),
+            b"false" | b"0" => Ok(false),
+            _ => Err(mismatch("boolean", value)),
+        },
+        _ => Err(mismatch("boolean", value)),
+    }
+}
+
+/// Integers and floats are taken as seconds since the Unix epoch, and strings as RFC 3339.
+fn to_timestamp(value: &Value) -> Result<DateTime<Utc>, CoercionError> {
+    match value {
+        Value::Timestamp(timestamp) => Ok(*timestamp),
+        Value::Integer(seconds) => Utc
+            .timestamp_opt(*seconds, 0)
+            .single()
+            .ok_or_else(|| mismatch("timestamp", value)),
+        Value::Float(seconds) => {
+            let nanos = (seconds.fract() * 1e9).round() as i64;
+            Utc.timestamp_opt(seconds.floor() as i64, 0)
+                .single()
+                .map(|timestamp| timestamp + chrono::Duration::nanoseconds(nanos))
+                .ok_or_else(|| mismatch("timestamp", value))
+        }
+        Value::Bytes(bytes) => DateTime::parse_from_rfc3339(String::from_utf8_lossy(bytes).trim())
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+            .map_err(|_| mismatch("timestamp", value)),
+        _ => Err(mismatch("timestamp", value)),
+    }
+}
+
+fn to_uuid(value: &Value) -> Result<uuid::Uuid, CoercionError> {
+    match value {
+        Value::Bytes(bytes) => uuid::Uuid::parse_str(String::from_utf8_lossy(bytes).trim())
+            .map_err(|_| mismatch("UUID", value)),
+        _ => Err(mismatch("UUID", value)),
+    }
+}
+
+fn to_days(timestamp: DateTime<Utc>) -> i64 {
+    timestamp
+        .date()
+        .naive_utc()
+        .signed_duration_since(NaiveDate::from_ymd(1970, 1, 1))
+        .num_days()
+}
+
+/// The number of ticks of a `DateTime64` with the given precision since the Unix epoch.
+fn to_ticks(timestamp: DateTime<Utc>, precision: u32) -> Option<i64> {
+    let subsec = timestamp.timestamp_subsec_nanos() as i64 / 10i64.pow(9 - precision);
+    timestamp
+        .timestamp()
+        .checked_mul(10i64.pow(precision))?
+        .checked_add(subsec)
+}
+
+fn in_range<T: TryFrom<i128>>(value: i128, column_type: &'static str) -> Result<T, CoercionError> {
+    T::try_from(value).map_err(|_| CoercionError::OutOfRange {
+        value: value.to_string(),
+        column_type,
+    })
+}
+
+fn enum_value<T: Copy + Into<i64>>(
+    variants: &[(String, T)],
+    value: &Value,
+) -> Result<T, CoercionError> {
+    let found = match value {
+        Value::Bytes(bytes) => variants
+            .iter()
+            .find(|(name, _)| name.as_bytes() == bytes.as_ref()),
+        Value::Integer(integer) => variants
+            .iter()
+            .find(|(_, variant)| Into::<i64>::into(*variant) == *integer),
+        _ => return Err(mismatch("enum", value)),
+    };
+    found
+        .map(|(_, variant)| *variant)
+        .ok_or_else(|| CoercionError::UnknownVariant {
+            value: value.to_string_lossy(),
+        })
+}
+
+/// Encodes the event as a `JSONEachRow` row, with only the columns of the table.
+///
+/// Columns missing from the event are left out, so that ClickHouse fills in their defaults.
+pub fn encode_json_row<'a>(
+    log: &LogEvent,
+    schema: &'a TableSchema,
+    buffer: &mut BytesMut,
+) -> Result<(), RowError<'a>> {
+    let mut row = serde_json::Map::new();
+    for column in &schema.columns {
+        if let Some(value) = log.get(column.name.as_str()) {
+            let value = to_json(value, &column.column_type).map_err(|error| RowError {
+                column: &column.name,
+                error,
+            })?;
+            row.insert(column.name.clone(), value);
+        }
+    }
+
+    let row = serde_json::to_vec(&row).expect("Rows should be valid json!");
+    buffer.extend_from_slice(&row);
+    buffer.put_u8(b'\n');
+    Ok(())
+}
+
+fn to_json(value: &Value, column_type: &ColumnType) -> Result<serde_json::Value, CoercionError> {
+    use serde_json::Value as Json;
+
+    if let Value::Null = value {
+        return Ok(Json::Null);
+    }
+
+    Ok(match column_type {
+        ColumnType::Nullable(inner) | ColumnType::LowCardinality(inner) => to_json(value, inner)?,
+        ColumnType::Int8 => in_range::<i8>(to_integer(value)?, "Int8")?.into(),
+        ColumnType::Int16 => in_range::<i16>(to_integer(value)?, "Int16")?.into(),
+        ColumnType::Int32 => in_range::<i32>(to_integer(value)?, "Int32")?.into(),
+        ColumnType::Int64 => in_range::<i64>(to_integer(value)?, "Int64")?.into(),
+        ColumnType::UInt8 => in_range::<u8>(to_integer(value)?, "UInt8")?.into(),
+        ColumnType::UInt16 => in_range::<u16>(to_integer(value)?, "UInt16")?.into(),
+        ColumnType::UInt32 => in_range::<u32>(to_integer(value)?, "UInt32")?.into(),
+        ColumnType::UInt64 => in_range::<u64>(to_integer(value)?, "UInt64")?.into(),
+        // JSON numbers aren't precise enough for these, so they go as strings.
+        ColumnType::Int128 => to_integer(value)?.to_string().into(),
+        ColumnType::UInt128 => in_range::<u128>(to_integer(value)?, "UInt128")?
+            .to_string()
+            .into(),
+        ColumnType::Float32 | ColumnType::Float64 => serde_json::Number::from_f64(to_float(value)?)
+            .map(Json::Number)
+            .unwrap_or(Json::Null),
+        ColumnType::Bool => to_bool(value)?.into(),
+        ColumnType::String | ColumnType::FixedString(_) => value.to_string_lossy().into(),
+        ColumnType::Uuid => to_uuid(value)?.to_string().into(),
+        ColumnType::Date | ColumnType::Date32 => {
+            to_timestamp(value)?.format("%Y-%m-%d").to_string().into()
+        }
+        ColumnType::DateTime => {
+            in_range::<u32>(to_timestamp(value)?.timestamp() as i128, "DateTime")?.into()
+        }
+        ColumnType::DateTime64(precision) => {
+            let ticks = to_ticks(to_timestamp(value)?, *precision).ok_or_else(|| {
+                CoercionError::OutOfRange {
+                    value: value.to_string_lossy(),
+                    column_type: "DateTime64",
+                }
+            })?;
+            format_ticks(ticks, *precision).into()
+        }
+        ColumnType::Enum8(variants) => match value {
+            Value::Integer(_) => enum_value(variants, value)?.into(),
+            _ => {
+                enum_value(variants, value)?;
+                value.to_string_lossy().into()
+            }
+        },
+        ColumnType::Enum16(variants) => match value {
+            Value::Integer(_) => enum_value(variants, value)?.into(),
+            _ => {
+                enum_value(variants, value)?;
+                value.to_string_lossy().into()
+            }
+        },
+        ColumnType::Array(inner) => match value {
+            Value::Array(items) => Json::Array(
+                items
+                    .iter()
+                    .map(|item| to_json(item, inner))
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => return Err(mismatch("array", value)),
+        },
+        // JSON keys are always strings, ClickHouse parses them as the key type.
+        ColumnType::Map(_, inner) => match value {
+            Value::Object(map) => Json::Object(
+                map.iter()
+                    .map(|(key, item)| Ok((key.clone(), to_json(item, inner)?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => return Err(mismatch("map", value)),
+        },
+        ColumnType::Other(_) => serde_json::to_value(value).expect("Values should be valid json!"),
+    })
+}
+
+/// Formats ticks as the decimal number of seconds they amount to, which ClickHouse reads as Unix
+/// time in any timezone.
+fn format_ticks(ticks: i64, precision: u32) -> String {
+    if precision == 0 {
+        return ticks.to_string();
+    }
+    let scale = 10u64.pow(precision);
+    let sign = if ticks < 0 { "-" } else { "" };
+    let ticks = ticks.unsigned_abs();
+    format!(
+        "{}{}.{:0width$}",
+        sign,
+        ticks / scale,
+        ticks % scale,
+        width = precision as usize
+    )
+}
+
+/// Encodes the event as a `RowBinary` row, which has every column of the table in order.
+///
+/// Columns missing from the event get the default value of their type, or null if nullable.
+pub fn encode_row_binary<'a>(
+    log: &LogEvent,
+    schema: &'a TableSchema,
+    buffer: &mut BytesMut,
+) -> Result<(), RowError<'a>> {
+    // Written aside first, so that a row failing halfway doesn't leave part of it in the batch.
+    let mut row = BytesMut::new();
+    for column in &schema.columns {
+        write_value(log.get(column.name.as_str()), &column.column_type, &mut row).map_err(
+            |error| RowError {
+                column: &column.name,
+                error,
+            },
+        )?;
+    }
+
+    buffer.extend_from_slice(&row);
+    Ok(())
+}
+
+fn write_varint(buffer: &mut BytesMut, mut value: u64) {
+    while value >= 0x80 {
+        buffer.put_u8((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buffer.put_u8(value as u8);
+}
+
+fn write_string(buffer: &mut BytesMut, bytes: &[u8]) {
+    write_varint(buffer, bytes.len() as u64);
+    buffer.put_slice(bytes);
+}
+
+fn write_value(
+    value: Option<&Value>,
+    column_type: &ColumnType,
+    buffer: &mut BytesMut,
+) -> Result<(), CoercionError> {
+    let value = match (value, column_type) {
+        (_, ColumnType::LowCardinality(inner)) => return write_value(value, inner, buffer),
+        (None | Some(Value::Null), ColumnType::Nullable(_)) => {
+            buffer.put_u8(1);
+            return Ok(());
+        }
+        (Some(value), ColumnType::Nullable(inner)) => {
+            buffer.put_u8(0);
+            return write_value(Some(value), inner, buffer);
+        }
+        (None | Some(Value::Null), _) => {
+            write_default(column_type, buffer);
+            return Ok(());
+        }
+        (Some(value), _) => value,
+    };
+
+    match column_type {
+        ColumnType::Int8 => buffer.put_i8(in_range(to_integer(value)?, "Int8")?),
+        ColumnType::Int16 => buffer.put_i16_le(in_range(to_integer(value)?, "Int16")?),
+        ColumnType::Int32 => buffer.put_i32_le(in_range(to_integer(value)?, "Int32")?),
+        ColumnType::Int64 => buffer.put_i64_le(in_range(to_integer(value)?, "Int64")?),
+        ColumnType::Int128 => buffer.put_i128_le(to_integer(value)?),
+        ColumnType::UInt8 => buffer.put_u8(in_range(to_integer(value)?, "UInt8")?),
+        ColumnType::UInt16 => buffer.put_u16_le(in_range(to_integer(value)?, "UInt16")?),
+        ColumnType::UInt32 => buffer.put_u32_le(in_range(to_integer(value)?, "UInt32")?),
+        ColumnType::UInt64 => buffer.put_u64_le(in_range(to_integer(value)?, "UInt64")?),
+        ColumnType::UInt128 => buffer.put_u128_le(in_range(to_integer(value)?, "UInt128")?),
+        ColumnType::Float32 => buffer.put_f32_le(to_float(value)? as f32),
+        ColumnType::Float64 => buffer.put_f64_le(to_float(value)?),
+        ColumnType::Bool => buffer.put_u8(to_bool(value)? as u8),
+        ColumnType::String => match value {
+            Value::Bytes(bytes) => write_string(buffer, bytes),
+            _ => write_string(buffer, value.to_string_lossy().as_bytes()),
+        },
+        ColumnType::FixedString(length) => {
+            let string = value.to_string_lossy();
+            if string.len() > *length {
+                return Err(CoercionError::OutOfRange {
+                    value: string,
+                    column_type: "FixedString",
+                });
+            }
+            buffer.put_slice(string.as_bytes());
+            buffer.put_bytes(0, length - string.len());
+        }
+        ColumnType::Uuid => {
+            // Stored as two little endian halves, the most significant one first.
+            let uuid = to_uuid(value)?.as_u128();
+            buffer.put_u64_le((uuid >> 64) as u64);
+            buffer.put_u64_le(uuid as u64);
+        }
+        ColumnType::Date => {
+            buffer.put_u16_le(in_range(to_days(to_timestamp(value)?) as i128, "Date")?)
+        }
+        ColumnType::Date32 => {
+            buffer.put_i32_le(in_range(to_days(to_timestamp(value)?) as i128, "Date32")?)
+        }
+        ColumnType::DateTime => buffer.put_u32_le(in_range(
+            to_timestamp(value)?.timestamp() as i128,
+            "DateTime",
+        )?),
+        ColumnType::DateTime64(precision) => {
+            let ticks = to_ticks(to_timestamp(value)?, *precision).ok_or_else(|| {
+                CoercionError::OutOfRange {
+                    value: value.to_string_lossy(),
+                    column_type: "DateTime64",
+                }
+            })?;
+            buffer.put_i64_le(ticks);
+        }
+        ColumnType::Enum8(variants) => buffer.put_i8(enum_value(variants, value)?),
+        ColumnType::Enum16(variants) => buffer.put_i16_le(enum_value(variants, value)?),
+        ColumnType::Array(inner) => match value {
+            Value::Array(items) => {
+                write_varint(buffer, items.len() as u64);
+                for item in items {
+                    write_value(Some(item), inner, buffer)?;
+                }
+            }
+            _ => return Err(mismatch("array", value)),
+        },
+        ColumnType::Map(key_type, inner) => match value {
+            Value::Object(map) => {
+                write_varint(buffer, map.len() as u64);
+                for (key, item) in map {
+                    write_value(Some(&Value::from(key.as_str())), key_type, buffer)?;
+                    write_value(Some(item), inner, buffer)?;
+                }
+            }
+            _ => return Err(mismatch("map", value)),
+        },
+        ColumnType::Nullable(_) | ColumnType::LowCardinality(_) => unreachable!("handled above"),
+        ColumnType::Other(column_type) => {
+            return Err(CoercionError::Unsupported {
+                column_type: column_type.clone(),
+            })
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the value ClickHouse defaults columns of the type to.
+fn write_default(column_type: &ColumnType, buffer: &mut BytesMut) {
+    let width = match column_type {
+        ColumnType::Nullable(_) => {
+            buffer.put_u8(1);
+            return;
+        }
+        ColumnType::LowCardinality(inner) => return write_default(inner, buffer),
+        ColumnType::Enum8(variants) => {
+            buffer.put_i8(variants.first().map_or(0, |(_, value)| *value));
+            return;
+        }
+        ColumnType::Enum16(variants) => {
+            buffer.put_i16_le(variants.first().map_or(0, |(_, value)| *value));
+            return;
+        }
+        // Empty strings, arrays and maps are all a zero length.
+        ColumnType::String | ColumnType::Array(_) | ColumnType::Map(_, _) => 1,
+        ColumnType::Int8 | ColumnType::UInt8 | ColumnType::Bool => 1,
+        ColumnType::Int16 | ColumnType::UInt16 | ColumnType::Date => 2,
+        ColumnType::Int32
+        | ColumnType::UInt32
+        | ColumnType::Float32
+        | ColumnType::Date32
+        | ColumnType::DateTime => 4,
+        ColumnType::Int64
+        | ColumnType::UInt64
+        | ColumnType::Float64
+        | ColumnType::DateTime64(_) => 8,
+        ColumnType::Int128 | ColumnType::UInt128 | ColumnType::Uuid => 16,
+        ColumnType::FixedString(length) => *length,
+        ColumnType::Other(_) => 0,
+    };
+    buffer.put_bytes(0, width);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::sinks::clickhouse::schema::Column;
+
+    fn schema(columns: &[(&str, &str)]) -> TableSchema {
+        TableSchema {
+            columns: columns
+                .iter()
+                .map(|(name, column_type)| Column {
+                    name: (*name).to_owned(),
+                    column_type: ColumnType::parse(column_type),
+                })
+                .collect(),
+        }
+    }
+
+    fn event() -> LogEvent {
+        let mut log = LogEvent::from("hello");
+        log.insert(
+            "timestamp",
+            Utc.ymd(2022, 6, 1).and_hms_milli(10, 15, 0, 120),
+        );
+        log.insert("status", "404");
+        log.insert("level", "warn");
+        log.insert(
+            "tags",
+            Value::from(BTreeMap::from([("env".to_owned(), Value::from(1))])),
+        );
+        log.insert("ids", vec![Value::from(1), Value::from("2")]);
+        log
+    }
+
+    #[test]
+    fn coerces_json_rows() {
+        let schema = schema(&[
+            ("timestamp", "DateTime64(3, 'UTC')"),
+            ("day", "Date"),
+            ("status", "UInt16"),
+            ("level", "LowCardinality(String)"),
+            ("tags", "Map(String, String)"),
+            ("ids", "Array(Int64)"),
+            ("missing", "Nullable(String)"),
+        ]);
+        let mut log = event();
+        log.insert("day", 1_654_078_500);
+
+        let mut buffer = BytesMut::new();
+        encode_json_row(&log, &schema, &mut buffer).unwrap();
+
+        let row: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(
+            row,
+            serde_json::json!({
+                "timestamp": "1654078500.120",
+                "day": "2022-06-01",
+                "status": 404,
+                "level": "warn",
+                "tags": {"env": "1"},
+                "ids": [1, 2],
+            })
+        );
+        assert_eq!(buffer.last(), Some(&b'\n'));
+    }
+
+    #[test]
+    fn reports_column_that_fails_coercion() {
+        let schema = schema(&[("message", "String"), ("level", "UInt8")]);
+
+        let error = encode_json_row(&event(), &schema, &mut BytesMut::new()).unwrap_err();
+        assert_eq!(error.column, "level");
+        assert!(matches!(error.error, CoercionError::Mismatch { .. }));
+    }
+
+    #[test]
+    fn formats_ticks() {
+        assert_eq!(format_ticks(1_654_078_500_120, 3), "1654078500.120");
+        assert_eq!(format_ticks(-1_500, 3), "-1.500");
+        assert_eq!(format_ticks(42, 0), "42");
+    }
+
+    #[test]
+    fn encodes_row_binary() {
+        let schema = schema(&[
+            ("timestamp", "DateTime64(3)"),
+            ("status", "UInt16"),
+            ("level", "Enum8('info' = 1, 'warn' = 2)"),
+            ("tags", "Map(String, String)"),
+            ("ids", "Array(Int64)"),
+            ("missing", "Nullable(String)"),
+            ("absent", "LowCardinality(String)"),
+        ]);
+
+        let mut buffer = BytesMut::new();
+        encode_row_binary(&event(), &schema, &mut buffer).unwrap();
+
+        let mut expected = BytesMut::new();
+        expected.put_i64_le(1_654_078_500_120);
+        expected.put_u16_le(404);
+        expected.put_i8(2);
+        expected.put_slice(b"\x01\x03env\x011");
+        expected.put_slice(b"\x02");
+        expected.put_i64_le(1);
+        expected.put_i64_le(2);
+        expected.put_u8(1);
+        expected.put_u8(0);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn leaves_no_partial_row_binary() {
+        let schema = schema(&[("message", "String"), ("level", "UInt8")]);
+
+        let mut buffer = BytesMut::new();
+        assert!(encode_row_binary(&event(), &schema, &mut buffer).is_err());
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn encodes_varints() {
+        let mut buffer = BytesMut::new();
+        write_varint(&mut buffer, 300);
+        assert_eq!(buffer.as_ref(), &[0xac, 0x02]);
+    }
+}
@@ -1,5 +1,7 @@
+use std::sync::Arc;
+
 use bytes::{BufMut, Bytes, BytesMut};
-use futures::{FutureExt, SinkExt};
+use futures::{future, future::Either, FutureExt, Sink, SinkExt};
 use http::{Request, StatusCode, Uri};
 use hyper::Body;
 use serde::{Deserialize, Serialize};
@@ -7,28 +9,56 @@ use snafu::ResultExt;
 
 use crate::{
     config::{AcknowledgementsConfig, Input, SinkConfig, SinkContext, SinkDescription},
-    event::Event,
+    event::{Event, EventFinalizers, EventStatus},
     http::{Auth, HttpClient, HttpError, MaybeAuth},
+    internal_events::ClickhouseRowCoercionError,
     sinks::util::{
         encoding::{EncodingConfigWithDefault, EncodingConfiguration},
         http::{BatchedHttpSink, HttpEventEncoder, HttpRetryLogic, HttpSink},
         retries::{RetryAction, RetryLogic},
+        service::{resolve_endpoints, DistributionConfig},
         BatchConfig, Buffer, Compression, RealtimeSizeBasedDefaultBatchSettings,
         TowerRequestConfig, UriSerde,
     },
     tls::{TlsConfig, TlsSettings},
 };
 
+mod encoder;
+mod schema;
+
+use self::{
+    encoder::{encode_json_row, encode_row_binary},
+    schema::SchemaCache,
+};
+
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields)]
 pub struct ClickhouseConfig {
     // Deprecated name
-    #[serde(alias = "host")]
+    #[serde(alias = "host", default)]
     pub endpoint: UriSerde,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<UriSerde>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub distribution: DistributionConfig,
     pub table: String,
     pub database: Option<String>,
     #[serde(default)]
     pub skip_unknown_fields: bool,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub format: InsertFormat,
+    /// Fetch the schema of the table and convert the fields of events to the types of its columns.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub coerce_types: bool,
     #[serde(default = "Compression::gzip_default")]
     pub compression: Compression,
     #[serde(
@@ -64,6 +94,44 @@ pub enum Encoding {
     Default,
 }
 
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum InsertFormat {
+    /// One JSON object per line.
+    #[derivative(Default)]
+    JsonEachRow,
+    /// ClickHouse's own binary format, which is cheaper to parse but requires the schema of the
+    /// table.
+    RowBinary,
+}
+
+impl InsertFormat {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::JsonEachRow => "JSONEachRow",
+            Self::RowBinary => "RowBinary",
+        }
+    }
+
+    const fn content_type(self) -> &'static str {
+        match self {
+            Self::JsonEachRow => "application/x-ndjson",
+            Self::RowBinary => "application/octet-stream",
+        }
+    }
+}
+
+impl ClickhouseConfig {
+    fn database(&self) -> &str {
+        self.database.as_deref().unwrap_or("default")
+    }
+
+    const fn needs_schema(&self) -> bool {
+        self.coerce_types || matches!(self.format, InsertFormat::RowBinary)
+    }
+}
+
 #[async_trait::async_trait]
 #[typetag::serde(name = "clickhouse")]
 impl SinkConfig for ClickhouseConfig {
@@ -76,25 +144,84 @@ impl SinkConfig for ClickhouseConfig {
         let tls_settings = TlsSettings::from_options(&self.tls)?;
         let client = HttpClient::new(tls_settings, &cx.proxy)?;
 
-        let config = ClickhouseConfig {
-            auth: self.auth.choose_one(&self.endpoint.auth)?,
-            ..self.clone()
+        let endpoint = Some(&self.endpoint).filter(|endpoint| !endpoint.is_unset());
+        let configs = resolve_endpoints("endpoint", endpoint, &self.endpoints)?
+            .into_iter()
+            .map(|endpoint| {
+                Ok(ClickhouseConfig {
+                    auth: self.auth.choose_one(&endpoint.auth)?,
+                    endpoint,
+                    endpoints: Vec::new(),
+                    ..self.clone()
+                })
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        // All the endpoints insert into the same table, so its schema is only fetched once.
+        let schema = SchemaCache::default();
+        let mut sinks = configs
+            .iter()
+            .map(|config| {
+                let sink = ClickhouseSink {
+                    config: config.clone(),
+                    schema: schema.clone(),
+                };
+                (config.endpoint.uri.to_string(), sink)
+            })
+            .collect::<Vec<_>>();
+
+        let retry_logic = ClickhouseRetryLogic {
+            inner: HttpRetryLogic,
+            schema: schema.clone(),
+        };
+        let sink = if sinks.len() == 1 {
+            let (_, sink) = sinks.swap_remove(0);
+            let sink = BatchedHttpSink::with_logic(
+                sink,
+                Buffer::new(batch.size, self.compression),
+                retry_logic,
+                request,
+                batch.timeout,
+                client.clone(),
+                cx.acker(),
+            )
+            .sink_map_err(|error| error!(message = "Fatal clickhouse sink error.", %error));
+            super::VectorSink::from_event_sink(fetch_schema_first(
+                sink,
+                &configs[0],
+                client.clone(),
+                schema.clone(),
+            ))
+        } else {
+            let sink = BatchedHttpSink::distributed(
+                sinks,
+                Buffer::new(batch.size, self.compression),
+                retry_logic,
+                request,
+                self.distribution,
+                batch.timeout,
+                client.clone(),
+                cx.acker(),
+            )
+            .sink_map_err(|error| error!(message = "Fatal clickhouse sink error.", %error));
+            super::VectorSink::from_event_sink(fetch_schema_first(
+                sink,
+                &configs[0],
+                client.clone(),
+                schema.clone(),
+            ))
         };
 
-        let sink = BatchedHttpSink::with_logic(
-            config.clone(),
-            Buffer::new(batch.size, self.compression),
-            ClickhouseRetryLogic::default(),
-            request,
-            batch.timeout,
-            client.clone(),
-            cx.acker(),
-        )
-        .sink_map_err(|error| error!(message = "Fatal clickhouse sink error.", %error));
-
-        let healthcheck = healthcheck(client, config).boxed();
+        // The sink is healthy as long as any of its endpoints is.
+        let healthcheck = future::select_ok(configs.into_iter().map(|config| {
+            let client = client.clone();
+            let schema = schema.clone();
+            async move { healthcheck(&client, &config, &schema).await }.boxed()
+        }))
+        .map(|result| result.map(|_| ()))
+        .boxed();
 
-        Ok((super::VectorSink::from_event_sink(sink), healthcheck))
+        Ok((sink, healthcheck))
     }
 
     fn input(&self) -> Input {
@@ -112,56 +239,132 @@ impl SinkConfig for ClickhouseConfig {
 
 pub struct ClickhouseEventEncoder {
     encoding: EncodingConfigWithDefault<Encoding>,
+    format: InsertFormat,
+    coerce_types: bool,
+    schema: SchemaCache,
+}
+
+impl ClickhouseEventEncoder {
+    const fn needs_schema(&self) -> bool {
+        self.coerce_types || matches!(self.format, InsertFormat::RowBinary)
+    }
 }
 
 impl HttpEventEncoder<BytesMut> for ClickhouseEventEncoder {
-    fn encode_event(&mut self, mut event: Event) -> Option<BytesMut> {
+    fn encode_event(&mut self, event: Event) -> Option<BytesMut> {
+        self.encode_event_with_finalizers(event, &EventFinalizers::default())
+    }
+
+    fn encode_event_with_finalizers(
+        &mut self,
+        mut event: Event,
+        finalizers: &EventFinalizers,
+    ) -> Option<BytesMut> {
         self.encoding.apply_rules(&mut event);
         let log = event.into_log();
 
-        let mut body = crate::serde::json::to_bytes(&log).expect("Events should be valid json!");
-        body.put_u8(b'\n');
+        if !self.needs_schema() {
+            let mut body =
+                crate::serde::json::to_bytes(&log).expect("Events should be valid json!");
+            body.put_u8(b'\n');
+            return Some(body);
+        }
 
-        Some(body)
+        // The sink waits for the schema to be fetched before passing events on to the encoder.
+        let schema = self
+            .schema
+            .current()
+            .expect("The schema should have been fetched.");
+        let mut body = BytesMut::new();
+        let result = match self.format {
+            InsertFormat::JsonEachRow => encode_json_row(&log, &schema, &mut body),
+            InsertFormat::RowBinary => encode_row_binary(&log, &schema, &mut body),
+        };
+        match result {
+            Ok(()) => Some(body),
+            Err(error) => {
+                emit!(ClickhouseRowCoercionError {
+                    column: error.column,
+                    error: &error.error,
+                });
+                finalizers.update_status(EventStatus::Rejected);
+                // The table may have been altered since the schema was fetched.
+                self.schema.invalidate();
+                None
+            }
+        }
     }
 }
 
+/// Makes sure the schema of the table is up to date before events are passed on to be encoded.
+fn fetch_schema_first<S>(
+    sink: S,
+    config: &ClickhouseConfig,
+    client: HttpClient,
+    schema: SchemaCache,
+) -> impl Sink<Event, Error = ()> + Send + Unpin + 'static
+where
+    S: Sink<Event, Error = ()> + Send + Unpin + 'static,
+{
+    let needs_schema = config.needs_schema();
+    let config = Arc::new(config.clone());
+    sink.with(move |event| {
+        if !needs_schema || schema.is_fresh() {
+            return Either::Left(future::ok(event));
+        }
+        let (config, client, schema) = (Arc::clone(&config), client.clone(), schema.clone());
+        Either::Right(
+            async move {
+                schema.refresh(&client, &config).await;
+                Ok::<_, ()>(event)
+            }
+            .boxed(),
+        )
+    })
+}
+
+/// Events are encoded before they are batched, with the schema of the table shared by all
+/// endpoints.
+pub struct ClickhouseSink {
+    config: ClickhouseConfig,
+    schema: SchemaCache,
+}
+
 #[async_trait::async_trait]
-impl HttpSink for ClickhouseConfig {
+impl HttpSink for ClickhouseSink {
     type Input = BytesMut;
     type Output = BytesMut;
     type Encoder = ClickhouseEventEncoder;
 
     fn build_encoder(&self) -> Self::Encoder {
         ClickhouseEventEncoder {
-            encoding: self.encoding.clone(),
+            encoding: self.config.encoding.clone(),
+            format: self.config.format,
+            coerce_types: self.config.coerce_types,
+            schema: self.schema.clone(),
         }
     }
 
     async fn build_request(&self, events: Self::Output) -> crate::Result<http::Request<Bytes>> {
-        let database = if let Some(database) = &self.database {
-            database.as_str()
-        } else {
-            "default"
-        };
-
+        let config = &self.config;
         let uri = set_uri_query(
-            &self.endpoint.uri,
-            database,
-            &self.table,
-            self.skip_unknown_fields,
+            &config.endpoint.uri,
+            config.database(),
+            &config.table,
+            config.format,
+            config.skip_unknown_fields,
         )
         .expect("Unable to encode uri");
 
-        let mut builder = Request::post(&uri).header("Content-Type", "application/x-ndjson");
+        let mut builder = Request::post(&uri).header("Content-Type", config.format.content_type());
 
-        if let Some(ce) = self.compression.content_encoding() {
+        if let Some(ce) = config.compression.content_encoding() {
             builder = builder.header("Content-Encoding", ce);
         }
 
         let mut request = builder.body(events.freeze()).unwrap();
 
-        if let Some(auth) = &self.auth {
+        if let Some(auth) = &config.auth {
             auth.apply(&mut request);
         }
 
@@ -169,8 +372,11 @@ impl HttpSink for ClickhouseConfig {
     }
 }
 
-async fn healthcheck(client: HttpClient, config: ClickhouseConfig) -> crate::Result<()> {
-    // TODO: check if table exists?
+async fn healthcheck(
+    client: &HttpClient,
+    config: &ClickhouseConfig,
+    schema: &SchemaCache,
+) -> crate::Result<()> {
     let uri = format!("{}/?query=SELECT%201", config.endpoint);
     let mut request = Request::get(uri).body(Body::empty()).unwrap();
 
@@ -181,19 +387,33 @@ async fn healthcheck(client: HttpClient, config: ClickhouseConfig) -> crate::Res
     let response = client.send(request).await?;
 
     match response.status() {
-        StatusCode::OK => Ok(()),
-        status => Err(super::HealthcheckError::UnexpectedStatus { status }.into()),
+        StatusCode::OK => {}
+        status => return Err(super::HealthcheckError::UnexpectedStatus { status }.into()),
     }
+
+    // Fetching the schema here also makes sure that the table exists.
+    if config.needs_schema() {
+        schema.get_or_fetch(client, config).await?;
+    }
+
+    Ok(())
 }
 
-fn set_uri_query(uri: &Uri, database: &str, table: &str, skip_unknown: bool) -> crate::Result<Uri> {
+fn set_uri_query(
+    uri: &Uri,
+    database: &str,
+    table: &str,
+    format: InsertFormat,
+    skip_unknown: bool,
+) -> crate::Result<Uri> {
     let query = url::form_urlencoded::Serializer::new(String::new())
         .append_pair(
             "query",
             format!(
-                "INSERT INTO \"{}\".\"{}\" FORMAT JSONEachRow",
+                "INSERT INTO \"{}\".\"{}\" FORMAT {}",
                 database,
-                table.replace('\"', "\\\"")
+                table.replace('\"', "\\\""),
+                format.name(),
             )
             .as_str(),
         )
@@ -217,6 +437,8 @@ fn set_uri_query(uri: &Uri, database: &str, table: &str, skip_unknown: bool) ->
 #[derive(Debug, Default, Clone)]
 struct ClickhouseRetryLogic {
     inner: HttpRetryLogic,
+    /// Invalidated when an insert is refused for not matching the table.
+    schema: SchemaCache,
 }
 
 impl RetryLogic for ClickhouseRetryLogic {
@@ -241,8 +463,10 @@ impl RetryLogic for ClickhouseRetryLogic {
                 //
                 // Fix already merged: https://github.com/ClickHouse/ClickHouse/pull/6271
                 if body.starts_with(b"Code: 117") {
+                    self.schema.invalidate();
                     RetryAction::DontRetry("incorrect data".into())
                 } else if body.starts_with(b"Code: 53") {
+                    self.schema.invalidate();
                     RetryAction::DontRetry("type mismatch".into())
                 } else {
                     RetryAction::Retry(String::from_utf8_lossy(body).to_string().into())
@@ -262,12 +486,27 @@ mod tests {
         crate::test_util::test_generate_config::<ClickhouseConfig>();
     }
 
+    #[tokio::test]
+    async fn rejects_endpoint_with_endpoints() {
+        let config: ClickhouseConfig = toml::from_str(
+            r#"
+            endpoint = "http://localhost:8123"
+            endpoints = ["http://localhost:8124"]
+            table = "logs"
+        "#,
+        )
+        .unwrap();
+
+        assert!(config.build(SinkContext::new_test()).await.is_err());
+    }
+
     #[test]
     fn encode_valid() {
         let uri = set_uri_query(
             &"http://localhost:80".parse().unwrap(),
             "my_database",
             "my_table",
+            InsertFormat::JsonEachRow,
             false,
         )
         .unwrap();
@@ -277,6 +516,7 @@ mod tests {
             &"http://localhost:80".parse().unwrap(),
             "my_database",
             "my_\"table\"",
+            InsertFormat::JsonEachRow,
             false,
         )
         .unwrap();
@@ -289,6 +529,7 @@ mod tests {
             &"localhost:80".parse().unwrap(),
             "my_database",
             "my_table",
+            InsertFormat::JsonEachRow,
             false,
         )
         .unwrap_err();
@@ -308,9 +549,10 @@ mod integration_tests {
     };
 
     use futures::future;
-    use serde_json::Value;
     use tokio::time::{timeout, Duration};
-    use vector_core::event::{BatchNotifier, BatchStatus, BatchStatusReceiver, Event, LogEvent};
+    use vector_core::event::{
+        BatchNotifier, BatchStatus, BatchStatusReceiver, Event, LogEvent, Value,
+    };
     use warp::Filter;
 
     use super::*;
@@ -539,6 +781,146 @@ timestamp_format = "unix""#,
         assert_eq!(expected, output.data[0]);
     }
 
+    async fn insert_events_with_schema(format: InsertFormat) {
+        trace_init();
+
+        let table = gen_table();
+        let host = clickhouse_address();
+
+        let mut batch = BatchConfig::default();
+        batch.max_events = Some(1);
+
+        let config = ClickhouseConfig {
+            endpoint: host.parse().unwrap(),
+            table: table.clone(),
+            format,
+            coerce_types: true,
+            compression: Compression::None,
+            batch,
+            request: TowerRequestConfig {
+                retry_attempts: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let client = ClickhouseClient::new(host);
+        client
+            .create_table(
+                &table,
+                "host String, timestamp DateTime64(3, 'UTC'), message String, \
+                 level LowCardinality(String), status Nullable(UInt16), \
+                 tags Map(String, String), items Array(Int64)",
+            )
+            .await;
+
+        let (sink, healthcheck) = config.build(SinkContext::new_test()).await.unwrap();
+        healthcheck.await.expect("healthcheck failed");
+
+        let (mut input_event, mut receiver) = make_event();
+        let log = input_event.as_mut_log();
+        log.insert("level", "info");
+        log.insert("status", "200");
+        log.insert("tags.region", "eu");
+        log.insert("items", vec![Value::from(1), Value::from("2")]);
+        log.insert("unknown", "mysteries");
+
+        components::run_sink_event(sink, input_event.clone(), &HTTP_SINK_TAGS).await;
+
+        let output = client.select_all(&table).await;
+        assert_eq!(1, output.rows);
+
+        let timestamp = input_event
+            .as_log()
+            .get(log_schema().timestamp_key())
+            .unwrap()
+            .as_timestamp()
+            .unwrap()
+            .format("%Y-%m-%d %H:%M:%S%.3f")
+            .to_string();
+        assert_eq!(
+            output.data[0],
+            serde_json::json!({
+                "host": "example.com",
+                "timestamp": timestamp,
+                "message": "raw log line",
+                "level": "info",
+                "status": 200,
+                "tags": {"region": "eu"},
+                "items": ["1", "2"],
+            })
+        );
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+    }
+
+    #[tokio::test]
+    async fn insert_events_coerced_json() {
+        insert_events_with_schema(InsertFormat::JsonEachRow).await;
+    }
+
+    #[tokio::test]
+    async fn insert_events_row_binary() {
+        insert_events_with_schema(InsertFormat::RowBinary).await;
+    }
+
+    #[tokio::test]
+    async fn rejects_rows_failing_coercion() {
+        trace_init();
+
+        let table = gen_table();
+        let host = clickhouse_address();
+
+        let mut batch = BatchConfig::default();
+        batch.max_events = Some(2);
+
+        let config = ClickhouseConfig {
+            endpoint: host.parse().unwrap(),
+            table: table.clone(),
+            coerce_types: true,
+            compression: Compression::None,
+            batch,
+            ..Default::default()
+        };
+
+        let client = ClickhouseClient::new(host);
+        client
+            .create_table(&table, "host String, status UInt16")
+            .await;
+
+        let (sink, _hc) = config.build(SinkContext::new_test()).await.unwrap();
+
+        let (mut valid_event, mut valid_receiver) = make_event();
+        valid_event.as_mut_log().insert("status", 200);
+        let (mut invalid_event, mut invalid_receiver) = make_event();
+        invalid_event.as_mut_log().insert("status", "not a number");
+
+        sink.run_events(vec![valid_event, invalid_event])
+            .await
+            .unwrap();
+
+        let output = client.select_all(&table).await;
+        assert_eq!(1, output.rows);
+
+        assert_eq!(valid_receiver.try_recv(), Ok(BatchStatus::Delivered));
+        assert_eq!(invalid_receiver.try_recv(), Ok(BatchStatus::Rejected));
+    }
+
+    #[tokio::test]
+    async fn healthcheck_fails_for_missing_table() {
+        trace_init();
+
+        let config = ClickhouseConfig {
+            endpoint: clickhouse_address().parse().unwrap(),
+            table: gen_table(),
+            format: InsertFormat::RowBinary,
+            ..Default::default()
+        };
+
+        let (_sink, healthcheck) = config.build(SinkContext::new_test()).await.unwrap();
+        assert!(healthcheck.await.is_err());
+    }
+
     #[tokio::test]
     async fn no_retry_on_incorrect_data() {
         trace_init();
@@ -685,8 +1067,8 @@ timestamp_format = "unix""#,
     #[derive(Debug, Deserialize)]
     #[allow(dead_code)] // deserialize all fields
     struct QueryResponse {
-        data: Vec<Value>,
-        meta: Vec<Value>,
+        data: Vec<serde_json::Value>,
+        meta: Vec<serde_json::Value>,
         rows: usize,
         statistics: Stats,
     }
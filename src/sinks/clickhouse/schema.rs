@@ -0,0 +1,486 @@
+//! The columns of the table the sink inserts into, as described by `system.columns`.
+
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use http::{Request, StatusCode, Uri};
+use hyper::Body;
+use serde::Deserialize;
+use snafu::{ResultExt, Snafu};
+use tokio::{sync::Mutex, time::sleep};
+
+use super::{ClickhouseConfig, InsertFormat};
+use crate::{
+    http::{HttpClient, HttpError},
+    internal_events::ClickhouseSchemaFetchError,
+};
+
+#[derive(Debug, Snafu)]
+pub enum SchemaError {
+    #[snafu(display("Failed to fetch the schema of table {:?}: {}", table, source))]
+    Request { table: String, source: HttpError },
+    #[snafu(display("Failed to read the schema of table {:?}: {}", table, source))]
+    Body { table: String, source: hyper::Error },
+    #[snafu(display(
+        "Fetching the schema of table {:?} failed with status {}: {}",
+        table,
+        status,
+        body
+    ))]
+    UnexpectedStatus {
+        table: String,
+        status: StatusCode,
+        body: String,
+    },
+    #[snafu(display("Invalid column description {:?}: {}", line, source))]
+    InvalidColumn {
+        line: String,
+        source: serde_json::Error,
+    },
+    #[snafu(display("Table {:?} was not found in database {:?}.", table, database))]
+    TableNotFound { database: String, table: String },
+    #[snafu(display(
+        "Column {:?} has type {}, which can't be inserted with the `row_binary` format.",
+        column,
+        column_type
+    ))]
+    UnsupportedType { column: String, column_type: String },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnType {
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Int128,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    UInt128,
+    Float32,
+    Float64,
+    Bool,
+    String,
+    FixedString(usize),
+    Uuid,
+    Date,
+    Date32,
+    /// Timestamps are always sent as Unix time, so the timezone of the column doesn't matter.
+    DateTime,
+    /// The number of decimal places of the seconds.
+    DateTime64(u32),
+    Enum8(Vec<(String, i8)>),
+    Enum16(Vec<(String, i16)>),
+    Nullable(Box<ColumnType>),
+    LowCardinality(Box<ColumnType>),
+    Array(Box<ColumnType>),
+    Map(Box<ColumnType>, Box<ColumnType>),
+    /// Any other type. Values of these are sent as they are with `JSONEachRow`, and can't be sent
+    /// with `RowBinary`.
+    Other(String),
+}
+
+impl ColumnType {
+    /// Parses a type the way ClickHouse spells it, like `Map(String, Array(DateTime64(3, 'UTC')))`.
+    pub fn parse(type_name: &str) -> Self {
+        let type_name = type_name.trim();
+        let (name, arguments) = match type_name.find('(') {
+            Some(open) if type_name.ends_with(')') => (
+                &type_name[..open],
+                split_arguments(&type_name[open + 1..type_name.len() - 1]),
+            ),
+            _ => (type_name, Vec::new()),
+        };
+
+        let parsed = match (name, arguments.as_slice()) {
+            ("Int8", []) => Some(Self::Int8),
+            ("Int16", []) => Some(Self::Int16),
+            ("Int32", []) => Some(Self::Int32),
+            ("Int64", []) => Some(Self::Int64),
+            ("Int128", []) => Some(Self::Int128),
+            ("UInt8", []) => Some(Self::UInt8),
+            ("UInt16", []) => Some(Self::UInt16),
+            ("UInt32", []) => Some(Self::UInt32),
+            ("UInt64", []) => Some(Self::UInt64),
+            ("UInt128", []) => Some(Self::UInt128),
+            ("Float32", []) => Some(Self::Float32),
+            ("Float64", []) => Some(Self::Float64),
+            ("Bool", []) => Some(Self::Bool),
+            ("String", []) => Some(Self::String),
+            ("FixedString", [length]) => length.parse().ok().map(Self::FixedString),
+            ("UUID", []) => Some(Self::Uuid),
+            ("Date", []) => Some(Self::Date),
+            ("Date32", []) => Some(Self::Date32),
+            ("DateTime", []) | ("DateTime", [_]) => Some(Self::DateTime),
+            ("DateTime64", [precision]) | ("DateTime64", [precision, _]) => precision
+                .parse()
+                .ok()
+                .filter(|precision| *precision <= 9)
+                .map(Self::DateTime64),
+            ("Enum8", variants) => parse_enum(variants).map(Self::Enum8),
+            ("Enum16", variants) => parse_enum(variants).map(Self::Enum16),
+            ("Nullable", [inner]) => Some(Self::Nullable(Box::new(Self::parse(inner)))),
+            ("LowCardinality", [inner]) => Some(Self::LowCardinality(Box::new(Self::parse(inner)))),
+            ("Array", [inner]) => Some(Self::Array(Box::new(Self::parse(inner)))),
+            ("Map", [key, value]) => Some(Self::Map(
+                Box::new(Self::parse(key)),
+                Box::new(Self::parse(value)),
+            )),
+            _ => None,
+        };
+
+        parsed.unwrap_or_else(|| Self::Other(type_name.to_owned()))
+    }
+
+    /// Finds a type, possibly nested in this one, that can't be sent with `RowBinary`.
+    fn unsupported_by_row_binary(&self) -> Option<&str> {
+        match self {
+            Self::Other(type_name) => Some(type_name),
+            Self::Nullable(inner) | Self::LowCardinality(inner) | Self::Array(inner) => {
+                inner.unsupported_by_row_binary()
+            }
+            Self::Map(key, value) => key
+                .unsupported_by_row_binary()
+                .or_else(|| value.unsupported_by_row_binary()),
+            _ => None,
+        }
+    }
+}
+
+/// Splits the arguments of a type at the commas that aren't nested in other types or quoted.
+fn split_arguments(arguments: &str) -> Vec<&str> {
+    let mut split = Vec::new();
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (index, ch) in arguments.char_indices() {
+        if quoted {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '\'' => quoted = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match ch {
+            '\'' => quoted = true,
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                split.push(arguments[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    let last = arguments[start..].trim();
+    if !last.is_empty() || !split.is_empty() {
+        split.push(last);
+    }
+    split
+}
+
+/// Parses the variants of an enum, like `'debug' = 1`.
+fn parse_enum<T: FromStr>(variants: &[&str]) -> Option<Vec<(String, T)>> {
+    variants
+        .iter()
+        .map(|variant| {
+            let (name, value) = variant.rsplit_once('=')?;
+            let name = name.trim().strip_prefix('\'')?.strip_suffix('\'')?;
+            let name = name.replace("\\'", "'").replace("\\\\", "\\");
+            Some((name, value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub column_type: ColumnType,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TableSchema {
+    /// The columns that can be inserted into, in the order of the table.
+    pub columns: Vec<Column>,
+}
+
+#[derive(Deserialize)]
+struct ColumnDescription {
+    name: String,
+    #[serde(rename = "type")]
+    column_type: String,
+}
+
+impl TableSchema {
+    fn parse(body: &[u8]) -> Result<Self, SchemaError> {
+        let columns = String::from_utf8_lossy(body)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let description = serde_json::from_str::<ColumnDescription>(line)
+                    .context(InvalidColumnSnafu { line })?;
+                Ok(Column {
+                    name: description.name,
+                    column_type: ColumnType::parse(&description.column_type),
+                })
+            })
+            .collect::<Result<_, SchemaError>>()?;
+
+        Ok(Self { columns })
+    }
+
+    fn check_row_binary(&self) -> Result<(), SchemaError> {
+        for column in &self.columns {
+            if let Some(column_type) = column.column_type.unsupported_by_row_binary() {
+                return Err(SchemaError::UnsupportedType {
+                    column: column.name.clone(),
+                    column_type: column_type.to_owned(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How long a fetched schema is used before it's fetched again, so that altered tables are picked
+/// up even if no insert fails because of them.
+const SCHEMA_TTL: Duration = Duration::from_secs(300);
+
+/// How long to wait before fetching the schema again when there is none to encode events with.
+const FETCH_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// The schema of the table, shared by the healthcheck, the encoder and all endpoints. It's fetched
+/// again once it's older than `SCHEMA_TTL` or has been invalidated.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaCache(Arc<SchemaCacheInner>);
+
+#[derive(Debug, Default)]
+struct SchemaCacheInner {
+    /// Held while fetching, so that concurrent callers wait for a single fetch.
+    fetching: Mutex<()>,
+    fetched: RwLock<Option<(Arc<TableSchema>, Instant)>>,
+    stale: AtomicBool,
+}
+
+impl SchemaCache {
+    /// The last schema fetched, even if it's due to be fetched again.
+    pub fn current(&self) -> Option<Arc<TableSchema>> {
+        let fetched = self.0.fetched.read().expect("poisoned lock");
+        fetched.as_ref().map(|(schema, _)| Arc::clone(schema))
+    }
+
+    /// Whether the last schema fetched can still be used as it is.
+    pub fn is_fresh(&self) -> bool {
+        let fetched = self.0.fetched.read().expect("poisoned lock");
+        matches!(
+            fetched.as_ref(),
+            Some((_, fetched_at)) if fetched_at.elapsed() < SCHEMA_TTL
+        ) && !self.0.stale.load(Ordering::Acquire)
+    }
+
+    pub async fn get_or_fetch(
+        &self,
+        client: &HttpClient,
+        config: &ClickhouseConfig,
+    ) -> crate::Result<Arc<TableSchema>> {
+        let _fetching = self.0.fetching.lock().await;
+        let stale = self.0.stale.swap(false, Ordering::AcqRel);
+        if !stale {
+            let fetched = self.0.fetched.read().expect("poisoned lock");
+            if let Some((schema, fetched_at)) = fetched.as_ref() {
+                if fetched_at.elapsed() < SCHEMA_TTL {
+                    return Ok(Arc::clone(schema));
+                }
+            }
+        }
+
+        match fetch_schema(client, config).await {
+            Ok(schema) => {
+                let schema = Arc::new(schema);
+                *self.0.fetched.write().expect("poisoned lock") =
+                    Some((Arc::clone(&schema), Instant::now()));
+                Ok(schema)
+            }
+            Err(error) => {
+                // Don't let a failed fetch hide that the schema is outdated.
+                if stale {
+                    self.0.stale.store(true, Ordering::Release);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Fetches the schema if it's due, so that `current` returns it. A failed fetch keeps the last
+    /// schema in use if there is one, and is otherwise retried until it succeeds, as no event can
+    /// be encoded without it.
+    pub async fn refresh(&self, client: &HttpClient, config: &ClickhouseConfig) {
+        loop {
+            match self.get_or_fetch(client, config).await {
+                Ok(_) => return,
+                Err(error) => {
+                    emit!(ClickhouseSchemaFetchError { error: &error });
+                    if self.current().is_some() {
+                        return;
+                    }
+                    sleep(FETCH_RETRY_DELAY).await;
+                }
+            }
+        }
+    }
+
+    /// Marks the schema as outdated, as the table no longer matched it, so that it's fetched
+    /// again before the next events are encoded.
+    pub fn invalidate(&self) {
+        self.0.stale.store(true, Ordering::Release);
+    }
+}
+
+async fn fetch_schema(
+    client: &HttpClient,
+    config: &ClickhouseConfig,
+) -> crate::Result<TableSchema> {
+    let database = config.database();
+    let table = config.table.as_str();
+    // Materialized, alias and ephemeral columns can't be inserted into.
+    let query = format!(
+        "SELECT name, type FROM system.columns \
+         WHERE database = {} AND table = {} \
+         AND default_kind NOT IN ('MATERIALIZED', 'ALIAS', 'EPHEMERAL') \
+         ORDER BY position FORMAT JSONEachRow",
+        quote_string(database),
+        quote_string(table),
+    );
+    let uri = query_uri(&config.endpoint.uri, &query)?;
+
+    let mut request = Request::get(uri).body(Body::empty()).unwrap();
+    if let Some(auth) = &config.auth {
+        auth.apply(&mut request);
+    }
+
+    let response = client.send(request).await.context(RequestSnafu { table })?;
+    let status = response.status();
+    let body = hyper::body::to_bytes(response.into_body())
+        .await
+        .context(BodySnafu { table })?;
+    if status != StatusCode::OK {
+        return Err(SchemaError::UnexpectedStatus {
+            table: table.to_owned(),
+            status,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        }
+        .into());
+    }
+
+    let schema = TableSchema::parse(&body)?;
+    if schema.columns.is_empty() {
+        return Err(SchemaError::TableNotFound {
+            database: database.to_owned(),
+            table: table.to_owned(),
+        }
+        .into());
+    }
+    if config.format == InsertFormat::RowBinary {
+        schema.check_row_binary()?;
+    }
+
+    Ok(schema)
+}
+
+/// Quotes a string literal for a ClickHouse query.
+fn quote_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn query_uri(uri: &Uri, query: &str) -> crate::Result<Uri> {
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("query", query)
+        .finish();
+
+    let mut uri = uri.to_string();
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+    uri.push('?');
+    uri.push_str(&query);
+
+    uri.parse::<Uri>()
+        .context(crate::sinks::UriParseSnafu)
+        .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_column_types() {
+        use ColumnType::*;
+
+        assert_eq!(ColumnType::parse("UInt64"), UInt64);
+        assert_eq!(ColumnType::parse("DateTime('Europe/Paris')"), DateTime);
+        assert_eq!(ColumnType::parse("DateTime64(6)"), DateTime64(6));
+        assert_eq!(ColumnType::parse("FixedString(16)"), FixedString(16));
+        assert_eq!(
+            ColumnType::parse("LowCardinality(Nullable(String))"),
+            LowCardinality(Box::new(Nullable(Box::new(String))))
+        );
+        assert_eq!(
+            ColumnType::parse("Map(String, Array(DateTime64(3, 'UTC')))"),
+            Map(Box::new(String), Box::new(Array(Box::new(DateTime64(3)))))
+        );
+        assert_eq!(
+            ColumnType::parse("Enum8('a, b' = 1, 'it\\'s' = -2)"),
+            Enum8(vec![("a, b".to_owned(), 1), ("it's".to_owned(), -2)])
+        );
+        assert_eq!(
+            ColumnType::parse("Decimal(18, 4)"),
+            Other("Decimal(18, 4)".to_owned())
+        );
+        assert_eq!(
+            ColumnType::parse("DateTime64(12)"),
+            Other("DateTime64(12)".to_owned())
+        );
+    }
+
+    #[test]
+    fn parses_system_columns() {
+        let schema = TableSchema::parse(
+            br#"{"name":"timestamp","type":"DateTime64(3)"}
+{"name":"tags","type":"Map(String, String)"}
+{"name":"amount","type":"Decimal(9, 2)"}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(schema.columns.len(), 3);
+        assert_eq!(schema.columns[0].name, "timestamp");
+        assert_eq!(
+            schema.columns[1].column_type,
+            ColumnType::Map(Box::new(ColumnType::String), Box::new(ColumnType::String))
+        );
+        assert!(matches!(
+            schema.check_row_binary(),
+            Err(SchemaError::UnsupportedType { column, .. }) if column == "amount"
+        ));
+    }
+
+    #[test]
+    fn quotes_strings() {
+        assert_eq!(quote_string(r"it's a \ test"), r"'it\'s a \\ test'");
+    }
+}
@@ -166,6 +166,9 @@ impl DatadogLogsConfig {
 #[typetag::serde(name = "datadog_logs")]
 impl SinkConfig for DatadogLogsConfig {
     async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        if let Some(compression) = self.compression {
+            compression.ensure_gzip_or_none()?;
+        }
         let client = self.create_client(&cx.proxy)?;
         let healthcheck = self.build_healthcheck(client.clone())?;
         let sink = self.build_processor(client, cx)?;
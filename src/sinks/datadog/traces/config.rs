@@ -178,6 +178,9 @@ impl DatadogTracesConfig {
 #[typetag::serde(name = "datadog_traces")]
 impl SinkConfig for DatadogTracesConfig {
     async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        if let Some(compression) = self.compression {
+            compression.ensure_gzip_or_none()?;
+        }
         let client = self.build_client(&cx.proxy)?;
         let healthcheck = self.build_healthcheck(client.clone())?;
         let sink = self.build_sink(client, cx)?;
@@ -244,7 +244,7 @@ impl DatadogArchivesSinkConfig {
         // thus no need of the higher number of outcoming requests
         let request_limits = self.request.unwrap_with(&Default::default());
         let service = ServiceBuilder::new()
-            .settings(request_limits, S3RetryLogic)
+            .settings(request_limits, S3RetryLogic::new())
             .service(service);
 
         match s3_options.storage_class {
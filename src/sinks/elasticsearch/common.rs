@@ -18,8 +18,8 @@ use crate::{
             ElasticsearchConfig, ParseError,
         },
         util::{
-            encoding::EncodingConfigFixed, http::RequestConfig, Compression, TowerRequestConfig,
-            UriSerde,
+            encoding::EncodingConfigFixed, http::RequestConfig, service::resolve_endpoints,
+            Compression, TowerRequestConfig, UriSerde,
         },
         HealthcheckError,
     },
@@ -46,15 +46,31 @@ pub struct ElasticsearchCommon {
 }
 
 impl ElasticsearchCommon {
+    /// Parses the config for its first endpoint.
     pub async fn parse_config(config: &ElasticsearchConfig) -> crate::Result<Self> {
+        let mut commons = Self::parse_many(config).await?;
+        Ok(commons.swap_remove(0))
+    }
+
+    /// Parses the config for each of its endpoints, in order.
+    pub async fn parse_many(config: &ElasticsearchConfig) -> crate::Result<Vec<Self>> {
+        let endpoint = Some(&config.endpoint).filter(|endpoint| !endpoint.is_empty());
+        let mut commons = Vec::new();
+        for endpoint in resolve_endpoints("endpoint", endpoint, &config.endpoints)? {
+            commons.push(Self::parse_endpoint(config, &endpoint).await?);
+        }
+        Ok(commons)
+    }
+
+    async fn parse_endpoint(config: &ElasticsearchConfig, endpoint: &str) -> crate::Result<Self> {
         // Test the configured host, but ignore the result
-        let uri = format!("{}/_test", &config.endpoint);
-        let uri = uri.parse::<Uri>().with_context(|_| InvalidHostSnafu {
-            host: &config.endpoint,
-        })?;
+        let uri = format!("{}/_test", endpoint);
+        let uri = uri
+            .parse::<Uri>()
+            .with_context(|_| InvalidHostSnafu { host: endpoint })?;
         if uri.host().is_none() {
             return Err(ParseError::HostMustIncludeHostname {
-                host: config.endpoint.clone(),
+                host: endpoint.to_owned(),
             }
             .into());
         }
@@ -66,7 +82,7 @@ impl ElasticsearchCommon {
             }),
             _ => None,
         };
-        let uri = config.endpoint.parse::<UriSerde>()?;
+        let uri = endpoint.parse::<UriSerde>()?;
         let http_auth = authorization.choose_one(&uri.auth)?;
         let base_url = uri.uri.to_string().trim_end_matches('/').to_owned();
 
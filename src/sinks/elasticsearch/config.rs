@@ -3,10 +3,11 @@ use std::{
     convert::TryFrom,
 };
 
-use futures::FutureExt;
+use futures::{future, FutureExt};
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 use tower::ServiceBuilder;
+use vector_core::{buffers::Acker, stream::BatcherSettings};
 
 use crate::aws::RegionOrEndpoint;
 use crate::{
@@ -25,8 +26,9 @@ use crate::{
             ElasticsearchCommonMode, ElasticsearchMode, IndexTemplateSnafu,
         },
         util::{
-            encoding::EncodingConfigFixed, http::RequestConfig, BatchConfig, Compression,
-            RealtimeSizeBasedDefaultBatchSettings, ServiceBuilderExt, TowerRequestConfig,
+            encoding::EncodingConfigFixed, http::RequestConfig, service::DistributionConfig,
+            BatchConfig, Compression, RealtimeSizeBasedDefaultBatchSettings, ServiceBuilderExt,
+            TowerRequestConfig,
         },
         Healthcheck, VectorSink,
     },
@@ -41,7 +43,15 @@ pub const DATA_STREAM_TIMESTAMP_KEY: &str = "@timestamp";
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields)]
 pub struct ElasticsearchConfig {
+    #[serde(default)]
     pub endpoint: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub distribution: DistributionConfig,
 
     pub doc_type: Option<String>,
     #[serde(default)]
@@ -90,6 +100,35 @@ pub enum Encoding {
 }
 
 impl ElasticsearchConfig {
+    fn build_sink<S>(
+        &self,
+        common: ElasticsearchCommon,
+        batch_settings: BatcherSettings,
+        acker: Acker,
+        service: S,
+    ) -> ElasticsearchSink<S> {
+        // This is a bit ugly, but removes a String allocation on every event
+        let mut encoding = self.encoding.clone();
+        encoding.codec.doc_type = common.doc_type;
+        encoding.codec.suppress_type_name = common.suppress_type_name;
+
+        let request_builder = ElasticsearchRequestBuilder {
+            compression: self.compression,
+            encoder: encoding,
+        };
+
+        ElasticsearchSink {
+            batch_settings,
+            request_builder,
+            compression: self.compression,
+            service,
+            acker,
+            metric_to_log: common.metric_to_log,
+            mode: common.mode,
+            id_key_field: self.id_key.clone(),
+        }
+    }
+
     pub fn bulk_action(&self) -> crate::Result<Option<Template>> {
         Ok(self
             .bulk
@@ -296,55 +335,68 @@ impl DataStreamConfig {
 #[typetag::serde(name = "elasticsearch")]
 impl SinkConfig for ElasticsearchConfig {
     async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
-        let common = ElasticsearchCommon::parse_config(self).await?;
+        self.compression.ensure_gzip_or_none()?;
 
-        let http_client = HttpClient::new(common.tls_settings.clone(), cx.proxy())?;
-        let batch_settings = self.batch.into_batcher_settings()?;
+        let mut commons = ElasticsearchCommon::parse_many(self).await?;
 
-        // This is a bit ugly, but removes a String allocation on every event
-        let mut encoding = self.encoding.clone();
-        encoding.codec.doc_type = common.doc_type;
-        encoding.codec.suppress_type_name = common.suppress_type_name;
-
-        let request_builder = ElasticsearchRequestBuilder {
-            compression: self.compression,
-            encoder: encoding,
-        };
+        let batch_settings = self.batch.into_batcher_settings()?;
 
         let request_limits = self
             .request
             .tower
             .unwrap_with(&TowerRequestConfig::default());
 
-        let http_request_builder = HttpRequestBuilder {
-            bulk_uri: common.bulk_uri,
-            http_request_config: self.request.clone(),
-            http_auth: common.http_auth,
-            query_params: common.query_params,
-            region: common.region,
-            compression: self.compression,
-            credentials_provider: common.aws_auth,
-        };
-
-        let service = ServiceBuilder::new()
-            .settings(request_limits, ElasticsearchRetryLogic)
-            .service(ElasticsearchService::new(http_client, http_request_builder));
-
-        let sink = ElasticsearchSink {
-            batch_settings,
-            request_builder,
-            compression: self.compression,
-            service,
-            acker: cx.acker(),
-            metric_to_log: common.metric_to_log,
-            mode: common.mode,
-            id_key_field: self.id_key.clone(),
+        let mut services = commons
+            .iter()
+            .map(|common| {
+                let http_client = HttpClient::new(common.tls_settings.clone(), cx.proxy())?;
+                let http_request_builder = HttpRequestBuilder {
+                    bulk_uri: common.bulk_uri.clone(),
+                    http_request_config: self.request.clone(),
+                    http_auth: common.http_auth.clone(),
+                    query_params: common.query_params.clone(),
+                    region: common.region.clone(),
+                    compression: self.compression,
+                    credentials_provider: common.aws_auth.clone(),
+                };
+                let service = ElasticsearchService::new(http_client, http_request_builder);
+                Ok((common.base_url.clone(), service))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        // Everything but the endpoint is the same for all of them.
+        let common = commons.swap_remove(0);
+        let acker = cx.acker();
+
+        let stream = if services.len() == 1 {
+            let (_, service) = services.swap_remove(0);
+            let service = ServiceBuilder::new()
+                .settings(request_limits, ElasticsearchRetryLogic)
+                .service(service);
+            let sink = self.build_sink(common, batch_settings, acker, service);
+            VectorSink::from_event_streamsink(sink)
+        } else {
+            let service = request_limits.distributed_service(
+                ElasticsearchRetryLogic,
+                services,
+                self.distribution,
+            );
+            let sink = self.build_sink(common, batch_settings, acker, service);
+            VectorSink::from_event_streamsink(sink)
         };
 
-        let common = ElasticsearchCommon::parse_config(self).await?;
-        let client = HttpClient::new(common.tls_settings.clone(), cx.proxy())?;
-        let healthcheck = common.healthcheck(client).boxed();
-        let stream = VectorSink::from_event_streamsink(sink);
+        let commons = ElasticsearchCommon::parse_many(self).await?;
+        let healthchecks = commons
+            .into_iter()
+            .map(|common| {
+                let client = HttpClient::new(common.tls_settings.clone(), cx.proxy())?;
+                Ok(common.healthcheck(client).boxed())
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        // The sink is healthy as long as any of its endpoints is.
+        let healthcheck = future::select_ok(healthchecks)
+            .map(|result| result.map(|_| ()))
+            .boxed();
         Ok((stream, healthcheck))
     }
 
@@ -1,3 +1,5 @@
+use std::sync::{Arc, Mutex};
+
 use bytes::Bytes;
 use vector_core::ByteSizeOf;
 
@@ -6,7 +8,7 @@ use crate::{
     sinks::{
         elasticsearch::{
             encoder::{ElasticsearchEncoder, ProcessedEvent},
-            service::ElasticsearchRequest,
+            service::{BulkDocument, BulkDocuments, ElasticsearchRequest},
         },
         util::{encoding::EncodingConfigFixed, Compression, RequestBuilder},
     },
@@ -21,6 +23,7 @@ pub struct Metadata {
     finalizers: EventFinalizers,
     batch_size: usize,
     events_byte_size: usize,
+    documents: Vec<BulkDocument>,
 }
 
 impl RequestBuilder<Vec<ProcessedEvent>> for ElasticsearchRequestBuilder {
@@ -40,16 +43,26 @@ impl RequestBuilder<Vec<ProcessedEvent>> for ElasticsearchRequestBuilder {
     }
 
     fn split_input(&self, mut events: Vec<ProcessedEvent>) -> (Self::Metadata, Self::Events) {
-        let events_byte_size = events
+        let documents = events
+            .iter_mut()
+            .map(|event| BulkDocument {
+                byte_size: event.log.size_of(),
+                finalizers: event.take_finalizers(),
+            })
+            .collect::<Vec<_>>();
+        let events_byte_size = documents.iter().map(|document| document.byte_size).sum();
+        let finalizers = documents
             .iter()
-            .map(|x| x.log.size_of())
-            .reduce(|a, b| a + b)
-            .unwrap_or(0);
+            .fold(EventFinalizers::default(), |mut acc, document| {
+                acc.merge(document.finalizers.clone());
+                acc
+            });
 
         let metadata = Metadata {
-            finalizers: events.take_finalizers(),
+            finalizers,
             batch_size: events.len(),
             events_byte_size,
+            documents,
         };
         (metadata, events)
     }
@@ -60,6 +73,7 @@ impl RequestBuilder<Vec<ProcessedEvent>> for ElasticsearchRequestBuilder {
             finalizers: metadata.finalizers,
             batch_size: metadata.batch_size,
             events_byte_size: metadata.events_byte_size,
+            documents: Arc::new(Mutex::new(BulkDocuments::new(metadata.documents))),
         }
     }
 }
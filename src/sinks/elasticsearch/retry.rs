@@ -2,6 +2,7 @@ use http::StatusCode;
 use serde::Deserialize;
 
 use crate::{
+    event::EventStatus,
     http::HttpError,
     sinks::{
         elasticsearch::service::ElasticsearchResponse,
@@ -10,12 +11,12 @@ use crate::{
 };
 
 #[derive(Deserialize, Debug)]
-struct EsResultResponse {
-    items: Vec<EsResultItem>,
+pub(super) struct EsResultResponse {
+    pub(super) items: Vec<EsResultItem>,
 }
 
 #[derive(Deserialize, Debug)]
-enum EsResultItem {
+pub(super) enum EsResultItem {
     #[serde(rename = "index")]
     Index(EsIndexResult),
     #[serde(rename = "create")]
@@ -24,7 +25,7 @@ enum EsResultItem {
 
 impl EsResultItem {
     #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
-    fn result(self) -> EsIndexResult {
+    pub(super) fn result(self) -> EsIndexResult {
         match self {
             EsResultItem::Index(r) => r,
             EsResultItem::Create(r) => r,
@@ -33,10 +34,24 @@ impl EsResultItem {
 }
 
 #[derive(Deserialize, Debug)]
-struct EsIndexResult {
+pub(super) struct EsIndexResult {
+    status: u16,
     error: Option<EsErrorDetails>,
 }
 
+impl EsIndexResult {
+    /// The status of the document this item reports on. Documents that were throttled or hit a
+    /// server side error may be indexed by sending them again, any other failure is permanent.
+    pub(super) fn event_status(&self) -> EventStatus {
+        match StatusCode::from_u16(self.status) {
+            Ok(status) if status.is_success() => EventStatus::Delivered,
+            Ok(StatusCode::TOO_MANY_REQUESTS) => EventStatus::Errored,
+            Ok(status) if status.is_server_error() => EventStatus::Errored,
+            _ => EventStatus::Rejected,
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 struct EsErrorDetails {
     reason: String,
@@ -78,10 +93,18 @@ impl RetryLogic for ElasticsearchRetryLogic {
             _ if status.is_success() => {
                 let body = String::from_utf8_lossy(response.http_response.body());
 
-                if body.contains("\"errors\":true") {
-                    RetryAction::DontRetry(get_error_reason(&body).into())
-                } else {
-                    RetryAction::Successful
+                if !body.contains("\"errors\":true") {
+                    return RetryAction::Successful;
+                }
+
+                // The service has already settled the documents that were indexed or failed
+                // permanently, any that remain errored are sent again on their own.
+                match response.event_status {
+                    EventStatus::Delivered => RetryAction::Successful,
+                    EventStatus::Errored => RetryAction::Retry(
+                        format!("some documents failed, {}", get_error_reason(&body)).into(),
+                    ),
+                    _ => RetryAction::DontRetry(get_error_reason(&body).into()),
                 }
             }
             _ => RetryAction::DontRetry(format!("response status: {}", status).into()),
@@ -109,7 +132,6 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
-    use crate::event::EventStatus;
 
     #[test]
     fn handles_error_response() {
@@ -130,6 +152,30 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn retries_partially_failed_response() {
+        let json = "{\"took\":34,\"errors\":true,\"items\":[{\"index\":{\"_index\":\"test-hgw28jv10u\",\"_id\":\"1\",\"status\":201}},{\"index\":{\"_index\":\"test-hgw28jv10u\",\"_id\":\"2\",\"status\":429,\"error\":{\"type\":\"es_rejected_execution_exception\",\"reason\":\"rejected execution of coordinating operation\"}}}]}";
+        let logic = ElasticsearchRetryLogic;
+        let response = |event_status| ElasticsearchResponse {
+            http_response: Response::builder()
+                .status(StatusCode::OK)
+                .body(Bytes::from(json))
+                .unwrap(),
+            event_status,
+            batch_size: 1,
+            events_byte_size: 1,
+        };
+
+        assert!(matches!(
+            logic.should_retry_response(&response(EventStatus::Errored)),
+            RetryAction::Retry(_)
+        ));
+        assert!(matches!(
+            logic.should_retry_response(&response(EventStatus::Delivered)),
+            RetryAction::Successful
+        ));
+    }
+
     #[test]
     fn get_index_error_reason() {
         let json = "{\"took\":185,\"errors\":true,\"items\":[{\"index\":{\"_index\":\"test-hgw28jv10u\",\"_type\":\"log_lines\",\"_id\":\"3GhQLXEBE62DvOOUKdFH\",\"status\":400,\"error\":{\"type\":\"illegal_argument_exception\",\"reason\":\"mapper [message] of different type, current_type [long], merged_type [text]\"}}}]}";
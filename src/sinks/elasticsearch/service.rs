@@ -1,12 +1,15 @@
 use std::{
     collections::HashMap,
-    sync::Arc,
+    io::{self, Read, Write},
+    mem,
+    sync::{Arc, Mutex},
     task::{Context, Poll},
 };
 
 use aws_types::credentials::SharedCredentialsProvider;
 use aws_types::region::Region;
 use bytes::Bytes;
+use flate2::read::MultiGzDecoder;
 use futures::future::BoxFuture;
 use http::{Response, Uri};
 use hyper::{service::Service, Body, Request};
@@ -15,14 +18,14 @@ use vector_core::{
     buffers::Ackable, internal_event::EventsSent, stream::DriverResponse, ByteSizeOf,
 };
 
-use crate::sinks::elasticsearch::sign_request;
+use crate::sinks::elasticsearch::{retry::EsResultResponse, sign_request};
 use crate::{
     event::{EventFinalizers, EventStatus, Finalizable},
     http::{Auth, HttpClient},
     internal_events::ElasticsearchResponseError,
     sinks::util::{
         http::{HttpBatchService, RequestConfig},
-        Compression, ElementCount,
+        Compression, Compressor, ElementCount,
     },
 };
 
@@ -32,6 +35,130 @@ pub struct ElasticsearchRequest {
     pub finalizers: EventFinalizers,
     pub batch_size: usize,
     pub events_byte_size: usize,
+    /// Shared by every attempt at sending the request, see `BulkDocuments`.
+    pub documents: Arc<Mutex<BulkDocuments>>,
+}
+
+#[derive(Debug)]
+pub struct BulkDocument {
+    pub finalizers: EventFinalizers,
+    pub byte_size: usize,
+}
+
+/// Tracks the documents of a bulk request across attempts.
+///
+/// The bulk API reports a status for every document, so when some of them fail, the ones that
+/// were indexed or failed permanently are finalized right away, and only those that failed with
+/// a retriable error are sent again.
+#[derive(Debug, Default)]
+pub struct BulkDocuments {
+    documents: Vec<BulkDocument>,
+    /// The indices of the documents left to send, or `None` while that is all of them.
+    pending: Option<Vec<usize>>,
+    delivered_count: usize,
+    delivered_byte_size: usize,
+}
+
+impl BulkDocuments {
+    pub fn new(documents: Vec<BulkDocument>) -> Self {
+        Self {
+            documents,
+            ..Default::default()
+        }
+    }
+
+    /// Narrows the request down to the documents left to send, returning their indices along
+    /// with it.
+    fn prepare(
+        &self,
+        mut request: ElasticsearchRequest,
+        compression: Compression,
+    ) -> io::Result<(ElasticsearchRequest, Vec<usize>)> {
+        let pending = match &self.pending {
+            Some(pending) => pending,
+            None => return Ok((request, (0..self.documents.len()).collect())),
+        };
+
+        let payload = decompress(&request.payload, compression)?;
+        let lines = payload.split_inclusive(|b| *b == b'\n').collect::<Vec<_>>();
+        let mut compressor = Compressor::from(compression);
+        for &index in pending {
+            // Every document takes up two lines, the action and the source.
+            let document = lines.get(index * 2..index * 2 + 2).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "bulk payload is missing documents",
+                )
+            })?;
+            for line in document {
+                compressor.write_all(line)?;
+            }
+        }
+
+        request.payload = compressor.into_inner().freeze();
+        request.batch_size = pending.len();
+        request.events_byte_size = pending
+            .iter()
+            .map(|&index| self.documents[index].byte_size)
+            .sum();
+        Ok((request, pending.clone()))
+    }
+
+    fn deliver(&mut self, sent: &[usize]) {
+        self.delivered_count += sent.len();
+        self.delivered_byte_size += sent
+            .iter()
+            .map(|&index| self.documents[index].byte_size)
+            .sum::<usize>();
+    }
+
+    /// Settles the documents sent in an attempt from the items of the bulk response, returning
+    /// the status of the documents left to send, or `None` if the items can't be matched with
+    /// the documents.
+    fn settle(&mut self, sent: Vec<usize>, body: &[u8]) -> Option<EventStatus> {
+        let items = serde_json::from_slice::<EsResultResponse>(body).ok()?.items;
+        if items.len() != sent.len() {
+            return None;
+        }
+
+        let mut pending = Vec::new();
+        for (item, index) in items.into_iter().zip(sent) {
+            let status = item.result().event_status();
+            if status == EventStatus::Errored {
+                pending.push(index);
+                continue;
+            }
+
+            let document = &mut self.documents[index];
+            if status == EventStatus::Delivered {
+                self.delivered_count += 1;
+                self.delivered_byte_size += document.byte_size;
+            }
+            // Recording the status keeps it from being overridden by the status of the request
+            // as a whole, which then only applies to the documents left to send.
+            let mut finalizers = mem::take(&mut document.finalizers);
+            finalizers.update_status(status);
+            finalizers.update_sources();
+        }
+
+        let status = if pending.is_empty() {
+            EventStatus::Delivered
+        } else {
+            EventStatus::Errored
+        };
+        self.pending = Some(pending);
+        Some(status)
+    }
+}
+
+/// Only gzip is accepted by `ElasticsearchConfig`, so that is the only compression to undo.
+fn decompress(payload: &Bytes, compression: Compression) -> io::Result<Bytes> {
+    if !compression.is_compressed() {
+        return Ok(payload.clone());
+    }
+    let mut decompressed = Vec::new();
+    MultiGzDecoder::new(payload.as_ref()).read_to_end(&mut decompressed)?;
+    Ok(decompressed.into())
 }
 
 impl ByteSizeOf for ElasticsearchRequest {
@@ -64,6 +191,7 @@ pub struct ElasticsearchService {
         BoxFuture<'static, Result<http::Request<Bytes>, crate::Error>>,
         ElasticsearchRequest,
     >,
+    compression: Compression,
 }
 
 impl ElasticsearchService {
@@ -71,6 +199,7 @@ impl ElasticsearchService {
         http_client: HttpClient<Body>,
         http_request_builder: HttpRequestBuilder,
     ) -> ElasticsearchService {
+        let compression = http_request_builder.compression;
         let http_request_builder = Arc::new(http_request_builder);
         let batch_service = HttpBatchService::new(http_client, move |req| {
             let request_builder = Arc::clone(&http_request_builder);
@@ -78,7 +207,10 @@ impl ElasticsearchService {
                 Box::pin(async move { request_builder.build_request(req).await });
             future
         });
-        ElasticsearchService { batch_service }
+        ElasticsearchService {
+            batch_service,
+            compression,
+        }
     }
 }
 
@@ -157,33 +289,46 @@ impl Service<ElasticsearchRequest> for ElasticsearchService {
 
     fn call(&mut self, req: ElasticsearchRequest) -> Self::Future {
         let mut http_service = self.batch_service.clone();
+        let compression = self.compression;
         Box::pin(async move {
             http_service.ready().await?;
-            let batch_size = req.batch_size;
-            let events_byte_size = req.events_byte_size;
+            let documents = Arc::clone(&req.documents);
+            let (req, sent) = documents
+                .lock()
+                .expect("bulk documents lock poisoned")
+                .prepare(req, compression)?;
             let http_response = http_service.call(req).await?;
-            let event_status = get_event_status(&http_response);
+
+            let mut documents = documents.lock().expect("bulk documents lock poisoned");
+            let event_status = get_event_status(&http_response, &mut documents, sent);
             Ok(ElasticsearchResponse {
                 event_status,
                 http_response,
-                batch_size,
-                events_byte_size,
+                batch_size: documents.delivered_count,
+                events_byte_size: documents.delivered_byte_size,
             })
         })
     }
 }
 
-fn get_event_status(response: &Response<Bytes>) -> EventStatus {
+fn get_event_status(
+    response: &Response<Bytes>,
+    documents: &mut BulkDocuments,
+    sent: Vec<usize>,
+) -> EventStatus {
     let status = response.status();
     if status.is_success() {
         let body = String::from_utf8_lossy(response.body());
         if body.contains("\"errors\":true") {
             emit!(ElasticsearchResponseError::new(
-                "Response containerd errors.",
+                "Response contained errors.",
                 response
             ));
-            EventStatus::Rejected
+            documents
+                .settle(sent, response.body())
+                .unwrap_or(EventStatus::Rejected)
         } else {
+            documents.deliver(&sent);
             EventStatus::Delivered
         }
     } else if status.is_server_error() {
@@ -200,3 +345,136 @@ fn get_event_status(response: &Response<Bytes>) -> EventStatus {
         EventStatus::Rejected
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use flate2::write::GzEncoder;
+    use vector_core::event::{BatchNotifier, BatchStatus, BatchStatusReceiver, EventFinalizer};
+
+    use super::*;
+
+    const PAYLOAD: &str = concat!(
+        "{\"index\":{\"_index\":\"vector\"}}\n{\"message\":\"first\"}\n",
+        "{\"index\":{\"_index\":\"vector\"}}\n{\"message\":\"second\"}\n",
+        "{\"index\":{\"_index\":\"vector\"}}\n{\"message\":\"third\"}\n",
+    );
+
+    fn bulk_documents(count: usize) -> (BulkDocuments, Vec<BatchStatusReceiver>) {
+        let (documents, receivers) = (0..count)
+            .map(|_| {
+                let (batch, receiver) = BatchNotifier::new_with_receiver();
+                let document = BulkDocument {
+                    finalizers: EventFinalizers::new(EventFinalizer::new(batch)),
+                    byte_size: 10,
+                };
+                (document, receiver)
+            })
+            .unzip();
+        (BulkDocuments::new(documents), receivers)
+    }
+
+    fn request(payload: Bytes, documents: BulkDocuments) -> ElasticsearchRequest {
+        ElasticsearchRequest {
+            payload,
+            finalizers: EventFinalizers::default(),
+            batch_size: 3,
+            events_byte_size: 30,
+            documents: Arc::new(Mutex::new(documents)),
+        }
+    }
+
+    fn item(status: u16) -> String {
+        if status < 300 {
+            format!(r#"{{"index":{{"_index":"vector","status":{}}}}}"#, status)
+        } else {
+            format!(
+                r#"{{"index":{{"_index":"vector","status":{},"error":{{"type":"error","reason":"failed"}}}}}}"#,
+                status
+            )
+        }
+    }
+
+    fn body(statuses: &[u16]) -> String {
+        let items = statuses
+            .iter()
+            .map(|&status| item(status))
+            .collect::<Vec<_>>();
+        format!(
+            r#"{{"took":1,"errors":true,"items":[{}]}}"#,
+            items.join(",")
+        )
+    }
+
+    #[test]
+    fn settles_documents_individually() {
+        let (mut documents, mut receivers) = bulk_documents(3);
+
+        let status = documents.settle(vec![0, 1, 2], body(&[201, 400, 429]).as_bytes());
+
+        assert_eq!(status, Some(EventStatus::Errored));
+        assert_eq!(documents.pending, Some(vec![2]));
+        assert_eq!(documents.delivered_count, 1);
+        assert_eq!(documents.delivered_byte_size, 10);
+        assert_eq!(receivers[0].try_recv(), Ok(BatchStatus::Delivered));
+        assert_eq!(receivers[1].try_recv(), Ok(BatchStatus::Rejected));
+        assert!(receivers[2].try_recv().is_err());
+
+        let status = documents.settle(vec![2], body(&[201]).as_bytes());
+
+        assert_eq!(status, Some(EventStatus::Delivered));
+        assert_eq!(documents.pending, Some(vec![]));
+        assert_eq!(documents.delivered_count, 2);
+        assert_eq!(receivers[2].try_recv(), Ok(BatchStatus::Delivered));
+    }
+
+    #[test]
+    fn does_not_settle_unmatched_items() {
+        let (mut documents, _receivers) = bulk_documents(3);
+
+        let status = documents.settle(vec![0, 1, 2], body(&[201, 429]).as_bytes());
+
+        assert_eq!(status, None);
+        assert_eq!(documents.pending, None);
+    }
+
+    #[test]
+    fn prepares_all_documents_on_first_attempt() {
+        let (documents, _receivers) = bulk_documents(3);
+        let payload = Bytes::from(PAYLOAD);
+
+        let (request, sent) = documents
+            .prepare(
+                request(payload.clone(), BulkDocuments::default()),
+                Compression::None,
+            )
+            .unwrap();
+
+        assert_eq!(sent, vec![0, 1, 2]);
+        assert_eq!(request.payload, payload);
+    }
+
+    #[test]
+    fn prepares_pending_documents() {
+        let (mut documents, _receivers) = bulk_documents(3);
+        documents.pending = Some(vec![0, 2]);
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(PAYLOAD.as_bytes()).unwrap();
+        let payload = Bytes::from(encoder.finish().unwrap());
+        let compression = Compression::gzip_default();
+
+        let (request, sent) = documents
+            .prepare(request(payload, BulkDocuments::default()), compression)
+            .unwrap();
+
+        assert_eq!(sent, vec![0, 2]);
+        assert_eq!(request.batch_size, 2);
+        assert_eq!(request.events_byte_size, 20);
+        assert_eq!(
+            decompress(&request.payload, compression).unwrap(),
+            Bytes::from(concat!(
+                "{\"index\":{\"_index\":\"vector\"}}\n{\"message\":\"first\"}\n",
+                "{\"index\":{\"_index\":\"vector\"}}\n{\"message\":\"third\"}\n",
+            ))
+        );
+    }
+}
@@ -1,4 +1,8 @@
-use std::time::{Duration, Instant};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use async_compression::tokio::write::GzipEncoder;
 use async_trait::async_trait;
@@ -14,6 +18,7 @@ use futures::{
     FutureExt,
 };
 use serde::{Deserialize, Serialize};
+use snafu::Snafu;
 use tokio::{
     fs::{self, File},
     io::AsyncWriteExt,
@@ -28,7 +33,10 @@ use crate::{
     },
     event::{Event, EventStatus, Finalizable},
     expiring_hash_map::ExpiringHashMap,
-    internal_events::{FileBytesSent, FileIoError, FileOpen, TemplateRenderingError},
+    internal_events::{
+        FileBytesSent, FileIoError, FileOpen, FileRetentionDeleted, FileRotated,
+        TemplateRenderingError,
+    },
     sinks::util::{
         encoding::{
             EncodingConfig, EncodingConfigWithFramingAdapter, EncodingConfigWithFramingMigrator,
@@ -39,9 +47,18 @@ use crate::{
     template::Template,
 };
 mod bytes_path;
+mod rotation;
 use std::convert::TryFrom;
 
 use bytes_path::BytesPath;
+use rotation::RotatedPaths;
+pub use rotation::{RetentionConfig, RotationConfig, RotationNaming};
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("`retention` requires `rotation` or `atomic_finalize` to be set"))]
+    RetentionWithoutRotation,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncodingMigrator;
@@ -71,6 +88,22 @@ pub struct FileSinkConfig {
         skip_serializing_if = "crate::serde::skip_serializing_if_default"
     )]
     pub compression: Compression,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub rotation: RotationConfig,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub retention: RetentionConfig,
+    /// Write files under a temporary name, and only move them to their final path once closed.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub atomic_finalize: bool,
     #[serde(
         default,
         deserialize_with = "crate::serde::bool_or_struct",
@@ -90,6 +123,9 @@ impl GenerateConfig for FileSinkConfig {
             idle_timeout_secs: None,
             encoding: EncodingConfig::from(Encoding::Text).into(),
             compression: Default::default(),
+            rotation: Default::default(),
+            retention: Default::default(),
+            atomic_finalize: false,
             acknowledgements: Default::default(),
         })
         .unwrap()
@@ -158,6 +194,44 @@ impl OutFile {
     }
 }
 
+/// A file the sink is writing to.
+struct ActiveFile {
+    file: OutFile,
+    /// The path rendered from the template, which the file is finalized at or rotated from.
+    path: PathBuf,
+    /// Where the file is written to until it is closed, when finalizing atomically.
+    temporary_path: Option<PathBuf>,
+    bytes_written: u64,
+    opened_at: Instant,
+}
+
+impl ActiveFile {
+    async fn open(
+        path: PathBuf,
+        compression: Compression,
+        atomic_finalize: bool,
+    ) -> io::Result<Self> {
+        let temporary_path = atomic_finalize.then(|| rotation::temporary_path(&path));
+        let file = open_file(temporary_path.as_ref().unwrap_or(&path)).await?;
+
+        // Files are appended to, so whatever they already hold counts towards their rotation.
+        let metadata = file.metadata().await?;
+        let age = metadata
+            .created()
+            .ok()
+            .and_then(|created| created.elapsed().ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            file: OutFile::new(file, compression),
+            path,
+            temporary_path,
+            bytes_written: metadata.len(),
+            opened_at: Instant::now().checked_sub(age).unwrap_or_else(Instant::now),
+        })
+    }
+}
+
 #[async_trait::async_trait]
 #[typetag::serde(name = "file")]
 impl SinkConfig for FileSinkConfig {
@@ -165,6 +239,10 @@ impl SinkConfig for FileSinkConfig {
         &self,
         cx: SinkContext,
     ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        if self.retention.is_enabled() && !self.rotation.is_enabled() && !self.atomic_finalize {
+            return Err(BuildError::RetentionWithoutRotation.into());
+        }
+
         let sink = FileSink::new(self, cx.acker());
         Ok((
             super::VectorSink::from_event_streamsink(sink),
@@ -192,8 +270,11 @@ pub struct FileSink {
     transformer: Transformer,
     encoder: Encoder<Framer>,
     idle_timeout: Duration,
-    files: ExpiringHashMap<Bytes, OutFile>,
+    files: ExpiringHashMap<Bytes, ActiveFile>,
     compression: Compression,
+    rotation: RotationConfig,
+    retention: RetentionConfig,
+    atomic_finalize: bool,
 }
 
 impl FileSink {
@@ -211,6 +292,9 @@ impl FileSink {
             idle_timeout: Duration::from_secs(config.idle_timeout_secs.unwrap_or(30)),
             files: ExpiringHashMap::default(),
             compression: config.compression,
+            rotation: config.rotation,
+            retention: config.retention,
+            atomic_finalize: config.atomic_finalize,
         }
     }
 
@@ -253,16 +337,10 @@ impl FileSink {
 
                             // Close all the open files.
                             debug!(message = "Closing all the open files.");
-                            for (path, file) in self.files.iter_mut() {
-                                if let Err(error) = file.close().await {
-                                    emit!(FileIoError {
-                                        error,
-                                        code: "failed_closing_file",
-                                        message: "Failed to close file.",
-                                        path: Some(path),
-                                    });
-                                } else{
-                                    trace!(message = "Successfully closed file.", path = ?path);
+                            let paths = self.files.iter_mut().map(|(path, _)| path.clone()).collect::<Vec<_>>();
+                            for path in paths {
+                                if let Some((file, _)) = self.files.remove(&path) {
+                                    self.close_file(&path, file, false).await;
                                 }
                             }
 
@@ -279,13 +357,10 @@ impl FileSink {
                         // We do not poll map when it's empty, so we should
                         // never reach this branch.
                         None => unreachable!(),
-                        Some((mut expired_file, path)) => {
+                        Some((expired_file, path)) => {
                             // We got an expired file. All we really want is to
                             // flush and close it.
-                            if let Err(error) = expired_file.close().await {
-                                error!(message = "Failed to close file.", path = ?path, %error);
-                            }
-                            drop(expired_file); // ignore close error
+                            self.close_file(path.get_ref(), expired_file, false).await;
                             emit!(FileOpen {
                                 count: self.files.len()
                             });
@@ -311,6 +386,16 @@ impl FileSink {
             }
         };
 
+        if let Some(max_age) = self.rotation.max_age() {
+            let expired = self
+                .files
+                .get(&path)
+                .map_or(false, |file| file.opened_at.elapsed() >= max_age);
+            if expired {
+                self.rotate(&path).await;
+            }
+        }
+
         let next_deadline = self.deadline_at();
         trace!(message = "Computed next deadline.", next_deadline = ?next_deadline, path = ?path);
 
@@ -319,26 +404,26 @@ impl FileSink {
             file
         } else {
             trace!(message = "Opening new file.", ?path);
-            let file = match open_file(BytesPath::new(path.clone())).await {
-                Ok(file) => file,
-                Err(error) => {
-                    // We couldn't open the file for this event.
-                    // Maybe other events will work though! Just log
-                    // the error and skip this event.
-                    emit!(FileIoError {
-                        code: "failed_opening_file",
-                        message: "Unable to open the file.",
-                        error,
-                        path: Some(&path),
-                    });
-                    event.metadata().update_status(EventStatus::Errored);
-                    return;
-                }
-            };
-
-            let outfile = OutFile::new(file, self.compression);
+            let file_path = BytesPath::new(path.clone()).as_ref().to_path_buf();
+            let file =
+                match ActiveFile::open(file_path, self.compression, self.atomic_finalize).await {
+                    Ok(file) => file,
+                    Err(error) => {
+                        // We couldn't open the file for this event.
+                        // Maybe other events will work though! Just log
+                        // the error and skip this event.
+                        emit!(FileIoError {
+                            code: "failed_opening_file",
+                            message: "Unable to open the file.",
+                            error,
+                            path: Some(&path),
+                        });
+                        event.metadata().update_status(EventStatus::Errored);
+                        return;
+                    }
+                };
 
-            self.files.insert_at(path.clone(), outfile, next_deadline);
+            self.files.insert_at(path.clone(), file, next_deadline);
             emit!(FileOpen {
                 count: self.files.len()
             });
@@ -348,8 +433,10 @@ impl FileSink {
         trace!(message = "Writing an event to file.", path = ?path);
         let event_size = event.size_of();
         let finalizers = event.take_finalizers();
-        match write_event_to_file(file, event, &self.transformer, &mut self.encoder).await {
+        match write_event_to_file(&mut file.file, event, &self.transformer, &mut self.encoder).await
+        {
             Ok(byte_size) => {
+                file.bytes_written += byte_size as u64;
                 finalizers.update_status(EventStatus::Delivered);
                 emit!(EventsSent {
                     count: 1,
@@ -371,6 +458,115 @@ impl FileSink {
                 });
             }
         }
+
+        if let Some(max_bytes) = self.rotation.max_bytes {
+            if file.bytes_written >= max_bytes {
+                self.rotate(&path).await;
+            }
+        }
+    }
+
+    async fn rotate(&mut self, path: &Bytes) {
+        if let Some((file, _)) = self.files.remove(path) {
+            self.close_file(path, file, true).await;
+            emit!(FileOpen {
+                count: self.files.len()
+            });
+        }
+    }
+
+    /// Closes a file, then moves it to its final path and applies retention, as configured.
+    async fn close_file(&self, path: &Bytes, mut file: ActiveFile, rotate: bool) {
+        if let Err(error) = file.file.close().await {
+            emit!(FileIoError {
+                error,
+                code: "failed_closing_file",
+                message: "Failed to close file.",
+                path: Some(path),
+            });
+            return;
+        }
+        trace!(message = "Successfully closed file.", path = ?path);
+        let ActiveFile {
+            file: out_file,
+            path: file_path,
+            temporary_path,
+            ..
+        } = file;
+        // Some platforms don't allow renaming files that are still open.
+        drop(out_file);
+
+        let rotated_paths = RotatedPaths::new(&file_path, self.rotation.naming);
+        match self
+            .finalize(&rotated_paths, &file_path, temporary_path, rotate)
+            .await
+        {
+            Ok(Some(rotated_path)) => {
+                emit!(FileRotated {
+                    path: &file_path,
+                    rotated_path: &rotated_path,
+                });
+            }
+            Ok(None) => return,
+            Err(error) => {
+                emit!(FileIoError {
+                    error,
+                    code: "failed_rotating_file",
+                    message: "Failed to rotate the file.",
+                    path: Some(path),
+                });
+                return;
+            }
+        }
+
+        if self.retention.is_enabled() {
+            match rotated_paths.apply_retention(&self.retention).await {
+                Ok(deleted) => {
+                    for deleted_path in deleted {
+                        emit!(FileRetentionDeleted {
+                            path: &deleted_path
+                        });
+                    }
+                }
+                Err(error) => emit!(FileIoError {
+                    error,
+                    code: "failed_deleting_file",
+                    message: "Failed to delete rotated files beyond retention.",
+                    path: Some(path),
+                }),
+            }
+        }
+    }
+
+    /// Moves a closed file to where it belongs, returning the new path if it was rotated.
+    ///
+    /// Files written to a temporary path are moved to their final path, unless that already
+    /// exists or rotation is enabled, in which case they are rotated instead so that finalized
+    /// files are never written to again. Otherwise files are only moved when rotated.
+    async fn finalize(
+        &self,
+        rotated_paths: &RotatedPaths,
+        path: &Path,
+        temporary_path: Option<PathBuf>,
+        rotate: bool,
+    ) -> io::Result<Option<PathBuf>> {
+        match temporary_path {
+            Some(temporary_path) => {
+                if !self.rotation.is_enabled() && fs::metadata(path).await.is_err() {
+                    fs::rename(temporary_path, path).await?;
+                    return Ok(None);
+                }
+                let rotated_path = rotated_paths.next().await?;
+                fs::rename(temporary_path, &rotated_path).await?;
+                Ok(Some(rotated_path))
+            }
+            None if rotate => {
+                let rotated_path = rotated_paths.next().await?;
+                fs::rename(path, &rotated_path).await?;
+                Ok(Some(rotated_path))
+            }
+            None => Ok(None),
+        }
     }
 }
 
@@ -448,6 +644,9 @@ mod tests {
             idle_timeout_secs: None,
             encoding: EncodingConfig::from(Encoding::Text).into(),
             compression: Compression::None,
+            rotation: Default::default(),
+            retention: Default::default(),
+            atomic_finalize: false,
             acknowledgements: Default::default(),
         };
 
@@ -476,6 +675,9 @@ mod tests {
             idle_timeout_secs: None,
             encoding: EncodingConfig::from(Encoding::Text).into(),
             compression: Compression::Gzip,
+            rotation: Default::default(),
+            retention: Default::default(),
+            atomic_finalize: false,
             acknowledgements: Default::default(),
         };
 
@@ -509,6 +711,9 @@ mod tests {
             idle_timeout_secs: None,
             encoding: EncodingConfig::from(Encoding::Text).into(),
             compression: Compression::None,
+            rotation: Default::default(),
+            retention: Default::default(),
+            atomic_finalize: false,
             acknowledgements: Default::default(),
         };
 
@@ -591,6 +796,9 @@ mod tests {
             idle_timeout_secs: Some(1),
             encoding: EncodingConfig::from(Encoding::Text).into(),
             compression: Compression::None,
+            rotation: Default::default(),
+            retention: Default::default(),
+            atomic_finalize: false,
             acknowledgements: Default::default(),
         };
 
@@ -623,4 +831,108 @@ mod tests {
 
         SINK_TESTS.assert(&FILE_SINK_TAGS);
     }
+
+    fn rotating_config(path: &std::path::Path) -> FileSinkConfig {
+        FileSinkConfig {
+            path: path.to_path_buf().try_into().unwrap(),
+            idle_timeout_secs: None,
+            encoding: EncodingConfig::from(Encoding::Text).into(),
+            compression: Compression::None,
+            rotation: Default::default(),
+            retention: Default::default(),
+            atomic_finalize: false,
+            acknowledgements: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn rotates_by_size_with_retention() {
+        trace_init();
+
+        let directory = temp_dir();
+        let config = FileSinkConfig {
+            rotation: RotationConfig {
+                max_bytes: Some(100),
+                ..Default::default()
+            },
+            retention: RetentionConfig {
+                max_files: Some(2),
+                ..Default::default()
+            },
+            ..rotating_config(&directory.join("app.log"))
+        };
+
+        // Every line takes 21 bytes with its newline, so files are rotated after 5 lines.
+        let mut sink = FileSink::new(&config, Acker::passthrough());
+        let (input, _) = random_lines_with_stream(20, 20, None);
+        let events = Box::pin(stream::iter(input.clone().into_iter().map(Event::from)));
+        sink.run(events).await.unwrap();
+
+        assert!(!directory.join("app.log").exists());
+        assert!(!directory.join("app.1.log").exists());
+        assert!(!directory.join("app.2.log").exists());
+        let mut output = lines_from_file(directory.join("app.3.log"));
+        output.extend(lines_from_file(directory.join("app.4.log")));
+        assert_eq!(output, input[10..].to_vec());
+    }
+
+    #[tokio::test]
+    async fn finalizes_files_atomically() {
+        trace_init();
+
+        let directory = temp_dir();
+        let config = FileSinkConfig {
+            atomic_finalize: true,
+            ..rotating_config(&directory.join("app.log"))
+        };
+
+        let mut sink = FileSink::new(&config, Acker::passthrough());
+        let (input, _) = random_lines_with_stream(10, 8, None);
+        let (mut tx, rx) = futures::channel::mpsc::channel(0);
+        let handle = tokio::spawn(async move { sink.run(Box::pin(rx)).await });
+        for line in input.clone() {
+            tx.send(Event::from(line)).await.unwrap();
+        }
+
+        // Nothing shows up at the final path while the file is being written.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!directory.join("app.log").exists());
+        assert!(directory.join(".app.log.tmp").exists());
+
+        drop(tx);
+        handle.await.unwrap().unwrap();
+        assert!(!directory.join(".app.log.tmp").exists());
+        assert_eq!(lines_from_file(directory.join("app.log")), input);
+
+        // Finalized files are never appended to, later ones are rotated instead.
+        let mut sink = FileSink::new(&config, Acker::passthrough());
+        let (more_input, _) = random_lines_with_stream(10, 8, None);
+        let events = Box::pin(stream::iter(
+            more_input.clone().into_iter().map(Event::from),
+        ));
+        sink.run(events).await.unwrap();
+        assert_eq!(lines_from_file(directory.join("app.log")), input);
+        assert_eq!(lines_from_file(directory.join("app.1.log")), more_input);
+    }
+
+    #[tokio::test]
+    async fn rejects_retention_without_rotation() {
+        let config = FileSinkConfig {
+            retention: RetentionConfig {
+                max_files: Some(2),
+                ..Default::default()
+            },
+            ..rotating_config(&temp_dir().join("app.log"))
+        };
+
+        let error = config
+            .build(SinkContext::new_test())
+            .await
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`retention` requires `rotation` or `atomic_finalize` to be set"
+        );
+    }
 }
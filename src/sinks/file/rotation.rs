@@ -0,0 +1,330 @@
+//! Naming, finalizing and cleaning up of the files rotated by the file sink.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RotationConfig {
+    /// Rotate the file once this many bytes, before compression, have been written to it.
+    pub max_bytes: Option<u64>,
+    /// Rotate the file once it has been open for this long.
+    pub max_age_secs: Option<u64>,
+    #[serde(default)]
+    pub naming: RotationNaming,
+}
+
+impl RotationConfig {
+    pub const fn is_enabled(&self) -> bool {
+        self.max_bytes.is_some() || self.max_age_secs.is_some()
+    }
+
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age_secs.map(Duration::from_secs)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Derivative, Clone, Copy, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationNaming {
+    /// Rotated files are numbered, starting from 1, with newer files getting higher numbers.
+    #[derivative(Default)]
+    Sequence,
+    /// Rotated files are named after the UTC time they were rotated at.
+    Timestamp,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// Keep at most this many rotated files per path, deleting the oldest ones.
+    pub max_files: Option<usize>,
+    /// Keep at most this many bytes of rotated files per path, deleting the oldest ones.
+    pub max_bytes: Option<u64>,
+}
+
+impl RetentionConfig {
+    pub const fn is_enabled(&self) -> bool {
+        self.max_files.is_some() || self.max_bytes.is_some()
+    }
+}
+
+/// The path of a file, split around where the suffix of its rotated files goes.
+///
+/// The suffix is inserted before the last extension, so that `app.log` is rotated to `app.1.log`
+/// and `app.log.gz` to `app.log.1.gz`, which keeps tools that go by the extension working.
+#[derive(Debug)]
+pub struct RotatedPaths {
+    directory: PathBuf,
+    stem: String,
+    extension: Option<String>,
+    naming: RotationNaming,
+}
+
+impl RotatedPaths {
+    pub fn new(path: &Path, naming: RotationNaming) -> Self {
+        let directory = path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(PathBuf::new);
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let (stem, extension) = match file_name.rfind('.') {
+            Some(index) if index > 0 => (
+                file_name[..index].to_owned(),
+                Some(file_name[index + 1..].to_owned()),
+            ),
+            _ => (file_name, None),
+        };
+
+        Self {
+            directory,
+            stem,
+            extension,
+            naming,
+        }
+    }
+
+    fn with_suffix(&self, suffix: &str) -> PathBuf {
+        let file_name = match &self.extension {
+            Some(extension) => format!("{}.{}.{}", self.stem, suffix, extension),
+            None => format!("{}.{}", self.stem, suffix),
+        };
+        self.directory.join(file_name)
+    }
+
+    /// Gets the key rotated files are ordered by, from oldest to newest, or `None` if the file
+    /// isn't one of them.
+    fn sort_key(&self, file_name: &str) -> Option<String> {
+        let suffix = file_name
+            .strip_prefix(self.stem.as_str())?
+            .strip_prefix('.')?;
+        let suffix = match &self.extension {
+            Some(extension) => suffix.strip_suffix(extension.as_str())?.strip_suffix('.')?,
+            None => suffix,
+        };
+
+        match self.naming {
+            RotationNaming::Sequence => suffix
+                .parse::<u64>()
+                .ok()
+                .filter(|_| suffix.bytes().all(|byte| byte.is_ascii_digit()))
+                .map(|sequence| format!("{:020}", sequence)),
+            RotationNaming::Timestamp => NaiveDateTime::parse_from_str(suffix, TIMESTAMP_FORMAT)
+                .ok()
+                .map(|_| suffix.to_owned()),
+        }
+    }
+
+    /// Lists the rotated files along with their sizes, from oldest to newest.
+    pub async fn list(&self) -> io::Result<Vec<(PathBuf, u64)>> {
+        let directory = if self.directory.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            self.directory.as_path()
+        };
+
+        let mut rotated = Vec::new();
+        let mut entries = fs::read_dir(directory).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let key = match entry
+                .file_name()
+                .to_str()
+                .and_then(|name| self.sort_key(name))
+            {
+                Some(key) => key,
+                None => continue,
+            };
+            let metadata = entry.metadata().await?;
+            if metadata.is_file() {
+                rotated.push((key, self.directory.join(entry.file_name()), metadata.len()));
+            }
+        }
+        rotated.sort();
+
+        Ok(rotated
+            .into_iter()
+            .map(|(_, path, len)| (path, len))
+            .collect())
+    }
+
+    /// Picks the path for the next rotated file, which is newer than all existing ones.
+    pub async fn next(&self) -> io::Result<PathBuf> {
+        let rotated = self.list().await?;
+        match self.naming {
+            RotationNaming::Sequence => {
+                let last = rotated
+                    .last()
+                    .and_then(|(path, _)| {
+                        path.file_name()?
+                            .to_str()
+                            .and_then(|name| self.sort_key(name))
+                    })
+                    .and_then(|key| key.parse::<u64>().ok())
+                    .unwrap_or(0);
+                Ok(self.with_suffix(&(last + 1).to_string()))
+            }
+            RotationNaming::Timestamp => {
+                let mut timestamp = Utc::now();
+                loop {
+                    let path = self.with_suffix(&timestamp.format(TIMESTAMP_FORMAT).to_string());
+                    // Several files rotated within the same millisecond are told apart by moving
+                    // later ones forward in time, which keeps them in order.
+                    if fs::metadata(&path).await.is_err() {
+                        return Ok(path);
+                    }
+                    timestamp = timestamp + chrono::Duration::milliseconds(1);
+                }
+            }
+        }
+    }
+
+    /// Deletes the oldest rotated files until what is left fits in the retention limits,
+    /// returning the deleted files.
+    pub async fn apply_retention(&self, retention: &RetentionConfig) -> io::Result<Vec<PathBuf>> {
+        let rotated = self.list().await?;
+        let mut count = rotated.len();
+        let mut total_bytes = rotated.iter().map(|(_, len)| len).sum::<u64>();
+
+        let mut deleted = Vec::new();
+        for (path, len) in rotated {
+            let over_count = retention.max_files.map_or(false, |max| count > max);
+            let over_bytes = retention.max_bytes.map_or(false, |max| total_bytes > max);
+            if !over_count && !over_bytes {
+                break;
+            }
+
+            fs::remove_file(&path).await?;
+            count -= 1;
+            total_bytes -= len;
+            deleted.push(path);
+        }
+
+        Ok(deleted)
+    }
+}
+
+/// Gets the temporary path a file is written to before being finalized, next to the file itself
+/// and hidden so that shippers watching the directory skip it.
+pub fn temporary_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp", file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn inserts_suffix_before_extension() {
+        let paths = RotatedPaths::new(Path::new("/var/log/app.log.gz"), RotationNaming::Sequence);
+        assert_eq!(paths.with_suffix("3"), Path::new("/var/log/app.log.3.gz"));
+
+        let paths = RotatedPaths::new(Path::new("/var/log/app"), RotationNaming::Sequence);
+        assert_eq!(paths.with_suffix("3"), Path::new("/var/log/app.3"));
+
+        let paths = RotatedPaths::new(Path::new("/var/log/.hidden"), RotationNaming::Sequence);
+        assert_eq!(paths.with_suffix("3"), Path::new("/var/log/.hidden.3"));
+
+        assert_eq!(
+            temporary_path(Path::new("/var/log/app.log")),
+            Path::new("/var/log/.app.log.tmp")
+        );
+    }
+
+    #[test]
+    fn recognizes_rotated_files() {
+        let paths = RotatedPaths::new(Path::new("app.log"), RotationNaming::Sequence);
+        assert!(paths.sort_key("app.12.log").unwrap() > paths.sort_key("app.9.log").unwrap());
+        assert_eq!(paths.sort_key("app.log"), None);
+        assert_eq!(paths.sort_key("app.x1.log"), None);
+        assert_eq!(paths.sort_key("app.+1.log"), None);
+        assert_eq!(paths.sort_key("other.1.log"), None);
+
+        let paths = RotatedPaths::new(Path::new("app.log"), RotationNaming::Timestamp);
+        assert!(paths.sort_key("app.20220601T101500.120Z.log").is_some());
+        assert_eq!(paths.sort_key("app.1.log"), None);
+    }
+
+    #[tokio::test]
+    async fn numbers_rotated_files_in_sequence() {
+        let directory = temp_dir();
+        std::fs::create_dir_all(&directory).unwrap();
+        let paths = RotatedPaths::new(&directory.join("app.log"), RotationNaming::Sequence);
+
+        for expected in ["app.1.log", "app.2.log", "app.3.log"] {
+            let next = paths.next().await.unwrap();
+            assert_eq!(next, directory.join(expected));
+            std::fs::write(&next, b"line\n").unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn orders_timestamped_files_within_the_same_millisecond() {
+        let directory = temp_dir();
+        std::fs::create_dir_all(&directory).unwrap();
+        let paths = RotatedPaths::new(&directory.join("app.log"), RotationNaming::Timestamp);
+
+        let mut rotated = Vec::new();
+        for _ in 0..3 {
+            let next = paths.next().await.unwrap();
+            std::fs::write(&next, b"line\n").unwrap();
+            rotated.push(next);
+        }
+
+        let listed = paths.list().await.unwrap();
+        assert_eq!(
+            listed.into_iter().map(|(path, _)| path).collect::<Vec<_>>(),
+            rotated
+        );
+    }
+
+    #[tokio::test]
+    async fn deletes_oldest_files_beyond_retention() {
+        let directory = temp_dir();
+        std::fs::create_dir_all(&directory).unwrap();
+        let paths = RotatedPaths::new(&directory.join("app.log"), RotationNaming::Sequence);
+        for sequence in 1..=5 {
+            std::fs::write(
+                directory.join(format!("app.{}.log", sequence)),
+                b"0123456789",
+            )
+            .unwrap();
+        }
+        std::fs::write(directory.join("app.log"), b"0123456789").unwrap();
+
+        let retention = RetentionConfig {
+            max_files: Some(4),
+            max_bytes: Some(25),
+        };
+        let deleted = paths.apply_retention(&retention).await.unwrap();
+        assert_eq!(
+            deleted,
+            vec![
+                directory.join("app.1.log"),
+                directory.join("app.2.log"),
+                directory.join("app.3.log")
+            ]
+        );
+        assert!(directory.join("app.4.log").exists());
+        assert!(directory.join("app.5.log").exists());
+        // The file currently written to is never deleted.
+        assert!(directory.join("app.log").exists());
+    }
+}
@@ -17,7 +17,7 @@ use snafu::ResultExt;
 use snafu::Snafu;
 use tower::ServiceBuilder;
 use uuid::Uuid;
-use vector_core::{event::Finalizable, ByteSizeOf};
+use vector_core::{event::Finalizable, stream::BatcherSettings, ByteSizeOf};
 
 use crate::{
     codecs::Encoder,
@@ -40,8 +40,9 @@ use crate::{
             batch::BatchConfig,
             encoding::{
                 EncodingConfig, EncodingConfigWithFramingAdapter, StandardEncodings,
-                StandardEncodingsWithFramingMigrator, Transformer,
+                StandardEncodingsWithFramingMigrator,
             },
+            parquet::{ObjectEncoder, ParquetEncodingConfig},
             partitioner::KeyPartitioner,
             BulkSizeBasedDefaultBatchSettings, Compression, RequestBuilder, ServiceBuilderExt,
             TowerRequestConfig,
@@ -76,6 +77,8 @@ pub struct GcsSinkConfig {
         EncodingConfig<StandardEncodings>,
         StandardEncodingsWithFramingMigrator,
     >,
+    /// Writes each batch as a Parquet file instead of encoding its events with `encoding.codec`.
+    parquet: Option<ParquetEncodingConfig>,
     #[serde(default)]
     compression: Compression,
     #[serde(default)]
@@ -105,6 +108,7 @@ fn default_config(e: StandardEncodings) -> GcsSinkConfig {
         filename_append_uuid: Default::default(),
         filename_extension: Default::default(),
         encoding: EncodingConfig::from(e).into(),
+        parquet: Default::default(),
         compression: Compression::gzip_default(),
         batch: Default::default(),
         request: Default::default(),
@@ -185,7 +189,7 @@ impl GcsSinkConfig {
             .settings(request, GcsRetryLogic)
             .service(GcsService::new(client, base_url, creds));
 
-        let request_settings = RequestSettings::new(self)?;
+        let request_settings = RequestSettings::new(self, &batch_settings)?;
 
         let sink = GcsSink::new(cx, svc, request_settings, partitioner, batch_settings);
 
@@ -213,14 +217,14 @@ struct RequestSettings {
     extension: String,
     time_format: String,
     append_uuid: bool,
-    encoder: (Transformer, Encoder<Framer>),
+    encoder: ObjectEncoder,
     compression: Compression,
 }
 
 impl RequestBuilder<(String, Vec<Event>)> for RequestSettings {
     type Metadata = GcsMetadata;
     type Events = Vec<Event>;
-    type Encoder = (Transformer, Encoder<Framer>);
+    type Encoder = ObjectEncoder;
     type Payload = Bytes;
     type Request = GcsRequest;
     type Error = io::Error; // TODO: this is ugly.
@@ -278,7 +282,7 @@ impl RequestBuilder<(String, Vec<Event>)> for RequestSettings {
 }
 
 impl RequestSettings {
-    fn new(config: &GcsSinkConfig) -> crate::Result<Self> {
+    fn new(config: &GcsSinkConfig, batch_settings: &BatcherSettings) -> crate::Result<Self> {
         let transformer = config.encoding.transformer();
         let (framer, serializer) = config.encoding.encoding();
         let framer = match (framer, &serializer) {
@@ -290,12 +294,18 @@ impl RequestSettings {
             }
         };
         let encoder = Encoder::<Framer>::new(framer, serializer);
+        let encoder = ObjectEncoder::new(
+            transformer,
+            encoder,
+            config.parquet.as_ref(),
+            batch_settings,
+        )?;
+        let compression = encoder.compression(config.compression);
         let acl = config
             .acl
             .map(|acl| HeaderValue::from_str(&to_string(acl)).unwrap());
         let content_type = HeaderValue::from_str(encoder.content_type()).unwrap();
-        let content_encoding = config
-            .compression
+        let content_encoding = compression
             .content_encoding()
             .map(|ce| HeaderValue::from_str(&to_string(ce)).unwrap());
         let storage_class = config.storage_class.unwrap_or_default();
@@ -313,7 +323,7 @@ impl RequestSettings {
         let extension = config
             .filename_extension
             .clone()
-            .unwrap_or_else(|| config.compression.extension().into());
+            .unwrap_or_else(|| encoder.extension(compression).into());
         let time_format = config
             .filename_time_format
             .clone()
@@ -328,8 +338,8 @@ impl RequestSettings {
             extension,
             time_format,
             append_uuid,
-            compression: config.compression,
-            encoder: (transformer, encoder),
+            compression,
+            encoder,
         })
     }
 }
@@ -375,7 +385,9 @@ mod tests {
     }
 
     fn request_settings(sink_config: &GcsSinkConfig) -> RequestSettings {
-        RequestSettings::new(sink_config).expect("Could not create request settings")
+        let batch_settings = sink_config.batch.into_batcher_settings().unwrap();
+        RequestSettings::new(sink_config, &batch_settings)
+            .expect("Could not create request settings")
     }
 
     fn build_request(extension: Option<&str>, uuid: bool, compression: Compression) -> GcsRequest {
@@ -411,5 +423,33 @@ mod tests {
 
         let req = build_request(None, true, Compression::gzip_default());
         assert_ne!(req.metadata.key, "key/date.log.gz".to_string());
+
+        let req = build_request(None, false, Compression::zstd_default());
+        assert_eq!(req.metadata.key, "key/date.log.zst".to_string());
+
+        let req = build_request(None, false, Compression::Snappy);
+        assert_eq!(req.metadata.key, "key/date.log.snappy".to_string());
+
+        let req = build_request(None, false, Compression::lz4_default());
+        assert_eq!(req.metadata.key, "key/date.log.lz4".to_string());
+    }
+
+    #[test]
+    fn gcs_build_parquet_request() {
+        let sink_config = GcsSinkConfig {
+            filename_time_format: Some("date".into()),
+            filename_append_uuid: Some(false),
+            parquet: Some(Default::default()),
+            ..default_config(StandardEncodings::Ndjson)
+        };
+        let request_settings = request_settings(&sink_config);
+        let (metadata, _events) =
+            request_settings.split_input(("key/".into(), vec![Event::new_empty_log()]));
+        let req = request_settings.build_request(metadata, Bytes::new());
+
+        // Parquet files compress their columns instead of being compressed as a whole.
+        assert_eq!(req.metadata.key, "key/date.parquet".to_string());
+        assert_eq!(req.settings.content_type, "application/vnd.apache.parquet");
+        assert_eq!(req.settings.content_encoding, None);
     }
 }
@@ -6,7 +6,6 @@ use codecs::encoding::{
     JsonSerializerConfig, NewlineDelimitedEncoder, NewlineDelimitedEncoderConfig,
     RawMessageSerializerConfig, Serializer, SerializerConfig,
 };
-use flate2::write::GzEncoder;
 use futures::{future, FutureExt, SinkExt};
 use http::{
     header::{self, HeaderName, HeaderValue},
@@ -31,8 +30,9 @@ use crate::{
             EncodingConfig, EncodingConfigWithFramingAdapter, EncodingConfigWithFramingMigrator,
             Transformer,
         },
-        http::{BatchedHttpSink, HttpEventEncoder, RequestConfig},
-        BatchConfig, Buffer, Compression, RealtimeSizeBasedDefaultBatchSettings,
+        http::{BatchedHttpSink, HttpEventEncoder, HttpRetryLogic, RequestConfig},
+        service::{resolve_endpoints, DistributionConfig},
+        BatchConfig, Buffer, Compression, Compressor, RealtimeSizeBasedDefaultBatchSettings,
         TowerRequestConfig, UriSerde,
     },
     tls::{TlsConfig, TlsSettings},
@@ -75,7 +75,15 @@ impl EncodingConfigWithFramingMigrator for Migrator {
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct HttpSinkConfig {
+    #[serde(default)]
     pub uri: UriSerde,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uris: Vec<UriSerde>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub distribution: DistributionConfig,
     pub method: Option<HttpMethod>,
     pub auth: Option<Auth>,
     // Deprecated, moved to request.
@@ -203,35 +211,48 @@ impl SinkConfig for HttpSinkConfig {
         let serializer = encoding.1;
         let encoder = Encoder::<Framer>::new(framing, serializer);
 
-        let sink = HttpSink {
-            uri: self.uri.with_default_parts(),
-            method: self.method.clone(),
-            auth: self.auth.choose_one(&self.uri.auth)?,
-            compression: self.compression,
-            transformer: self.encoding.transformer(),
-            encoder,
-            batch: self.batch,
-            request,
+        let uri = Some(&self.uri).filter(|uri| !uri.is_unset());
+        let mut sinks = resolve_endpoints("uri", uri, &self.uris)?
+            .into_iter()
+            .map(|uri| {
+                let sink = HttpSink {
+                    uri: uri.with_default_parts(),
+                    method: self.method.clone(),
+                    auth: self.auth.choose_one(&uri.auth)?,
+                    compression: self.compression,
+                    transformer: self.encoding.transformer(),
+                    encoder: encoder.clone(),
+                    batch: self.batch,
+                    request: request.clone(),
+                };
+                Ok((sink.uri.uri.to_string(), sink))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let batch = self.batch.into_batch_settings()?;
+        let request = request.tower.unwrap_with(&TowerRequestConfig::default());
+        let buffer = Buffer::new(batch.size, Compression::None);
+        let sink = if sinks.len() == 1 {
+            let (_, sink) = sinks.swap_remove(0);
+            let sink =
+                BatchedHttpSink::new(sink, buffer, request, batch.timeout, client, cx.acker())
+                    .sink_map_err(|error| error!(message = "Fatal HTTP sink error.", %error));
+            super::VectorSink::from_event_sink(sink)
+        } else {
+            let sink = BatchedHttpSink::distributed(
+                sinks,
+                buffer,
+                HttpRetryLogic,
+                request,
+                self.distribution,
+                batch.timeout,
+                client,
+                cx.acker(),
+            )
+            .sink_map_err(|error| error!(message = "Fatal HTTP sink error.", %error));
+            super::VectorSink::from_event_sink(sink)
         };
 
-        let request = sink
-            .request
-            .tower
-            .unwrap_with(&TowerRequestConfig::default());
-
-        let batch = sink.batch.into_batch_settings()?;
-        let sink = BatchedHttpSink::new(
-            sink,
-            Buffer::new(batch.size, Compression::None),
-            request,
-            batch.timeout,
-            client,
-            cx.acker(),
-        )
-        .sink_map_err(|error| error!(message = "Fatal HTTP sink error.", %error));
-
-        let sink = super::VectorSink::from_event_sink(sink);
-
         Ok((sink, healthcheck))
     }
 
@@ -328,16 +349,14 @@ impl util::http::HttpSink for HttpSink {
             builder = builder.header("Content-Type", content_type);
         }
 
-        match self.compression {
-            Compression::Gzip(level) => {
-                builder = builder.header("Content-Encoding", "gzip");
+        if let Some(content_encoding) = self.compression.content_encoding() {
+            builder = builder.header("Content-Encoding", content_encoding);
 
-                let buffer = BytesMut::new();
-                let mut w = GzEncoder::new(buffer.writer(), level);
-                w.write_all(&body).expect("Writing to Vec can't fail");
-                body = w.finish().expect("Writing to Vec can't fail").into_inner();
-            }
-            Compression::None => {}
+            let mut compressor = Compressor::from(self.compression);
+            compressor
+                .write_all(&body)
+                .expect("Writing to Vec can't fail");
+            body = compressor.finish().expect("Writing to Vec can't fail");
         }
 
         for (header, value) in self.request.headers.iter() {
@@ -728,6 +747,104 @@ mod tests {
         assert_eq!(input_lines, output_lines);
     }
 
+    #[tokio::test]
+    async fn zstd_compression() {
+        let num_lines = 1000;
+
+        let in_addr = next_addr();
+
+        let config = r#"
+        uri = "http://$IN_ADDR/frames"
+        compression = "zstd"
+        encoding = "json"
+    "#
+        .replace("$IN_ADDR", &in_addr.to_string());
+        let config: HttpSinkConfig = toml::from_str(&config).unwrap();
+
+        let (sink, _) = config.build(SinkContext::new_test()).await.unwrap();
+        let (rx, trigger, server) = build_test_server(in_addr);
+
+        let (input_lines, events) = random_lines_with_stream(100, num_lines, None);
+        tokio::spawn(server);
+        sink.run(events).await.unwrap();
+        drop(trigger);
+
+        let output_lines = rx
+            .flat_map(|(parts, body)| {
+                assert_eq!(
+                    Some("zstd"),
+                    parts
+                        .headers
+                        .get("Content-Encoding")
+                        .map(|value| value.to_str().unwrap())
+                );
+
+                let body = zstd::stream::decode_all(body.reader()).unwrap();
+                let lines: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+                stream::iter(lines)
+            })
+            .map(|line| line.get("message").unwrap().as_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(input_lines, output_lines);
+    }
+
+    #[tokio::test]
+    async fn spreads_requests_over_uris() {
+        let num_lines = 1000;
+
+        let addrs = [next_addr(), next_addr()];
+        let (sink, _) = build_sink_with_uris(&addrs).await;
+
+        let (rx1, trigger1, server1) = build_test_server(addrs[0]);
+        let (rx2, trigger2, server2) = build_test_server(addrs[1]);
+        tokio::spawn(server1);
+        tokio::spawn(server2);
+
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let (mut input_lines, events) = random_lines_with_stream(100, num_lines, Some(batch));
+        sink.run(events).await.unwrap();
+        drop(trigger1);
+        drop(trigger2);
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+
+        let output1 = get_received(rx1, |_| {}).await;
+        let output2 = get_received(rx2, |_| {}).await;
+        assert!(!output1.is_empty());
+        assert!(!output2.is_empty());
+
+        let mut output_lines = [output1, output2].concat();
+        output_lines.sort();
+        input_lines.sort();
+        assert_eq!(input_lines, output_lines);
+    }
+
+    #[tokio::test]
+    async fn fails_over_from_unreachable_uri() {
+        let num_lines = 1000;
+
+        let addrs = [next_addr(), next_addr()];
+        let (sink, _) = build_sink_with_uris(&addrs).await;
+
+        // Nothing listens on the first address.
+        let (rx, trigger, server) = build_test_server(addrs[1]);
+        tokio::spawn(server);
+
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let (mut input_lines, events) = random_lines_with_stream(100, num_lines, Some(batch));
+        sink.run(events).await.unwrap();
+        drop(trigger);
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+
+        let mut output_lines = get_received(rx, |_| {}).await;
+        output_lines.sort();
+        input_lines.sort();
+        assert_eq!(input_lines, output_lines);
+    }
+
     async fn get_received(
         rx: mpsc::Receiver<(Parts, Bytes)>,
         assert_parts: impl Fn(Parts),
@@ -766,6 +883,28 @@ mod tests {
         assert_eq!(input_lines, output_lines);
     }
 
+    async fn build_sink_with_uris(
+        addrs: &[std::net::SocketAddr],
+    ) -> (crate::sinks::VectorSink, crate::sinks::Healthcheck) {
+        let uris = addrs
+            .iter()
+            .map(|addr| format!(r#""http://{}/frames""#, addr))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let config = format!(
+            r#"
+                uris = [{uris}]
+                compression = "gzip"
+                encoding = "ndjson"
+                batch.max_events = 10
+            "#,
+            uris = uris
+        );
+        let config: HttpSinkConfig = toml::from_str(&config).unwrap();
+
+        config.build(SinkContext::new_test()).await.unwrap()
+    }
+
     async fn build_sink(extra_config: &str) -> (std::net::SocketAddr, crate::sinks::VectorSink) {
         let in_addr = next_addr();
         let config = format!(
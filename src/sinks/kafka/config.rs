@@ -1,15 +1,19 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, num::NonZeroUsize, time::Duration};
 
 use futures::FutureExt;
 use rdkafka::ClientConfig;
 use serde::{Deserialize, Serialize};
+use vector_core::stream::BatcherSettings;
 
 use crate::{
     config::{AcknowledgementsConfig, GenerateConfig, Input, SinkConfig, SinkContext},
     kafka::{KafkaAuthConfig, KafkaCompression},
     serde::json::to_string,
     sinks::{
-        kafka::sink::{healthcheck, KafkaSink},
+        kafka::{
+            partitioner::KafkaPartitioner,
+            sink::{healthcheck, KafkaSink},
+        },
         util::{
             encoding::{
                 EncodingConfig, EncodingConfigAdapter, StandardEncodings, StandardEncodingsMigrator,
@@ -18,6 +22,7 @@ use crate::{
         },
         Healthcheck, VectorSink,
     },
+    template::Template,
 };
 
 pub(crate) const QUEUED_MIN_MESSAGES: u64 = 100000;
@@ -27,6 +32,10 @@ pub(crate) struct KafkaSinkConfig {
     pub bootstrap_servers: String,
     pub topic: String,
     pub key_field: Option<String>,
+    /// The field whose value the `partitioner` hashes, defaulting to the message key.
+    pub partition_key_field: Option<String>,
+    /// Picks the partition of each record instead of librdkafka, which hashes the message key.
+    pub partitioner: Option<KafkaPartitioner>,
     #[serde(flatten)]
     pub(crate) encoding:
         EncodingConfigAdapter<EncodingConfig<StandardEncodings>, StandardEncodingsMigrator>,
@@ -45,6 +54,15 @@ pub(crate) struct KafkaSinkConfig {
     pub librdkafka_options: HashMap<String, String>,
     #[serde(alias = "headers_field")] // accidentally released as `headers_field` in 0.18
     pub headers_key: Option<String>,
+    /// Headers added to every record, rendered from templates.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, Template>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub delivery_mode: DeliveryMode,
+    pub transaction: Option<TransactionConfig>,
     #[serde(
         default,
         deserialize_with = "crate::serde::bool_or_struct",
@@ -53,6 +71,72 @@ pub(crate) struct KafkaSinkConfig {
     pub acknowledgements: AcknowledgementsConfig,
 }
 
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryMode {
+    /// Records are retried until acknowledged by the brokers, which may write them more than once.
+    #[derivative(Default)]
+    AtLeastOnce,
+    /// The brokers discard records retried by the producer that they have already written.
+    Idempotent,
+    /// Each batch of events is written in a Kafka transaction, which is committed before the
+    /// events are acknowledged, so that consumers reading committed records see it exactly once.
+    Transactional,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionConfig {
+    /// Identifies the producer across restarts, which must be unique to each Vector instance.
+    pub transactional_id: String,
+    /// How long the brokers wait for a transaction to be committed before aborting it.
+    #[serde(default = "default_transaction_timeout_ms")]
+    pub timeout_ms: u64,
+    /// The most events written in one transaction.
+    #[serde(default = "default_transaction_max_events")]
+    pub max_events: usize,
+    /// The most bytes of encoded records written in one transaction.
+    #[serde(default = "default_transaction_max_bytes")]
+    pub max_bytes: usize,
+    /// How long a transaction is kept open waiting for more events before it is committed.
+    #[serde(default = "default_transaction_commit_interval_secs")]
+    pub commit_interval_secs: f64,
+}
+
+const fn default_transaction_timeout_ms() -> u64 {
+    60000 // default in librdkafka
+}
+
+const fn default_transaction_max_events() -> usize {
+    1000
+}
+
+const fn default_transaction_max_bytes() -> usize {
+    10_000_000
+}
+
+const fn default_transaction_commit_interval_secs() -> f64 {
+    1.0
+}
+
+impl TransactionConfig {
+    pub(crate) fn batch_settings(&self) -> crate::Result<BatcherSettings> {
+        let max_events = NonZeroUsize::new(self.max_events)
+            .ok_or("`transaction.max_events` must be greater than 0.")?;
+        let max_bytes = NonZeroUsize::new(self.max_bytes)
+            .ok_or("`transaction.max_bytes` must be greater than 0.")?;
+        if self.commit_interval_secs.is_nan() || self.commit_interval_secs <= 0.0 {
+            return Err("`transaction.commit_interval_secs` must be greater than 0.".into());
+        }
+        Ok(BatcherSettings::new(
+            Duration::from_secs_f64(self.commit_interval_secs),
+            max_bytes,
+            max_events,
+        ))
+    }
+}
+
 const fn default_socket_timeout_ms() -> u64 {
     60000 // default in librdkafka
 }
@@ -86,6 +170,30 @@ impl KafkaSinkConfig {
                     .set("compression.codec", &to_string(self.compression))
                     .set("message.timeout.ms", &self.message_timeout_ms.to_string());
 
+                match (self.delivery_mode, &self.transaction) {
+                    (DeliveryMode::AtLeastOnce, None) => {}
+                    (DeliveryMode::Idempotent, None) => {
+                        client_config.set("enable.idempotence", "true");
+                    }
+                    // Transactions imply idempotence.
+                    (DeliveryMode::Transactional, Some(transaction)) => {
+                        client_config
+                            .set("transactional.id", &transaction.transactional_id)
+                            .set(
+                                "transaction.timeout.ms",
+                                &transaction.timeout_ms.to_string(),
+                            );
+                    }
+                    (DeliveryMode::Transactional, None) => return Err(
+                        "`delivery_mode = \"transactional\"` requires the `transaction` options."
+                            .into(),
+                    ),
+                    (_, Some(_)) => return Err(
+                        "The `transaction` options require `delivery_mode = \"transactional\"`."
+                            .into(),
+                    ),
+                }
+
                 if let Some(value) = self.batch.timeout_secs {
                     // Delay in milliseconds to wait for messages in the producer queue to accumulate before
                     // constructing message batches (MessageSets) to transmit to brokers. A higher value
@@ -167,6 +275,8 @@ impl GenerateConfig for KafkaSinkConfig {
             bootstrap_servers: "10.14.22.123:9092,10.14.23.332:9092".to_owned(),
             topic: "topic-1234".to_owned(),
             key_field: Some("user_id".to_owned()),
+            partition_key_field: None,
+            partitioner: None,
             encoding: EncodingConfig::from(StandardEncodings::Json).into(),
             batch: Default::default(),
             compression: KafkaCompression::None,
@@ -175,6 +285,9 @@ impl GenerateConfig for KafkaSinkConfig {
             message_timeout_ms: default_message_timeout_ms(),
             librdkafka_options: Default::default(),
             headers_key: None,
+            headers: Default::default(),
+            delivery_mode: Default::default(),
+            transaction: None,
             acknowledgements: Default::default(),
         })
         .unwrap()
@@ -211,4 +324,46 @@ mod tests {
     fn generate_config() {
         KafkaSinkConfig::generate_config();
     }
+
+    fn producer_option(config: &str, key: &str) -> crate::Result<Option<String>> {
+        let config: KafkaSinkConfig = toml::from_str(config).unwrap();
+        let client_config = config.to_rdkafka(KafkaRole::Producer)?;
+        Ok(client_config.get(key).map(Into::into))
+    }
+
+    #[test]
+    fn applies_delivery_mode() {
+        let base = r#"
+            bootstrap_servers = "localhost:9092"
+            topic = "logs"
+            encoding.codec = "json"
+        "#;
+
+        assert_eq!(producer_option(base, "enable.idempotence").unwrap(), None);
+
+        let idempotent = format!("{}delivery_mode = \"idempotent\"", base);
+        assert_eq!(
+            producer_option(&idempotent, "enable.idempotence").unwrap(),
+            Some("true".into())
+        );
+
+        let transactional = format!(
+            "{}delivery_mode = \"transactional\"\ntransaction.transactional_id = \"vector-1\"",
+            base
+        );
+        assert_eq!(
+            producer_option(&transactional, "transactional.id").unwrap(),
+            Some("vector-1".into())
+        );
+        assert_eq!(
+            producer_option(&transactional, "transaction.timeout.ms").unwrap(),
+            Some("60000".into())
+        );
+
+        let missing_transaction = format!("{}delivery_mode = \"transactional\"", base);
+        assert!(producer_option(&missing_transaction, "transactional.id").is_err());
+
+        let unused_transaction = format!("{}transaction.transactional_id = \"vector-1\"", base);
+        assert!(producer_option(&unused_transaction, "transactional.id").is_err());
+    }
 }
@@ -1,6 +1,7 @@
 use crate::config::SinkDescription;
 
 pub(crate) mod config;
+pub(crate) mod partitioner;
 pub(crate) mod request_builder;
 pub(crate) mod service;
 pub(crate) mod sink;
@@ -0,0 +1,221 @@
+//! Choosing the partition of each record, instead of leaving it to librdkafka.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use rand::{thread_rng, Rng};
+use rdkafka::producer::{FutureProducer, Producer};
+use serde::{Deserialize, Serialize};
+
+use crate::kafka::KafkaStatisticsContext;
+
+/// How long the partition count of a topic is trusted before it is fetched again, so that
+/// partitions added to a topic start receiving records.
+const PARTITION_COUNT_TTL: Duration = Duration::from_secs(300);
+
+/// How long a topic whose partitions couldn't be looked up is left to librdkafka before trying
+/// again.
+const FAILED_LOOKUP_BACKOFF: Duration = Duration::from_secs(10);
+
+const METADATA_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The partition count of a topic, or `None` if it couldn't be looked up, and when it was fetched.
+type PartitionCount = Arc<tokio::sync::Mutex<Option<(Option<i32>, Instant)>>>;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KafkaPartitioner {
+    /// Hashes the partition key the same way as the Java client's default partitioner, so that
+    /// records land on the same partitions as those produced by Java clients with the same key.
+    Murmur2,
+    /// Picks a partition at random for each record.
+    Random,
+    /// Cycles through the partitions of the topic.
+    RoundRobin,
+}
+
+impl KafkaPartitioner {
+    /// Chooses one of `partition_count` partitions, or `None` to leave the choice to librdkafka,
+    /// which is the case for `murmur2` when there is no key to hash.
+    fn choose(self, key: Option<&[u8]>, partition_count: i32, next: &AtomicUsize) -> Option<i32> {
+        let partition_count = partition_count as u32;
+        let partition = match self {
+            Self::Murmur2 => (murmur2(key?) & 0x7fff_ffff) % partition_count,
+            Self::Random => thread_rng().gen_range(0..partition_count),
+            Self::RoundRobin => (next.fetch_add(1, Ordering::Relaxed) as u32) % partition_count,
+        };
+        Some(partition as i32)
+    }
+}
+
+/// The 32 bit MurmurHash2 as implemented by `org.apache.kafka.common.utils.Utils.murmur2`.
+fn murmur2(data: &[u8]) -> u32 {
+    const SEED: u32 = 0x9747_b28c;
+    const M: u32 = 0x5bd1_e995;
+    const R: u32 = 24;
+
+    let mut h = SEED ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (index, byte) in tail.iter().enumerate() {
+            h ^= u32::from(*byte) << (8 * index);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+    h
+}
+
+/// Picks the partitions of records, keeping track of how many partitions each topic has.
+pub struct PartitionSelector {
+    partitioner: KafkaPartitioner,
+    partition_counts: Mutex<HashMap<String, PartitionCount>>,
+    next: AtomicUsize,
+}
+
+impl PartitionSelector {
+    pub fn new(partitioner: KafkaPartitioner) -> Self {
+        Self {
+            partitioner,
+            partition_counts: Mutex::new(HashMap::new()),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Chooses the partition of a record for `topic`, or `None` to leave the choice to
+    /// librdkafka, which is also the case while the topic's partitions can't be looked up.
+    pub async fn partition(
+        &self,
+        producer: &FutureProducer<KafkaStatisticsContext>,
+        topic: &str,
+        key: Option<&[u8]>,
+    ) -> Option<i32> {
+        if self.partitioner == KafkaPartitioner::Murmur2 && key.is_none() {
+            return None;
+        }
+
+        let partition_count = self.partition_count(producer, topic).await?;
+        self.partitioner.choose(key, partition_count, &self.next)
+    }
+
+    async fn partition_count(
+        &self,
+        producer: &FutureProducer<KafkaStatisticsContext>,
+        topic: &str,
+    ) -> Option<i32> {
+        let partition_count = Arc::clone(
+            self.partition_counts
+                .lock()
+                .unwrap()
+                .entry(topic.to_owned())
+                .or_default(),
+        );
+        // Holding the lock of the topic while fetching its metadata makes concurrent lookups
+        // wait for a single fetch.
+        let mut partition_count = partition_count.lock().await;
+        if let Some((count, fetched_at)) = *partition_count {
+            let ttl = match count {
+                Some(_) => PARTITION_COUNT_TTL,
+                None => FAILED_LOOKUP_BACKOFF,
+            };
+            if fetched_at.elapsed() < ttl {
+                return count;
+            }
+        }
+
+        let producer = producer.clone();
+        let topic_name = topic.to_owned();
+        let metadata = tokio::task::spawn_blocking(move || {
+            producer
+                .client()
+                .fetch_metadata(Some(&topic_name), METADATA_TIMEOUT)
+        })
+        .await
+        .map_err(crate::Error::from)
+        .and_then(|result| result.map_err(Into::into));
+
+        let count = match metadata {
+            Ok(metadata) => metadata
+                .topics()
+                .iter()
+                .find(|metadata| metadata.name() == topic && metadata.error().is_none())
+                .map(|metadata| metadata.partitions().len() as i32)
+                .filter(|count| *count > 0),
+            Err(error) => {
+                warn!(
+                    message = "Failed to fetch topic metadata, leaving partitioning to librdkafka.",
+                    %topic,
+                    %error,
+                    internal_log_rate_secs = 10,
+                );
+                None
+            }
+        };
+
+        *partition_count = Some((count, Instant::now()));
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn murmur2_matches_java_client() {
+        // Test vectors from the Java client's `UtilsTest.testMurmur2`.
+        let cases: [(&[u8], i32); 6] = [
+            (b"21", -973932308),
+            (b"foobar", -790332482),
+            (b"a-little-bit-long-string", -985981536),
+            (b"a-little-bit-longer-string", -1486304829),
+            (
+                b"lkjh234lh9fiuh90y23oiuhsafujhadof229phr9h19h89h8",
+                -58897971,
+            ),
+            (b"abc", 479470107),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(murmur2(data) as i32, expected);
+        }
+    }
+
+    #[test]
+    fn chooses_partitions() {
+        let next = AtomicUsize::new(0);
+
+        // 0x7fff_ffff & -790332482 is 1357151166.
+        let partition = KafkaPartitioner::Murmur2.choose(Some(b"foobar"), 12, &next);
+        assert_eq!(partition, Some(1357151166 % 12));
+        assert_eq!(KafkaPartitioner::Murmur2.choose(None, 12, &next), None);
+
+        let partitions = (0..4)
+            .map(|_| KafkaPartitioner::RoundRobin.choose(None, 3, &next))
+            .collect::<Vec<_>>();
+        assert_eq!(partitions, vec![Some(0), Some(1), Some(2), Some(0)]);
+
+        for _ in 0..100 {
+            let partition = KafkaPartitioner::Random.choose(None, 3, &next).unwrap();
+            assert!((0..3).contains(&partition));
+        }
+    }
+}
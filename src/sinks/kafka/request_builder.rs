@@ -6,7 +6,7 @@ use vector_core::{config::LogSchema, ByteSizeOf};
 use crate::{
     codecs::Encoder,
     event::{Event, Finalizable, Value},
-    internal_events::KafkaHeaderExtractionError,
+    internal_events::{KafkaHeaderExtractionError, TemplateRenderingError},
     sinks::{
         kafka::service::{KafkaRequest, KafkaRequestMetadata},
         util::encoding::Transformer,
@@ -16,7 +16,9 @@ use crate::{
 
 pub struct KafkaRequestBuilder {
     pub key_field: Option<String>,
+    pub partition_key_field: Option<String>,
     pub headers_key: Option<String>,
+    pub headers: Vec<(String, Template)>,
     pub topic_template: Template,
     pub transformer: Transformer,
     pub encoder: Encoder<()>,
@@ -29,8 +31,9 @@ impl KafkaRequestBuilder {
         let metadata = KafkaRequestMetadata {
             finalizers: event.take_finalizers(),
             key: get_key(&event, &self.key_field),
+            partition_key: get_key(&event, &self.partition_key_field),
             timestamp_millis: get_timestamp_millis(&event, self.log_schema),
-            headers: get_headers(&event, &self.headers_key),
+            headers: get_headers(&event, &self.headers, &self.headers_key),
             topic,
         };
         let event_byte_size = event.size_of();
@@ -71,23 +74,49 @@ fn get_timestamp_millis(event: &Event, log_schema: &'static LogSchema) -> Option
     .map(|ts| ts.timestamp_millis())
 }
 
-fn get_headers(event: &Event, headers_key: &Option<String>) -> Option<OwnedHeaders> {
-    headers_key.as_ref().and_then(|headers_key| {
+fn get_headers(
+    event: &Event,
+    headers: &[(String, Template)],
+    headers_key: &Option<String>,
+) -> Option<OwnedHeaders> {
+    let mut owned_headers = None;
+
+    for (key, template) in headers {
+        match template.render(event) {
+            Ok(value) => {
+                owned_headers = Some(
+                    owned_headers
+                        .unwrap_or_else(|| OwnedHeaders::new_with_capacity(headers.len()))
+                        .add(key, value.as_ref()),
+                );
+            }
+            Err(error) => {
+                emit!(TemplateRenderingError {
+                    error,
+                    field: Some("headers"),
+                    drop_event: false,
+                });
+            }
+        }
+    }
+
+    if let Some(headers_key) = headers_key {
         if let Event::Log(log) = event {
             if let Some(headers) = log.get(headers_key.as_str()) {
                 match headers {
                     Value::Object(headers_map) => {
-                        let mut owned_headers = OwnedHeaders::new_with_capacity(headers_map.len());
+                        let mut field_headers = owned_headers
+                            .unwrap_or_else(|| OwnedHeaders::new_with_capacity(headers_map.len()));
                         for (key, value) in headers_map {
                             if let Value::Bytes(value_bytes) = value {
-                                owned_headers = owned_headers.add(key, value_bytes.as_ref());
+                                field_headers = field_headers.add(key, value_bytes.as_ref());
                             } else {
                                 emit!(KafkaHeaderExtractionError {
                                     header_field: headers_key
                                 });
                             }
                         }
-                        return Some(owned_headers);
+                        owned_headers = Some(field_headers);
                     }
                     _ => {
                         emit!(KafkaHeaderExtractionError {
@@ -97,13 +126,14 @@ fn get_headers(event: &Event, headers_key: &Option<String>) -> Option<OwnedHeade
                 }
             }
         }
-        None
-    })
+    }
+
+    owned_headers
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeMap;
+    use std::{collections::BTreeMap, convert::TryFrom};
 
     use bytes::Bytes;
     use rdkafka::message::Headers;
@@ -120,10 +150,39 @@ mod tests {
         let mut event = Event::from("hello");
         event.as_mut_log().insert(headers_key, header_values);
 
-        let headers = get_headers(&event, &Some(headers_key.to_string())).unwrap();
+        let headers = get_headers(&event, &[], &Some(headers_key.to_string())).unwrap();
         assert_eq!(headers.get(0).unwrap().0, "a-key");
         assert_eq!(headers.get(0).unwrap().1, "a-value".as_bytes());
         assert_eq!(headers.get(1).unwrap().0, "b-key");
         assert_eq!(headers.get(1).unwrap().1, "b-value".as_bytes());
     }
+
+    #[test]
+    fn kafka_get_templated_headers() {
+        let templates = vec![
+            (
+                "service".to_string(),
+                Template::try_from("{{ service }}").unwrap(),
+            ),
+            (
+                "missing".to_string(),
+                Template::try_from("{{ missing }}").unwrap(),
+            ),
+            ("static".to_string(), Template::try_from("vector").unwrap()),
+        ];
+        let mut header_values = BTreeMap::new();
+        header_values.insert("a-key".to_string(), Value::Bytes(Bytes::from("a-value")));
+
+        let mut event = Event::from("hello");
+        event.as_mut_log().insert("service", "billing");
+        event.as_mut_log().insert("headers", header_values);
+
+        let headers = get_headers(&event, &templates, &Some("headers".to_string())).unwrap();
+        assert_eq!(headers.count(), 3);
+        assert_eq!(headers.get(0).unwrap(), ("service", "billing".as_bytes()));
+        assert_eq!(headers.get(1).unwrap(), ("static", "vector".as_bytes()));
+        assert_eq!(headers.get(2).unwrap(), ("a-key", "a-value".as_bytes()));
+
+        assert!(get_headers(&Event::from("hello"), &[], &None).is_none());
+    }
 }
@@ -1,11 +1,15 @@
-use std::task::{Context, Poll};
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
 
 use bytes::Bytes;
-use futures::future::BoxFuture;
+use futures::future::{self, BoxFuture};
 use rdkafka::{
     error::KafkaError,
     message::OwnedHeaders,
-    producer::{FutureProducer, FutureRecord},
+    producer::{FutureProducer, FutureRecord, Producer},
     util::Timeout,
 };
 use tower::Service;
@@ -15,8 +19,10 @@ use vector_core::{
     stream::DriverResponse,
 };
 
+use super::partitioner::PartitionSelector;
 use crate::{
     event::{EventFinalizers, EventStatus, Finalizable},
+    internal_events::KafkaTransactionError,
     kafka::KafkaStatisticsContext,
 };
 
@@ -29,12 +35,14 @@ pub struct KafkaRequest {
 pub struct KafkaRequestMetadata {
     pub finalizers: EventFinalizers,
     pub key: Option<Bytes>,
+    pub partition_key: Option<Bytes>,
     pub timestamp_millis: Option<i64>,
     pub headers: Option<OwnedHeaders>,
     pub topic: String,
 }
 
 pub struct KafkaResponse {
+    event_count: usize,
     event_byte_size: usize,
 }
 
@@ -45,7 +53,7 @@ impl DriverResponse for KafkaResponse {
 
     fn events_sent(&self) -> EventsSent {
         EventsSent {
-            count: 1,
+            count: self.event_count,
             byte_size: self.event_byte_size,
             output: None,
         }
@@ -65,15 +73,89 @@ impl Finalizable for KafkaRequest {
     }
 }
 
+/// The records written in one Kafka transaction.
+pub struct KafkaTransactionRequest {
+    pub requests: Vec<KafkaRequest>,
+}
+
+impl Ackable for KafkaTransactionRequest {
+    fn ack_size(&self) -> usize {
+        self.requests.len()
+    }
+}
+
+impl Finalizable for KafkaTransactionRequest {
+    fn take_finalizers(&mut self) -> EventFinalizers {
+        self.requests
+            .iter_mut()
+            .fold(EventFinalizers::default(), |mut finalizers, request| {
+                finalizers.merge(request.take_finalizers());
+                finalizers
+            })
+    }
+}
+
+async fn produce(
+    kafka_producer: &FutureProducer<KafkaStatisticsContext>,
+    partitions: Option<&PartitionSelector>,
+    request: KafkaRequest,
+) -> Result<usize, KafkaError> {
+    let mut record = FutureRecord::to(&request.metadata.topic).payload(request.body.as_ref());
+    if let Some(key) = &request.metadata.key {
+        record = record.key(&key[..]);
+    }
+    if let Some(timestamp) = request.metadata.timestamp_millis {
+        record = record.timestamp(timestamp);
+    }
+    if let Some(headers) = request.metadata.headers {
+        record = record.headers(headers);
+    }
+    if let Some(partitions) = partitions {
+        let partition_key = request
+            .metadata
+            .partition_key
+            .as_ref()
+            .or_else(|| request.metadata.key.as_ref());
+        let partition = partitions
+            .partition(
+                kafka_producer,
+                &request.metadata.topic,
+                partition_key.map(|key| &key[..]),
+            )
+            .await;
+        if let Some(partition) = partition {
+            record = record.partition(partition);
+        }
+    }
+
+    //rdkafka will internally retry forever if the queue is full
+    match kafka_producer.send(record, Timeout::Never).await {
+        Ok((_partition, _offset)) => {
+            let byte_size = request.body.len() + request.metadata.key.map(|x| x.len()).unwrap_or(0);
+            emit!(BytesSent {
+                byte_size,
+                protocol: "kafka"
+            });
+            Ok(byte_size)
+        }
+        Err((kafka_err, _original_record)) => Err(kafka_err),
+    }
+}
+
 pub struct KafkaService {
     kafka_producer: FutureProducer<KafkaStatisticsContext>,
+    partitions: Option<Arc<PartitionSelector>>,
 }
 
 impl KafkaService {
     pub(crate) const fn new(
         kafka_producer: FutureProducer<KafkaStatisticsContext>,
+        partitions: Option<Arc<PartitionSelector>>,
     ) -> KafkaService {
-        KafkaService { kafka_producer }
+        KafkaService {
+            kafka_producer,
+            partitions,
+        }
     }
 }
 
@@ -88,35 +170,156 @@ impl Service<KafkaRequest> for KafkaService {
 
     fn call(&mut self, request: KafkaRequest) -> Self::Future {
         let kafka_producer = self.kafka_producer.clone();
+        let partitions = self.partitions.clone();
 
         Box::pin(async move {
-            let mut record =
-                FutureRecord::to(&request.metadata.topic).payload(request.body.as_ref());
-            if let Some(key) = &request.metadata.key {
-                record = record.key(&key[..]);
-            }
-            if let Some(timestamp) = request.metadata.timestamp_millis {
-                record = record.timestamp(timestamp);
-            }
-            if let Some(headers) = request.metadata.headers {
-                record = record.headers(headers);
-            }
+            let event_byte_size = request.event_byte_size;
+            produce(&kafka_producer, partitions.as_deref(), request).await?;
+            Ok(KafkaResponse {
+                event_count: 1,
+                event_byte_size,
+            })
+        })
+    }
+}
+
+/// How many times a commit that timed out is retried before the transaction is aborted.
+const MAX_COMMIT_RETRIES: usize = 3;
+
+/// Writes each request in its own transaction, which the producer allows only one of at a time,
+/// so this must not be called again before the previous call completes.
+pub struct KafkaTransactionService {
+    kafka_producer: FutureProducer<KafkaStatisticsContext>,
+    partitions: Option<Arc<PartitionSelector>>,
+    timeout: Duration,
+    /// The fatal error the producer ran into, after which it can't write transactions anymore,
+    /// such as being fenced by another producer with the same `transactional.id`.
+    fatal_error: Arc<Mutex<Option<KafkaError>>>,
+}
+
+impl KafkaTransactionService {
+    pub(crate) const fn new(
+        kafka_producer: FutureProducer<KafkaStatisticsContext>,
+        partitions: Option<Arc<PartitionSelector>>,
+        timeout: Duration,
+    ) -> KafkaTransactionService {
+        KafkaTransactionService {
+            kafka_producer,
+            partitions,
+            timeout,
+            fatal_error: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl Service<KafkaTransactionRequest> for KafkaTransactionService {
+    type Response = KafkaResponse;
+    type Error = KafkaError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    // The sink stops once the producer ran into a fatal error.
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.fatal_error.lock().unwrap().clone() {
+            Some(error) => Poll::Ready(Err(error)),
+            None => Poll::Ready(Ok(())),
+        }
+    }
 
-            //rdkafka will internally retry forever if the queue is full
-            let result = match kafka_producer.send(record, Timeout::Never).await {
-                Ok((_partition, _offset)) => {
-                    emit!(BytesSent {
-                        byte_size: request.body.len()
-                            + request.metadata.key.map(|x| x.len()).unwrap_or(0),
-                        protocol: "kafka"
-                    });
-                    Ok(KafkaResponse {
-                        event_byte_size: request.event_byte_size,
+    fn call(&mut self, request: KafkaTransactionRequest) -> Self::Future {
+        let kafka_producer = self.kafka_producer.clone();
+        let partitions = self.partitions.clone();
+        let timeout = self.timeout;
+        let fatal_error = Arc::clone(&self.fatal_error);
+
+        Box::pin(async move {
+            let event_count = request.requests.len();
+            let event_byte_size = request
+                .requests
+                .iter()
+                .map(|request| request.event_byte_size)
+                .sum();
+
+            kafka_producer.begin_transaction().map_err(|error| {
+                emit!(KafkaTransactionError {
+                    error: &error,
+                    operation: "begin",
+                });
+                record_fatal(&fatal_error, &error);
+                error
+            })?;
+
+            let produced = future::try_join_all(
+                request
+                    .requests
+                    .into_iter()
+                    .map(|request| produce(&kafka_producer, partitions.as_deref(), request)),
+            )
+            .await;
+
+            // Committing and aborting block until the brokers respond.
+            tokio::task::spawn_blocking(move || {
+                produced
+                    .and_then(|_| commit_transaction(&kafka_producer, timeout))
+                    .map_err(|error| {
+                        // A producer in a fatal state can't abort its transaction either.
+                        if record_fatal(&fatal_error, &error) {
+                            return error;
+                        }
+                        if let Err(error) = kafka_producer.abort_transaction(timeout) {
+                            emit!(KafkaTransactionError {
+                                error: &error,
+                                operation: "abort",
+                            });
+                            record_fatal(&fatal_error, &error);
+                        }
+                        error
                     })
-                }
-                Err((kafka_err, _original_record)) => Err(kafka_err),
-            };
-            result
+            })
+            .await
+            .unwrap_or(Err(KafkaError::Canceled))?;
+
+            Ok(KafkaResponse {
+                event_count,
+                event_byte_size,
+            })
         })
     }
 }
+
+/// Keeps the error if it's fatal, returning whether it is.
+fn record_fatal(fatal_error: &Mutex<Option<KafkaError>>, error: &KafkaError) -> bool {
+    let fatal = matches!(error, KafkaError::Transaction(error) if error.is_fatal());
+    if fatal {
+        fatal_error
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| error.clone());
+    }
+    fatal
+}
+
+fn commit_transaction(
+    kafka_producer: &FutureProducer<KafkaStatisticsContext>,
+    timeout: Duration,
+) -> Result<(), KafkaError> {
+    let mut retries = 0;
+    loop {
+        match kafka_producer.commit_transaction(timeout) {
+            Ok(()) => return Ok(()),
+            // The commit timed out, which is retried as librdkafka recommends, until the
+            // transaction is given up on and aborted.
+            Err(KafkaError::Transaction(error))
+                if error.is_retriable() && retries < MAX_COMMIT_RETRIES =>
+            {
+                retries += 1;
+            }
+            Err(error) => {
+                emit!(KafkaTransactionError {
+                    error: &error,
+                    operation: "commit",
+                });
+                return Err(error);
+            }
+        }
+    }
+}
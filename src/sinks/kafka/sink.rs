@@ -1,27 +1,32 @@
-use std::convert::TryFrom;
+use std::{convert::TryFrom, sync::Arc};
 
 use async_trait::async_trait;
 use futures::{future, stream::BoxStream, StreamExt};
 use rdkafka::{
     consumer::{BaseConsumer, Consumer},
     error::KafkaError,
-    producer::FutureProducer,
+    producer::{FutureProducer, Producer},
     ClientConfig,
 };
 use snafu::{ResultExt, Snafu};
 use tokio::time::Duration;
 use tower::limit::ConcurrencyLimit;
-use vector_core::{buffers::Acker, config::log_schema};
+use vector_core::{buffers::Acker, config::log_schema, stream::BatcherSettings};
 
 use super::config::{KafkaRole, KafkaSinkConfig};
 use crate::{
     codecs::Encoder,
     event::Event,
+    internal_events::KafkaTransactionError,
     kafka::KafkaStatisticsContext,
     sinks::{
         kafka::{
-            config::QUEUED_MIN_MESSAGES, request_builder::KafkaRequestBuilder,
-            service::KafkaService,
+            config::QUEUED_MIN_MESSAGES,
+            partitioner::{KafkaPartitioner, PartitionSelector},
+            request_builder::KafkaRequestBuilder,
+            service::{
+                KafkaRequest, KafkaService, KafkaTransactionRequest, KafkaTransactionService,
+            },
         },
         util::{builder::SinkBuilderExt, encoding::Transformer, StreamSink},
     },
@@ -40,10 +45,14 @@ pub struct KafkaSink {
     transformer: Transformer,
     encoder: Encoder<()>,
     acker: Acker,
-    service: KafkaService,
+    producer: FutureProducer<KafkaStatisticsContext>,
+    partitions: Option<Arc<PartitionSelector>>,
+    transaction: Option<(BatcherSettings, Duration)>,
     topic: Template,
     key_field: Option<String>,
+    partition_key_field: Option<String>,
     headers_key: Option<String>,
+    headers: Vec<(String, Template)>,
 }
 
 pub(crate) fn create_producer(
@@ -63,32 +72,95 @@ impl KafkaSink {
         let serializer = config.encoding.encoding();
         let encoder = Encoder::<()>::new(serializer);
 
+        let partitioner = config.partitioner.or_else(|| {
+            config
+                .partition_key_field
+                .as_ref()
+                .map(|_| KafkaPartitioner::Murmur2)
+        });
+        let transaction = config
+            .transaction
+            .as_ref()
+            .map(|transaction| {
+                transaction
+                    .batch_settings()
+                    .map(|settings| (settings, Duration::from_millis(transaction.timeout_ms)))
+            })
+            .transpose()?;
+        // Sorted so that records get their headers in the same order every time.
+        let mut headers = config.headers.into_iter().collect::<Vec<_>>();
+        headers.sort_by(|a, b| a.0.cmp(&b.0));
+
         Ok(KafkaSink {
             headers_key: config.headers_key,
+            headers,
             transformer,
             encoder,
             acker,
-            service: KafkaService::new(producer),
+            producer,
+            partitions: partitioner
+                .map(|partitioner| Arc::new(PartitionSelector::new(partitioner))),
+            transaction,
             topic: Template::try_from(config.topic).context(TopicTemplateSnafu)?,
             key_field: config.key_field,
+            partition_key_field: config.partition_key_field,
         })
     }
 
     async fn run_inner(self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
-        // rdkafka will internally retry forever, so we need some limit to prevent this from overflowing
-        let service = ConcurrencyLimit::new(self.service, QUEUED_MIN_MESSAGES as usize);
         let mut request_builder = KafkaRequestBuilder {
             key_field: self.key_field,
+            partition_key_field: self.partition_key_field,
             headers_key: self.headers_key,
+            headers: self.headers,
             topic_template: self.topic,
             transformer: self.transformer,
             encoder: self.encoder,
             log_schema: log_schema(),
         };
-        let sink = input
-            .filter_map(|event| future::ready(request_builder.build_request(event)))
-            .into_driver(service, self.acker);
-        sink.run().await
+        let requests =
+            input.filter_map(|event| future::ready(request_builder.build_request(event)));
+
+        match self.transaction {
+            None => {
+                // rdkafka will internally retry forever, so we need some limit to prevent this from overflowing
+                let service = ConcurrencyLimit::new(
+                    KafkaService::new(self.producer, self.partitions),
+                    QUEUED_MIN_MESSAGES as usize,
+                );
+                requests.into_driver(service, self.acker).run().await
+            }
+            Some((batch_settings, timeout)) => {
+                let producer = self.producer.clone();
+                tokio::task::spawn_blocking(move || producer.init_transactions(timeout))
+                    .await
+                    .unwrap_or(Err(KafkaError::Canceled))
+                    .map_err(|error| {
+                        emit!(KafkaTransactionError {
+                            error: &error,
+                            operation: "init",
+                        });
+                    })?;
+
+                // A producer has at most one transaction open at a time, and every batch is
+                // committed before its events are acknowledged.
+                let service = ConcurrencyLimit::new(
+                    KafkaTransactionService::new(self.producer, self.partitions, timeout),
+                    1,
+                );
+                requests
+                    .batched(
+                        batch_settings.into_item_size_config(|request: &KafkaRequest| {
+                            request.body.len()
+                                + request.metadata.key.as_ref().map_or(0, |key| key.len())
+                        }),
+                    )
+                    .map(|requests| KafkaTransactionRequest { requests })
+                    .into_driver(service, self.acker)
+                    .run()
+                    .await
+            }
+        }
     }
 }
 
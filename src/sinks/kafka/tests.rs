@@ -5,6 +5,7 @@
 mod integration_test {
     use std::{
         collections::{BTreeMap, HashMap},
+        convert::TryFrom,
         future::ready,
         thread,
         time::Duration,
@@ -27,7 +28,8 @@ mod integration_test {
         kafka::{KafkaAuthConfig, KafkaCompression, KafkaSaslConfig, KafkaTlsConfig},
         sinks::{
             kafka::{
-                config::{KafkaRole, KafkaSinkConfig},
+                config::{DeliveryMode, KafkaRole, KafkaSinkConfig, TransactionConfig},
+                partitioner::KafkaPartitioner,
                 sink::KafkaSink,
                 *,
             },
@@ -37,6 +39,7 @@ mod integration_test {
             },
             VectorSink,
         },
+        template::Template,
         test_util::{components, random_lines_with_stream, random_string, wait_for},
         tls::TlsConfig,
     };
@@ -59,6 +62,8 @@ mod integration_test {
             bootstrap_servers: kafka_address(9091),
             topic: topic.clone(),
             key_field: None,
+            partition_key_field: None,
+            partitioner: None,
             encoding: EncodingConfig::from(StandardEncodings::Text).into(),
             batch: BatchConfig::default(),
             compression: KafkaCompression::None,
@@ -67,6 +72,9 @@ mod integration_test {
             message_timeout_ms: 300000,
             librdkafka_options: HashMap::new(),
             headers_key: None,
+            headers: HashMap::new(),
+            delivery_mode: Default::default(),
+            transaction: None,
             acknowledgements: Default::default(),
         };
         self::sink::healthcheck(config).await.unwrap();
@@ -113,6 +121,8 @@ mod integration_test {
             compression: KafkaCompression::None,
             encoding: EncodingConfig::from(StandardEncodings::Text).into(),
             key_field: None,
+            partition_key_field: None,
+            partitioner: None,
             auth: KafkaAuthConfig {
                 sasl: None,
                 tls: None,
@@ -122,6 +132,9 @@ mod integration_test {
             batch,
             librdkafka_options,
             headers_key: None,
+            headers: HashMap::new(),
+            delivery_mode: Default::default(),
+            transaction: None,
             acknowledgements: Default::default(),
         };
         let (acker, _ack_counter) = Acker::basic();
@@ -244,6 +257,97 @@ mod integration_test {
         .await;
     }
 
+    #[tokio::test]
+    async fn kafka_transactional_with_header_templates() {
+        crate::test_util::trace_init();
+
+        let server = kafka_address(9091);
+        let topic = format!("test-{}", random_string(10));
+        let config = KafkaSinkConfig {
+            bootstrap_servers: server.clone(),
+            topic: topic.clone(),
+            key_field: None,
+            partition_key_field: Some("message".to_string()),
+            partitioner: Some(KafkaPartitioner::Murmur2),
+            encoding: EncodingConfig::from(StandardEncodings::Text).into(),
+            batch: BatchConfig::default(),
+            compression: KafkaCompression::None,
+            auth: KafkaAuthConfig::default(),
+            socket_timeout_ms: 60000,
+            message_timeout_ms: 300000,
+            librdkafka_options: HashMap::new(),
+            headers_key: None,
+            headers: vec![(
+                "sink".to_string(),
+                Template::try_from("{{ sink }}").unwrap(),
+            )]
+            .into_iter()
+            .collect(),
+            delivery_mode: DeliveryMode::Transactional,
+            transaction: Some(TransactionConfig {
+                transactional_id: format!("vector-{}", random_string(10)),
+                timeout_ms: 60000,
+                max_events: 100,
+                max_bytes: 10_000_000,
+                commit_interval_secs: 1.0,
+            }),
+            acknowledgements: Default::default(),
+        };
+        let (acker, ack_counter) = Acker::basic();
+        let sink = KafkaSink::new(config, acker).unwrap();
+        let sink = VectorSink::from_event_streamsink(sink);
+
+        let num_events = 250;
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let (input, events) = random_lines_with_stream(100, num_events, Some(batch));
+        let input_events = events.map(|mut events| {
+            events.for_each_log(|log| {
+                log.insert("sink", "kafka");
+            });
+            events
+        });
+        sink.run(input_events).await.unwrap();
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+        assert_eq!(
+            ack_counter.load(std::sync::atomic::Ordering::Relaxed),
+            num_events
+        );
+
+        // only committed records are visible to this consumer
+        let mut client_config = rdkafka::ClientConfig::new();
+        client_config.set("bootstrap.servers", server.as_str());
+        client_config.set("group.id", &random_string(10));
+        client_config.set("isolation.level", "read_committed");
+
+        let mut tpl = TopicPartitionList::new();
+        tpl.add_partition(&topic, 0)
+            .set_offset(Offset::Beginning)
+            .unwrap();
+
+        let consumer: BaseConsumer = client_config.create().unwrap();
+        consumer.assign(&tpl).unwrap();
+
+        let mut failures = 0;
+        let mut out = Vec::new();
+        while failures < 100 && out.len() < input.len() {
+            match consumer.poll(Duration::from_secs(3)) {
+                Some(Ok(msg)) => {
+                    let s: &str = msg.payload_view().unwrap().unwrap();
+                    out.push(s.to_owned());
+                    let (header_key, header_val) = msg.headers().unwrap().get(0).unwrap();
+                    assert_eq!(header_key, "sink");
+                    assert_eq!(header_val, "kafka".as_bytes());
+                }
+                _ => {
+                    failures += 1;
+                    thread::sleep(Duration::from_millis(50));
+                }
+            }
+        }
+
+        assert_eq!(out, input);
+    }
+
     async fn kafka_happy_path(
         server: String,
         sasl: Option<KafkaSaslConfig>,
@@ -257,6 +361,8 @@ mod integration_test {
             bootstrap_servers: server.clone(),
             topic: format!("{}-%Y%m%d", topic),
             key_field: None,
+            partition_key_field: None,
+            partitioner: None,
             encoding: EncodingConfig::from(StandardEncodings::Text).into(),
             batch: BatchConfig::default(),
             compression,
@@ -265,6 +371,9 @@ mod integration_test {
             message_timeout_ms: 300000,
             librdkafka_options: HashMap::new(),
             headers_key: Some(headers_key.clone()),
+            headers: HashMap::new(),
+            delivery_mode: Default::default(),
+            transaction: None,
             acknowledgements: Default::default(),
         };
         let topic = format!("{}-{}", topic, chrono::Utc::now().format("%Y%m%d"));
@@ -1,16 +1,24 @@
 use std::collections::HashMap;
 
-use futures::future::FutureExt;
+use futures::future::{self, FutureExt};
 use serde::{Deserialize, Serialize};
+use tower::ServiceBuilder;
 
-use super::{healthcheck::healthcheck, sink::LokiSink};
+use super::{
+    healthcheck::healthcheck,
+    service::{LokiRetryLogic, LokiService},
+    sink::LokiSink,
+};
 use crate::sinks::util::Compression;
 use crate::{
     config::{AcknowledgementsConfig, GenerateConfig, Input, SinkConfig, SinkContext},
     http::{Auth, HttpClient, MaybeAuth},
     sinks::{
         util::{
-            encoding::EncodingConfig, BatchConfig, SinkBatchSettings, TowerRequestConfig, UriSerde,
+            encoding::EncodingConfig,
+            service::{resolve_endpoints, DistributionConfig},
+            BatchConfig, ServiceBuilderExt, SinkBatchSettings, TowerRequestConfig,
+            TowerRequestSettings, UriSerde,
         },
         VectorSink,
     },
@@ -21,7 +29,15 @@ use crate::{
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct LokiConfig {
+    #[serde(default)]
     pub endpoint: UriSerde,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<UriSerde>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub distribution: DistributionConfig,
     pub encoding: EncodingConfig<Encoding>,
 
     pub tenant_id: Option<Template>,
@@ -33,8 +49,14 @@ pub struct LokiConfig {
     pub remove_timestamp: bool,
     #[serde(default)]
     pub compression: Compression,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    pub payload_format: PayloadFormat,
     #[serde(default)]
     pub out_of_order_action: OutOfOrderAction,
+    pub label_cardinality_limit: Option<LabelCardinalityLimit>,
 
     pub auth: Option<Auth>,
 
@@ -73,6 +95,31 @@ pub enum OutOfOrderAction {
     Accept,
 }
 
+#[derive(Copy, Clone, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    /// JSON, compressed as set by `compression`.
+    #[derivative(Default)]
+    Json,
+    /// Protocol Buffers, which Loki requires to be compressed with Snappy.
+    Protobuf,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LabelCardinalityLimit {
+    /// The maximum number of distinct label sets, that is streams, each tenant may use within
+    /// `window_secs`.
+    pub max_label_sets: usize,
+    #[serde(default = "default_label_cardinality_window_secs")]
+    pub window_secs: u64,
+}
+
+const fn default_label_cardinality_window_secs() -> u64 {
+    3600
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum Encoding {
@@ -98,6 +145,41 @@ impl LokiConfig {
         let client = HttpClient::new(tls, cx.proxy())?;
         Ok(client)
     }
+
+    fn request_limits(&self) -> TowerRequestSettings {
+        // if Vector is configured to allow events with out of order timestamps, then then we can
+        // safely enable concurrency settings.
+        //
+        // For rewritten timestamps, we use a static concurrency of 1 to avoid out-of-order
+        // timestamps across requests. We used to support concurrency across partitions (Loki
+        // streams) but this was lost in #9506. Rather than try to re-add it, since Loki no longer
+        // requires in-order processing for version >= 2.4, instead we just keep the static limit
+        // of 1 for now.
+        match self.out_of_order_action {
+            OutOfOrderAction::Accept => self.request.unwrap_with(&Default::default()),
+            OutOfOrderAction::Drop | OutOfOrderAction::RewriteTimestamp => {
+                let mut settings = self.request.unwrap_with(&Default::default());
+                settings.concurrency = Some(1);
+                settings
+            }
+        }
+    }
+
+    /// One copy of this config for each of its endpoints, with the authentication to use for it.
+    pub(super) fn endpoint_configs(&self) -> crate::Result<Vec<LokiConfig>> {
+        let endpoint = Some(&self.endpoint).filter(|endpoint| !endpoint.is_unset());
+        resolve_endpoints("endpoint", endpoint, &self.endpoints)?
+            .into_iter()
+            .map(|endpoint| {
+                Ok(LokiConfig {
+                    auth: self.auth.choose_one(&endpoint.auth)?,
+                    endpoint,
+                    endpoints: Vec::new(),
+                    ..self.clone()
+                })
+            })
+            .collect()
+    }
 }
 
 #[async_trait::async_trait]
@@ -117,18 +199,62 @@ impl SinkConfig for LokiConfig {
             }
         }
 
+        match self.payload_format {
+            PayloadFormat::Json => self.compression.ensure_gzip_or_none()?,
+            PayloadFormat::Protobuf => {
+                if !matches!(self.compression, Compression::None | Compression::Snappy) {
+                    return Err(
+                        "Protobuf payloads are always compressed with Snappy, `compression` must be \"none\" or \"snappy\"."
+                            .into(),
+                    );
+                }
+            }
+        }
+
+        if self
+            .label_cardinality_limit
+            .map_or(false, |limit| limit.max_label_sets == 0)
+        {
+            return Err("`label_cardinality_limit.max_label_sets` must be greater than 0.".into());
+        }
+
         let client = self.build_client(cx.clone())?;
 
-        let config = LokiConfig {
-            auth: self.auth.choose_one(&self.endpoint.auth)?,
-            ..self.clone()
+        let configs = self.endpoint_configs()?;
+
+        let request_limits = self.request_limits();
+        let mut services = configs
+            .iter()
+            .map(|config| {
+                let name = config.endpoint.uri.to_string();
+                let service =
+                    LokiService::new(client.clone(), config.endpoint.clone(), config.auth.clone())?;
+                Ok((name, service))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let sink = if services.len() == 1 {
+            let (_, service) = services.swap_remove(0);
+            let service = ServiceBuilder::new()
+                .settings(request_limits, LokiRetryLogic)
+                .service(service);
+            VectorSink::from_event_streamsink(LokiSink::new(self.clone(), service, cx)?)
+        } else {
+            let service =
+                request_limits.distributed_service(LokiRetryLogic, services, self.distribution);
+            VectorSink::from_event_streamsink(LokiSink::new(self.clone(), service, cx)?)
         };
 
-        let sink = LokiSink::new(config.clone(), client.clone(), cx)?;
-
-        let healthcheck = healthcheck(config, client).boxed();
+        // The sink is healthy as long as any of its endpoints is.
+        let healthcheck = future::select_ok(
+            configs
+                .into_iter()
+                .map(|config| healthcheck(config, client.clone()).boxed()),
+        )
+        .map(|result| result.map(|_| ()))
+        .boxed();
 
-        Ok((VectorSink::from_event_streamsink(sink), healthcheck))
+        Ok((sink, healthcheck))
     }
 
     fn input(&self) -> Input {
@@ -1,17 +1,32 @@
-use std::{collections::HashMap, io};
+use std::{collections::HashMap, fmt::Write as _, io};
 
+use prost::Message;
 use serde::{ser::SerializeSeq, Serialize};
 use vector_core::{
     event::{EventFinalizers, Finalizable},
     ByteSizeOf,
 };
 
+use super::config::PayloadFormat;
 use crate::sinks::util::encoding::Encoder;
 
+mod proto {
+    include!(concat!(env!("OUT_DIR"), "/logproto.rs"));
+}
+
 pub type Labels = Vec<(String, String)>;
 
 #[derive(Clone, Default)]
-pub struct LokiBatchEncoder;
+pub struct LokiBatchEncoder(pub PayloadFormat);
+
+impl LokiBatchEncoder {
+    pub const fn content_type(&self) -> &'static str {
+        match self.0 {
+            PayloadFormat::Json => "application/json",
+            PayloadFormat::Protobuf => "application/x-protobuf",
+        }
+    }
+}
 
 impl Encoder<Vec<LokiRecord>> for LokiBatchEncoder {
     fn encode_input(
@@ -20,8 +35,22 @@ impl Encoder<Vec<LokiRecord>> for LokiBatchEncoder {
         writer: &mut dyn io::Write,
     ) -> io::Result<usize> {
         let batch = LokiBatch::from(input);
-        let body = serde_json::json!({ "streams": [batch] });
-        let body = serde_json::to_vec(&body)?;
+        let body = match self.0 {
+            PayloadFormat::Json => {
+                let body = serde_json::json!({ "streams": [batch] });
+                serde_json::to_vec(&body)?
+            }
+            PayloadFormat::Protobuf => {
+                let body = proto::PushRequest {
+                    streams: vec![batch.into()],
+                }
+                .encode_to_vec();
+                // Loki expects the raw Snappy block format here, not the framed one.
+                snap::raw::Encoder::new()
+                    .compress_vec(&body)
+                    .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?
+            }
+        };
         writer.write(&body)
     }
 }
@@ -49,6 +78,57 @@ impl From<Vec<LokiRecord>> for LokiBatch {
     }
 }
 
+impl From<LokiBatch> for proto::StreamAdapter {
+    fn from(batch: LokiBatch) -> Self {
+        Self {
+            labels: format_labels(&batch.stream),
+            entries: batch
+                .values
+                .into_iter()
+                .map(|event| proto::EntryAdapter {
+                    timestamp: Some(proto::Timestamp {
+                        seconds: event.timestamp.div_euclid(1_000_000_000),
+                        nanos: event.timestamp.rem_euclid(1_000_000_000) as i32,
+                    }),
+                    line: event.event,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Formats labels the way Loki parses them in protobuf pushes, `{name="value", ...}`, sorted by
+/// name and with the values quoted as Go strings.
+fn format_labels(labels: &HashMap<String, String>) -> String {
+    let mut labels = labels.iter().collect::<Vec<_>>();
+    labels.sort();
+
+    let mut formatted = String::from("{");
+    for (index, (name, value)) in labels.into_iter().enumerate() {
+        if index > 0 {
+            formatted.push_str(", ");
+        }
+        formatted.push_str(name);
+        formatted.push_str("=\"");
+        for ch in value.chars() {
+            match ch {
+                '\\' => formatted.push_str("\\\\"),
+                '"' => formatted.push_str("\\\""),
+                '\n' => formatted.push_str("\\n"),
+                '\r' => formatted.push_str("\\r"),
+                '\t' => formatted.push_str("\\t"),
+                ch if ch.is_control() => {
+                    let _ = write!(formatted, "\\u{:04x}", ch as u32);
+                }
+                ch => formatted.push(ch),
+            }
+        }
+        formatted.push('"');
+    }
+    formatted.push('}');
+    formatted
+}
+
 #[derive(Clone, Debug)]
 pub struct LokiEvent {
     pub timestamp: i64,
@@ -127,3 +207,75 @@ impl PartitionKey {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_labels_as_loki_parses_them() {
+        let labels = vec![
+            ("job".to_owned(), "vector".to_owned()),
+            ("app".to_owned(), "say \"hi\"\n\\o/".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            format_labels(&labels),
+            r#"{app="say \"hi\"\n\\o/", job="vector"}"#
+        );
+        assert_eq!(format_labels(&HashMap::new()), "{}");
+    }
+
+    #[test]
+    fn encodes_protobuf_with_snappy() {
+        let records = [(2_000_000_001, "second"), (-1, "first")]
+            .into_iter()
+            .map(|(timestamp, line)| {
+                let mut labels = vec![("job".to_owned(), "vector".to_owned())];
+                LokiRecord {
+                    partition: PartitionKey::new(None, &mut labels),
+                    labels,
+                    event: LokiEvent {
+                        timestamp,
+                        event: line.to_owned(),
+                    },
+                    finalizers: Default::default(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut body = Vec::new();
+        LokiBatchEncoder(PayloadFormat::Protobuf)
+            .encode_input(records, &mut body)
+            .unwrap();
+
+        let body = snap::raw::Decoder::new().decompress_vec(&body).unwrap();
+        let request = proto::PushRequest::decode(body.as_slice()).unwrap();
+        assert_eq!(
+            request,
+            proto::PushRequest {
+                streams: vec![proto::StreamAdapter {
+                    labels: r#"{job="vector"}"#.to_owned(),
+                    entries: vec![
+                        proto::EntryAdapter {
+                            timestamp: Some(proto::Timestamp {
+                                seconds: -1,
+                                nanos: 999_999_999,
+                            }),
+                            line: "first".to_owned(),
+                        },
+                        proto::EntryAdapter {
+                            timestamp: Some(proto::Timestamp {
+                                seconds: 2,
+                                nanos: 1,
+                            }),
+                            line: "second".to_owned(),
+                        },
+                    ],
+                }],
+            }
+        );
+    }
+}
@@ -69,6 +69,7 @@ impl DriverResponse for LokiResponse {
 #[derive(Clone)]
 pub struct LokiRequest {
     pub compression: Compression,
+    pub content_type: &'static str,
     pub batch_size: usize,
     pub finalizers: EventFinalizers,
     pub payload: Bytes,
@@ -113,7 +114,7 @@ impl Service<LokiRequest> for LokiService {
 
     fn call(&mut self, request: LokiRequest) -> Self::Future {
         let mut req =
-            http::Request::post(&self.endpoint.uri).header("Content-Type", "application/json");
+            http::Request::post(&self.endpoint.uri).header("Content-Type", request.content_type);
 
         if let Some(tenant_id) = request.tenant_id {
             req = req.header("X-Scope-OrgID", tenant_id);
@@ -1,36 +1,40 @@
-use std::{collections::HashMap, num::NonZeroUsize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+};
 
 use bytes::Bytes;
 use futures::{stream::BoxStream, StreamExt};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use snafu::Snafu;
+use tower::Service;
 use vector_common::encode_logfmt;
 use vector_core::{
     buffers::Acker,
-    event::{self, Event, EventFinalizers, Finalizable, Value},
+    event::{self, Event, EventFinalizers, Finalizable, LogEvent, Value},
     partition::Partitioner,
     sink::StreamSink,
-    stream::BatcherSettings,
+    stream::{BatcherSettings, DriverResponse},
     ByteSizeOf,
 };
 
 use super::{
-    config::{Encoding, LokiConfig, OutOfOrderAction},
-    event::{LokiBatchEncoder, LokiEvent, LokiRecord, PartitionKey},
-    service::{LokiRequest, LokiRetryLogic, LokiService},
+    config::{Encoding, LabelCardinalityLimit, LokiConfig, OutOfOrderAction, PayloadFormat},
+    event::{Labels, LokiBatchEncoder, LokiEvent, LokiRecord, PartitionKey},
+    service::LokiRequest,
 };
 use crate::{
     config::{log_schema, SinkContext},
-    http::HttpClient,
     internal_events::{
-        LokiEventUnlabeled, LokiOutOfOrderEventDropped, LokiOutOfOrderEventRewritten,
-        TemplateRenderingError,
+        LokiEventUnlabeled, LokiLabelSetLimited, LokiOutOfOrderEventDropped,
+        LokiOutOfOrderEventRewritten, TemplateRenderingError,
     },
     sinks::util::{
         builder::SinkBuilderExt,
         encoding::{EncodingConfig, EncodingConfiguration},
-        service::{ServiceBuilderExt, Svc},
         Compression, RequestBuilder,
     },
     template::Template,
@@ -138,6 +142,7 @@ impl RequestBuilder<(PartitionKey, Vec<LokiRecord>)> for LokiRequestBuilder {
 
         LokiRequest {
             compression,
+            content_type: self.encoder.content_type(),
             batch_size,
             finalizers,
             payload,
@@ -154,6 +159,7 @@ pub(super) struct EventEncoder {
     labels: HashMap<Template, Template>,
     remove_label_fields: bool,
     remove_timestamp: bool,
+    label_limiter: Option<LabelSetLimiter>,
 }
 
 impl EventEncoder {
@@ -198,12 +204,57 @@ impl EventEncoder {
         }
     }
 
-    pub(super) fn encode_event(&self, mut event: Event) -> LokiRecord {
+    /// Whether the label is rendered from static templates, so that it can't add to the number
+    /// of label sets.
+    fn is_static_label(&self, name: &str) -> bool {
+        self.labels.iter().any(|(key, value)| {
+            !key.is_dynamic()
+                && !value.is_dynamic()
+                && !key.get_ref().ends_with('*')
+                && key.get_ref() == name
+        })
+    }
+
+    pub(super) fn encode_event(&mut self, mut event: Event) -> LokiRecord {
         let tenant_id = self.key_partitioner.partition(&event);
         let finalizers = event.take_finalizers();
         let mut labels = self.build_labels(&event);
         self.remove_label_fields(&mut event);
 
+        // If no labels are provided we set our own default
+        // `{agent="vector"}` label. This can happen if the only
+        // label is a templatable one but the event doesn't match.
+        let unlabeled = labels.is_empty();
+        if unlabeled {
+            emit!(LokiEventUnlabeled);
+            labels = default_labels();
+        }
+
+        let mut partition = PartitionKey::new(tenant_id, &mut labels);
+
+        // Events whose label set would take their tenant over the limit keep only their static
+        // labels, the others are moved into the log line so that they can still be queried.
+        let mut moved_labels = Labels::new();
+        let over_limit = !unlabeled
+            && self
+                .label_limiter
+                .as_mut()
+                .map_or(false, |limiter| !limiter.admit(&partition));
+        if over_limit {
+            emit!(LokiLabelSetLimited {
+                tenant_id: partition.tenant_id.as_deref()
+            });
+            let (static_labels, dynamic_labels): (Labels, Labels) = labels
+                .into_iter()
+                .partition(|(name, _)| self.is_static_label(name));
+            labels = static_labels;
+            if labels.is_empty() {
+                labels = default_labels();
+            }
+            moved_labels = dynamic_labels;
+            partition = PartitionKey::new(partition.tenant_id, &mut labels);
+        }
+
         let schema = log_schema();
         let timestamp_key = schema.timestamp_key();
         let timestamp = match event.as_log().get(timestamp_key) {
@@ -215,6 +266,13 @@ impl EventEncoder {
             event.as_mut_log().remove(timestamp_key);
         }
 
+        // The moved labels are fields of the log line, so the encoding rules apply to them too.
+        let mut moved_labels = moved_labels.into_iter().collect::<LogEvent>();
+        self.encoding.apply_rules(&mut moved_labels);
+        let mut moved_labels = BTreeMap::from(moved_labels);
+        if self.encoding.codec() != &Encoding::Text {
+            event.as_mut_log().extend(std::mem::take(&mut moved_labels));
+        }
         self.encoding.apply_rules(&mut event);
         let log = event.into_log();
         let event = match &self.encoding.codec() {
@@ -222,26 +280,25 @@ impl EventEncoder {
                 serde_json::to_string(&log).expect("json encoding should never fail.")
             }
 
-            Encoding::Text => log
-                .get(schema.message_key())
-                .map(Value::to_string_lossy)
-                .unwrap_or_default(),
+            Encoding::Text => {
+                let message = log
+                    .get(schema.message_key())
+                    .map(Value::to_string_lossy)
+                    .unwrap_or_default();
+                if moved_labels.is_empty() {
+                    message
+                } else {
+                    let moved_labels = encode_logfmt::to_string(&moved_labels)
+                        .expect("Logfmt encoding should never fail.");
+                    format!("{} {}", moved_labels, message)
+                }
+            }
 
             Encoding::Logfmt => {
                 encode_logfmt::to_string(log.as_map()).expect("Logfmt encoding should never fail.")
             }
         };
 
-        // If no labels are provided we set our own default
-        // `{agent="vector"}` label. This can happen if the only
-        // label is a templatable one but the event doesn't match.
-        if labels.is_empty() {
-            emit!(LokiEventUnlabeled);
-            labels = vec![("agent".to_string(), "vector".to_string())]
-        }
-
-        let partition = PartitionKey::new(tenant_id, &mut labels);
-
         LokiRecord {
             labels,
             event: LokiEvent { timestamp, event },
@@ -251,6 +308,101 @@ impl EventEncoder {
     }
 }
 
+fn default_labels() -> Labels {
+    vec![("agent".to_string(), "vector".to_string())]
+}
+
+/// Limits the number of distinct label sets each tenant uses within a sliding window, as every
+/// label set is a stream that Loki has to index.
+#[derive(Clone)]
+struct LabelSetLimiter {
+    max_label_sets: usize,
+    window: Duration,
+    tenants: HashMap<Option<String>, TenantLabelSets>,
+    /// Tenants that have been idle for the whole window are dropped from then on.
+    next_sweep: Option<Instant>,
+}
+
+#[derive(Clone, Default)]
+struct TenantLabelSets {
+    last_seen: HashMap<PartitionKey, Instant>,
+    /// No label set falls out of the window before this, so the expired ones are only looked for
+    /// from then on.
+    next_expiry: Option<Instant>,
+}
+
+impl LabelSetLimiter {
+    fn new(limit: LabelCardinalityLimit) -> Self {
+        Self {
+            max_label_sets: limit.max_label_sets,
+            window: Duration::from_secs(limit.window_secs),
+            tenants: HashMap::new(),
+            next_sweep: None,
+        }
+    }
+
+    /// Records the label set as seen, returning `false` if it's new and its tenant has already
+    /// used up its label sets.
+    fn admit(&mut self, partition: &PartitionKey) -> bool {
+        self.admit_at(partition, Instant::now())
+    }
+
+    fn admit_at(&mut self, partition: &PartitionKey, now: Instant) -> bool {
+        if self.next_sweep.map_or(true, |sweep| sweep <= now) {
+            self.evict_idle_tenants(now);
+            self.next_sweep = Some(now + self.window);
+        }
+
+        if !self.tenants.contains_key(&partition.tenant_id) {
+            self.tenants
+                .insert(partition.tenant_id.clone(), TenantLabelSets::default());
+        }
+        let tenant = self
+            .tenants
+            .get_mut(&partition.tenant_id)
+            .expect("inserted above");
+
+        if let Some(last_seen) = tenant.last_seen.get_mut(partition) {
+            *last_seen = now;
+            return true;
+        }
+
+        let window = self.window;
+        if tenant.last_seen.len() >= self.max_label_sets
+            && tenant.next_expiry.map_or(false, |expiry| expiry <= now)
+        {
+            tenant
+                .last_seen
+                .retain(|_, last_seen| now.duration_since(*last_seen) < window);
+            tenant.next_expiry = tenant
+                .last_seen
+                .values()
+                .min()
+                .map(|last_seen| *last_seen + window);
+        }
+
+        if tenant.last_seen.len() < self.max_label_sets {
+            tenant.last_seen.insert(partition.clone(), now);
+            tenant.next_expiry.get_or_insert(now + window);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Drops the tenants none of whose label sets have been seen within the window, so that
+    /// short-lived tenants don't accumulate.
+    fn evict_idle_tenants(&mut self, now: Instant) {
+        let window = self.window;
+        self.tenants.retain(|_, tenant| {
+            tenant
+                .last_seen
+                .values()
+                .any(|last_seen| now.duration_since(*last_seen) < window)
+        });
+    }
+}
+
 struct FilteredRecord {
     pub rewritten: bool,
     pub inner: LokiRecord,
@@ -324,46 +476,35 @@ impl RecordFilter {
     }
 }
 
-pub struct LokiSink {
+pub struct LokiSink<S> {
     acker: Acker,
     request_builder: LokiRequestBuilder,
     pub(super) encoder: EventEncoder,
     batch_settings: BatcherSettings,
     out_of_order_action: OutOfOrderAction,
-    service: Svc<LokiService, LokiRetryLogic>,
+    service: S,
 }
 
-impl LokiSink {
+impl<S> LokiSink<S>
+where
+    S: Service<LokiRequest> + Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: DriverResponse + Send + 'static,
+    S::Error: fmt::Debug + Into<crate::Error> + Send,
+{
     #[allow(clippy::missing_const_for_fn)] // const cannot run destructor
-    pub fn new(config: LokiConfig, client: HttpClient, cx: SinkContext) -> crate::Result<Self> {
-        let compression = config.compression;
-
-        // if Vector is configured to allow events with out of order timestamps, then then we can
-        // safely enable concurrency settings.
-        //
-        // For rewritten timestamps, we use a static concurrency of 1 to avoid out-of-order
-        // timestamps across requests. We used to support concurrency across partitions (Loki
-        // streams) but this was lost in #9506. Rather than try to re-add it, since Loki no longer
-        // requires in-order processing for version >= 2.4, instead we just keep the static limit
-        // of 1 for now.
-        let request_limits = match config.out_of_order_action {
-            OutOfOrderAction::Accept => config.request.unwrap_with(&Default::default()),
-            OutOfOrderAction::Drop | OutOfOrderAction::RewriteTimestamp => {
-                let mut settings = config.request.unwrap_with(&Default::default());
-                settings.concurrency = Some(1);
-                settings
-            }
+    pub fn new(config: LokiConfig, service: S, cx: SinkContext) -> crate::Result<Self> {
+        // Protobuf payloads are compressed by the encoder, as Loki requires them to be.
+        let compression = match config.payload_format {
+            PayloadFormat::Json => config.compression,
+            PayloadFormat::Protobuf => Compression::None,
         };
 
-        let service = tower::ServiceBuilder::new()
-            .settings(request_limits, LokiRetryLogic)
-            .service(LokiService::new(client, config.endpoint, config.auth)?);
-
         Ok(Self {
             acker: cx.acker(),
             request_builder: LokiRequestBuilder {
                 compression,
-                encoder: Default::default(),
+                encoder: LokiBatchEncoder(config.payload_format),
             },
             encoder: EventEncoder {
                 key_partitioner: KeyPartitioner::new(config.tenant_id),
@@ -371,6 +512,7 @@ impl LokiSink {
                 labels: config.labels,
                 remove_label_fields: config.remove_label_fields,
                 remove_timestamp: config.remove_timestamp,
+                label_limiter: config.label_cardinality_limit.map(LabelSetLimiter::new),
             },
             batch_settings: config.batch.into_batcher_settings()?,
             out_of_order_action: config.out_of_order_action,
@@ -379,7 +521,7 @@ impl LokiSink {
     }
 
     async fn run_inner(self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
-        let encoder = self.encoder.clone();
+        let mut encoder = self.encoder.clone();
         let mut filter = RecordFilter::new(self.out_of_order_action);
 
         // out_of_order_action's that require a complete ordering are limited to building 1 request
@@ -434,7 +576,13 @@ impl LokiSink {
 }
 
 #[async_trait::async_trait]
-impl StreamSink<Event> for LokiSink {
+impl<S> StreamSink<Event> for LokiSink<S>
+where
+    S: Service<LokiRequest> + Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: DriverResponse + Send + 'static,
+    S::Error: fmt::Debug + Into<crate::Error> + Send,
+{
     async fn run(mut self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
         self.run_inner(input).await
     }
@@ -452,16 +600,20 @@ mod tests {
     use std::{
         collections::{BTreeMap, HashMap},
         convert::TryFrom,
+        time::{Duration, Instant},
     };
 
     use futures::stream::StreamExt;
     use vector_core::event::{Event, Value};
 
-    use super::{EventEncoder, KeyPartitioner, RecordFilter};
+    use super::{EventEncoder, KeyPartitioner, LabelSetLimiter, RecordFilter};
     use crate::{
         config::log_schema,
         sinks::{
-            loki::config::{Encoding, OutOfOrderAction},
+            loki::{
+                config::{Encoding, LabelCardinalityLimit, OutOfOrderAction},
+                event::PartitionKey,
+            },
             util::encoding::EncodingConfig,
         },
         template::Template,
@@ -470,12 +622,13 @@ mod tests {
 
     #[test]
     fn encoder_no_labels() {
-        let encoder = EventEncoder {
+        let mut encoder = EventEncoder {
             key_partitioner: KeyPartitioner::new(None),
             encoding: EncodingConfig::from(Encoding::Json),
             labels: HashMap::default(),
             remove_label_fields: false,
             remove_timestamp: false,
+            label_limiter: None,
         };
         let mut event = Event::from("hello world");
         let log = event.as_mut_log();
@@ -508,12 +661,13 @@ mod tests {
             Template::try_from("going_to_fail_*").unwrap(),
             Template::try_from("{{ value }}").unwrap(),
         );
-        let encoder = EventEncoder {
+        let mut encoder = EventEncoder {
             key_partitioner: KeyPartitioner::new(None),
             encoding: EncodingConfig::from(Encoding::Json),
             labels,
             remove_label_fields: false,
             remove_timestamp: false,
+            label_limiter: None,
         };
         let mut event = Event::from("hello world");
         let log = event.as_mut_log();
@@ -539,12 +693,13 @@ mod tests {
 
     #[test]
     fn encoder_no_ts() {
-        let encoder = EventEncoder {
+        let mut encoder = EventEncoder {
             key_partitioner: KeyPartitioner::new(None),
             encoding: EncodingConfig::from(Encoding::Json),
             labels: HashMap::default(),
             remove_label_fields: false,
             remove_timestamp: true,
+            label_limiter: None,
         };
         let mut event = Event::from("hello world");
         let log = event.as_mut_log();
@@ -564,12 +719,13 @@ mod tests {
             Template::try_from("{{ name }}").unwrap(),
             Template::try_from("{{ value }}").unwrap(),
         );
-        let encoder = EventEncoder {
+        let mut encoder = EventEncoder {
             key_partitioner: KeyPartitioner::new(None),
             encoding: EncodingConfig::from(Encoding::Json),
             labels,
             remove_label_fields: true,
             remove_timestamp: false,
+            label_limiter: None,
         };
         let mut event = Event::from("hello world");
         let log = event.as_mut_log();
@@ -582,12 +738,13 @@ mod tests {
 
     #[tokio::test]
     async fn filter_encoder_drop() {
-        let encoder = EventEncoder {
+        let mut encoder = EventEncoder {
             key_partitioner: KeyPartitioner::new(None),
             encoding: EncodingConfig::from(Encoding::Json),
             labels: HashMap::default(),
             remove_label_fields: false,
             remove_timestamp: false,
+            label_limiter: None,
         };
         let base = chrono::Utc::now();
         let events = random_lines(100)
@@ -619,4 +776,55 @@ mod tests {
         }
         assert_eq!(result.len(), 17);
     }
+
+    #[test]
+    fn limiter_expires_label_sets_outside_window() {
+        let mut limiter = LabelSetLimiter::new(LabelCardinalityLimit {
+            max_label_sets: 2,
+            window_secs: 60,
+        });
+        let key = |tenant: &str, value: &str| {
+            PartitionKey::new(
+                Some(tenant.to_owned()),
+                &mut vec![("host".to_owned(), value.to_owned())],
+            )
+        };
+        let start = Instant::now();
+
+        assert!(limiter.admit_at(&key("a", "1"), start));
+        assert!(limiter.admit_at(&key("a", "2"), start + Duration::from_secs(30)));
+        assert!(!limiter.admit_at(&key("a", "3"), start + Duration::from_secs(40)));
+        // Label sets are limited per tenant.
+        assert!(limiter.admit_at(&key("b", "3"), start + Duration::from_secs(40)));
+        // Seen label sets are always admitted, which keeps them in the window.
+        assert!(limiter.admit_at(&key("a", "1"), start + Duration::from_secs(50)));
+        assert!(!limiter.admit_at(&key("a", "3"), start + Duration::from_secs(80)));
+        // Only the second label set has fallen out of the window by now.
+        assert!(limiter.admit_at(&key("a", "3"), start + Duration::from_secs(90)));
+        assert!(!limiter.admit_at(&key("a", "2"), start + Duration::from_secs(100)));
+    }
+
+    #[test]
+    fn limiter_evicts_idle_tenants() {
+        let mut limiter = LabelSetLimiter::new(LabelCardinalityLimit {
+            max_label_sets: 1,
+            window_secs: 60,
+        });
+        let key = |tenant: &str| {
+            PartitionKey::new(
+                Some(tenant.to_owned()),
+                &mut vec![("host".to_owned(), "1".to_owned())],
+            )
+        };
+        let start = Instant::now();
+
+        assert!(limiter.admit_at(&key("a"), start));
+        assert!(limiter.admit_at(&key("b"), start + Duration::from_secs(30)));
+        assert_eq!(limiter.tenants.len(), 2);
+
+        // Only the first tenant has been idle for the whole window by the next sweep.
+        assert!(limiter.admit_at(&key("b"), start + Duration::from_secs(70)));
+        assert_eq!(limiter.tenants.len(), 1);
+        assert!(limiter.tenants.contains_key(&Some("b".to_owned())));
+    }
 }
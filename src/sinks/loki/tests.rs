@@ -1,8 +1,8 @@
 use futures::StreamExt;
 
-use super::{config::LokiConfig, healthcheck::healthcheck, sink::LokiSink};
+use super::{config::LokiConfig, healthcheck::healthcheck, service::LokiService, sink::LokiSink};
 use crate::{
-    config::ProxyConfig,
+    config::{ProxyConfig, SinkConfig, SinkContext},
     event::Event,
     http::HttpClient,
     sinks::util::test::{build_test_server, load_sink},
@@ -10,6 +10,12 @@ use crate::{
     tls::TlsSettings,
 };
 
+fn build_sink(config: LokiConfig, cx: SinkContext) -> LokiSink<LokiService> {
+    let client = config.build_client(cx.clone()).unwrap();
+    let service = LokiService::new(client, config.endpoint.clone(), config.auth.clone()).unwrap();
+    LokiSink::new(config, service, cx).unwrap()
+}
+
 #[test]
 fn generate_config() {
     crate::test_util::test_generate_config::<LokiConfig>();
@@ -26,8 +32,7 @@ async fn interpolate_labels() {
     "#,
     )
     .unwrap();
-    let client = config.build_client(cx.clone()).unwrap();
-    let sink = LokiSink::new(config, client, cx).unwrap();
+    let mut sink = build_sink(config, cx);
 
     let mut e1 = Event::from("hello world");
 
@@ -67,8 +72,7 @@ async fn use_label_from_dropped_fields() {
         "#,
     )
     .unwrap();
-    let client = config.build_client(cx.clone()).unwrap();
-    let sink = LokiSink::new(config, client, cx).unwrap();
+    let mut sink = build_sink(config, cx);
 
     let mut e1 = Event::from("hello world");
 
@@ -86,6 +90,105 @@ async fn use_label_from_dropped_fields() {
     assert_eq!(record.labels[0], ("bar".to_string(), "bar".to_string()));
 }
 
+#[tokio::test]
+async fn moves_dynamic_labels_over_cardinality_limit_into_line() {
+    let (config, cx) = load_sink::<LokiConfig>(
+        r#"
+            endpoint = "http://localhost:3100"
+            labels = {app = "web", host = "{{ host }}"}
+            encoding = "json"
+            remove_label_fields = true
+            label_cardinality_limit.max_label_sets = 1
+        "#,
+    )
+    .unwrap();
+    let mut sink = build_sink(config, cx);
+
+    let mut encode = |host: &str| {
+        let mut event = Event::from("hello world");
+        event.as_mut_log().insert("host", host);
+        let mut record = sink.encoder.encode_event(event);
+        record.labels.sort();
+        record
+    };
+
+    let record = encode("a");
+    assert_eq!(
+        record.labels,
+        vec![
+            ("app".to_string(), "web".to_string()),
+            ("host".to_string(), "a".to_string())
+        ]
+    );
+    assert!(!record.event.event.contains("host"));
+
+    // A second host would be a second label set for the tenant.
+    let record = encode("b");
+    assert_eq!(record.labels, vec![("app".to_string(), "web".to_string())]);
+    let line: serde_json::Value = serde_json::from_str(&record.event.event).unwrap();
+    assert_eq!(line["host"], "b");
+
+    let record = encode("a");
+    assert_eq!(record.labels.len(), 2);
+}
+
+#[tokio::test]
+async fn applies_encoding_rules_to_moved_labels() {
+    let (config, cx) = load_sink::<LokiConfig>(
+        r#"
+            endpoint = "http://localhost:3100"
+            labels = {app = "web", host = "{{ host }}"}
+            encoding.codec = "json"
+            encoding.except_fields = ["host"]
+            label_cardinality_limit.max_label_sets = 1
+        "#,
+    )
+    .unwrap();
+    let mut sink = build_sink(config, cx);
+
+    let mut encode = |host: &str| {
+        let mut event = Event::from("hello world");
+        event.as_mut_log().insert("host", host);
+        sink.encoder.encode_event(event)
+    };
+
+    encode("a");
+    let record = encode("b");
+    assert_eq!(record.labels, vec![("app".to_string(), "web".to_string())]);
+    assert!(!record.event.event.contains("host"));
+}
+
+#[tokio::test]
+async fn protobuf_requires_snappy_compression() {
+    let (config, cx) = load_sink::<LokiConfig>(
+        r#"
+            endpoint = "http://localhost:3100"
+            labels = {app = "web"}
+            encoding = "json"
+            payload_format = "protobuf"
+            compression = "gzip"
+        "#,
+    )
+    .unwrap();
+
+    assert!(config.build(cx).await.is_err());
+}
+
+#[tokio::test]
+async fn json_requires_gzip_compression() {
+    let (config, cx) = load_sink::<LokiConfig>(
+        r#"
+            endpoint = "http://localhost:3100"
+            labels = {app = "web"}
+            encoding = "json"
+            compression = "zstd"
+        "#,
+    )
+    .unwrap();
+
+    assert!(config.build(cx).await.is_err());
+}
+
 #[tokio::test]
 async fn healthcheck_includes_auth() {
     let (mut config, _cx) = load_sink::<LokiConfig>(
@@ -0,0 +1,503 @@
+use std::{collections::HashMap, convert::TryFrom, time::Duration};
+
+use async_trait::async_trait;
+use bytes::BytesMut;
+use codecs::{encoding::SerializerConfig, JsonSerializerConfig, RawMessageSerializerConfig};
+use futures::{
+    future::{self, BoxFuture},
+    stream::{BoxStream, FuturesOrdered},
+    FutureExt, StreamExt,
+};
+use rumqttc::{AsyncClient, ConnectionError, Event as MqttEvent, EventLoop, Outgoing, Packet};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::codec::Encoder as _;
+use vector_buffers::Acker;
+use vector_common::internal_event::{BytesSent, EventsSent};
+use vector_core::ByteSizeOf;
+
+use crate::{
+    codecs::Encoder,
+    config::{
+        AcknowledgementsConfig, GenerateConfig, Input, SinkConfig, SinkContext, SinkDescription,
+    },
+    event::{Event, EventStatus, Finalizable},
+    internal_events::{
+        prelude::error_stage, MqttConnectionError, MqttEventSendError, TemplateRenderingError,
+    },
+    mqtt::{MqttConfigError, MqttConnectionConfig, MqttQoS},
+    sinks::util::{
+        encoding::{EncodingConfig, EncodingConfigAdapter, EncodingConfigMigrator, Transformer},
+        StreamSink,
+    },
+    template::{Template, TemplateParseError},
+};
+
+/// How long to wait before polling the event loop again after a connection error, which makes
+/// it reconnect.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// The maximum amount of published messages awaiting delivery.
+const MAX_IN_FLIGHT_MESSAGES: usize = 1024;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("invalid topic template: {}", source))]
+    TopicTemplate { source: TemplateParseError },
+    #[snafu(display("MQTT Config Error: {}", source))]
+    Config { source: MqttConfigError },
+}
+
+#[derive(Debug, Snafu)]
+enum HealthcheckError {
+    #[snafu(display("MQTT Connect Error: {}", source))]
+    Connect { source: ConnectionError },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncodingMigrator;
+
+impl EncodingConfigMigrator for EncodingMigrator {
+    type Codec = Encoding;
+
+    fn migrate(codec: &Self::Codec) -> SerializerConfig {
+        match codec {
+            Encoding::Text => RawMessageSerializerConfig::new().into(),
+            Encoding::Json => JsonSerializerConfig::new().into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MqttSinkConfig {
+    #[serde(flatten)]
+    connection: MqttConnectionConfig,
+    topic: String,
+    #[serde(default)]
+    qos: MqttQoS,
+    #[serde(default)]
+    retain: bool,
+    #[serde(flatten)]
+    encoding: EncodingConfigAdapter<EncodingConfig<Encoding>, EncodingMigrator>,
+    #[serde(
+        default,
+        deserialize_with = "crate::serde::bool_or_struct",
+        skip_serializing_if = "crate::serde::skip_serializing_if_default"
+    )]
+    acknowledgements: AcknowledgementsConfig,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    Text,
+    Json,
+}
+
+inventory::submit! {
+    SinkDescription::new::<MqttSinkConfig>("mqtt")
+}
+
+impl GenerateConfig for MqttSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            host = "localhost"
+            port = 1883
+            topic = "vector/{{ host }}"
+            encoding.codec = "json""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "mqtt")]
+impl SinkConfig for MqttSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let sink = MqttSink::new(self, cx.acker())?;
+        let healthcheck = healthcheck(self.clone()).boxed();
+        Ok((super::VectorSink::from_event_streamsink(sink), healthcheck))
+    }
+
+    fn input(&self) -> Input {
+        Input::new(self.encoding.config().input_type())
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "mqtt"
+    }
+
+    fn acknowledgements(&self) -> Option<&AcknowledgementsConfig> {
+        Some(&self.acknowledgements)
+    }
+}
+
+/// Connects with a separate client identifier, since a broker drops the existing connection of
+/// a client that connects again with the same identifier.
+async fn healthcheck(config: MqttSinkConfig) -> crate::Result<()> {
+    let client_id = format!("{}-healthcheck", config.connection.client_id);
+    let options = config.connection.options(&client_id, true)?;
+    let mut eventloop = EventLoop::new(options, 1);
+    loop {
+        if let MqttEvent::Incoming(Packet::ConnAck(_)) =
+            eventloop.poll().await.context(ConnectSnafu)?
+        {
+            return Ok(());
+        }
+    }
+}
+
+pub struct MqttSink {
+    transformer: Transformer,
+    encoder: Encoder<()>,
+    client: AsyncClient,
+    eventloop: Option<EventLoop>,
+    topic: Template,
+    qos: MqttQoS,
+    retain: bool,
+    acker: Acker,
+}
+
+impl MqttSink {
+    fn new(config: &MqttSinkConfig, acker: Acker) -> Result<Self, BuildError> {
+        let (client, eventloop) = config
+            .connection
+            .connect(true, false)
+            .context(ConfigSnafu)?;
+        let transformer = config.encoding.transformer();
+        let serializer = config.encoding.encoding();
+        let encoder = Encoder::<()>::new(serializer);
+
+        Ok(MqttSink {
+            transformer,
+            encoder,
+            client,
+            eventloop: Some(eventloop),
+            topic: Template::try_from(config.topic.as_str()).context(TopicTemplateSnafu)?,
+            qos: config.qos,
+            retain: config.retain,
+            acker,
+        })
+    }
+}
+
+/// Drives the connection until every handle to the client has been dropped.
+///
+/// Every publish is preceded by a sender in `deliveries`, which is signaled once the broker
+/// confirmed the message with the configured quality of service. The event loop sends the
+/// requests in the order they were made, so the senders are taken in that order as each message
+/// goes out, and then kept by packet identifier until the matching acknowledgement comes in.
+async fn run_eventloop(
+    mut eventloop: EventLoop,
+    qos: MqttQoS,
+    mut deliveries: mpsc::UnboundedReceiver<oneshot::Sender<()>>,
+) {
+    let mut awaiting_ack = HashMap::new();
+    loop {
+        match eventloop.poll().await {
+            Ok(MqttEvent::Outgoing(Outgoing::Publish(pkid))) => {
+                // Messages still awaiting their acknowledgement are sent again with the same
+                // packet identifier after a reconnection.
+                if awaiting_ack.contains_key(&pkid) {
+                    continue;
+                }
+                if let Ok(delivered) = deliveries.try_recv() {
+                    if qos == MqttQoS::AtMostOnce {
+                        let _ = delivered.send(());
+                    } else {
+                        awaiting_ack.insert(pkid, delivered);
+                    }
+                }
+            }
+            Ok(MqttEvent::Incoming(Packet::PubAck(ack))) if qos == MqttQoS::AtLeastOnce => {
+                if let Some(delivered) = awaiting_ack.remove(&ack.pkid) {
+                    let _ = delivered.send(());
+                }
+            }
+            Ok(MqttEvent::Incoming(Packet::PubComp(ack))) if qos == MqttQoS::ExactlyOnce => {
+                if let Some(delivered) = awaiting_ack.remove(&ack.pkid) {
+                    let _ = delivered.send(());
+                }
+            }
+            Ok(_) => {}
+            Err(ConnectionError::RequestsDone) => break,
+            Err(error) => {
+                emit!(MqttConnectionError {
+                    error,
+                    stage: error_stage::SENDING,
+                });
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+impl MqttSink {
+    /// Publishes the event, returning a future resolving once the broker confirmed it, or `None`
+    /// if the event could not be published.
+    async fn publish(
+        &mut self,
+        mut event: Event,
+        deliveries: &mpsc::UnboundedSender<oneshot::Sender<()>>,
+    ) -> Option<BoxFuture<'static, ()>> {
+        let finalizers = event.take_finalizers();
+
+        let topic = match self.topic.render_string(&event) {
+            Ok(topic) => topic,
+            Err(error) => {
+                emit!(TemplateRenderingError {
+                    error,
+                    field: Some("topic"),
+                    drop_event: true,
+                });
+                finalizers.update_status(EventStatus::Rejected);
+                return None;
+            }
+        };
+
+        self.transformer.transform(&mut event);
+
+        let event_byte_size = event.size_of();
+        let mut bytes = BytesMut::new();
+        if self.encoder.encode(event, &mut bytes).is_err() {
+            // Error is logged by `Encoder`.
+            finalizers.update_status(EventStatus::Rejected);
+            return None;
+        }
+        let byte_size = bytes.len();
+
+        // The sender is queued before the message, so that it is in place by the time the event
+        // loop sends the message. It is dropped along with the event loop if the publish fails.
+        let (delivered_tx, delivered_rx) = oneshot::channel();
+        let _ = deliveries.send(delivered_tx);
+
+        if let Err(error) = self
+            .client
+            .publish(topic, self.qos.into(), self.retain, bytes.to_vec())
+            .await
+        {
+            emit!(MqttEventSendError { error });
+            finalizers.update_status(EventStatus::Errored);
+            return None;
+        }
+
+        Some(
+            async move {
+                match delivered_rx.await {
+                    Ok(()) => {
+                        emit!(EventsSent {
+                            count: 1,
+                            byte_size: event_byte_size,
+                            output: None,
+                        });
+                        emit!(BytesSent {
+                            byte_size,
+                            protocol: "mqtt",
+                        });
+                        finalizers.update_status(EventStatus::Delivered);
+                    }
+                    // The event loop stopped before the broker confirmed the message.
+                    Err(_) => finalizers.update_status(EventStatus::Errored),
+                }
+            }
+            .boxed(),
+        )
+    }
+}
+
+#[async_trait]
+impl StreamSink<Event> for MqttSink {
+    async fn run(mut self: Box<Self>, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let mut input = input.fuse();
+        let (deliveries_tx, deliveries_rx) = mpsc::unbounded_channel();
+        let eventloop = self.eventloop.take().expect("sink is only run once");
+        let connection = tokio::spawn(run_eventloop(eventloop, self.qos, deliveries_rx));
+
+        // Messages are delivered in the order they were published, so the events are
+        // acknowledged in the order they were received.
+        let mut in_flight = FuturesOrdered::new();
+
+        loop {
+            tokio::select! {
+                biased;
+
+                Some(()) = in_flight.next(), if !in_flight.is_empty() => self.acker.ack(1),
+
+                event = input.next(), if in_flight.len() < MAX_IN_FLIGHT_MESSAGES => match event {
+                    Some(event) => match self.publish(event, &deliveries_tx).await {
+                        Some(delivered) => in_flight.push(delivered),
+                        None => in_flight.push(future::ready(()).boxed()),
+                    },
+                    None => break,
+                },
+            }
+        }
+
+        while in_flight.next().await.is_some() {
+            self.acker.ack(1);
+        }
+
+        // Dropping the client lets the event loop stop once its queue is drained.
+        drop(self);
+        let _ = connection.await;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<MqttSinkConfig>();
+    }
+
+    #[test]
+    fn parses_config() {
+        let config: MqttSinkConfig = toml::from_str(
+            r#"
+            host = "broker"
+            topic = "sensors/{{ host }}"
+            qos = 2
+            retain = true
+            encoding.codec = "text""#,
+        )
+        .unwrap();
+
+        assert_eq!(config.connection.host, "broker");
+        assert_eq!(config.connection.port, 1883);
+        assert_eq!(config.qos, MqttQoS::ExactlyOnce);
+        assert!(config.retain);
+    }
+}
+
+#[cfg(feature = "mqtt-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use rumqttc::QoS;
+
+    use super::*;
+    use crate::sinks::VectorSink;
+    use crate::test_util::{random_lines_with_stream, random_string, trace_init};
+
+    fn mqtt_host() -> String {
+        std::env::var("MQTT_HOST").unwrap_or_else(|_| "127.0.0.1".to_owned())
+    }
+
+    fn make_config(topic: &str, qos: MqttQoS, retain: bool) -> MqttSinkConfig {
+        MqttSinkConfig {
+            connection: MqttConnectionConfig {
+                host: mqtt_host(),
+                client_id: format!("vector-sink-{}", random_string(10)),
+                ..Default::default()
+            },
+            topic: topic.to_owned(),
+            qos,
+            retain,
+            encoding: EncodingConfig::from(Encoding::Text).into(),
+            acknowledgements: Default::default(),
+        }
+    }
+
+    /// Subscribes to `topic` and returns the payloads of the first `count` messages.
+    async fn subscribe(topic: &str, count: usize) -> tokio::task::JoinHandle<Vec<String>> {
+        let connection = MqttConnectionConfig {
+            host: mqtt_host(),
+            client_id: format!("vector-subscriber-{}", random_string(10)),
+            ..Default::default()
+        };
+        let (client, mut eventloop) = connection.connect(true, false).unwrap();
+        client.subscribe(topic, QoS::ExactlyOnce).await.unwrap();
+
+        // Wait for the subscription to be in place before anything gets published.
+        loop {
+            if let MqttEvent::Incoming(Packet::SubAck(_)) = eventloop.poll().await.unwrap() {
+                break;
+            }
+        }
+
+        tokio::spawn(async move {
+            let _client = client;
+            let mut payloads = Vec::new();
+            while payloads.len() < count {
+                if let MqttEvent::Incoming(Packet::Publish(publish)) =
+                    eventloop.poll().await.unwrap()
+                {
+                    payloads.push(String::from_utf8(publish.payload.to_vec()).unwrap());
+                }
+            }
+            payloads
+        })
+    }
+
+    async fn publish_and_check(qos: MqttQoS) {
+        trace_init();
+
+        let topic = format!("test-{}", random_string(10));
+        let config = make_config(&topic, qos, false);
+        healthcheck(config.clone())
+            .await
+            .expect("healthcheck failed");
+
+        let num_events = 100;
+        let received = subscribe(&topic, num_events).await;
+
+        let (acker, ack_counter) = Acker::basic();
+        let sink = MqttSink::new(&config, acker).unwrap();
+        let (input, events) = random_lines_with_stream(100, num_events, None);
+        VectorSink::from_event_streamsink(sink)
+            .run(events)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ack_counter.load(std::sync::atomic::Ordering::Relaxed),
+            num_events
+        );
+        let received = tokio::time::timeout(Duration::from_secs(10), received)
+            .await
+            .expect("timed out waiting for messages")
+            .unwrap();
+        assert_eq!(received, input);
+    }
+
+    #[tokio::test]
+    async fn mqtt_at_most_once() {
+        publish_and_check(MqttQoS::AtMostOnce).await;
+    }
+
+    #[tokio::test]
+    async fn mqtt_exactly_once() {
+        publish_and_check(MqttQoS::ExactlyOnce).await;
+    }
+
+    #[tokio::test]
+    async fn mqtt_retained_message() {
+        trace_init();
+
+        let topic = format!("test-{}", random_string(10));
+        let config = make_config(&topic, MqttQoS::AtLeastOnce, true);
+        let (acker, _) = Acker::basic();
+        let sink = MqttSink::new(&config, acker).unwrap();
+        let (input, events) = random_lines_with_stream(100, 1, None);
+        VectorSink::from_event_streamsink(sink)
+            .run(events)
+            .await
+            .unwrap();
+
+        // A subscriber arriving after the fact still gets the retained message.
+        let received = subscribe(&topic, 1).await;
+        let received = tokio::time::timeout(Duration::from_secs(10), received)
+            .await
+            .expect("timed out waiting for retained message")
+            .unwrap();
+        assert_eq!(received, input);
+    }
+}
@@ -104,6 +104,7 @@ impl SinkConfig for NewRelicConfig {
         &self,
         cx: SinkContext,
     ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        self.compression.ensure_gzip_or_none()?;
         let encoding = self.encoding.clone();
 
         let batcher_settings = self
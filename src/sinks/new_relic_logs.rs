@@ -106,6 +106,7 @@ impl SinkConfig for NewRelicLogsConfig {
         &self,
         cx: SinkContext,
     ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        self.compression.ensure_gzip_or_none()?;
         let http_conf = self.create_config()?;
         http_conf.build(cx).await
     }
@@ -160,6 +161,8 @@ impl NewRelicLogsConfig {
 
         Ok(HttpSinkConfig {
             uri: uri.into(),
+            uris: Vec::new(),
+            distribution: Default::default(),
             method: Some(HttpMethod::Post),
             auth: None,
             headers: None,
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, marker::PhantomData};
 
 use aws_sdk_s3::error::PutObjectError;
 use aws_sdk_s3::model::{ObjectCannedAcl, ServerSideEncryption, StorageClass};
@@ -111,12 +111,42 @@ impl From<S3CannedAcl> for ObjectCannedAcl {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct S3RetryLogic;
+/// Retries the S3 requests failing with `SdkError<E>` and answered with `R`, which are objects
+/// put in one go unless otherwise specified.
+#[derive(Debug)]
+pub struct S3RetryLogic<E = PutObjectError, R = S3Response> {
+    phantom: PhantomData<fn() -> (E, R)>,
+}
+
+impl S3RetryLogic {
+    pub const fn new() -> Self {
+        S3RetryLogic {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E, R> Default for S3RetryLogic<E, R> {
+    fn default() -> Self {
+        S3RetryLogic {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E, R> Clone for S3RetryLogic<E, R> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
 
-impl RetryLogic for S3RetryLogic {
-    type Error = SdkError<PutObjectError>;
-    type Response = S3Response;
+impl<E, R> RetryLogic for S3RetryLogic<E, R>
+where
+    E: std::error::Error + Send + Sync + 'static,
+    R: 'static,
+{
+    type Error = SdkError<E>;
+    type Response = R;
 
     fn is_retriable_error(&self, error: &Self::Error) -> bool {
         is_retriable_error(error)
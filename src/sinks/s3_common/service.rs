@@ -1,4 +1,7 @@
-use std::task::{Context, Poll};
+use std::{
+    collections::BTreeMap,
+    task::{Context, Poll},
+};
 
 use aws_sdk_s3::error::PutObjectError;
 use aws_sdk_s3::types::{ByteStream, SdkError};
@@ -87,6 +90,10 @@ impl S3Service {
     pub fn client(&self) -> S3Client {
         self.client.clone()
     }
+
+    pub fn region(&self) -> Option<Region> {
+        self.region.clone()
+    }
 }
 
 impl Service<S3Request> for S3Service {
@@ -111,13 +118,7 @@ impl Service<S3Request> for S3Service {
 
         let content_md5 = base64::encode(md5::Md5::digest(&request.body));
 
-        let tagging = options.tags.map(|tags| {
-            let mut tagging = url::form_urlencoded::Serializer::new(String::new());
-            for (p, v) in tags {
-                tagging.append_pair(&p, &v);
-            }
-            tagging.finish()
-        });
+        let tagging = options.tags.map(format_tags);
         let count = request.metadata.count;
         let events_byte_size = request.metadata.byte_size;
 
@@ -161,6 +162,15 @@ impl Service<S3Request> for S3Service {
     }
 }
 
+/// Formats object tags the way the `x-amz-tagging` header expects them.
+pub fn format_tags(tags: BTreeMap<String, String>) -> String {
+    let mut tagging = url::form_urlencoded::Serializer::new(String::new());
+    for (p, v) in tags {
+        tagging.append_pair(&p, &v);
+    }
+    tagging.finish()
+}
+
 fn bytes_to_bytestream(buf: Bytes) -> ByteStream {
     ByteStream::from(buf)
 }
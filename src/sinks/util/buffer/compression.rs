@@ -8,12 +8,37 @@ pub const GZIP_FAST: u32 = 1;
 pub const GZIP_DEFAULT: u32 = 6;
 pub const GZIP_BEST: u32 = 9;
 
+#[cfg(feature = "zstd")]
+pub const ZSTD_FAST: i32 = 1;
+#[cfg(feature = "zstd")]
+pub const ZSTD_DEFAULT: i32 = 3;
+// Levels above 19 need a lot more memory on the decompressing side, so they are not offered.
+#[cfg(feature = "zstd")]
+pub const ZSTD_BEST: i32 = 19;
+
+#[cfg(feature = "lz4")]
+pub const LZ4_FAST: u32 = 0;
+#[cfg(feature = "lz4")]
+pub const LZ4_DEFAULT: u32 = 0;
+// Levels above 12 are treated as 12 by liblz4.
+#[cfg(feature = "lz4")]
+pub const LZ4_BEST: u32 = 12;
+
 #[derive(Debug, Derivative, Copy, Clone, Eq, PartialEq)]
 #[derivative(Default)]
 pub enum Compression {
     #[derivative(Default)]
     None,
     Gzip(flate2::Compression),
+    #[cfg(feature = "zstd")]
+    Zstd(i32),
+    /// Snappy in its raw block format, which is what HTTP endpoints accepting
+    /// `Content-Encoding: snappy` expect. It has no compression levels.
+    #[cfg(feature = "snap")]
+    Snappy,
+    /// LZ4 in its frame format. Levels from 3 upwards use the high compression mode.
+    #[cfg(feature = "lz4")]
+    Lz4(u32),
 }
 
 impl Compression {
@@ -36,10 +61,40 @@ impl Compression {
         Compression::Gzip(flate2::Compression::new(6))
     }
 
+    #[cfg(feature = "zstd")]
+    pub const fn zstd_default() -> Compression {
+        Compression::Zstd(ZSTD_DEFAULT)
+    }
+
+    #[cfg(feature = "lz4")]
+    pub const fn lz4_default() -> Compression {
+        Compression::Lz4(LZ4_DEFAULT)
+    }
+
+    /// Rejects the algorithms other than `gzip`, for sinks sending to services that only decode
+    /// gzip compressed payloads.
+    pub fn ensure_gzip_or_none(self) -> crate::Result<()> {
+        if matches!(self, Compression::None | Compression::Gzip(_)) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Unsupported compression {}, `compression` must be \"none\" or \"gzip\".",
+                self
+            )
+            .into())
+        }
+    }
+
     pub const fn content_encoding(self) -> Option<&'static str> {
         match self {
             Self::None => None,
             Self::Gzip(_) => Some("gzip"),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(_) => Some("zstd"),
+            #[cfg(feature = "snap")]
+            Self::Snappy => Some("snappy"),
+            #[cfg(feature = "lz4")]
+            Self::Lz4(_) => Some("lz4"),
         }
     }
 
@@ -47,6 +102,12 @@ impl Compression {
         match self {
             Self::None => "log",
             Self::Gzip(_) => "log.gz",
+            #[cfg(feature = "zstd")]
+            Self::Zstd(_) => "log.zst",
+            #[cfg(feature = "snap")]
+            Self::Snappy => "log.snappy",
+            #[cfg(feature = "lz4")]
+            Self::Lz4(_) => "log.lz4",
         }
     }
 }
@@ -55,10 +116,56 @@ impl fmt::Display for Compression {
         match *self {
             Compression::None => write!(f, "none"),
             Compression::Gzip(ref level) => write!(f, "gzip({})", level.level()),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd(level) => write!(f, "zstd({})", level),
+            #[cfg(feature = "snap")]
+            Compression::Snappy => write!(f, "snappy"),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4(level) => write!(f, "lz4({})", level),
         }
     }
 }
 
+const ALGORITHMS: &[&str] = &[
+    "none",
+    "gzip",
+    #[cfg(feature = "zstd")]
+    "zstd",
+    #[cfg(feature = "snap")]
+    "snappy",
+    #[cfg(feature = "lz4")]
+    "lz4",
+];
+
+/// Parses the `level` of a compression algorithm, given the range of numeric levels it supports
+/// and the numeric levels its named levels map to.
+fn parse_level<E: de::Error>(
+    level: Value,
+    range: (i64, i64),
+    named: &[(&str, i64)],
+    expected_numeric: &'static str,
+    expected_named: &'static str,
+) -> Result<i64, E> {
+    match level {
+        Value::Number(level) => match level.as_i64() {
+            Some(value) if value >= range.0 && value <= range.1 => Ok(value),
+            Some(_) | None => Err(de::Error::invalid_value(
+                de::Unexpected::Other(&level.to_string()),
+                &expected_numeric,
+            )),
+        },
+        Value::String(level) => named
+            .iter()
+            .find(|(name, _)| *name == level)
+            .map(|(_, value)| *value)
+            .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&level), &expected_named)),
+        value => Err(de::Error::invalid_type(
+            de::Unexpected::Other(&value.to_string()),
+            &"integer or string",
+        )),
+    }
+}
+
 impl<'de> de::Deserialize<'de> for Compression {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -80,9 +187,15 @@ impl<'de> de::Deserialize<'de> for Compression {
                 match s {
                     "none" => Ok(Compression::None),
                     "gzip" => Ok(Compression::gzip_default()),
+                    #[cfg(feature = "zstd")]
+                    "zstd" => Ok(Compression::zstd_default()),
+                    #[cfg(feature = "snap")]
+                    "snappy" => Ok(Compression::Snappy),
+                    #[cfg(feature = "lz4")]
+                    "lz4" => Ok(Compression::lz4_default()),
                     _ => Err(de::Error::invalid_value(
                         de::Unexpected::Str(s),
-                        &r#""none" or "gzip""#,
+                        &r#""none", "gzip", "zstd", "snappy" or "lz4""#,
                     )),
                 }
             }
@@ -106,37 +219,8 @@ impl<'de> de::Deserialize<'de> for Compression {
                             if level.is_some() {
                                 return Err(de::Error::duplicate_field("level"));
                             }
-                            level = Some(match map.next_value::<Value>()? {
-                                Value::Number(level) => match level.as_u64() {
-                                    Some(value) if value <= 9 => {
-                                        flate2::Compression::new(value as u32)
-                                    }
-                                    Some(_) | None => {
-                                        return Err(de::Error::invalid_value(
-                                            de::Unexpected::Other(&level.to_string()),
-                                            &"0, 1, 2, 3, 4, 5, 6, 7, 8 or 9",
-                                        ))
-                                    }
-                                },
-                                Value::String(level) => match level.as_str() {
-                                    "none" => flate2::Compression::none(),
-                                    "fast" => flate2::Compression::fast(),
-                                    "default" => flate2::Compression::default(),
-                                    "best" => flate2::Compression::best(),
-                                    level => {
-                                        return Err(de::Error::invalid_value(
-                                            de::Unexpected::Str(level),
-                                            &r#""none", "fast", "best" or "default""#,
-                                        ))
-                                    }
-                                },
-                                value => {
-                                    return Err(de::Error::invalid_type(
-                                        de::Unexpected::Other(&value.to_string()),
-                                        &"integer or string",
-                                    ));
-                                }
-                            });
+                            // The valid levels depend on the algorithm, which may come later.
+                            level = Some(map.next_value::<Value>()?);
                         }
                         _ => return Err(de::Error::unknown_field(key, &["algorithm", "level"])),
                     };
@@ -147,8 +231,60 @@ impl<'de> de::Deserialize<'de> for Compression {
                         Some(_) => Err(de::Error::unknown_field("level", &[])),
                         None => Ok(Compression::None),
                     },
-                    "gzip" => Ok(Compression::Gzip(level.unwrap_or_default())),
-                    algorithm => Err(de::Error::unknown_variant(algorithm, &["none", "gzip"])),
+                    "gzip" => match level {
+                        Some(level) => parse_level(
+                            level,
+                            (GZIP_NONE.into(), GZIP_BEST.into()),
+                            &[
+                                ("none", GZIP_NONE.into()),
+                                ("fast", GZIP_FAST.into()),
+                                ("default", GZIP_DEFAULT.into()),
+                                ("best", GZIP_BEST.into()),
+                            ],
+                            "0, 1, 2, 3, 4, 5, 6, 7, 8 or 9",
+                            r#""none", "fast", "best" or "default""#,
+                        )
+                        .map(|level| Compression::Gzip(flate2::Compression::new(level as u32))),
+                        None => Ok(Compression::gzip_default()),
+                    },
+                    #[cfg(feature = "zstd")]
+                    "zstd" => match level {
+                        Some(level) => parse_level(
+                            level,
+                            (ZSTD_FAST.into(), ZSTD_BEST.into()),
+                            &[
+                                ("fast", ZSTD_FAST.into()),
+                                ("default", ZSTD_DEFAULT.into()),
+                                ("best", ZSTD_BEST.into()),
+                            ],
+                            "an integer from 1 to 19",
+                            r#""fast", "best" or "default""#,
+                        )
+                        .map(|level| Compression::Zstd(level as i32)),
+                        None => Ok(Compression::zstd_default()),
+                    },
+                    #[cfg(feature = "snap")]
+                    "snappy" => match level {
+                        Some(_) => Err(de::Error::unknown_field("level", &[])),
+                        None => Ok(Compression::Snappy),
+                    },
+                    #[cfg(feature = "lz4")]
+                    "lz4" => match level {
+                        Some(level) => parse_level(
+                            level,
+                            (LZ4_FAST.into(), LZ4_BEST.into()),
+                            &[
+                                ("fast", LZ4_FAST.into()),
+                                ("default", LZ4_DEFAULT.into()),
+                                ("best", LZ4_BEST.into()),
+                            ],
+                            "an integer from 0 to 12",
+                            r#""fast", "best" or "default""#,
+                        )
+                        .map(|level| Compression::Lz4(level as u32)),
+                        None => Ok(Compression::lz4_default()),
+                    },
+                    algorithm => Err(de::Error::unknown_variant(algorithm, ALGORITHMS)),
                 }
             }
         }
@@ -179,6 +315,22 @@ impl ser::Serialize for Compression {
                     level => map.serialize_entry("level", &level)?,
                 };
             }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd(level) => {
+                map.serialize_entry("algorithm", "zstd")?;
+                if *level != ZSTD_DEFAULT {
+                    map.serialize_entry("level", level)?;
+                }
+            }
+            #[cfg(feature = "snap")]
+            Compression::Snappy => map.serialize_entry("algorithm", "snappy")?,
+            #[cfg(feature = "lz4")]
+            Compression::Lz4(level) => {
+                map.serialize_entry("algorithm", "lz4")?;
+                if *level != LZ4_DEFAULT {
+                    map.serialize_entry("level", level)?;
+                }
+            }
         };
         map.end()
     }
@@ -189,6 +341,7 @@ mod test {
     use super::Compression;
 
     #[test]
+    #[cfg(all(feature = "zstd", feature = "snap", feature = "lz4"))]
     fn deserialization() {
         let fixtures_valid = [
             (r#""none""#, Compression::None),
@@ -205,6 +358,21 @@ mod test {
                 r#"{"algorithm": "gzip", "level": 8}"#,
                 Compression::Gzip(flate2::Compression::new(8)),
             ),
+            (r#""zstd""#, Compression::Zstd(3)),
+            (
+                r#"{"level": 19, "algorithm": "zstd"}"#,
+                Compression::Zstd(19),
+            ),
+            (
+                r#"{"algorithm": "zstd", "level": "fast"}"#,
+                Compression::Zstd(1),
+            ),
+            (r#"{"algorithm": "snappy"}"#, Compression::Snappy),
+            (r#""lz4""#, Compression::Lz4(0)),
+            (
+                r#"{"algorithm": "lz4", "level": "best"}"#,
+                Compression::Lz4(12),
+            ),
         ];
         for (sources, result) in fixtures_valid.iter() {
             let deserialized: Result<Compression, _> = serde_json::from_str(sources);
@@ -218,11 +386,11 @@ mod test {
             ),
             (
                 r#""b42""#,
-                r#"invalid value: string "b42", expected "none" or "gzip" at line 1 column 5"#,
+                r#"invalid value: string "b42", expected "none", "gzip", "zstd", "snappy" or "lz4" at line 1 column 5"#,
             ),
             (
                 r#"{"algorithm": "b42"}"#,
-                r#"unknown variant `b42`, expected `none` or `gzip` at line 1 column 20"#,
+                r#"unknown variant `b42`, expected one of `none`, `gzip`, `zstd`, `snappy`, `lz4` at line 1 column 20"#,
             ),
             (
                 r#"{"algorithm": "none", "level": "default"}"#,
@@ -244,6 +412,22 @@ mod test {
                 r#"{"algorithm": "gzip", "level": "default", "key": 42}"#,
                 r#"unknown field `key`, expected `algorithm` or `level` at line 1 column 47"#,
             ),
+            (
+                r#"{"algorithm": "zstd", "level": 22}"#,
+                r#"invalid value: 22, expected an integer from 1 to 19 at line 1 column 34"#,
+            ),
+            (
+                r#"{"algorithm": "zstd", "level": "none"}"#,
+                r#"invalid value: string "none", expected "fast", "best" or "default" at line 1 column 38"#,
+            ),
+            (
+                r#"{"algorithm": "snappy", "level": 1}"#,
+                r#"unknown field `level`, there are no fields at line 1 column 35"#,
+            ),
+            (
+                r#"{"algorithm": "lz4", "level": 13}"#,
+                r#"invalid value: 13, expected an integer from 0 to 12 at line 1 column 33"#,
+            ),
         ];
         for (source, result) in fixtures_invalid.iter() {
             let deserialized: Result<Compression, _> = serde_json::from_str(source);
@@ -251,4 +435,33 @@ mod test {
             assert_eq!(error.to_string().as_str(), *result);
         }
     }
+
+    #[test]
+    #[cfg(all(feature = "zstd", feature = "snap", feature = "lz4"))]
+    fn gzip_or_none() {
+        assert!(Compression::None.ensure_gzip_or_none().is_ok());
+        assert!(Compression::gzip_default().ensure_gzip_or_none().is_ok());
+        assert!(Compression::zstd_default().ensure_gzip_or_none().is_err());
+        assert!(Compression::Snappy.ensure_gzip_or_none().is_err());
+        assert!(Compression::lz4_default().ensure_gzip_or_none().is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "zstd", feature = "snap", feature = "lz4"))]
+    fn serialization_roundtrip() {
+        for compression in [
+            Compression::None,
+            Compression::gzip_default(),
+            Compression::Gzip(flate2::Compression::new(2)),
+            Compression::zstd_default(),
+            Compression::Zstd(10),
+            Compression::Snappy,
+            Compression::lz4_default(),
+            Compression::Lz4(9),
+        ] {
+            let serialized = serde_json::to_string(&compression).unwrap();
+            let deserialized: Compression = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(deserialized, compression);
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use std::io;
+use std::{fmt, io};
 
 use bytes::{BufMut, BytesMut};
 use flate2::write::{GzEncoder, ZlibEncoder};
@@ -9,25 +9,57 @@ enum Writer {
     Plain(bytes::buf::Writer<BytesMut>),
     Gzip(GzEncoder<bytes::buf::Writer<BytesMut>>),
     Zlib(ZlibEncoder<bytes::buf::Writer<BytesMut>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, bytes::buf::Writer<BytesMut>>),
+    // Snappy's raw format can't be streamed, so the input is buffered as-is and compressed in one
+    // go when finishing.
+    #[cfg(feature = "snap")]
+    Snappy(bytes::buf::Writer<BytesMut>),
+    #[cfg(feature = "lz4")]
+    Lz4(lz4::Encoder<bytes::buf::Writer<BytesMut>>),
 }
 
 impl Writer {
+    fn new(compression: Compression, capacity: usize) -> Self {
+        let writer = BytesMut::with_capacity(capacity).writer();
+        match compression {
+            Compression::None => Writer::Plain(writer),
+            Compression::Gzip(level) => Writer::Gzip(GzEncoder::new(writer, level)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd(level) => Writer::Zstd(
+                zstd::stream::write::Encoder::new(writer, level)
+                    .expect("zstd encoder should not fail to initialize"),
+            ),
+            #[cfg(feature = "snap")]
+            Compression::Snappy => Writer::Snappy(writer),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4(level) => Writer::Lz4(
+                lz4::EncoderBuilder::new()
+                    .level(level)
+                    .build(writer)
+                    .expect("lz4 encoder should not fail to initialize"),
+            ),
+        }
+    }
+
     pub fn get_ref(&self) -> &BytesMut {
         match self {
             Writer::Plain(inner) => inner.get_ref(),
             Writer::Gzip(inner) => inner.get_ref().get_ref(),
             Writer::Zlib(inner) => inner.get_ref().get_ref(),
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(inner) => inner.get_ref().get_ref(),
+            #[cfg(feature = "snap")]
+            Writer::Snappy(inner) => inner.get_ref(),
+            #[cfg(feature = "lz4")]
+            Writer::Lz4(inner) => inner.writer().get_ref(),
         }
     }
 }
 
 impl From<Compression> for Writer {
     fn from(compression: Compression) -> Self {
-        let writer = BytesMut::with_capacity(1_024).writer();
-        match compression {
-            Compression::None => Writer::Plain(writer),
-            Compression::Gzip(level) => Writer::Gzip(GzEncoder::new(writer, level)),
-        }
+        Writer::new(compression, 1_024)
     }
 }
 
@@ -37,6 +69,12 @@ impl io::Write for Writer {
             Writer::Plain(inner_buf) => inner_buf.write(buf),
             Writer::Gzip(writer) => writer.write(buf),
             Writer::Zlib(writer) => writer.write(buf),
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(writer) => writer.write(buf),
+            #[cfg(feature = "snap")]
+            Writer::Snappy(writer) => writer.write(buf),
+            #[cfg(feature = "lz4")]
+            Writer::Lz4(writer) => writer.write(buf),
         }
     }
 
@@ -45,18 +83,49 @@ impl io::Write for Writer {
             Writer::Plain(writer) => writer.flush(),
             Writer::Gzip(writer) => writer.flush(),
             Writer::Zlib(writer) => writer.flush(),
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(writer) => writer.flush(),
+            #[cfg(feature = "snap")]
+            Writer::Snappy(writer) => writer.flush(),
+            #[cfg(feature = "lz4")]
+            Writer::Lz4(writer) => writer.flush(),
         }
     }
 }
 
+impl fmt::Debug for Writer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Writer::Plain(_) => "Plain",
+            Writer::Gzip(_) => "Gzip",
+            Writer::Zlib(_) => "Zlib",
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(_) => "Zstd",
+            #[cfg(feature = "snap")]
+            Writer::Snappy(_) => "Snappy",
+            #[cfg(feature = "lz4")]
+            Writer::Lz4(_) => "Lz4",
+        };
+        f.debug_tuple(kind).field(self.get_ref()).finish()
+    }
+}
+
 /// Simple compressor implementation based on [`Compression`].
 ///
 /// Users can acquire a `Compressor` via [`Compressor::from`] based on the desired compression scheme.
+#[derive(Debug)]
 pub struct Compressor {
     inner: Writer,
 }
 
 impl Compressor {
+    /// Creates a compressor whose internal buffer starts out with the given capacity.
+    pub fn with_capacity(compression: Compression, capacity: usize) -> Self {
+        Self {
+            inner: Writer::new(compression, capacity),
+        }
+    }
+
     /// Creates a zlib-based compressor with the default compression level.
     pub fn zlib_default() -> Self {
         let buf = BytesMut::with_capacity(1_024);
@@ -68,10 +137,32 @@ impl Compressor {
         }
     }
 
+    /// Gets a reference to the internal buffer.
+    ///
+    /// With Snappy compression, this holds the uncompressed input until the compressor is finished.
     pub fn get_ref(&self) -> &BytesMut {
         self.inner.get_ref()
     }
 
+    /// Takes the output compressed so far, leaving the compressor to carry on with the rest of the
+    /// input, so that a payload can be sent in parts while it is being written.
+    ///
+    /// Returns `None` for Snappy and LZ4, whose output can only be taken once finished.
+    pub fn take_output(&mut self) -> Option<BytesMut> {
+        let buf = match &mut self.inner {
+            Writer::Plain(writer) => writer.get_mut(),
+            Writer::Gzip(writer) => writer.get_mut().get_mut(),
+            Writer::Zlib(writer) => writer.get_mut().get_mut(),
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(writer) => writer.get_mut().get_mut(),
+            #[cfg(feature = "snap")]
+            Writer::Snappy(_) => return None,
+            #[cfg(feature = "lz4")]
+            Writer::Lz4(_) => return None,
+        };
+        Some(buf.split())
+    }
+
     /// Consumes the compressor, returning the internal buffer used by the compressor.
     ///
     /// # Errors
@@ -83,6 +174,19 @@ impl Compressor {
             Writer::Plain(writer) => writer,
             Writer::Gzip(writer) => writer.finish()?,
             Writer::Zlib(writer) => writer.finish()?,
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(writer) => writer.finish()?,
+            #[cfg(feature = "snap")]
+            Writer::Snappy(writer) => {
+                let compressed = snap::raw::Encoder::new().compress_vec(writer.get_ref())?;
+                BytesMut::from(compressed.as_slice()).writer()
+            }
+            #[cfg(feature = "lz4")]
+            Writer::Lz4(writer) => {
+                let (writer, result) = writer.finish();
+                result?;
+                writer
+            }
         }
         .into_inner();
 
@@ -100,15 +204,20 @@ impl Compressor {
     /// Consider using `finish` if catching these scenarios is important.
     pub fn into_inner(self) -> BytesMut {
         match self.inner {
-            Writer::Plain(writer) => writer,
+            Writer::Plain(writer) => writer.into_inner(),
             Writer::Gzip(writer) => writer
                 .finish()
-                .expect("gzip writer should not fail to finish"),
+                .expect("gzip writer should not fail to finish")
+                .into_inner(),
             Writer::Zlib(writer) => writer
                 .finish()
-                .expect("zlib writer should not fail to finish"),
+                .expect("zlib writer should not fail to finish")
+                .into_inner(),
+            #[cfg(any(feature = "zstd", feature = "snap", feature = "lz4"))]
+            inner => Self { inner }
+                .finish()
+                .expect("compressor should not fail to finish"),
         }
-        .into_inner()
     }
 }
 
@@ -129,3 +238,90 @@ impl From<Compression> for Compressor {
         }
     }
 }
+
+#[cfg(all(test, feature = "zstd", feature = "snap", feature = "lz4"))]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::*;
+
+    fn decompress(compression: Compression, data: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        match compression {
+            Compression::None => output.extend_from_slice(data),
+            Compression::Gzip(_) => {
+                flate2::read::MultiGzDecoder::new(data)
+                    .read_to_end(&mut output)
+                    .unwrap();
+            }
+            Compression::Zstd(_) => output = zstd::stream::decode_all(data).unwrap(),
+            Compression::Snappy => output = snap::raw::Decoder::new().decompress_vec(data).unwrap(),
+            Compression::Lz4(_) => {
+                lz4::Decoder::new(data)
+                    .unwrap()
+                    .read_to_end(&mut output)
+                    .unwrap();
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn takes_output_while_compressing() {
+        let input = "the quick brown fox jumps over the lazy dog\n".repeat(10_000);
+
+        for compression in [
+            Compression::None,
+            Compression::gzip_default(),
+            Compression::zstd_default(),
+        ] {
+            let mut compressor = Compressor::from(compression);
+            let mut output = Vec::new();
+            for chunk in input.as_bytes().chunks(4_096) {
+                compressor.write_all(chunk).unwrap();
+                output.extend_from_slice(&compressor.take_output().unwrap());
+            }
+            output.extend_from_slice(&compressor.finish().unwrap());
+
+            assert_eq!(
+                decompress(compression, &output),
+                input.as_bytes(),
+                "{}",
+                compression
+            );
+        }
+
+        assert!(Compressor::from(Compression::Snappy)
+            .take_output()
+            .is_none());
+    }
+
+    #[test]
+    fn roundtrips_every_compression() {
+        let input = "the quick brown fox jumps over the lazy dog\n".repeat(1_000);
+
+        for compression in [
+            Compression::None,
+            Compression::gzip_default(),
+            Compression::zstd_default(),
+            Compression::Zstd(19),
+            Compression::Snappy,
+            Compression::lz4_default(),
+            Compression::Lz4(12),
+        ] {
+            let mut compressor = Compressor::from(compression);
+            compressor.write_all(input.as_bytes()).unwrap();
+            let compressed = compressor.finish().unwrap();
+
+            if compression.is_compressed() {
+                assert!(compressed.len() < input.len(), "{}", compression);
+            }
+            assert_eq!(
+                decompress(compression, &compressed),
+                input.as_bytes(),
+                "{}",
+                compression
+            );
+        }
+    }
+}
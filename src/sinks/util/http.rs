@@ -21,17 +21,32 @@ use vector_core::{buffers::Acker, ByteSizeOf};
 
 use super::{
     retries::{RetryAction, RetryLogic},
-    sink, uri, Batch, EncodedEvent, Partition, TowerBatchedSink, TowerPartitionSink,
-    TowerRequestConfig, TowerRequestSettings,
+    service::{DistributedSvc, DistributionConfig, Svc},
+    sink, uri, Batch, BatchSink, EncodedEvent, Partition, TowerPartitionSink, TowerRequestConfig,
+    TowerRequestSettings,
 };
 use crate::{
-    event::Event,
+    event::{Event, EventFinalizers},
     http::{HttpClient, HttpError},
     internal_events::EndpointBytesSent,
 };
 
+type HttpBatchSvc<B> =
+    HttpBatchService<BoxFuture<'static, crate::Result<hyper::Request<Bytes>>>, B>;
+
 pub trait HttpEventEncoder<Output> {
     fn encode_event(&mut self, event: Event) -> Option<Output>;
+
+    /// Encodes the event, given the finalizers taken from it to be updated with the status of the
+    /// request it's sent in. Encoders that leave out events they can't encode use them to reject
+    /// those events.
+    fn encode_event_with_finalizers(
+        &mut self,
+        event: Event,
+        _finalizers: &EventFinalizers,
+    ) -> Option<Output> {
+        self.encode_event(event)
+    }
 }
 
 #[async_trait::async_trait]
@@ -58,20 +73,22 @@ pub trait HttpSink: Send + Sync + 'static {
 /// this we must provide a single buffer slot. To ensure the buffer is
 /// fully flushed make sure `poll_flush` returns ready.
 #[pin_project]
-pub struct BatchedHttpSink<T, B, RL = HttpRetryLogic>
-where
+pub struct BatchedHttpSink<
+    T,
+    B,
+    RL = HttpRetryLogic,
+    S = Svc<HttpBatchSvc<<B as Batch>::Output>, RL>,
+> where
     B: Batch,
     B::Output: ByteSizeOf + Clone + Send + 'static,
     T: HttpSink<Input = B::Input, Output = B::Output>,
     RL: RetryLogic<Response = http::Response<Bytes>> + Send + 'static,
+    S: Service<B::Output>,
 {
     sink: Arc<T>,
     #[pin]
-    inner: TowerBatchedSink<
-        HttpBatchService<BoxFuture<'static, crate::Result<hyper::Request<Bytes>>>, B::Output>,
-        B,
-        RL,
-    >,
+    inner: BatchSink<S, B>,
+    _retry_logic: PhantomData<RL>,
     encoder: T::Encoder,
     // An empty slot is needed to buffer an item where we encoded it but
     // the inner sink is applying back pressure. This trick is used in the `WithFlatMap`
@@ -136,18 +153,82 @@ where
         Self {
             sink,
             inner,
+            _retry_logic: PhantomData,
+            encoder,
+            slot: None,
+        }
+    }
+}
+
+impl<T, B, RL> BatchedHttpSink<T, B, RL, DistributedSvc<HttpBatchSvc<B::Output>, RL>>
+where
+    B: Batch,
+    B::Output: ByteSizeOf + Clone + Send + 'static,
+    RL: RetryLogic<Response = http::Response<Bytes>, Error = HttpError> + Send + 'static,
+    T: HttpSink<Input = B::Input, Output = B::Output>,
+{
+    /// Spreads the batches over several endpoints, given one `HttpSink` per endpoint paired
+    /// with its name. Events are encoded by the first of them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn distributed(
+        sinks: Vec<(String, T)>,
+        batch: B,
+        retry_logic: RL,
+        request_settings: TowerRequestSettings,
+        distribution: DistributionConfig,
+        batch_timeout: Duration,
+        client: HttpClient,
+        acker: Acker,
+    ) -> Self {
+        let sinks = sinks
+            .into_iter()
+            .map(|(endpoint, sink)| (endpoint, Arc::new(sink)))
+            .collect::<Vec<_>>();
+        let sink = Arc::clone(&sinks.first().expect("At least one endpoint is required.").1);
+
+        let services = sinks
+            .into_iter()
+            .map(|(endpoint, sink)| {
+                let request_builder =
+                    move |b| -> BoxFuture<'static, crate::Result<http::Request<Bytes>>> {
+                        let sink = Arc::clone(&sink);
+                        Box::pin(async move { sink.build_request(b).await })
+                    };
+                (
+                    endpoint,
+                    HttpBatchService::new(client.clone(), request_builder),
+                )
+            })
+            .collect();
+
+        let service = request_settings.distributed_service::<B::Output, _, _>(
+            retry_logic,
+            services,
+            distribution,
+        );
+        let inner = BatchSink::new(service, batch, batch_timeout, acker);
+        let encoder = sink.build_encoder();
+
+        Self {
+            sink,
+            inner,
+            _retry_logic: PhantomData,
             encoder,
             slot: None,
         }
     }
 }
 
-impl<T, B, RL> Sink<Event> for BatchedHttpSink<T, B, RL>
+impl<T, B, RL, S> Sink<Event> for BatchedHttpSink<T, B, RL, S>
 where
     B: Batch,
     B::Output: ByteSizeOf + Clone + Send + 'static,
     T: HttpSink<Input = B::Input, Output = B::Output>,
     RL: RetryLogic<Response = http::Response<Bytes>> + Send + 'static,
+    S: Service<B::Output>,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::Error> + Send + 'static,
+    S::Response: sink::Response + Send + 'static,
 {
     type Error = crate::Error;
 
@@ -170,7 +251,10 @@ where
     fn start_send(mut self: Pin<&mut Self>, mut event: Event) -> Result<(), Self::Error> {
         let byte_size = event.size_of();
         let finalizers = event.metadata_mut().take_finalizers();
-        if let Some(item) = self.encoder.encode_event(event) {
+        if let Some(item) = self
+            .encoder
+            .encode_event_with_finalizers(event, &finalizers)
+        {
             *self.project().slot = Some(EncodedEvent {
                 item,
                 finalizers,
@@ -322,7 +406,10 @@ where
     fn start_send(mut self: Pin<&mut Self>, mut event: Event) -> Result<(), Self::Error> {
         let finalizers = event.metadata_mut().take_finalizers();
         let byte_size = event.size_of();
-        if let Some(item) = self.encoder.encode_event(event) {
+        if let Some(item) = self
+            .encoder
+            .encode_event_with_finalizers(event, &finalizers)
+        {
             *self.project().slot = Some(EncodedEvent {
                 item,
                 finalizers,
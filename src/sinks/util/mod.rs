@@ -6,6 +6,8 @@ pub mod compressor;
 pub mod encoding;
 pub mod http;
 pub mod normalizer;
+#[cfg(any(feature = "sinks-aws_s3", feature = "sinks-azure_blob", feature = "sinks-gcp"))]
+pub mod parquet;
 pub mod partitioner;
 pub mod processed_event;
 pub mod request_builder;
@@ -0,0 +1,651 @@
+//! Encoding batches of events as Parquet files, for the sinks writing objects to object stores.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    sync::Arc,
+};
+
+use chrono::DateTime;
+use codecs::encoding::Framer;
+use lookup::lookup_v2::{parse_path, OwnedPath};
+use once_cell::sync::OnceCell;
+use parquet::{
+    basic::{Compression as ParquetCodec, ConvertedType, Repetition, Type as PhysicalType},
+    data_type::{BoolType, ByteArray, ByteArrayType, DataType, DoubleType, Int64Type},
+    errors::ParquetError,
+    file::{
+        properties::WriterProperties,
+        writer::{SerializedColumnWriter, SerializedFileWriter},
+    },
+    schema::types::Type,
+};
+use serde::{Deserialize, Serialize};
+use vector_core::stream::BatcherSettings;
+
+use crate::{
+    codecs::Encoder,
+    event::{Event, LogEvent, Value},
+    internal_events::{ParquetFieldsDropped, ParquetValueConversionError},
+    sinks::util::{
+        encoding::{Encoder as _, Transformer},
+        Compression,
+    },
+};
+
+pub const PARQUET_CONTENT_TYPE: &str = "application/vnd.apache.parquet";
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ParquetEncodingConfig {
+    /// The columns of the files, keyed by the path of the field each one is read from. When not
+    /// set, the columns are inferred from the top level fields of the first batch.
+    pub schema: Option<BTreeMap<String, ParquetColumnType>>,
+    #[serde(default)]
+    pub compression: ParquetCompression,
+    /// The maximum number of rows of each row group, which defaults to the `max_events` of the
+    /// sink's batches.
+    pub row_group_size: Option<usize>,
+}
+
+impl ParquetEncodingConfig {
+    pub fn build(&self, batch: &BatcherSettings) -> crate::Result<ParquetEncoder> {
+        let row_group_size = self.row_group_size.unwrap_or(batch.item_limit);
+        if row_group_size == 0 {
+            return Err("`parquet.row_group_size` must be greater than 0.".into());
+        }
+
+        let schema = OnceCell::new();
+        if let Some(columns) = &self.schema {
+            if columns.is_empty() {
+                return Err("`parquet.schema` must include at least one column.".into());
+            }
+            let columns = columns
+                .iter()
+                .map(|(name, column_type)| Column {
+                    name: name.clone(),
+                    path: parse_path(name),
+                    column_type: *column_type,
+                })
+                .collect();
+            schema
+                .set(ParquetSchema::new(columns)?)
+                .expect("schema is only set once");
+        }
+
+        let properties = WriterProperties::builder()
+            .set_compression(self.compression.into())
+            .set_max_row_group_size(row_group_size)
+            .build();
+
+        Ok(ParquetEncoder {
+            schema: Arc::new(schema),
+            properties: Arc::new(properties),
+            row_group_size,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetColumnType {
+    String,
+    Int64,
+    Double,
+    Boolean,
+    /// Microseconds since the Unix epoch.
+    Timestamp,
+    /// Objects and arrays, or any other value, serialized as JSON.
+    Json,
+}
+
+impl ParquetColumnType {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Int64 => "int64",
+            Self::Double => "double",
+            Self::Boolean => "boolean",
+            Self::Timestamp => "timestamp",
+            Self::Json => "json",
+        }
+    }
+
+    fn infer(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bytes(_) | Value::Regex(_) => Some(Self::String),
+            Value::Integer(_) => Some(Self::Int64),
+            Value::Float(_) => Some(Self::Double),
+            Value::Boolean(_) => Some(Self::Boolean),
+            Value::Timestamp(_) => Some(Self::Timestamp),
+            Value::Object(_) | Value::Array(_) => Some(Self::Json),
+            Value::Null => None,
+        }
+    }
+
+    fn to_parquet(self, name: &str) -> Result<Type, ParquetError> {
+        let (physical_type, converted_type) = match self {
+            Self::String => (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8),
+            Self::Int64 => (PhysicalType::INT64, ConvertedType::NONE),
+            Self::Double => (PhysicalType::DOUBLE, ConvertedType::NONE),
+            Self::Boolean => (PhysicalType::BOOLEAN, ConvertedType::NONE),
+            Self::Timestamp => (PhysicalType::INT64, ConvertedType::TIMESTAMP_MICROS),
+            Self::Json => (PhysicalType::BYTE_ARRAY, ConvertedType::JSON),
+        };
+        Type::primitive_type_builder(name, physical_type)
+            .with_repetition(Repetition::OPTIONAL)
+            .with_converted_type(converted_type)
+            .build()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetCompression {
+    None,
+    #[derivative(Default)]
+    Snappy,
+    Gzip,
+}
+
+impl From<ParquetCompression> for ParquetCodec {
+    fn from(compression: ParquetCompression) -> Self {
+        match compression {
+            ParquetCompression::None => Self::UNCOMPRESSED,
+            ParquetCompression::Snappy => Self::SNAPPY,
+            ParquetCompression::Gzip => Self::GZIP,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Column {
+    name: String,
+    path: OwnedPath,
+    column_type: ParquetColumnType,
+}
+
+impl Column {
+    /// Writes the values of the column for `rows`, leaving values that can't be converted to the
+    /// type of the column null and reporting them.
+    fn write(
+        &self,
+        rows: &[LogEvent],
+        writer: &mut SerializedColumnWriter<'_>,
+    ) -> Result<(), ParquetError> {
+        let mut failed = 0;
+        let values = rows
+            .iter()
+            .map(|log| log.get(&self.path).filter(|value| !value.is_null()));
+        match self.column_type {
+            ParquetColumnType::String => write_column::<ByteArrayType>(
+                writer,
+                values.map(|value| value.map(|value| ByteArray::from(to_bytes(value)))),
+            ),
+            ParquetColumnType::Int64 => write_column::<Int64Type>(
+                writer,
+                values.map(|value| convert(value, &mut failed, to_int64)),
+            ),
+            ParquetColumnType::Double => write_column::<DoubleType>(
+                writer,
+                values.map(|value| convert(value, &mut failed, to_double)),
+            ),
+            ParquetColumnType::Boolean => write_column::<BoolType>(
+                writer,
+                values.map(|value| convert(value, &mut failed, to_boolean)),
+            ),
+            ParquetColumnType::Timestamp => write_column::<Int64Type>(
+                writer,
+                values.map(|value| convert(value, &mut failed, to_timestamp_micros)),
+            ),
+            ParquetColumnType::Json => write_column::<ByteArrayType>(
+                writer,
+                values.map(|value| {
+                    convert(value, &mut failed, |value| {
+                        serde_json::to_vec(value).ok().map(ByteArray::from)
+                    })
+                }),
+            ),
+        }?;
+
+        if failed > 0 {
+            emit!(ParquetValueConversionError {
+                column: &self.name,
+                column_type: self.column_type.as_str(),
+                count: failed,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Converts a present value, counting it in `failed` when it can't be converted.
+fn convert<T>(
+    value: Option<&Value>,
+    failed: &mut usize,
+    convert: impl FnOnce(&Value) -> Option<T>,
+) -> Option<T> {
+    let converted = value.and_then(convert);
+    if value.is_some() && converted.is_none() {
+        *failed += 1;
+    }
+    converted
+}
+
+fn write_column<T: DataType>(
+    writer: &mut SerializedColumnWriter<'_>,
+    values: impl Iterator<Item = Option<T::T>>,
+) -> Result<(), ParquetError> {
+    let mut definition_levels = Vec::new();
+    let mut present = Vec::new();
+    for value in values {
+        definition_levels.push(i16::from(value.is_some()));
+        present.extend(value);
+    }
+    writer
+        .typed::<T>()
+        .write_batch(&present, Some(&definition_levels[..]), None)?;
+    Ok(())
+}
+
+fn to_bytes(value: &Value) -> Vec<u8> {
+    match value {
+        Value::Bytes(bytes) => bytes.to_vec(),
+        value => value.to_string_lossy().into_bytes(),
+    }
+}
+
+fn to_int64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Integer(integer) => Some(*integer),
+        Value::Float(float) => {
+            let float = float.into_inner();
+            (float.fract() == 0.0 && float >= i64::MIN as f64 && float < i64::MAX as f64)
+                .then(|| float as i64)
+        }
+        Value::Bytes(bytes) => std::str::from_utf8(bytes).ok()?.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn to_double(value: &Value) -> Option<f64> {
+    match value {
+        Value::Float(float) => Some(float.into_inner()),
+        Value::Integer(integer) => Some(*integer as f64),
+        Value::Bytes(bytes) => std::str::from_utf8(bytes).ok()?.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn to_boolean(value: &Value) -> Option<bool> {
+    match value {
+        Value::Boolean(boolean) => Some(*boolean),
+        Value::Bytes(bytes) => std::str::from_utf8(bytes).ok()?.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn to_timestamp_micros(value: &Value) -> Option<i64> {
+    let timestamp = match value {
+        Value::Timestamp(timestamp) => *timestamp,
+        // Timestamps reformatted by `encoding.timestamp_format = "unix"`.
+        Value::Integer(seconds) => return seconds.checked_mul(1_000_000),
+        Value::Bytes(bytes) => DateTime::parse_from_rfc3339(std::str::from_utf8(bytes).ok()?)
+            .ok()?
+            .into(),
+        _ => return None,
+    };
+    timestamp
+        .timestamp()
+        .checked_mul(1_000_000)?
+        .checked_add(i64::from(timestamp.timestamp_subsec_micros()))
+}
+
+#[derive(Debug)]
+struct ParquetSchema {
+    columns: Vec<Column>,
+    schema: Arc<Type>,
+    inferred: bool,
+}
+
+impl ParquetSchema {
+    fn new(columns: Vec<Column>) -> Result<Self, ParquetError> {
+        let mut fields = columns
+            .iter()
+            .map(|column| column.column_type.to_parquet(&column.name).map(Arc::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        let schema = Type::group_type_builder("event")
+            .with_fields(&mut fields)
+            .build()?;
+
+        Ok(Self {
+            columns,
+            schema: Arc::new(schema),
+            inferred: false,
+        })
+    }
+
+    /// Infers the columns from the top level fields of `logs`, typed after the first non-null
+    /// value of each field, except that fields holding both integers and floats are doubles.
+    fn infer(logs: &[LogEvent]) -> Result<Self, ParquetError> {
+        let mut columns = BTreeMap::new();
+        for log in logs {
+            for (name, value) in log.as_map() {
+                let inferred = match ParquetColumnType::infer(value) {
+                    Some(inferred) => inferred,
+                    None => continue,
+                };
+                let column_type = columns.entry(name.as_str()).or_insert(inferred);
+                if (*column_type, inferred) == (ParquetColumnType::Int64, ParquetColumnType::Double)
+                {
+                    *column_type = ParquetColumnType::Double;
+                }
+            }
+        }
+
+        if columns.is_empty() {
+            return Err(ParquetError::General(
+                "No fields to infer the Parquet schema from.".into(),
+            ));
+        }
+
+        let mut schema = Self::new(
+            columns
+                .into_iter()
+                .map(|(name, column_type)| Column {
+                    name: name.to_owned(),
+                    path: OwnedPath::single_field(name),
+                    column_type,
+                })
+                .collect(),
+        )?;
+        schema.inferred = true;
+        Ok(schema)
+    }
+
+    /// Reports the top level fields of `logs` that aren't columns of an inferred schema, which
+    /// are left out of the files.
+    fn report_dropped_fields(&self, logs: &[LogEvent]) {
+        if !self.inferred {
+            return;
+        }
+
+        let mut fields = BTreeSet::new();
+        let mut count = 0;
+        for log in logs {
+            for (name, value) in log.as_map() {
+                if !value.is_null() && !self.columns.iter().any(|column| &column.name == name) {
+                    fields.insert(name.as_str());
+                    count += 1;
+                }
+            }
+        }
+
+        if count > 0 {
+            emit!(ParquetFieldsDropped {
+                fields: &fields.into_iter().collect::<Vec<_>>(),
+                count,
+            });
+        }
+    }
+}
+
+/// Writes each batch of events as a Parquet file.
+///
+/// An inferred schema is kept for all later batches, fields that weren't part of the first batch
+/// are left out of the files and reported.
+#[derive(Clone, Debug)]
+pub struct ParquetEncoder {
+    schema: Arc<OnceCell<ParquetSchema>>,
+    properties: Arc<WriterProperties>,
+    row_group_size: usize,
+}
+
+impl ParquetEncoder {
+    fn encode(&self, logs: &[LogEvent], writer: &mut dyn io::Write) -> Result<usize, ParquetError> {
+        let schema = self.schema.get_or_try_init(|| ParquetSchema::infer(logs))?;
+        schema.report_dropped_fields(logs);
+
+        let mut buffer = Vec::new();
+        let mut file = SerializedFileWriter::new(
+            &mut buffer,
+            Arc::clone(&schema.schema),
+            Arc::clone(&self.properties),
+        )?;
+        for rows in logs.chunks(self.row_group_size) {
+            let mut row_group = file.next_row_group()?;
+            for column in &schema.columns {
+                let mut column_writer = row_group.next_column()?.ok_or_else(|| {
+                    ParquetError::General("Fewer columns than in the schema.".into())
+                })?;
+                column.write(rows, &mut column_writer)?;
+                column_writer.close()?;
+            }
+            row_group.close()?;
+        }
+        file.close()?;
+
+        writer.write_all(&buffer)?;
+        Ok(buffer.len())
+    }
+}
+
+/// The encoder of the sinks writing objects, which either writes the events of a batch one after
+/// the other with a codec and framing, or writes the batch as a Parquet file.
+#[derive(Clone, Debug)]
+pub enum ObjectEncoder {
+    Framed((Transformer, Encoder<Framer>)),
+    Parquet(Transformer, ParquetEncoder),
+}
+
+impl ObjectEncoder {
+    pub fn new(
+        transformer: Transformer,
+        encoder: Encoder<Framer>,
+        parquet: Option<&ParquetEncodingConfig>,
+        batch: &BatcherSettings,
+    ) -> crate::Result<Self> {
+        Ok(match parquet {
+            Some(parquet) => Self::Parquet(transformer, parquet.build(batch)?),
+            None => Self::Framed((transformer, encoder)),
+        })
+    }
+
+    pub const fn is_parquet(&self) -> bool {
+        matches!(self, Self::Parquet(..))
+    }
+
+    /// Parquet files compress their columns, so they aren't compressed as a whole.
+    pub const fn compression(&self, compression: Compression) -> Compression {
+        match self {
+            Self::Framed(_) => compression,
+            Self::Parquet(..) => Compression::None,
+        }
+    }
+
+    pub fn content_type(&self) -> &str {
+        match self {
+            Self::Framed((_, encoder)) => encoder.content_type(),
+            Self::Parquet(..) => PARQUET_CONTENT_TYPE,
+        }
+    }
+
+    pub const fn extension(&self, compression: Compression) -> &'static str {
+        match self {
+            Self::Framed(_) => compression.extension(),
+            Self::Parquet(..) => "parquet",
+        }
+    }
+}
+
+impl crate::sinks::util::encoding::Encoder<Vec<Event>> for ObjectEncoder {
+    fn encode_input(&self, events: Vec<Event>, writer: &mut dyn io::Write) -> io::Result<usize> {
+        match self {
+            Self::Framed(encoder) => encoder.encode_input(events, writer),
+            Self::Parquet(transformer, encoder) => {
+                let logs = events
+                    .into_iter()
+                    .filter_map(|mut event| {
+                        transformer.transform(&mut event);
+                        event.try_into_log()
+                    })
+                    .collect::<Vec<_>>();
+                encoder
+                    .encode(&logs, writer)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Write, num::NonZeroUsize, time::Duration};
+
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::Field,
+    };
+
+    use super::*;
+
+    fn batch_settings(max_events: usize) -> BatcherSettings {
+        BatcherSettings::new(
+            Duration::from_secs(1),
+            NonZeroUsize::new(usize::MAX).unwrap(),
+            NonZeroUsize::new(max_events).unwrap(),
+        )
+    }
+
+    fn encode(encoder: &ParquetEncoder, events: Vec<Event>) -> SerializedFileReader<File> {
+        let encoder = ObjectEncoder::Parquet(Transformer::default(), encoder.clone());
+        let mut file = tempfile::tempfile().unwrap();
+        encoder.encode_input(events, &mut file).unwrap();
+        file.flush().unwrap();
+        SerializedFileReader::new(file).unwrap()
+    }
+
+    fn rows(reader: &SerializedFileReader<File>) -> Vec<Vec<(String, Field)>> {
+        reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| {
+                row.get_column_iter()
+                    .map(|(name, field)| (name.clone(), field.clone()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn log(fields: &[(&str, Value)]) -> Event {
+        let mut log = LogEvent::default();
+        for (name, value) in fields {
+            log.insert(*name, value.clone());
+        }
+        log.into()
+    }
+
+    #[test]
+    fn writes_configured_schema_in_row_groups() {
+        let config = ParquetEncodingConfig {
+            schema: Some(
+                [
+                    ("message".to_owned(), ParquetColumnType::String),
+                    ("http.status".to_owned(), ParquetColumnType::Int64),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+            compression: ParquetCompression::Gzip,
+            row_group_size: None,
+        };
+        let encoder = config.build(&batch_settings(2)).unwrap();
+
+        let reader = encode(
+            &encoder,
+            vec![
+                log(&[("message", "one".into()), ("http.status", 200.into())]),
+                log(&[("message", "two".into()), ("http.status", "404".into())]),
+                log(&[("message", "three".into()), ("http.status", "none".into())]),
+            ],
+        );
+
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        assert_eq!(
+            rows(&reader),
+            vec![
+                vec![
+                    ("http.status".to_owned(), Field::Long(200)),
+                    ("message".to_owned(), Field::Str("one".to_owned())),
+                ],
+                vec![
+                    ("http.status".to_owned(), Field::Long(404)),
+                    ("message".to_owned(), Field::Str("two".to_owned())),
+                ],
+                vec![
+                    ("http.status".to_owned(), Field::Null),
+                    ("message".to_owned(), Field::Str("three".to_owned())),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn infers_schema_from_first_batch() {
+        let config = ParquetEncodingConfig {
+            row_group_size: Some(10),
+            ..Default::default()
+        };
+        let encoder = config.build(&batch_settings(1000)).unwrap();
+
+        let reader = encode(
+            &encoder,
+            vec![
+                log(&[("count", 1.into()), ("ok", true.into())]),
+                log(&[("count", 1.5.into()), ("ok", Value::Null)]),
+            ],
+        );
+        assert_eq!(
+            rows(&reader),
+            vec![
+                vec![
+                    ("count".to_owned(), Field::Double(1.0)),
+                    ("ok".to_owned(), Field::Bool(true)),
+                ],
+                vec![
+                    ("count".to_owned(), Field::Double(1.5)),
+                    ("ok".to_owned(), Field::Null),
+                ],
+            ]
+        );
+
+        // Later batches keep the schema of the first one.
+        let reader = encode(
+            &encoder,
+            vec![log(&[("ok", "false".into()), ("new", "field".into())])],
+        );
+        assert_eq!(
+            rows(&reader),
+            vec![vec![
+                ("count".to_owned(), Field::Null),
+                ("ok".to_owned(), Field::Bool(false)),
+            ]]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        let config = ParquetEncodingConfig {
+            row_group_size: Some(0),
+            ..Default::default()
+        };
+        assert!(config.build(&batch_settings(10)).is_err());
+
+        let config = ParquetEncodingConfig {
+            schema: Some(BTreeMap::new()),
+            ..Default::default()
+        };
+        assert!(config.build(&batch_settings(10)).is_err());
+    }
+}
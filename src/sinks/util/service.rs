@@ -12,6 +12,9 @@ use vector_buffers::Acker;
 
 pub use crate::sinks::util::service::{
     concurrency::{concurrency_is_none, Concurrency},
+    distributed::{
+        resolve_endpoints, BalanceStrategy, DistributedService, DistributionConfig, EndpointsError,
+    },
     map::Map,
 };
 use crate::sinks::util::{
@@ -25,11 +28,15 @@ use crate::sinks::util::{
 };
 
 mod concurrency;
+mod distributed;
 mod map;
 
 pub type Svc<S, L> = RateLimit<AdaptiveConcurrencyLimit<Retry<FixedRetryPolicy<L>, Timeout<S>>, L>>;
 pub type TowerBatchedSink<S, B, RL> = BatchSink<Svc<S, RL>, B>;
 pub type TowerPartitionSink<S, B, RL, K> = PartitionBatchSink<Svc<S, RL>, B, K>;
+pub type DistributedSvc<S, L> = RateLimit<
+    Retry<FixedRetryPolicy<L>, DistributedService<AdaptiveConcurrencyLimit<Timeout<S>, L>, L>>,
+>;
 
 pub trait ServiceBuilderExt<L> {
     fn map<R1, R2, F>(self, f: F) -> ServiceBuilder<Stack<MapLayer<R1, R2>, L>>
@@ -247,6 +254,45 @@ impl TowerRequestSettings {
             .service(service);
         BatchSink::new(service, batch, batch_timeout, acker)
     }
+
+    /// Builds a service spreading requests over several endpoints, given one service per
+    /// endpoint paired with its name. Each endpoint gets its own concurrency limit and timeout,
+    /// while retries may go to any healthy endpoint.
+    pub fn distributed_service<Request, RL, S>(
+        &self,
+        retry_logic: RL,
+        services: Vec<(String, S)>,
+        distribution: DistributionConfig,
+    ) -> DistributedSvc<S, RL>
+    where
+        RL: RetryLogic<Response = S::Response>,
+        S: Service<Request> + Clone + Send + 'static,
+        S::Error: Into<crate::Error> + Send + Sync + 'static,
+        S::Response: Send + 'static,
+        S::Future: Send + 'static,
+        Request: Clone + Send + 'static,
+    {
+        let policy = self.retry_policy(retry_logic.clone());
+        let services = services
+            .into_iter()
+            .map(|(endpoint, service)| {
+                let service = ServiceBuilder::new()
+                    .layer(AdaptiveConcurrencyLimitLayer::new(
+                        self.concurrency,
+                        self.adaptive_concurrency,
+                        retry_logic.clone(),
+                    ))
+                    .timeout(self.timeout)
+                    .service(service);
+                (endpoint, service)
+            })
+            .collect();
+
+        ServiceBuilder::new()
+            .rate_limit(self.rate_limit_num, self.rate_limit_duration)
+            .retry(policy)
+            .service(DistributedService::new(services, retry_logic, distribution))
+    }
 }
 
 #[derive(Debug, Clone)]
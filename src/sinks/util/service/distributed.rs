@@ -0,0 +1,683 @@
+use std::{
+    cmp,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::ready;
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use tokio::time::{sleep_until, Instant, Sleep};
+use tower::{timeout::error::Elapsed, Service};
+
+use crate::{
+    http::HttpError,
+    internal_events::{EndpointEjected, EndpointRestored},
+    sinks::util::retries::{RetryAction, RetryLogic},
+};
+
+/// How requests are spread over the endpoints.
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceStrategy {
+    /// Take turns sending to each endpoint.
+    #[derivative(Default)]
+    RoundRobin,
+    /// Send to the endpoint with the fewest requests in flight.
+    LeastOutstanding,
+}
+
+/// Load balancing and health tracking settings for sinks sending to several endpoints.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct DistributionConfig {
+    #[serde(default)]
+    pub strategy: BalanceStrategy,
+    pub unhealthy_threshold: Option<u32>,        // 3
+    pub retry_initial_backoff_secs: Option<u64>, // 1
+    pub retry_max_duration_secs: Option<u64>,    // 1 hour
+}
+
+pub const UNHEALTHY_THRESHOLD_DEFAULT: u32 = 3;
+pub const HEALTH_RETRY_INITIAL_BACKOFF_SECONDS_DEFAULT: u64 = 1; // one second
+pub const HEALTH_RETRY_MAX_DURATION_SECONDS_DEFAULT: u64 = 3_600; // one hour
+
+impl DistributionConfig {
+    fn unhealthy_threshold(&self) -> u32 {
+        self.unhealthy_threshold
+            .unwrap_or(UNHEALTHY_THRESHOLD_DEFAULT)
+            .max(1)
+    }
+
+    fn initial_backoff(&self) -> Duration {
+        Duration::from_secs(
+            self.retry_initial_backoff_secs
+                .unwrap_or(HEALTH_RETRY_INITIAL_BACKOFF_SECONDS_DEFAULT),
+        )
+    }
+
+    fn max_backoff(&self) -> Duration {
+        Duration::from_secs(
+            self.retry_max_duration_secs
+                .unwrap_or(HEALTH_RETRY_MAX_DURATION_SECONDS_DEFAULT),
+        )
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum EndpointsError {
+    #[snafu(display("Missing endpoint, either `{}` or `endpoints` must be set", option))]
+    MissingEndpoint { option: &'static str },
+    #[snafu(display("Only one of `{}` and `endpoints` can be set", option))]
+    ConflictingEndpoints { option: &'static str },
+}
+
+/// Resolves the endpoints of a sink from its single endpoint `option`, if set, and its
+/// `endpoints` list, exactly one of which must be given.
+pub fn resolve_endpoints<T: Clone>(
+    option: &'static str,
+    endpoint: Option<&T>,
+    endpoints: &[T],
+) -> Result<Vec<T>, EndpointsError> {
+    match (endpoint, endpoints.is_empty()) {
+        (Some(endpoint), true) => Ok(vec![endpoint.clone()]),
+        (None, false) => Ok(endpoints.to_vec()),
+        (None, true) => Err(EndpointsError::MissingEndpoint { option }),
+        (Some(_), false) => Err(EndpointsError::ConflictingEndpoints { option }),
+    }
+}
+
+/// Spreads requests over the services of several endpoints.
+///
+/// The health of each endpoint is tracked passively from the outcome of the requests sent to
+/// it, as classified by the `RetryLogic`. An endpoint failing `unhealthy_threshold` requests in
+/// a row is ejected from the rotation. Once its backoff has elapsed a single request is sent to
+/// it as a probe: if that succeeds the endpoint is put back, otherwise the backoff is doubled.
+///
+/// Clones share the health of the endpoints, but not their readiness.
+pub struct DistributedService<S, L> {
+    endpoints: Vec<Endpoint<S>>,
+    strategy: BalanceStrategy,
+    next: Arc<AtomicUsize>,
+    logic: L,
+    ready_index: Option<usize>,
+    probe_delay: Option<Pin<Box<Sleep>>>,
+}
+
+struct Endpoint<S> {
+    service: S,
+    state: Arc<EndpointState>,
+}
+
+impl<S, L> DistributedService<S, L> {
+    /// Creates a service distributing requests over `services`, each paired with the name of
+    /// its endpoint.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `services` is empty.
+    pub fn new(services: Vec<(String, S)>, logic: L, config: DistributionConfig) -> Self {
+        assert!(!services.is_empty(), "At least one endpoint is required.");
+
+        // Ejecting the only endpoint would just stall the sink on top of the retry backoff.
+        let track_health = services.len() > 1;
+        let endpoints = services
+            .into_iter()
+            .map(|(name, service)| Endpoint {
+                service,
+                state: Arc::new(EndpointState::new(name, track_health, &config)),
+            })
+            .collect();
+
+        Self {
+            endpoints,
+            strategy: config.strategy,
+            next: Arc::new(AtomicUsize::new(0)),
+            logic,
+            ready_index: None,
+            probe_delay: None,
+        }
+    }
+
+    /// Indices of the endpoints that can take a request, in order of preference.
+    fn candidates(&self, now: Instant) -> Vec<usize> {
+        let len = self.endpoints.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % len;
+        let mut candidates = (start..start + len)
+            .map(|index| index % len)
+            .filter(|&index| self.endpoints[index].state.is_available(now))
+            .collect::<Vec<_>>();
+
+        if self.strategy == BalanceStrategy::LeastOutstanding {
+            // The sort is stable, so ties are still broken in round robin order.
+            candidates.sort_by_key(|&index| self.endpoints[index].state.outstanding());
+        }
+
+        candidates
+    }
+
+    /// When the next ejected endpoint can be probed, if any endpoint is ejected.
+    fn next_probe(&self) -> Option<Instant> {
+        self.endpoints
+            .iter()
+            .filter_map(|endpoint| endpoint.state.next_probe())
+            .min()
+    }
+}
+
+impl<S, L, Request> Service<Request> for DistributedService<S, L>
+where
+    S: Service<Request>,
+    S::Error: Into<crate::Error>,
+    L: RetryLogic<Response = S::Response>,
+{
+    type Response = S::Response;
+    type Error = crate::Error;
+    type Future = ResponseFuture<S::Future, L>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.ready_index.is_some() {
+            return Poll::Ready(Ok(()));
+        }
+
+        let now = Instant::now();
+        // Endpoints left pending here keep their place in line and are polled again next time.
+        for index in self.candidates(now) {
+            match self.endpoints[index].service.poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    self.ready_index = Some(index);
+                    self.probe_delay = None;
+                    return Poll::Ready(Ok(()));
+                }
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error.into())),
+                Poll::Pending => {}
+            }
+        }
+
+        // Nothing wakes us up when an ejected endpoint becomes due for a probe, so set a timer.
+        if let Some(deadline) = self.next_probe() {
+            let deadline = cmp::max(deadline, now);
+            let delay = self
+                .probe_delay
+                .get_or_insert_with(|| Box::pin(sleep_until(deadline)));
+            delay.as_mut().reset(deadline);
+            if delay.as_mut().poll(cx).is_ready() {
+                cx.waker().wake_by_ref();
+            }
+        }
+
+        Poll::Pending
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let index = self
+            .ready_index
+            .take()
+            .expect("No endpoint is ready; poll_ready must be called first");
+        let endpoint = &mut self.endpoints[index];
+
+        let in_flight = InFlight::start(Arc::clone(&endpoint.state));
+        let future = endpoint.service.call(request);
+
+        ResponseFuture {
+            inner: future,
+            in_flight: Some(in_flight),
+            logic: self.logic.clone(),
+        }
+    }
+}
+
+impl<S, L> Clone for DistributedService<S, L>
+where
+    S: Clone,
+    L: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            endpoints: self
+                .endpoints
+                .iter()
+                .map(|endpoint| Endpoint {
+                    service: endpoint.service.clone(),
+                    state: Arc::clone(&endpoint.state),
+                })
+                .collect(),
+            strategy: self.strategy,
+            next: Arc::clone(&self.next),
+            logic: self.logic.clone(),
+            ready_index: None,
+            probe_delay: None,
+        }
+    }
+}
+
+#[pin_project]
+pub struct ResponseFuture<F, L> {
+    #[pin]
+    inner: F,
+    in_flight: Option<InFlight>,
+    logic: L,
+}
+
+impl<F, L, Response, E> Future for ResponseFuture<F, L>
+where
+    F: Future<Output = Result<Response, E>>,
+    E: Into<crate::Error>,
+    L: RetryLogic<Response = Response>,
+{
+    type Output = Result<Response, crate::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = ready!(this.inner.poll(cx)).map_err(Into::into);
+
+        if let Some(mut in_flight) = this.in_flight.take() {
+            in_flight.healthy = is_healthy(this.logic, &result);
+        }
+
+        Poll::Ready(result)
+    }
+}
+
+/// Whether the outcome of a request says anything about the health of its endpoint.
+fn is_healthy<L: RetryLogic>(
+    logic: &L,
+    result: &Result<L::Response, crate::Error>,
+) -> Option<bool> {
+    match result {
+        Ok(response) => Some(!matches!(
+            logic.should_retry_response(response),
+            RetryAction::Retry(_)
+        )),
+        Err(error) => {
+            if let Some(error) = error.downcast_ref::<L::Error>() {
+                // Errors that aren't worth retrying are caused by the request, not the endpoint.
+                logic.is_retriable_error(error).then(|| false)
+            } else if error.downcast_ref::<Elapsed>().is_some()
+                || error.downcast_ref::<HttpError>().is_some()
+            {
+                Some(false)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Keeps track of a request in flight to an endpoint and settles its health when dropped, which
+/// also covers requests that are cancelled before they complete.
+struct InFlight {
+    state: Arc<EndpointState>,
+    probe: bool,
+    healthy: Option<bool>,
+}
+
+impl InFlight {
+    fn start(state: Arc<EndpointState>) -> Self {
+        let probe = state.start_request();
+        Self {
+            state,
+            probe,
+            healthy: None,
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.state.finish_request(self.probe, self.healthy);
+    }
+}
+
+struct EndpointState {
+    name: String,
+    track_health: bool,
+    unhealthy_threshold: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    outstanding: AtomicUsize,
+    health: Mutex<Health>,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    ejection: Option<Ejection>,
+}
+
+#[derive(Debug)]
+struct Ejection {
+    until: Instant,
+    backoff: Duration,
+    probing: bool,
+}
+
+impl EndpointState {
+    fn new(name: String, track_health: bool, config: &DistributionConfig) -> Self {
+        Self {
+            name,
+            track_health,
+            unhealthy_threshold: config.unhealthy_threshold(),
+            initial_backoff: config.initial_backoff(),
+            max_backoff: config.max_backoff(),
+            outstanding: AtomicUsize::new(0),
+            health: Mutex::new(Health::default()),
+        }
+    }
+
+    fn outstanding(&self) -> usize {
+        self.outstanding.load(Ordering::Relaxed)
+    }
+
+    fn health(&self) -> std::sync::MutexGuard<'_, Health> {
+        self.health.lock().expect("Endpoint health lock poisoned")
+    }
+
+    fn is_available(&self, now: Instant) -> bool {
+        match &self.health().ejection {
+            None => true,
+            Some(ejection) => !ejection.probing && ejection.until <= now,
+        }
+    }
+
+    fn next_probe(&self) -> Option<Instant> {
+        match &self.health().ejection {
+            Some(ejection) if !ejection.probing => Some(ejection.until),
+            // Check back once the probe has had time to complete.
+            Some(_) => Some(Instant::now() + self.initial_backoff),
+            None => None,
+        }
+    }
+
+    /// Registers a request, returning whether it probes an ejected endpoint.
+    fn start_request(&self) -> bool {
+        self.outstanding.fetch_add(1, Ordering::Relaxed);
+        match &mut self.health().ejection {
+            Some(ejection) if !ejection.probing => {
+                debug!(message = "Probing ejected endpoint.", endpoint = %self.name);
+                ejection.probing = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn finish_request(&self, probe: bool, healthy: Option<bool>) {
+        self.outstanding.fetch_sub(1, Ordering::Relaxed);
+        if !self.track_health {
+            return;
+        }
+
+        let mut health = self.health();
+        match healthy {
+            Some(true) => {
+                health.consecutive_failures = 0;
+                if health.ejection.take().is_some() {
+                    emit!(EndpointRestored {
+                        endpoint: &self.name
+                    });
+                }
+            }
+            Some(false) => {
+                health.consecutive_failures += 1;
+                let backoff = if let Some(ejection) = health.ejection.as_mut() {
+                    // Requests sent before the endpoint was ejected don't count.
+                    if !probe {
+                        return;
+                    }
+                    ejection.backoff = cmp::min(ejection.backoff * 2, self.max_backoff);
+                    ejection.until = Instant::now() + ejection.backoff;
+                    ejection.probing = false;
+                    ejection.backoff
+                } else if health.consecutive_failures >= self.unhealthy_threshold {
+                    let backoff = cmp::min(self.initial_backoff, self.max_backoff);
+                    health.ejection = Some(Ejection {
+                        until: Instant::now() + backoff,
+                        backoff,
+                        probing: false,
+                    });
+                    backoff
+                } else {
+                    return;
+                };
+
+                emit!(EndpointEjected {
+                    endpoint: &self.name,
+                    backoff,
+                });
+            }
+            None => {
+                // The outcome of the probe was inconclusive, so let another request probe.
+                if let Some(ejection) = health.ejection.as_mut().filter(|_| probe) {
+                    ejection.probing = false;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::{self, BoxFuture};
+    use tokio::time::{advance, pause};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[derive(Debug, snafu::Snafu)]
+    #[snafu(display("endpoint failure"))]
+    struct TestError;
+
+    #[derive(Clone, Debug)]
+    struct TestRetryLogic;
+
+    impl RetryLogic for TestRetryLogic {
+        type Error = TestError;
+        type Response = bool;
+
+        fn is_retriable_error(&self, _error: &Self::Error) -> bool {
+            true
+        }
+
+        fn should_retry_response(&self, response: &Self::Response) -> RetryAction {
+            if *response {
+                RetryAction::Successful
+            } else {
+                RetryAction::Retry("failed".into())
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct TestEndpoint {
+        calls: Arc<AtomicUsize>,
+        healthy: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl TestEndpoint {
+        fn new() -> Self {
+            Self {
+                calls: Arc::new(AtomicUsize::new(0)),
+                healthy: Arc::new(std::sync::atomic::AtomicBool::new(true)),
+            }
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+
+        fn set_healthy(&self, healthy: bool) {
+            self.healthy.store(healthy, Ordering::SeqCst);
+        }
+    }
+
+    impl Service<()> for TestEndpoint {
+        type Response = bool;
+        type Error = TestError;
+        type Future = BoxFuture<'static, Result<bool, TestError>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: ()) -> Self::Future {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Box::pin(future::ok(self.healthy.load(Ordering::SeqCst)))
+        }
+    }
+
+    fn distribute(
+        endpoints: &[TestEndpoint],
+        config: DistributionConfig,
+    ) -> DistributedService<TestEndpoint, TestRetryLogic> {
+        let services = endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| (format!("endpoint-{}", index), endpoint.clone()))
+            .collect();
+        DistributedService::new(services, TestRetryLogic, config)
+    }
+
+    async fn send(service: &mut DistributedService<TestEndpoint, TestRetryLogic>) -> bool {
+        service.ready().await.unwrap().call(()).await.unwrap()
+    }
+
+    #[test]
+    fn resolves_endpoints() {
+        let one = "http://one".to_owned();
+        let two = "http://two".to_owned();
+
+        assert_eq!(
+            resolve_endpoints("endpoint", Some(&one), &[]).unwrap(),
+            vec![one.clone()]
+        );
+        assert_eq!(
+            resolve_endpoints("endpoint", None, &[one.clone(), two.clone()]).unwrap(),
+            vec![one.clone(), two.clone()]
+        );
+        assert!(resolve_endpoints::<String>("endpoint", None, &[]).is_err());
+        assert!(resolve_endpoints("endpoint", Some(&one), &[two]).is_err());
+    }
+
+    #[tokio::test]
+    async fn round_robin_spreads_requests() {
+        let endpoints = vec![
+            TestEndpoint::new(),
+            TestEndpoint::new(),
+            TestEndpoint::new(),
+        ];
+        let mut service = distribute(&endpoints, DistributionConfig::default());
+
+        for _ in 0..9 {
+            assert!(send(&mut service).await);
+        }
+
+        for endpoint in &endpoints {
+            assert_eq!(endpoint.calls(), 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn least_outstanding_prefers_idle_endpoints() {
+        let endpoints = vec![TestEndpoint::new(), TestEndpoint::new()];
+        let mut service = distribute(
+            &endpoints,
+            DistributionConfig {
+                strategy: BalanceStrategy::LeastOutstanding,
+                ..Default::default()
+            },
+        );
+
+        // Keep a request in flight to whichever endpoint gets the first one.
+        let pending = service.ready().await.unwrap().call(());
+        let busy = endpoints.iter().position(|e| e.calls() == 1).unwrap();
+
+        for _ in 0..4 {
+            assert!(send(&mut service).await);
+        }
+        assert_eq!(endpoints[busy].calls(), 1);
+        assert_eq!(endpoints[1 - busy].calls(), 4);
+
+        assert!(pending.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn ejects_and_restores_unhealthy_endpoint() {
+        pause();
+
+        let endpoints = vec![TestEndpoint::new(), TestEndpoint::new()];
+        let mut service = distribute(
+            &endpoints,
+            DistributionConfig {
+                unhealthy_threshold: Some(2),
+                retry_initial_backoff_secs: Some(10),
+                ..Default::default()
+            },
+        );
+
+        endpoints[0].set_healthy(false);
+        for _ in 0..4 {
+            send(&mut service).await;
+        }
+        assert_eq!(endpoints[0].calls(), 2);
+
+        // Ejected endpoints don't get any requests.
+        for _ in 0..4 {
+            assert!(send(&mut service).await);
+        }
+        assert_eq!(endpoints[0].calls(), 2);
+        assert_eq!(endpoints[1].calls(), 6);
+
+        // A failed probe doubles the backoff.
+        advance(Duration::from_secs(10)).await;
+        for _ in 0..2 {
+            send(&mut service).await;
+        }
+        assert_eq!(endpoints[0].calls(), 3);
+        advance(Duration::from_secs(10)).await;
+        send(&mut service).await;
+        assert_eq!(endpoints[0].calls(), 3);
+
+        // A successful probe puts the endpoint back in the rotation.
+        endpoints[0].set_healthy(true);
+        advance(Duration::from_secs(10)).await;
+        for _ in 0..4 {
+            assert!(send(&mut service).await);
+        }
+        assert_eq!(endpoints[0].calls(), 5);
+    }
+
+    #[tokio::test]
+    async fn waits_for_ejected_endpoints_to_be_probed() {
+        pause();
+
+        let endpoints = vec![TestEndpoint::new(), TestEndpoint::new()];
+        let mut service = distribute(
+            &endpoints,
+            DistributionConfig {
+                unhealthy_threshold: Some(1),
+                ..Default::default()
+            },
+        );
+
+        for endpoint in &endpoints {
+            endpoint.set_healthy(false);
+        }
+        send(&mut service).await;
+        send(&mut service).await;
+
+        // Both endpoints are ejected, so readiness has to wait for the backoff.
+        let mut ready = tokio_test::task::spawn(ServiceExt::<()>::ready(&mut service));
+        tokio_test::assert_pending!(ready.poll());
+        advance(Duration::from_secs(1)).await;
+        assert!(ready.is_woken());
+        tokio_test::assert_ready_ok!(ready.poll());
+    }
+}
@@ -55,6 +55,11 @@ impl UriSerde {
         })
     }
 
+    /// Whether this is still the default, empty URI, as for endpoint options left unset.
+    pub fn is_unset(&self) -> bool {
+        self.uri == Uri::default()
+    }
+
     #[allow(clippy::missing_const_for_fn)] // constant functions cannot evaluate destructors
     pub fn with_auth(mut self, auth: Option<Auth>) -> Self {
         self.auth = auth;
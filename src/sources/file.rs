@@ -1519,13 +1519,6 @@ mod tests {
         let dir = tempdir().unwrap();
         let config = file::FileConfig {
             include: vec![PathBuf::from("tests/data/gzipped.log")],
-            // TODO: remove this once files are fingerprinted after decompression
-            //
-            // Currently, this needs to be smaller than the total size of the compressed file
-            // because the fingerprinter tries to read until a newline, which it's not going to see
-            // in the compressed data, or this number of bytes. If it hits EOF before that, it
-            // can't return a fingerprint because the value would change once more data is written.
-            max_line_bytes: 100,
             ..test_default_file_config(&dir)
         };
 
@@ -1545,6 +1538,22 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_zstd_compressed_file() {
+        let dir = tempdir().unwrap();
+        let config = file::FileConfig {
+            include: vec![PathBuf::from("tests/data/multipart-zst.log.zst")],
+            ..test_default_file_config(&dir)
+        };
+
+        let received = run_file_source(&config, false, NoAcks, sleep_500_millis()).await;
+
+        let received = extract_messages_string(received);
+        let expected = std::fs::read_to_string("tests/data/multipart-zst.log").unwrap();
+
+        assert_eq!(received, expected.lines().collect::<Vec<_>>());
+    }
+
     #[tokio::test]
     async fn test_non_utf8_encoded_file() {
         let dir = tempdir().unwrap();
@@ -31,6 +31,8 @@ mod disk;
 mod filesystem;
 mod memory;
 mod network;
+#[cfg(target_os = "linux")]
+mod process;
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -44,6 +46,8 @@ pub enum Collector {
     Host,
     Memory,
     Network,
+    #[cfg(target_os = "linux")]
+    Process,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -86,6 +90,9 @@ pub struct HostMetricsConfig {
     pub filesystem: filesystem::FilesystemConfig,
     #[serde(default)]
     pub network: network::NetworkConfig,
+    #[cfg(target_os = "linux")]
+    #[serde(default)]
+    pub(crate) process: process::ProcessConfig,
 }
 
 const fn default_scrape_interval() -> f64 {
@@ -157,6 +164,10 @@ impl HostMetricsConfig {
 
     fn has_collector(&self, collector: Collector) -> bool {
         match &self.collectors {
+            // Per-process metrics can have a very high cardinality, so they must be asked for.
+            #[cfg(target_os = "linux")]
+            None => collector != Collector::Process,
+            #[cfg(not(target_os = "linux"))]
             None => true,
             Some(collectors) => collectors.iter().any(|&c| c == collector),
         }
@@ -214,6 +225,10 @@ impl HostMetrics {
         if self.config.has_collector(Collector::Network) {
             metrics.extend(add_collector("network", self.network_metrics().await));
         }
+        #[cfg(target_os = "linux")]
+        if self.config.has_collector(Collector::Process) {
+            metrics.extend(add_collector("process", self.process_metrics().await));
+        }
         if let Ok(hostname) = &hostname {
             for metric in &mut metrics {
                 metric.insert_tag("host".into(), hostname.into());
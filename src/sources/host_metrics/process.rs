@@ -0,0 +1,343 @@
+use std::{collections::BTreeMap, io, path::Path};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use vector_common::btreemap;
+
+use super::{FilterList, HostMetrics};
+use crate::event::metric::Metric;
+
+/// The number of clock ticks per second used by the kernel when reporting process CPU times.
+/// This is part of the userspace ABI and is 100 on every architecture Linux supports, which is
+/// also what the Prometheus `procfs` library assumes.
+const USER_HZ: f64 = 100.0;
+const KILOBYTE: f64 = 1024.0;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub(crate) struct ProcessConfig {
+    /// Matched against the process name, as found in `/proc/<pid>/comm`.
+    names: FilterList,
+    /// Matched against the full command line, arguments joined with spaces.
+    cmdlines: FilterList,
+}
+
+impl HostMetrics {
+    pub async fn process_metrics(&self) -> Vec<Metric> {
+        let procfs = heim::os::linux::procfs_root();
+        let mut entries = match fs::read_dir(&procfs).await {
+            Ok(entries) => entries,
+            Err(error) => {
+                error!(message = "Failed to list processes.", %error, internal_log_rate_secs = 60);
+                return vec![];
+            }
+        };
+
+        let now = Utc::now();
+        let mut output = Vec::new();
+        loop {
+            let entry = match entries.next_entry().await {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(error) => {
+                    error!(message = "Failed to list processes.", %error, internal_log_rate_secs = 60);
+                    break;
+                }
+            };
+            let pid = match entry.file_name().to_str().map(str::parse::<u32>) {
+                Some(Ok(pid)) => pid,
+                // Not a process directory.
+                _ => continue,
+            };
+            // Processes routinely exit between listing and reading, so failures to read a
+            // single process are not reported.
+            if let Ok(Some(process)) = self.load_process(&entry.path()).await {
+                self.push_process_metrics(&mut output, now, pid, process);
+            }
+        }
+        output
+    }
+
+    async fn load_process(&self, dir: &Path) -> io::Result<Option<Process>> {
+        let name = fs::read_to_string(dir.join("comm")).await?;
+        let name = name.trim_end_matches('\n').to_string();
+        if !self.config.process.names.contains_str(Some(&name)) {
+            return Ok(None);
+        }
+
+        let cmdline = fs::read(dir.join("cmdline")).await?;
+        let cmdline = parse_cmdline(&cmdline);
+        if !self.config.process.cmdlines.contains_str(Some(&cmdline)) {
+            return Ok(None);
+        }
+
+        let stat = parse_stat(&fs::read_to_string(dir.join("stat")).await?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed stat file"))?;
+        let status = parse_status(&fs::read_to_string(dir.join("status")).await?);
+
+        // Both of these require the same privileges as the process owner, so they are only
+        // reported when available.
+        let open_fds = count_entries(&dir.join("fd")).await.ok();
+        let io = fs::read_to_string(dir.join("io"))
+            .await
+            .ok()
+            .map(|text| parse_io(&text));
+
+        Ok(Some(Process {
+            name,
+            stat,
+            status,
+            open_fds,
+            io,
+        }))
+    }
+
+    fn push_process_metrics(
+        &self,
+        output: &mut Vec<Metric>,
+        now: DateTime<Utc>,
+        pid: u32,
+        process: Process,
+    ) {
+        let tags: BTreeMap<String, String> = btreemap! {
+            "process_name" => process.name,
+            "pid" => pid.to_string(),
+        };
+        let with_mode = |mode: &str| {
+            let mut tags = tags.clone();
+            tags.insert("mode".into(), mode.into());
+            tags
+        };
+
+        output.push(self.counter(
+            "process_cpu_seconds_total",
+            now,
+            process.stat.utime as f64 / USER_HZ,
+            with_mode("user"),
+        ));
+        output.push(self.counter(
+            "process_cpu_seconds_total",
+            now,
+            process.stat.stime as f64 / USER_HZ,
+            with_mode("system"),
+        ));
+        if let Some(rss) = process.status.rss_kb {
+            output.push(self.gauge(
+                "process_memory_rss_bytes",
+                now,
+                rss as f64 * KILOBYTE,
+                tags.clone(),
+            ));
+        }
+        if let Some(threads) = process.status.threads {
+            output.push(self.gauge("process_threads", now, threads as f64, tags.clone()));
+        }
+        if let Some(open_fds) = process.open_fds {
+            output.push(self.gauge("process_open_fds", now, open_fds as f64, tags.clone()));
+        }
+        if let Some(io) = process.io {
+            output.push(self.counter(
+                "process_io_read_bytes_total",
+                now,
+                io.read_bytes as f64,
+                tags.clone(),
+            ));
+            output.push(self.counter(
+                "process_io_write_bytes_total",
+                now,
+                io.write_bytes as f64,
+                tags,
+            ));
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Process {
+    name: String,
+    stat: ProcessStat,
+    status: ProcessStatus,
+    open_fds: Option<usize>,
+    io: Option<ProcessIo>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ProcessStat {
+    utime: u64,
+    stime: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ProcessStatus {
+    rss_kb: Option<u64>,
+    threads: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ProcessIo {
+    read_bytes: u64,
+    write_bytes: u64,
+}
+
+/// Arguments in `/proc/<pid>/cmdline` are separated, and terminated, by NUL bytes.
+fn parse_cmdline(cmdline: &[u8]) -> String {
+    cmdline
+        .split(|&byte| byte == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(String::from_utf8_lossy)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parses the CPU times out of `/proc/<pid>/stat`. The second field is the process name in
+/// parentheses, which may itself contain spaces and parentheses, so fields are counted from the
+/// last closing parenthesis.
+fn parse_stat(stat: &str) -> Option<ProcessStat> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    // `fields` starts at the third field, `state`, and `utime` and `stime` are the 14th and
+    // 15th fields.
+    let mut fields = fields.split_whitespace().skip(11);
+    Some(ProcessStat {
+        utime: fields.next()?.parse().ok()?,
+        stime: fields.next()?.parse().ok()?,
+    })
+}
+
+fn parse_status(status: &str) -> ProcessStatus {
+    let fields: BTreeMap<&str, &str> = status
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key, value.trim()))
+        .collect();
+    let number = |key: &str| {
+        fields
+            .get(key)
+            .and_then(|value| value.split_whitespace().next())
+            .and_then(|value| value.parse().ok())
+    };
+    ProcessStatus {
+        // Kernel threads have no memory map, and so no `VmRSS` line.
+        rss_kb: number("VmRSS"),
+        threads: number("Threads"),
+    }
+}
+
+fn parse_io(io: &str) -> ProcessIo {
+    let mut result = ProcessIo::default();
+    for line in io.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim().parse().unwrap_or_default();
+            match key {
+                "read_bytes" => result.read_bytes = value,
+                "write_bytes" => result.write_bytes = value,
+                _ => {}
+            }
+        }
+    }
+    result
+}
+
+async fn count_entries(dir: &Path) -> io::Result<usize> {
+    let mut entries = fs::read_dir(dir).await?;
+    let mut count = 0;
+    while entries.next_entry().await?.is_some() {
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{
+        super::{
+            tests::{count_name, count_tag},
+            FilterList, HostMetrics, HostMetricsConfig, PatternWrapper,
+        },
+        parse_cmdline, parse_io, parse_stat, parse_status, ProcessConfig, ProcessIo, ProcessStat,
+        ProcessStatus,
+    };
+
+    #[test]
+    fn parses_cmdline() {
+        assert_eq!(
+            parse_cmdline(b"/usr/bin/vector\0--config\0/etc/vector.toml\0"),
+            "/usr/bin/vector --config /etc/vector.toml"
+        );
+        assert_eq!(parse_cmdline(b""), "");
+    }
+
+    #[test]
+    fn parses_stat() {
+        let stat = "1234 (my (odd) proc) S 1 1234 1234 0 -1 4194560 2520 0 0 0 150 42 0 0 20 0 3 0 12345 1000000 500 18446744073709551615";
+        assert_eq!(
+            parse_stat(stat),
+            Some(ProcessStat {
+                utime: 150,
+                stime: 42
+            })
+        );
+        assert_eq!(parse_stat("1234 (truncated) S 1"), None);
+    }
+
+    #[test]
+    fn parses_status_and_io() {
+        let status = "Name:\tvector\nState:\tS (sleeping)\nVmRSS:\t   20480 kB\nThreads:\t8\n";
+        assert_eq!(
+            parse_status(status),
+            ProcessStatus {
+                rss_kb: Some(20480),
+                threads: Some(8)
+            }
+        );
+
+        let io = "rchar: 100\nwchar: 200\nread_bytes: 4096\nwrite_bytes: 8192\n";
+        assert_eq!(
+            parse_io(io),
+            ProcessIo {
+                read_bytes: 4096,
+                write_bytes: 8192
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn generates_process_metrics() {
+        let metrics = HostMetrics::new(HostMetricsConfig::default())
+            .process_metrics()
+            .await;
+
+        assert!(!metrics.is_empty());
+        assert_eq!(count_tag(&metrics, "pid"), metrics.len());
+        assert_eq!(count_tag(&metrics, "process_name"), metrics.len());
+        assert!(count_name(&metrics, "process_cpu_seconds_total") > 0);
+        assert!(count_name(&metrics, "process_threads") > 0);
+    }
+
+    #[tokio::test]
+    async fn process_metrics_filters_on_cmdline() {
+        let pid = std::process::id().to_string();
+        let metrics = HostMetrics::new(HostMetricsConfig {
+            process: ProcessConfig {
+                cmdlines: FilterList {
+                    includes: Some(vec![PatternWrapper::new(format!(
+                        "{}*",
+                        std::env::args().next().unwrap()
+                    ))
+                    .unwrap()]),
+                    excludes: None,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .process_metrics()
+        .await;
+
+        assert!(metrics
+            .iter()
+            .any(|metric| metric.tags().unwrap().get("pid") == Some(&pid)));
+    }
+}
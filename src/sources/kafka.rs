@@ -2,11 +2,12 @@ use std::{
     collections::{BTreeMap, HashMap},
     io::Cursor,
     sync::Arc,
+    time::Duration,
 };
 
 use async_stream::stream;
 use bytes::Bytes;
-use chrono::{TimeZone, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use codecs::{
     decoding::{DeserializerConfig, FramingConfig},
     StreamDecodingError,
@@ -15,7 +16,9 @@ use futures::{Stream, StreamExt};
 use rdkafka::{
     config::ClientConfig,
     consumer::{Consumer, StreamConsumer},
+    error::KafkaError,
     message::{BorrowedMessage, Headers, Message},
+    Offset, TopicPartitionList,
 };
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
@@ -45,6 +48,22 @@ enum BuildError {
     KafkaCreateError { source: rdkafka::error::KafkaError },
     #[snafu(display("Could not subscribe to Kafka topics: {}", source))]
     KafkaSubscribeError { source: rdkafka::error::KafkaError },
+    #[snafu(display("Could not assign Kafka partitions: {}", source))]
+    KafkaAssignError { source: rdkafka::error::KafkaError },
+    #[snafu(display("Exactly one of `topics` and `assignments` must be set"))]
+    TopicsOrAssignments,
+    #[snafu(display(
+        "Only one of `{}_offset` and `{}_timestamp` may be set for partition {} of topic {:?}",
+        bound,
+        bound,
+        partition,
+        topic
+    ))]
+    ConflictingBounds {
+        bound: &'static str,
+        topic: String,
+        partition: i32,
+    },
 }
 
 #[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
@@ -52,7 +71,12 @@ enum BuildError {
 #[serde(deny_unknown_fields)]
 pub struct KafkaSourceConfig {
     bootstrap_servers: String,
+    #[serde(default)]
     topics: Vec<String>,
+    /// Partitions to consume instead of subscribing to `topics`, which is not balanced across the
+    /// consumers of the group.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    assignments: Vec<PartitionAssignment>,
     group_id: String,
     #[serde(default = "default_auto_offset_reset")]
     auto_offset_reset: String,
@@ -87,6 +111,22 @@ pub struct KafkaSourceConfig {
     acknowledgements: AcknowledgementsConfig,
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct PartitionAssignment {
+    topic: String,
+    partition: i32,
+    /// The offset to start consuming at, defaulting to the offset committed for the group.
+    start_offset: Option<i64>,
+    /// Starts consuming at the first message at or after this time.
+    start_timestamp: Option<DateTime<Utc>>,
+    /// The offset to stop consuming at, the message at this offset is not consumed.
+    stop_offset: Option<i64>,
+    /// Stops consuming at the first message at or after this time, or at the end of the partition
+    /// when the source starts if there is none.
+    stop_timestamp: Option<DateTime<Utc>>,
+}
+
 const fn default_session_timeout_ms() -> u64 {
     10000 // default in librdkafka
 }
@@ -137,13 +177,20 @@ impl_generate_config_from_default!(KafkaSourceConfig);
 #[typetag::serde(name = "kafka")]
 impl SourceConfig for KafkaSourceConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
-        let consumer = create_consumer(self)?;
+        let consumer = Arc::new(create_consumer(self)?);
+        let stops = assign_partitions(
+            Arc::clone(&consumer),
+            self.assignments.clone(),
+            Duration::from_millis(self.socket_timeout_ms),
+        )
+        .await?;
         let decoder = DecodingConfig::new(self.framing.clone(), self.decoding.clone()).build();
         let acknowledgements = cx.do_acknowledgements(&self.acknowledgements);
 
         Ok(Box::pin(kafka_source(
             self.clone(),
             consumer,
+            stops,
             decoder,
             cx.shutdown,
             cx.out,
@@ -166,13 +213,13 @@ impl SourceConfig for KafkaSourceConfig {
 
 async fn kafka_source(
     config: KafkaSourceConfig,
-    consumer: StreamConsumer<KafkaStatisticsContext>,
+    consumer: Arc<StreamConsumer<KafkaStatisticsContext>>,
+    mut stops: PartitionStops,
     decoder: Decoder,
     shutdown: ShutdownSignal,
     mut out: SourceSender,
     acknowledgements: bool,
 ) -> Result<(), ()> {
-    let consumer = Arc::new(consumer);
     let mut finalizer = acknowledgements.then(|| {
         let consumer = Arc::clone(&consumer);
         OrderedFinalizer::new(shutdown.clone(), move |entry: FinalizerEntry| {
@@ -189,12 +236,43 @@ async fn kafka_source(
     let mut stream = consumer.stream().take_until(shutdown);
     let schema = log_schema();
 
-    while let Some(message) = stream.next().await {
+    // Bounded replays end once every partition has reached its stop offset.
+    while !stops.is_done() {
+        let message = match stream.next().await {
+            Some(message) => message,
+            None => break,
+        };
         match message {
+            // Partitions with nothing left to consume before their stop offset are only noticed
+            // at their end.
+            Err(KafkaError::PartitionEOF(_)) => match consumer.position() {
+                Ok(positions) => {
+                    for element in positions.elements() {
+                        if let Offset::Offset(offset) = element.offset() {
+                            if stops.reach(element.topic(), element.partition(), offset) {
+                                pause_partition(&consumer, element.topic(), element.partition());
+                            }
+                        }
+                    }
+                }
+                Err(error) => {
+                    emit!(KafkaReadError { error });
+                }
+            },
             Err(error) => {
                 emit!(KafkaReadError { error });
             }
+            Ok(msg) if stops.is_past(msg.topic(), msg.partition(), msg.offset()) => {
+                // Messages fetched before the partition was paused.
+                if stops.reach(msg.topic(), msg.partition(), msg.offset()) {
+                    pause_partition(&consumer, msg.topic(), msg.partition());
+                }
+            }
             Ok(msg) => {
+                if stops.reach(msg.topic(), msg.partition(), msg.offset() + 1) {
+                    pause_partition(&consumer, msg.topic(), msg.partition());
+                }
+
                 emit!(KafkaBytesReceived {
                     byte_size: msg.payload_len(),
                     protocol: "tcp",
@@ -316,9 +394,75 @@ async fn kafka_source(
         }
     }
 
+    if stops.is_done() {
+        info!(message = "Reached the stop offsets of all partitions, stopping.");
+    }
+
     Ok(())
 }
 
+/// The offsets that the assigned partitions stop being consumed at.
+#[derive(Debug, Default)]
+struct PartitionStops {
+    /// The stop offset of each partition, and whether it has been reached.
+    stops: HashMap<String, HashMap<i32, (i64, bool)>>,
+    /// Whether some partitions are consumed without stopping, which keeps the source running.
+    unbounded: bool,
+}
+
+impl PartitionStops {
+    fn insert(&mut self, topic: &str, partition: i32, stop: i64) {
+        self.stops
+            .entry(topic.to_owned())
+            .or_default()
+            .insert(partition, (stop, false));
+    }
+
+    /// Whether the message at `offset` is at or after the stop offset of its partition.
+    fn is_past(&self, topic: &str, partition: i32, offset: i64) -> bool {
+        self.stops
+            .get(topic)
+            .and_then(|partitions| partitions.get(&partition))
+            .map_or(false, |(stop, _)| offset >= *stop)
+    }
+
+    /// Marks the partition as done if the offset of its next message, `next_offset`, is at its
+    /// stop offset, returning whether it wasn't done already.
+    fn reach(&mut self, topic: &str, partition: i32, next_offset: i64) -> bool {
+        match self
+            .stops
+            .get_mut(topic)
+            .and_then(|partitions| partitions.get_mut(&partition))
+        {
+            Some((stop, reached)) if !*reached && next_offset >= *stop => {
+                *reached = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether all partitions have stop offsets and have reached them.
+    fn is_done(&self) -> bool {
+        !self.unbounded
+            && !self.stops.is_empty()
+            && self
+                .stops
+                .values()
+                .flat_map(HashMap::values)
+                .all(|(_, reached)| *reached)
+    }
+}
+
+fn pause_partition(consumer: &StreamConsumer<KafkaStatisticsContext>, topic: &str, partition: i32) {
+    debug!(message = "Reached the stop offset of partition.", %topic, %partition);
+    let mut tpl = TopicPartitionList::new();
+    tpl.add_partition(topic, partition);
+    if let Err(error) = consumer.pause(&tpl) {
+        warn!(message = "Failed to pause partition.", %topic, %partition, %error);
+    }
+}
+
 #[derive(Debug)]
 struct FinalizerEntry {
     topic: String,
@@ -336,9 +480,46 @@ impl<'a> From<BorrowedMessage<'a>> for FinalizerEntry {
     }
 }
 
+impl KafkaSourceConfig {
+    fn validate(&self) -> crate::Result<()> {
+        if self.topics.is_empty() == self.assignments.is_empty() {
+            return Err(BuildError::TopicsOrAssignments.into());
+        }
+
+        for assignment in &self.assignments {
+            let conflict =
+                if assignment.start_offset.is_some() && assignment.start_timestamp.is_some() {
+                    Some("start")
+                } else if assignment.stop_offset.is_some() && assignment.stop_timestamp.is_some() {
+                    Some("stop")
+                } else {
+                    None
+                };
+            if let Some(bound) = conflict {
+                return Err(BuildError::ConflictingBounds {
+                    bound,
+                    topic: assignment.topic.clone(),
+                    partition: assignment.partition,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn has_stops(&self) -> bool {
+        self.assignments.iter().any(|assignment| {
+            assignment.stop_offset.is_some() || assignment.stop_timestamp.is_some()
+        })
+    }
+}
+
 fn create_consumer(
     config: &KafkaSourceConfig,
 ) -> crate::Result<StreamConsumer<KafkaStatisticsContext>> {
+    config.validate()?;
+
     let mut client_config = ClientConfig::new();
     client_config
         .set("group.id", &config.group_id)
@@ -347,7 +528,7 @@ fn create_consumer(
         .set("session.timeout.ms", &config.session_timeout_ms.to_string())
         .set("socket.timeout.ms", &config.socket_timeout_ms.to_string())
         .set("fetch.wait.max.ms", &config.fetch_wait_max_ms.to_string())
-        .set("enable.partition.eof", "false")
+        .set("enable.partition.eof", &config.has_stops().to_string())
         .set("enable.auto.commit", "true")
         .set(
             "auto.commit.interval.ms",
@@ -368,12 +549,116 @@ fn create_consumer(
     let consumer = client_config
         .create_with_context::<_, StreamConsumer<_>>(KafkaStatisticsContext)
         .context(KafkaCreateSnafu)?;
-    let topics: Vec<&str> = config.topics.iter().map(|s| s.as_str()).collect();
-    consumer.subscribe(&topics).context(KafkaSubscribeSnafu)?;
+    if !config.topics.is_empty() {
+        let topics: Vec<&str> = config.topics.iter().map(|s| s.as_str()).collect();
+        consumer.subscribe(&topics).context(KafkaSubscribeSnafu)?;
+    }
 
     Ok(consumer)
 }
 
+/// Assigns the configured partitions to the consumer, if any, looking up the offsets of their
+/// start and stop times.
+async fn assign_partitions(
+    consumer: Arc<StreamConsumer<KafkaStatisticsContext>>,
+    assignments: Vec<PartitionAssignment>,
+    timeout: Duration,
+) -> crate::Result<PartitionStops> {
+    if assignments.is_empty() {
+        return Ok(PartitionStops::default());
+    }
+
+    let stops = tokio::task::spawn_blocking(move || {
+        let start_times = offsets_for_times(
+            &consumer,
+            assignments.iter().filter_map(|assignment| {
+                let timestamp = assignment.start_timestamp?;
+                Some((assignment.topic.as_str(), assignment.partition, timestamp))
+            }),
+            timeout,
+        )?;
+        let stop_times = offsets_for_times(
+            &consumer,
+            assignments.iter().filter_map(|assignment| {
+                let timestamp = assignment.stop_timestamp?;
+                Some((assignment.topic.as_str(), assignment.partition, timestamp))
+            }),
+            timeout,
+        )?;
+
+        let mut tpl = TopicPartitionList::new();
+        let mut stops = PartitionStops::default();
+        for assignment in &assignments {
+            let key = (assignment.topic.clone(), assignment.partition);
+
+            let start = match (assignment.start_offset, start_times.get(&key)) {
+                (Some(offset), _) => Offset::Offset(offset),
+                // This is the end of the partition if no message is at or after the time.
+                (None, Some(offset)) => *offset,
+                (None, None) => Offset::Stored,
+            };
+            tpl.add_partition_offset(&assignment.topic, assignment.partition, start)?;
+
+            let stop = match (assignment.stop_offset, stop_times.get(&key)) {
+                (Some(offset), _) => Some(offset),
+                (None, Some(Offset::Offset(offset))) => Some(*offset),
+                // No message is at or after the time yet, so the partition is consumed up to
+                // its current end.
+                (None, Some(_)) => Some(
+                    consumer
+                        .fetch_watermarks(&assignment.topic, assignment.partition, timeout)?
+                        .1,
+                ),
+                (None, None) => None,
+            };
+            match stop {
+                Some(stop) => stops.insert(&assignment.topic, assignment.partition, stop),
+                None => stops.unbounded = true,
+            }
+        }
+
+        consumer.assign(&tpl)?;
+        Ok::<_, KafkaError>(stops)
+    })
+    .await?
+    .context(KafkaAssignSnafu)?;
+
+    Ok(stops)
+}
+
+/// Looks up the offsets of the first messages at or after the given times, which is
+/// `Offset::End` for partitions without any.
+fn offsets_for_times<'a>(
+    consumer: &StreamConsumer<KafkaStatisticsContext>,
+    times: impl Iterator<Item = (&'a str, i32, DateTime<Utc>)>,
+    timeout: Duration,
+) -> Result<HashMap<(String, i32), Offset>, KafkaError> {
+    let mut tpl = TopicPartitionList::new();
+    for (topic, partition, timestamp) in times {
+        tpl.add_partition_offset(
+            topic,
+            partition,
+            Offset::Offset(timestamp.timestamp_millis()),
+        )?;
+    }
+    if tpl.count() == 0 {
+        return Ok(HashMap::new());
+    }
+
+    consumer
+        .offsets_for_times(tpl, timeout)?
+        .elements()
+        .iter()
+        .map(|element| {
+            element.error()?;
+            Ok((
+                (element.topic().to_owned(), element.partition()),
+                element.offset(),
+            ))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -424,6 +709,62 @@ mod test {
         };
         assert!(create_consumer(&config).is_err());
     }
+
+    #[tokio::test]
+    async fn consumer_create_validates_assignments() {
+        let assignment = PartitionAssignment {
+            topic: "topic".into(),
+            partition: 0,
+            start_offset: Some(10),
+            stop_timestamp: Some(Utc::now()),
+            ..Default::default()
+        };
+        let config = KafkaSourceConfig {
+            topics: Vec::new(),
+            assignments: vec![assignment.clone()],
+            ..make_config("topic", "group")
+        };
+        assert!(create_consumer(&config).is_ok());
+
+        // Topics and assignments are exclusive.
+        let config = KafkaSourceConfig {
+            assignments: vec![assignment.clone()],
+            ..make_config("topic", "group")
+        };
+        assert!(create_consumer(&config).is_err());
+
+        let config = KafkaSourceConfig {
+            topics: Vec::new(),
+            assignments: vec![PartitionAssignment {
+                stop_offset: Some(100),
+                ..assignment
+            }],
+            ..make_config("topic", "group")
+        };
+        assert!(create_consumer(&config).is_err());
+    }
+
+    #[test]
+    fn partition_stops() {
+        let mut stops = PartitionStops::default();
+        stops.insert("a", 0, 10);
+        stops.insert("a", 1, 0);
+        assert!(!stops.is_done());
+
+        assert!(!stops.is_past("a", 0, 8));
+        assert!(!stops.reach("a", 0, 9));
+        assert!(stops.reach("a", 0, 10));
+        assert!(!stops.reach("a", 0, 10));
+        assert!(stops.is_past("a", 0, 10));
+        assert!(!stops.is_past("b", 0, 10));
+        assert!(!stops.is_done());
+
+        assert!(stops.reach("a", 1, 0));
+        assert!(stops.is_done());
+
+        stops.unbounded = true;
+        assert!(!stops.is_done());
+    }
 }
 
 #[cfg(feature = "kafka-integration-tests")]
@@ -445,7 +786,9 @@ mod integration_test {
     use super::{test::*, *};
     use crate::{
         shutdown::ShutdownSignal,
-        test_util::{collect_n, components::assert_source_compliance, random_string},
+        test_util::{
+            collect_n, collect_ready, components::assert_source_compliance, random_string,
+        },
         SourceSender,
     };
 
@@ -517,10 +860,11 @@ mod integration_test {
         let events = assert_source_compliance(&["protocol", "topic", "partition"], async move {
             let (trigger_shutdown, shutdown, shutdown_done) = ShutdownSignal::new_wired();
             let (tx, rx) = SourceSender::new_test_finalize(EventStatus::Delivered);
-            let consumer = create_consumer(&config).unwrap();
+            let consumer = Arc::new(create_consumer(&config).unwrap());
             tokio::spawn(kafka_source(
                 config,
                 consumer,
+                PartitionStops::default(),
                 crate::codecs::Decoder::default(),
                 shutdown,
                 tx,
@@ -572,4 +916,68 @@ mod integration_test {
             assert_eq!(event.as_log()["headers"], Value::from(expected_headers));
         }
     }
+
+    #[tokio::test]
+    async fn replays_assigned_partition_between_offsets() {
+        let topic = format!("test-topic-{}", random_string(10));
+        let group_id = format!("test-group-{}", random_string(10));
+        let now = Utc::now();
+
+        send_events(
+            topic.clone(),
+            10,
+            "my key",
+            "my message",
+            now.timestamp_millis(),
+            "my header",
+            "my header value",
+        )
+        .await;
+
+        let config = KafkaSourceConfig {
+            topics: Vec::new(),
+            assignments: vec![PartitionAssignment {
+                topic: topic.clone(),
+                partition: 0,
+                start_offset: Some(2),
+                stop_offset: Some(7),
+                ..Default::default()
+            }],
+            ..make_config(&topic, &group_id)
+        };
+
+        let (tx, rx) = SourceSender::new_test_finalize(EventStatus::Delivered);
+        let consumer = Arc::new(create_consumer(&config).unwrap());
+        let stops = assign_partitions(
+            Arc::clone(&consumer),
+            config.assignments.clone(),
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+
+        // The source ends on its own once the stop offset is reached.
+        tokio::time::timeout(
+            Duration::from_secs(30),
+            kafka_source(
+                config,
+                consumer,
+                stops,
+                crate::codecs::Decoder::default(),
+                ShutdownSignal::noop(),
+                tx,
+                false,
+            ),
+        )
+        .await
+        .expect("Source did not stop at the stop offset")
+        .unwrap();
+
+        let events = collect_ready(rx).await;
+        let offsets = events
+            .iter()
+            .map(|event| event.as_log()["offset"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, (2i64..7).map(Value::from).collect::<Vec<_>>());
+    }
 }
@@ -0,0 +1,448 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use chrono::Utc;
+use codecs::decoding::{DeserializerConfig, FramingConfig, StreamDecodingError};
+use futures::StreamExt;
+use rumqttc::{
+    AsyncClient, ConnectionError, Event as MqttEvent, EventLoop, Outgoing, Packet, Publish,
+    SubscribeFilter,
+};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use tokio::sync::mpsc;
+use tokio_util::codec::FramedRead;
+use vector_core::ByteSizeOf;
+
+use crate::{
+    codecs::{Decoder, DecodingConfig},
+    config::{log_schema, GenerateConfig, Output, SourceConfig, SourceContext, SourceDescription},
+    event::Event,
+    internal_events::{
+        prelude::error_stage, BytesReceived, EventsReceived, MqttAcknowledgementError,
+        MqttConnectionError, MqttSubscribeError, StreamClosedError,
+    },
+    mqtt::{MqttConfigError, MqttConnectionConfig, MqttQoS},
+    serde::{default_decoding, default_framing_message_based},
+    shutdown::ShutdownSignal,
+    SourceSender,
+};
+
+/// How long to wait before polling the event loop again after a connection error, which makes
+/// it reconnect.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// How long the event loop is given to send the disconnection when the source stops.
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("MQTT Config Error: {}", source))]
+    Config { source: MqttConfigError },
+    #[snafu(display("At least one topic filter is required"))]
+    NoTopics,
+    #[snafu(display("Invalid topic filter {:?}", filter))]
+    InvalidTopicFilter { filter: String },
+}
+
+#[derive(Clone, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+struct MqttSourceConfig {
+    #[serde(flatten)]
+    connection: MqttConnectionConfig,
+    topics: Vec<String>,
+    #[serde(default)]
+    qos: MqttQoS,
+    #[serde(default = "crate::serde::default_true")]
+    #[derivative(Default(value = "true"))]
+    clean_session: bool,
+    #[serde(default = "default_topic_key")]
+    #[derivative(Default(value = "default_topic_key()"))]
+    topic_key: String,
+    #[serde(default = "default_framing_message_based")]
+    #[derivative(Default(value = "default_framing_message_based()"))]
+    framing: FramingConfig,
+    #[serde(default = "default_decoding")]
+    #[derivative(Default(value = "default_decoding()"))]
+    decoding: DeserializerConfig,
+}
+
+fn default_topic_key() -> String {
+    String::from("topic")
+}
+
+inventory::submit! {
+    SourceDescription::new::<MqttSourceConfig>("mqtt")
+}
+
+impl GenerateConfig for MqttSourceConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            host = "localhost"
+            port = 1883
+            topics = ["vector/#"]"#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "mqtt")]
+impl SourceConfig for MqttSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let filters = self.subscribe_filters()?;
+        // Messages are only acknowledged once they have been handed to the topology, so that a
+        // broker holding a persistent session redelivers them if Vector stops in between.
+        let (client, eventloop) = self
+            .connection
+            .connect(self.clean_session, true)
+            .context(ConfigSnafu)?;
+        let decoder = DecodingConfig::new(self.framing.clone(), self.decoding.clone()).build();
+
+        Ok(Box::pin(mqtt_source(
+            client,
+            eventloop,
+            filters,
+            self.topic_key.clone(),
+            decoder,
+            cx.shutdown,
+            cx.out,
+        )))
+    }
+
+    fn outputs(&self) -> Vec<Output> {
+        vec![Output::default(self.decoding.output_type())]
+    }
+
+    fn source_type(&self) -> &'static str {
+        "mqtt"
+    }
+
+    fn can_acknowledge(&self) -> bool {
+        false
+    }
+}
+
+impl MqttSourceConfig {
+    fn subscribe_filters(&self) -> Result<Vec<SubscribeFilter>, BuildError> {
+        if self.topics.is_empty() {
+            return Err(BuildError::NoTopics);
+        }
+        self.topics
+            .iter()
+            .map(|filter| {
+                if valid_topic_filter(filter) {
+                    Ok(SubscribeFilter::new(filter.clone(), self.qos.into()))
+                } else {
+                    Err(BuildError::InvalidTopicFilter {
+                        filter: filter.clone(),
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+/// Checks the wildcard rules for topic filters: `+` must occupy a whole level, and `#` must
+/// occupy the last level.
+fn valid_topic_filter(filter: &str) -> bool {
+    if filter.is_empty() {
+        return false;
+    }
+    let levels = filter.split('/').collect::<Vec<_>>();
+    levels
+        .iter()
+        .enumerate()
+        .all(|(index, level)| match *level {
+            "+" => true,
+            "#" => index == levels.len() - 1,
+            level => !level.contains(|c| c == '+' || c == '#'),
+        })
+}
+
+async fn mqtt_source(
+    client: AsyncClient,
+    eventloop: EventLoop,
+    filters: Vec<SubscribeFilter>,
+    topic_key: String,
+    decoder: Decoder,
+    mut shutdown: ShutdownSignal,
+    mut out: SourceSender,
+) -> Result<(), ()> {
+    // The event loop runs on its own, so that it keeps answering the broker, keep alive pings
+    // included, while sending the messages downstream waits on backpressure. QoS 1 and 2
+    // messages are acknowledged only once they have been sent downstream, which bounds how many
+    // of them the broker has in flight.
+    let (publishes_tx, mut publishes) = mpsc::unbounded_channel();
+    let mut connection = tokio::spawn(run_eventloop(
+        client.clone(),
+        eventloop,
+        filters,
+        publishes_tx,
+    ));
+
+    let mut result = Ok(());
+    loop {
+        let publish = tokio::select! {
+            _ = &mut shutdown => break,
+            publish = publishes.recv() => match publish {
+                Some(publish) => publish,
+                None => break,
+            },
+        };
+
+        if handle_message(&publish, &topic_key, &decoder, &mut out)
+            .await
+            .is_err()
+        {
+            result = Err(());
+            break;
+        }
+        if let Err(error) = client.try_ack(&publish) {
+            emit!(MqttAcknowledgementError { error });
+        }
+    }
+
+    // Best effort, the broker cleans up after an abrupt disconnection anyway.
+    let _ = client.try_disconnect();
+    if tokio::time::timeout(DISCONNECT_TIMEOUT, &mut connection)
+        .await
+        .is_err()
+    {
+        connection.abort();
+    }
+    result
+}
+
+/// Drives the connection, forwarding the received messages, until the client disconnects or the
+/// source stops.
+async fn run_eventloop(
+    client: AsyncClient,
+    mut eventloop: EventLoop,
+    filters: Vec<SubscribeFilter>,
+    publishes: mpsc::UnboundedSender<Publish>,
+) {
+    loop {
+        match eventloop.poll().await {
+            Ok(MqttEvent::Incoming(Packet::ConnAck(_))) => {
+                // Subscriptions are renewed on every connection, as the broker forgets them when
+                // the session is clean or has expired.
+                if let Err(error) = client.try_subscribe_many(filters.clone()) {
+                    emit!(MqttSubscribeError { error });
+                }
+            }
+            Ok(MqttEvent::Incoming(Packet::Publish(publish))) => {
+                if publishes.send(publish).is_err() {
+                    break;
+                }
+            }
+            Ok(MqttEvent::Outgoing(Outgoing::Disconnect)) => break,
+            Ok(_) => {}
+            Err(ConnectionError::RequestsDone) => break,
+            Err(error) => {
+                if publishes.is_closed() {
+                    break;
+                }
+                emit!(MqttConnectionError {
+                    error,
+                    stage: error_stage::RECEIVING,
+                });
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+async fn handle_message(
+    publish: &Publish,
+    topic_key: &str,
+    decoder: &Decoder,
+    out: &mut SourceSender,
+) -> Result<(), ()> {
+    emit!(BytesReceived {
+        byte_size: publish.payload.len(),
+        protocol: "mqtt",
+    });
+
+    let now = Utc::now();
+    let mut stream = FramedRead::new(publish.payload.as_ref(), decoder.clone());
+    while let Some(next) = stream.next().await {
+        match next {
+            Ok((events, _byte_size)) => {
+                let count = events.len();
+                emit!(EventsReceived {
+                    byte_size: events.size_of(),
+                    count,
+                });
+
+                let events = events.into_iter().map(|mut event| {
+                    if let Event::Log(ref mut log) = event {
+                        log.try_insert(log_schema().source_type_key(), Bytes::from("mqtt"));
+                        log.try_insert(log_schema().timestamp_key(), now);
+                        log.try_insert(topic_key, publish.topic.clone());
+                    }
+                    event
+                });
+
+                out.send_batch(events).await.map_err(|error| {
+                    emit!(StreamClosedError { error, count });
+                })?;
+            }
+            Err(error) => {
+                // Error is logged by `crate::codecs::Decoder`, no further
+                // handling is needed here.
+                if !error.can_continue() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<MqttSourceConfig>();
+    }
+
+    #[test]
+    fn validates_topic_filters() {
+        for filter in &[
+            "sensors",
+            "sensors/+/temperature",
+            "sensors/#",
+            "#",
+            "+",
+            "/a//b",
+        ] {
+            assert!(valid_topic_filter(filter), "{:?} should be valid", filter);
+        }
+        for filter in &["", "sensors/#/temperature", "sensors+", "sensors/temp#"] {
+            assert!(
+                !valid_topic_filter(filter),
+                "{:?} should be invalid",
+                filter
+            );
+        }
+    }
+
+    #[test]
+    fn requires_topics() {
+        let config = MqttSourceConfig::default();
+        assert!(matches!(
+            config.subscribe_filters(),
+            Err(BuildError::NoTopics)
+        ));
+
+        let config = MqttSourceConfig {
+            topics: vec!["sensors/#".into(), "bad/#/filter".into()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.subscribe_filters(),
+            Err(BuildError::InvalidTopicFilter { .. })
+        ));
+    }
+}
+
+#[cfg(feature = "mqtt-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use rumqttc::QoS;
+
+    use super::*;
+    use crate::test_util::{
+        collect_n,
+        components::{assert_source_compliance, SOURCE_TAGS},
+        random_string,
+    };
+
+    fn mqtt_host() -> String {
+        std::env::var("MQTT_HOST").unwrap_or_else(|_| "127.0.0.1".to_owned())
+    }
+
+    fn make_config(topic: &str, qos: MqttQoS) -> MqttSourceConfig {
+        MqttSourceConfig {
+            connection: MqttConnectionConfig {
+                host: mqtt_host(),
+                client_id: format!("vector-source-{}", random_string(10)),
+                ..Default::default()
+            },
+            topics: vec![format!("{}/#", topic)],
+            qos,
+            ..Default::default()
+        }
+    }
+
+    /// Publishes messages from a separate client until the source has received them all, since
+    /// messages published before its subscription is in place are not delivered.
+    async fn publish_and_check(config: MqttSourceConfig, topic: &str) {
+        let publisher_config = MqttConnectionConfig {
+            client_id: format!("vector-publisher-{}", random_string(10)),
+            ..config.connection.clone()
+        };
+        let (publisher, mut publisher_loop) = publisher_config.connect(true, false).unwrap();
+        tokio::spawn(async move { while publisher_loop.poll().await.is_ok() {} });
+
+        let filters = config.subscribe_filters().unwrap();
+        let (client, eventloop) = config
+            .connection
+            .connect(config.clean_session, true)
+            .unwrap();
+        let decoder = DecodingConfig::new(config.framing.clone(), config.decoding.clone()).build();
+        let topic = format!("{}/sensor-1", topic);
+        let expected_topic = topic.clone();
+
+        let events = assert_source_compliance(&SOURCE_TAGS, async move {
+            let (tx, rx) = SourceSender::new_test();
+            tokio::spawn(mqtt_source(
+                client,
+                eventloop,
+                filters,
+                config.topic_key.clone(),
+                decoder,
+                ShutdownSignal::noop(),
+                tx,
+            ));
+
+            let publishing = tokio::spawn(async move {
+                loop {
+                    publisher
+                        .publish(&topic, QoS::AtLeastOnce, false, "my message")
+                        .await
+                        .unwrap();
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            });
+            let events = collect_n(rx, 1).await;
+            publishing.abort();
+            events
+        })
+        .await;
+
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "my message".into());
+        assert_eq!(log["topic"], expected_topic.into());
+    }
+
+    #[tokio::test]
+    async fn mqtt_receives_messages() {
+        let topic = format!("test-{}", random_string(10));
+        publish_and_check(make_config(&topic, MqttQoS::AtMostOnce), &topic).await;
+    }
+
+    #[tokio::test]
+    async fn mqtt_receives_messages_exactly_once() {
+        let topic = format!("test-{}", random_string(10));
+        let config = MqttSourceConfig {
+            clean_session: false,
+            ..make_config(&topic, MqttQoS::ExactlyOnce)
+        };
+        publish_and_check(config, &topic).await;
+    }
+}
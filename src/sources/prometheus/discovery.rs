@@ -0,0 +1,519 @@
+//! Target discovery for the `prometheus_scrape` source.
+//!
+//! Discovered targets are kept in a [`TargetSet`] which is refreshed in the background by one
+//! task per configured provider, and read on every scrape tick, so that targets come and go
+//! without a configuration reload.
+
+use std::{
+    collections::BTreeMap,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use futures::{future::BoxFuture, FutureExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use tokio_stream::wrappers::IntervalStream;
+use trust_dns_resolver::TokioAsyncResolver;
+
+use crate::{
+    internal_events::{PrometheusDiscoveryError, PrometheusTargetsDiscovered},
+    shutdown::ShutdownSignal,
+};
+
+/// Labels starting with this prefix are reserved for discovery metadata and are never
+/// attached to scraped metrics.
+const RESERVED_LABEL_PREFIX: &str = "__";
+const SCHEME_LABEL: &str = "__scheme__";
+const METRICS_PATH_LABEL: &str = "__metrics_path__";
+
+#[derive(Debug, Snafu)]
+pub(super) enum DiscoveryError {
+    #[snafu(display("Invalid glob pattern {:?}: {}", pattern, source))]
+    InvalidPattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+    #[snafu(display("Unable to read {:?}: {}", path, source))]
+    ReadFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Unable to parse {:?} as JSON: {}", path, source))]
+    ParseJson {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[snafu(display("Unable to parse {:?} as YAML: {}", path, source))]
+    ParseYaml {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+    #[snafu(display("Invalid target {:?}: {}", target, source))]
+    InvalidTarget {
+        target: String,
+        source: http::Error,
+    },
+    #[snafu(display("Unable to create DNS resolver: {}", source))]
+    CreateResolver {
+        source: trust_dns_resolver::error::ResolveError,
+    },
+    #[snafu(display("Unable to resolve {:?}: {}", name, source))]
+    Resolve {
+        name: String,
+        source: trust_dns_resolver::error::ResolveError,
+    },
+    #[snafu(display("A `port` is required when resolving {} records", record_type))]
+    MissingPort { record_type: &'static str },
+}
+
+/// Discovers targets from Prometheus `file_sd` compatible JSON or YAML files.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub(super) struct FileSdConfig {
+    /// Paths of the files to read, glob patterns are allowed.
+    pub files: Vec<String>,
+    #[serde(default = "default_refresh_interval_secs")]
+    pub refresh_interval_secs: u64,
+    #[serde(default = "default_scheme")]
+    pub scheme: String,
+    #[serde(default = "default_metrics_path")]
+    pub metrics_path: String,
+}
+
+/// Discovers targets by periodically resolving DNS SRV, A or AAAA records.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub(super) struct DnsSdConfig {
+    pub names: Vec<String>,
+    #[serde(default, rename = "type")]
+    pub record_type: DnsRecordType,
+    /// The port to scrape, required for A and AAAA records as they don't carry one.
+    pub port: Option<u16>,
+    #[serde(default = "default_refresh_interval_secs")]
+    pub refresh_interval_secs: u64,
+    #[serde(default = "default_scheme")]
+    pub scheme: String,
+    #[serde(default = "default_metrics_path")]
+    pub metrics_path: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub(super) enum DnsRecordType {
+    Srv,
+    A,
+    Aaaa,
+}
+
+impl Default for DnsRecordType {
+    fn default() -> Self {
+        Self::Srv
+    }
+}
+
+impl DnsRecordType {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Srv => "SRV",
+            Self::A => "A",
+            Self::Aaaa => "AAAA",
+        }
+    }
+}
+
+const fn default_refresh_interval_secs() -> u64 {
+    30
+}
+
+fn default_scheme() -> String {
+    "http".to_string()
+}
+
+fn default_metrics_path() -> String {
+    "/metrics".to_string()
+}
+
+/// A single scrape target along with the labels to attach to its metrics.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct Target {
+    pub url: http::Uri,
+    pub labels: BTreeMap<String, String>,
+}
+
+impl Target {
+    pub(super) fn new(url: http::Uri) -> Self {
+        Self {
+            url,
+            labels: BTreeMap::new(),
+        }
+    }
+}
+
+/// The set of targets to scrape, made of the statically configured endpoints and the
+/// latest targets reported by each discovery provider.
+#[derive(Clone, Debug)]
+pub(super) struct TargetSet {
+    static_targets: Arc<Vec<Target>>,
+    discovered: Arc<RwLock<BTreeMap<String, Vec<Target>>>>,
+}
+
+impl TargetSet {
+    pub(super) fn new(static_targets: Vec<Target>) -> Self {
+        Self {
+            static_targets: Arc::new(static_targets),
+            discovered: Arc::default(),
+        }
+    }
+
+    /// Returns every target currently known, deduplicated by URL. Static endpoints win over
+    /// discovered ones, and earlier providers win over later ones.
+    pub(super) fn snapshot(&self) -> Vec<Target> {
+        let discovered = self.discovered.read().expect("poisoned lock");
+        let mut targets: Vec<Target> = Vec::with_capacity(
+            self.static_targets.len() + discovered.values().map(Vec::len).sum::<usize>(),
+        );
+        for target in self
+            .static_targets
+            .iter()
+            .chain(discovered.values().flatten())
+        {
+            if !targets.iter().any(|existing| existing.url == target.url) {
+                targets.push(target.clone());
+            }
+        }
+        targets
+    }
+
+    fn update(&self, provider: &str, targets: Vec<Target>) {
+        let mut discovered = self.discovered.write().expect("poisoned lock");
+        if discovered.get(provider) != Some(&targets) {
+            emit!(PrometheusTargetsDiscovered {
+                provider,
+                count: targets.len(),
+            });
+            discovered.insert(provider.to_string(), targets);
+        }
+    }
+}
+
+type Discover = Box<dyn Fn() -> BoxFuture<'static, Result<Vec<Target>, DiscoveryError>> + Send>;
+
+/// Spawns a background refresh task for each configured provider. Tasks stop when the source
+/// shuts down.
+pub(super) fn spawn(
+    file_sd: Option<&FileSdConfig>,
+    dns_sd: Option<&DnsSdConfig>,
+    targets: &TargetSet,
+    shutdown: ShutdownSignal,
+) -> crate::Result<()> {
+    // Validate everything up front so that no task is left running if the build fails.
+    if let Some(config) = file_sd {
+        for pattern in &config.files {
+            glob::Pattern::new(pattern).context(InvalidPatternSnafu { pattern })?;
+        }
+    }
+    let resolver = match dns_sd {
+        Some(config) => {
+            if config.record_type != DnsRecordType::Srv && config.port.is_none() {
+                return Err(DiscoveryError::MissingPort {
+                    record_type: config.record_type.as_str(),
+                }
+                .into());
+            }
+            Some(TokioAsyncResolver::tokio_from_system_conf().context(CreateResolverSnafu)?)
+        }
+        None => None,
+    };
+
+    if let Some(config) = file_sd {
+        let refresh_interval_secs = config.refresh_interval_secs;
+        let config = config.clone();
+        let discover: Discover = Box::new(move || {
+            let config = config.clone();
+            async move { discover_files(&config) }.boxed()
+        });
+        spawn_refresh(
+            "file_sd",
+            refresh_interval_secs,
+            discover,
+            targets.clone(),
+            shutdown.clone(),
+        );
+    }
+
+    if let (Some(config), Some(resolver)) = (dns_sd, resolver) {
+        let refresh_interval_secs = config.refresh_interval_secs;
+        let config = config.clone();
+        let discover: Discover = Box::new(move || {
+            let config = config.clone();
+            let resolver = resolver.clone();
+            async move { discover_dns(&resolver, &config).await }.boxed()
+        });
+        spawn_refresh(
+            "dns_sd",
+            refresh_interval_secs,
+            discover,
+            targets.clone(),
+            shutdown,
+        );
+    }
+
+    Ok(())
+}
+
+fn spawn_refresh(
+    provider: &'static str,
+    refresh_interval_secs: u64,
+    discover: Discover,
+    targets: TargetSet,
+    shutdown: ShutdownSignal,
+) {
+    let interval = Duration::from_secs(refresh_interval_secs.max(1));
+    tokio::spawn(async move {
+        let mut ticks = IntervalStream::new(tokio::time::interval(interval)).take_until(shutdown);
+        while ticks.next().await.is_some() {
+            match discover().await {
+                Ok(discovered) => targets.update(provider, discovered),
+                // Keep the previously discovered targets, a transient failure shouldn't
+                // stop us from scraping them.
+                Err(error) => emit!(PrometheusDiscoveryError {
+                    provider,
+                    error: error.into(),
+                }),
+            }
+        }
+    });
+}
+
+/// A target group as found in Prometheus `file_sd` files.
+#[derive(Deserialize, Debug)]
+struct TargetGroup {
+    targets: Vec<String>,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+}
+
+fn discover_files(config: &FileSdConfig) -> Result<Vec<Target>, DiscoveryError> {
+    let mut targets = Vec::new();
+    for pattern in &config.files {
+        let paths = glob::glob(pattern).context(InvalidPatternSnafu { pattern })?;
+        // Unreadable directory entries are skipped, like a missing file would be.
+        for path in paths.flatten() {
+            for group in read_target_groups(&path)? {
+                for address in &group.targets {
+                    targets.push(build_target(
+                        address,
+                        &config.scheme,
+                        &config.metrics_path,
+                        &group.labels,
+                    )?);
+                }
+            }
+        }
+    }
+    Ok(targets)
+}
+
+fn read_target_groups(path: &Path) -> Result<Vec<TargetGroup>, DiscoveryError> {
+    let content = std::fs::read(path).context(ReadFileSnafu { path })?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yml" | "yaml") => serde_yaml::from_slice(&content).context(ParseYamlSnafu { path }),
+        _ => serde_json::from_slice(&content).context(ParseJsonSnafu { path }),
+    }
+}
+
+async fn discover_dns(
+    resolver: &TokioAsyncResolver,
+    config: &DnsSdConfig,
+) -> Result<Vec<Target>, DiscoveryError> {
+    let mut targets = Vec::new();
+    for name in &config.names {
+        let addresses: Vec<String> = match config.record_type {
+            DnsRecordType::Srv => resolver
+                .srv_lookup(name.as_str())
+                .await
+                .context(ResolveSnafu { name })?
+                .iter()
+                .map(|srv| {
+                    let host = srv.target().to_utf8();
+                    format!("{}:{}", host.trim_end_matches('.'), srv.port())
+                })
+                .collect(),
+            DnsRecordType::A => resolver
+                .ipv4_lookup(name.as_str())
+                .await
+                .context(ResolveSnafu { name })?
+                .iter()
+                .map(|ip| socket_address(IpAddr::V4(*ip), config.port))
+                .collect(),
+            DnsRecordType::Aaaa => resolver
+                .ipv6_lookup(name.as_str())
+                .await
+                .context(ResolveSnafu { name })?
+                .iter()
+                .map(|ip| socket_address(IpAddr::V6(*ip), config.port))
+                .collect(),
+        };
+
+        let labels = BTreeMap::from([("__meta_dns_name".to_string(), name.clone())]);
+        for address in addresses {
+            targets.push(build_target(
+                &address,
+                &config.scheme,
+                &config.metrics_path,
+                &labels,
+            )?);
+        }
+    }
+    Ok(targets)
+}
+
+fn socket_address(ip: IpAddr, port: Option<u16>) -> String {
+    // The port presence is validated when the source is built.
+    let port = port.unwrap_or_default();
+    match ip {
+        IpAddr::V4(ip) => format!("{}:{}", ip, port),
+        IpAddr::V6(ip) => format!("[{}]:{}", ip, port),
+    }
+}
+
+/// Builds a target from a `host:port` address. The `__scheme__` and `__metrics_path__` labels
+/// override the configured defaults, and all reserved labels are dropped from the result.
+fn build_target(
+    address: &str,
+    scheme: &str,
+    metrics_path: &str,
+    labels: &BTreeMap<String, String>,
+) -> Result<Target, DiscoveryError> {
+    let scheme = labels.get(SCHEME_LABEL).map_or(scheme, String::as_str);
+    let metrics_path = labels
+        .get(METRICS_PATH_LABEL)
+        .map_or(metrics_path, String::as_str);
+
+    let url = http::Uri::builder()
+        .scheme(scheme)
+        .authority(address)
+        .path_and_query(metrics_path)
+        .build()
+        .context(InvalidTargetSnafu { target: address })?;
+
+    let labels = labels
+        .iter()
+        .filter(|(name, _)| !name.starts_with(RESERVED_LABEL_PREFIX))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+
+    Ok(Target { url, labels })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn write_file(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        path
+    }
+
+    fn file_sd_config(files: Vec<String>) -> FileSdConfig {
+        FileSdConfig {
+            files,
+            refresh_interval_secs: default_refresh_interval_secs(),
+            scheme: default_scheme(),
+            metrics_path: default_metrics_path(),
+        }
+    }
+
+    #[test]
+    fn discovers_json_and_yaml_files() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        write_file(
+            &dir,
+            "targets.json",
+            r#"[{"targets": ["10.0.0.1:9100", "10.0.0.2:9100"], "labels": {"env": "prod"}}]"#,
+        );
+        write_file(
+            &dir,
+            "targets.yml",
+            "- targets: ['10.0.0.3:8080']\n  labels:\n    __metrics_path__: /stats\n    __scheme__: https\n    job: api\n",
+        );
+
+        let pattern = format!("{}/targets.*", dir.display());
+        let mut targets = discover_files(&file_sd_config(vec![pattern])).unwrap();
+        targets.sort_by_key(|target| target.url.to_string());
+
+        assert_eq!(
+            targets,
+            vec![
+                Target {
+                    url: "http://10.0.0.1:9100/metrics".parse().unwrap(),
+                    labels: BTreeMap::from([("env".to_string(), "prod".to_string())]),
+                },
+                Target {
+                    url: "http://10.0.0.2:9100/metrics".parse().unwrap(),
+                    labels: BTreeMap::from([("env".to_string(), "prod".to_string())]),
+                },
+                Target {
+                    url: "https://10.0.0.3:8080/stats".parse().unwrap(),
+                    labels: BTreeMap::from([("job".to_string(), "api".to_string())]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_malformed_files() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = write_file(&dir, "broken.json", r#"[{"targets": "#);
+
+        let error = discover_files(&file_sd_config(vec![path.display().to_string()])).unwrap_err();
+        assert!(matches!(error, DiscoveryError::ParseJson { .. }));
+    }
+
+    #[test]
+    fn snapshot_deduplicates_targets() {
+        let targets = TargetSet::new(vec![Target::new(
+            "http://localhost:9090/metrics".parse().unwrap(),
+        )]);
+        targets.update(
+            "file_sd",
+            vec![
+                Target {
+                    url: "http://localhost:9090/metrics".parse().unwrap(),
+                    labels: BTreeMap::from([("env".to_string(), "prod".to_string())]),
+                },
+                Target::new("http://localhost:9091/metrics".parse().unwrap()),
+            ],
+        );
+
+        let snapshot = targets.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert!(snapshot[0].labels.is_empty());
+        assert_eq!(snapshot[1].url, "http://localhost:9091/metrics");
+
+        targets.update("file_sd", vec![]);
+        assert_eq!(targets.snapshot().len(), 1);
+    }
+
+    #[test]
+    fn formats_socket_addresses() {
+        assert_eq!(
+            socket_address("10.0.0.1".parse().unwrap(), Some(9100)),
+            "10.0.0.1:9100"
+        );
+        assert_eq!(
+            socket_address("::1".parse().unwrap(), Some(9100)),
+            "[::1]:9100"
+        );
+    }
+}
@@ -11,11 +11,15 @@ use snafu::{ResultExt, Snafu};
 use tokio_stream::wrappers::IntervalStream;
 use vector_core::ByteSizeOf;
 
-use super::parser;
+use super::{
+    discovery::{self, DnsSdConfig, FileSdConfig, Target, TargetSet},
+    parser,
+};
 use crate::{
     config::{
         self, GenerateConfig, Output, ProxyConfig, SourceConfig, SourceContext, SourceDescription,
     },
+    event::Metric,
     http::{Auth, HttpClient},
     internal_events::{
         EndpointBytesReceived, PrometheusEventsReceived, PrometheusHttpError,
@@ -40,13 +44,17 @@ static NOT_FOUND_NO_PATH: &str = "No path is set on the endpoint and we got a 40
 enum ConfigError {
     #[snafu(display("Cannot set both `endpoints` and `hosts`"))]
     BothEndpointsAndHosts,
+    #[snafu(display("One of `endpoints`, `file_sd` or `dns_sd` must be set"))]
+    NoTargets,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 struct PrometheusScrapeConfig {
     // Deprecated name
-    #[serde(alias = "hosts")]
+    #[serde(alias = "hosts", default)]
     endpoints: Vec<String>,
+    file_sd: Option<FileSdConfig>,
+    dns_sd: Option<DnsSdConfig>,
     #[serde(default = "default_scrape_interval_secs")]
     scrape_interval_secs: u64,
     instance_tag: Option<String>,
@@ -74,6 +82,8 @@ impl GenerateConfig for PrometheusScrapeConfig {
     fn generate_config() -> toml::Value {
         toml::Value::try_from(Self {
             endpoints: vec!["http://localhost:9090/metrics".to_string()],
+            file_sd: None,
+            dns_sd: None,
             scrape_interval_secs: default_scrape_interval_secs(),
             instance_tag: Some("instance".to_string()),
             endpoint_tag: Some("endpoint".to_string()),
@@ -90,42 +100,29 @@ impl GenerateConfig for PrometheusScrapeConfig {
 #[typetag::serde(name = "prometheus_scrape")]
 impl SourceConfig for PrometheusScrapeConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<sources::Source> {
-        let urls = self
+        if self.endpoints.is_empty() && self.file_sd.is_none() && self.dns_sd.is_none() {
+            return Err(ConfigError::NoTargets.into());
+        }
+        let static_targets = self
             .endpoints
             .iter()
-            .map(|s| s.parse::<http::Uri>().context(sources::UriParseSnafu))
-            .map(|r| {
-                r.map(|uri| {
-                    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
-                    if let Some(query) = uri.query() {
-                        serializer.extend_pairs(url::form_urlencoded::parse(query.as_bytes()));
-                    };
-                    if let Some(query) = &self.query {
-                        for (k, l) in query {
-                            for v in l {
-                                serializer.append_pair(k, v);
-                            }
-                        }
-                    };
-                    let mut builder = http::Uri::builder();
-                    if let Some(scheme) = uri.scheme() {
-                        builder = builder.scheme(scheme.clone());
-                    };
-                    if let Some(authority) = uri.authority() {
-                        builder = builder.authority(authority.clone());
-                    };
-                    builder = builder.path_and_query(match serializer.finish() {
-                        query if !query.is_empty() => format!("{}?{}", uri.path(), query),
-                        _ => uri.path().to_string(),
-                    });
-                    builder.build().expect("error building URI")
-                })
+            .map(|s| {
+                s.parse::<http::Uri>()
+                    .context(sources::UriParseSnafu)
+                    .map(Target::new)
             })
-            .collect::<Result<Vec<http::Uri>, sources::BuildError>>()?;
+            .collect::<Result<Vec<Target>, sources::BuildError>>()?;
+        let targets = TargetSet::new(static_targets);
+        discovery::spawn(
+            self.file_sd.as_ref(),
+            self.dns_sd.as_ref(),
+            &targets,
+            cx.shutdown.clone(),
+        )?;
         let tls = TlsSettings::from_options(&self.tls)?;
         Ok(prometheus(
             self.clone(),
-            urls,
+            targets,
             tls,
             cx.proxy.clone(),
             cx.shutdown,
@@ -153,8 +150,10 @@ impl SourceConfig for PrometheusScrapeConfig {
 struct PrometheusCompatConfig {
     // Clone of PrometheusScrapeConfig to work around serde bug
     // https://github.com/serde-rs/serde/issues/1504
-    #[serde(alias = "hosts")]
+    #[serde(alias = "hosts", default)]
     endpoints: Vec<String>,
+    file_sd: Option<FileSdConfig>,
+    dns_sd: Option<DnsSdConfig>,
     instance_tag: Option<String>,
     endpoint_tag: Option<String>,
     #[serde(default = "crate::serde::default_false")]
@@ -174,6 +173,8 @@ impl SourceConfig for PrometheusCompatConfig {
         // https://github.com/serde-rs/serde/issues/1504
         let config = PrometheusScrapeConfig {
             endpoints: self.endpoints.clone(),
+            file_sd: self.file_sd.clone(),
+            dns_sd: self.dns_sd.clone(),
             instance_tag: self.instance_tag.clone(),
             endpoint_tag: self.endpoint_tag.clone(),
             honor_labels: self.honor_labels,
@@ -218,9 +219,50 @@ struct EndpointInfo {
     honor_label: bool,
 }
 
+fn build_url(uri: &http::Uri, query: &Option<HashMap<String, Vec<String>>>) -> http::Uri {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    if let Some(query) = uri.query() {
+        serializer.extend_pairs(url::form_urlencoded::parse(query.as_bytes()));
+    };
+    if let Some(query) = query {
+        for (k, l) in query {
+            for v in l {
+                serializer.append_pair(k, v);
+            }
+        }
+    };
+    let mut builder = http::Uri::builder();
+    if let Some(scheme) = uri.scheme() {
+        builder = builder.scheme(scheme.clone());
+    };
+    if let Some(authority) = uri.authority() {
+        builder = builder.authority(authority.clone());
+    };
+    builder = builder.path_and_query(match serializer.finish() {
+        query if !query.is_empty() => format!("{}?{}", uri.path(), query),
+        _ => uri.path().to_string(),
+    });
+    builder.build().expect("error building URI")
+}
+
+// Inserts a tag that Vector adds to scraped metrics, resolving conflicts with a tag of the same
+// name in the scraped metric according to `honor_labels`.
+fn insert_tag(metric: &mut Metric, tag: &str, value: &str, honor_label: bool) {
+    match (honor_label, metric.tag_value(tag)) {
+        (false, Some(old_value)) => {
+            metric.insert_tag(format!("exported_{}", tag), old_value);
+            metric.insert_tag(tag.to_string(), value.to_string());
+        }
+        (true, Some(_)) => {}
+        (_, None) => {
+            metric.insert_tag(tag.to_string(), value.to_string());
+        }
+    }
+}
+
 async fn prometheus(
     config: PrometheusScrapeConfig,
-    urls: Vec<http::Uri>,
+    targets: TargetSet,
     tls: TlsSettings,
     proxy: ProxyConfig,
     shutdown: ShutdownSignal,
@@ -230,11 +272,14 @@ async fn prometheus(
         config.scrape_interval_secs,
     )))
     .take_until(shutdown)
-    .map(move |_| stream::iter(urls.clone()))
+    .map(move |_| stream::iter(targets.snapshot()))
     .flatten()
-    .map(move |url| {
+    .map(move |target| {
         let client = HttpClient::new(tls.clone(), &proxy).expect("Building HTTP client failed");
+        let url = build_url(&target.url, &config.query);
         let endpoint = url.to_string();
+        let labels = target.labels;
+        let honor_labels = config.honor_labels;
 
         let mut request = Request::get(&url)
             .body(Body::empty())
@@ -283,6 +328,7 @@ async fn prometheus(
             .filter_map(move |response| {
                 let instance_info = instance_info.clone();
                 let endpoint_info = endpoint_info.clone();
+                let labels = labels.clone();
 
                 ready(match response {
                     Ok((header, body)) if header.status == hyper::StatusCode::OK => {
@@ -302,25 +348,16 @@ async fn prometheus(
                                 });
                                 Some(stream::iter(events).map(move |mut event| {
                                     let metric = event.as_mut_metric();
+                                    for (tag, value) in &labels {
+                                        insert_tag(metric, tag, value, honor_labels);
+                                    }
                                     if let Some(InstanceInfo {
                                         tag,
                                         instance,
                                         honor_label,
                                     }) = &instance_info
                                     {
-                                        match (honor_label, metric.tag_value(tag)) {
-                                            (false, Some(old_instance)) => {
-                                                metric.insert_tag(
-                                                    format!("exported_{}", tag),
-                                                    old_instance,
-                                                );
-                                                metric.insert_tag(tag.clone(), instance.clone());
-                                            }
-                                            (true, Some(_)) => {}
-                                            (_, None) => {
-                                                metric.insert_tag(tag.clone(), instance.clone());
-                                            }
-                                        }
+                                        insert_tag(metric, tag, instance, *honor_label);
                                     }
                                     if let Some(EndpointInfo {
                                         tag,
@@ -328,19 +365,7 @@ async fn prometheus(
                                         honor_label,
                                     }) = &endpoint_info
                                     {
-                                        match (honor_label, metric.tag_value(tag)) {
-                                            (false, Some(old_endpoint)) => {
-                                                metric.insert_tag(
-                                                    format!("exported_{}", tag),
-                                                    old_endpoint,
-                                                );
-                                                metric.insert_tag(tag.clone(), endpoint.clone());
-                                            }
-                                            (true, Some(_)) => {}
-                                            (_, None) => {
-                                                metric.insert_tag(tag.clone(), endpoint.clone());
-                                            }
-                                        }
+                                        insert_tag(metric, tag, endpoint, *honor_label);
                                     }
                                     event
                                 }))
@@ -445,6 +470,8 @@ mod test {
 
         let config = PrometheusScrapeConfig {
             endpoints: vec![format!("http://{}/metrics", in_addr)],
+            file_sd: None,
+            dns_sd: None,
             scrape_interval_secs: 1,
             instance_tag: Some("instance".to_string()),
             endpoint_tag: Some("endpoint".to_string()),
@@ -495,6 +522,8 @@ mod test {
 
         let config = PrometheusScrapeConfig {
             endpoints: vec![format!("http://{}/metrics", in_addr)],
+            file_sd: None,
+            dns_sd: None,
             scrape_interval_secs: 1,
             instance_tag: Some("instance".to_string()),
             endpoint_tag: Some("endpoint".to_string()),
@@ -558,6 +587,8 @@ mod test {
 
         let config = PrometheusScrapeConfig {
             endpoints: vec![format!("http://{}/metrics?key1=val1", in_addr)],
+            file_sd: None,
+            dns_sd: None,
             scrape_interval_secs: 1,
             instance_tag: Some("instance".to_string()),
             endpoint_tag: Some("endpoint".to_string()),
@@ -612,6 +643,64 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn test_prometheus_file_sd() {
+        let in_addr = next_addr();
+
+        let dummy_endpoint = warp::path!("metrics").map(|| {
+            r#"
+                promhttp_metric_handler_requests_total{code="200", env="dev"} 100 1612411516789
+            "#
+        });
+
+        tokio::spawn(warp::serve(dummy_endpoint).run(in_addr));
+
+        let dir = crate::test_util::temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("targets.json");
+        std::fs::write(
+            &file,
+            format!(
+                r#"[{{"targets": ["{}"], "labels": {{"env": "prod", "job": "node"}}}}]"#,
+                in_addr
+            ),
+        )
+        .unwrap();
+
+        let config = PrometheusScrapeConfig {
+            endpoints: vec![],
+            file_sd: Some(FileSdConfig {
+                files: vec![file.display().to_string()],
+                refresh_interval_secs: 1,
+                scheme: "http".to_string(),
+                metrics_path: "/metrics".to_string(),
+            }),
+            dns_sd: None,
+            scrape_interval_secs: 1,
+            instance_tag: None,
+            endpoint_tag: None,
+            honor_labels: false,
+            query: None,
+            auth: None,
+            tls: None,
+        };
+
+        let events = run_and_assert_source_compliance(
+            config,
+            Duration::from_secs(3),
+            &HTTP_PULL_SOURCE_TAGS,
+        )
+        .await;
+        assert!(!events.is_empty());
+
+        for event in events {
+            let metric = event.into_metric();
+            assert_eq!(metric.tag_value("job"), Some(String::from("node")));
+            assert_eq!(metric.tag_value("env"), Some(String::from("prod")));
+            assert_eq!(metric.tag_value("exported_env"), Some(String::from("dev")));
+        }
+    }
+
     #[tokio::test]
     async fn test_prometheus_routing() {
         let in_addr = next_addr();
@@ -663,6 +752,8 @@ mod test {
             "in",
             PrometheusScrapeConfig {
                 endpoints: vec![format!("http://{}", in_addr)],
+                file_sd: None,
+                dns_sd: None,
                 instance_tag: None,
                 endpoint_tag: None,
                 honor_labels: false,
@@ -751,6 +842,8 @@ mod integration_tests {
     async fn scrapes_metrics() {
         let config = PrometheusScrapeConfig {
             endpoints: vec!["http://localhost:9090/metrics".into()],
+            file_sd: None,
+            dns_sd: None,
             scrape_interval_secs: 1,
             instance_tag: Some("instance".to_string()),
             endpoint_tag: Some("endpoint".to_string()),
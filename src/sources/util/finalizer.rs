@@ -1,6 +1,7 @@
 use std::marker::{PhantomData, Unpin};
 use std::{future::Future, pin::Pin, task::Poll};
 
+use futures::future::OptionFuture;
 use futures::stream::{FuturesOrdered, FuturesUnordered};
 use futures::{FutureExt, Stream, StreamExt};
 use tokio::sync::mpsc;
@@ -22,6 +23,7 @@ pub(crate) type OrderedFinalizer<T> = FinalizerSet<T, FuturesOrdered<FinalizerFu
 /// done in a single background task *in the order the finalization
 /// happens on the event batches*, using `FinalizerSet`.
 #[cfg(any(
+    feature = "sources-amqp",
     feature = "sources-aws_sqs",
     feature = "sources-splunk_hec",
     feature = "sources-gcp_pubsub"
@@ -46,9 +48,26 @@ where
     where
         F: Fn(T) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self::with_status(shutdown, move |status, entry| {
+            OptionFuture::from((status == BatchStatus::Delivered).then(|| apply_done(entry)))
+        })
+    }
+
+    /// Like `new`, except that `apply_status` is called with the final status of every entry,
+    /// not only for the delivered ones.
+    pub(crate) fn with_status<F, Fut>(shutdown: ShutdownSignal, apply_status: F) -> Self
+    where
+        F: Fn(BatchStatus, T) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
     {
         let (sender, receiver) = mpsc::unbounded_channel();
-        tokio::spawn(run_finalizer(shutdown, receiver, apply_done, S::default()));
+        tokio::spawn(run_finalizer(
+            shutdown,
+            receiver,
+            apply_status,
+            S::default(),
+        ));
         Self {
             sender: Some(sender),
             _phantom: Default::default(),
@@ -64,10 +83,10 @@ where
     }
 }
 
-async fn run_finalizer<T, F: Future<Output = ()>>(
+async fn run_finalizer<T, F: Future>(
     shutdown: ShutdownSignal,
     mut new_entries: mpsc::UnboundedReceiver<(BatchStatusReceiver, T)>,
-    apply_done: impl Fn(T) -> F,
+    apply_status: impl Fn(BatchStatus, T) -> F,
     mut status_receivers: impl FuturesSet<FinalizerFuture<T>> + Unpin,
 ) {
     loop {
@@ -83,8 +102,8 @@ async fn run_finalizer<T, F: Future<Output = ()>>(
                 None => break,
             },
             finished = status_receivers.next(), if !status_receivers.is_empty() => match finished {
-                Some((status, entry)) => if status == BatchStatus::Delivered {
-                    apply_done(entry).await;
+                Some((status, entry)) => {
+                    apply_status(status, entry).await;
                 }
                 // The is_empty guard above prevents this from being reachable.
                 None => unreachable!(),
@@ -95,9 +114,7 @@ async fn run_finalizer<T, F: Future<Output = ()>>(
     // closed. Wait for the last statuses to come in before indicating
     // we are done.
     while let Some((status, entry)) = status_receivers.next().await {
-        if status == BatchStatus::Delivered {
-            apply_done(entry).await;
-        }
+        apply_status(status, entry).await;
     }
     drop(shutdown);
 }
@@ -2,6 +2,7 @@
 mod body_decoding;
 mod encoding_config;
 #[cfg(any(
+    feature = "sources-amqp",
     feature = "sources-aws_sqs",
     feature = "sources-file",
     feature = "sources-gcp_pubsub",
@@ -20,7 +21,11 @@ pub mod framestream;
     feature = "sources-utils-http-query"
 ))]
 mod http;
-#[cfg(any(feature = "sources-aws_sqs", feature = "sources-gcp_pubsub"))]
+#[cfg(any(
+    feature = "sources-amqp",
+    feature = "sources-aws_sqs",
+    feature = "sources-gcp_pubsub"
+))]
 mod message_decoding;
 pub mod multiline_config;
 #[cfg(all(feature = "sources-utils-tls", feature = "listenfd"))]
@@ -71,5 +76,9 @@ pub use self::http::ErrorMessage;
 pub use self::http::HttpSource;
 #[cfg(feature = "sources-utils-http-auth")]
 pub use self::http::HttpSourceAuthConfig;
-#[cfg(any(feature = "sources-aws_sqs", feature = "sources-gcp_pubsub"))]
+#[cfg(any(
+    feature = "sources-amqp",
+    feature = "sources-aws_sqs",
+    feature = "sources-gcp_pubsub"
+))]
 pub use self::message_decoding::decode_message;
@@ -159,7 +159,19 @@ impl TlsSettings {
         })
     }
 
-    #[cfg(feature = "sources-gcp_pubsub")]
+    /// The identity as a DER encoded PKCS#12 archive, along with its password.
+    #[cfg(any(feature = "sources-amqp", feature = "sinks-amqp"))]
+    pub fn identity_pkcs12(&self) -> Option<(Vec<u8>, String)> {
+        self.identity
+            .as_ref()
+            .map(|identity| (identity.0.clone(), identity.1.clone()))
+    }
+
+    #[cfg(any(
+        feature = "sources-gcp_pubsub",
+        feature = "sources-amqp",
+        feature = "sinks-amqp"
+    ))]
     pub fn authorities_pem(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
         self.authorities.iter().map(|authority| {
             authority
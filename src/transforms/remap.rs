@@ -1,15 +1,20 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
 use std::{
     collections::BTreeMap,
     fs::File,
     io::{self, Read},
     path::PathBuf,
+    time::{Duration, Instant},
 };
 
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
 use value::Kind;
 use vector_common::TimeZone;
+use vector_vrl_functions::{StateSnapshot, StateStore, StateStoreLimits};
 use vrl::{
     diagnostic::{Formatter, Note},
     prelude::{DiagnosticMessage, ExpressionError},
@@ -22,7 +27,10 @@ use crate::{
         TransformDescription,
     },
     event::{Event, VrlTarget},
-    internal_events::{RemapMappingAbort, RemapMappingError},
+    internal_events::{
+        RemapMappingAbort, RemapMappingError, RemapStateCheckpointError,
+        RemapStateCheckpointLoaded, RemapStateStoreMetrics,
+    },
     schema,
     transforms::{SyncTransform, Transform, TransformOutputsBuf},
     Result,
@@ -30,12 +38,16 @@ use crate::{
 
 const DROPPED: &str = "dropped";
 
+/// How often the metrics of the state store are reported.
+const STATE_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Deserialize, Serialize, Debug, Clone, Derivative)]
 #[serde(deny_unknown_fields, default)]
 #[derivative(Default)]
 pub struct RemapConfig {
     pub source: Option<String>,
     pub file: Option<PathBuf>,
+    pub import_paths: Vec<PathBuf>,
     #[serde(default)]
     pub timezone: TimeZone,
     pub drop_on_error: bool,
@@ -44,6 +56,48 @@ pub struct RemapConfig {
     pub reroute_dropped: bool,
     #[serde(default)]
     pub runtime: VrlRuntime,
+    pub state: Option<RemapStateConfig>,
+}
+
+/// The configuration of the state shared between events by the `get_state`,
+/// `set_state` and `incr_state` functions.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Derivative)]
+#[serde(deny_unknown_fields, default)]
+#[derivative(Default)]
+pub struct RemapStateConfig {
+    /// The maximum number of entries kept in the state.
+    #[derivative(Default(value = "10_000"))]
+    pub max_entries: usize,
+
+    /// The maximum (approximate) number of bytes used by the state.
+    #[derivative(Default(value = "10 * 1024 * 1024"))]
+    pub max_bytes: usize,
+
+    /// The number of seconds after which entries without an explicit TTL
+    /// expire.
+    #[derivative(Default(value = "3600"))]
+    pub ttl_secs: u64,
+
+    /// Whether to write the state to disk, to restore it after a restart.
+    pub checkpoint: bool,
+
+    /// How often the state is written to disk, in seconds.
+    #[derivative(Default(value = "60"))]
+    pub checkpoint_interval_secs: u64,
+
+    /// The directory in which the state is written, defaults to the global
+    /// `data_dir`.
+    pub data_dir: Option<PathBuf>,
+}
+
+impl RemapStateConfig {
+    fn limits(&self) -> StateStoreLimits {
+        StateStoreLimits {
+            max_entries: self.max_entries,
+            max_bytes: self.max_bytes,
+            ttl: Duration::from_secs(self.ttl_secs),
+        }
+    }
 }
 
 impl RemapConfig {
@@ -51,6 +105,7 @@ impl RemapConfig {
         &self,
         enrichment_tables: enrichment::TableRegistry,
         merged_schema_definition: schema::Definition,
+        state_store: Option<StateStore>,
     ) -> Result<(
         vrl::Program,
         String,
@@ -74,12 +129,14 @@ impl RemapConfig {
 
         let mut functions = vrl_stdlib::all();
         functions.append(&mut enrichment::vrl_functions());
-        functions.append(&mut vector_vrl_functions::vrl_functions());
+        functions.append(&mut vector_vrl_functions::vrl_functions_with_state(
+            state_store,
+        ));
 
         let mut state = vrl::state::ExternalEnv::new_with_kind(merged_schema_definition.into());
         state.set_external_context(enrichment_tables);
 
-        vrl::compile_with_state(&source, &functions, &mut state)
+        vrl::compile_with_search_path(&source, &functions, &mut state, &self.import_paths)
             .map_err(|diagnostics| {
                 Formatter::new(&source, diagnostics)
                     .colored()
@@ -143,6 +200,9 @@ impl TransformConfig for RemapConfig {
             .compile_vrl_program(
                 enrichment::TableRegistry::default(),
                 merged_definition.clone(),
+                self.state
+                    .as_ref()
+                    .map(|state| StateStore::new(state.limits())),
             )
             .ok()
             .and_then(|(_, _, _, state)| state.target_kind().cloned())
@@ -202,6 +262,124 @@ where
     default_schema_definition: Arc<schema::Definition>,
     dropped_schema_definition: Arc<schema::Definition>,
     runner: Runner,
+    state: Option<RemapState>,
+}
+
+/// The state store shared by all clones of a remap transform.
+#[derive(Debug, Clone)]
+struct RemapState {
+    store: StateStore,
+    checkpoint: Option<Arc<StateCheckpoint>>,
+    last_report: Instant,
+}
+
+impl RemapState {
+    fn new(config: &RemapStateConfig, context: &TransformContext) -> crate::Result<Self> {
+        let store = StateStore::new(config.limits());
+
+        let checkpoint = if config.checkpoint {
+            let id = context.key.as_ref().map_or("remap", ComponentKey::id);
+            let path = context
+                .globals
+                .resolve_and_make_data_subdir(config.data_dir.as_ref(), id)?
+                .join("state.json");
+
+            // A checkpoint that can't be read shouldn't prevent the transform
+            // from starting, it merely starts with an empty state.
+            match store.restore(&path) {
+                Ok(entries) => emit!(RemapStateCheckpointLoaded {
+                    entries,
+                    path: &path,
+                }),
+                Err(error) => emit!(RemapStateCheckpointError { error, path: &path }),
+            }
+
+            Some(Arc::new(StateCheckpoint {
+                store: store.clone(),
+                path,
+                interval: Duration::from_secs(config.checkpoint_interval_secs),
+                last_write: Mutex::new(Instant::now()),
+                writing: AtomicBool::new(false),
+            }))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            store,
+            checkpoint,
+            last_report: Instant::now(),
+        })
+    }
+
+    /// Reports the metrics of the store, and writes a checkpoint if one is
+    /// due.
+    fn tick(&mut self) {
+        if self.last_report.elapsed() >= STATE_REPORT_INTERVAL {
+            self.last_report = Instant::now();
+
+            let metrics = self.store.metrics();
+            emit!(RemapStateStoreMetrics {
+                entries: metrics.entries,
+                bytes: metrics.bytes,
+                evicted: metrics.evicted,
+                expired: metrics.expired,
+            });
+        }
+
+        if let Some(checkpoint) = &self.checkpoint {
+            checkpoint.write_if_due();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct StateCheckpoint {
+    store: StateStore,
+    path: PathBuf,
+    interval: Duration,
+    last_write: Mutex<Instant>,
+    /// Set while a checkpoint is being written in the background.
+    writing: AtomicBool,
+}
+
+impl StateCheckpoint {
+    /// Takes a snapshot of the store if a checkpoint is due, and writes it on a
+    /// blocking task so that events aren't held up by serializing the store.
+    fn write_if_due(self: &Arc<Self>) {
+        // If another clone of the transform is writing the checkpoint, there's
+        // no need to wait for it.
+        if let Ok(mut last_write) = self.last_write.try_lock() {
+            if last_write.elapsed() >= self.interval && !self.writing.swap(true, Ordering::AcqRel) {
+                *last_write = Instant::now();
+
+                let snapshot = self.store.snapshot();
+                // The task keeps the checkpoint alive, so that the one written
+                // when it's dropped can't be replaced by an older snapshot.
+                let checkpoint = Arc::clone(self);
+                tokio::task::spawn_blocking(move || {
+                    checkpoint.write(&snapshot);
+                    checkpoint.writing.store(false, Ordering::Release);
+                });
+            }
+        }
+    }
+
+    fn write(&self, snapshot: &StateSnapshot) {
+        if let Err(error) = snapshot.write(&self.path) {
+            emit!(RemapStateCheckpointError {
+                error,
+                path: &self.path,
+            });
+        }
+    }
+}
+
+impl Drop for StateCheckpoint {
+    fn drop(&mut self) {
+        // The last clone of the transform is dropped, persist the latest state.
+        self.write(&self.store.snapshot());
+    }
 }
 
 pub trait VrlRunner {
@@ -235,7 +413,9 @@ impl VrlRunner for VmRunner {
         _: &Program,
         timezone: &TimeZone,
     ) -> std::result::Result<value::Value, Terminate> {
-        self.runtime.run_vm(&self.vm, target, timezone)
+        let result = self.runtime.run_vm(&self.vm, target, timezone);
+        self.runtime.clear();
+        result
     }
 }
 
@@ -270,19 +450,26 @@ impl Remap<VmRunner> {
         config: RemapConfig,
         context: &TransformContext,
     ) -> crate::Result<(Self, String)> {
-        let (program, warnings, functions, mut state) = config.compile_vrl_program(
+        let state = config
+            .state
+            .as_ref()
+            .map(|state| RemapState::new(state, context))
+            .transpose()?;
+
+        let (program, warnings, functions, mut external) = config.compile_vrl_program(
             context.enrichment_tables.clone(),
             context.merged_schema_definition.clone(),
+            state.as_ref().map(|state| state.store.clone()),
         )?;
 
         let runtime = Runtime::default();
-        let vm = runtime.compile(functions, &program, &mut state)?;
+        let vm = runtime.compile(functions, &program, &mut external)?;
         let runner = VmRunner {
             runtime,
             vm: Arc::new(vm),
         };
 
-        Self::new(config, context, program, runner).map(|remap| (remap, warnings))
+        Self::new(config, context, program, runner, state).map(|remap| (remap, warnings))
     }
 }
 
@@ -291,15 +478,22 @@ impl Remap<AstRunner> {
         config: RemapConfig,
         context: &TransformContext,
     ) -> crate::Result<(Self, String)> {
+        let state = config
+            .state
+            .as_ref()
+            .map(|state| RemapState::new(state, context))
+            .transpose()?;
+
         let (program, warnings, _, _) = config.compile_vrl_program(
             context.enrichment_tables.clone(),
             context.merged_schema_definition.clone(),
+            state.as_ref().map(|state| state.store.clone()),
         )?;
 
         let runtime = Runtime::default();
         let runner = AstRunner { runtime };
 
-        Self::new(config, context, program, runner).map(|remap| (remap, warnings))
+        Self::new(config, context, program, runner, state).map(|remap| (remap, warnings))
     }
 }
 
@@ -312,6 +506,7 @@ where
         context: &TransformContext,
         program: Program,
         runner: Runner,
+        state: Option<RemapState>,
     ) -> crate::Result<Self> {
         let default_schema_definition = context
             .schema_definitions
@@ -336,6 +531,7 @@ where
             default_schema_definition: Arc::new(default_schema_definition),
             dropped_schema_definition: Arc::new(dropped_schema_definition),
             runner,
+            state,
         })
     }
 
@@ -426,6 +622,10 @@ where
         let mut target = VrlTarget::new(event, self.program.info());
         let result = self.run_vrl(&mut target);
 
+        if let Some(state) = self.state.as_mut() {
+            state.tick();
+        }
+
         match result {
             Ok(_) => {
                 for event in target.into_events() {
@@ -549,6 +749,16 @@ mod tests {
             .map(|(remap, _)| remap)
     }
 
+    fn remap_vm(config: RemapConfig) -> Result<Remap<VmRunner>> {
+        let schema_definitions = HashMap::from([
+            (None, test_default_schema_definition()),
+            (Some(DROPPED.to_owned()), test_dropped_schema_definition()),
+        ]);
+
+        Remap::new_vm(config, &TransformContext::new_test(schema_definitions))
+            .map(|(remap, _)| remap)
+    }
+
     #[test]
     fn generate_config() {
         crate::test_util::test_generate_config::<RemapConfig>();
@@ -629,6 +839,102 @@ mod tests {
         assert!(tform.runner().runtime.is_empty());
     }
 
+    #[test]
+    fn check_remap_vm_doesnt_share_state_between_events() {
+        let conf = RemapConfig {
+            source: Some("foo = .sentinel\n.foo = foo".to_string()),
+            file: None,
+            timezone: TimeZone::default(),
+            drop_on_error: true,
+            drop_on_abort: false,
+            runtime: VrlRuntime::Vm,
+            ..Default::default()
+        };
+        let mut tform = remap_vm(conf).unwrap();
+        assert!(tform.runner().runtime.is_empty());
+
+        let event1 = {
+            let mut event1 = LogEvent::from("event1");
+            event1.insert("sentinel", "bar");
+            Event::from(event1)
+        };
+        let result1 = transform_one(&mut tform, event1).unwrap();
+        assert_eq!(get_field_string(&result1, "foo"), "bar");
+        assert!(tform.runner().runtime.is_empty());
+
+        let event2 = Event::from(LogEvent::from("event2"));
+        let result2 = transform_one(&mut tform, event2).unwrap();
+        assert_eq!(result2.as_log().get("foo"), Some(&Value::Null));
+        assert!(tform.runner().runtime.is_empty());
+    }
+
+    #[test]
+    fn check_remap_state_is_shared_between_events() {
+        let source = indoc! {r#"
+            .count = incr_state!("count")
+            .previous = get_state("last")
+            set_state!("last", .message)
+        "#};
+
+        for runtime in [VrlRuntime::Ast, VrlRuntime::Vm] {
+            let conf = RemapConfig {
+                source: Some(source.to_owned()),
+                state: Some(RemapStateConfig::default()),
+                runtime,
+                ..Default::default()
+            };
+            let mut tform: Box<dyn SyncTransform> = match runtime {
+                VrlRuntime::Ast => Box::new(remap(conf).unwrap()),
+                VrlRuntime::Vm => Box::new(remap_vm(conf).unwrap()),
+            };
+
+            let result = transform_one(tform.as_mut(), Event::from("first")).unwrap();
+            assert_eq!(result.as_log().get("count"), Some(&Value::from(1)));
+            assert_eq!(result.as_log().get("previous"), Some(&Value::Null));
+
+            let result = transform_one(tform.as_mut(), Event::from("second")).unwrap();
+            assert_eq!(result.as_log().get("count"), Some(&Value::from(2)));
+            assert_eq!(get_field_string(&result, "previous"), "first");
+        }
+    }
+
+    #[test]
+    fn check_remap_state_requires_config() {
+        let conf = RemapConfig {
+            source: Some(r#"get_state("foo")"#.to_owned()),
+            ..Default::default()
+        };
+
+        let err = remap(conf).unwrap_err().to_string();
+        assert!(err.contains("state storage is not enabled"));
+    }
+
+    #[test]
+    fn check_remap_state_checkpoint() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let conf = RemapConfig {
+            source: Some(r#".count = incr_state!("count")"#.to_owned()),
+            state: Some(RemapStateConfig {
+                checkpoint: true,
+                data_dir: Some(data_dir.path().to_path_buf()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let mut tform = remap(conf.clone()).unwrap();
+        let result = transform_one(&mut tform, Event::from("first")).unwrap();
+        assert_eq!(result.as_log().get("count"), Some(&Value::from(1)));
+
+        // Dropping the transform writes the checkpoint, which is restored by
+        // the next transform.
+        drop(tform);
+
+        let mut tform = remap(conf).unwrap();
+        let result = transform_one(&mut tform, Event::from("second")).unwrap();
+        assert_eq!(result.as_log().get("count"), Some(&Value::from(2)));
+    }
+
     #[test]
     fn check_remap_adds() {
         let event = {
@@ -486,6 +486,94 @@ cli: {
 				}
 			}
 		}
+
+		"vrl fmt": {
+			description: """
+				Format VRL programs, and the programs of the `remap` transforms in Vector
+				configuration files
+				"""
+
+			flags: _default_flags & {
+				"check": {
+					description: """
+						Don't write the formatted files, exit with an error if any of them isn't
+						formatted
+						"""
+				}
+			}
+
+			args: {
+				paths: {
+					description: """
+						Any number of VRL files, formatted as a whole, or Vector config files
+						(`.toml`, `.yaml`, `.yml` or `.json`), in which the `source` of the `remap`
+						transforms is formatted. If none are specified, the program read from stdin
+						is written formatted to stdout
+						"""
+					type: "list"
+				}
+			}
+		}
+
+		"vrl test": {
+			description: """
+				Run VRL test files, checking the output of programs for given input events, and
+				report the lines of the programs covered by the tests
+				"""
+
+			flags: _default_flags & {
+				"coverage": {
+					description: """
+						Report the lines of each program reached by its tests. A line is covered
+						once all the expressions starting on it were reached
+						"""
+				}
+			}
+
+			options: {
+				"import-path": {
+					description: """
+						A directory in which modules imported by the programs are searched for. Can
+						be provided multiple times, the directories are searched in order
+						"""
+					type: "string"
+				}
+
+				"runtime": {
+					_short:      "r"
+					description: "The runtime the tests are run with"
+					default:     "ast"
+					enum: {
+						ast: "The AST runtime"
+						vm:  "The VM runtime"
+					}
+				}
+
+				"timezone": {
+					_short:      "z"
+					description: "The timezone used to parse dates"
+					type:        "string"
+				}
+			}
+
+			args: {
+				paths: {
+					description: """
+						Any number of test files, or directories in which the files ending with
+						`_test.vrl` are run, including those of subdirectories. A test file starts
+						with comment headers: `input` is the event the program runs on, `output` the
+						event expected after the run, `result` the expected value of the program,
+						and `error` part of the message of the error the program is expected to
+						fail with. The values are written as VRL expressions, JSON documents for
+						example, and can span multiple comment lines. The program is either written
+						below the headers, or read from the file set in the `program` header,
+						relative to the test file
+						"""
+					type:     "list"
+					required: true
+				}
+			}
+		}
 	}
 
 	env_vars: {
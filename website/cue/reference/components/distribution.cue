@@ -0,0 +1,76 @@
+package metadata
+
+components: _distribution: {
+	configuration: {
+		distribution: {
+			common:      false
+			description: "Options for spreading requests over several endpoints. Only relevant when more than one endpoint is configured."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					strategy: {
+						common:      false
+						description: "How to choose the endpoint each request is sent to."
+						required:    false
+						type: string: {
+							default: "round_robin"
+							enum: {
+								round_robin:       "Take turns sending requests to each healthy endpoint."
+								least_outstanding: "Send each request to the healthy endpoint with the fewest requests in flight."
+							}
+						}
+					}
+					unhealthy_threshold: {
+						common:      false
+						description: "The number of consecutive failed requests after which an endpoint is considered unhealthy and no longer sent requests."
+						required:    false
+						type: uint: {
+							default: 3
+							unit:    null
+						}
+					}
+					retry_initial_backoff_secs: {
+						common:      false
+						description: "The amount of time to wait before probing an unhealthy endpoint for the first time."
+						required:    false
+						type: uint: {
+							default: 1
+							unit:    "seconds"
+						}
+					}
+					retry_max_duration_secs: {
+						common:      false
+						description: "The maximum amount of time to wait between probes of an unhealthy endpoint."
+						required:    false
+						type: uint: {
+							default: 3_600
+							unit:    "seconds"
+						}
+					}
+				}
+			}
+		}
+	}
+
+	how_it_works: {
+		load_balancing: {
+			title: "Load balancing"
+			body:  """
+				When several endpoints are configured, requests are spread over them according to
+				`distribution.strategy`. Each endpoint gets its own timeout and concurrency limit, so
+				adaptive concurrency adjusts to each of them separately, and failed requests are retried
+				on whichever endpoint is picked next.
+
+				The health of each endpoint is tracked from the requests sent to it. Once
+				`distribution.unhealthy_threshold` requests in a row have failed, with a retriable error or
+				a timeout, the endpoint stops receiving requests. After `distribution.retry_initial_backoff_secs`
+				a single request is sent to it as a probe. If it succeeds the endpoint receives requests
+				again, otherwise the wait before the next probe is doubled, up to
+				`distribution.retry_max_duration_secs`.
+
+				The health check passes as long as any of the endpoints is healthy.
+				"""
+		}
+	}
+}
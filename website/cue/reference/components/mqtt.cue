@@ -0,0 +1,135 @@
+package metadata
+
+components: _mqtt: {
+	features: {
+		collect: from: {
+			service: services.mqtt
+			interface: {
+				socket: {
+					api: {
+						title: "MQTT protocol"
+						url:   urls.mqtt
+					}
+					direction: "incoming"
+					port:      1883
+					protocols: ["tcp"]
+					ssl: "optional"
+				}
+			}
+		}
+
+		send: to: {
+			service: services.mqtt
+			interface: {
+				socket: {
+					api: {
+						title: "MQTT protocol"
+						url:   urls.mqtt
+					}
+					direction: "outgoing"
+					protocols: ["tcp"]
+					ssl: "optional"
+				}
+			}
+		}
+	}
+
+	support: {
+		requirements: []
+		notices: []
+		warnings: []
+	}
+
+	configuration: {
+		host: {
+			common:      true
+			description: "The host name of the MQTT broker."
+			required:    false
+			type: string: {
+				default: "localhost"
+				examples: ["mosquitto.example.com", "127.0.0.1"]
+			}
+		}
+		port: {
+			common:      true
+			description: "The port of the MQTT broker. Brokers usually listen on 8883 for TLS connections."
+			required:    false
+			type: uint: {
+				default: 1883
+				examples: [1883, 8883]
+				unit: null
+			}
+		}
+		client_id: {
+			common:      false
+			description: """
+				The client identifier presented to the broker. Brokers disconnect an existing client when another
+				one connects with the same identifier, so it must be unique per Vector instance and component.
+				"""
+			required:    false
+			type: string: {
+				default: "vector"
+				examples: ["vector-gateway-1"]
+			}
+		}
+		user: {
+			common:      false
+			description: "The user name to authenticate with. Must be set together with `password`."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["vector"]
+			}
+		}
+		password: {
+			common:      false
+			description: "The password to authenticate with. Must be set together with `user`."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["${MQTT_PASSWORD}"]
+			}
+		}
+		keep_alive_secs: {
+			common:      false
+			description: "The interval at which the client pings the broker when there is no other traffic."
+			required:    false
+			type: uint: {
+				default: 60
+				unit:    "seconds"
+			}
+		}
+		qos: {
+			common:      false
+			description: "The MQTT quality of service level."
+			required:    false
+			type: uint: {
+				default: 1
+				enum: {
+					"0": "At most once, messages may be lost."
+					"1": "At least once, messages may be duplicated."
+					"2": "Exactly once."
+				}
+				unit: null
+			}
+		}
+	}
+
+	how_it_works: {
+		rumqttc: {
+			title: "rumqttc"
+			body:  """
+				The `mqtt` source/sink uses [`rumqttc`](\(urls.rumqttc)) under the hood and speaks MQTT 3.1.1.
+				The connection is re-established automatically whenever it is lost.
+				"""
+		}
+		tls: {
+			title: "TLS"
+			body:  """
+				When TLS is enabled, the `tls.ca_file` option is required, and the broker certificate is always
+				verified against it. Set `tls.crt_file` and `tls.key_file` to authenticate with a client
+				certificate.
+				"""
+		}
+	}
+}
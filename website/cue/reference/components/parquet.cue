@@ -0,0 +1,90 @@
+package metadata
+
+components: _parquet: {
+	configuration: {
+		parquet: {
+			common:      false
+			description: """
+				Writes each batch as a [Parquet](\(urls.apache_parquet)) file instead of encoding its events
+				with `encoding.codec`. The other `encoding` options still apply to the events, and
+				`compression` is ignored since Parquet compresses each column instead.
+				"""
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					compression: {
+						common:      false
+						description: "The compression of the columns."
+						required:    false
+						type: string: {
+							default: "snappy"
+							enum: {
+								none:   "No compression."
+								snappy: "[Snappy](\(urls.snappy)) compression."
+								gzip:   "[Gzip](\(urls.gzip)) compression."
+							}
+						}
+					}
+					row_group_size: {
+						common:      false
+						description: "The maximum number of rows of each row group. Defaults to the `batch.max_events` of the sink, so that each batch is written as a single row group."
+						required:    false
+						type: uint: {
+							default: null
+							unit:    "events"
+						}
+					}
+					schema: {
+						common:      false
+						description: "The columns of the files, keyed by the path of the field each one is read from. When not set, the columns are inferred from the first batch, see [schema inference](#parquet-schema-inference)."
+						required:    false
+						type: object: {
+							examples: [
+								{
+									"message":     "string"
+									"http.status": "int64"
+									"timestamp":   "timestamp"
+								},
+							]
+							options: {
+								"*": {
+									common:      false
+									description: "The type of the column."
+									required:    false
+									type: string: {
+										default: null
+										enum: {
+											string:    "UTF-8 strings. Values of other types are converted to strings."
+											int64:     "64 bit signed integers."
+											double:    "64 bit floats."
+											boolean:   "Booleans."
+											timestamp: "Microseconds since the Unix epoch. Integers are taken to be seconds since the Unix epoch, and strings RFC 3339 timestamps."
+											json:      "JSON documents, for objects and arrays."
+										}
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	how_it_works: {
+		parquet_schema_inference: {
+			title: "Parquet schema inference"
+			body:  """
+				When `parquet.schema` isn't set, the columns are inferred from the top level fields of the
+				first batch the sink writes, typed after the first value of each field that isn't null.
+				Fields holding both integers and floats are written as doubles. The inferred schema is
+				kept for the lifetime of the sink, so fields that weren't part of the first batch are left
+				out of later files. Configure the schema when the fields of the events vary.
+
+				Values that can't be converted to the type of their column, and missing fields, are
+				written as nulls.
+				"""
+		}
+	}
+}
@@ -0,0 +1,84 @@
+package metadata
+
+components: sinks: amqp: {
+	title: "AMQP"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		acknowledgements: true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					enum: ["json", "text"]
+				}
+			}
+			request: enabled: false
+			tls: {
+				enabled:                true
+				can_verify_certificate: false
+				can_verify_hostname:    false
+				enabled_default:        false
+			}
+			to: components._amqp.features.send.to
+		}
+	}
+
+	support: components._amqp.support
+
+	configuration: components._amqp.configuration & {
+		exchange: {
+			description: "The exchange to publish messages to. Use an empty string for the default exchange, which routes messages to the queue named by their routing key."
+			required:    true
+			type: string: {
+				examples: ["logs", "logs-{{ application }}"]
+				syntax: "template"
+			}
+		}
+		routing_key: {
+			common:      true
+			description: "The routing key to publish messages with. Messages are published with an empty routing key when unset."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ host }}", "logs.{{ level }}"]
+				syntax: "template"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+		traces:  false
+	}
+
+	how_it_works: components._amqp.how_it_works & {
+		publisher_confirms: {
+			title: "Publisher confirms"
+			body:  """
+				The sink enables [publisher confirms](\(urls.amqp_publisher_confirms)) on its channel, and
+				waits for the broker to confirm each message before acknowledging the event. Messages the
+				broker negatively acknowledges are reported as errors.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		component_errors_total:      components.sources.internal_metrics.output.metrics.component_errors_total
+		component_sent_bytes_total:  components.sources.internal_metrics.output.metrics.component_sent_bytes_total
+		component_sent_events_total: components.sources.internal_metrics.output.metrics.component_sent_events_total
+		send_errors_total:           components.sources.internal_metrics.output.metrics.send_errors_total
+	}
+}
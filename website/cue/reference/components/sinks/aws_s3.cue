@@ -25,8 +25,8 @@ components: sinks: aws_s3: components._aws & {
 			compression: {
 				enabled: true
 				default: "gzip"
-				algorithms: ["none", "gzip"]
-				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+				algorithms: ["none", "gzip", "zstd", "snappy", "lz4"]
+				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19]
 			}
 			encoding: {
 				enabled: true
@@ -244,6 +244,36 @@ components: sinks: aws_s3: components._aws & {
 				options: {}
 			}
 		}
+		parquet: components._parquet.configuration.parquet
+		multipart: {
+			common:      false
+			description: "Streams each batch to S3 with a multipart upload while it is being built, instead of uploading it in one request once it is complete. See [Multipart uploads](#multipart-uploads) for more details."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					part_size_bytes: {
+						common:      false
+						description: "The size of each uploaded part, after compression. It must be between 5MiB and 5GiB, and a batch can be made of at most 10,000 parts."
+						required:    false
+						type: uint: {
+							default: 16777216
+							unit:    "bytes"
+						}
+					}
+					data_dir: {
+						common:      false
+						description: "The directory used to keep track of the uploads in progress. By default, the global `data_dir` option is used. Please make sure the Vector project has write permissions to this dir."
+						required:    false
+						type: string: {
+							default: null
+							examples: ["/var/lib/vector"]
+							syntax: "file_system_path"
+						}
+					}
+				}
+			}
+		}
 	}
 
 	input: {
@@ -253,6 +283,29 @@ components: sinks: aws_s3: components._aws & {
 	}
 
 	how_it_works: {
+		parquet_schema_inference: components._parquet.how_it_works.parquet_schema_inference
+		multipart_uploads: {
+			title: "Multipart uploads"
+			body:  """
+				When `multipart` is set, each batch is written to S3 with a
+				[multipart upload](\(urls.aws_s3_multipart_upload)): events are encoded and
+				compressed as they arrive, and a part is uploaded every time
+				`multipart.part_size_bytes` of data is buffered. This keeps the memory used by
+				very large batches bounded by the part size instead of the batch size. Parts are
+				uploaded while the next events are written, and every request of the uploads
+				follows the [`request`](#request) settings for concurrency, rate limits, timeouts
+				and retries.
+
+				The upload is completed when the batch closes, after which its events are
+				acknowledged in the order they were received. If a request still fails after
+				its retries, the upload is aborted and its events are marked as errored. Only `none`, `gzip` and `zstd` compression can be streamed this
+				way, and the `parquet` encoding isn't supported.
+
+				The uploads in progress are recorded in the data directory. When Vector starts,
+				the uploads left behind by a previous run are aborted; paired with a disk
+				buffer, their events were never acknowledged and are sent again.
+				"""
+		}
 		cross_account: {
 			title: "Cross account object writing"
 			body:  """
@@ -373,11 +426,16 @@ components: sinks: aws_s3: components._aws & {
 				{
 					_action: "PutObject"
 				},
+				{
+					_action:       "AbortMultipartUpload"
+					required_when: "[`multipart`](#multipart) is set"
+				},
 			]
 		},
 	]
 
 	telemetry: metrics: {
+		component_errors_total:           components.sources.internal_metrics.output.metrics.component_errors_total
 		component_sent_bytes_total:       components.sources.internal_metrics.output.metrics.component_sent_bytes_total
 		component_sent_events_total:      components.sources.internal_metrics.output.metrics.component_sent_events_total
 		component_sent_event_bytes_total: components.sources.internal_metrics.output.metrics.component_sent_event_bytes_total
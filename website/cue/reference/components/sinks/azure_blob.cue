@@ -25,8 +25,8 @@ components: sinks: azure_blob: {
 			compression: {
 				enabled: true
 				default: "gzip"
-				algorithms: ["none", "gzip"]
-				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+				algorithms: ["none", "gzip", "zstd", "snappy", "lz4"]
+				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19]
 			}
 			encoding: {
 				enabled: true
@@ -109,6 +109,7 @@ components: sinks: azure_blob: {
 				syntax:  "strftime"
 			}
 		}
+		parquet: components._parquet.configuration.parquet
 	}
 
 	input: {
@@ -118,6 +119,7 @@ components: sinks: azure_blob: {
 	}
 
 	how_it_works: {
+		parquet_schema_inference: components._parquet.how_it_works.parquet_schema_inference
 		object_naming: {
 			title: "Object naming"
 			body:  """
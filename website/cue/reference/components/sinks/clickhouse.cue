@@ -25,8 +25,8 @@ components: sinks: clickhouse: {
 			compression: {
 				enabled: true
 				default: "gzip"
-				algorithms: ["none", "gzip"]
-				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+				algorithms: ["none", "gzip", "zstd", "snappy", "lz4"]
+				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19]
 			}
 			encoding: {
 				enabled: true
@@ -76,6 +76,12 @@ components: sinks: clickhouse: {
 			password_example: "${CLICKHOUSE_PASSWORD}"
 			username_example: "${CLICKHOUSE_USERNAME}"
 		}}
+		coerce_types: {
+			common:      false
+			description: "Fetch the schema of the table and convert the fields of events to the types of its columns before inserting them. See [type coercion](#type-coercion) for more details."
+			required:    false
+			type: bool: default: false
+		}
 		database: {
 			common:      true
 			description: "The database that contains the table that data will be inserted into."
@@ -85,13 +91,39 @@ components: sinks: clickhouse: {
 				examples: ["mydatabase"]
 			}
 		}
+		distribution: components._distribution.configuration.distribution
 		endpoint: {
-			description: "The endpoint of the [Clickhouse](\(urls.clickhouse)) server."
-			required:    true
+			description: "The endpoint of the [Clickhouse](\(urls.clickhouse)) server. Either this or `endpoints` is required."
+			required:    false
+			common:      true
 			type: string: {
+				default: null
 				examples: ["http://localhost:8123"]
 			}
 		}
+		endpoints: {
+			description: "The endpoints of the [Clickhouse](\(urls.clickhouse)) servers to spread requests over, see [load balancing](#load-balancing). Either this or `endpoint` is required."
+			required:    false
+			common:      false
+			type: array: {
+				default: null
+				items: type: string: {
+					examples: ["http://clickhouse-1:8123", "http://clickhouse-2:8123"]
+				}
+			}
+		}
+		format: {
+			common:      false
+			description: "The format events are inserted in."
+			required:    false
+			type: string: {
+				default: "json_each_row"
+				enum: {
+					json_each_row: "[`JSONEachRow`](\(urls.clickhouse_json_each_row)), one JSON object per event."
+					row_binary:    "[`RowBinary`](\(urls.clickhouse_row_binary)), which is more compact and cheaper for ClickHouse to parse. The schema of the table is always fetched and events are coerced to it, as with `coerce_types`."
+				}
+			}
+		}
 		table: {
 			description: "The table that data will be inserted into."
 			required:    true
@@ -115,8 +147,44 @@ components: sinks: clickhouse: {
 
 	telemetry: metrics: {
 		component_sent_bytes_total:       components.sources.internal_metrics.output.metrics.component_sent_bytes_total
+		endpoint_ejections_total:         components.sources.internal_metrics.output.metrics.endpoint_ejections_total
+		endpoint_restorations_total:      components.sources.internal_metrics.output.metrics.endpoint_restorations_total
 		component_sent_events_total:      components.sources.internal_metrics.output.metrics.component_sent_events_total
 		component_sent_event_bytes_total: components.sources.internal_metrics.output.metrics.component_sent_event_bytes_total
+		component_discarded_events_total: components.sources.internal_metrics.output.metrics.component_discarded_events_total
+		component_errors_total:           components.sources.internal_metrics.output.metrics.component_errors_total
 		events_out_total:                 components.sources.internal_metrics.output.metrics.events_out_total
 	}
+
+	how_it_works: {
+		load_balancing: components._distribution.how_it_works.load_balancing
+
+		type_coercion: {
+			title: "Type coercion"
+			body: """
+				With `coerce_types` enabled, or the `row_binary` format, the sink fetches the columns of
+				the table from `system.columns` during the health check, or before encoding the first
+				event if the health check is disabled, and events wait until they could be fetched. Only
+				the fields of events that match a column are sent, and their values are converted to the
+				type of the column as events are encoded, before they are batched:
+
+				* Integers, floats and booleans are parsed from strings where needed, and checked to fit
+				  the column.
+				* `Date`, `DateTime` and `DateTime64` columns take timestamps, Unix times in seconds or
+				  RFC 3339 strings, and keep the precision of the column regardless of its timezone.
+				* `Array` and `Map` columns take arrays and objects, with their items converted to the
+				  type of the items of the column.
+				* `Nullable` and `LowCardinality` columns take the values of the type they wrap.
+				* `String` columns take any value, with arrays and objects encoded as JSON.
+
+				Events with a field that can't be converted are rejected, and the rest of their batch is
+				inserted. The columns are fetched again every five minutes, and after an event couldn't be
+				converted or ClickHouse refused an insert for not matching the table. With `JSONEachRow`
+				columns missing from an event get their default, while with `RowBinary` they get the
+				default value of their type, or null if they are nullable.
+				`RowBinary` doesn't support some types, like `Decimal` or `Tuple`, and the health check
+				fails if the table has columns of those types.
+				"""
+		}
+	}
 }
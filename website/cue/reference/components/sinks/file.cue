@@ -42,6 +42,12 @@ components: sinks: file: {
 	}
 
 	configuration: {
+		atomic_finalize: {
+			common:      false
+			description: "Write files under a temporary name and only move them to their final path once they are closed, so that nothing reading the final path ever sees a partially written file. See [atomic finalize](#atomic-finalize) for where finalized files end up."
+			required:    false
+			type: bool: default: false
+		}
 		idle_timeout_secs: {
 			common:      false
 			description: "The amount of time a file can be idle  and stay open. After not receiving any events for this timeout, the file will be flushed and closed.\n"
@@ -59,6 +65,74 @@ components: sinks: file: {
 				syntax: "template"
 			}
 		}
+		retention: {
+			common:      false
+			description: "Limits on the rotated files kept for each path. The oldest rotated files are deleted whenever a file is rotated and the limits are exceeded. Requires `rotation` or `atomic_finalize`."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					max_bytes: {
+						common:      false
+						description: "The maximum total size of the rotated files kept for each path."
+						required:    false
+						type: uint: {
+							default: null
+							unit:    "bytes"
+						}
+					}
+					max_files: {
+						common:      false
+						description: "The maximum number of rotated files kept for each path."
+						required:    false
+						type: uint: {
+							default: null
+							unit:    null
+						}
+					}
+				}
+			}
+		}
+		rotation: {
+			common:      false
+			description: "When to rotate files. Rotated files are renamed by inserting a sequence number or timestamp before their extension, so `app.log` is rotated to `app.1.log`, and the next events are written to a new `app.log`."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					max_age_secs: {
+						common:      false
+						description: "Rotate a file once it was created this long ago. This is checked whenever an event is written to the file."
+						required:    false
+						type: uint: {
+							default: null
+							unit:    "seconds"
+						}
+					}
+					max_bytes: {
+						common:      false
+						description: "Rotate a file once it holds this many bytes. Bytes are counted before compression."
+						required:    false
+						type: uint: {
+							default: null
+							unit:    "bytes"
+						}
+					}
+					naming: {
+						common:      false
+						description: "How rotated files are named."
+						required:    false
+						type: string: {
+							default: "sequence"
+							enum: {
+								sequence:  "Number rotated files from 1, with newer files getting higher numbers, like `app.3.log`."
+								timestamp: "Name rotated files after the UTC time they were rotated at, like `app.20220601T101500.120Z.log`."
+							}
+						}
+					}
+				}
+			}
+		}
 	}
 
 	input: {
@@ -78,6 +152,33 @@ components: sinks: file: {
 				"""
 		}
 
+		atomic_finalize: {
+			title: "Atomic finalize"
+			body: """
+				With `atomic_finalize` enabled, files are written to a hidden temporary file next to
+				them, named like `.app.log.tmp`, and moved to their final path once closed, which
+				happens after `idle_timeout_secs` without events, when they are rotated, or when
+				Vector shuts down.
+
+				Finalized files are never written to again. If rotation is configured, or if a file
+				already exists at the final path, the file is moved to the next rotated name instead.
+				"""
+		}
+
+		rotation: {
+			title: "Rotation & retention"
+			body: """
+				Files are rotated once they reach `rotation.max_bytes` or `rotation.max_age_secs`.
+				Without `atomic_finalize`, rotating renames the file, and the next events for the
+				same path are written to a new file.
+
+				Rotated files are kept next to the file they were rotated from. When `retention`
+				limits are set, the oldest rotated files of a path are deleted every time a file of
+				that path is rotated, until the remaining ones fit in the limits. The file currently
+				written to is never deleted.
+				"""
+		}
+
 		durability: {
 			title: "Durability of Created Files"
 			body: """
@@ -96,6 +197,8 @@ components: sinks: file: {
 		component_sent_events_total:      components.sources.internal_metrics.output.metrics.component_sent_events_total
 		component_sent_event_bytes_total: components.sources.internal_metrics.output.metrics.component_sent_event_bytes_total
 		events_discarded_total:           components.sources.internal_metrics.output.metrics.events_discarded_total
+		files_rotated_total:              components.sources.internal_metrics.output.metrics.files_rotated_total
 		processing_errors_total:          components.sources.internal_metrics.output.metrics.processing_errors_total
+		rotated_files_deleted_total:      components.sources.internal_metrics.output.metrics.rotated_files_deleted_total
 	}
 }
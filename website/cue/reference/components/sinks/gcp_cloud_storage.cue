@@ -25,8 +25,8 @@ components: sinks: gcp_cloud_storage: {
 			compression: {
 				enabled: true
 				default: "none"
-				algorithms: ["gzip"]
-				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+				algorithms: ["gzip", "zstd", "snappy", "lz4"]
+				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19]
 			}
 			encoding: {
 				enabled: true
@@ -167,6 +167,7 @@ components: sinks: gcp_cloud_storage: {
 				}
 			}
 		}
+		parquet: components._parquet.configuration.parquet
 	}
 
 	input: {
@@ -176,6 +177,7 @@ components: sinks: gcp_cloud_storage: {
 	}
 
 	how_it_works: {
+		parquet_schema_inference: components._parquet.how_it_works.parquet_schema_inference
 		object_access_control_list: {
 			title: "Object access control list (ACL)"
 			body:  """
@@ -25,8 +25,8 @@ components: sinks: http: {
 			compression: {
 				enabled: true
 				default: "none"
-				algorithms: ["none", "gzip"]
-				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+				algorithms: ["none", "gzip", "zstd", "snappy", "lz4"]
+				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19]
 			}
 			encoding: {
 				enabled: true
@@ -77,16 +77,34 @@ components: sinks: http: {
 			password_example: "${HTTP_PASSWORD}"
 			username_example: "${HTTP_USERNAME}"
 		}}
+		distribution: components._distribution.configuration.distribution
 		uri: {
 			description: """
 				The full URI to make HTTP requests to. This should include the protocol and host,
 				but can also include the port, path, and any other valid part of a URI.
+				Either this or `uris` is required.
 				"""
-			required: true
+			required: false
+			common:   true
 			type: string: {
+				default: null
 				examples: ["https://10.22.212.22:9000/endpoint"]
 			}
 		}
+		uris: {
+			description: """
+				The full URIs to spread HTTP requests over, see [load balancing](#load-balancing).
+				Either this or `uri` is required.
+				"""
+			required: false
+			common:   false
+			type: array: {
+				default: null
+				items: type: string: {
+					examples: ["https://10.22.212.22:9000/endpoint", "https://10.22.212.23:9000/endpoint"]
+				}
+			}
+		}
 		healthcheck: type: object: options: uri: {
 			common: false
 			description: """
@@ -109,6 +127,8 @@ components: sinks: http: {
 
 	telemetry: metrics: {
 		component_sent_bytes_total:       components.sources.internal_metrics.output.metrics.component_sent_bytes_total
+		endpoint_ejections_total:         components.sources.internal_metrics.output.metrics.endpoint_ejections_total
+		endpoint_restorations_total:      components.sources.internal_metrics.output.metrics.endpoint_restorations_total
 		component_sent_events_total:      components.sources.internal_metrics.output.metrics.component_sent_events_total
 		component_sent_event_bytes_total: components.sources.internal_metrics.output.metrics.component_sent_event_bytes_total
 		events_out_total:                 components.sources.internal_metrics.output.metrics.events_out_total
@@ -117,4 +137,8 @@ components: sinks: http: {
 		processed_bytes_total:            components.sources.internal_metrics.output.metrics.processed_bytes_total
 		processed_events_total:           components.sources.internal_metrics.output.metrics.processed_events_total
 	}
+
+	how_it_works: {
+		load_balancing: components._distribution.how_it_works.load_balancing
+	}
 }
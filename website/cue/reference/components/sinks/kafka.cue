@@ -46,6 +46,19 @@ components: sinks: kafka: {
 
 	configuration: {
 		bootstrap_servers: components._kafka.configuration.bootstrap_servers
+		delivery_mode: {
+			common:      false
+			description: "The delivery guarantee of the producer. See [Delivery modes](#delivery-modes) for more info."
+			required:    false
+			type: string: {
+				default: "at_least_once"
+				enum: {
+					at_least_once: "Records are retried until the brokers acknowledge them, which may write a record more than once."
+					idempotent:    "The brokers discard records retried by the producer that they have already written."
+					transactional: "Each batch of events is written in a Kafka transaction that is committed before the events are acknowledged. Requires the `transaction` options."
+				}
+			}
+		}
 		key_field: {
 			common:      true
 			description: "The log field name or tags key to use for the topic key. If the field does not exist in the log or in tags, a blank value will be used. If unspecified, the key is not sent. Kafka uses a hash of the key to choose the partition or uses round-robin if the record has no key."
@@ -66,6 +79,28 @@ components: sinks: kafka: {
 				unit: null
 			}
 		}
+		partition_key_field: {
+			common:      false
+			description: "The log field name or tags key whose value the `partitioner` hashes. If unspecified, the key set by `key_field` is hashed. Setting this without a `partitioner` uses `murmur2`."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["tenant_id"]
+			}
+		}
+		partitioner: {
+			common:      false
+			description: "Chooses the partition of each record in Vector instead of leaving it to librdkafka, which hashes the record key. See [Partitioning](#partitioning) for more info."
+			required:    false
+			type: string: {
+				default: null
+				enum: {
+					murmur2:     "Hashes the partition key like the Java client's default partitioner, so records land on the same partitions as those produced by Java clients. Records without a partition key are left to librdkafka."
+					random:      "Picks a partition at random for each record."
+					round_robin: "Cycles through the partitions of the topic."
+				}
+			}
+		}
 		sasl: {
 			common:      false
 			description: "Options for SASL/SCRAM authentication support."
@@ -118,6 +153,77 @@ components: sinks: kafka: {
 				syntax: "template"
 			}
 		}
+		transaction: {
+			common:      false
+			description: "Options for the transactions written with `delivery_mode` set to `transactional`."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					commit_interval_secs: {
+						common:      false
+						description: "How long a transaction is kept open waiting for more events before it is committed."
+						required:    false
+						type: float: {
+							default: 1.0
+							unit:    "seconds"
+						}
+					}
+					max_bytes: {
+						common:      false
+						description: "The most bytes of encoded records written in one transaction."
+						required:    false
+						type: uint: {
+							default: 10000000
+							unit:    "bytes"
+						}
+					}
+					max_events: {
+						common:      false
+						description: "The most events written in one transaction."
+						required:    false
+						type: uint: {
+							default: 1000
+							unit:    "events"
+						}
+					}
+					timeout_ms: {
+						common:      false
+						description: "How long the brokers wait for a transaction to be committed before aborting it."
+						required:    false
+						type: uint: {
+							default: 60000
+							unit:    "milliseconds"
+						}
+					}
+					transactional_id: {
+						description: "Identifies the producer across restarts, fencing off earlier instances using the same ID. Must be unique to each Vector instance."
+						required:    true
+						type: string: {
+							examples: ["vector-${HOSTNAME}"]
+						}
+					}
+				}
+			}
+		}
+		headers: {
+			common:      false
+			description: "Headers added to every record, rendered from templates. These come before the headers read from `headers_key`."
+			required:    false
+			type: object: {
+				examples: [{"source": "vector", "service": "{{ service }}"}]
+				options: {
+					"*": {
+						description: "A header to add to records."
+						required:    true
+						type: string: {
+							examples: ["vector", "{{ service }}"]
+							syntax: "template"
+						}
+					}
+				}
+			}
+		}
 		headers_key: {
 			common:      false
 			description: "The log field name to use for the Kafka headers. If omitted, no headers will be written."
@@ -142,9 +248,42 @@ components: sinks: kafka: {
 		traces: false
 	}
 
-	how_it_works: components._kafka.how_it_works
+	how_it_works: {
+		components._kafka.how_it_works
+
+		delivery_modes: {
+			title: "Delivery modes"
+			body: """
+				By default records are retried until the brokers acknowledge them, so a record
+				may be written more than once when an acknowledgement is lost. Setting
+				`delivery_mode` to `idempotent` lets the brokers discard such duplicates.
+
+				With `delivery_mode` set to `transactional`, events are batched as set by the
+				`transaction` options and each batch is written in a Kafka transaction. The
+				transaction is committed before the events of the batch are acknowledged, and
+				aborted if any of its records fails or its commit keeps timing out, in which
+				case none of them are visible to consumers reading with `isolation.level` set
+				to `read_committed`. Combined with end-to-end acknowledgements, this gives
+				exactly-once delivery for sources that only advance past events once they are
+				acknowledged. The sink stops if the producer runs into a fatal error, such as
+				being fenced by another producer with the same `transactional_id`.
+				"""
+		}
+
+		partitioning: {
+			title: "Partitioning"
+			body: """
+				By default librdkafka chooses the partition of each record by hashing its key.
+				The `partitioner` option chooses it in Vector instead, looking up the number of
+				partitions of each topic every few minutes. The `murmur2` partitioner hashes
+				`partition_key_field`, or the record key, exactly like the Java client's default
+				partitioner, so Java producers and Vector agree on the partition of each key.
+				"""
+		}
+	}
 
 	telemetry: metrics: {
+		component_errors_total:              components.sources.internal_metrics.output.metrics.component_errors_total
 		component_sent_events_total:         components.sources.internal_metrics.output.metrics.component_sent_events_total
 		component_sent_event_bytes_total:    components.sources.internal_metrics.output.metrics.component_sent_event_bytes_total
 		component_sent_bytes_total:          components.sources.internal_metrics.output.metrics.component_sent_bytes_total
@@ -26,7 +26,7 @@ components: sinks: loki: {
 			compression: {
 				enabled: true
 				default: "none"
-				algorithms: ["none", "gzip"]
+				algorithms: ["none", "gzip", "snappy"]
 				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
 			}
 			encoding: {
@@ -68,17 +68,65 @@ components: sinks: loki: {
 	}
 
 	configuration: {
+		distribution: components._distribution.configuration.distribution
 		endpoint: {
-			description: "The base URL of the Loki instance. Vector will append `/loki/api/v1/push` to this."
-			required:    true
+			description: "The base URL of the Loki instance. Vector will append `/loki/api/v1/push` to this. Either this or `endpoints` is required."
+			required:    false
+			common:      true
 			type: string: {
+				default: null
 				examples: ["http://localhost:3100"]
 			}
 		}
+		endpoints: {
+			description: """
+				The base URLs of the Loki instances to spread requests over, see [load balancing](#load-balancing).
+				Vector will append `/loki/api/v1/push` to each of them. Either this or `endpoint` is required.
+
+				Requests to different instances may complete out of order, so this is best used together with
+				`out_of_order_action = "accept"`.
+				"""
+			required:    false
+			common:      false
+			type: array: {
+				default: null
+				items: type: string: {
+					examples: ["http://loki-1:3100", "http://loki-2:3100"]
+				}
+			}
+		}
 		auth: configuration._http_auth & {_args: {
 			password_example: "${LOKI_PASSWORD}"
 			username_example: "${LOKI_USERNAME}"
 		}}
+		label_cardinality_limit: {
+			common:      false
+			description: "Limits the number of distinct label sets, that is streams, each tenant sends to Loki. See [label cardinality](#label-cardinality) for more details."
+			required:    false
+			type: object: {
+				examples: [{max_label_sets: 10_000, window_secs: 3_600}]
+				options: {
+					max_label_sets: {
+						common:      true
+						description: "The maximum number of distinct label sets each tenant may use within `window_secs`."
+						required:    true
+						type: uint: {
+							examples: [10_000]
+							unit: null
+						}
+					}
+					window_secs: {
+						common:      false
+						description: "How long a label set counts towards the limit after the last event that used it."
+						required:    false
+						type: uint: {
+							default: 3_600
+							unit:    "seconds"
+						}
+					}
+				}
+			}
+		}
 		labels: {
 			description: """
 				A set of labels that are attached to each batch of events. Both keys and values are templatable, which
@@ -129,6 +177,18 @@ components: sinks: loki: {
 				}
 			}
 		}
+		payload_format: {
+			common:      false
+			description: "The format of the requests pushed to Loki."
+			required:    false
+			type: string: {
+				default: "json"
+				enum: {
+					json:     "JSON, compressed as set by `compression`."
+					protobuf: "Protocol Buffers compressed with Snappy, as used by Promtail. This is more compact and cheaper for Loki to decode. `compression` must be `none` or `snappy`."
+				}
+			}
+		}
 		remove_label_fields: {
 			common:      false
 			description: "If this is set to `true` then when labels are collected from events those fields will also get removed from the event."
@@ -166,6 +226,7 @@ components: sinks: loki: {
 	}
 
 	how_it_works: {
+		load_balancing: components._distribution.how_it_works.load_balancing
 		decentralized_deployments: {
 			title: "Decentralized Deployments"
 			body: """
@@ -179,6 +240,23 @@ components: sinks: loki: {
 				"""
 		}
 
+		label_cardinality: {
+			title: "Label cardinality"
+			body: """
+				Every distinct set of labels is a separate stream in Loki, so labels templated from
+				fields with many values, like request or user IDs, can create more streams than Loki
+				can index. `label_cardinality_limit` guards against this by tracking, for each tenant,
+				the label sets used within the last `label_cardinality_limit.window_secs`.
+
+				Once a tenant has used `label_cardinality_limit.max_label_sets` label sets, events that
+				would start a new one are sent with only their static labels, those whose name and value
+				are both fixed. Their other labels are moved into the log line instead, as fields for the
+				`json` and `logfmt` codecs and as a `logfmt` prefix of the line for the `text` codec, so
+				they can still be queried. The `label_set_limited_events_total` metric counts these
+				events.
+				"""
+		}
+
 		event_ordering: {
 			title: "Event Ordering"
 			body: """
@@ -193,10 +271,13 @@ components: sinks: loki: {
 
 	telemetry: metrics: {
 		component_sent_bytes_total:       components.sources.internal_metrics.output.metrics.component_sent_bytes_total
+		endpoint_ejections_total:         components.sources.internal_metrics.output.metrics.endpoint_ejections_total
+		endpoint_restorations_total:      components.sources.internal_metrics.output.metrics.endpoint_restorations_total
 		component_sent_events_total:      components.sources.internal_metrics.output.metrics.component_sent_events_total
 		component_sent_event_bytes_total: components.sources.internal_metrics.output.metrics.component_sent_event_bytes_total
 		events_discarded_total:           components.sources.internal_metrics.output.metrics.events_discarded_total
 		events_out_total:                 components.sources.internal_metrics.output.metrics.events_out_total
+		label_set_limited_events_total:   components.sources.internal_metrics.output.metrics.label_set_limited_events_total
 		processed_bytes_total:            components.sources.internal_metrics.output.metrics.processed_bytes_total
 		processing_errors_total:          components.sources.internal_metrics.output.metrics.processing_errors_total
 		streams_total:                    components.sources.internal_metrics.output.metrics.streams_total
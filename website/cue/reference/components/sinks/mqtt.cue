@@ -0,0 +1,81 @@
+package metadata
+
+components: sinks: mqtt: {
+	title: "MQTT"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		acknowledgements: true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					enum: ["json", "text"]
+				}
+			}
+			request: enabled: false
+			tls: {
+				enabled:                true
+				can_verify_certificate: false
+				can_verify_hostname:    false
+				enabled_default:        false
+			}
+			to: components._mqtt.features.send.to
+		}
+	}
+
+	support: components._mqtt.support
+
+	configuration: components._mqtt.configuration & {
+		topic: {
+			description: "The topic to publish messages to."
+			required:    true
+			type: string: {
+				examples: ["vector", "sensors/{{ host }}/readings"]
+				syntax: "template"
+			}
+		}
+		retain: {
+			common:      false
+			description: "Whether the broker should retain the last message of each topic and deliver it to new subscribers."
+			required:    false
+			type: bool: default: false
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+		traces:  false
+	}
+
+	how_it_works: components._mqtt.how_it_works & {
+		delivery: {
+			title: "Delivery"
+			body:  """
+				Events are acknowledged once the broker confirmed their message with the configured `qos`: on
+				`PUBACK` for QoS 1, and on `PUBCOMP` for QoS 2. Messages published with QoS 0 are never
+				confirmed, so their events are acknowledged as soon as the messages are sent. Messages still
+				awaiting a confirmation are sent again when the connection is re-established.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		component_errors_total:      components.sources.internal_metrics.output.metrics.component_errors_total
+		component_sent_bytes_total:  components.sources.internal_metrics.output.metrics.component_sent_bytes_total
+		component_sent_events_total: components.sources.internal_metrics.output.metrics.component_sent_events_total
+		send_errors_total:           components.sources.internal_metrics.output.metrics.send_errors_total
+	}
+}
@@ -0,0 +1,157 @@
+package metadata
+
+components: sources: amqp: {
+	title: "AMQP"
+
+	features: {
+		acknowledgements: true
+		collect: {
+			checkpoint: enabled: false
+			tls: {
+				enabled:                true
+				can_verify_certificate: false
+				can_verify_hostname:    false
+				enabled_default:        false
+			}
+			from: components._amqp.features.collect.from
+		}
+		multiline: enabled: false
+		codecs: {
+			enabled:         true
+			default_framing: "bytes"
+		}
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	support: components._amqp.support
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: components._amqp.configuration & {
+		queue: {
+			common:      true
+			description: "The queue to consume from. The queue must already exist."
+			required:    false
+			type: string: {
+				default: "vector"
+				examples: ["logs"]
+			}
+		}
+		consumer: {
+			common:      false
+			description: "The consumer tag identifying this consumer to the broker."
+			required:    false
+			type: string: {
+				default: "vector"
+				examples: ["vector-aggregator-1"]
+			}
+		}
+		prefetch_count: {
+			common:      false
+			description: "The maximum number of messages the broker delivers before waiting for them to be acknowledged. Unlimited when unset."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [100]
+				unit: null
+			}
+		}
+		routing_key_field: {
+			common:      false
+			description: "The log field name to use for the routing key the message was published with."
+			required:    false
+			type: string: {
+				default: "routing"
+				examples: ["routing"]
+			}
+		}
+		exchange_key: {
+			common:      false
+			description: "The log field name to use for the exchange the message was published to."
+			required:    false
+			type: string: {
+				default: "exchange"
+				examples: ["exchange"]
+			}
+		}
+		offset_key: {
+			common:      false
+			description: "The log field name to use for the delivery tag of the message."
+			required:    false
+			type: string: {
+				default: "offset"
+				examples: ["offset"]
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "An individual AMQP message"
+		fields: {
+			message: {
+				description: "The raw payload of the AMQP message."
+				required:    true
+				type: string: {
+					examples: ["53.126.150.246 - - [01/Oct/2020:11:25:58 -0400] \"GET /disintermediate HTTP/2.0\" 401 20308"]
+				}
+			}
+			routing: {
+				description: "The routing key the message was published with."
+				required:    true
+				type: string: {
+					examples: ["logs.nginx"]
+				}
+			}
+			exchange: {
+				description: "The exchange the message was published to. Empty for the default exchange."
+				required:    true
+				type: string: {
+					examples: ["logs"]
+				}
+			}
+			offset: {
+				description: "The delivery tag of the message, unique within the channel it was delivered on."
+				required:    true
+				type: uint: {
+					examples: [1]
+					unit: null
+				}
+			}
+			timestamp: {
+				description: "The timestamp set by the publisher, or the time the message was received when missing."
+				required:    true
+				type: timestamp: {}
+			}
+		}
+	}
+
+	telemetry: metrics: {
+		component_errors_total:               components.sources.internal_metrics.output.metrics.component_errors_total
+		component_received_bytes_total:       components.sources.internal_metrics.output.metrics.component_received_bytes_total
+		component_received_events_total:      components.sources.internal_metrics.output.metrics.component_received_events_total
+		component_received_event_bytes_total: components.sources.internal_metrics.output.metrics.component_received_event_bytes_total
+	}
+
+	how_it_works: components._amqp.how_it_works & {
+		acknowledgements: {
+			title: "Acknowledgements"
+			body:  """
+				Messages are consumed with manual acknowledgements. With end-to-end acknowledgements enabled,
+				a message is acknowledged to the broker once all of its events have been delivered, and
+				rejected without being requeued when delivery failed or its events were rejected, which
+				dead-letters it if the queue is configured to. Otherwise, messages are acknowledged as soon as
+				they have been handed to the rest of the pipeline.
+				"""
+		}
+	}
+}
@@ -287,19 +287,22 @@ components: sources: file: {
 			title: "Compressed Files"
 			body: """
 				Vector will transparently detect files which have been compressed
-				using Gzip and decompress them for reading. This detection process
-				looks for the unique sequence of bytes in the Gzip header and does
-				not rely on the compressed files adhering to any kind of naming
-				convention.
+				using Gzip or Zstandard and decompress them for reading. This detection
+				process looks for the unique sequence of bytes in the Gzip and Zstandard
+				headers and does not rely on the compressed files adhering to any kind
+				of naming convention.
+
+				Compressed files are fingerprinted on their decompressed content, so a
+				file compressed by log rotation is recognized as the file it was rotated
+				from. Vector then resumes reading it from the position it had reached in
+				the uncompressed file, so that lines written before the rotation but not
+				yet read are not lost. Compressed files are expected to be complete and
+				are read to completion once, they are not tailed for new data.
 
 				One caveat with reading compressed files is that Vector is not able
-				to efficiently seek into them. Rather than implement a
-				potentially-expensive full scan as a seek mechanism, Vector
-				currently will not attempt to make further reads from a file for
-				which it has already stored a checkpoint in a previous run. For
-				this reason, users should take care to allow Vector to fully
-				process any compressed files before shutting the process down or moving the
-				files to another location on disk.
+				to efficiently seek into them, so resuming from a checkpoint requires
+				decompressing the file up to that position. Compressed files matching
+				the `include` patterns are not read at all when `read_from` is `end`.
 				"""
 		}
 
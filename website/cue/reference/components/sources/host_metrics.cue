@@ -68,7 +68,7 @@ components: sources: host_metrics: {
 
 	configuration: {
 		collectors: {
-			description: "The list of host metric collector services to use. Defaults to all collectors."
+			description: "The list of host metric collector services to use. Defaults to all collectors except `process`."
 			common:      true
 			required:    false
 			type: array: {
@@ -83,6 +83,7 @@ components: sources: host_metrics: {
 						host:       "Metrics related to host"
 						memory:     "Metrics related to memory utilization."
 						network:    "Metrics related to network utilization."
+						process:    "Metrics related to individual processes (Linux only)."
 					}
 				}
 			}
@@ -401,6 +402,101 @@ components: sources: host_metrics: {
 				}
 			}
 		}
+		process: {
+			common: false
+			description: #"""
+				Options for the "process" metrics collector.
+
+				Note: this collector is only available on Linux systems. A process is reported when both its
+				name and its command line pass the filters below, beware that reporting every process on a busy
+				host produces a large number of metrics.
+				"""#
+			required: false
+			type: object: options: {
+				names: {
+					common:      false
+					required:    false
+					description: "Lists of process name patterns to include or exclude, matched against `/proc/<pid>/comm`."
+					type: object: options: {
+						includes: {
+							required: false
+							common:   false
+							description: """
+								The list of process name patterns for which to gather process metrics.
+
+								Defaults to including all processes.
+
+								The patterns are matched using globbing.
+								"""
+							type: array: {
+								default: ["*"]
+								items: type: string: {
+									examples: ["vector", "java*"]
+								}
+							}
+						}
+						excludes: {
+							required: false
+							common:   false
+							description: """
+								The list of process name patterns for which to not gather process metrics.
+
+								Defaults to excluding no processes.
+
+								The patterns are matched using globbing.
+								"""
+							type: array: {
+								default: []
+								items: type: string: {
+									examples: ["vector", "java*"]
+								}
+							}
+						}
+					}
+				}
+				cmdlines: {
+					common:      false
+					required:    false
+					description: "Lists of command line patterns to include or exclude, matched against the process arguments joined with spaces."
+					type: object: options: {
+						includes: {
+							required: false
+							common:   false
+							description: """
+								The list of command line patterns for which to gather process metrics.
+
+								Defaults to including all processes.
+
+								The patterns are matched using globbing.
+								"""
+							type: array: {
+								default: ["*"]
+								items: type: string: {
+									examples: ["*--config /etc/app.toml*"]
+								}
+							}
+						}
+						excludes: {
+							required: false
+							common:   false
+							description: """
+								The list of command line patterns for which to not gather process metrics.
+
+								Defaults to excluding no processes.
+
+								The patterns are matched using globbing.
+								"""
+							type: array: {
+								default: []
+								items: type: string: {
+									examples: ["*--config /etc/app.toml*"]
+								}
+							}
+						}
+					}
+				}
+			}
+		}
 	}
 
 	output: metrics: {
@@ -494,6 +590,21 @@ components: sources: host_metrics: {
 		network_transmit_packets_drop_total: _host & _network_nomac & {description: "The number of packets dropped during transmits on this interface."}
 		network_transmit_packets_total:      _host & _network_nomac & {description: "The number of packets transmitted on this interface."}
 
+		// Host processes
+		process_cpu_seconds_total: _host & _process_counter & {
+			description: "The number of CPU seconds used by this process."
+			tags: mode: {
+				description: "Which mode the CPU was running in."
+				required:    true
+				examples: ["user", "system"]
+			}
+		}
+		process_io_read_bytes_total:  _host & _process_counter & {description: "The number of bytes this process caused to be read from storage."}
+		process_io_write_bytes_total: _host & _process_counter & {description: "The number of bytes this process caused to be written to storage."}
+		process_memory_rss_bytes:     _host & _process_gauge & {description:   "The resident set size of this process, in bytes."}
+		process_open_fds:             _host & _process_gauge & {description:   "The number of file descriptors opened by this process."}
+		process_threads:              _host & _process_gauge & {description:   "The number of threads of this process."}
+
 		// Helpers
 		_host: {
 			default_namespace: "host"
@@ -583,6 +694,29 @@ components: sources: host_metrics: {
 			}
 		}
 		_network_nomac: _network_gauge & {relevant_when: "OS is not macOS"}
+		_process_tags: _host_metrics_tags & {
+			collector: examples: ["process"]
+			process_name: {
+				description: "The process name."
+				required:    true
+				examples: ["vector", "nginx"]
+			}
+			pid: {
+				description: "The process ID."
+				required:    true
+				examples: ["4232"]
+			}
+		}
+		_process_counter: {
+			type:          "counter"
+			tags:          _process_tags
+			relevant_when: "OS is Linux"
+		}
+		_process_gauge: {
+			type:          "gauge"
+			tags:          _process_tags
+			relevant_when: "OS is Linux"
+		}
 	}
 
 	telemetry: metrics: {
@@ -411,12 +411,43 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		discovered_targets: {
+			description:       "The number of scrape targets currently discovered by a provider of the `prometheus_scrape` source."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				provider: {
+					description: "The discovery provider that reported the targets."
+					required:    true
+					enum: {
+						"dns_sd":  "Targets resolved from DNS records."
+						"file_sd": "Targets read from `file_sd` files."
+					}
+				}
+			}
+		}
 		encode_errors_total: {
 			description:       "The total number of errors encountered when encoding an event."
 			type:              "counter"
 			default_namespace: "vector"
 			tags:              _internal_metrics_tags
 		}
+		endpoint_ejections_total: {
+			description:       "The total number of times an endpoint was found unhealthy and stopped receiving requests."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags & {
+				endpoint: _endpoint
+			}
+		}
+		endpoint_restorations_total: {
+			description:       "The total number of times an unhealthy endpoint recovered and received requests again."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags & {
+				endpoint: _endpoint
+			}
+		}
 		events_discarded_total: {
 			description:       "The total number of events discarded by this component."
 			type:              "counter"
@@ -668,6 +699,23 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		kafka_consumer_lag: {
+			description:       "The number of messages the Kafka consumer is behind the end of each partition it consumes."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				topic_id: {
+					description: "The Kafka topic."
+					required:    true
+					examples: ["logs"]
+				}
+				partition_id: {
+					description: "The partition of the Kafka topic."
+					required:    true
+					examples: ["0"]
+				}
+			}
+		}
 		file_delete_errors_total: {
 			description:       "The total number of failures to delete a file. This metric is deprecated in favor of `component_errors_total`."
 			type:              "counter"
@@ -708,6 +756,12 @@ components: sources: internal_metrics: {
 				file: _file
 			}
 		}
+		files_rotated_total: {
+			description:       "The total number of files rotated by the file sink."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		files_unwatched_total: {
 			description:       "The total number of times Vector has stopped watching a file."
 			type:              "counter"
@@ -790,6 +844,12 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		label_set_limited_events_total: {
+			description:       "The total number of events sent without their dynamic labels because their tenant was over its label set limit."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		logging_driver_errors_total: {
 			description: """
 				The total number of logging driver errors encountered caused by not using either
@@ -884,6 +944,33 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		remap_state_bytes: {
+			description:       "The approximate number of bytes used by the state of the remap transform."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		remap_state_entries: {
+			description:       "The number of entries in the state of the remap transform."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		remap_state_evictions_total: {
+			description:       "The total number of entries removed from the state of the remap transform."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				reason: {
+					description: "The reason the entry was removed."
+					required:    true
+					enum: {
+						expired: "The TTL of the entry elapsed."
+						limit:   "The state exceeded its configured limits."
+					}
+				}
+			}
+		}
 		request_errors_total: {
 			description:       "The total number of requests errors for this component."
 			type:              "counter"
@@ -920,6 +1007,12 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		rotated_files_deleted_total: {
+			description:       "The total number of rotated files deleted by the file sink to stay within its retention limits."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		send_errors_total: {
 			description:       "The total number of errors sending messages."
 			type:              "counter"
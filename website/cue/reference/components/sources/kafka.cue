@@ -34,6 +34,64 @@ components: sources: kafka: {
 
 	configuration: {
 		acknowledgements: configuration._source_acknowledgements
+		assignments: {
+			common:      false
+			description: "Partitions to consume, instead of subscribing to `topics`. Assigned partitions are not balanced across the consumers of `group_id`, which is only used to commit offsets. See [Bounded replays](#bounded-replays) for more info."
+			required:    false
+			type: array: {
+				default: []
+				items: type: object: {
+					examples: [{topic: "logs", partition: 0, start_timestamp: "2022-06-01T00:00:00Z", stop_timestamp: "2022-06-02T00:00:00Z"}]
+					options: {
+						partition: {
+							description: "The partition to consume."
+							required:    true
+							type: uint: {
+								examples: [0, 1]
+								unit: null
+							}
+						}
+						start_offset: {
+							common:      false
+							description: "The offset to start consuming at. If neither this nor `start_timestamp` is set, consuming starts at the offset committed for `group_id`, or as set by `auto_offset_reset` if there is none."
+							required:    false
+							type: uint: {
+								default: null
+								examples: [1000]
+								unit: null
+							}
+						}
+						start_timestamp: {
+							common:      false
+							description: "Starts consuming at the first message at or after this time."
+							required:    false
+							type: timestamp: default: null
+						}
+						stop_offset: {
+							common:      false
+							description: "The offset to stop consuming at. The message at this offset is not consumed."
+							required:    false
+							type: uint: {
+								default: null
+								examples: [2000]
+								unit: null
+							}
+						}
+						stop_timestamp: {
+							common:      false
+							description: "Stops consuming at the first message at or after this time, or at the end of the partition when the source starts if there is no such message."
+							required:    false
+							type: timestamp: default: null
+						}
+						topic: {
+							description: "The topic of the partition."
+							required:    true
+							type: string: examples: ["logs"]
+						}
+					}
+				}
+			}
+		}
 		auto_offset_reset: {
 			common:      false
 			description: """
@@ -176,10 +234,14 @@ components: sources: kafka: {
 		}
 		socket_timeout_ms: components._kafka.configuration.socket_timeout_ms
 		topics: {
-			description: "The Kafka topics names to read events from. Regex is supported if the topic begins with `^`."
-			required:    true
-			type: array: items: type: string: {
-				examples: ["^(prefix1|prefix2)-.+", "topic-1", "topic-2"]
+			common:      true
+			description: "The Kafka topics names to read events from. Regex is supported if the topic begins with `^`. Required unless `assignments` is set."
+			required:    false
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["^(prefix1|prefix2)-.+", "topic-1", "topic-2"]
+				}
 			}
 		}
 	}
@@ -236,6 +298,7 @@ components: sources: kafka: {
 		kafka_produced_messages_bytes_total:  components.sources.internal_metrics.output.metrics.kafka_produced_messages_bytes_total
 		kafka_consumed_messages_total:        components.sources.internal_metrics.output.metrics.kafka_consumed_messages_total
 		kafka_consumed_messages_bytes_total:  components.sources.internal_metrics.output.metrics.kafka_consumed_messages_bytes_total
+		kafka_consumer_lag:                   components.sources.internal_metrics.output.metrics.kafka_consumer_lag
 		processed_bytes_total:                components.sources.internal_metrics.output.metrics.processed_bytes_total
 		processed_events_total:               components.sources.internal_metrics.output.metrics.processed_events_total
 		component_discarded_events_total:     components.sources.internal_metrics.output.metrics.component_discarded_events_total
@@ -245,5 +308,20 @@ components: sources: kafka: {
 		component_received_event_bytes_total: components.sources.internal_metrics.output.metrics.component_received_event_bytes_total
 	}
 
-	how_it_works: components._kafka.how_it_works
+	how_it_works: {
+		components._kafka.how_it_works
+
+		bounded_replays: {
+			title: "Bounded replays"
+			body: """
+				To backfill from a known range of messages, the `assignments` option consumes
+				explicit partitions from a start offset or time instead of subscribing to
+				`topics`. Once every assigned partition has a stop offset or time and has
+				reached it, the source stops, which ends Vector once its other sources have
+				stopped as well. Offsets are committed for `group_id` as usual, so setting it to
+				a group that isn't used by other consumers keeps the replay from moving their
+				offsets.
+				"""
+		}
+	}
 }
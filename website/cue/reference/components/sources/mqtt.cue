@@ -0,0 +1,107 @@
+package metadata
+
+components: sources: mqtt: {
+	title: "MQTT"
+
+	features: {
+		acknowledgements: false
+		collect: {
+			checkpoint: enabled: false
+			tls: {
+				enabled:                true
+				can_verify_certificate: false
+				can_verify_hostname:    false
+				enabled_default:        false
+			}
+			from: components._mqtt.features.collect.from
+		}
+		multiline: enabled: false
+		codecs: {
+			enabled:         true
+			default_framing: "bytes"
+		}
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "best_effort"
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	support: components._mqtt.support
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: components._mqtt.configuration & {
+		topics: {
+			description: "The topic filters to subscribe to. Filters may use the `+` and `#` [wildcards](\(urls.mqtt_topics))."
+			required:    true
+			type: array: items: type: string: {
+				examples: ["sensors/+/temperature", "factory/#"]
+			}
+		}
+		clean_session: {
+			common:      false
+			description: """
+				Whether to start a clean session on every connection. When disabled, the broker keeps the
+				subscriptions and queues QoS 1 and 2 messages for this client while it is disconnected,
+				provided the `client_id` stays the same.
+				"""
+			required:    false
+			type: bool: default: true
+		}
+		topic_key: {
+			common:      false
+			description: "The log field name to use for the topic the message was published to."
+			required:    false
+			type: string: {
+				default: "topic"
+				examples: ["topic"]
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "An individual MQTT message"
+		fields: {
+			message: {
+				description: "The raw payload of the MQTT message."
+				required:    true
+				type: string: {
+					examples: ["{\"temperature\": 21.5}"]
+				}
+			}
+			topic: {
+				description: "The topic the message was published to."
+				required:    true
+				type: string: {
+					examples: ["sensors/line-1/temperature"]
+				}
+			}
+			timestamp: fields._current_timestamp
+		}
+	}
+
+	telemetry: metrics: {
+		component_errors_total:               components.sources.internal_metrics.output.metrics.component_errors_total
+		component_received_bytes_total:       components.sources.internal_metrics.output.metrics.component_received_bytes_total
+		component_received_events_total:      components.sources.internal_metrics.output.metrics.component_received_events_total
+		component_received_event_bytes_total: components.sources.internal_metrics.output.metrics.component_received_event_bytes_total
+	}
+
+	how_it_works: components._mqtt.how_it_works & {
+		delivery: {
+			title: "Delivery guarantees"
+			body:  """
+				QoS 1 and 2 messages are acknowledged to the broker only once they have been handed to the
+				rest of the pipeline. Combined with `clean_session = false`, messages that were received but
+				not yet acknowledged when Vector stopped are redelivered by the broker.
+				"""
+		}
+	}
+}
@@ -51,17 +51,113 @@ components: sources: prometheus_scrape: {
 		platform_name: null
 	}
 
+	_refresh_interval_secs: {
+		common:      false
+		description: "The interval between target refreshes, in seconds."
+		required:    false
+		type: uint: {
+			default: 30
+			unit:    "seconds"
+		}
+	}
+	_scheme: {
+		common:      false
+		description: "The scheme used to scrape discovered targets."
+		required:    false
+		type: string: {
+			default: "http"
+			enum: {
+				http:  "Scrape targets over HTTP."
+				https: "Scrape targets over HTTPS."
+			}
+		}
+	}
+	_metrics_path: {
+		common:      false
+		description: "The path to scrape on discovered targets."
+		required:    false
+		type: string: default: "/metrics"
+	}
+
 	configuration: {
 		endpoints: {
-			description: "Endpoints to scrape metrics from."
-			required:    true
+			common:      true
+			description: "Endpoints to scrape metrics from. Required unless `file_sd` or `dns_sd` is set."
+			required:    false
 			warnings: ["You must explicitly add the path to your endpoints. Vector will _not_ automatically add `/metrics`."]
 			type: array: {
+				default: []
 				items: type: string: {
 					examples: ["http://localhost:9090/metrics"]
 				}
 			}
 		}
+		file_sd: {
+			common: false
+			description: """
+				Discovers scrape targets from files in the Prometheus `file_sd` format, a list of target
+				groups made of `targets` and `labels`. Files are re-read every `refresh_interval_secs`,
+				and the labels of each group are added as tags to the metrics scraped from its targets.
+				The `__scheme__` and `__metrics_path__` labels override the scheme and path of a group's
+				targets, any other label starting with `__` is dropped.
+				"""
+			required: false
+			type: object: options: {
+				files: {
+					description: "The JSON or YAML files to read targets from, glob patterns are allowed. Files ending in `.yml` or `.yaml` are read as YAML, others as JSON."
+					required:    true
+					type: array: items: type: string: {
+						examples: ["/etc/prometheus/targets/*.json"]
+					}
+				}
+				refresh_interval_secs: _refresh_interval_secs
+				scheme:                _scheme
+				metrics_path:          _metrics_path
+			}
+		}
+		dns_sd: {
+			common: false
+			description: """
+				Discovers scrape targets by resolving DNS records every `refresh_interval_secs`. Targets
+				that disappear from the records stop being scraped.
+				"""
+			required: false
+			type: object: options: {
+				names: {
+					description: "The DNS names to resolve."
+					required:    true
+					type: array: items: type: string: {
+						examples: ["_metrics._tcp.example.com"]
+					}
+				}
+				type: {
+					common:      true
+					description: "The type of DNS records to resolve."
+					required:    false
+					type: string: {
+						default: "SRV"
+						enum: {
+							SRV:  "Resolve SRV records, using the host and port of each record."
+							A:    "Resolve A records, scraping each address on `port`."
+							AAAA: "Resolve AAAA records, scraping each address on `port`."
+						}
+					}
+				}
+				port: {
+					common:      false
+					description: "The port to scrape. Required for `A` and `AAAA` records."
+					required:    false
+					type: uint: {
+						default: null
+						examples: [9100]
+						unit: null
+					}
+				}
+				refresh_interval_secs: _refresh_interval_secs
+				scheme:                _scheme
+				metrics_path:          _metrics_path
+			}
+		}
 		scrape_interval_secs: {
 			common:      true
 			description: "The interval between scrapes, in seconds."
@@ -178,6 +274,7 @@ components: sources: prometheus_scrape: {
 	}
 
 	telemetry: metrics: {
+		discovered_targets:                   components.sources.internal_metrics.output.metrics.discovered_targets
 		events_in_total:                      components.sources.internal_metrics.output.metrics.events_in_total
 		http_error_response_total:            components.sources.internal_metrics.output.metrics.http_error_response_total
 		http_request_errors_total:            components.sources.internal_metrics.output.metrics.http_request_errors_total
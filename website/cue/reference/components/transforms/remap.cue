@@ -75,6 +75,22 @@ components: transforms: "remap": {
 				]
 			}
 		}
+		import_paths: {
+			common:      false
+			required:    false
+			description: """
+				The directories in which modules imported by the program with `import "path/to/module.vrl"` are
+				searched for, in order. The first directory containing the module is used.
+
+				If a relative path is provided, its root is the current working directory.
+				"""
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["/etc/vector/vrl", "./vrl"]
+				}
+			}
+		}
 		drop_on_error: {
 			common:   false
 			required: false
@@ -104,6 +120,74 @@ components: transforms: "remap": {
 				"""
 			type: bool: default: false
 		}
+		state: {
+			common:   false
+			required: false
+			description: """
+				Enables a key/value store shared between events, which the program can access with the
+				`get_state`, `set_state`, and `incr_state` functions. The store is shared by all
+				instances of this transform, and is bounded both in the number of entries and in its
+				memory usage. When one of the limits is reached, the entries closest to expiring are
+				evicted.
+				"""
+			type: object: options: {
+				max_entries: {
+					common:      false
+					description: "The maximum number of entries kept in the store."
+					required:    false
+					type: uint: {
+						default: 10000
+						unit:    null
+					}
+				}
+				max_bytes: {
+					common:      false
+					description: "The maximum (approximate) number of bytes used by the keys and values in the store."
+					required:    false
+					type: uint: {
+						default: 10485760
+						unit:    "bytes"
+					}
+				}
+				ttl_secs: {
+					common:      false
+					description: "The time after which entries expire, unless a different TTL is given when the entry is set."
+					required:    false
+					type: uint: {
+						default: 3600
+						unit:    "seconds"
+					}
+				}
+				checkpoint: {
+					common:      false
+					description: """
+						Whether to periodically write the store to disk, and restore it when Vector restarts.
+						Values are written as JSON, timestamps are therefore restored as strings.
+						"""
+					required: false
+					type: bool: default: false
+				}
+				checkpoint_interval_secs: {
+					common:      false
+					description: "How often the store is written to disk when `checkpoint` is enabled."
+					required:    false
+					type: uint: {
+						default: 60
+						unit:    "seconds"
+					}
+				}
+				data_dir: {
+					common:      false
+					description: "The directory used to persist the store. By default, the global `data_dir` option is used. Please make sure the Vector project has write permissions to this dir."
+					required:    false
+					type: string: {
+						default: null
+						examples: ["/var/lib/vector"]
+						syntax: "file_system_path"
+					}
+				}
+			}
+		}
 	}
 
 	input: {
@@ -146,6 +230,29 @@ components: transforms: "remap": {
 				[Vector Remap Language reference](\#(urls.vrl_reference)).
 				"""#
 		}
+		functions_and_modules: {
+			title: "Functions and modules"
+			body:  #"""
+				Programs can define their own functions, with optionally typed parameters and
+				return values:
+
+				```coffee
+				fn normalize_level(level: string) -> string {
+					downcase(level)
+				}
+
+				.level = normalize_level(.level) ?? "info"
+				```
+
+				A function only has access to its parameters and to the event, and is defined
+				before the expressions calling it.
+
+				Functions shared by several transforms can be moved to a module, a `.vrl` file
+				containing only function definitions and imports. A program imports a module with
+				`import "path/to/module.vrl"`, relative to one of the directories configured in
+				`import_paths`. Errors in a module are reported with the name of the module file.
+				"""#
+		}
 		event_data_model: {
 			title: "Event Data Model"
 			body:  """
@@ -226,6 +333,9 @@ components: transforms: "remap": {
 	]
 
 	telemetry: metrics: {
-		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+		processing_errors_total:     components.sources.internal_metrics.output.metrics.processing_errors_total
+		remap_state_bytes:           components.sources.internal_metrics.output.metrics.remap_state_bytes
+		remap_state_entries:         components.sources.internal_metrics.output.metrics.remap_state_entries
+		remap_state_evictions_total: components.sources.internal_metrics.output.metrics.remap_state_evictions_total
 	}
 }
@@ -0,0 +1,39 @@
+package metadata
+
+remap: functions: encode_cef: {
+	category:    "Codec"
+	description: """
+		Encodes the `value` object as an ArcSight Common Event Format (CEF) message.
+
+		The `deviceVendor`, `deviceProduct`, `deviceVersion`, `deviceEventClassId`, `name` and `severity`
+		fields are written to the header, `cefVersion` defaulting to `0`. All other fields are written as
+		escaped extension fields. Timestamps are written as milliseconds since the Unix epoch and `null`
+		fields are omitted.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The object to encode."
+			required:    true
+			type: ["object"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` is missing a header field",
+		"a header field contains a line break",
+		"a field name isn't a valid CEF extension key",
+		"a field is an array or an object",
+	]
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Encode CEF message"
+			source: #"""
+				encode_cef!({ "deviceVendor": "Security", "deviceProduct": "threatmanager", "deviceVersion": "1.0", "deviceEventClassId": "100", "name": "worm stopped", "severity": 10, "src": "10.0.0.1", "msg": "a=b" })
+				"""#
+			return: #"CEF:0|Security|threatmanager|1.0|100|worm stopped|10|msg=a\=b src=10.0.0.1"#
+		},
+	]
+}
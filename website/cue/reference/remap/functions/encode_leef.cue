@@ -0,0 +1,45 @@
+package metadata
+
+remap: functions: encode_leef: {
+	category:    "Codec"
+	description: """
+		Encodes the `value` object as an IBM QRadar Log Event Extended Format (LEEF) message.
+
+		The `vendor`, `product`, `productVersion` and `eventId` fields are written to the header,
+		`leefVersion` defaulting to `2.0`. All other fields are written as escaped event attributes.
+		Timestamps are written as milliseconds since the Unix epoch and `null` fields are omitted.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The object to encode."
+			required:    true
+			type: ["object"]
+		},
+		{
+			name:        "delimiter"
+			description: "The single character separating attributes. LEEF 1.0 only supports tabs."
+			required:    false
+			default:     "\t"
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` is missing a header field",
+		"`delimiter` isn't a single character, or isn't a tab for LEEF 1.0",
+		"a header field or attribute contains a line break",
+		"a field is an array or an object",
+	]
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Encode LEEF 2.0 message"
+			source: #"""
+				encode_leef!({ "vendor": "Lancope", "product": "StealthWatch", "productVersion": "1.0", "eventId": "41", "src": "10.0.1.8", "sev": 5 }, delimiter: "^")
+				"""#
+			return: "LEEF:2.0|Lancope|StealthWatch|1.0|41|^|sev=5^src=10.0.1.8"
+		},
+	]
+}
@@ -0,0 +1,35 @@
+package metadata
+
+remap: functions: encode_msgpack: {
+	category:    "Codec"
+	description: """
+		Encodes the `value` to [MessagePack](\(urls.msgpack)).
+		"""
+	notices: [
+		"""
+			Strings that are valid UTF-8 are encoded as MessagePack strings, all others as binary.
+			Timestamps are encoded using the MessagePack timestamp extension.
+			""",
+	]
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The value to encode."
+			required:    true
+			type: ["any"]
+		},
+	]
+	internal_failure_reasons: []
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Encode to MessagePack"
+			source: #"""
+				encode_base64(encode_msgpack({"field": "value"}))
+				"""#
+			return: "gaVmaWVsZKV2YWx1ZQ=="
+		},
+	]
+}
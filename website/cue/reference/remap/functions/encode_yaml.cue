@@ -0,0 +1,29 @@
+package metadata
+
+remap: functions: encode_yaml: {
+	category:    "Codec"
+	description: """
+		Encodes the `value` to [YAML](\(urls.yaml)).
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The value to convert to a YAML string."
+			required:    true
+			type: ["any"]
+		},
+	]
+	internal_failure_reasons: []
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Encode to YAML"
+			source: #"""
+				encode_yaml({"service": "api", "replicas": 3})
+				"""#
+			return: "replicas: 3\nservice: api"
+		},
+	]
+}
@@ -0,0 +1,35 @@
+package metadata
+
+remap: functions: get_state: {
+	category: "State"
+	description: """
+		Returns the value stored in the state for the given `key`, or `null` if there is no
+		such value.
+
+		\(remap._state_explainer)
+		"""
+
+	arguments: [
+		{
+			name:        "key"
+			description: "The key of the value to look up."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: []
+	return: types: ["any"]
+
+	examples: [
+		{
+			title: "Detect the first occurrence of a value"
+			source: #"""
+				key = "seen:" + string!(.user_id)
+				.first_seen = get_state(key) == null
+				set_state!(key, true)
+				.first_seen
+				"""#
+			return: true
+		},
+	]
+}
@@ -0,0 +1,68 @@
+package metadata
+
+remap: functions: hmac: {
+	category:    "Cryptography"
+	description: """
+		Calculates a [HMAC](\(urls.hmac)) of the `value` using the given `key`, for example to verify the
+		signature of a webhook payload.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The string to calculate the HMAC for."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "key"
+			description: "The secret key."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "algorithm"
+			description: "The hashing algorithm to use."
+			enum: {
+				"SHA1":    "SHA-1 algorithm"
+				"SHA-224": "SHA-224 algorithm"
+				"SHA-256": "SHA-256 algorithm"
+				"SHA-384": "SHA-384 algorithm"
+				"SHA-512": "SHA-512 algorithm"
+			}
+			required: false
+			default:  "SHA-256"
+			type: ["string"]
+		},
+		{
+			name:        "encoding"
+			description: "The encoding of the returned HMAC."
+			enum: {
+				"hex":    "Lowercase hexadecimal encoding"
+				"base64": "Standard base64 encoding, with padding"
+			}
+			required: false
+			default:  "hex"
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: []
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Calculate HMAC"
+			source: #"""
+				hmac("Hello there", "super-secret-key")
+				"""#
+			return: "78b184f1832f8aff3934f5e0212454671b2d04d494e3b25075c5e45167029662"
+		},
+		{
+			title: "Calculate base64 encoded HMAC-SHA1"
+			source: #"""
+				hmac("Hello there", "super-secret-key", algorithm: "SHA1", encoding: "base64")
+				"""#
+			return: "MiyBIHO8Set9+6crALiwkS0yFPE="
+		},
+	]
+}
@@ -0,0 +1,55 @@
+package metadata
+
+remap: functions: incr_state: {
+	category: "State"
+	description: """
+		Increments the integer stored in the state for the given `key` and returns the new
+		value. A missing value is treated as `0`.
+
+		The TTL only applies when the counter is created, incrementing an existing counter
+		doesn't extend its lifetime. This makes it possible to count events in fixed windows.
+
+		\(remap._state_explainer)
+		"""
+
+	arguments: [
+		{
+			name:        "key"
+			description: "The key of the counter to increment."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "by"
+			description: "The amount to increment the counter by."
+			required:    false
+			default:     1
+			type: ["integer"]
+		},
+		{
+			name:        "ttl_secs"
+			description: """
+				The number of seconds after which a newly created counter expires. Defaults to the
+				`ttl_secs` option of the transform's `state` configuration.
+				"""
+			required: false
+			type: ["integer"]
+		},
+	]
+	internal_failure_reasons: [
+		"The value stored for `key` isn't an integer.",
+		"The counter overflows.",
+		"`ttl_secs` isn't a positive integer.",
+	]
+	return: types: ["integer"]
+
+	examples: [
+		{
+			title: "Count the events of each host per minute"
+			source: #"""
+				.host_events = incr_state!("events:" + string!(.host), ttl_secs: 60)
+				"""#
+			return: 1
+		},
+	]
+}
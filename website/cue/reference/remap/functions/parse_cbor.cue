@@ -0,0 +1,39 @@
+package metadata
+
+remap: functions: parse_cbor: {
+	category: "Parse"
+	description: """
+		Parses the `value` as [CBOR](\(urls.cbor)).
+		"""
+	notices: [
+		"""
+			Semantic tags are discarded and only the tagged value is returned. Integer map keys are
+			converted to strings.
+			""",
+	]
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The CBOR-encoded data to parse."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a valid CBOR data item",
+		"`value` contains an integer that doesn't fit in a signed 64-bit integer",
+		"`value` contains a map key that isn't a string or an integer",
+	]
+	return: types: ["boolean", "integer", "float", "string", "object", "array", "null"]
+
+	examples: [
+		{
+			title: "Parse CBOR"
+			source: #"""
+				parse_cbor!(decode_base64!("oWVmaWVsZGV2YWx1ZQ=="))
+				"""#
+			return: field: "value"
+		},
+	]
+}
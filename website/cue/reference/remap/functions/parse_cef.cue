@@ -0,0 +1,72 @@
+package metadata
+
+remap: functions: parse_cef: {
+	category:    "Parse"
+	description: """
+		Parses the `value` in the ArcSight Common Event Format (CEF). Any syslog prefix before the `CEF:`
+		marker is skipped.
+
+		Header fields are returned as `cefVersion`, `deviceVendor`, `deviceProduct`, `deviceVersion`,
+		`deviceEventClassId`, `name` and `severity`, alongside the unescaped extension fields. Well-known
+		numeric extension fields, such as `spt` or `cn1`, are returned as integers or floats and timestamp
+		fields, such as `rt`, as timestamps. Timestamps without a time zone are interpreted in the configured
+		timezone.
+		"""
+	arguments: [
+		{
+			name:        "value"
+			description: "The string to parse."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "translate_custom_fields"
+			description: "Replace pairs of custom fields, such as `cs1` and `cs1Label`, with a single field named after the label."
+			required:    false
+			default:     false
+			type: ["boolean"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a properly formatted CEF message",
+	]
+	return: types: ["object"]
+
+	examples: [
+		{
+			title: "Parse CEF message"
+			source: #"""
+				parse_cef!(s'CEF:0|Security|threatmanager|1.0|100|worm successfully stopped|10|src=10.0.0.1 dst=2.1.2.2 spt=1232 msg=Detected a threat. No action needed.')
+				"""#
+			return: {
+				"cefVersion":         0
+				"deviceVendor":       "Security"
+				"deviceProduct":      "threatmanager"
+				"deviceVersion":      "1.0"
+				"deviceEventClassId": "100"
+				"name":               "worm successfully stopped"
+				"severity":           10
+				"src":                "10.0.0.1"
+				"dst":                "2.1.2.2"
+				"spt":                1232
+				"msg":                "Detected a threat. No action needed."
+			}
+		},
+		{
+			title: "Parse CEF message with custom fields"
+			source: #"""
+				parse_cef!(s'CEF:0|Vendor|Product|1|id|name|3|cs1=admin cs1Label=user', translate_custom_fields: true)
+				"""#
+			return: {
+				"cefVersion":         0
+				"deviceVendor":       "Vendor"
+				"deviceProduct":      "Product"
+				"deviceVersion":      "1"
+				"deviceEventClassId": "id"
+				"name":               "name"
+				"severity":           3
+				"user":               "admin"
+			}
+		},
+	]
+}
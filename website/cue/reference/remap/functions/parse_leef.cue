@@ -0,0 +1,63 @@
+package metadata
+
+remap: functions: parse_leef: {
+	category:    "Parse"
+	description: """
+		Parses the `value` in the IBM QRadar Log Event Extended Format (LEEF), versions 1.0 and 2.0. Any
+		syslog prefix before the `LEEF:` marker is skipped.
+
+		Header fields are returned as `leefVersion`, `vendor`, `product`, `productVersion` and `eventId`,
+		alongside the unescaped event attributes. Numeric attributes, such as `sev` or `srcPort`, are
+		returned as integers. `devTime` is returned as a timestamp, parsed according to `devTimeFormat`
+		when present.
+		"""
+	arguments: [
+		{
+			name:        "value"
+			description: "The string to parse."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a properly formatted LEEF message",
+	]
+	return: types: ["object"]
+
+	examples: [
+		{
+			title: "Parse LEEF 2.0 message"
+			source: #"""
+				parse_leef!(s'LEEF:2.0|Lancope|StealthWatch|1.0|41|^|src=10.0.1.8^dst=10.0.0.5^sev=5^srcPort=81^usrName=joe.black')
+				"""#
+			return: {
+				"leefVersion":    "2.0"
+				"vendor":         "Lancope"
+				"product":        "StealthWatch"
+				"productVersion": "1.0"
+				"eventId":        "41"
+				"src":            "10.0.1.8"
+				"dst":            "10.0.0.5"
+				"sev":            5
+				"srcPort":        81
+				"usrName":        "joe.black"
+			}
+		},
+		{
+			title: "Parse LEEF 1.0 message"
+			source: #"""
+				parse_leef!("LEEF:1.0|Microsoft|MSExchange|4.0 SP1|15345|src=192.0.2.0\tdst=172.50.123.1\tdevTime=1644865494000")
+				"""#
+			return: {
+				"leefVersion":    "1.0"
+				"vendor":         "Microsoft"
+				"product":        "MSExchange"
+				"productVersion": "4.0 SP1"
+				"eventId":        "15345"
+				"src":            "192.0.2.0"
+				"dst":            "172.50.123.1"
+				"devTime":        "2022-02-14T19:04:54Z"
+			}
+		},
+	]
+}
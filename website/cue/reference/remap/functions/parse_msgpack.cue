@@ -0,0 +1,41 @@
+package metadata
+
+remap: functions: parse_msgpack: {
+	category: "Parse"
+	description: """
+		Parses the `value` as [MessagePack](\(urls.msgpack)).
+		"""
+	notices: [
+		"""
+			The MessagePack timestamp extension and Fluent's `EventTime` extension are returned as
+			timestamps. Other extension types are returned as an object with the `msgpack_extension_code`
+			and `bytes` fields.
+			""",
+	]
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The MessagePack-encoded data to parse."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a valid MessagePack value",
+		"`value` has trailing bytes after the first value",
+		"`value` contains an integer that doesn't fit in a signed 64-bit integer",
+		"`value` contains a map key that isn't a string or an integer",
+	]
+	return: types: ["boolean", "integer", "float", "string", "object", "array", "timestamp", "null"]
+
+	examples: [
+		{
+			title: "Parse MessagePack"
+			source: #"""
+				parse_msgpack!(decode_base64!("gaVmaWVsZKV2YWx1ZQ=="))
+				"""#
+			return: field: "value"
+		},
+	]
+}
@@ -0,0 +1,41 @@
+package metadata
+
+remap: functions: parse_toml: {
+	category: "Parse"
+	description: """
+		Parses the `value` as a [TOML](\(urls.toml)) document.
+		"""
+	notices: [
+		"""
+			TOML offset datetimes are returned as timestamps. Local dates and times, which carry no offset,
+			can't be represented and fail to parse.
+			""",
+	]
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The string representation of the TOML document to parse."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a valid TOML document",
+		"`value` contains a local date or time, or a `nan` float",
+	]
+	return: types: ["object"]
+
+	examples: [
+		{
+			title: "Parse TOML"
+			source: #"""
+				parse_toml!("[server]\nhost = \"localhost\"\nports = [80, 443]")
+				"""#
+			return: server: {
+				host: "localhost"
+				ports: [80, 443]
+			}
+		},
+	]
+}
@@ -0,0 +1,41 @@
+package metadata
+
+remap: functions: parse_yaml: {
+	category: "Parse"
+	description: """
+		Parses the `value` as [YAML](\(urls.yaml)).
+		"""
+	notices: [
+		"""
+			Mapping keys that are not strings are converted to their string representation. Mappings with
+			sequence or mapping keys can't be represented and fail to parse.
+			""",
+	]
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The string representation of the YAML document to parse."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a valid YAML document",
+		"`value` contains a mapping with a sequence or mapping key",
+	]
+	return: types: ["boolean", "integer", "float", "string", "object", "array", "null"]
+
+	examples: [
+		{
+			title: "Parse YAML"
+			source: #"""
+				parse_yaml!("service: api\nports:\n  - 80\n  - 443\n")
+				"""#
+			return: {
+				service: "api"
+				ports: [80, 443]
+			}
+		},
+	]
+}
@@ -0,0 +1,49 @@
+package metadata
+
+remap: functions: set_state: {
+	category: "State"
+	description: """
+		Stores the `value` in the state for the given `key`, replacing any existing value.
+
+		\(remap._state_explainer)
+		"""
+
+	arguments: [
+		{
+			name:        "key"
+			description: "The key of the value to store."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "value"
+			description: "The value to store."
+			required:    true
+			type: ["any"]
+		},
+		{
+			name:        "ttl_secs"
+			description: """
+				The number of seconds after which the value expires. Defaults to the `ttl_secs`
+				option of the transform's `state` configuration.
+				"""
+			required: false
+			type: ["integer"]
+		},
+	]
+	internal_failure_reasons: [
+		"`ttl_secs` isn't a positive integer.",
+		"The key and value exceed the `max_bytes` limit of the state.",
+	]
+	return: types: ["null"]
+
+	examples: [
+		{
+			title: "Remember the last status of a session"
+			source: #"""
+				set_state!("session:" + string!(.session_id), .status, ttl_secs: 1800)
+				"""#
+			return: null
+		},
+	]
+}
@@ -0,0 +1,85 @@
+package metadata
+
+remap: functions: verify_jwt: {
+	category:    "Cryptography"
+	description: """
+		Verifies the signature of the `value` [JSON Web Token](\(urls.jwt)) and returns its claims.
+
+		The `exp` and `nbf` claims are validated against the current time when the token has them, and the
+		`aud` and `iss` claims when `audience` or `issuer` are given.
+		"""
+	notices: [
+		"""
+			The key file is read once, when the program is compiled. Changes to the file require a reload of
+			the configuration.
+			""",
+	]
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The token to verify."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name: "key_file"
+			description: """
+				The path to the file containing the key. For `HS*` algorithms the file contains the shared
+				secret, ignoring a trailing line break. For `RS*` and `ES*` algorithms the file contains
+				the PEM encoded public key. This must be a string literal.
+				"""
+			required: true
+			type: ["string"]
+		},
+		{
+			name:        "algorithm"
+			description: "The algorithm the token must be signed with. Tokens signed with any other algorithm are rejected."
+			enum: {
+				"HS256": "HMAC using SHA-256"
+				"HS384": "HMAC using SHA-384"
+				"HS512": "HMAC using SHA-512"
+				"RS256": "RSASSA-PKCS1-v1_5 using SHA-256"
+				"RS384": "RSASSA-PKCS1-v1_5 using SHA-384"
+				"RS512": "RSASSA-PKCS1-v1_5 using SHA-512"
+				"ES256": "ECDSA using P-256 and SHA-256"
+				"ES384": "ECDSA using P-384 and SHA-384"
+			}
+			required: true
+			type: ["string"]
+		},
+		{
+			name:        "audience"
+			description: "The audience the `aud` claim must contain."
+			required:    false
+			type: ["string"]
+		},
+		{
+			name:        "issuer"
+			description: "The issuer the `iss` claim must match."
+			required:    false
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a properly formatted JWT",
+		"the signature of `value` doesn't match the key, or `value` is signed with another algorithm",
+		"`value` has expired or isn't valid yet",
+		"the `aud` or `iss` claim of `value` doesn't match `audience` or `issuer`",
+	]
+	return: types: ["object"]
+
+	examples: [
+		{
+			title: "Verify JWT"
+			source: #"""
+				verify_jwt!(.token, key_file: "/etc/vector/jwt.pem", algorithm: "RS256", issuer: "https://auth.example.com")
+				"""#
+			return: {
+				iss: "https://auth.example.com"
+				sub: "1234567890"
+				exp: 4102444800
+			}
+		},
+	]
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: amqp: {
+	name:     "AMQP"
+	thing:    "an \(name) broker"
+	url:      urls.amqp
+	versions: null
+
+	description: "[AMQP](\(urls.amqp)) 0-9-1 is an open standard messaging protocol, implemented most notably by [RabbitMQ](\(urls.rabbitmq)), where producers publish messages to exchanges that route them to the queues consumers read from."
+}
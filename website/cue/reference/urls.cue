@@ -4,6 +4,8 @@ urls: {
 	azure_blob_storage:                                       "https://azure.microsoft.com/en-us/services/storage/blobs/"
 	affine_type_system:                                       "\(wikipedia)/wiki/Substructural_type_system#Affine_type_systems"
 	adaptive_request_concurrency_post:                        "/blog/adaptive-request-concurrency/"
+	amqp:                                                     "\(rabbitmq)/tutorials/amqp-concepts.html"
+	amqp_publisher_confirms:                                  "\(rabbitmq)/confirms.html#publisher-confirms"
 	amazon_linux:                                             "https://aws.amazon.com/amazon-linux-ami/"
 	ansi_escape_codes:                                        "\(wikipedia)/wiki/ANSI_escape_code"
 	apache:                                                   "https://httpd.apache.org"
@@ -13,6 +15,7 @@ urls: {
 	apache_extended_status:                                   "\(apache)/docs/current/mod/core.html#extendedstatus"
 	apache_install:                                           "\(apache)/docs/current/install.html"
 	apache_mod_status:                                        "http://httpd.apache.org/docs/current/mod/mod_status.html"
+	apache_parquet:                                           "https://parquet.apache.org/"
 	apt:                                                      "\(wikipedia)/wiki/APT_(software)"
 	arm:                                                      "\(wikipedia)/wiki/ARM_architecture"
 	aws_access_keys:                                          "\(aws_docs)/IAM/latest/UserGuide/id_credentials_access-keys.html"
@@ -70,6 +73,7 @@ urls: {
 	aws_s3_endpoints:                                         "\(aws_docs)/general/latest/gr/rande.html#s3_endpoint"
 	aws_s3_grantee:                                           "\(aws_docs)/AmazonS3/latest/dev/acl-overview.html#specifying-grantee"
 	aws_s3_metadata:                                          "\(aws_docs)/AmazonS3/latest/dev/UsingMetadata.html#object-metadata"
+	aws_s3_multipart_upload:                                  "\(aws_docs)/AmazonS3/latest/userguide/mpuoverview.html"
 	aws_s3_regions:                                           "\(aws_docs)/general/latest/gr/rande.html#s3_region"
 	aws_s3_server_access_logs:                                "\(aws_docs)/AmazonS3/latest/dev/ServerLogs.html"
 	aws_s3_service_limits:                                    "\(aws_docs)/streams/latest/dev/service-sizes-and-limits.html"
@@ -94,11 +98,14 @@ urls: {
 	bind_dnstap:                                              "https://kb.isc.org/docs/aa-01342"
 	b_tree_map:                                               "https://doc.rust-lang.org/std/collections/struct.BTreeMap.html"
 	cargo_audit:                                              "\(github)/RustSec/cargo-audit"
+	cbor:                                                     "https://cbor.io/"
 	centos:                                                   "https://www.centos.org/"
 	chrono_time_formats:                                      "https://docs.rs/chrono/latest/chrono/format/strftime/index.html#specifiers"
 	cgroups_limit_resources:                                  "https://the.binbashtheory.com/control-resources-cgroups/"
 	clickhouse:                                               "https://clickhouse.yandex/"
 	clickhouse_http:                                          "https://clickhouse.yandex/docs/en/interfaces/http/"
+	clickhouse_json_each_row:                                 "https://clickhouse.com/docs/en/interfaces/formats/#jsoneachrow"
+	clickhouse_row_binary:                                    "https://clickhouse.com/docs/en/interfaces/formats/#rowbinary"
 	cloudsmith:                                               "https://cloudsmith.io/~timber/repos/vector/packages/"
 	cloudsmith_apt:                                           "https://cloudsmith.io/~timber/repos/vector/setup/#formats-deb"
 	cloudsmith_yum:                                           "https://cloudsmith.io/~timber/repos/vector/setup/#formats-rpm"
@@ -227,6 +234,7 @@ urls: {
 	heroku:                                                   "https://www.heroku.com"
 	heroku_http_log_drain:                                    "https://devcenter.heroku.com/articles/log-drains#https-drains"
 	heroku_start:                                             "https://devcenter.heroku.com/start"
+	hmac:                                                     "\(wikipedia)/wiki/HMAC"
 	homebrew:                                                 "https://brew.sh/"
 	homebrew_services:                                        "\(github)/Homebrew/homebrew-services"
 	honeycomb:                                                "https://honeycomb.io"
@@ -263,6 +271,7 @@ urls: {
 	json:                                                     "\(wikipedia)/wiki/JSON"
 	json_types:                                               "\(wikipedia)/wiki/JSON#Data_types_and_syntax"
 	jsonnet:                                                  "https://jsonnet.org/"
+	jwt:                                                      "https://datatracker.ietf.org/doc/html/rfc7519"
 	kafka:                                                    "https://kafka.apache.org/"
 	kafka_partitioning_docs:                                  "https://cwiki.apache.org/confluence/display/KAFKA/A+Guide+To+The+Kafka+Protocol#AGuideToTheKafkaProtocol-Partitioningandbootstrapping"
 	kafka_protocol:                                           "https://kafka.apache.org/protocol"
@@ -281,6 +290,7 @@ urls: {
 	kubernetes_rbac:                                          "\(kubernetes)/docs/reference/access-authn-authz/rbac/"
 	kubernetes_request_verbs:                                 "\(kubernetes)/docs/reference/access-authn-authz/authorization/#determine-the-request-verb"
 	kubernetes_watch_api:                                     "\(kubernetes)/docs/reference/generated/kubernetes-api/v1.10/#watch-30"
+	lapin:                                                    "\(github)/amqp-rs/lapin"
 	leveldb:                                                  "\(github)/google/leveldb"
 	leveldb_sys_2:                                            "https://crates.io/crates/leveldb-sys"
 	leveldb_sys_3:                                            "\(github)/vectordotdev/leveldb-sys/tree/v3.0.0"
@@ -334,6 +344,9 @@ urls: {
 	mongodb:                                                  "https://www.mongodb.com"
 	mongodb_command_server_status:                            "https://docs.mongodb.com/manual/reference/command/serverStatus/"
 	mongodb_connection_string_uri_format:                     "https://docs.mongodb.com/manual/reference/connection-string/"
+	mqtt:                                                     "https://mqtt.org/"
+	mqtt_topics:                                              "https://docs.oasis-open.org/mqtt/mqtt/v3.1.1/os/mqtt-v3.1.1-os.html#_Toc398718106"
+	msgpack:                                                  "https://msgpack.org/"
 	musl_builder_docker_image:                                "\(vector_repo)/blob/master/scripts/ci-docker-images/builder-x86_64-unknown-linux-musl/Dockerfile"
 	native_proto_schema:                                      "\(vector_repo)/blob/master/lib/vector-core/proto/event.proto"
 	native_json_schema:                                       "\(vector_repo)/blob/master/lib/codecs/tests/data/native_encoding/schema.cue"
@@ -392,6 +405,7 @@ urls: {
 	protobuf:                                                 "https://developers.google.com/protocol-buffers"
 	pulsar:                                                   "https://pulsar.apache.org/"
 	pulsar_protocol:                                          "https://pulsar.apache.org/docs/en/develop-binary-protocol/"
+	rabbitmq:                                                 "https://www.rabbitmq.com"
 	raspbian:                                                 "https://www.raspbian.org/"
 	rdkafka:                                                  "\(github)/edenhill/librdkafka"
 	regex:                                                    "\(wikipedia)/wiki/Regular_expression"
@@ -412,6 +426,7 @@ urls: {
 	rfc_6891:                                                 "https://tools.ietf.org/html/rfc6891"
 	rhel:                                                     "https://www.redhat.com/en/technologies/linux-platforms/enterprise-linux"
 	rpm:                                                      "https://rpm.org/"
+	rumqttc:                                                  "\(github)/bytebeamio/rumqtt"
 	rust:                                                     "https://www.rust-lang.org/"
 	rust_date_time:                                           "https://docs.rs/chrono/latest/chrono/struct.DateTime.html"
 	rust_grok_library:                                        "\(github)/daschl/grok"